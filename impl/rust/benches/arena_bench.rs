@@ -0,0 +1,188 @@
+//! Node storage for linked structures: one `Box` per node against arenas
+//!
+//! Each variant builds an unbalanced binary search tree from the same
+//! random keys, then looks every key up. `boxed` allocates each node
+//! separately; `slab` and `slot_map` keep the nodes in one vector and link
+//! them by key, so allocation is amortized over the whole tree and nodes
+//! sit together in memory.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use dsa_lab::{Slab, SlotKey, SlotMap};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+const SIZES: [usize; 2] = [1_000, 100_000];
+
+fn random_keys(n: usize, seed: u64) -> Vec<u64> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..n).map(|_| rng.gen()).collect()
+}
+
+struct BoxNode {
+    key: u64,
+    children: [Option<Box<BoxNode>>; 2],
+}
+
+#[derive(Default)]
+struct BoxTree {
+    root: Option<Box<BoxNode>>,
+}
+
+impl BoxTree {
+    fn insert(&mut self, key: u64) {
+        let mut link = &mut self.root;
+        while let Some(node) = link {
+            if key == node.key {
+                return;
+            }
+            link = &mut node.children[usize::from(key > node.key)];
+        }
+        *link = Some(Box::new(BoxNode {
+            key,
+            children: [None, None],
+        }));
+    }
+
+    fn contains(&self, key: u64) -> bool {
+        let mut link = &self.root;
+        while let Some(node) = link {
+            if key == node.key {
+                return true;
+            }
+            link = &node.children[usize::from(key > node.key)];
+        }
+        false
+    }
+}
+
+/// A tree whose nodes live in an arena and link to each other by key.
+trait Arena: Default {
+    type Key: Copy;
+    fn insert(&mut self, node: ArenaNode<Self::Key>) -> Self::Key;
+    fn get(&self, key: Self::Key) -> &ArenaNode<Self::Key>;
+    fn get_mut(&mut self, key: Self::Key) -> &mut ArenaNode<Self::Key>;
+}
+
+struct ArenaNode<K> {
+    key: u64,
+    children: [Option<K>; 2],
+}
+
+impl Arena for Slab<ArenaNode<usize>> {
+    type Key = usize;
+    fn insert(&mut self, node: ArenaNode<usize>) -> usize {
+        Slab::insert(self, node)
+    }
+    fn get(&self, key: usize) -> &ArenaNode<usize> {
+        Slab::get(self, key).expect("live node")
+    }
+    fn get_mut(&mut self, key: usize) -> &mut ArenaNode<usize> {
+        Slab::get_mut(self, key).expect("live node")
+    }
+}
+
+impl Arena for SlotMap<ArenaNode<SlotKey>> {
+    type Key = SlotKey;
+    fn insert(&mut self, node: ArenaNode<SlotKey>) -> SlotKey {
+        SlotMap::insert(self, node)
+    }
+    fn get(&self, key: SlotKey) -> &ArenaNode<SlotKey> {
+        SlotMap::get(self, key).expect("live node")
+    }
+    fn get_mut(&mut self, key: SlotKey) -> &mut ArenaNode<SlotKey> {
+        SlotMap::get_mut(self, key).expect("live node")
+    }
+}
+
+struct ArenaTree<A: Arena> {
+    nodes: A,
+    root: Option<A::Key>,
+}
+
+impl<A: Arena> Default for ArenaTree<A> {
+    fn default() -> Self {
+        Self {
+            nodes: A::default(),
+            root: None,
+        }
+    }
+}
+
+impl<A: Arena> ArenaTree<A> {
+    fn insert(&mut self, key: u64) {
+        let node = ArenaNode {
+            key,
+            children: [None, None],
+        };
+        let Some(mut current) = self.root else {
+            self.root = Some(self.nodes.insert(node));
+            return;
+        };
+        loop {
+            let existing = self.nodes.get(current);
+            if key == existing.key {
+                return;
+            }
+            let side = usize::from(key > existing.key);
+            match existing.children[side] {
+                Some(child) => current = child,
+                None => {
+                    let child = self.nodes.insert(node);
+                    self.nodes.get_mut(current).children[side] = Some(child);
+                    return;
+                }
+            }
+        }
+    }
+
+    fn contains(&self, key: u64) -> bool {
+        let mut link = self.root;
+        while let Some(current) = link {
+            let node = self.nodes.get(current);
+            if key == node.key {
+                return true;
+            }
+            link = node.children[usize::from(key > node.key)];
+        }
+        false
+    }
+}
+
+fn bench_build_and_search(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bst_build_search");
+    for n in SIZES {
+        let keys = random_keys(n, 57);
+
+        group.bench_function(BenchmarkId::new("boxed", n), |b| {
+            b.iter(|| {
+                let mut tree = BoxTree::default();
+                for &key in &keys {
+                    tree.insert(key);
+                }
+                black_box(keys.iter().filter(|&&key| tree.contains(key)).count())
+            })
+        });
+        group.bench_function(BenchmarkId::new("slab", n), |b| {
+            b.iter(|| {
+                let mut tree = ArenaTree::<Slab<_>>::default();
+                for &key in &keys {
+                    tree.insert(key);
+                }
+                black_box(keys.iter().filter(|&&key| tree.contains(key)).count())
+            })
+        });
+        group.bench_function(BenchmarkId::new("slot_map", n), |b| {
+            b.iter(|| {
+                let mut tree = ArenaTree::<SlotMap<_>>::default();
+                for &key in &keys {
+                    tree.insert(key);
+                }
+                black_box(keys.iter().filter(|&&key| tree.contains(key)).count())
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_build_and_search);
+criterion_main!(benches);
@@ -0,0 +1,93 @@
+//! Finds the Karatsuba crossover for `BigUint` multiplication.
+//!
+//! For operands of each size, one level of Karatsuba, splitting once and
+//! multiplying the halves by the schoolbook method, is timed against the
+//! schoolbook method alone. The crossover is the smallest size from which
+//! the split wins at every larger size measured; below it, recursing only
+//! adds overhead, so it is the threshold to use. The report then times
+//! full products with thresholds around it, against the current
+//! `KARATSUBA_THRESHOLD`.
+
+use dsa_lab::bigint::KARATSUBA_THRESHOLD;
+use dsa_lab::BigUint;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const SIZES: [usize; 14] = [4, 8, 12, 16, 20, 24, 32, 40, 48, 64, 96, 128, 192, 256];
+
+fn random_uint(rng: &mut StdRng, limbs: usize) -> BigUint {
+    BigUint::from_limbs((0..limbs).map(|_| rng.gen()).collect())
+}
+
+/// Returns the fastest of several timings of `f`, each repeated for about
+/// 20 ms and averaged.
+fn time(mut f: impl FnMut() -> BigUint) -> Duration {
+    let start = Instant::now();
+    black_box(f());
+    let once = start.elapsed().max(Duration::from_nanos(1));
+    let reps = (Duration::from_millis(20).as_nanos() / once.as_nanos()).clamp(1, 1_000_000) as u32;
+    (0..5)
+        .map(|_| {
+            let start = Instant::now();
+            for _ in 0..reps {
+                black_box(f());
+            }
+            start.elapsed() / reps
+        })
+        .min()
+        .unwrap()
+}
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(917);
+    println!(
+        "{:>6} {:>12} {:>12} {:>7}",
+        "limbs", "schoolbook", "one split", "ratio"
+    );
+    let mut split_wins = Vec::new();
+    for n in SIZES {
+        let a = random_uint(&mut rng, n);
+        let b = random_uint(&mut rng, n);
+        let schoolbook = time(|| a.mul_schoolbook(&b));
+        // Halves of n limbs, and their sums, have at most n - 1.
+        let split = time(|| a.mul_karatsuba(&b, n - 1));
+        let ratio = split.as_secs_f64() / schoolbook.as_secs_f64();
+        println!(
+            "{:>6} {:>12.2?} {:>12.2?} {:>7.3}",
+            n, schoolbook, split, ratio
+        );
+        split_wins.push(ratio < 1.0);
+    }
+    let crossover = (0..SIZES.len())
+        .find(|&i| split_wins[i..].iter().all(|&wins| wins))
+        .map(|i| SIZES[i]);
+    match crossover {
+        Some(n) => println!("\ncrossover: one split wins from {} limbs up", n),
+        None => println!("\ncrossover: one split never wins consistently"),
+    }
+
+    let threshold = crossover.unwrap_or(KARATSUBA_THRESHOLD);
+    let mut thresholds = vec![threshold / 2, threshold, threshold * 2, KARATSUBA_THRESHOLD];
+    thresholds.sort();
+    thresholds.dedup();
+    println!(
+        "\nfull products by threshold (KARATSUBA_THRESHOLD = {})",
+        KARATSUBA_THRESHOLD
+    );
+    print!("{:>6}", "limbs");
+    for t in &thresholds {
+        print!(" {:>12}", format!("t={}", t));
+    }
+    println!();
+    for n in [256, 1_024, 4_096] {
+        let a = random_uint(&mut rng, n);
+        let b = random_uint(&mut rng, n);
+        print!("{:>6}", n);
+        for &t in &thresholds {
+            print!(" {:>12.2?}", time(|| a.mul_karatsuba(&b, t)));
+        }
+        println!();
+    }
+}
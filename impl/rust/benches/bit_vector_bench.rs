@@ -0,0 +1,112 @@
+//! Benchmarks for rank and select on the succinct bitvector
+//!
+//! Each query batch runs against a random half-full bitvector. The
+//! `naive` variants answer the same queries by popcounting every word
+//! from the start, which needs no extra space but is O(n). Before timing,
+//! the space the rank samples add on top of the bits is printed per size.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use dsa_lab::BitVector;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+const SIZES: [usize; 3] = [1 << 16, 1 << 20, 1 << 24];
+const QUERIES: usize = 1_000;
+
+fn random_bits(len: usize, seed: u64) -> BitVector {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..len).map(|_| rng.gen_bool(0.5)).collect()
+}
+
+fn naive_rank1(words: &[u64], index: usize) -> usize {
+    let full: usize = words[..index / 64]
+        .iter()
+        .map(|word| word.count_ones() as usize)
+        .sum();
+    let partial = match index % 64 {
+        0 => 0,
+        offset => (words[index / 64] & ((1 << offset) - 1)).count_ones() as usize,
+    };
+    full + partial
+}
+
+fn naive_select1(words: &[u64], mut k: usize) -> Option<usize> {
+    for (i, &word) in words.iter().enumerate() {
+        let ones = word.count_ones() as usize;
+        if k < ones {
+            let mut word = word;
+            for _ in 0..k {
+                word &= word - 1;
+            }
+            return Some(i * 64 + word.trailing_zeros() as usize);
+        }
+        k -= ones;
+    }
+    None
+}
+
+fn report_space() {
+    println!("BitVector space, bits vs bits plus rank samples");
+    println!(
+        "{:>10} {:>12} {:>12} {:>9}",
+        "bits", "bit bytes", "total bytes", "overhead"
+    );
+    for len in SIZES {
+        let bits = random_bits(len, 0);
+        let raw = bits.words().len() * 8;
+        println!(
+            "{:>10} {:>12} {:>12} {:>8.2}%",
+            len,
+            raw,
+            bits.size_in_bytes(),
+            (bits.size_in_bytes() - raw) as f64 / raw as f64 * 100.0
+        );
+    }
+    println!();
+}
+
+fn bench_rank_select(c: &mut Criterion) {
+    report_space();
+    let mut rng = StdRng::seed_from_u64(868);
+    let mut rank = c.benchmark_group("rank1");
+    rank.throughput(Throughput::Elements(QUERIES as u64));
+    for len in SIZES {
+        let bits = random_bits(len, len as u64);
+        let positions: Vec<usize> = (0..QUERIES).map(|_| rng.gen_range(0..=len)).collect();
+        rank.bench_function(BenchmarkId::new("bit_vector", len), |b| {
+            b.iter(|| positions.iter().map(|&i| bits.rank1(i)).sum::<usize>())
+        });
+        rank.bench_function(BenchmarkId::new("naive", len), |b| {
+            b.iter(|| {
+                positions
+                    .iter()
+                    .map(|&i| naive_rank1(black_box(bits.words()), i))
+                    .sum::<usize>()
+            })
+        });
+    }
+    rank.finish();
+
+    let mut select = c.benchmark_group("select1");
+    select.throughput(Throughput::Elements(QUERIES as u64));
+    for len in SIZES {
+        let bits = random_bits(len, len as u64);
+        let ks: Vec<usize> = (0..QUERIES)
+            .map(|_| rng.gen_range(0..bits.count_ones()))
+            .collect();
+        select.bench_function(BenchmarkId::new("bit_vector", len), |b| {
+            b.iter(|| ks.iter().filter_map(|&k| bits.select1(k)).sum::<usize>())
+        });
+        select.bench_function(BenchmarkId::new("naive", len), |b| {
+            b.iter(|| {
+                ks.iter()
+                    .filter_map(|&k| naive_select1(black_box(bits.words()), k))
+                    .sum::<usize>()
+            })
+        });
+    }
+    select.finish();
+}
+
+criterion_group!(benches, bench_rank_select);
+criterion_main!(benches);
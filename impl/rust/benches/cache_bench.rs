@@ -0,0 +1,155 @@
+//! Hit ratios of the cache structures on the map workloads.
+//!
+//! Each workload is replayed read-through: a backing map holds every value
+//! written, and a get that misses the cache loads the key from the backing
+//! map into the cache. Inserts write to both, deletes remove from both. The
+//! reported number is the fraction of gets served by the cache.
+//!
+//! After the table, each skewed workload's key stream is fed through a
+//! Space-Saving summary to report its hottest keys.
+
+use dsa_lab::{HashMap, LfuCache, LruCache, SpaceSaving};
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+const CAPACITIES: [usize; 3] = [16, 64, 256];
+const HOT_KEY_COUNTERS: usize = 256;
+
+#[derive(Debug, Deserialize)]
+struct Operation {
+    op: String,
+    key: String,
+    value: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Workload {
+    name: String,
+    operations: Vec<Operation>,
+}
+
+fn load_workload(name: &str) -> Option<Workload> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .join("workloads")
+        .join("map")
+        .join(format!("{}.json", name));
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+trait Cache<K, V> {
+    fn new(capacity: usize) -> Self;
+    fn get(&mut self, key: &K) -> Option<&V>;
+    fn put(&mut self, key: K, value: V);
+    fn remove(&mut self, key: &K);
+    fn hit_ratio(&self) -> f64;
+}
+
+macro_rules! impl_cache {
+    ($cache:ty) => {
+        impl Cache<String, String> for $cache {
+            fn new(capacity: usize) -> Self {
+                <$cache>::new(capacity)
+            }
+            fn get(&mut self, key: &String) -> Option<&String> {
+                <$cache>::get(self, key)
+            }
+            fn put(&mut self, key: String, value: String) {
+                <$cache>::put(self, key, value);
+            }
+            fn remove(&mut self, key: &String) {
+                <$cache>::remove(self, key);
+            }
+            fn hit_ratio(&self) -> f64 {
+                <$cache>::hit_ratio(self)
+            }
+        }
+    };
+}
+
+impl_cache!(LruCache<String, String>);
+impl_cache!(LfuCache<String, String>);
+
+fn hit_ratio<C: Cache<String, String>>(workload: &Workload, capacity: usize) -> f64 {
+    let mut backing: HashMap<String, String> = HashMap::new();
+    let mut cache = C::new(capacity);
+    for op in &workload.operations {
+        match op.op.as_str() {
+            "insert" => {
+                let value = op.value.clone().unwrap_or_default();
+                backing.insert(op.key.clone(), value.clone());
+                cache.put(op.key.clone(), value);
+            }
+            // A hit needs no further work; a miss loads from the backing map.
+            "get" if cache.get(&op.key).is_none() => {
+                if let Some(value) = backing.get(&op.key) {
+                    cache.put(op.key.clone(), value.clone());
+                }
+            }
+            "delete" => {
+                backing.remove(&op.key);
+                cache.remove(&op.key);
+            }
+            _ => {}
+        }
+    }
+    cache.hit_ratio()
+}
+
+fn main() {
+    println!("get hit ratio, read-through");
+    print!("{:<28} {:<6}", "workload", "cache");
+    for capacity in CAPACITIES {
+        print!(" {:>8}", capacity);
+    }
+    println!();
+
+    for workload_name in [
+        "read_heavy_uniform_medium",
+        "read_heavy_zipf_medium",
+        "mixed_uniform_medium",
+        "mixed_zipf_medium",
+    ] {
+        let Some(workload) = load_workload(workload_name) else {
+            eprintln!("Workload {} not found, skipping", workload_name);
+            continue;
+        };
+        print!("{:<28} {:<6}", workload.name, "lru");
+        for capacity in CAPACITIES {
+            print!(
+                " {:>8.3}",
+                hit_ratio::<LruCache<String, String>>(&workload, capacity)
+            );
+        }
+        println!();
+        print!("{:<28} {:<6}", workload.name, "lfu");
+        for capacity in CAPACITIES {
+            print!(
+                " {:>8.3}",
+                hit_ratio::<LfuCache<String, String>>(&workload, capacity)
+            );
+        }
+        println!();
+    }
+
+    println!();
+    println!("hot keys, Space-Saving with {} counters", HOT_KEY_COUNTERS);
+    for workload_name in ["read_heavy_zipf_medium", "mixed_zipf_medium"] {
+        let Some(workload) = load_workload(workload_name) else {
+            continue;
+        };
+        let mut summary = SpaceSaving::new(HOT_KEY_COUNTERS);
+        for op in &workload.operations {
+            summary.observe(op.key.as_str());
+        }
+        println!("{} ({} operations)", workload.name, summary.total());
+        for (key, count, error) in summary.top_k(5) {
+            println!("  {:<24} {:>8} ±{}", key, count, error);
+        }
+    }
+}
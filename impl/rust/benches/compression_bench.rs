@@ -0,0 +1,108 @@
+//! Compression ratio and speed on the workload files.
+//!
+//! Every medium-sized workload JSON file is compressed and decompressed by
+//! each compressor, checking the round trip, and the report gives the
+//! compressed size, the ratio to the original, and the time each way. The
+//! order-0 entropy of the file's bytes, the best any code that looks at
+//! one byte at a time can do, is printed alongside in bits per byte; the
+//! dictionary coders, LZ77 and LZW, can beat it by exploiting repeated
+//! strings, of which JSON has plenty.
+
+use dsa_lab::{huffman, lz77, lzw};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+type Compressor = (
+    &'static str,
+    fn(&[u8]) -> Vec<u8>,
+    fn(&[u8]) -> Option<Vec<u8>>,
+);
+
+const COMPRESSORS: [Compressor; 3] = [
+    ("huffman", huffman::compress, huffman::decompress),
+    ("lz77", lz77::compress, lz77::decompress),
+    ("lzw", lzw::compress, lzw::decompress),
+];
+
+/// Every `*_medium.json` file under `workloads/`, sorted by path.
+fn workload_files() -> Vec<PathBuf> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .join("workloads");
+    let mut files = Vec::new();
+    for dir in fs::read_dir(root).into_iter().flatten().flatten() {
+        for file in fs::read_dir(dir.path()).into_iter().flatten().flatten() {
+            let path = file.path();
+            if path.to_string_lossy().ends_with("_medium.json") {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// Returns the order-0 entropy of `data` in bits per byte.
+fn entropy(data: &[u8]) -> f64 {
+    let mut counts = [0u64; 256];
+    for &byte in data {
+        counts[byte as usize] += 1;
+    }
+    let n = data.len() as f64;
+    counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f64 / n;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+fn main() {
+    let files = workload_files();
+    if files.is_empty() {
+        eprintln!("No workload files found, skipping");
+        return;
+    }
+
+    println!(
+        "{:<38} {:<8} {:>10} {:>10} {:>7} {:>9} {:>9} {:>10} {:>10}",
+        "file", "codec", "bytes", "packed", "ratio", "bits/B", "entropy", "compress", "decompress"
+    );
+    for path in files {
+        let data = fs::read(&path).expect("workload file is readable");
+        let dir = path
+            .parent()
+            .unwrap()
+            .file_name()
+            .unwrap()
+            .to_string_lossy();
+        let name = format!("{}/{}", dir, path.file_name().unwrap().to_string_lossy());
+        for (codec, compress, decompress) in COMPRESSORS {
+            let start = Instant::now();
+            let packed = compress(&data);
+            let compress_time = start.elapsed();
+            let start = Instant::now();
+            let unpacked = decompress(&packed);
+            let decompress_time = start.elapsed();
+            assert_eq!(unpacked.as_deref(), Some(&data[..]), "{} round trip", codec);
+            println!(
+                "{:<38} {:<8} {:>10} {:>10} {:>7.3} {:>9.3} {:>9.3} {:>10.1?} {:>10.1?}",
+                name,
+                codec,
+                data.len(),
+                packed.len(),
+                packed.len() as f64 / data.len() as f64,
+                packed.len() as f64 * 8.0 / data.len() as f64,
+                entropy(&data),
+                compress_time,
+                decompress_time,
+            );
+        }
+    }
+}
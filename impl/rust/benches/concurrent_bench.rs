@@ -0,0 +1,185 @@
+//! Contended throughput of the lock-free structures against locking.
+//!
+//! In `contended`, every thread runs the same mix of gets and inserts over a
+//! shared key range, so threads collide on keys as well as on shards. In
+//! `queue`, half the threads push and half pop until every value is through,
+//! against a `Mutex<VecDeque>` baseline.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use dsa_lab::hashmap::DefaultHashBuilder;
+use dsa_lab::{HashMap, LockFreeMap, LockFreeQueue};
+use std::collections::VecDeque;
+use std::hash::BuildHasher;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+const KEYS: u64 = 4096;
+const OPS_PER_THREAD: u64 = 50_000;
+const SHARDS: usize = 16;
+
+/// The baseline: a `HashMap` per shard, each behind its own lock.
+struct ShardedMap {
+    shards: Vec<Mutex<HashMap<u64, u64>>>,
+}
+
+impl ShardedMap {
+    fn new() -> Self {
+        Self {
+            shards: (0..SHARDS).map(|_| Mutex::new(HashMap::new())).collect(),
+        }
+    }
+
+    fn shard(&self, key: u64) -> &Mutex<HashMap<u64, u64>> {
+        // High bits, so shards do not correlate with home buckets.
+        let hash = DefaultHashBuilder::default().hash_one(key);
+        &self.shards[(hash >> 60) as usize % SHARDS]
+    }
+}
+
+trait ConcurrentMap: Sync {
+    fn insert(&self, key: u64, value: u64);
+    fn get(&self, key: u64) -> Option<u64>;
+}
+
+impl ConcurrentMap for ShardedMap {
+    fn insert(&self, key: u64, value: u64) {
+        self.shard(key).lock().unwrap().insert(key, value);
+    }
+    fn get(&self, key: u64) -> Option<u64> {
+        self.shard(key).lock().unwrap().get(&key).copied()
+    }
+}
+
+impl ConcurrentMap for LockFreeMap<u64, u64> {
+    fn insert(&self, key: u64, value: u64) {
+        LockFreeMap::insert(self, key, value);
+    }
+    fn get(&self, key: u64) -> Option<u64> {
+        LockFreeMap::get(self, &key).copied()
+    }
+}
+
+/// Run `threads` workers, each doing 90% gets and 10% inserts.
+fn run<M: ConcurrentMap>(map: &M, threads: u64) {
+    thread::scope(|s| {
+        for t in 0..threads {
+            s.spawn(move || {
+                let mut key = t.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+                for i in 0..OPS_PER_THREAD {
+                    key = key.wrapping_mul(6364136223846793005).wrapping_add(1);
+                    let k = (key >> 33) % KEYS;
+                    if i % 10 == 0 {
+                        map.insert(k, i);
+                    } else {
+                        black_box(map.get(k));
+                    }
+                }
+            });
+        }
+    });
+}
+
+fn bench_contended(c: &mut Criterion) {
+    let mut group = c.benchmark_group("contended");
+    group.sample_size(10);
+    for threads in [1, 2, 4, 8] {
+        // Updates retire a version each, so the lock-free map is rebuilt
+        // per iteration to keep its memory bounded.
+        group.bench_with_input(
+            BenchmarkId::new("lock_free", threads),
+            &threads,
+            |b, &threads| {
+                b.iter(|| run(&LockFreeMap::with_capacity(KEYS as usize), threads));
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("sharded", threads),
+            &threads,
+            |b, &threads| {
+                b.iter(|| run(&ShardedMap::new(), threads));
+            },
+        );
+    }
+    group.finish();
+}
+
+trait ConcurrentQueue: Sync {
+    fn push(&self, value: u64);
+    fn pop(&self) -> Option<u64>;
+}
+
+impl ConcurrentQueue for Mutex<VecDeque<u64>> {
+    fn push(&self, value: u64) {
+        self.lock().unwrap().push_back(value);
+    }
+    fn pop(&self) -> Option<u64> {
+        self.lock().unwrap().pop_front()
+    }
+}
+
+impl ConcurrentQueue for LockFreeQueue<u64> {
+    fn push(&self, value: u64) {
+        LockFreeQueue::push(self, value);
+    }
+    fn pop(&self) -> Option<u64> {
+        LockFreeQueue::pop(self)
+    }
+}
+
+/// Run `threads / 2` producers pushing `OPS_PER_THREAD` values each, and as
+/// many consumers popping until all of them are through.
+fn run_queue<Q: ConcurrentQueue>(queue: &Q, threads: u64) {
+    let pairs = threads / 2;
+    let remaining = AtomicU64::new(pairs * OPS_PER_THREAD);
+    thread::scope(|s| {
+        for t in 0..pairs {
+            s.spawn(move || {
+                for i in 0..OPS_PER_THREAD {
+                    queue.push(t * OPS_PER_THREAD + i);
+                }
+            });
+        }
+        for _ in 0..pairs {
+            let remaining = &remaining;
+            s.spawn(move || {
+                while remaining.load(Ordering::Relaxed) > 0 {
+                    match queue.pop() {
+                        Some(value) => {
+                            black_box(value);
+                            remaining.fetch_sub(1, Ordering::Relaxed);
+                        }
+                        None => thread::yield_now(),
+                    }
+                }
+            });
+        }
+    });
+}
+
+fn bench_queue(c: &mut Criterion) {
+    let mut group = c.benchmark_group("queue");
+    group.sample_size(10);
+    for threads in [2, 4, 8] {
+        group.bench_with_input(
+            BenchmarkId::new("lock_free", threads),
+            &threads,
+            |b, &threads| {
+                let queue = LockFreeQueue::new();
+                b.iter(|| run_queue(&queue, threads));
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("mutex_vec_deque", threads),
+            &threads,
+            |b, &threads| {
+                let queue = Mutex::new(VecDeque::new());
+                b.iter(|| run_queue(&queue, threads));
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_contended, bench_queue);
+criterion_main!(benches);
@@ -0,0 +1,102 @@
+//! Benchmarks for the dynamic forest structures
+//!
+//! Each workload is a seeded sequence of edge insertions, edge removals and
+//! connectivity queries on a forest of `n` vertices: about 40% links
+//! between random vertices (skipped by the generator when they would close
+//! a cycle), 20% cuts of a random existing edge and 40% queries. Every
+//! structure replays the same sequence from an empty forest.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use dsa_lab::{EulerTourTree, LinkCutTree, Sum};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+const SIZES: [usize; 3] = [1_000, 10_000, 100_000];
+const OPERATIONS: usize = 10_000;
+
+#[derive(Debug, Clone, Copy)]
+enum Operation {
+    Link(usize, usize),
+    Cut(usize, usize),
+    Connected(usize, usize),
+}
+
+fn generate(n: usize, seed: u64) -> Vec<Operation> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut forest = EulerTourTree::new(n);
+    let mut edges = Vec::new();
+    let mut operations = Vec::with_capacity(OPERATIONS);
+    while operations.len() < OPERATIONS {
+        let u = rng.gen_range(0..n);
+        let v = rng.gen_range(0..n);
+        match rng.gen_range(0..5) {
+            0 | 1 => {
+                if forest.link(u, v) {
+                    edges.push((u, v));
+                    operations.push(Operation::Link(u, v));
+                }
+            }
+            2 if !edges.is_empty() => {
+                let (u, v) = edges.swap_remove(rng.gen_range(0..edges.len()));
+                forest.cut(u, v);
+                operations.push(Operation::Cut(u, v));
+            }
+            _ => operations.push(Operation::Connected(u, v)),
+        }
+    }
+    operations
+}
+
+fn bench_link_cut_connected(c: &mut Criterion) {
+    let mut group = c.benchmark_group("link_cut_connected");
+    for n in SIZES {
+        let operations = generate(n, 42);
+
+        group.bench_function(BenchmarkId::new("euler_tour", n), |b| {
+            b.iter_batched_ref(
+                || EulerTourTree::new(n),
+                |forest| {
+                    for &operation in &operations {
+                        match operation {
+                            Operation::Link(u, v) => {
+                                forest.link(u, v);
+                            }
+                            Operation::Cut(u, v) => {
+                                forest.cut(u, v);
+                            }
+                            Operation::Connected(u, v) => {
+                                black_box(forest.connected(u, v));
+                            }
+                        }
+                    }
+                },
+                criterion::BatchSize::LargeInput,
+            )
+        });
+        group.bench_function(BenchmarkId::new("link_cut", n), |b| {
+            b.iter_batched_ref(
+                || LinkCutTree::<Sum<i64>>::new(n),
+                |forest| {
+                    for &operation in &operations {
+                        match operation {
+                            Operation::Link(u, v) => {
+                                forest.link(u, v);
+                            }
+                            Operation::Cut(u, v) => {
+                                forest.cut(u, v);
+                            }
+                            Operation::Connected(u, v) => {
+                                black_box(forest.connected(u, v));
+                            }
+                        }
+                    }
+                },
+                criterion::BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_link_cut_connected);
+criterion_main!(benches);
@@ -0,0 +1,151 @@
+//! Benchmarks for the graph algorithms
+//!
+//! All-pairs shortest paths run on seeded random directed graphs with an
+//! edge between about a quarter of all ordered pairs, weights in 1..1000.
+//! The plain Floyd-Warshall triple loop is set against the cache-blocked
+//! one; the matrices for 1,024 nodes take 16 MiB, well past L2.
+//!
+//! The single-source and component benchmarks run on the medium graph
+//! workloads from `workloads/graph`, one per generator model, so each
+//! algorithm meets sparse random, scale-free, grid, DAG and tree shapes.
+//!
+//! Matrix-vector products set the dense `Matrix` against `CsrMatrix` on
+//! each graph's adjacency matrix: the small workloads, whose dense form
+//! is 1,000 x 1,000, and the quarter-full random graphs above.
+
+use criterion::measurement::WallTime;
+use criterion::{
+    black_box, criterion_group, criterion_main, BenchmarkGroup, BenchmarkId, Criterion,
+};
+use dsa_lab::{
+    dijkstra, floyd_warshall, floyd_warshall_blocked, tarjan_scc, CsrMatrix, Graph, NodeId,
+};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::Deserialize;
+use std::fs;
+use std::ops::{Add, Mul};
+use std::path::Path;
+
+const MODELS: [&str; 5] = [
+    "erdos_renyi",
+    "barabasi_albert",
+    "grid",
+    "random_dag",
+    "random_tree",
+];
+
+#[derive(Debug, Deserialize)]
+struct GraphWorkload {
+    directed: bool,
+    nodes: usize,
+    edges: Vec<(usize, usize, u64)>,
+}
+
+/// Load a graph workload into a `Graph`, with nodes in file order.
+fn load_workload(name: &str) -> Option<(Graph<(), u64>, Vec<NodeId>)> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .join("workloads")
+        .join("graph")
+        .join(format!("{}.json", name));
+    let content = fs::read_to_string(path).ok()?;
+    let workload: GraphWorkload = serde_json::from_str(&content).ok()?;
+    let mut graph = if workload.directed {
+        Graph::directed()
+    } else {
+        Graph::undirected()
+    };
+    let nodes: Vec<_> = (0..workload.nodes).map(|_| graph.add_node(())).collect();
+    for (source, target, weight) in workload.edges {
+        graph.add_edge(nodes[source], nodes[target], weight);
+    }
+    Some((graph, nodes))
+}
+
+const SIZES: [usize; 3] = [256, 512, 1_024];
+const BLOCK: usize = 64;
+
+fn dense_graph(n: usize, seed: u64) -> Graph<(), i64> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut graph = Graph::directed();
+    let nodes: Vec<_> = (0..n).map(|_| graph.add_node(())).collect();
+    for &source in &nodes {
+        for &target in &nodes {
+            if rng.gen_bool(0.25) {
+                graph.add_edge(source, target, rng.gen_range(1..1_000));
+            }
+        }
+    }
+    graph
+}
+
+fn bench_floyd_warshall(c: &mut Criterion) {
+    let mut group = c.benchmark_group("floyd_warshall");
+    group.sample_size(10);
+    for n in SIZES {
+        let graph = dense_graph(n, 42);
+        group.bench_function(BenchmarkId::new("plain", n), |b| {
+            b.iter(|| black_box(floyd_warshall(&graph)))
+        });
+        group.bench_function(BenchmarkId::new("blocked", n), |b| {
+            b.iter(|| black_box(floyd_warshall_blocked(&graph, BLOCK)))
+        });
+    }
+    group.finish();
+}
+
+fn bench_workloads(c: &mut Criterion) {
+    let mut group = c.benchmark_group("graph_workloads");
+    for model in MODELS {
+        let Some((graph, nodes)) = load_workload(&format!("{}_medium", model)) else {
+            eprintln!("skipping {}: run tools/gen_workloads.py first", model);
+            continue;
+        };
+        group.bench_function(BenchmarkId::new("dijkstra", model), |b| {
+            b.iter(|| black_box(dijkstra(&graph, nodes[0])))
+        });
+        group.bench_function(BenchmarkId::new("tarjan_scc", model), |b| {
+            b.iter(|| black_box(tarjan_scc(&graph)))
+        });
+    }
+    group.finish();
+}
+
+/// Time `x -> A x` for a graph's adjacency matrix in both forms.
+fn bench_adjacency<W>(group: &mut BenchmarkGroup<'_, WallTime>, name: &str, graph: &Graph<(), W>)
+where
+    W: Copy + Default + Add<Output = W> + Mul<Output = W> + From<u8>,
+{
+    let sparse = CsrMatrix::from_graph(graph);
+    let dense = sparse.to_dense();
+    let x = vec![W::from(1); sparse.cols()];
+    group.bench_function(BenchmarkId::new("dense", name), |b| {
+        b.iter(|| black_box(dense.mul_vec(&x)))
+    });
+    group.bench_function(BenchmarkId::new("csr", name), |b| {
+        b.iter(|| black_box(sparse.mul_vec(&x)))
+    });
+    group.bench_function(BenchmarkId::new("csr_transpose", name), |b| {
+        b.iter(|| black_box(sparse.transpose()))
+    });
+}
+
+fn bench_matvec(c: &mut Criterion) {
+    let mut group = c.benchmark_group("matvec");
+    for model in MODELS {
+        let Some((graph, _)) = load_workload(&format!("{}_small", model)) else {
+            eprintln!("skipping {}: run tools/gen_workloads.py first", model);
+            continue;
+        };
+        bench_adjacency(&mut group, model, &graph);
+    }
+    bench_adjacency(&mut group, "dense_1024", &dense_graph(1_024, 42));
+    group.finish();
+}
+
+criterion_group!(benches, bench_floyd_warshall, bench_workloads, bench_matvec);
+criterion_main!(benches);
@@ -0,0 +1,217 @@
+//! Resize count and allocation volume per growth policy.
+//!
+//! Growth policies trade memory slack against resize work, which shows up in
+//! allocation volume rather than in throughput alone. This benchmark replays
+//! the insert-heavy workload under a counting allocator and reports, per map
+//! and policy, how many times the table resized, the total bytes allocated
+//! by the map, and the final capacity. It then pushes `ARRAY_PUSHES` integers
+//! into a `DynArray` per policy and reports its reallocations, the bytes
+//! they copied, the bytes allocated, the final capacity and the time taken.
+
+use dsa_lab::{ChainedHashMap, DynArray, GrowthPolicy, RobinHoodMap};
+use serde::Deserialize;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fs;
+use std::hint::black_box;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+const WORKLOAD: &str = "insert_heavy_uniform_medium";
+/// Small enough that exact fit, quadratic in the pushes, finishes quickly.
+const ARRAY_PUSHES: u64 = 50_000;
+const POLICIES: [GrowthPolicy; 5] = [
+    GrowthPolicy::Doubling,
+    GrowthPolicy::OneAndHalf,
+    GrowthPolicy::GoldenRatio,
+    GrowthPolicy::ExactFit,
+    GrowthPolicy::Primes,
+];
+
+/// Counts every byte requested from the system allocator.
+struct CountingAlloc;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATED.fetch_add(new_size, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+#[derive(Debug, Deserialize)]
+struct Operation {
+    op: String,
+    key: String,
+    value: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Workload {
+    operations: Vec<Operation>,
+}
+
+fn load_workload(name: &str) -> Option<Workload> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .join("workloads")
+        .join("map")
+        .join(format!("{}.json", name));
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+trait GrowthMap<K, V> {
+    fn with_growth_policy(policy: GrowthPolicy) -> Self;
+    fn insert(&mut self, key: K, value: V);
+    fn get(&self, key: &K) -> Option<&V>;
+    fn remove(&mut self, key: &K) -> Option<V>;
+    fn capacity(&self) -> usize;
+}
+
+macro_rules! impl_growth_map {
+    ($map:ty) => {
+        impl GrowthMap<String, String> for $map {
+            fn with_growth_policy(policy: GrowthPolicy) -> Self {
+                <$map>::with_growth_policy(policy)
+            }
+            fn insert(&mut self, key: String, value: String) {
+                <$map>::insert(self, key, value);
+            }
+            fn get(&self, key: &String) -> Option<&String> {
+                <$map>::get(self, key)
+            }
+            fn remove(&mut self, key: &String) -> Option<String> {
+                <$map>::remove(self, key)
+            }
+            fn capacity(&self) -> usize {
+                <$map>::capacity(self)
+            }
+        }
+    };
+}
+
+impl_growth_map!(ChainedHashMap<String, String>);
+impl_growth_map!(RobinHoodMap<String, String>);
+
+struct Report {
+    resizes: usize,
+    bytes_allocated: usize,
+    capacity: usize,
+}
+
+fn replay<M: GrowthMap<String, String>>(workload: &Workload, policy: GrowthPolicy) -> Report {
+    // Clone the keys and values up front so only the map's allocations count.
+    let operations: Vec<(&str, String, String)> = workload
+        .operations
+        .iter()
+        .map(|op| {
+            let value = op.value.clone().unwrap_or_default();
+            (op.op.as_str(), op.key.clone(), value)
+        })
+        .collect();
+
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    let mut map = M::with_growth_policy(policy);
+    let mut resizes = 0;
+    for (op, key, value) in operations {
+        let capacity = map.capacity();
+        match op {
+            "insert" => map.insert(key, value),
+            "get" => {
+                black_box(map.get(&key));
+            }
+            "delete" => {
+                black_box(map.remove(&key));
+            }
+            _ => {}
+        }
+        if map.capacity() != capacity {
+            resizes += 1;
+        }
+    }
+    Report {
+        resizes,
+        bytes_allocated: ALLOCATED.load(Ordering::Relaxed) - before,
+        capacity: map.capacity(),
+    }
+}
+
+fn report_dyn_array() {
+    println!("DynArray, {} pushes of u64", ARRAY_PUSHES);
+    println!(
+        "{:<12} {:>8} {:>14} {:>14} {:>10} {:>10}",
+        "policy", "reallocs", "bytes copied", "bytes alloc", "capacity", "time"
+    );
+    for policy in POLICIES {
+        let before = ALLOCATED.load(Ordering::Relaxed);
+        let start = Instant::now();
+        let mut array = DynArray::with_growth_policy(policy);
+        for i in 0..ARRAY_PUSHES {
+            array.push(black_box(i));
+        }
+        let elapsed = start.elapsed();
+        println!(
+            "{:<12} {:>8} {:>14} {:>14} {:>10} {:>10.2?}",
+            format!("{:?}", policy),
+            array.reallocations(),
+            array.bytes_copied(),
+            ALLOCATED.load(Ordering::Relaxed) - before,
+            array.capacity(),
+            elapsed
+        );
+    }
+    println!();
+}
+
+fn main() {
+    report_dyn_array();
+
+    let Some(workload) = load_workload(WORKLOAD) else {
+        eprintln!("Workload {} not found, skipping", WORKLOAD);
+        return;
+    };
+
+    println!("growth policies on {}", WORKLOAD);
+    println!(
+        "{:<12} {:<12} {:>8} {:>14} {:>10}",
+        "map", "policy", "resizes", "bytes alloc", "capacity"
+    );
+    for policy in POLICIES {
+        for (name, report) in [
+            (
+                "chained",
+                replay::<ChainedHashMap<String, String>>(&workload, policy),
+            ),
+            (
+                "robin_hood",
+                replay::<RobinHoodMap<String, String>>(&workload, policy),
+            ),
+        ] {
+            println!(
+                "{:<12} {:<12} {:>8} {:>14} {:>10}",
+                name,
+                format!("{:?}", policy),
+                report.resizes,
+                report.bytes_allocated,
+                report.capacity
+            );
+        }
+    }
+}
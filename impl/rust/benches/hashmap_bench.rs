@@ -1,9 +1,20 @@
 //! Benchmarks for HashMap implementation
 
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
-use dsa_lab::HashMap;
+#[cfg(feature = "ahash")]
+use dsa_lab::AHashMap;
+#[cfg(feature = "fxhash")]
+use dsa_lab::FxHashMap;
+use dsa_lab::{
+    AvlMap, BPlusTreeMap, BTreeMap, ChainedHashMap, CuckooHashMap, DoubleHashMap, HashMap,
+    IndexMap, Interner, OpenAddressingMap, PerfectHashMap, ProbeStrategy, QuadraticMap,
+    QuotientFilter, RadixTrie, RobinHoodMap, SkipListMap, SoaMap, SwissMap, TreapMap, XorFilter,
+    ZipTreeMap,
+};
 use serde::Deserialize;
+use std::collections::BTreeMap as StdBTreeMap;
 use std::fs;
+use std::hash::BuildHasher;
 use std::path::Path;
 
 #[derive(Debug, Deserialize)]
@@ -16,7 +27,6 @@ struct Operation {
 #[derive(Debug, Deserialize)]
 struct Workload {
     name: String,
-    size: usize,
     operations: Vec<Operation>,
 }
 
@@ -38,6 +48,118 @@ fn load_workload(name: &str) -> Option<Workload> {
     serde_json::from_str(&content).ok()
 }
 
+/// The operations a workload replay needs from a map implementation.
+trait BenchMap<K, V> {
+    fn new() -> Self;
+    fn insert(&mut self, key: K, value: V);
+    fn get(&self, key: &K) -> Option<&V>;
+    fn remove(&mut self, key: &K) -> Option<V>;
+}
+
+macro_rules! impl_bench_map {
+    ($map:ty) => {
+        impl BenchMap<String, String> for $map {
+            fn new() -> Self {
+                <$map>::default()
+            }
+            fn insert(&mut self, key: String, value: String) {
+                <$map>::insert(self, key, value);
+            }
+            fn get(&self, key: &String) -> Option<&String> {
+                <$map>::get(self, key)
+            }
+            fn remove(&mut self, key: &String) -> Option<String> {
+                <$map>::remove(self, key)
+            }
+        }
+    };
+}
+
+// Covers HashMap, QuadraticMap, DoubleHashMap and every hasher alias.
+impl<S, P> BenchMap<String, String> for OpenAddressingMap<String, String, S, P>
+where
+    S: BuildHasher + Default,
+    P: ProbeStrategy,
+{
+    fn new() -> Self {
+        Self::default()
+    }
+    fn insert(&mut self, key: String, value: String) {
+        OpenAddressingMap::insert(self, key, value);
+    }
+    fn get(&self, key: &String) -> Option<&String> {
+        OpenAddressingMap::get(self, key)
+    }
+    fn remove(&mut self, key: &String) -> Option<String> {
+        OpenAddressingMap::remove(self, key)
+    }
+}
+
+impl_bench_map!(RobinHoodMap<String, String>);
+impl_bench_map!(ChainedHashMap<String, String>);
+impl_bench_map!(CuckooHashMap<String, String>);
+impl_bench_map!(SwissMap<String, String>);
+impl_bench_map!(SoaMap<String, String>);
+impl_bench_map!(AvlMap<String, String>);
+impl_bench_map!(BTreeMap<String, String>);
+impl_bench_map!(BPlusTreeMap<String, String>);
+impl_bench_map!(SkipListMap<String, String>);
+impl_bench_map!(TreapMap<String, String>);
+impl_bench_map!(ZipTreeMap<String, String>);
+impl_bench_map!(StdBTreeMap<String, String>);
+
+impl BenchMap<String, String> for IndexMap<String, String> {
+    fn new() -> Self {
+        Self::default()
+    }
+    fn insert(&mut self, key: String, value: String) {
+        IndexMap::insert(self, key, value);
+    }
+    fn get(&self, key: &String) -> Option<&String> {
+        IndexMap::get(self, key)
+    }
+    fn remove(&mut self, key: &String) -> Option<String> {
+        IndexMap::swap_remove(self, key)
+    }
+}
+
+impl BenchMap<String, String> for RadixTrie<String> {
+    fn new() -> Self {
+        Self::default()
+    }
+    fn insert(&mut self, key: String, value: String) {
+        RadixTrie::insert(self, key, value);
+    }
+    fn get(&self, key: &String) -> Option<&String> {
+        RadixTrie::get(self, key.as_bytes())
+    }
+    fn remove(&mut self, key: &String) -> Option<String> {
+        RadixTrie::remove(self, key.as_bytes())
+    }
+}
+
+fn replay<M: BenchMap<String, String>>(workload: &Workload) -> M {
+    let mut map = M::new();
+    for op in &workload.operations {
+        match op.op.as_str() {
+            "insert" => {
+                map.insert(
+                    black_box(op.key.clone()),
+                    black_box(op.value.clone().unwrap_or_default()),
+                );
+            }
+            "get" => {
+                black_box(map.get(&op.key));
+            }
+            "delete" => {
+                black_box(map.remove(&op.key));
+            }
+            _ => {}
+        }
+    }
+    map
+}
+
 fn bench_workload(c: &mut Criterion, workload_name: &str) {
     let workload = match load_workload(workload_name) {
         Some(w) => w,
@@ -47,35 +169,491 @@ fn bench_workload(c: &mut Criterion, workload_name: &str) {
         }
     };
 
-    c.bench_with_input(
+    let mut group = c.benchmark_group("workload");
+    group.bench_with_input(
         BenchmarkId::new("hashmap", &workload.name),
         &workload,
-        |b, workload| {
+        |b, w| b.iter(|| replay::<HashMap<String, String>>(w)),
+    );
+    group.bench_with_input(
+        BenchmarkId::new("quadratic", &workload.name),
+        &workload,
+        |b, w| b.iter(|| replay::<QuadraticMap<String, String>>(w)),
+    );
+    group.bench_with_input(
+        BenchmarkId::new("double_hash", &workload.name),
+        &workload,
+        |b, w| b.iter(|| replay::<DoubleHashMap<String, String>>(w)),
+    );
+    group.bench_with_input(
+        BenchmarkId::new("robin_hood", &workload.name),
+        &workload,
+        |b, w| b.iter(|| replay::<RobinHoodMap<String, String>>(w)),
+    );
+    group.bench_with_input(
+        BenchmarkId::new("chained", &workload.name),
+        &workload,
+        |b, w| b.iter(|| replay::<ChainedHashMap<String, String>>(w)),
+    );
+    group.bench_with_input(
+        BenchmarkId::new("cuckoo", &workload.name),
+        &workload,
+        |b, w| b.iter(|| replay::<CuckooHashMap<String, String>>(w)),
+    );
+    group.bench_with_input(
+        BenchmarkId::new("swiss", &workload.name),
+        &workload,
+        |b, w| b.iter(|| replay::<SwissMap<String, String>>(w)),
+    );
+    group.bench_with_input(
+        BenchmarkId::new("soa", &workload.name),
+        &workload,
+        |b, w| b.iter(|| replay::<SoaMap<String, String>>(w)),
+    );
+    group.bench_with_input(
+        BenchmarkId::new("index_map", &workload.name),
+        &workload,
+        |b, w| b.iter(|| replay::<IndexMap<String, String>>(w)),
+    );
+    group.finish();
+}
+
+/// Compare hash functions on the linear-probing map.
+///
+/// FxHash and AHash only run when the `fxhash` and `ahash` features are on:
+/// `cargo bench --features fxhash,ahash -- hasher`.
+fn bench_hashers(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hasher");
+    for workload_name in [
+        "mixed_uniform_medium",
+        "insert_heavy_uniform_medium",
+        "read_heavy_uniform_medium",
+    ] {
+        let Some(workload) = load_workload(workload_name) else {
+            eprintln!("Workload {} not found, skipping", workload_name);
+            continue;
+        };
+        group.bench_with_input(
+            BenchmarkId::new("siphash", &workload.name),
+            &workload,
+            |b, w| b.iter(|| replay::<HashMap<String, String>>(w)),
+        );
+        #[cfg(feature = "fxhash")]
+        group.bench_with_input(
+            BenchmarkId::new("fxhash", &workload.name),
+            &workload,
+            |b, w| b.iter(|| replay::<FxHashMap<String, String>>(w)),
+        );
+        #[cfg(feature = "ahash")]
+        group.bench_with_input(
+            BenchmarkId::new("ahash", &workload.name),
+            &workload,
+            |b, w| b.iter(|| replay::<AHashMap<String, String>>(w)),
+        );
+    }
+    group.finish();
+}
+
+/// Read-heavy replays on the linear-probing map, at sizes where the table
+/// outgrows the cache.
+///
+/// Run once without and once with the `prefetch` feature to measure the
+/// probe-loop prefetch hints:
+/// `cargo bench -- --save-baseline plain read_heavy` then
+/// `cargo bench --features prefetch -- --baseline plain read_heavy`.
+fn bench_read_heavy_prefetch(c: &mut Criterion) {
+    let mut group = c.benchmark_group("read_heavy");
+    for workload_name in [
+        "read_heavy_uniform_medium",
+        "read_heavy_zipf_medium",
+        "read_heavy_uniform_large",
+        "read_heavy_zipf_large",
+    ] {
+        let Some(workload) = load_workload(workload_name) else {
+            eprintln!("Workload {} not found, skipping", workload_name);
+            continue;
+        };
+        group.bench_with_input(
+            BenchmarkId::new("hashmap", &workload.name),
+            &workload,
+            |b, w| b.iter(|| replay::<HashMap<String, String>>(w)),
+        );
+    }
+    group.finish();
+}
+
+/// The lookups of the read-heavy workloads against the table their inserts
+/// build, frozen into a `PerfectHashMap` and left in a `HashMap`.
+///
+/// Writes and removals are applied up front, so only the gets are timed.
+fn bench_get_frozen(c: &mut Criterion) {
+    let mut group = c.benchmark_group("get_frozen");
+    for workload_name in [
+        "read_heavy_uniform_medium",
+        "read_heavy_zipf_medium",
+        "read_heavy_uniform_large",
+        "read_heavy_zipf_large",
+    ] {
+        let Some(workload) = load_workload(workload_name) else {
+            eprintln!("Workload {} not found, skipping", workload_name);
+            continue;
+        };
+        let dynamic = replay::<HashMap<String, String>>(&workload);
+        let frozen = PerfectHashMap::build(dynamic.iter().map(|(k, v)| (k.clone(), v.clone())));
+        let gets: Vec<&String> = workload
+            .operations
+            .iter()
+            .filter(|op| op.op == "get")
+            .map(|op| &op.key)
+            .collect();
+
+        group.bench_with_input(
+            BenchmarkId::new("hashmap", &workload.name),
+            &gets,
+            |b, gets| {
+                b.iter(|| {
+                    for key in gets {
+                        black_box(dynamic.get(key));
+                    }
+                })
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("perfect", &workload.name),
+            &gets,
+            |b, gets| {
+                b.iter(|| {
+                    for key in gets {
+                        black_box(frozen.get(key));
+                    }
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+/// Membership queries for the gets of the read-heavy workloads against
+/// filters built from the keys their inserts leave behind, with the map
+/// itself as the exact baseline.
+///
+/// The quotient filter probes one contiguous cluster; the XOR filter reads
+/// three scattered bytes.
+fn bench_membership(c: &mut Criterion) {
+    let mut group = c.benchmark_group("membership");
+    for workload_name in ["read_heavy_uniform_medium", "read_heavy_uniform_large"] {
+        let Some(workload) = load_workload(workload_name) else {
+            eprintln!("Workload {} not found, skipping", workload_name);
+            continue;
+        };
+        let map = replay::<HashMap<String, String>>(&workload);
+        let xor_filter = XorFilter::build(map.iter().map(|(k, _)| k.clone()));
+        // Sized for a load factor of at most one half, with 8-bit remainders.
+        let quotient_bits = (2 * map.len()).next_power_of_two().trailing_zeros().max(1);
+        let mut quotient_filter = QuotientFilter::new(quotient_bits, 8);
+        for (key, _) in map.iter() {
+            quotient_filter.insert(key);
+        }
+        let gets: Vec<&String> = workload
+            .operations
+            .iter()
+            .filter(|op| op.op == "get")
+            .map(|op| &op.key)
+            .collect();
+
+        group.bench_with_input(
+            BenchmarkId::new("hashmap", &workload.name),
+            &gets,
+            |b, gets| {
+                b.iter(|| {
+                    for key in gets {
+                        black_box(map.contains_key(key));
+                    }
+                })
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("xor_filter", &workload.name),
+            &gets,
+            |b, gets| {
+                b.iter(|| {
+                    for key in gets {
+                        black_box(xor_filter.contains(*key));
+                    }
+                })
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("quotient_filter", &workload.name),
+            &gets,
+            |b, gets| {
+                b.iter(|| {
+                    for key in gets {
+                        black_box(quotient_filter.contains(*key));
+                    }
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+/// Workload replay against the ordered maps, with std's B-tree as the
+/// baseline.
+fn bench_ordered(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ordered");
+    for workload_name in [
+        "insert_heavy_uniform_medium",
+        "read_heavy_uniform_medium",
+        "mixed_uniform_medium",
+    ] {
+        let Some(workload) = load_workload(workload_name) else {
+            eprintln!("Workload {} not found, skipping", workload_name);
+            continue;
+        };
+        group.bench_with_input(
+            BenchmarkId::new("avl", &workload.name),
+            &workload,
+            |b, w| b.iter(|| replay::<AvlMap<String, String>>(w)),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("btree", &workload.name),
+            &workload,
+            |b, w| b.iter(|| replay::<BTreeMap<String, String>>(w)),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("bplus_tree", &workload.name),
+            &workload,
+            |b, w| b.iter(|| replay::<BPlusTreeMap<String, String>>(w)),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("skip_list", &workload.name),
+            &workload,
+            |b, w| b.iter(|| replay::<SkipListMap<String, String>>(w)),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("treap", &workload.name),
+            &workload,
+            |b, w| b.iter(|| replay::<TreapMap<String, String>>(w)),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("zip_tree", &workload.name),
+            &workload,
+            |b, w| b.iter(|| replay::<ZipTreeMap<String, String>>(w)),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("std_btree", &workload.name),
+            &workload,
+            |b, w| b.iter(|| replay::<StdBTreeMap<String, String>>(w)),
+        );
+    }
+    group.finish();
+}
+
+/// Workload replay with string keys against the radix trie, whose cost
+/// grows with key length rather than key count.
+fn bench_string_keys(c: &mut Criterion) {
+    let mut group = c.benchmark_group("string_keys");
+    for workload_name in [
+        "insert_heavy_uniform_medium",
+        "read_heavy_uniform_medium",
+        "mixed_zipf_medium",
+    ] {
+        let Some(workload) = load_workload(workload_name) else {
+            eprintln!("Workload {} not found, skipping", workload_name);
+            continue;
+        };
+        group.bench_with_input(
+            BenchmarkId::new("hashmap", &workload.name),
+            &workload,
+            |b, w| b.iter(|| replay::<HashMap<String, String>>(w)),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("radix_trie", &workload.name),
+            &workload,
+            |b, w| b.iter(|| replay::<RadixTrie<String>>(w)),
+        );
+    }
+    group.finish();
+}
+
+/// Short range scans over the map a workload builds: 100 entries from each
+/// of 100 start keys spread across the key space.
+///
+/// The B+ tree walks its leaf chain; the B-tree climbs back through internal
+/// nodes between leaves.
+fn bench_range_scan(c: &mut Criterion) {
+    let mut group = c.benchmark_group("range_scan");
+    for workload_name in ["insert_heavy_uniform_medium", "insert_heavy_uniform_large"] {
+        let Some(workload) = load_workload(workload_name) else {
+            eprintln!("Workload {} not found, skipping", workload_name);
+            continue;
+        };
+        let std_btree = replay::<StdBTreeMap<String, String>>(&workload);
+        let btree = replay::<BTreeMap<String, String>>(&workload);
+        let bplus_tree =
+            BPlusTreeMap::from_sorted(std_btree.iter().map(|(k, v)| (k.clone(), v.clone())));
+        let step = (std_btree.len() / 100).max(1);
+        let starts: Vec<&String> = std_btree.keys().step_by(step).collect();
+
+        group.bench_function(BenchmarkId::new("btree", &workload.name), |b| {
             b.iter(|| {
-                let mut map: HashMap<String, String> = HashMap::new();
-                for op in &workload.operations {
-                    match op.op.as_str() {
-                        "insert" => {
-                            map.insert(
-                                black_box(op.key.clone()),
-                                black_box(op.value.clone().unwrap_or_default()),
-                            );
-                        }
-                        "get" => {
-                            black_box(map.get(&op.key));
-                        }
-                        "delete" => {
-                            black_box(map.remove(&op.key));
-                        }
-                        _ => {}
+                for &start in &starts {
+                    for entry in btree.range(start.clone()..).take(100) {
+                        black_box(entry);
+                    }
+                }
+            })
+        });
+        group.bench_function(BenchmarkId::new("bplus_tree", &workload.name), |b| {
+            b.iter(|| {
+                for &start in &starts {
+                    for entry in bplus_tree.range(start.clone()..).take(100) {
+                        black_box(entry);
+                    }
+                }
+            })
+        });
+        group.bench_function(BenchmarkId::new("std_btree", &workload.name), |b| {
+            b.iter(|| {
+                for &start in &starts {
+                    for entry in std_btree.range(start.clone()..).take(100) {
+                        black_box(entry);
                     }
                 }
+            })
+        });
+    }
+    group.finish();
+}
+
+/// Full iteration over the table a workload builds.
+///
+/// `IndexMap` walks a dense `Vec`; the open-addressing map skips empty and
+/// deleted slots.
+fn bench_iterate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("iterate");
+    for workload_name in ["insert_heavy_uniform_medium", "mixed_uniform_medium"] {
+        let Some(workload) = load_workload(workload_name) else {
+            eprintln!("Workload {} not found, skipping", workload_name);
+            continue;
+        };
+        let hashmap = replay::<HashMap<String, String>>(&workload);
+        let index_map = replay::<IndexMap<String, String>>(&workload);
+
+        group.bench_function(BenchmarkId::new("hashmap", &workload.name), |b| {
+            b.iter(|| {
+                for entry in hashmap.iter() {
+                    black_box(entry);
+                }
+            })
+        });
+        group.bench_function(BenchmarkId::new("index_map", &workload.name), |b| {
+            b.iter(|| {
+                for entry in index_map.iter() {
+                    black_box(entry);
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+/// Workload replay with string keys against the same replay over symbols
+/// interned up front, where each operation hashes a `u32` instead of a
+/// string.
+fn bench_interned(c: &mut Criterion) {
+    let mut group = c.benchmark_group("interned");
+    for workload_name in ["mixed_uniform_medium", "read_heavy_zipf_medium"] {
+        let Some(workload) = load_workload(workload_name) else {
+            eprintln!("Workload {} not found, skipping", workload_name);
+            continue;
+        };
+        let mut interner = Interner::new();
+        let ops: Vec<_> = workload
+            .operations
+            .iter()
+            .map(|op| (op.op.as_str(), interner.intern(&op.key), &op.value))
+            .collect();
+
+        group.bench_with_input(
+            BenchmarkId::new("string", &workload.name),
+            &workload,
+            |b, w| b.iter(|| replay::<HashMap<String, String>>(w)),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("symbol", &workload.name),
+            &ops,
+            |b, ops| {
+                b.iter(|| {
+                    let mut map = HashMap::new();
+                    for &(op, symbol, value) in ops {
+                        match op {
+                            "insert" => {
+                                map.insert(
+                                    black_box(symbol),
+                                    black_box(value.clone().unwrap_or_default()),
+                                );
+                            }
+                            "get" => {
+                                black_box(map.get(&symbol));
+                            }
+                            "delete" => {
+                                black_box(map.remove(&symbol));
+                            }
+                            _ => {}
+                        }
+                    }
+                    map
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+/// Building a large map sequentially against the sharded `from_par_iter`
+/// on pools of 1, 2, 4, 8 and 16 threads, up to the core count:
+/// `cargo bench --features rayon -- bulk_build`. A pool of one thread
+/// only hashes in parallel, so it measures the fallback path.
+#[cfg(feature = "rayon")]
+fn bench_bulk_build(c: &mut Criterion) {
+    use rayon::prelude::*;
+
+    let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut threads = vec![1, 2, 4, 8, 16];
+    threads.retain(|&t| t < cores);
+    threads.push(cores);
+    let mut group = c.benchmark_group("bulk_build");
+    group.sample_size(10);
+    for size in [100_000, 1_000_000] {
+        let pairs: Vec<(String, u64)> = (0..size).map(|i| (format!("key_{}", i), i)).collect();
+        group.bench_with_input(BenchmarkId::new("sequential", size), &pairs, |b, pairs| {
+            b.iter(|| {
+                let mut map = HashMap::new();
+                map.insert_batch(pairs.iter().cloned());
                 map
             })
-        },
-    );
+        });
+        for &t in &threads {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(t)
+                .build()
+                .unwrap();
+            let id = BenchmarkId::new(format!("threads_{}", t), size);
+            group.bench_with_input(id, &pairs, |b, pairs| {
+                b.iter(|| {
+                    pool.install(|| pairs.par_iter().cloned().collect::<HashMap<String, u64>>())
+                })
+            });
+        }
+    }
+    group.finish();
 }
 
+#[cfg(not(feature = "rayon"))]
+fn bench_bulk_build(_: &mut Criterion) {}
+
 fn bench_insert_only(c: &mut Criterion) {
     let sizes = [100, 1000, 10000];
 
@@ -121,6 +699,105 @@ fn bench_get_only(c: &mut Criterion) {
     group.finish();
 }
 
+/// Lookups with small keys, where slot density rather than hashing and key
+/// comparison dominates once the table outgrows the cache.
+fn bench_get_u64(c: &mut Criterion) {
+    let sizes = [1_000, 100_000, 1_000_000];
+
+    let mut group = c.benchmark_group("get_u64");
+    group.sample_size(10);
+    for size in sizes {
+        let mut map: HashMap<u64, u64> = HashMap::new();
+        for key in 0..size {
+            map.insert(key, key);
+        }
+
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, &size| {
+            b.iter(|| {
+                for key in 0..size {
+                    black_box(map.get(&key));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+/// A value large enough that interleaving it with keys spreads them out.
+type LargeValue = [u64; 32];
+
+/// Get-heavy lookups with 256-byte values, half hits and half misses.
+///
+/// Misses probe until an empty slot, so this shows how much of each probe's
+/// cache traffic is spent on values that are never read.
+fn bench_get_large_values(c: &mut Criterion) {
+    const SIZE: u64 = 100_000;
+
+    let mut group = c.benchmark_group("get_large_values");
+    group.sample_size(10);
+
+    let mut linear: HashMap<u64, LargeValue> = HashMap::new();
+    let mut quadratic: QuadraticMap<u64, LargeValue> = QuadraticMap::new();
+    let mut soa: SoaMap<u64, LargeValue> = SoaMap::new();
+    for key in 0..SIZE {
+        linear.insert(key, [key; 32]);
+        quadratic.insert(key, [key; 32]);
+        soa.insert(key, [key; 32]);
+    }
+
+    group.bench_function("hashmap", |b| {
+        b.iter(|| {
+            for key in 0..2 * SIZE {
+                black_box(linear.get(&key));
+            }
+        })
+    });
+    group.bench_function("quadratic", |b| {
+        b.iter(|| {
+            for key in 0..2 * SIZE {
+                black_box(quadratic.get(&key));
+            }
+        })
+    });
+    group.bench_function("soa", |b| {
+        b.iter(|| {
+            for key in 0..2 * SIZE {
+                black_box(soa.get(&key));
+            }
+        })
+    });
+    group.finish();
+}
+
+fn bench_insert_batch(c: &mut Criterion) {
+    let sizes = [1000, 10000, 100000];
+
+    let mut group = c.benchmark_group("insert_batch");
+    for size in sizes {
+        let pairs: Vec<(String, String)> = (0..size)
+            .map(|i| (format!("key_{}", i), format!("value_{}", i)))
+            .collect();
+
+        group.bench_with_input(BenchmarkId::new("sequential", size), &pairs, |b, pairs| {
+            b.iter(|| {
+                let mut map: HashMap<String, String> = HashMap::new();
+                for (key, value) in pairs {
+                    map.insert(key.clone(), value.clone());
+                }
+                map
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("batch", size), &pairs, |b, pairs| {
+            b.iter(|| {
+                let mut map: HashMap<String, String> = HashMap::new();
+                map.insert_batch(black_box(pairs.iter().cloned()));
+                map
+            })
+        });
+    }
+    group.finish();
+}
+
 fn bench_mixed(c: &mut Criterion) {
     bench_workload(c, "mixed_uniform_medium");
 }
@@ -137,8 +814,21 @@ criterion_group!(
     benches,
     bench_insert_only,
     bench_get_only,
+    bench_get_u64,
+    bench_get_large_values,
+    bench_insert_batch,
     bench_mixed,
     bench_insert_heavy,
     bench_read_heavy,
+    bench_hashers,
+    bench_read_heavy_prefetch,
+    bench_get_frozen,
+    bench_membership,
+    bench_iterate,
+    bench_ordered,
+    bench_range_scan,
+    bench_string_keys,
+    bench_interned,
+    bench_bulk_build,
 );
 criterion_main!(benches);
@@ -0,0 +1,266 @@
+//! Benchmarks for the priority queues
+//!
+//! Every heap replays the same seeded sequences: `push_pop` pushes `n`
+//! random keys then pops them all, and `decrease_key` pushes `n` keys and
+//! then interleaves lowering random live keys with pops, the access pattern
+//! of Dijkstra's algorithm. Heaps without decrease-key stand in for it the
+//! way Dijkstra usually does with std, by pushing a duplicate entry with the
+//! lower key and skipping stale entries when they pop. `meld` combines many
+//! small heaps into one. The mergeable heaps run through the shared
+//! `MergeableHeap` trait, so a new variant needs one line per group.
+
+use criterion::measurement::WallTime;
+use criterion::BenchmarkGroup;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use dsa_lab::{
+    BinaryHeap, BinomialHeap, FibonacciHeap, HeapOrder, IndexedPriorityQueue, LeftistHeap,
+    MergeableHeap, SkewHeap,
+};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap as StdBinaryHeap;
+
+const SIZES: [usize; 2] = [1_000, 100_000];
+
+fn random_keys(n: usize, seed: u64) -> Vec<u64> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..n).map(|_| rng.gen_range(0..1 << 32)).collect()
+}
+
+fn push_pop<H: MergeableHeap<u64>>(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    name: &str,
+    keys: &[u64],
+) {
+    group.bench_function(BenchmarkId::new(name, keys.len()), |b| {
+        b.iter(|| {
+            let mut heap = H::default();
+            for &key in keys {
+                heap.push(key);
+            }
+            while let Some(key) = heap.pop() {
+                black_box(key);
+            }
+        })
+    });
+}
+
+fn bench_push_pop(c: &mut Criterion) {
+    let mut group = c.benchmark_group("push_pop");
+    for n in SIZES {
+        let keys = random_keys(n, 42);
+
+        group.bench_function(BenchmarkId::new("binary_heap", n), |b| {
+            b.iter(|| {
+                let mut heap = BinaryHeap::with_capacity(HeapOrder::Min, n);
+                for &key in &keys {
+                    heap.push(key);
+                }
+                while let Some(key) = heap.pop() {
+                    black_box(key);
+                }
+            })
+        });
+        push_pop::<FibonacciHeap<u64>>(&mut group, "fibonacci_heap", &keys);
+        push_pop::<BinomialHeap<u64>>(&mut group, "binomial_heap", &keys);
+        push_pop::<LeftistHeap<u64>>(&mut group, "leftist_heap", &keys);
+        push_pop::<SkewHeap<u64>>(&mut group, "skew_heap", &keys);
+        group.bench_function(BenchmarkId::new("std_binary_heap", n), |b| {
+            b.iter(|| {
+                let mut heap = StdBinaryHeap::with_capacity(n);
+                for &key in &keys {
+                    heap.push(Reverse(key));
+                }
+                while let Some(key) = heap.pop() {
+                    black_box(key);
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+/// A step of the decrease-key workload: lower element `id` by a fraction of
+/// its key, or pop the minimum.
+#[derive(Debug, Clone, Copy)]
+enum Step {
+    Lower(usize, u64),
+    Pop,
+}
+
+fn decrease_key_steps(n: usize, seed: u64) -> Vec<Step> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..4 * n)
+        .map(|_| {
+            if rng.gen_bool(0.75) {
+                Step::Lower(rng.gen_range(0..n), rng.gen_range(1..1 << 16))
+            } else {
+                Step::Pop
+            }
+        })
+        .collect()
+}
+
+fn bench_decrease_key(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decrease_key");
+    for n in SIZES {
+        let keys = random_keys(n, 43);
+        let steps = decrease_key_steps(n, 44);
+
+        group.bench_function(BenchmarkId::new("fibonacci_heap", n), |b| {
+            b.iter(|| {
+                let mut current = keys.clone();
+                let mut popped = vec![false; n];
+                let mut heap = FibonacciHeap::new();
+                let handles: Vec<_> = (0..n).map(|id| heap.push((keys[id], id))).collect();
+                for &step in &steps {
+                    match step {
+                        Step::Lower(id, by) if !popped[id] => {
+                            current[id] = current[id].saturating_sub(by);
+                            heap.decrease_key(handles[id], (current[id], id));
+                        }
+                        Step::Lower(..) => {}
+                        Step::Pop => {
+                            if let Some((_, id)) = heap.pop() {
+                                popped[id] = true;
+                            }
+                        }
+                    }
+                }
+                black_box(heap.len())
+            })
+        });
+        group.bench_function(BenchmarkId::new("indexed_priority_queue", n), |b| {
+            b.iter(|| {
+                let mut current = keys.clone();
+                let mut queue = IndexedPriorityQueue::with_capacity(n);
+                for (id, &key) in keys.iter().enumerate() {
+                    queue.push(id, key);
+                }
+                for &step in &steps {
+                    match step {
+                        Step::Lower(id, by) if queue.contains(id) => {
+                            current[id] = current[id].saturating_sub(by);
+                            queue.decrease_key(id, current[id]);
+                        }
+                        Step::Lower(..) => {}
+                        Step::Pop => {
+                            queue.pop();
+                        }
+                    }
+                }
+                black_box(queue.len())
+            })
+        });
+        group.bench_function(BenchmarkId::new("binary_heap_lazy", n), |b| {
+            b.iter(|| {
+                let mut current = keys.clone();
+                let mut popped = vec![false; n];
+                let mut heap = BinaryHeap::with_capacity(HeapOrder::Min, n);
+                for (id, &key) in keys.iter().enumerate() {
+                    heap.push((key, id));
+                }
+                for &step in &steps {
+                    match step {
+                        Step::Lower(id, by) if !popped[id] => {
+                            current[id] = current[id].saturating_sub(by);
+                            heap.push((current[id], id));
+                        }
+                        Step::Lower(..) => {}
+                        Step::Pop => {
+                            while let Some((key, id)) = heap.pop() {
+                                if !popped[id] && key == current[id] {
+                                    popped[id] = true;
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                }
+                black_box(heap.len())
+            })
+        });
+    }
+    group.finish();
+}
+
+fn meld<H: MergeableHeap<u64>>(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    name: &str,
+    parts: &[&[u64]],
+) {
+    let n: usize = parts.iter().map(|part| part.len()).sum();
+    group.bench_function(BenchmarkId::new(name, n), |b| {
+        b.iter_batched(
+            || {
+                parts
+                    .iter()
+                    .map(|part| {
+                        let mut heap = H::default();
+                        for &key in part.iter() {
+                            heap.push(key);
+                        }
+                        heap
+                    })
+                    .collect::<Vec<_>>()
+            },
+            |heaps| {
+                let mut melded = H::default();
+                for heap in heaps {
+                    melded.meld(heap);
+                }
+                for _ in 0..10 {
+                    black_box(melded.pop());
+                }
+                // Returned so that dropping it is not timed.
+                melded
+            },
+            criterion::BatchSize::LargeInput,
+        )
+    });
+}
+
+/// Meld `MELD_PARTS` heaps of `n / MELD_PARTS` keys each into one, then
+/// pop the ten smallest. The binary heaps have no meld and append by
+/// pushing every element.
+fn bench_meld(c: &mut Criterion) {
+    const MELD_PARTS: usize = 64;
+
+    let mut group = c.benchmark_group("meld");
+    for n in SIZES {
+        let keys = random_keys(n, 45);
+        let parts: Vec<&[u64]> = keys.chunks(n / MELD_PARTS).collect();
+
+        meld::<BinomialHeap<u64>>(&mut group, "binomial_heap", &parts);
+        meld::<FibonacciHeap<u64>>(&mut group, "fibonacci_heap", &parts);
+        meld::<LeftistHeap<u64>>(&mut group, "leftist_heap", &parts);
+        meld::<SkewHeap<u64>>(&mut group, "skew_heap", &parts);
+        group.bench_function(BenchmarkId::new("binary_heap", n), |b| {
+            b.iter_batched(
+                || {
+                    parts
+                        .iter()
+                        .map(|part| BinaryHeap::from_vec(part.to_vec(), HeapOrder::Min))
+                        .collect::<Vec<_>>()
+                },
+                |heaps| {
+                    let mut melded = BinaryHeap::new(HeapOrder::Min);
+                    for heap in heaps {
+                        melded.extend(heap.into_vec());
+                    }
+                    for _ in 0..10 {
+                        black_box(melded.pop());
+                    }
+                    // Returned so that dropping it is not timed.
+                    melded
+                },
+                criterion::BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_push_pop, bench_decrease_key, bench_meld);
+criterion_main!(benches);
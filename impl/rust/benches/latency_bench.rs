@@ -0,0 +1,191 @@
+//! Single-operation latency benchmark for hash maps.
+//!
+//! Criterion reports mean throughput, which hides the latency spikes caused
+//! by a resize rehashing the whole table in one call. This benchmark times
+//! every insert individually and reports tail percentiles and the maximum.
+//! It then replays a map workload, timing every operation, and reports the
+//! median and tail of each window of `WINDOW` operations, so a spike shows
+//! up at the point in the replay where it happened.
+
+use dsa_lab::{ChainedHashMap, HashMap, IncrementalHashMap, RunningMedian, WindowQuantiles};
+use serde::Deserialize;
+use std::fs;
+use std::hash::Hash;
+use std::hint::black_box;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+const INSERTS: u64 = 1 << 20;
+const REPLAY_WORKLOAD: &str = "mixed_uniform_large";
+/// Operations per reported window in the workload replay.
+const WINDOW: usize = 10_000;
+
+#[derive(Debug, Deserialize)]
+struct Operation {
+    op: String,
+    key: String,
+    value: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Workload {
+    operations: Vec<Operation>,
+}
+
+fn load_workload(name: &str) -> Option<Workload> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .join("workloads")
+        .join("map")
+        .join(format!("{}.json", name));
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+trait LatencyMap<K, V> {
+    fn new() -> Self;
+    fn insert(&mut self, key: K, value: V);
+    fn get(&self, key: &K) -> Option<&V>;
+    fn remove(&mut self, key: &K) -> Option<V>;
+}
+
+macro_rules! impl_latency_map {
+    ($map:ident) => {
+        impl<K: Hash + Eq, V> LatencyMap<K, V> for $map<K, V> {
+            fn new() -> Self {
+                <$map<K, V>>::new()
+            }
+            fn insert(&mut self, key: K, value: V) {
+                <$map<K, V>>::insert(self, key, value);
+            }
+            fn get(&self, key: &K) -> Option<&V> {
+                <$map<K, V>>::get(self, key)
+            }
+            fn remove(&mut self, key: &K) -> Option<V> {
+                <$map<K, V>>::remove(self, key)
+            }
+        }
+    };
+}
+
+impl_latency_map!(HashMap);
+impl_latency_map!(ChainedHashMap);
+impl_latency_map!(IncrementalHashMap);
+
+fn insert_latencies<M: LatencyMap<u64, u64>>() -> Vec<Duration> {
+    let mut map = M::new();
+    let mut latencies = Vec::with_capacity(INSERTS as usize);
+    for key in 0..INSERTS {
+        let start = Instant::now();
+        map.insert(black_box(key), key);
+        latencies.push(start.elapsed());
+    }
+    black_box(map);
+    latencies
+}
+
+fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    let index = ((sorted.len() - 1) as f64 * p).round() as usize;
+    sorted[index]
+}
+
+fn report(name: &str, mut latencies: Vec<Duration>) {
+    latencies.sort();
+    let total: Duration = latencies.iter().sum();
+    println!(
+        "{:<12} {:>10.0?} {:>10.0?} {:>10.0?} {:>10.0?} {:>12.0?}",
+        name,
+        total / latencies.len() as u32,
+        percentile(&latencies, 0.99),
+        percentile(&latencies, 0.999),
+        percentile(&latencies, 0.9999),
+        latencies[latencies.len() - 1],
+    );
+}
+
+/// Replay `workload`, timing every operation, and print the median, p99
+/// and maximum latency of the last `WINDOW` operations at the end of each
+/// window, then the median over the whole replay.
+fn replay<M: LatencyMap<String, String>>(name: &str, workload: &Workload) {
+    // Clone the keys and values up front so only the map's work is timed.
+    let operations: Vec<(&str, String, String)> = workload
+        .operations
+        .iter()
+        .map(|op| {
+            let value = op.value.clone().unwrap_or_default();
+            (op.op.as_str(), op.key.clone(), value)
+        })
+        .collect();
+
+    let mut window = WindowQuantiles::new(WINDOW);
+    let mut overall = RunningMedian::new();
+    let mut map = M::new();
+    for (i, (op, key, value)) in operations.into_iter().enumerate() {
+        let start = Instant::now();
+        match op {
+            "insert" => map.insert(key, value),
+            "get" => {
+                black_box(map.get(&key));
+            }
+            "delete" => {
+                black_box(map.remove(&key));
+            }
+            _ => {}
+        }
+        let latency = start.elapsed();
+        window.push(latency);
+        overall.push(latency);
+        if (i + 1) % WINDOW == 0 {
+            println!(
+                "{:<12} {:>10} {:>10.0?} {:>10.0?} {:>12.0?}",
+                name,
+                i + 1,
+                window.median().unwrap(),
+                window.quantile(0.99).unwrap(),
+                window.quantile(1.0).unwrap(),
+            );
+        }
+    }
+    black_box(map);
+    println!(
+        "{:<12} {:>10} {:>10.0?}",
+        name,
+        "all",
+        overall.median().unwrap()
+    );
+}
+
+fn main() {
+    println!("insert latency over {} sequential inserts", INSERTS);
+    println!(
+        "{:<12} {:>10} {:>10} {:>10} {:>10} {:>12}",
+        "map", "mean", "p99", "p99.9", "p99.99", "max"
+    );
+    report("hashmap", insert_latencies::<HashMap<u64, u64>>());
+    report("chained", insert_latencies::<ChainedHashMap<u64, u64>>());
+    report(
+        "incremental",
+        insert_latencies::<IncrementalHashMap<u64, u64>>(),
+    );
+    println!();
+
+    let Some(workload) = load_workload(REPLAY_WORKLOAD) else {
+        eprintln!("Workload {} not found, skipping", REPLAY_WORKLOAD);
+        return;
+    };
+
+    println!(
+        "operation latency per {} operations of {}",
+        WINDOW, REPLAY_WORKLOAD
+    );
+    println!(
+        "{:<12} {:>10} {:>10} {:>10} {:>12}",
+        "map", "ops", "p50", "p99", "max"
+    );
+    replay::<HashMap<String, String>>("hashmap", &workload);
+    replay::<ChainedHashMap<String, String>>("chained", &workload);
+    replay::<IncrementalHashMap<String, String>>("incremental", &workload);
+}
@@ -0,0 +1,173 @@
+//! Memory kept alive by old versions of the persistent maps.
+//!
+//! Builds a map of `BASE` random `u64` keys, then derives `VERSIONS` versions
+//! from it, each one insert or remove away from the one before, and keeps
+//! every version alive. A counting allocator tracks live heap bytes, so the
+//! growth over the base map is what the versions cost between them. The
+//! same churn on a std `BTreeMap` that clones itself for every version
+//! shows what a map without structural sharing would pay.
+
+use dsa_lab::{PersistentMap, PersistentSortedMap};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::BTreeMap;
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+const BASE: usize = 100_000;
+const VERSIONS: usize = 10_000;
+/// Full copies are expensive, so the copying map gets fewer versions; the
+/// report is per version either way.
+const COPIED_VERSIONS: usize = 100;
+
+/// Tracks the bytes currently allocated from the system allocator.
+struct CountingAlloc;
+
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        LIVE.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        LIVE.fetch_add(new_size, Ordering::Relaxed);
+        LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+#[derive(Debug, Clone, Copy)]
+enum Update {
+    Insert(u64, u64),
+    Remove(u64),
+}
+
+/// The base keys, and a churn of updates that mostly overwrite or remove
+/// existing keys and sometimes add new ones.
+fn generate(seed: u64) -> (Vec<u64>, Vec<Update>) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let keys: Vec<u64> = (0..BASE).map(|_| rng.gen()).collect();
+    let updates = (0..VERSIONS)
+        .map(|i| {
+            let key = match rng.gen_range(0..4) {
+                0 => rng.gen(),
+                _ => keys[rng.gen_range(0..BASE)],
+            };
+            if rng.gen_bool(0.25) {
+                Update::Remove(key)
+            } else {
+                Update::Insert(key, i as u64)
+            }
+        })
+        .collect();
+    (keys, updates)
+}
+
+trait Versioned: Sized {
+    fn build(keys: &[u64]) -> Self;
+    fn update(&self, update: Update) -> Self;
+}
+
+impl Versioned for PersistentSortedMap<u64, u64> {
+    fn build(keys: &[u64]) -> Self {
+        keys.iter()
+            .fold(Self::new(), |map, &key| map.insert(key, key))
+    }
+
+    fn update(&self, update: Update) -> Self {
+        match update {
+            Update::Insert(key, value) => self.insert(key, value),
+            Update::Remove(key) => self.remove(&key),
+        }
+    }
+}
+
+impl Versioned for PersistentMap<u64, u64> {
+    fn build(keys: &[u64]) -> Self {
+        keys.iter()
+            .fold(Self::new(), |map, &key| map.insert(key, key))
+    }
+
+    fn update(&self, update: Update) -> Self {
+        match update {
+            Update::Insert(key, value) => self.insert(key, value),
+            Update::Remove(key) => self.remove(&key),
+        }
+    }
+}
+
+impl Versioned for BTreeMap<u64, u64> {
+    fn build(keys: &[u64]) -> Self {
+        keys.iter().map(|&key| (key, key)).collect()
+    }
+
+    fn update(&self, update: Update) -> Self {
+        let mut map = self.clone();
+        match update {
+            Update::Insert(key, value) => {
+                map.insert(key, value);
+            }
+            Update::Remove(key) => {
+                map.remove(&key);
+            }
+        }
+        map
+    }
+}
+
+/// Build the base map and derive one version per update from it, printing
+/// the live bytes of the base and the average added by each version.
+fn report<M: Versioned>(name: &str, keys: &[u64], updates: &[Update]) {
+    let before = LIVE.load(Ordering::Relaxed);
+    let base = M::build(keys);
+    let base_bytes = LIVE.load(Ordering::Relaxed) - before;
+
+    let start = Instant::now();
+    let mut versions = Vec::with_capacity(updates.len());
+    let mut latest = base;
+    for &update in updates {
+        let next = latest.update(update);
+        versions.push(std::mem::replace(&mut latest, next));
+    }
+    let elapsed = start.elapsed();
+    // Leave the Vec of handles out; it would be there with any map.
+    let handles = versions.capacity() * std::mem::size_of::<M>();
+    let version_bytes = LIVE.load(Ordering::Relaxed) - before - base_bytes - handles;
+
+    println!(
+        "{:<22} {:>8} {:>12} {:>16} {:>14.2?}",
+        name,
+        updates.len(),
+        base_bytes,
+        version_bytes / updates.len(),
+        elapsed / updates.len() as u32
+    );
+    black_box((versions, latest));
+}
+
+fn main() {
+    let (keys, updates) = generate(42);
+    println!(
+        "version churn: base of {} u64 keys, each version one update away",
+        BASE
+    );
+    println!(
+        "{:<22} {:>8} {:>12} {:>16} {:>14}",
+        "map", "versions", "base bytes", "bytes/version", "time/version"
+    );
+    report::<PersistentSortedMap<u64, u64>>("persistent_sorted", &keys, &updates);
+    report::<PersistentMap<u64, u64>>("persistent_hamt", &keys, &updates);
+    report::<BTreeMap<u64, u64>>("std_btree_clone", &keys, &updates[..COPIED_VERSIONS]);
+}
@@ -0,0 +1,99 @@
+//! Benchmarks for the range-query structures
+//!
+//! Each workload is a seeded sequence of point additions and range sums over
+//! a sequence of `n` integers, half of each, with range endpoints drawn
+//! uniformly. Every structure replays the same sequence.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use dsa_lab::{FenwickTree, SegmentTree, SqrtDecomposition, Sum};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+const SIZES: [usize; 4] = [100, 1_000, 100_000, 1_000_000];
+const OPERATIONS: usize = 10_000;
+
+#[derive(Debug, Clone, Copy)]
+enum Operation {
+    Add(usize, i64),
+    Sum(usize, usize),
+}
+
+fn generate(n: usize, seed: u64) -> (Vec<i64>, Vec<Operation>) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let values = (0..n).map(|_| rng.gen_range(-1000..1000)).collect();
+    let operations = (0..OPERATIONS)
+        .map(|_| {
+            if rng.gen_bool(0.5) {
+                Operation::Add(rng.gen_range(0..n), rng.gen_range(-1000..1000))
+            } else {
+                let start = rng.gen_range(0..n);
+                Operation::Sum(start, rng.gen_range(start..=n))
+            }
+        })
+        .collect();
+    (values, operations)
+}
+
+fn bench_point_add_range_sum(c: &mut Criterion) {
+    let mut group = c.benchmark_group("point_add_range_sum");
+    for n in SIZES {
+        let (values, operations) = generate(n, 42);
+
+        group.bench_function(BenchmarkId::new("segment_tree", n), |b| {
+            b.iter_batched_ref(
+                || SegmentTree::<Sum<i64>>::from_vec(values.clone()),
+                |tree| {
+                    for &operation in &operations {
+                        match operation {
+                            Operation::Add(index, delta) => {
+                                tree.set(index, tree.get(index) + delta)
+                            }
+                            Operation::Sum(start, end) => {
+                                black_box(tree.query(start..end));
+                            }
+                        }
+                    }
+                },
+                criterion::BatchSize::LargeInput,
+            )
+        });
+        group.bench_function(BenchmarkId::new("sqrt_decomposition", n), |b| {
+            b.iter_batched_ref(
+                || SqrtDecomposition::<Sum<i64>>::from_vec(values.clone()),
+                |blocks| {
+                    for &operation in &operations {
+                        match operation {
+                            Operation::Add(index, delta) => {
+                                blocks.set(index, blocks.get(index) + delta)
+                            }
+                            Operation::Sum(start, end) => {
+                                black_box(blocks.query(start..end));
+                            }
+                        }
+                    }
+                },
+                criterion::BatchSize::LargeInput,
+            )
+        });
+        group.bench_function(BenchmarkId::new("fenwick", n), |b| {
+            b.iter_batched_ref(
+                || FenwickTree::from_vec(values.clone()),
+                |tree| {
+                    for &operation in &operations {
+                        match operation {
+                            Operation::Add(index, delta) => tree.add(index, delta),
+                            Operation::Sum(start, end) => {
+                                black_box(tree.range_sum(start..end));
+                            }
+                        }
+                    }
+                },
+                criterion::BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_point_add_range_sum);
+criterion_main!(benches);
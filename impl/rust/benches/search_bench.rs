@@ -0,0 +1,56 @@
+//! Benchmarks for searching sorted slices
+//!
+//! Each iteration looks up 1,000 keys drawn from the slice. On uniform
+//! keys interpolation search guesses each position closely and takes
+//! O(log log n) probes to binary search's O(log n); on keys skewed
+//! towards small values its guesses go wrong and it decays towards a
+//! linear scan.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use dsa_lab::search::{binary_search, exponential_search, interpolation_search};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+const SIZES: [usize; 3] = [1_000, 100_000, 10_000_000];
+const LOOKUPS: usize = 1_000;
+
+type Search = fn(&[u64], &u64) -> Result<usize, usize>;
+
+fn bench_shape(c: &mut Criterion, shape: &str, mut key: impl FnMut(&mut StdRng) -> u64) {
+    let mut rng = StdRng::seed_from_u64(909);
+    let mut group = c.benchmark_group(format!("search/{}", shape));
+    group.throughput(Throughput::Elements(LOOKUPS as u64));
+    let searches: [(&str, Search); 4] = [
+        ("std", |v, x| v.binary_search(x)),
+        ("binary", binary_search),
+        ("exponential", exponential_search),
+        ("interpolation", interpolation_search),
+    ];
+    for n in SIZES {
+        let mut v: Vec<u64> = (0..n).map(|_| key(&mut rng)).collect();
+        v.sort_unstable();
+        let probes: Vec<u64> = (0..LOOKUPS).map(|_| v[rng.gen_range(0..n)]).collect();
+        for (name, search) in searches {
+            group.bench_function(BenchmarkId::new(name, n), |b| {
+                b.iter(|| {
+                    for x in &probes {
+                        black_box(search(&v, x).is_ok());
+                    }
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_search(c: &mut Criterion) {
+    bench_shape(c, "uniform", |rng| rng.gen());
+    // Squaring a uniform draw piles the keys up near zero.
+    bench_shape(c, "skewed", |rng| {
+        let x: u64 = rng.gen_range(0..1 << 32);
+        x * x
+    });
+}
+
+criterion_group!(benches, bench_search);
+criterion_main!(benches);
@@ -0,0 +1,220 @@
+//! Benchmarks for the sorting algorithms
+//!
+//! `sort_workloads` runs every sort in the lab over the inputs in
+//! `workloads/sort/`, one group per input distribution: how a sort reacts
+//! to presorted runs, reversal and duplicates matters as much as its
+//! asymptotic cost. `linear_sorts` pits radix and counting sort against
+//! the comparison sorts on random keys: radix sort's O(n·w) beats
+//! O(n log n) once log n outgrows the passes it makes, and counting sort
+//! wins outright when keys come from a small alphabet. Every run sorts a
+//! fresh clone of the input, made outside the timed section.
+
+use criterion::measurement::WallTime;
+use criterion::{
+    black_box, criterion_group, criterion_main, BatchSize, BenchmarkGroup, BenchmarkId, Criterion,
+    Throughput,
+};
+use dsa_lab::radix_sort::{counting_sort, lsd_radix_sort, msd_radix_sort, RadixKey};
+use dsa_lab::sort::Algorithm;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+const SIZES: [usize; 3] = [1_000, 100_000, 1_000_000];
+
+/// The input distributions in `workloads/sort/`.
+const DISTRIBUTIONS: [&str; 5] = [
+    "random",
+    "nearly_sorted",
+    "reversed",
+    "few_unique",
+    "sawtooth",
+];
+
+/// Slices the parallel sorts hand to their sequential counterparts.
+#[cfg(feature = "rayon")]
+const CUTOFF: usize = 4096;
+
+#[derive(Debug, Deserialize)]
+struct SortWorkload {
+    keys: Vec<u32>,
+}
+
+/// Load the keys of a sort workload.
+fn load_workload(name: &str) -> Option<Vec<u32>> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .join("workloads")
+        .join("sort")
+        .join(format!("{}.json", name));
+    let content = fs::read_to_string(path).ok()?;
+    let workload: SortWorkload = serde_json::from_str(&content).ok()?;
+    Some(workload.keys)
+}
+
+/// Time `sort` on fresh clones of `input`.
+fn bench_sort<T: Copy>(
+    group: &mut BenchmarkGroup<WallTime>,
+    id: BenchmarkId,
+    input: &[T],
+    sort: impl Fn(&mut [T]),
+) {
+    group.bench_function(id, |b| {
+        b.iter_batched_ref(
+            || input.to_vec(),
+            |v| {
+                sort(v);
+                black_box(v.first().copied());
+            },
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+fn bench_workloads(c: &mut Criterion) {
+    for distribution in DISTRIBUTIONS {
+        let mut group = c.benchmark_group(format!("sort_workloads/{}", distribution));
+        group.sample_size(10);
+        for size in ["small", "medium"] {
+            let Some(keys) = load_workload(&format!("{}_{}", distribution, size)) else {
+                eprintln!(
+                    "skipping {}_{}: run tools/gen_workloads.py first",
+                    distribution, size
+                );
+                continue;
+            };
+            group.throughput(Throughput::Elements(keys.len() as u64));
+            let id = |name: &str| BenchmarkId::new(name, size);
+            bench_sort(&mut group, id("std_stable"), &keys, |v| v.sort());
+            bench_sort(&mut group, id("std_unstable"), &keys, |v| v.sort_unstable());
+            for algorithm in Algorithm::ALL {
+                // Quadratic: 100,000 random keys would take seconds a run.
+                if algorithm == Algorithm::Insertion && keys.len() > 10_000 {
+                    continue;
+                }
+                let name = format!("{:?}", algorithm).to_lowercase();
+                bench_sort(&mut group, id(&name), &keys, |v| algorithm.sort(v));
+            }
+            bench_sort(&mut group, id("lsd_radix"), &keys, lsd_radix_sort);
+            bench_sort(&mut group, id("msd_radix"), &keys, msd_radix_sort);
+            #[cfg(feature = "rayon")]
+            {
+                use dsa_lab::par_sort::{par_merge_sort, par_quick_sort};
+                bench_sort(&mut group, id("par_merge"), &keys, |v| {
+                    par_merge_sort(v, CUTOFF)
+                });
+                bench_sort(&mut group, id("par_quick"), &keys, |v| {
+                    par_quick_sort(v, CUTOFF)
+                });
+            }
+        }
+        group.finish();
+    }
+}
+
+fn bench_keys<T>(c: &mut Criterion, name: &str, mut key: impl FnMut(&mut StdRng) -> T)
+where
+    T: RadixKey + Ord + Copy,
+{
+    let mut rng = StdRng::seed_from_u64(905);
+    let mut group = c.benchmark_group(format!("linear_sorts/{}", name));
+    group.sample_size(20);
+    for n in SIZES {
+        let input: Vec<T> = (0..n).map(|_| key(&mut rng)).collect();
+        group.throughput(Throughput::Elements(n as u64));
+        let id = |name: &str| BenchmarkId::new(name, n);
+        bench_sort(&mut group, id("std_unstable"), &input, |v| {
+            v.sort_unstable()
+        });
+        bench_sort(&mut group, id("merge"), &input, |v| {
+            Algorithm::Merge.sort(v)
+        });
+        bench_sort(&mut group, id("intro"), &input, |v| {
+            Algorithm::Intro.sort(v)
+        });
+        bench_sort(&mut group, id("lsd_radix"), &input, lsd_radix_sort);
+        bench_sort(&mut group, id("msd_radix"), &input, msd_radix_sort);
+    }
+    group.finish();
+}
+
+fn bench_linear_sorts(c: &mut Criterion) {
+    bench_keys(c, "u32", |rng| rng.gen::<u32>());
+    bench_keys(c, "u64", |rng| rng.gen::<u64>());
+}
+
+fn bench_small_alphabet(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(906);
+    let mut group = c.benchmark_group("linear_sorts/alphabet_16");
+    group.sample_size(20);
+    for n in SIZES {
+        let input: Vec<u8> = (0..n).map(|_| rng.gen_range(0..16)).collect();
+        group.throughput(Throughput::Elements(n as u64));
+        let id = |name: &str| BenchmarkId::new(name, n);
+        bench_sort(&mut group, id("std_unstable"), &input, |v| {
+            v.sort_unstable()
+        });
+        bench_sort(&mut group, id("intro"), &input, |v| {
+            Algorithm::Intro.sort(v)
+        });
+        bench_sort(&mut group, id("lsd_radix"), &input, lsd_radix_sort);
+        bench_sort(&mut group, id("counting"), &input, |v| {
+            counting_sort(v, 16, |&b| b as usize)
+        });
+    }
+    group.finish();
+}
+
+/// Speedup of the parallel sorts across thread counts over a million
+/// random `u64`s: `cargo bench --features rayon --bench sort_bench --
+/// par_sort`. The `sequential` entries are the sorts they fall back to
+/// below the cutoff, the baseline each speedup is measured against.
+#[cfg(feature = "rayon")]
+fn bench_par_sort(c: &mut Criterion) {
+    use dsa_lab::par_sort::{par_merge_sort, par_quick_sort};
+    type ParSort = fn(&mut [u64], usize);
+
+    const N: usize = 1_000_000;
+    let mut rng = StdRng::seed_from_u64(907);
+    let input: Vec<u64> = (0..N).map(|_| rng.gen()).collect();
+    let mut group = c.benchmark_group("par_sort");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(N as u64));
+    for (name, sort) in [("merge", Algorithm::Merge), ("quick", Algorithm::Quick)] {
+        let id = BenchmarkId::new(name, "sequential");
+        bench_sort(&mut group, id, &input, |v| sort.sort(v));
+    }
+    let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut threads = vec![1, 2, 4, 8, 16];
+    threads.retain(|&t| t < cores);
+    threads.push(cores);
+    for t in threads {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(t)
+            .build()
+            .unwrap();
+        let parallel: [(&str, ParSort); 2] = [("merge", par_merge_sort), ("quick", par_quick_sort)];
+        for (name, sort) in parallel {
+            let id = BenchmarkId::new(name, format!("threads_{}", t));
+            bench_sort(&mut group, id, &input, |v| pool.install(|| sort(v, CUTOFF)));
+        }
+    }
+    group.finish();
+}
+
+#[cfg(not(feature = "rayon"))]
+fn bench_par_sort(_: &mut Criterion) {}
+
+criterion_group!(
+    benches,
+    bench_workloads,
+    bench_linear_sorts,
+    bench_small_alphabet,
+    bench_par_sort
+);
+criterion_main!(benches);
@@ -0,0 +1,40 @@
+//! Benchmarks for the single-pattern string search algorithms
+//!
+//! Each algorithm finds every occurrence of a pattern cut from a random
+//! 1 MiB text, across alphabet sizes and pattern lengths. Small alphabets
+//! mean short bad-character shifts for Horspool and long partial matches
+//! for everyone; large alphabets let Horspool skip most of the text.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use dsa_lab::strsearch::Algorithm;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+const TEXT_LEN: usize = 1 << 20;
+const ALPHABETS: [usize; 3] = [2, 4, 26];
+const PATTERN_LENS: [usize; 3] = [4, 16, 64];
+
+fn bench_find_all(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(863);
+    for alphabet in ALPHABETS {
+        let text: Vec<u8> = (0..TEXT_LEN)
+            .map(|_| b'a' + rng.gen_range(0..alphabet) as u8)
+            .collect();
+        let mut group = c.benchmark_group(format!("find_all/alphabet_{}", alphabet));
+        group.throughput(Throughput::Bytes(TEXT_LEN as u64));
+        for m in PATTERN_LENS {
+            let start = rng.gen_range(0..TEXT_LEN - m);
+            let pattern = &text[start..start + m];
+            for algorithm in Algorithm::ALL {
+                let name = format!("{:?}", algorithm).to_lowercase();
+                group.bench_function(BenchmarkId::new(name, m), |b| {
+                    b.iter(|| black_box(algorithm.find_all(pattern, &text).len()))
+                });
+            }
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_find_all);
+criterion_main!(benches);
@@ -0,0 +1,437 @@
+//! AA tree ordered map.
+
+use crate::btree::{before_start, check_range, past_end};
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::RangeBounds;
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+#[derive(Debug, Clone)]
+struct Node<K, V> {
+    key: K,
+    value: V,
+    /// Level of the node; leaves are at level 1.
+    level: usize,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+fn level<K, V>(link: &Link<K, V>) -> usize {
+    link.as_ref().map_or(0, |node| node.level)
+}
+
+impl<K, V> Node<K, V> {
+    fn leaf(key: K, value: V) -> Box<Self> {
+        Box::new(Self {
+            key,
+            value,
+            level: 1,
+            left: None,
+            right: None,
+        })
+    }
+}
+
+/// Remove a left horizontal link by rotating the left child up.
+fn skew<K, V>(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    match node.left.take() {
+        Some(mut left) if left.level == node.level => {
+            node.left = left.right.take();
+            left.right = Some(node);
+            left
+        }
+        left => {
+            node.left = left;
+            node
+        }
+    }
+}
+
+/// Remove two consecutive right horizontal links by rotating the right
+/// child up a level.
+fn split<K, V>(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    let two_right = node
+        .right
+        .as_ref()
+        .is_some_and(|right| level(&right.right) == node.level);
+    if !two_right {
+        return node;
+    }
+    let mut right = node.right.take().expect("checked above");
+    node.right = right.left.take();
+    right.left = Some(node);
+    right.level += 1;
+    right
+}
+
+/// Restore the invariants on the way up from a removal below `node`.
+fn rebalance<K, V>(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    // Drop the level to one above the lower child, taking a horizontal
+    // right child down with it.
+    let should_be = 1 + level(&node.left).min(level(&node.right));
+    if should_be < node.level {
+        node.level = should_be;
+        if let Some(right) = node.right.as_mut() {
+            right.level = right.level.min(should_be);
+        }
+    }
+    // Up to three skews and two splits straighten out the level.
+    let mut node = skew(node);
+    if let Some(right) = node.right.take() {
+        let mut right = skew(right);
+        if let Some(right_right) = right.right.take() {
+            right.right = Some(skew(right_right));
+        }
+        node.right = Some(right);
+    }
+    let mut node = split(node);
+    if let Some(right) = node.right.take() {
+        node.right = Some(split(right));
+    }
+    node
+}
+
+fn insert<K: Ord, V>(link: Link<K, V>, key: K, value: V) -> (Box<Node<K, V>>, Option<V>) {
+    let Some(mut node) = link else {
+        return (Node::leaf(key, value), None);
+    };
+    let old = match key.cmp(&node.key) {
+        Ordering::Less => {
+            let (left, old) = insert(node.left.take(), key, value);
+            node.left = Some(left);
+            old
+        }
+        Ordering::Greater => {
+            let (right, old) = insert(node.right.take(), key, value);
+            node.right = Some(right);
+            old
+        }
+        Ordering::Equal => {
+            let old = std::mem::replace(&mut node.value, value);
+            return (node, Some(old));
+        }
+    };
+    (split(skew(node)), old)
+}
+
+/// Detach the minimum node of a subtree, returning it and the rest.
+fn remove_min<K, V>(mut node: Box<Node<K, V>>) -> (Box<Node<K, V>>, Link<K, V>) {
+    match node.left.take() {
+        None => {
+            let rest = node.right.take();
+            (node, rest)
+        }
+        Some(left) => {
+            let (min, left) = remove_min(left);
+            node.left = left;
+            (min, Some(rebalance(node)))
+        }
+    }
+}
+
+fn remove<K: Ord, V>(link: Link<K, V>, key: &K) -> (Link<K, V>, Option<V>) {
+    let Some(mut node) = link else {
+        return (None, None);
+    };
+    let removed = match key.cmp(&node.key) {
+        Ordering::Less => {
+            let (left, removed) = remove(node.left.take(), key);
+            node.left = left;
+            removed
+        }
+        Ordering::Greater => {
+            let (right, removed) = remove(node.right.take(), key);
+            node.right = right;
+            removed
+        }
+        Ordering::Equal => {
+            let Some(right) = node.right.take() else {
+                // Only a level-1 node lacks a right child, and it then has
+                // no left child either.
+                return (None, Some(node.value));
+            };
+            // Replace the node with its in-order successor.
+            let (mut successor, right) = remove_min(right);
+            successor.left = node.left.take();
+            successor.right = right;
+            successor.level = node.level;
+            return (Some(rebalance(successor)), Some(node.value));
+        }
+    };
+    (Some(rebalance(node)), removed)
+}
+
+/// An ordered map stored in an AA tree.
+///
+/// An AA tree is a red-black tree in which only right children may be red.
+/// Colors become levels: a left child is always one level below its
+/// parent, a right child is at most one level below it, and a node's
+/// right grandchild never shares its level. Two local fixes, `skew` (a
+/// right rotation removing a left link on one level) and `split` (a left
+/// rotation that lifts the middle of three nodes on one level), are all
+/// insertion needs, and removal is the same pair applied a few times on the
+/// way back up. Height stays within 2 log2 n, like a red-black tree, with
+/// a fraction of the cases to get right, which makes it a useful reference
+/// when checking the other ordered maps.
+#[derive(Clone)]
+pub struct AaTreeMap<K, V> {
+    root: Link<K, V>,
+    len: usize,
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for AaTreeMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V> Default for AaTreeMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> AaTreeMap<K, V> {
+    /// Create a new empty AaTreeMap.
+    pub fn new() -> Self {
+        Self { root: None, len: 0 }
+    }
+
+    /// Returns the number of elements in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the map contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the level of the root, which is at most log2(n + 1).
+    pub fn level(&self) -> usize {
+        level(&self.root)
+    }
+
+    /// Clear all entries from the map.
+    pub fn clear(&mut self) {
+        self.root = None;
+        self.len = 0;
+    }
+}
+
+impl<K: Ord, V> AaTreeMap<K, V> {
+    /// Insert a key-value pair into the map.
+    ///
+    /// Returns the previous value if the key existed.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let (root, old) = insert(self.root.take(), key, value);
+        self.root = Some(root);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    /// Get a reference to the value associated with the key.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let mut link = &self.root;
+        while let Some(node) = link {
+            link = match key.cmp(&node.key) {
+                Ordering::Less => &node.left,
+                Ordering::Greater => &node.right,
+                Ordering::Equal => return Some(&node.value),
+            };
+        }
+        None
+    }
+
+    /// Check if the map contains the given key.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Remove a key-value pair from the map.
+    ///
+    /// Returns the removed value if the key existed.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let (root, removed) = remove(self.root.take(), key);
+        self.root = root;
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    /// Iterate over all key-value pairs in key order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.range(..)
+    }
+
+    /// Iterate over the key-value pairs whose keys fall in `range`, in key
+    /// order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range starts after it ends, or starts and ends at the
+    /// same excluded key.
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Iter<'_, K, V> {
+        check_range(&range);
+        let mut iter = Iter {
+            stack: Vec::new(),
+            end: None,
+        };
+        let mut link = &self.root;
+        while let Some(node) = link {
+            if before_start(&node.key, range.start_bound()) {
+                link = &node.right;
+            } else {
+                iter.stack.push(node);
+                link = &node.left;
+            }
+        }
+        let mut link = &self.root;
+        while let Some(node) = link {
+            if past_end(&node.key, range.end_bound()) {
+                iter.end = Some(node);
+                link = &node.left;
+            } else {
+                link = &node.right;
+            }
+        }
+        iter
+    }
+
+    /// Check the AA invariants, panicking on the first violation.
+    ///
+    /// Checks that keys are strictly increasing in order, that leaves are
+    /// at level 1, that left children are one level down and right children
+    /// at most one, that no right grandchild shares its grandparent's level,
+    /// and that every node above level 1 has two children.
+    pub fn debug_validate(&self) {
+        /// Returns the entry count of the subtree.
+        fn walk<K: Ord, V>(link: &Link<K, V>, lower: Option<&K>, upper: Option<&K>) -> usize {
+            let Some(node) = link else {
+                return 0;
+            };
+            assert!(
+                lower.map_or(true, |lower| node.key > *lower),
+                "keys out of order"
+            );
+            assert!(
+                upper.map_or(true, |upper| node.key < *upper),
+                "keys out of order"
+            );
+            if node.left.is_none() && node.right.is_none() {
+                assert_eq!(node.level, 1, "leaf above level 1");
+            }
+            if node.level > 1 {
+                assert!(
+                    node.left.is_some() && node.right.is_some(),
+                    "node above level 1 lacks a child"
+                );
+            }
+            assert_eq!(
+                level(&node.left) + 1,
+                node.level,
+                "left child not one level down"
+            );
+            let right_level = level(&node.right);
+            assert!(
+                right_level + 1 >= node.level && right_level <= node.level,
+                "right child level out of range"
+            );
+            if let Some(right) = &node.right {
+                assert!(
+                    level(&right.right) < node.level,
+                    "two right links on one level"
+                );
+            }
+            let left_count = walk(&node.left, lower, Some(&node.key));
+            let right_count = walk(&node.right, Some(&node.key), upper);
+            1 + left_count + right_count
+        }
+
+        let count = walk(&self.root, None, None);
+        assert_eq!(count, self.len, "len does not match entry count");
+    }
+}
+
+/// An iterator over a key range of an [`AaTreeMap`], in key order.
+pub struct Iter<'a, K, V> {
+    /// Nodes still to visit whose left subtrees are already handled; the
+    /// next entry is on top.
+    stack: Vec<&'a Node<K, V>>,
+    /// The first node past the range, if there is one.
+    end: Option<&'a Node<K, V>>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        if self.end.is_some_and(|end| std::ptr::eq(end, node)) {
+            self.stack.clear();
+            return None;
+        }
+        let mut link = &node.right;
+        while let Some(child) = link {
+            self.stack.push(child);
+            link = &child.left;
+        }
+        Some((&node.key, &node.value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sorted_inserts_stay_balanced() {
+        let mut map = AaTreeMap::new();
+        for i in 0..1023 {
+            assert_eq!(map.insert(i, i), None);
+            map.debug_validate();
+        }
+        // A node at level L roots at least 2^L - 1 keys.
+        assert!(map.level() <= 10);
+        assert!(map.iter().map(|(k, _)| *k).eq(0..1023));
+    }
+
+    #[test]
+    fn test_skew_then_split() {
+        // Inserting below a lone node makes a left link, which skew turns
+        // into two right links, which split lifts.
+        let mut map = AaTreeMap::new();
+        for key in [3, 2, 1] {
+            map.insert(key, ());
+            map.debug_validate();
+        }
+        let root = map.root.as_ref().unwrap();
+        assert_eq!((root.key, root.level), (2, 2));
+    }
+
+    #[test]
+    fn test_remove_keeps_levels() {
+        let mut map = AaTreeMap::new();
+        for i in 0..500 {
+            map.insert(i, i * 10);
+        }
+        assert_eq!(map.insert(7, 0), Some(70));
+        for i in (0..500).filter(|i| i % 3 != 0) {
+            assert!(map.remove(&i).is_some());
+            map.debug_validate();
+        }
+        assert_eq!(map.remove(&1), None);
+        assert_eq!(map.len(), 167);
+        assert_eq!(map.get(&9), Some(&90));
+        assert!(map.range(10..=20).map(|(k, _)| *k).eq([12, 15, 18]));
+        for i in (0..500).step_by(3) {
+            map.remove(&i);
+            map.debug_validate();
+        }
+        assert!(map.is_empty());
+    }
+}
@@ -0,0 +1,418 @@
+//! AVL tree ordered map.
+
+use crate::btree::{before_start, check_range, past_end};
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::RangeBounds;
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+#[derive(Debug, Clone)]
+struct Node<K, V> {
+    key: K,
+    value: V,
+    /// Height of the subtree rooted here; a leaf has height 1.
+    height: usize,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+fn height<K, V>(link: &Link<K, V>) -> usize {
+    link.as_ref().map_or(0, |node| node.height)
+}
+
+impl<K, V> Node<K, V> {
+    fn leaf(key: K, value: V) -> Box<Self> {
+        Box::new(Self {
+            key,
+            value,
+            height: 1,
+            left: None,
+            right: None,
+        })
+    }
+
+    fn update_height(&mut self) {
+        self.height = 1 + height(&self.left).max(height(&self.right));
+    }
+
+    /// Left height minus right height.
+    fn balance_factor(&self) -> isize {
+        height(&self.left) as isize - height(&self.right) as isize
+    }
+}
+
+/// Lift the left child above `node`.
+fn rotate_right<K, V>(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    let mut pivot = node.left.take().expect("rotate_right needs a left child");
+    node.left = pivot.right.take();
+    node.update_height();
+    pivot.right = Some(node);
+    pivot.update_height();
+    pivot
+}
+
+/// Lift the right child above `node`.
+fn rotate_left<K, V>(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    let mut pivot = node.right.take().expect("rotate_left needs a right child");
+    node.right = pivot.left.take();
+    node.update_height();
+    pivot.left = Some(node);
+    pivot.update_height();
+    pivot
+}
+
+/// Restore the balance of a node whose subtrees differ in height by at most
+/// two, with one or two rotations.
+fn rebalance<K, V>(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    node.update_height();
+    match node.balance_factor() {
+        2 => {
+            let left = node.left.take().expect("left-heavy node has a left child");
+            // Left-right case: straighten the zig-zag first.
+            node.left = Some(if left.balance_factor() < 0 {
+                rotate_left(left)
+            } else {
+                left
+            });
+            rotate_right(node)
+        }
+        -2 => {
+            let right = node
+                .right
+                .take()
+                .expect("right-heavy node has a right child");
+            node.right = Some(if right.balance_factor() > 0 {
+                rotate_right(right)
+            } else {
+                right
+            });
+            rotate_left(node)
+        }
+        _ => node,
+    }
+}
+
+fn insert<K: Ord, V>(link: Link<K, V>, key: K, value: V) -> (Box<Node<K, V>>, Option<V>) {
+    let Some(mut node) = link else {
+        return (Node::leaf(key, value), None);
+    };
+    let old = match key.cmp(&node.key) {
+        Ordering::Less => {
+            let (left, old) = insert(node.left.take(), key, value);
+            node.left = Some(left);
+            old
+        }
+        Ordering::Greater => {
+            let (right, old) = insert(node.right.take(), key, value);
+            node.right = Some(right);
+            old
+        }
+        Ordering::Equal => {
+            let old = std::mem::replace(&mut node.value, value);
+            return (node, Some(old));
+        }
+    };
+    (rebalance(node), old)
+}
+
+/// Detach the minimum node of a subtree, returning it and the rest.
+fn remove_min<K, V>(mut node: Box<Node<K, V>>) -> (Box<Node<K, V>>, Link<K, V>) {
+    match node.left.take() {
+        None => {
+            let rest = node.right.take();
+            (node, rest)
+        }
+        Some(left) => {
+            let (min, left) = remove_min(left);
+            node.left = left;
+            (min, Some(rebalance(node)))
+        }
+    }
+}
+
+fn remove<K: Ord, V>(link: Link<K, V>, key: &K) -> (Link<K, V>, Option<V>) {
+    let Some(mut node) = link else {
+        return (None, None);
+    };
+    let removed = match key.cmp(&node.key) {
+        Ordering::Less => {
+            let (left, removed) = remove(node.left.take(), key);
+            node.left = left;
+            removed
+        }
+        Ordering::Greater => {
+            let (right, removed) = remove(node.right.take(), key);
+            node.right = right;
+            removed
+        }
+        Ordering::Equal => {
+            // Replace the node with its in-order successor.
+            let rest = match node.right.take() {
+                None => node.left.take(),
+                Some(right) => {
+                    let (mut successor, right) = remove_min(right);
+                    successor.left = node.left.take();
+                    successor.right = right;
+                    Some(rebalance(successor))
+                }
+            };
+            return (rest, Some(node.value));
+        }
+    };
+    (Some(rebalance(node)), removed)
+}
+
+/// An ordered map stored in an AVL tree.
+///
+/// Every node records the height of its subtree, and after each insert or
+/// remove the nodes on the search path are rotated until the heights of
+/// their two subtrees differ by at most one. That keeps the tree within
+/// about 1.44 log2 n levels, so lookups are O(log n) in the worst case,
+/// at the price of more rotations per update than looser schemes.
+#[derive(Clone)]
+pub struct AvlMap<K, V> {
+    root: Link<K, V>,
+    len: usize,
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for AvlMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V> Default for AvlMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> AvlMap<K, V> {
+    /// Create a new empty AvlMap.
+    pub fn new() -> Self {
+        Self { root: None, len: 0 }
+    }
+
+    /// Returns the number of elements in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the map contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of levels in the tree.
+    pub fn height(&self) -> usize {
+        height(&self.root)
+    }
+
+    /// Clear all entries from the map.
+    pub fn clear(&mut self) {
+        self.root = None;
+        self.len = 0;
+    }
+}
+
+impl<K: Ord, V> AvlMap<K, V> {
+    /// Insert a key-value pair into the map.
+    ///
+    /// Returns the previous value if the key existed.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let (root, old) = insert(self.root.take(), key, value);
+        self.root = Some(root);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    /// Get a reference to the value associated with the key.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let mut link = &self.root;
+        while let Some(node) = link {
+            link = match key.cmp(&node.key) {
+                Ordering::Less => &node.left,
+                Ordering::Greater => &node.right,
+                Ordering::Equal => return Some(&node.value),
+            };
+        }
+        None
+    }
+
+    /// Check if the map contains the given key.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Remove a key-value pair from the map.
+    ///
+    /// Returns the removed value if the key existed.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let (root, removed) = remove(self.root.take(), key);
+        self.root = root;
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    /// Iterate over all key-value pairs in key order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.range(..)
+    }
+
+    /// Iterate over the key-value pairs whose keys fall in `range`, in key
+    /// order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range starts after it ends, or starts and ends at the
+    /// same excluded key.
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Iter<'_, K, V> {
+        check_range(&range);
+        let mut iter = Iter {
+            stack: Vec::new(),
+            end: None,
+        };
+        // The ancestors of the first entry in the range that it sits left
+        // of, and the last node seen that is past the end.
+        let mut link = &self.root;
+        while let Some(node) = link {
+            if before_start(&node.key, range.start_bound()) {
+                link = &node.right;
+            } else {
+                iter.stack.push(node);
+                link = &node.left;
+            }
+        }
+        let mut link = &self.root;
+        while let Some(node) = link {
+            if past_end(&node.key, range.end_bound()) {
+                iter.end = Some(node);
+                link = &node.left;
+            } else {
+                link = &node.right;
+            }
+        }
+        iter
+    }
+
+    /// Check the AVL invariants, panicking on the first violation.
+    ///
+    /// Checks that keys are strictly increasing in order, that every stored
+    /// height is correct, and that no node's subtrees differ in height by
+    /// more than one.
+    pub fn debug_validate(&self) {
+        /// Returns the height and entry count of the subtree.
+        fn walk<K: Ord, V>(
+            link: &Link<K, V>,
+            lower: Option<&K>,
+            upper: Option<&K>,
+        ) -> (usize, usize) {
+            let Some(node) = link else {
+                return (0, 0);
+            };
+            assert!(
+                lower.map_or(true, |lower| node.key > *lower),
+                "keys out of order"
+            );
+            assert!(
+                upper.map_or(true, |upper| node.key < *upper),
+                "keys out of order"
+            );
+            let (left_height, left_count) = walk(&node.left, lower, Some(&node.key));
+            let (right_height, right_count) = walk(&node.right, Some(&node.key), upper);
+            assert_eq!(
+                node.height,
+                1 + left_height.max(right_height),
+                "stale height"
+            );
+            assert!(
+                left_height.abs_diff(right_height) <= 1,
+                "balance factor out of range"
+            );
+            (node.height, 1 + left_count + right_count)
+        }
+
+        let (_, count) = walk(&self.root, None, None);
+        assert_eq!(count, self.len, "len does not match entry count");
+    }
+}
+
+/// An iterator over a key range of an [`AvlMap`], in key order.
+pub struct Iter<'a, K, V> {
+    /// Nodes still to visit whose left subtrees are already handled; the
+    /// next entry is on top.
+    stack: Vec<&'a Node<K, V>>,
+    /// The first node past the range, if there is one.
+    end: Option<&'a Node<K, V>>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        if self.end.is_some_and(|end| std::ptr::eq(end, node)) {
+            self.stack.clear();
+            return None;
+        }
+        let mut link = &node.right;
+        while let Some(child) = link {
+            self.stack.push(child);
+            link = &child.left;
+        }
+        Some((&node.key, &node.value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sorted_inserts_stay_balanced() {
+        let mut map = AvlMap::new();
+        for i in 0..1023 {
+            assert_eq!(map.insert(i, i), None);
+        }
+        // Sorted input would make a plain BST a 1023-level list.
+        assert_eq!(map.height(), 10);
+        map.debug_validate();
+        assert!(map.iter().map(|(k, _)| *k).eq(0..1023));
+    }
+
+    #[test]
+    fn test_zig_zag_rotations() {
+        // Left-right and right-left cases each need a double rotation.
+        for keys in [[3, 1, 2], [1, 3, 2]] {
+            let mut map = AvlMap::new();
+            for key in keys {
+                map.insert(key, ());
+            }
+            map.debug_validate();
+            assert_eq!(map.height(), 2);
+            assert_eq!(map.root.as_ref().unwrap().key, 2);
+        }
+    }
+
+    #[test]
+    fn test_remove_keeps_balance() {
+        let mut map = AvlMap::new();
+        for i in 0..500 {
+            map.insert(i, i * 10);
+        }
+        assert_eq!(map.insert(7, 0), Some(70));
+        for i in (0..500).filter(|i| i % 3 != 0) {
+            assert!(map.remove(&i).is_some());
+            map.debug_validate();
+        }
+        assert_eq!(map.remove(&1), None);
+        assert_eq!(map.len(), 167);
+        assert_eq!(map.get(&9), Some(&90));
+        assert!(map.range(10..=20).map(|(k, _)| *k).eq([12, 15, 18]));
+    }
+}
@@ -0,0 +1,756 @@
+//! Arbitrary-precision integers: [`BigUint`] and the signed [`BigInt`].
+//!
+//! Magnitudes are vectors of 32-bit limbs, least significant first, with
+//! no high zero limbs, so zero is the empty vector and every number has
+//! exactly one form. Limb products and sums with carries fit in a `u64`.
+//!
+//! The operators work on values and references alike, and panic where the
+//! primitive integers do: subtracting a larger `BigUint`, and dividing by
+//! zero. `checked_sub` and `div_rem` return `None` instead.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
+use std::str::FromStr;
+
+/// Operand size in limbs at or below which [`BigUint::mul_karatsuba`] falls
+/// back to the schoolbook method, as measured by `bigint_bench`.
+pub const KARATSUBA_THRESHOLD: usize = 48;
+
+const LIMB_BITS: u32 = 32;
+/// The largest power of ten in a limb, for decimal conversion nine digits
+/// at a time.
+const DECIMAL_CHUNK: u32 = 1_000_000_000;
+const DECIMAL_CHUNK_DIGITS: usize = 9;
+
+/// The error from parsing a string that is not a decimal integer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseBigIntError;
+
+/// An unsigned integer of any size.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct BigUint {
+    /// Little-endian limbs with no high zeros.
+    limbs: Vec<u32>,
+}
+
+fn normalize(limbs: &mut Vec<u32>) {
+    while limbs.last() == Some(&0) {
+        limbs.pop();
+    }
+}
+
+/// Returns the limbs of `limbs` without its high zeros.
+fn trimmed(limbs: &[u32]) -> &[u32] {
+    let len = limbs
+        .iter()
+        .rposition(|&limb| limb != 0)
+        .map_or(0, |i| i + 1);
+    &limbs[..len]
+}
+
+fn cmp_limbs(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_limbs(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut sum = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0u64;
+    for (i, &limb) in long.iter().enumerate() {
+        let total = u64::from(limb) + u64::from(short.get(i).copied().unwrap_or(0)) + carry;
+        sum.push(total as u32);
+        carry = total >> LIMB_BITS;
+    }
+    if carry > 0 {
+        sum.push(carry as u32);
+    }
+    sum
+}
+
+/// Add `x`, shifted up by `shift` limbs, into `acc`.
+fn add_shifted(acc: &mut Vec<u32>, x: &[u32], shift: usize) {
+    if acc.len() < shift + x.len() {
+        acc.resize(shift + x.len(), 0);
+    }
+    let mut carry = 0u64;
+    let mut i = shift;
+    for &limb in x {
+        let total = u64::from(acc[i]) + u64::from(limb) + carry;
+        acc[i] = total as u32;
+        carry = total >> LIMB_BITS;
+        i += 1;
+    }
+    while carry > 0 {
+        if i == acc.len() {
+            acc.push(0);
+        }
+        let total = u64::from(acc[i]) + carry;
+        acc[i] = total as u32;
+        carry = total >> LIMB_BITS;
+        i += 1;
+    }
+}
+
+/// Subtract `b` from `a` in place; `a` must be at least `b`.
+fn sub_limbs(a: &mut Vec<u32>, b: &[u32]) {
+    let mut borrow = 0i64;
+    for (i, limb) in a.iter_mut().enumerate() {
+        if i >= b.len() && borrow == 0 {
+            break;
+        }
+        let diff = i64::from(*limb) - i64::from(b.get(i).copied().unwrap_or(0)) - borrow;
+        *limb = diff as u32;
+        borrow = i64::from(diff < 0);
+    }
+    debug_assert_eq!(borrow, 0, "subtrahend larger than minuend");
+    normalize(a);
+}
+
+/// The schoolbook product: every limb of one times every limb of the
+/// other, in O(n m).
+fn schoolbook(a: &[u32], b: &[u32]) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let mut product = vec![0u32; a.len() + b.len()];
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, &y) in b.iter().enumerate() {
+            // At most (2^32 - 1)^2 + 2 (2^32 - 1) = 2^64 - 1.
+            let total = u64::from(product[i + j]) + u64::from(x) * u64::from(y) + carry;
+            product[i + j] = total as u32;
+            carry = total >> LIMB_BITS;
+        }
+        product[i + b.len()] = carry as u32;
+    }
+    normalize(&mut product);
+    product
+}
+
+/// Split `x` into its low `m` limbs and the rest, each without high zeros.
+fn split_at(x: &[u32], m: usize) -> (&[u32], &[u32]) {
+    let (low, high) = x.split_at(m.min(x.len()));
+    (trimmed(low), high)
+}
+
+fn karatsuba(a: &[u32], b: &[u32], threshold: usize) -> Vec<u32> {
+    if a.len().min(b.len()) <= threshold.max(3) {
+        return schoolbook(a, b);
+    }
+    let m = (a.len().max(b.len()) + 1) / 2;
+    let (a0, a1) = split_at(a, m);
+    let (b0, b1) = split_at(b, m);
+    let z0 = karatsuba(a0, b0, threshold);
+    let z2 = karatsuba(a1, b1, threshold);
+    // (a0 + a1)(b0 + b1) = z0 + z1 + z2, one product for the middle term.
+    let mut z1 = karatsuba(&add_limbs(a0, a1), &add_limbs(b0, b1), threshold);
+    sub_limbs(&mut z1, &z0);
+    sub_limbs(&mut z1, &z2);
+    let mut product = z0;
+    add_shifted(&mut product, &z1, m);
+    add_shifted(&mut product, &z2, 2 * m);
+    normalize(&mut product);
+    product
+}
+
+/// Divide by a single limb, returning the quotient and remainder.
+fn div_rem_limb(a: &[u32], divisor: u32) -> (Vec<u32>, u32) {
+    let mut quotient = vec![0u32; a.len()];
+    let mut rem = 0u64;
+    for (digit, &limb) in quotient.iter_mut().zip(a).rev() {
+        let current = rem << LIMB_BITS | u64::from(limb);
+        *digit = (current / u64::from(divisor)) as u32;
+        rem = current % u64::from(divisor);
+    }
+    normalize(&mut quotient);
+    (quotient, rem as u32)
+}
+
+/// Long division of `u` by `v`, which has at least two limbs and is no
+/// larger than `u` (Knuth's Algorithm D).
+fn div_rem_limbs(u: &[u32], v: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    let n = v.len();
+    // Shift so the divisor's top limb has its high bit set; then the
+    // quotient limb estimated from the top two limbs is at most two over.
+    let shift = v[n - 1].leading_zeros();
+    let shifted = |x: &[u32], extra: usize| {
+        let mut out = vec![0u32; x.len() + extra];
+        for (i, &limb) in x.iter().enumerate() {
+            let wide = u64::from(limb) << shift;
+            out[i] |= wide as u32;
+            if i + 1 < out.len() {
+                out[i + 1] |= (wide >> LIMB_BITS) as u32;
+            }
+        }
+        out
+    };
+    let vn = shifted(v, 0);
+    let mut un = shifted(u, 1);
+    let base = 1u64 << LIMB_BITS;
+    let (top, second) = (u64::from(vn[n - 1]), u64::from(vn[n - 2]));
+    let mut quotient = vec![0u32; u.len() - n + 1];
+    for j in (0..quotient.len()).rev() {
+        let numerator = u64::from(un[j + n]) << LIMB_BITS | u64::from(un[j + n - 1]);
+        let mut qhat = numerator / top;
+        let mut rhat = numerator % top;
+        while qhat >= base || qhat * second > (rhat << LIMB_BITS | u64::from(un[j + n - 2])) {
+            qhat -= 1;
+            rhat += top;
+            if rhat >= base {
+                break;
+            }
+        }
+        // Subtract qhat times the divisor from the window of the dividend.
+        let mut borrow = 0i64;
+        let mut carry = 0u64;
+        for (i, &limb) in vn.iter().enumerate() {
+            let product = qhat * u64::from(limb) + carry;
+            carry = product >> LIMB_BITS;
+            let diff = i64::from(un[i + j]) - borrow - (product & u64::from(u32::MAX)) as i64;
+            un[i + j] = diff as u32;
+            borrow = i64::from(diff < 0);
+        }
+        let diff = i64::from(un[j + n]) - borrow - carry as i64;
+        un[j + n] = diff as u32;
+        if diff < 0 {
+            // Still one too many, which is rare: add the divisor back.
+            qhat -= 1;
+            let mut carry = 0u64;
+            for (i, &limb) in vn.iter().enumerate() {
+                let total = u64::from(un[i + j]) + u64::from(limb) + carry;
+                un[i + j] = total as u32;
+                carry = total >> LIMB_BITS;
+            }
+            un[j + n] = un[j + n].wrapping_add(carry as u32);
+        }
+        quotient[j] = qhat as u32;
+    }
+    normalize(&mut quotient);
+    // Shift the remainder back down.
+    let mut rem: Vec<u32> = (0..n)
+        .map(|i| {
+            let wide = u64::from(un[i]) | u64::from(un[i + 1]) << LIMB_BITS;
+            (wide >> shift) as u32
+        })
+        .collect();
+    normalize(&mut rem);
+    (quotient, rem)
+}
+
+impl BigUint {
+    /// Returns zero.
+    pub fn zero() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if the number is zero.
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    /// Returns the limbs, least significant first, without high zeros.
+    pub fn limbs(&self) -> &[u32] {
+        &self.limbs
+    }
+
+    /// Create a number from its limbs, least significant first.
+    pub fn from_limbs(mut limbs: Vec<u32>) -> Self {
+        normalize(&mut limbs);
+        Self { limbs }
+    }
+
+    /// Create a number from its bytes, least significant first.
+    pub fn from_bytes_le(bytes: &[u8]) -> Self {
+        let limbs = bytes
+            .chunks(4)
+            .map(|chunk| {
+                let mut word = [0u8; 4];
+                word[..chunk.len()].copy_from_slice(chunk);
+                u32::from_le_bytes(word)
+            })
+            .collect();
+        Self::from_limbs(limbs)
+    }
+
+    /// Returns the bytes, least significant first, without high zeros.
+    pub fn to_bytes_le(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = self
+            .limbs
+            .iter()
+            .flat_map(|limb| limb.to_le_bytes())
+            .collect();
+        while bytes.last() == Some(&0) {
+            bytes.pop();
+        }
+        bytes
+    }
+
+    /// Returns the number of bits needed to write the number, 0 for zero.
+    pub fn bits(&self) -> u64 {
+        match self.limbs.last() {
+            None => 0,
+            Some(top) => {
+                self.limbs.len() as u64 * u64::from(LIMB_BITS) - u64::from(top.leading_zeros())
+            }
+        }
+    }
+
+    /// Returns `self - other`, or `None` if `other` is larger.
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        if cmp_limbs(&self.limbs, &other.limbs) == Ordering::Less {
+            return None;
+        }
+        let mut limbs = self.limbs.clone();
+        sub_limbs(&mut limbs, &other.limbs);
+        Some(Self { limbs })
+    }
+
+    /// Returns the product by the schoolbook method, in O(n m) for n and m
+    /// limbs.
+    pub fn mul_schoolbook(&self, other: &Self) -> Self {
+        Self {
+            limbs: schoolbook(&self.limbs, &other.limbs),
+        }
+    }
+
+    /// Returns the product by Karatsuba's method, switching to the
+    /// schoolbook one for operands of at most `threshold` limbs.
+    ///
+    /// Each number is split into a high and low half, `x = x1 B + x0`, and
+    /// the product `z2 B^2 + z1 B + z0` needs only three half-size
+    /// products rather than four: `z2 = x1 y1`, `z0 = x0 y0`, and `z1`
+    /// from `(x0 + x1)(y0 + y1) - z2 - z0`. That gives O(n^1.585), but the
+    /// extra additions and allocations lose to the schoolbook loop on
+    /// small operands, so the recursion stops at `threshold`. `*` uses
+    /// [`KARATSUBA_THRESHOLD`]. A threshold below 3 counts as 3, since the
+    /// sums of smaller halves, with their carries, are no shorter than the
+    /// operands.
+    pub fn mul_karatsuba(&self, other: &Self, threshold: usize) -> Self {
+        Self {
+            limbs: karatsuba(&self.limbs, &other.limbs, threshold),
+        }
+    }
+
+    /// Returns the quotient and remainder of dividing by `divisor`, or
+    /// `None` if it is zero.
+    ///
+    /// Long division one limb of quotient at a time, each estimated from
+    /// the top limbs of the divisor and the partial remainder and then
+    /// corrected by at most two. O(n m) for a quotient of n limbs and a
+    /// divisor of m.
+    pub fn div_rem(&self, divisor: &Self) -> Option<(Self, Self)> {
+        let (quotient, rem) = match divisor.limbs.len() {
+            0 => return None,
+            _ if cmp_limbs(&self.limbs, &divisor.limbs) == Ordering::Less => {
+                (Vec::new(), self.limbs.clone())
+            }
+            1 => {
+                let (quotient, rem) = div_rem_limb(&self.limbs, divisor.limbs[0]);
+                (quotient, vec![rem])
+            }
+            _ => div_rem_limbs(&self.limbs, &divisor.limbs),
+        };
+        Some((Self::from_limbs(quotient), Self::from_limbs(rem)))
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(n: u64) -> Self {
+        Self::from_limbs(vec![n as u32, (n >> LIMB_BITS) as u32])
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        cmp_limbs(&self.limbs, &other.limbs)
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for BigUint {
+    /// Writes the number in decimal, peeling off nine digits per division.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut chunks = Vec::new();
+        let mut rest = self.limbs.clone();
+        while !rest.is_empty() {
+            let (quotient, chunk) = div_rem_limb(&rest, DECIMAL_CHUNK);
+            chunks.push(chunk);
+            rest = quotient;
+        }
+        let mut digits = match chunks.pop() {
+            None => String::from("0"),
+            Some(top) => top.to_string(),
+        };
+        for chunk in chunks.iter().rev() {
+            digits.push_str(&format!("{:09}", chunk));
+        }
+        f.pad_integral(true, "", &digits)
+    }
+}
+
+impl fmt::Debug for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl FromStr for BigUint {
+    type Err = ParseBigIntError;
+
+    /// Parses a string of decimal digits, nine at a time.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(ParseBigIntError);
+        }
+        let mut limbs: Vec<u32> = Vec::new();
+        for chunk in s.as_bytes().chunks(DECIMAL_CHUNK_DIGITS) {
+            let scale = 10u64.pow(chunk.len() as u32);
+            let mut carry = chunk
+                .iter()
+                .fold(0u64, |n, &digit| n * 10 + u64::from(digit - b'0'));
+            for limb in &mut limbs {
+                let total = u64::from(*limb) * scale + carry;
+                *limb = total as u32;
+                carry = total >> LIMB_BITS;
+            }
+            if carry > 0 {
+                limbs.push(carry as u32);
+            }
+        }
+        Ok(Self::from_limbs(limbs))
+    }
+}
+
+/// Implement an operator for every mix of values and references, given its
+/// implementation on two references.
+macro_rules! forward_binop {
+    ($Type:ident, $Op:ident, $op:ident) => {
+        impl $Op<$Type> for $Type {
+            type Output = $Type;
+            fn $op(self, other: $Type) -> $Type {
+                (&self).$op(&other)
+            }
+        }
+
+        impl $Op<&$Type> for $Type {
+            type Output = $Type;
+            fn $op(self, other: &$Type) -> $Type {
+                (&self).$op(other)
+            }
+        }
+
+        impl $Op<$Type> for &$Type {
+            type Output = $Type;
+            fn $op(self, other: $Type) -> $Type {
+                self.$op(&other)
+            }
+        }
+    };
+}
+
+impl Add<&BigUint> for &BigUint {
+    type Output = BigUint;
+    fn add(self, other: &BigUint) -> BigUint {
+        BigUint {
+            limbs: add_limbs(&self.limbs, &other.limbs),
+        }
+    }
+}
+
+impl Sub<&BigUint> for &BigUint {
+    type Output = BigUint;
+    fn sub(self, other: &BigUint) -> BigUint {
+        self.checked_sub(other)
+            .expect("attempt to subtract with overflow")
+    }
+}
+
+impl Mul<&BigUint> for &BigUint {
+    type Output = BigUint;
+    fn mul(self, other: &BigUint) -> BigUint {
+        self.mul_karatsuba(other, KARATSUBA_THRESHOLD)
+    }
+}
+
+impl Div<&BigUint> for &BigUint {
+    type Output = BigUint;
+    fn div(self, other: &BigUint) -> BigUint {
+        self.div_rem(other).expect("attempt to divide by zero").0
+    }
+}
+
+impl Rem<&BigUint> for &BigUint {
+    type Output = BigUint;
+    fn rem(self, other: &BigUint) -> BigUint {
+        self.div_rem(other)
+            .expect("attempt to calculate the remainder with a divisor of zero")
+            .1
+    }
+}
+
+forward_binop!(BigUint, Add, add);
+forward_binop!(BigUint, Sub, sub);
+forward_binop!(BigUint, Mul, mul);
+forward_binop!(BigUint, Div, div);
+forward_binop!(BigUint, Rem, rem);
+
+/// A signed integer of any size: a sign and a [`BigUint`] magnitude.
+///
+/// Zero is never negative, so equal numbers have equal forms. Division
+/// truncates toward zero and the remainder takes the dividend's sign, as
+/// with the primitive integers.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    magnitude: BigUint,
+}
+
+impl BigInt {
+    /// Create a number from a sign and magnitude; a negative zero is zero.
+    pub fn from_parts(negative: bool, magnitude: BigUint) -> Self {
+        Self {
+            negative: negative && !magnitude.is_zero(),
+            magnitude,
+        }
+    }
+
+    /// Returns zero.
+    pub fn zero() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if the number is zero.
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_zero()
+    }
+
+    /// Returns true if the number is below zero.
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// Returns the absolute value.
+    pub fn magnitude(&self) -> &BigUint {
+        &self.magnitude
+    }
+
+    /// Returns the quotient, truncated toward zero, and the remainder of
+    /// dividing by `divisor`, or `None` if it is zero.
+    pub fn div_rem(&self, divisor: &Self) -> Option<(Self, Self)> {
+        let (quotient, rem) = self.magnitude.div_rem(&divisor.magnitude)?;
+        Some((
+            Self::from_parts(self.negative != divisor.negative, quotient),
+            Self::from_parts(self.negative, rem),
+        ))
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(n: i64) -> Self {
+        Self::from_parts(n < 0, BigUint::from(n.unsigned_abs()))
+    }
+}
+
+impl From<BigUint> for BigInt {
+    fn from(magnitude: BigUint) -> Self {
+        Self::from_parts(false, magnitude)
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, false) => self.magnitude.cmp(&other.magnitude),
+            (true, true) => other.magnitude.cmp(&self.magnitude),
+            (negative, _) => {
+                if negative {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                }
+            }
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad_integral(!self.negative, "", &self.magnitude.to_string())
+    }
+}
+
+impl fmt::Debug for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl FromStr for BigInt {
+    type Err = ParseBigIntError;
+
+    /// Parses decimal digits with an optional leading `-`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, s),
+        };
+        Ok(Self::from_parts(negative, digits.parse()?))
+    }
+}
+
+impl Neg for BigInt {
+    type Output = BigInt;
+    fn neg(self) -> BigInt {
+        Self::from_parts(!self.negative, self.magnitude)
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+    fn neg(self) -> BigInt {
+        -self.clone()
+    }
+}
+
+impl Add<&BigInt> for &BigInt {
+    type Output = BigInt;
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_parts(self.negative, &self.magnitude + &other.magnitude);
+        }
+        // Opposite signs: the larger magnitude wins, less the smaller.
+        match self.magnitude.cmp(&other.magnitude) {
+            Ordering::Less => {
+                BigInt::from_parts(other.negative, &other.magnitude - &self.magnitude)
+            }
+            _ => BigInt::from_parts(self.negative, &self.magnitude - &other.magnitude),
+        }
+    }
+}
+
+impl Sub<&BigInt> for &BigInt {
+    type Output = BigInt;
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &-other
+    }
+}
+
+impl Mul<&BigInt> for &BigInt {
+    type Output = BigInt;
+    fn mul(self, other: &BigInt) -> BigInt {
+        BigInt::from_parts(
+            self.negative != other.negative,
+            &self.magnitude * &other.magnitude,
+        )
+    }
+}
+
+impl Div<&BigInt> for &BigInt {
+    type Output = BigInt;
+    fn div(self, other: &BigInt) -> BigInt {
+        self.div_rem(other).expect("attempt to divide by zero").0
+    }
+}
+
+impl Rem<&BigInt> for &BigInt {
+    type Output = BigInt;
+    fn rem(self, other: &BigInt) -> BigInt {
+        self.div_rem(other)
+            .expect("attempt to calculate the remainder with a divisor of zero")
+            .1
+    }
+}
+
+forward_binop!(BigInt, Add, add);
+forward_binop!(BigInt, Sub, sub);
+forward_binop!(BigInt, Mul, mul);
+forward_binop!(BigInt, Div, div);
+forward_binop!(BigInt, Rem, rem);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(s: &str) -> BigUint {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_unsigned_arithmetic() {
+        let a = big("340282366920938463463374607431768211455"); // 2^128 - 1
+        let one = BigUint::from(1);
+        assert_eq!(
+            (&a + &one).to_string(),
+            "340282366920938463463374607431768211456"
+        );
+        assert_eq!((&a + &one).bits(), 129);
+        assert_eq!(&a + &one - &one, a);
+        assert_eq!(one.checked_sub(&a), None);
+        assert_eq!(
+            (&a * &a).to_string(),
+            "115792089237316195423570985008687907852589419931798687112530834793049593217025"
+        );
+        let (q, r) = big("1000000000000000000000007")
+            .div_rem(&big("12345678901234"))
+            .unwrap();
+        assert_eq!(
+            (q.to_string(), r.to_string()),
+            ("81000000729".into(), "46081000421".into())
+        );
+        assert_eq!(a.div_rem(&BigUint::zero()), None);
+        assert_eq!(BigUint::zero().to_string(), "0");
+        assert!("12a".parse::<BigUint>().is_err());
+        assert!("".parse::<BigUint>().is_err());
+    }
+
+    #[test]
+    fn test_karatsuba_matches_schoolbook() {
+        // Operands of uneven length, with runs of zero and full limbs.
+        let a = BigUint::from_limbs((0..70u32).map(|i| i.wrapping_mul(2_654_435_761)).collect());
+        let b = BigUint::from_limbs(
+            (0..45u32)
+                .map(|i| if i % 5 == 0 { 0 } else { u32::MAX })
+                .collect(),
+        );
+        let expected = a.mul_schoolbook(&b);
+        for threshold in [0, 3, 4, 8, 40] {
+            assert_eq!(a.mul_karatsuba(&b, threshold), expected);
+            assert_eq!(b.mul_karatsuba(&a, threshold), expected);
+        }
+        assert_eq!(&expected / &b, a);
+        assert!((&expected % &a).is_zero());
+        assert!(a.mul_karatsuba(&BigUint::zero(), 0).is_zero());
+    }
+
+    #[test]
+    fn test_signed_arithmetic() {
+        let a = BigInt::from(-17);
+        let b = BigInt::from(5);
+        assert_eq!(&a + &b, BigInt::from(-12));
+        assert_eq!(&b - &a, BigInt::from(22));
+        assert_eq!(&a * &b, BigInt::from(-85));
+        // Truncated division, as for i64.
+        assert_eq!(a.div_rem(&b), Some((BigInt::from(-3), BigInt::from(-2))));
+        assert_eq!(b.div_rem(&a), Some((BigInt::zero(), BigInt::from(5))));
+        assert!(a < b && BigInt::from(-20) < a);
+        assert_eq!(&a + &BigInt::from(17), BigInt::zero());
+        assert!(!(&a + &BigInt::from(17)).is_negative());
+        assert_eq!("-0".parse::<BigInt>(), Ok(BigInt::zero()));
+        assert_eq!(format!("{:>5}", a), "  -17");
+        assert_eq!(BigInt::from(i64::MIN).to_string(), i64::MIN.to_string());
+    }
+}
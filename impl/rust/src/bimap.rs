@@ -0,0 +1,188 @@
+//! Bidirectional one-to-one map built on the lab hash map.
+
+use crate::hashmap::HashMap;
+use std::hash::Hash;
+
+/// Pairs evicted by [`BiMap::insert`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Evicted<L, R> {
+    /// Neither value was paired.
+    Neither,
+    /// The same pair was already present.
+    Pair(L, R),
+    /// The left value was paired with a different right value.
+    Left(L, R),
+    /// The right value was paired with a different left value.
+    Right(L, R),
+    /// Both values were paired elsewhere: the left value's pair, then the
+    /// right value's pair.
+    Both((L, R), (L, R)),
+}
+
+/// A one-to-one map that can be looked up from either side.
+///
+/// Keeps a forward map from left to right values and a reverse map from
+/// right to left values, and every operation updates both, so each left
+/// value pairs with at most one right value and vice versa.
+#[derive(Debug, Clone)]
+pub struct BiMap<L, R> {
+    left_to_right: HashMap<L, R>,
+    right_to_left: HashMap<R, L>,
+}
+
+impl<L, R> Default for BiMap<L, R>
+where
+    L: Hash + Eq + Clone,
+    R: Hash + Eq + Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<L, R> BiMap<L, R>
+where
+    L: Hash + Eq + Clone,
+    R: Hash + Eq + Clone,
+{
+    /// Create a new empty BiMap.
+    pub fn new() -> Self {
+        Self {
+            left_to_right: HashMap::new(),
+            right_to_left: HashMap::new(),
+        }
+    }
+
+    /// Returns the number of pairs.
+    pub fn len(&self) -> usize {
+        self.left_to_right.len()
+    }
+
+    /// Returns true if the map contains no pairs.
+    pub fn is_empty(&self) -> bool {
+        self.left_to_right.is_empty()
+    }
+
+    /// Insert a pair, evicting any pairs that share either value.
+    ///
+    /// Returns the pairs that were removed to keep the map one-to-one.
+    pub fn insert(&mut self, left: L, right: R) -> Evicted<L, R> {
+        let by_left = self.remove_by_left(&left);
+        let by_right = self.remove_by_right(&right);
+        let evicted = match (by_left, by_right) {
+            (None, None) => Evicted::Neither,
+            (Some((l, r)), None) if r == right => Evicted::Pair(l, r),
+            (Some((l, r)), None) => Evicted::Left(l, r),
+            (None, Some((l, r))) => Evicted::Right(l, r),
+            (Some(by_left), Some(by_right)) => Evicted::Both(by_left, by_right),
+        };
+        self.left_to_right.insert(left.clone(), right.clone());
+        self.right_to_left.insert(right, left);
+        evicted
+    }
+
+    /// Get the right value paired with `left`.
+    pub fn get_by_left(&self, left: &L) -> Option<&R> {
+        self.left_to_right.get(left)
+    }
+
+    /// Get the left value paired with `right`.
+    pub fn get_by_right(&self, right: &R) -> Option<&L> {
+        self.right_to_left.get(right)
+    }
+
+    /// Check if `left` is paired.
+    pub fn contains_left(&self, left: &L) -> bool {
+        self.left_to_right.contains_key(left)
+    }
+
+    /// Check if `right` is paired.
+    pub fn contains_right(&self, right: &R) -> bool {
+        self.right_to_left.contains_key(right)
+    }
+
+    /// Remove the pair holding `left`.
+    pub fn remove_by_left(&mut self, left: &L) -> Option<(L, R)> {
+        let right = self.left_to_right.remove(left)?;
+        let left = self
+            .right_to_left
+            .remove(&right)
+            .expect("reverse index has every pair");
+        Some((left, right))
+    }
+
+    /// Remove the pair holding `right`.
+    pub fn remove_by_right(&mut self, right: &R) -> Option<(L, R)> {
+        let left = self.right_to_left.remove(right)?;
+        let right = self
+            .left_to_right
+            .remove(&left)
+            .expect("forward index has every pair");
+        Some((left, right))
+    }
+
+    /// Clear all pairs.
+    pub fn clear(&mut self) {
+        self.left_to_right.clear();
+        self.right_to_left.clear();
+    }
+
+    /// Iterate over all pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (&L, &R)> {
+        self.left_to_right.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_both_ways() {
+        let mut map = BiMap::new();
+        map.insert("one", 1);
+        map.insert("two", 2);
+        assert_eq!(map.get_by_left(&"one"), Some(&1));
+        assert_eq!(map.get_by_right(&2), Some(&"two"));
+        assert_eq!(map.get_by_right(&3), None);
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    fn test_insert_evicts_conflicting_pairs() {
+        let mut map = BiMap::new();
+        map.insert('a', 1);
+        map.insert('b', 2);
+        assert_eq!(map.insert('a', 2), Evicted::Both(('a', 1), ('b', 2)));
+        assert_eq!(map.len(), 1);
+        assert!(!map.contains_left(&'b'));
+        assert!(!map.contains_right(&1));
+        assert_eq!(map.insert('a', 2), Evicted::Pair('a', 2));
+        assert_eq!(map.insert('a', 3), Evicted::Left('a', 2));
+        assert_eq!(map.insert('c', 3), Evicted::Right('a', 3));
+        assert_eq!(map.insert('d', 4), Evicted::Neither);
+        assert_eq!(map.get_by_right(&3), Some(&'c'));
+    }
+
+    #[test]
+    fn test_remove_keeps_sides_in_sync() {
+        let mut map = BiMap::new();
+        for i in 0..100 {
+            map.insert(i, i.to_string());
+        }
+        for i in (0..100).step_by(2) {
+            assert_eq!(map.remove_by_left(&i), Some((i, i.to_string())));
+        }
+        for i in (1..100).step_by(4) {
+            assert_eq!(
+                map.remove_by_right(&i.to_string()),
+                Some((i, i.to_string()))
+            );
+        }
+        assert_eq!(map.len(), 25);
+        assert_eq!(map.right_to_left.len(), 25);
+        for (left, right) in map.iter() {
+            assert_eq!(map.get_by_right(right), Some(left));
+        }
+    }
+}
@@ -0,0 +1,214 @@
+//! Array-backed binary heap priority queue.
+
+/// Which end of the ordering a [`BinaryHeap`] hands out first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeapOrder {
+    /// The smallest element is on top.
+    Min,
+    /// The largest element is on top.
+    Max,
+}
+
+/// A priority queue stored as an implicit binary tree in a `Vec`.
+///
+/// The children of the element at index `i` are at `2i + 1` and `2i + 2`,
+/// and every element comes before its children in the heap's order, so the
+/// top is always at index 0. Push appends and sifts up, pop moves the last
+/// element to the top and sifts down, both O(log n) with no allocation
+/// beyond the `Vec`'s. Building from an existing `Vec` sifts down from the
+/// last parent backwards, in place in O(n). One type covers both min- and
+/// max-heaps, chosen by a [`HeapOrder`] at construction.
+#[derive(Debug, Clone)]
+pub struct BinaryHeap<T> {
+    data: Vec<T>,
+    order: HeapOrder,
+}
+
+impl<T: Ord> BinaryHeap<T> {
+    /// Create a new empty BinaryHeap with the given order.
+    pub fn new(order: HeapOrder) -> Self {
+        Self {
+            data: Vec::new(),
+            order,
+        }
+    }
+
+    /// Create a new empty BinaryHeap with the given order and capacity.
+    pub fn with_capacity(order: HeapOrder, capacity: usize) -> Self {
+        Self {
+            data: Vec::with_capacity(capacity),
+            order,
+        }
+    }
+
+    /// Turn a Vec into a BinaryHeap in place, in O(n).
+    pub fn from_vec(data: Vec<T>, order: HeapOrder) -> Self {
+        let mut heap = Self { data, order };
+        heap.heapify();
+        heap
+    }
+
+    /// Returns the heap's order.
+    pub fn order(&self) -> HeapOrder {
+        self.order
+    }
+
+    /// Returns the number of elements in the heap.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns true if the heap contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Clear all elements from the heap.
+    pub fn clear(&mut self) {
+        self.data.clear();
+    }
+
+    /// Push an element onto the heap.
+    pub fn push(&mut self, item: T) {
+        self.data.push(item);
+        self.sift_up(self.data.len() - 1);
+    }
+
+    /// Remove and return the top element, or `None` if the heap is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        let last = self.data.pop()?;
+        if self.data.is_empty() {
+            return Some(last);
+        }
+        let top = std::mem::replace(&mut self.data[0], last);
+        self.sift_down(0, self.data.len());
+        Some(top)
+    }
+
+    /// Get a reference to the top element, or `None` if the heap is empty.
+    pub fn peek(&self) -> Option<&T> {
+        self.data.first()
+    }
+
+    /// Returns the underlying Vec, in heap order.
+    pub fn into_vec(self) -> Vec<T> {
+        self.data
+    }
+
+    /// Returns the elements sorted in ascending order, whatever the heap's
+    /// order, by heapsort in place.
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        // Each pass moves the current top behind the shrinking heap, which
+        // leaves a max-heap ascending and a min-heap descending.
+        for end in (1..self.data.len()).rev() {
+            self.data.swap(0, end);
+            self.sift_down(0, end);
+        }
+        if self.order == HeapOrder::Min {
+            self.data.reverse();
+        }
+        self.data
+    }
+
+    /// Whether `a` belongs above `b`.
+    fn above(&self, a: &T, b: &T) -> bool {
+        match self.order {
+            HeapOrder::Min => a < b,
+            HeapOrder::Max => a > b,
+        }
+    }
+
+    fn heapify(&mut self) {
+        let len = self.data.len();
+        for position in (0..len / 2).rev() {
+            self.sift_down(position, len);
+        }
+    }
+
+    fn sift_up(&mut self, mut position: usize) {
+        while position > 0 {
+            let parent = (position - 1) / 2;
+            if !self.above(&self.data[position], &self.data[parent]) {
+                break;
+            }
+            self.data.swap(position, parent);
+            position = parent;
+        }
+    }
+
+    /// Sift down within the first `end` elements.
+    fn sift_down(&mut self, mut position: usize, end: usize) {
+        loop {
+            let left = 2 * position + 1;
+            if left >= end {
+                break;
+            }
+            let right = left + 1;
+            let child = if right < end && self.above(&self.data[right], &self.data[left]) {
+                right
+            } else {
+                left
+            };
+            if !self.above(&self.data[child], &self.data[position]) {
+                break;
+            }
+            self.data.swap(position, child);
+            position = child;
+        }
+    }
+}
+
+impl<T: Ord> Extend<T> for BinaryHeap<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_min_and_max_order() {
+        let mut max = BinaryHeap::new(HeapOrder::Max);
+        let mut min = BinaryHeap::new(HeapOrder::Min);
+        for value in [5, 1, 8, 3, 9, 2, 8] {
+            max.push(value);
+            min.push(value);
+        }
+        assert_eq!(max.peek(), Some(&9));
+        assert_eq!(min.peek(), Some(&1));
+        let drained: Vec<i32> = std::iter::from_fn(|| max.pop()).collect();
+        assert_eq!(drained, [9, 8, 8, 5, 3, 2, 1]);
+        let drained: Vec<i32> = std::iter::from_fn(|| min.pop()).collect();
+        assert_eq!(drained, [1, 2, 3, 5, 8, 8, 9]);
+        assert_eq!(min.pop(), None);
+    }
+
+    #[test]
+    fn test_from_vec_heapifies() {
+        let values: Vec<u32> = (0..100).map(|i| i * 37 % 101).collect();
+        for order in [HeapOrder::Min, HeapOrder::Max] {
+            let heap = BinaryHeap::from_vec(values.clone(), order);
+            let data = heap.clone().into_vec();
+            for child in 1..data.len() {
+                assert!(!heap.above(&data[child], &data[(child - 1) / 2]));
+            }
+            let mut sorted = values.clone();
+            sorted.sort_unstable();
+            assert_eq!(heap.into_sorted_vec(), sorted);
+        }
+    }
+
+    #[test]
+    fn test_empty_and_single() {
+        let mut heap = BinaryHeap::new(HeapOrder::Min);
+        assert!(heap.is_empty());
+        assert_eq!(heap.peek(), None);
+        heap.push("only");
+        assert_eq!(heap.pop(), Some("only"));
+        assert!(heap.into_sorted_vec().is_empty());
+    }
+}
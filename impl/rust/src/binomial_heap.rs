@@ -0,0 +1,237 @@
+//! Binomial heap with meld.
+
+#[derive(Debug, Clone)]
+struct Tree<T> {
+    item: T,
+    /// `children[i]` has rank `i`, so a tree's rank is its child count.
+    children: Vec<Tree<T>>,
+}
+
+impl<T: Ord> Tree<T> {
+    fn rank(&self) -> usize {
+        self.children.len()
+    }
+
+    /// Join two trees of equal rank into one of the next rank, the larger
+    /// root becoming the last child of the smaller.
+    fn link(self, other: Self) -> Self {
+        let (mut parent, child) = if other.item < self.item {
+            (other, self)
+        } else {
+            (self, other)
+        };
+        parent.children.push(child);
+        parent
+    }
+}
+
+/// A mergeable min-heap made of binomial trees, at most one of each rank.
+///
+/// A binomial tree of rank `k` is two rank `k - 1` trees with one root
+/// under the other, so it holds exactly `2^k` elements, and the ranks
+/// present in a heap of `n` elements are the set bits of `n`. Melding two
+/// heaps is binary addition: equal ranks link into a carry for the next
+/// rank up, O(log n) in all. Push melds in a rank-0 tree, usually touching
+/// only the low ranks, and pop removes the smallest root and melds its
+/// children, which already form a heap of ranks `0..k`.
+#[derive(Debug, Clone)]
+pub struct BinomialHeap<T> {
+    /// `trees[k]` is the tree of rank `k`, if there is one.
+    trees: Vec<Option<Tree<T>>>,
+    len: usize,
+}
+
+impl<T: Ord> Default for BinomialHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> BinomialHeap<T> {
+    /// Create a new empty BinomialHeap.
+    pub fn new() -> Self {
+        Self {
+            trees: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Returns the number of elements in the heap.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the heap contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Add trees, given lowest rank first with at most one per rank,
+    /// carrying equal ranks upwards.
+    fn add_trees<I: IntoIterator<Item = Tree<T>>>(&mut self, trees: I) {
+        let mut carry: Option<Tree<T>> = None;
+        let mut incoming = trees.into_iter().peekable();
+        let mut rank = 0;
+        while incoming.peek().is_some() || carry.is_some() {
+            if rank == self.trees.len() {
+                self.trees.push(None);
+            }
+            let other = incoming.next_if(|tree| tree.rank() == rank);
+            // Up to three trees of this rank: ours, the other heap's, the
+            // carry. One stays and any pair links into the next carry.
+            let mut here = [self.trees[rank].take(), other, carry.take()];
+            here.sort_by_key(Option::is_none);
+            let [first, second, third] = here;
+            match (first, second) {
+                (Some(a), Some(b)) => {
+                    carry = Some(a.link(b));
+                    self.trees[rank] = third;
+                }
+                (first, _) => self.trees[rank] = first,
+            }
+            rank += 1;
+        }
+    }
+
+    /// Push an element onto the heap.
+    pub fn push(&mut self, item: T) {
+        self.add_trees([Tree {
+            item,
+            children: Vec::new(),
+        }]);
+        self.len += 1;
+    }
+
+    /// The rank of the tree with the smallest root.
+    fn min_rank(&self) -> Option<usize> {
+        self.trees
+            .iter()
+            .enumerate()
+            .filter_map(|(rank, tree)| tree.as_ref().map(|tree| (rank, &tree.item)))
+            .min_by(|a, b| a.1.cmp(b.1))
+            .map(|(rank, _)| rank)
+    }
+
+    /// Get a reference to the smallest element, or `None` if the heap is
+    /// empty.
+    pub fn peek(&self) -> Option<&T> {
+        let rank = self.min_rank()?;
+        self.trees[rank].as_ref().map(|tree| &tree.item)
+    }
+
+    /// Remove and return the smallest element, or `None` if the heap is
+    /// empty.
+    pub fn pop(&mut self) -> Option<T> {
+        let rank = self.min_rank()?;
+        let tree = self.trees[rank].take().expect("min tree");
+        while self.trees.last().is_some_and(Option::is_none) {
+            self.trees.pop();
+        }
+        self.add_trees(tree.children);
+        self.len -= 1;
+        Some(tree.item)
+    }
+
+    /// Move every element of `other` into this heap, in O(log n).
+    pub fn meld(&mut self, other: Self) {
+        self.len += other.len;
+        self.add_trees(other.trees.into_iter().flatten());
+    }
+
+    /// Check the binomial heap invariants, panicking on the first
+    /// violation.
+    ///
+    /// Checks that each tree sits in the slot for its rank, that every
+    /// node's children have ranks `0..k` in order and are no smaller than
+    /// it, so each rank-`k` tree holds `2^k` elements, that the ranks
+    /// present are the set bits of `len`, and that the top slot is in use.
+    pub fn debug_validate(&self) {
+        fn walk<T: Ord>(tree: &Tree<T>) -> usize {
+            let mut size = 1;
+            for (rank, child) in tree.children.iter().enumerate() {
+                assert_eq!(child.rank(), rank, "child out of rank order");
+                assert!(tree.item <= child.item, "heap order violated");
+                size += walk(child);
+            }
+            assert_eq!(size, 1 << tree.rank(), "tree size is not 2^rank");
+            size
+        }
+
+        let mut total = 0;
+        for (rank, tree) in self.trees.iter().enumerate() {
+            if let Some(tree) = tree {
+                assert_eq!(tree.rank(), rank, "tree in the wrong rank slot");
+                total += walk(tree);
+            }
+        }
+        assert_eq!(total, self.len, "len does not match element count");
+        assert!(
+            self.trees.last().map_or(true, Option::is_some),
+            "trailing empty rank slot"
+        );
+    }
+}
+
+impl<T: Ord> Extend<T> for BinomialHeap<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate<T: Ord>(heap: &BinomialHeap<T>) {
+        if cfg!(debug_assertions) {
+            heap.debug_validate();
+        }
+    }
+
+    #[test]
+    fn test_ranks_follow_binary_count() {
+        let mut heap = BinomialHeap::new();
+        for i in 0..13u32 {
+            heap.push(i * 5 % 13);
+            validate(&heap);
+        }
+        // 13 = 0b1101.
+        let ranks: Vec<bool> = heap.trees.iter().map(Option::is_some).collect();
+        assert_eq!(ranks, [true, false, true, true]);
+        assert_eq!(heap.peek(), Some(&0));
+    }
+
+    #[test]
+    fn test_push_pop_sorted() {
+        let mut heap = BinomialHeap::new();
+        heap.extend((0..300u32).map(|i| i * 7919 % 307));
+        let mut previous = None;
+        while let Some(item) = heap.pop() {
+            assert!(previous <= Some(item));
+            previous = Some(item);
+            validate(&heap);
+        }
+        assert!(heap.is_empty());
+        assert_eq!(heap.trees.len(), 0);
+    }
+
+    #[test]
+    fn test_meld_carries() {
+        let mut left = BinomialHeap::new();
+        let mut right = BinomialHeap::new();
+        left.extend([10, 30, 50, 70, 90, 110, 130]);
+        right.extend([20, 40, 60, 80, 100]);
+        left.meld(right);
+        validate(&left);
+        // 7 + 5 = 12 = 0b1100.
+        assert_eq!(left.trees.iter().filter(|tree| tree.is_some()).count(), 2);
+        assert_eq!(left.len(), 12);
+        let drained: Vec<i32> = std::iter::from_fn(|| left.pop()).collect();
+        assert_eq!(
+            drained,
+            (1..=11).map(|i| i * 10).chain([130]).collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,358 @@
+//! Bitset over small non-negative integers, backed by u64 words.
+
+use std::fmt;
+use std::ops::{BitAnd, BitOr, BitXor, Not};
+
+const WORD_BITS: usize = 64;
+
+/// A set of `usize` values stored as one bit each.
+///
+/// Value `i` is bit `i % 64` of word `i / 64`, so membership tests and
+/// updates are a shift and a mask, and union, intersection, difference and
+/// complement work a whole word of 64 values at a time. The set covers the
+/// values below its [`capacity`](Self::capacity): it can be sized once up
+/// front, for a visited set over a graph's vertices, or grow as larger
+/// values are inserted. Space is one bit per value below the largest ever
+/// inserted, so this suits dense sets of small integers; sparse or large
+/// values want a hash set.
+#[derive(Clone, Default)]
+pub struct BitSet {
+    words: Vec<u64>,
+    /// Bits past `capacity` in the last word are kept clear.
+    capacity: usize,
+}
+
+/// Returns the number of words covering `bits` bits.
+fn words_for(bits: usize) -> usize {
+    (bits + WORD_BITS - 1) / WORD_BITS
+}
+
+impl BitSet {
+    /// Create a new empty BitSet.
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Create a new empty BitSet covering the values below `capacity`.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            words: vec![0; words_for(capacity)],
+            capacity,
+        }
+    }
+
+    /// Returns the number of values the set covers without growing.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Cover the values below `capacity`, if it exceeds the current
+    /// capacity.
+    pub fn grow(&mut self, capacity: usize) {
+        if capacity > self.capacity {
+            self.words.resize(words_for(capacity), 0);
+            self.capacity = capacity;
+        }
+    }
+
+    /// Returns the number of values in the set.
+    pub fn count(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Returns true if the set contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&word| word == 0)
+    }
+
+    /// Returns the words backing the set: value `i` is bit `i % 64` of
+    /// word `i / 64`.
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// Check if `value` is in the set.
+    pub fn contains(&self, value: usize) -> bool {
+        self.words
+            .get(value / WORD_BITS)
+            .is_some_and(|word| word >> (value % WORD_BITS) & 1 == 1)
+    }
+
+    /// Set the bit for `value`, growing the set if needed.
+    ///
+    /// Returns true if the value was not already present.
+    pub fn insert(&mut self, value: usize) -> bool {
+        self.grow(value + 1);
+        let word = &mut self.words[value / WORD_BITS];
+        let mask = 1 << (value % WORD_BITS);
+        let absent = *word & mask == 0;
+        *word |= mask;
+        absent
+    }
+
+    /// Clear the bit for `value`.
+    ///
+    /// Returns true if the value was present.
+    pub fn remove(&mut self, value: usize) -> bool {
+        let Some(word) = self.words.get_mut(value / WORD_BITS) else {
+            return false;
+        };
+        let mask = 1 << (value % WORD_BITS);
+        let present = *word & mask != 0;
+        *word &= !mask;
+        present
+    }
+
+    /// Clear all values from the set, keeping its capacity.
+    pub fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
+    /// Add every value of `other` to the set, growing to its capacity.
+    pub fn union_with(&mut self, other: &BitSet) {
+        self.grow(other.capacity);
+        for (word, &theirs) in self.words.iter_mut().zip(&other.words) {
+            *word |= theirs;
+        }
+    }
+
+    /// Keep only the values also in `other`.
+    pub fn intersect_with(&mut self, other: &BitSet) {
+        for (i, word) in self.words.iter_mut().enumerate() {
+            *word &= other.words.get(i).copied().unwrap_or(0);
+        }
+    }
+
+    /// Remove every value of `other` from the set.
+    pub fn difference_with(&mut self, other: &BitSet) {
+        for (word, &theirs) in self.words.iter_mut().zip(&other.words) {
+            *word &= !theirs;
+        }
+    }
+
+    /// Keep the values in exactly one of the two sets, growing to the
+    /// capacity of `other`.
+    pub fn symmetric_difference_with(&mut self, other: &BitSet) {
+        self.grow(other.capacity);
+        for (word, &theirs) in self.words.iter_mut().zip(&other.words) {
+            *word ^= theirs;
+        }
+    }
+
+    /// Flip every bit below the capacity, so the set holds exactly the
+    /// covered values it did not.
+    pub fn complement(&mut self) {
+        for word in &mut self.words {
+            *word = !*word;
+        }
+        self.mask_tail();
+    }
+
+    /// Clear the bits past the capacity in the last word.
+    fn mask_tail(&mut self) {
+        let used = self.capacity % WORD_BITS;
+        if used != 0 {
+            if let Some(last) = self.words.last_mut() {
+                *last &= (1 << used) - 1;
+            }
+        }
+    }
+
+    /// Check if every value in the set is also in `other`.
+    pub fn is_subset(&self, other: &BitSet) -> bool {
+        self.words
+            .iter()
+            .enumerate()
+            .all(|(i, &word)| word & !other.words.get(i).copied().unwrap_or(0) == 0)
+    }
+
+    /// Check if the set shares no value with `other`.
+    pub fn is_disjoint(&self, other: &BitSet) -> bool {
+        self.words
+            .iter()
+            .zip(&other.words)
+            .all(|(&ours, &theirs)| ours & theirs == 0)
+    }
+
+    /// Iterate over the values in the set, in increasing order.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            words: &self.words,
+            index: 0,
+            current: self.words.first().copied().unwrap_or(0),
+        }
+    }
+}
+
+/// Iterator over the values of a [`BitSet`], in increasing order.
+///
+/// Skips a word at a time and jumps to each set bit by counting trailing
+/// zeros.
+#[derive(Debug, Clone)]
+pub struct Iter<'a> {
+    words: &'a [u64],
+    /// Index of the word `current` came from.
+    index: usize,
+    /// The bits of that word not yet yielded.
+    current: u64,
+}
+
+impl Iterator for Iter<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while self.current == 0 {
+            self.index += 1;
+            self.current = *self.words.get(self.index)?;
+        }
+        let bit = self.current.trailing_zeros() as usize;
+        self.current &= self.current - 1;
+        Some(self.index * WORD_BITS + bit)
+    }
+}
+
+impl<'a> IntoIterator for &'a BitSet {
+    type Item = usize;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+impl Extend<usize> for BitSet {
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl FromIterator<usize> for BitSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+/// Sets are equal when they hold the same values, whatever their
+/// capacities.
+impl PartialEq for BitSet {
+    fn eq(&self, other: &Self) -> bool {
+        let (short, long) = if self.words.len() <= other.words.len() {
+            (&self.words, &other.words)
+        } else {
+            (&other.words, &self.words)
+        };
+        long[..short.len()] == short[..] && long[short.len()..].iter().all(|&word| word == 0)
+    }
+}
+
+impl Eq for BitSet {}
+
+impl fmt::Debug for BitSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl BitOr for &BitSet {
+    type Output = BitSet;
+
+    fn bitor(self, other: &BitSet) -> BitSet {
+        let mut set = self.clone();
+        set.union_with(other);
+        set
+    }
+}
+
+impl BitAnd for &BitSet {
+    type Output = BitSet;
+
+    fn bitand(self, other: &BitSet) -> BitSet {
+        let mut set = self.clone();
+        set.intersect_with(other);
+        set
+    }
+}
+
+impl BitXor for &BitSet {
+    type Output = BitSet;
+
+    fn bitxor(self, other: &BitSet) -> BitSet {
+        let mut set = self.clone();
+        set.symmetric_difference_with(other);
+        set
+    }
+}
+
+/// The complement within the set's capacity.
+impl Not for &BitSet {
+    type Output = BitSet;
+
+    fn not(self) -> BitSet {
+        let mut set = self.clone();
+        set.complement();
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_remove_contains() {
+        let mut set = BitSet::with_capacity(100);
+        assert!(set.insert(3));
+        assert!(!set.insert(3));
+        assert!(set.insert(64));
+        assert!(set.contains(3) && set.contains(64) && !set.contains(4));
+        assert!(!set.contains(1_000));
+        assert!(set.remove(3));
+        assert!(!set.remove(3));
+        assert!(!set.remove(1_000));
+        assert_eq!(set.count(), 1);
+        // Growing past the capacity.
+        assert!(set.insert(200));
+        assert_eq!(set.capacity(), 201);
+        assert_eq!(set.iter().collect::<Vec<_>>(), [64, 200]);
+        set.clear();
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a: BitSet = [1, 2, 3, 70].into_iter().collect();
+        let b: BitSet = [2, 3, 4, 130].into_iter().collect();
+        assert_eq!((&a | &b).iter().collect::<Vec<_>>(), [1, 2, 3, 4, 70, 130]);
+        assert_eq!((&a & &b).iter().collect::<Vec<_>>(), [2, 3]);
+        assert_eq!((&a ^ &b).iter().collect::<Vec<_>>(), [1, 4, 70, 130]);
+        let mut difference = a.clone();
+        difference.difference_with(&b);
+        assert_eq!(difference.iter().collect::<Vec<_>>(), [1, 70]);
+        assert!((&a & &b).is_subset(&a));
+        assert!(!a.is_subset(&b));
+        assert!(difference.is_disjoint(&b));
+    }
+
+    #[test]
+    fn test_complement_within_capacity() {
+        let mut set = BitSet::with_capacity(70);
+        set.insert(0);
+        set.insert(69);
+        let complement = !&set;
+        assert_eq!(complement.count(), 68);
+        assert!(!complement.contains(0) && !complement.contains(69));
+        assert!(!complement.contains(70));
+        assert_eq!(!&complement, set);
+        // Equality ignores capacity.
+        let mut wide = BitSet::with_capacity(1_000);
+        wide.extend([0, 69]);
+        assert_eq!(wide, set);
+    }
+}
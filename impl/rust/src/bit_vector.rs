@@ -0,0 +1,326 @@
+//! Succinct bitvector with rank and select.
+
+use crate::BitSet;
+use std::fmt;
+
+const WORD_BITS: usize = 64;
+/// Words per block; blocks store their rank relative to their superblock.
+const BLOCK_WORDS: usize = 8;
+/// Blocks per superblock; superblocks store their absolute rank.
+const SUPERBLOCK_BLOCKS: usize = 128;
+const BLOCK_BITS: usize = BLOCK_WORDS * WORD_BITS;
+const SUPERBLOCK_BITS: usize = SUPERBLOCK_BLOCKS * BLOCK_BITS;
+
+/// An immutable bit sequence answering rank in O(1) and select in
+/// O(log n), with about 3% extra space.
+///
+/// Rank is sampled at two levels. Every superblock of 65,536 bits stores
+/// the number of ones before it as a `u64`, and every block of 512 bits
+/// stores the ones between its superblock's start and itself as a `u16`,
+/// which is enough since a superblock holds fewer than 2^16 bits before its
+/// last block. A rank query adds the two samples and the popcounts of at
+/// most eight words. Select binary searches the superblock samples, then
+/// the block samples within one superblock, then scans at most eight
+/// words, so it uses the rank samples and no index of its own. Bits past
+/// `len` in the last word are kept zero.
+#[derive(Clone)]
+pub struct BitVector {
+    words: Vec<u64>,
+    len: usize,
+    ones: usize,
+    /// Ones before each superblock.
+    superblocks: Vec<u64>,
+    /// Ones before each block, counted from the start of its superblock.
+    blocks: Vec<u16>,
+}
+
+/// Returns the first index in `lo..hi` for which `pred` is false, given
+/// that `pred` is true for a prefix of the range.
+fn partition_point(mut lo: usize, mut hi: usize, pred: impl Fn(usize) -> bool) -> usize {
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if pred(mid) {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
+
+/// Returns the position of the `k`th one bit of `word`, counting from 0.
+fn select_in_word(mut word: u64, k: usize) -> usize {
+    for _ in 0..k {
+        word &= word - 1;
+    }
+    word.trailing_zeros() as usize
+}
+
+impl BitVector {
+    /// Create a BitVector of `len` bits stored little-endian in `words`:
+    /// bit `i` is bit `i % 64` of `words[i / 64]`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `words` does not hold exactly the words `len` bits need.
+    pub fn from_words(mut words: Vec<u64>, len: usize) -> Self {
+        let needed = (len + WORD_BITS - 1) / WORD_BITS;
+        assert_eq!(
+            words.len(),
+            needed,
+            "{} bits need {} words, got {}",
+            len,
+            needed,
+            words.len()
+        );
+        if len % WORD_BITS != 0 {
+            words[needed - 1] &= (1 << (len % WORD_BITS)) - 1;
+        }
+        let block_count = (words.len() + BLOCK_WORDS - 1) / BLOCK_WORDS;
+        let mut superblocks = Vec::with_capacity(block_count / SUPERBLOCK_BLOCKS + 1);
+        let mut blocks = Vec::with_capacity(block_count);
+        let (mut ones, mut superblock_start) = (0, 0);
+        for (block, chunk) in words.chunks(BLOCK_WORDS).enumerate() {
+            if block % SUPERBLOCK_BLOCKS == 0 {
+                superblocks.push(ones as u64);
+                superblock_start = ones;
+            }
+            blocks.push((ones - superblock_start) as u16);
+            ones += chunk
+                .iter()
+                .map(|word| word.count_ones() as usize)
+                .sum::<usize>();
+        }
+        Self {
+            words,
+            len,
+            ones,
+            superblocks,
+            blocks,
+        }
+    }
+
+    /// Returns the number of bits.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the bitvector has no bits.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of one bits.
+    pub fn count_ones(&self) -> usize {
+        self.ones
+    }
+
+    /// Returns the number of zero bits.
+    pub fn count_zeros(&self) -> usize {
+        self.len - self.ones
+    }
+
+    /// Returns the bits packed 64 to a word, as passed to
+    /// [`from_words`](Self::from_words).
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// Returns the heap size of the bits and the rank samples in bytes.
+    pub fn size_in_bytes(&self) -> usize {
+        self.words.len() * 8 + self.superblocks.len() * 8 + self.blocks.len() * 2
+    }
+
+    /// Returns bit `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn get(&self, index: usize) -> bool {
+        assert!(
+            index < self.len,
+            "index {} out of bounds for length {}",
+            index,
+            self.len
+        );
+        self.words[index / WORD_BITS] >> (index % WORD_BITS) & 1 == 1
+    }
+
+    /// Returns the number of one bits before `index`, in O(1).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length.
+    pub fn rank1(&self, index: usize) -> usize {
+        assert!(
+            index <= self.len,
+            "rank index {} out of bounds for length {}",
+            index,
+            self.len
+        );
+        if index == self.len {
+            return self.ones;
+        }
+        let word = index / WORD_BITS;
+        let block = word / BLOCK_WORDS;
+        let mut rank = self.superblocks[block / SUPERBLOCK_BLOCKS] as usize
+            + self.blocks[block] as usize
+            + self.words[block * BLOCK_WORDS..word]
+                .iter()
+                .map(|word| word.count_ones() as usize)
+                .sum::<usize>();
+        let offset = index % WORD_BITS;
+        if offset != 0 {
+            rank += (self.words[word] & ((1 << offset) - 1)).count_ones() as usize;
+        }
+        rank
+    }
+
+    /// Returns the number of zero bits before `index`, in O(1).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length.
+    pub fn rank0(&self, index: usize) -> usize {
+        index - self.rank1(index)
+    }
+
+    /// Returns the position of the `k`th one bit, counting from 0, or
+    /// `None` if there are not that many, in O(log n).
+    pub fn select1(&self, k: usize) -> Option<usize> {
+        if k >= self.ones {
+            return None;
+        }
+        Some(self.select(k, |ones, _| ones, |word| word))
+    }
+
+    /// Returns the position of the `k`th zero bit, counting from 0, or
+    /// `None` if there are not that many, in O(log n).
+    pub fn select0(&self, k: usize) -> Option<usize> {
+        if k >= self.count_zeros() {
+            return None;
+        }
+        // Padding past `len` is zero but lies after every real zero, so it
+        // never reaches a valid `k`.
+        Some(self.select(k, |ones, bits| bits - ones, |word| !word))
+    }
+
+    /// Finds the `k`th set bit of the words mapped by `bit`, given `count`
+    /// turning the ones before a position into the wanted bits before it.
+    fn select(
+        &self,
+        mut k: usize,
+        count: impl Fn(usize, usize) -> usize,
+        bit: impl Fn(u64) -> u64,
+    ) -> usize {
+        let before_superblock = |s: usize| count(self.superblocks[s] as usize, s * SUPERBLOCK_BITS);
+        let superblock =
+            partition_point(1, self.superblocks.len(), |s| before_superblock(s) <= k) - 1;
+        let superblock_ones = self.superblocks[superblock] as usize;
+        let first_block = superblock * SUPERBLOCK_BLOCKS;
+        let last_block = self.blocks.len().min(first_block + SUPERBLOCK_BLOCKS);
+        let block = partition_point(first_block + 1, last_block, |b| {
+            count(superblock_ones + self.blocks[b] as usize, b * BLOCK_BITS) <= k
+        }) - 1;
+        k -= count(
+            superblock_ones + self.blocks[block] as usize,
+            block * BLOCK_BITS,
+        );
+        let mut word = block * BLOCK_WORDS;
+        loop {
+            let bits = bit(self.words[word]);
+            let found = bits.count_ones() as usize;
+            if k < found {
+                return word * WORD_BITS + select_in_word(bits, k);
+            }
+            k -= found;
+            word += 1;
+        }
+    }
+}
+
+impl FromIterator<bool> for BitVector {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut words = Vec::new();
+        let mut len = 0;
+        for bit in iter {
+            if len % WORD_BITS == 0 {
+                words.push(0);
+            }
+            if bit {
+                words[len / WORD_BITS] |= 1 << (len % WORD_BITS);
+            }
+            len += 1;
+        }
+        Self::from_words(words, len)
+    }
+}
+
+/// The bits of a [`BitSet`] below its capacity.
+impl From<&BitSet> for BitVector {
+    fn from(set: &BitSet) -> Self {
+        Self::from_words(set.words().to_vec(), set.capacity())
+    }
+}
+
+impl fmt::Debug for BitVector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BitVector")
+            .field("len", &self.len)
+            .field("ones", &self.ones)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rank_and_select_small() {
+        let bits: BitVector = [true, false, true, true, false, false, true]
+            .into_iter()
+            .collect();
+        assert_eq!(bits.len(), 7);
+        assert_eq!(bits.count_ones(), 4);
+        assert_eq!(
+            (0..=7).map(|i| bits.rank1(i)).collect::<Vec<_>>(),
+            [0, 1, 1, 2, 3, 3, 3, 4]
+        );
+        assert_eq!(bits.rank0(7), 3);
+        assert_eq!(
+            (0..5).map(|k| bits.select1(k)).collect::<Vec<_>>(),
+            [Some(0), Some(2), Some(3), Some(6), None]
+        );
+        assert_eq!(
+            (0..4).map(|k| bits.select0(k)).collect::<Vec<_>>(),
+            [Some(1), Some(4), Some(5), None]
+        );
+        assert!(bits.get(3) && !bits.get(4));
+    }
+
+    #[test]
+    fn test_padding_masked() {
+        let bits = BitVector::from_words(vec![u64::MAX], 10);
+        assert_eq!(bits.count_ones(), 10);
+        assert_eq!(bits.words(), [0x3ff]);
+        assert_eq!(bits.select0(0), None);
+        let empty = BitVector::from_words(Vec::new(), 0);
+        assert_eq!((empty.rank1(0), empty.select1(0)), (0, None));
+    }
+
+    #[test]
+    fn test_across_superblocks() {
+        // Every third bit set, across three superblocks.
+        let len = 3 * SUPERBLOCK_BITS + 100;
+        let bits: BitVector = (0..len).map(|i| i % 3 == 0).collect();
+        for i in [0, 511, 512, SUPERBLOCK_BITS, SUPERBLOCK_BITS + 1, len] {
+            assert_eq!(bits.rank1(i), (i + 2) / 3);
+        }
+        for k in [0, 170, 171, 21845, 21846, bits.count_ones() - 2] {
+            assert_eq!(bits.select1(k), Some(3 * k));
+            assert_eq!(bits.select0(2 * k), Some(3 * k + 1));
+        }
+        assert!(bits.size_in_bytes() < len / 8 * 104 / 100);
+    }
+}
@@ -0,0 +1,682 @@
+//! B+ tree ordered map with linked leaves and bulk loading.
+
+use crate::btree::{before_start, check_range, past_end};
+use std::fmt;
+use std::ops::RangeBounds;
+
+const DEFAULT_FANOUT: usize = 16;
+/// Index of a missing leaf: the end of the leaf chain.
+const NIL: usize = usize::MAX;
+
+#[derive(Debug, Clone)]
+struct Leaf<K, V> {
+    keys: Vec<K>,
+    values: Vec<V>,
+    /// The leaf holding the next keys in order.
+    next: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Internal<K> {
+    /// Separators: `keys[i]` is at most every key under `children[i + 1]`
+    /// and greater than every key under `children[i]`.
+    keys: Vec<K>,
+    children: Vec<usize>,
+}
+
+#[derive(Debug, Clone)]
+enum Node<K, V> {
+    Leaf(Leaf<K, V>),
+    Internal(Internal<K>),
+}
+
+impl<K, V> Node<K, V> {
+    fn empty_leaf() -> Self {
+        Node::Leaf(Leaf {
+            keys: Vec::new(),
+            values: Vec::new(),
+            next: NIL,
+        })
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Node::Leaf(leaf) => leaf.keys.len(),
+            Node::Internal(node) => node.keys.len(),
+        }
+    }
+
+    fn leaf(&self) -> &Leaf<K, V> {
+        match self {
+            Node::Leaf(leaf) => leaf,
+            Node::Internal(_) => unreachable!("expected a leaf"),
+        }
+    }
+
+    fn leaf_mut(&mut self) -> &mut Leaf<K, V> {
+        match self {
+            Node::Leaf(leaf) => leaf,
+            Node::Internal(_) => unreachable!("expected a leaf"),
+        }
+    }
+
+    fn internal_mut(&mut self) -> &mut Internal<K> {
+        match self {
+            Node::Internal(node) => node,
+            Node::Leaf(_) => unreachable!("expected an internal node"),
+        }
+    }
+}
+
+/// An ordered map stored in a B+ tree.
+///
+/// Unlike [`BTreeMap`](crate::BTreeMap), internal nodes hold only copies of
+/// keys to route searches, and every entry lives in a leaf. The leaves are
+/// chained in key order, so a `range` scan finds its first leaf with one
+/// descent and then walks the chain without going back up the tree, the way
+/// database indexes serve range queries. Sorted input can be loaded bottom
+/// up with [`from_sorted`](BPlusTreeMap::from_sorted) in O(n). Nodes live in
+/// an arena and refer to each other by index.
+#[derive(Clone)]
+pub struct BPlusTreeMap<K, V> {
+    nodes: Vec<Node<K, V>>,
+    /// Arena slots freed by merges.
+    free: Vec<usize>,
+    root: usize,
+    len: usize,
+    fanout: usize,
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for BPlusTreeMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V> Default for BPlusTreeMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> BPlusTreeMap<K, V> {
+    /// Create a new empty BPlusTreeMap.
+    pub fn new() -> Self {
+        Self::with_fanout(DEFAULT_FANOUT)
+    }
+
+    /// Create a new empty BPlusTreeMap whose nodes have at most `fanout`
+    /// children, and whose leaves hold at most `fanout - 1` entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fanout` is less than 3.
+    pub fn with_fanout(fanout: usize) -> Self {
+        assert!(fanout >= 3, "B+ tree fanout must be at least 3");
+        Self {
+            nodes: vec![Node::empty_leaf()],
+            free: Vec::new(),
+            root: 0,
+            len: 0,
+            fanout,
+        }
+    }
+
+    /// Returns the maximum number of children per node.
+    pub fn fanout(&self) -> usize {
+        self.fanout
+    }
+
+    /// Returns the number of elements in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the map contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn max_keys(&self) -> usize {
+        self.fanout - 1
+    }
+
+    /// The fewest keys a node other than the root may hold.
+    fn min_keys(&self) -> usize {
+        (self.fanout + 1) / 2 - 1
+    }
+
+    /// Clear all entries from the map.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.nodes.push(Node::empty_leaf());
+        self.free.clear();
+        self.root = 0;
+        self.len = 0;
+    }
+
+    fn alloc(&mut self, node: Node<K, V>) -> usize {
+        match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = node;
+                index
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    fn release(&mut self, index: usize) -> Node<K, V> {
+        self.free.push(index);
+        std::mem::replace(&mut self.nodes[index], Node::empty_leaf())
+    }
+
+    /// Mutable references to two distinct nodes.
+    fn pair_mut(&mut self, a: usize, b: usize) -> (&mut Node<K, V>, &mut Node<K, V>) {
+        debug_assert_ne!(a, b);
+        if a < b {
+            let (low, high) = self.nodes.split_at_mut(b);
+            (&mut low[a], &mut high[0])
+        } else {
+            let (low, high) = self.nodes.split_at_mut(a);
+            (&mut high[0], &mut low[b])
+        }
+    }
+
+    /// Skip past the end of exhausted leaves to the next real position.
+    fn normalize(&self, mut leaf: usize, mut index: usize) -> (usize, usize) {
+        while leaf != NIL && index == self.nodes[leaf].leaf().keys.len() {
+            leaf = self.nodes[leaf].leaf().next;
+            index = 0;
+        }
+        (leaf, index)
+    }
+
+    /// The position of the first entry whose key does not satisfy
+    /// `is_before`, which must hold for a prefix of the keys in order.
+    fn position<F: Fn(&K) -> bool>(&self, is_before: F) -> (usize, usize) {
+        let mut index = self.root;
+        loop {
+            match &self.nodes[index] {
+                Node::Internal(node) => {
+                    index = node.children[node.keys.partition_point(&is_before)];
+                }
+                Node::Leaf(leaf) => {
+                    return self.normalize(index, leaf.keys.partition_point(&is_before));
+                }
+            }
+        }
+    }
+}
+
+impl<K: Ord + Clone, V> BPlusTreeMap<K, V> {
+    /// Build a map from entries sorted by strictly increasing key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the keys are not strictly increasing.
+    pub fn from_sorted<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self::from_sorted_with_fanout(DEFAULT_FANOUT, iter)
+    }
+
+    /// Build a map with the given fanout from entries sorted by strictly
+    /// increasing key.
+    ///
+    /// Fills the leaves left to right in one pass and then builds each
+    /// internal level over the one below, spreading entries evenly so every
+    /// node is at least half full. Takes O(n) time, against O(n log n) for
+    /// repeated inserts.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fanout` is less than 3 or the keys are not strictly
+    /// increasing.
+    pub fn from_sorted_with_fanout<I>(fanout: usize, iter: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let mut map = Self::with_fanout(fanout);
+        let (keys, values): (Vec<K>, Vec<V>) = iter.into_iter().unzip();
+        assert!(
+            keys.windows(2).all(|pair| pair[0] < pair[1]),
+            "bulk load keys must be strictly increasing"
+        );
+        map.len = keys.len();
+        if keys.is_empty() {
+            return map;
+        }
+        map.nodes.clear();
+
+        // Each level as (node index, smallest key under it).
+        let mut level: Vec<(usize, K)> = Vec::new();
+        let leaf_count = (keys.len() + map.max_keys() - 1) / map.max_keys();
+        let mut keys = keys.into_iter();
+        let mut values = values.into_iter();
+        for (i, size) in even_chunks(map.len, leaf_count).enumerate() {
+            let keys: Vec<K> = keys.by_ref().take(size).collect();
+            let values = values.by_ref().take(size).collect();
+            let next = if i + 1 < leaf_count { i + 1 } else { NIL };
+            level.push((i, keys[0].clone()));
+            map.nodes.push(Node::Leaf(Leaf { keys, values, next }));
+        }
+        while level.len() > 1 {
+            let parent_count = (level.len() + fanout - 1) / fanout;
+            let mut children = level.into_iter();
+            level = Vec::with_capacity(parent_count);
+            for size in even_chunks(children.len(), parent_count) {
+                let (indices, mut keys): (Vec<usize>, Vec<K>) =
+                    children.by_ref().take(size).unzip();
+                // The first child's smallest key bounds the parent instead.
+                let min_key = keys.remove(0);
+                let index = map.nodes.len();
+                map.nodes.push(Node::Internal(Internal {
+                    keys,
+                    children: indices,
+                }));
+                level.push((index, min_key));
+            }
+        }
+        map.root = level[0].0;
+        map
+    }
+
+    /// Insert a key-value pair into the map.
+    ///
+    /// Returns the previous value if the key existed.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let (old, split) = self.insert_at(self.root, key, value);
+        if let Some((separator, right)) = split {
+            self.root = self.alloc(Node::Internal(Internal {
+                keys: vec![separator],
+                children: vec![self.root, right],
+            }));
+        }
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    /// Insert below `index`, returning the old value and, if the node split,
+    /// the separator and index of its new right sibling.
+    fn insert_at(&mut self, index: usize, key: K, value: V) -> (Option<V>, Option<(K, usize)>) {
+        let max_keys = self.max_keys();
+        match &mut self.nodes[index] {
+            Node::Leaf(leaf) => {
+                match leaf.keys.binary_search(&key) {
+                    Ok(i) => return (Some(std::mem::replace(&mut leaf.values[i], value)), None),
+                    Err(i) => {
+                        leaf.keys.insert(i, key);
+                        leaf.values.insert(i, value);
+                    }
+                }
+                if leaf.keys.len() <= max_keys {
+                    return (None, None);
+                }
+                let mid = leaf.keys.len() / 2;
+                let right = Leaf {
+                    keys: leaf.keys.split_off(mid),
+                    values: leaf.values.split_off(mid),
+                    next: leaf.next,
+                };
+                let separator = right.keys[0].clone();
+                let right = self.alloc(Node::Leaf(right));
+                self.nodes[index].leaf_mut().next = right;
+                (None, Some((separator, right)))
+            }
+            Node::Internal(node) => {
+                let i = node.keys.partition_point(|separator| *separator <= key);
+                let child = node.children[i];
+                let (old, split) = self.insert_at(child, key, value);
+                let Some((separator, right)) = split else {
+                    return (old, None);
+                };
+                let node = self.nodes[index].internal_mut();
+                node.keys.insert(i, separator);
+                node.children.insert(i + 1, right);
+                if node.keys.len() <= max_keys {
+                    return (old, None);
+                }
+                // The middle separator moves up rather than being copied.
+                let mid = node.keys.len() / 2;
+                let right = Internal {
+                    keys: node.keys.split_off(mid + 1),
+                    children: node.children.split_off(mid + 1),
+                };
+                let separator = node.keys.pop().expect("split node has a middle key");
+                let right = self.alloc(Node::Internal(right));
+                (old, Some((separator, right)))
+            }
+        }
+    }
+
+    /// Remove a key-value pair from the map.
+    ///
+    /// Returns the removed value if the key existed.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let removed = self.remove_at(self.root, key)?;
+        if let Node::Internal(root) = &self.nodes[self.root] {
+            if root.keys.is_empty() {
+                let child = root.children[0];
+                self.release(self.root);
+                self.root = child;
+            }
+        }
+        self.len -= 1;
+        Some(removed)
+    }
+
+    fn remove_at(&mut self, index: usize, key: &K) -> Option<V> {
+        match &mut self.nodes[index] {
+            Node::Leaf(leaf) => {
+                let i = leaf.keys.binary_search(key).ok()?;
+                leaf.keys.remove(i);
+                Some(leaf.values.remove(i))
+            }
+            Node::Internal(node) => {
+                let i = node.keys.partition_point(|separator| separator <= key);
+                let child = node.children[i];
+                let removed = self.remove_at(child, key)?;
+                if self.nodes[child].len() < self.min_keys() {
+                    self.rebalance(index, i);
+                }
+                Some(removed)
+            }
+        }
+    }
+
+    /// Refill the underfull child `i` of `parent` by borrowing from a
+    /// sibling, or merge it with one.
+    fn rebalance(&mut self, parent: usize, i: usize) {
+        let min_keys = self.min_keys();
+        let Node::Internal(node) = &self.nodes[parent] else {
+            unreachable!("expected an internal node");
+        };
+        let child = node.children[i];
+        let left = i.checked_sub(1).map(|i| node.children[i]);
+        let right = node.children.get(i + 1).copied();
+
+        if let Some(left) = left.filter(|&left| self.nodes[left].len() > min_keys) {
+            self.borrow_from_left(parent, i, left, child);
+        } else if let Some(right) = right.filter(|&right| self.nodes[right].len() > min_keys) {
+            self.borrow_from_right(parent, i, child, right);
+        } else if left.is_some() {
+            self.merge_children(parent, i - 1);
+        } else {
+            self.merge_children(parent, i);
+        }
+    }
+
+    /// Move the last entry of `left` to the front of its sibling `child`,
+    /// the child at `i` in `parent`.
+    fn borrow_from_left(&mut self, parent: usize, i: usize, left: usize, child: usize) {
+        match self.pair_mut(left, child) {
+            (Node::Leaf(from), Node::Leaf(to)) => {
+                to.keys
+                    .insert(0, from.keys.pop().expect("sibling has spare keys"));
+                to.values
+                    .insert(0, from.values.pop().expect("sibling has spare keys"));
+                let separator = to.keys[0].clone();
+                self.nodes[parent].internal_mut().keys[i - 1] = separator;
+            }
+            (Node::Internal(from), Node::Internal(to)) => {
+                // The sibling's last key rotates down through the parent.
+                to.children
+                    .insert(0, from.children.pop().expect("sibling has spare keys"));
+                let up = from.keys.pop().expect("sibling has spare keys");
+                let separator = &mut self.nodes[parent].internal_mut().keys[i - 1];
+                let down = std::mem::replace(separator, up);
+                self.nodes[child].internal_mut().keys.insert(0, down);
+            }
+            _ => unreachable!("siblings at different depths"),
+        }
+    }
+
+    /// Move the first entry of `right` to the back of its sibling `child`,
+    /// the child at `i` in `parent`.
+    fn borrow_from_right(&mut self, parent: usize, i: usize, child: usize, right: usize) {
+        match self.pair_mut(child, right) {
+            (Node::Leaf(to), Node::Leaf(from)) => {
+                to.keys.push(from.keys.remove(0));
+                to.values.push(from.values.remove(0));
+                let separator = from.keys[0].clone();
+                self.nodes[parent].internal_mut().keys[i] = separator;
+            }
+            (Node::Internal(to), Node::Internal(from)) => {
+                to.children.push(from.children.remove(0));
+                let up = from.keys.remove(0);
+                let separator = &mut self.nodes[parent].internal_mut().keys[i];
+                let down = std::mem::replace(separator, up);
+                self.nodes[child].internal_mut().keys.push(down);
+            }
+            _ => unreachable!("siblings at different depths"),
+        }
+    }
+
+    /// Merge child `i + 1` of `parent` into child `i`.
+    fn merge_children(&mut self, parent: usize, i: usize) {
+        let node = self.nodes[parent].internal_mut();
+        let separator = node.keys.remove(i);
+        let left = node.children[i];
+        let right = node.children.remove(i + 1);
+        let right = self.release(right);
+        match (&mut self.nodes[left], right) {
+            (Node::Leaf(left), Node::Leaf(right)) => {
+                left.keys.extend(right.keys);
+                left.values.extend(right.values);
+                left.next = right.next;
+            }
+            (Node::Internal(left), Node::Internal(right)) => {
+                left.keys.push(separator);
+                left.keys.extend(right.keys);
+                left.children.extend(right.children);
+            }
+            _ => unreachable!("siblings at different depths"),
+        }
+    }
+}
+
+impl<K: Ord, V> BPlusTreeMap<K, V> {
+    /// Get a reference to the value associated with the key.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let mut index = self.root;
+        loop {
+            match &self.nodes[index] {
+                Node::Internal(node) => {
+                    index = node.children[node.keys.partition_point(|separator| separator <= key)];
+                }
+                Node::Leaf(leaf) => {
+                    let i = leaf.keys.binary_search(key).ok()?;
+                    return Some(&leaf.values[i]);
+                }
+            }
+        }
+    }
+
+    /// Check if the map contains the given key.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Iterate over all key-value pairs in key order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.range(..)
+    }
+
+    /// Iterate over the key-value pairs whose keys fall in `range`, in key
+    /// order.
+    ///
+    /// Descends once to each end of the range, then follows the leaf chain.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range starts after it ends, or starts and ends at the
+    /// same excluded key.
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Iter<'_, K, V> {
+        check_range(&range);
+        let (leaf, index) = self.position(|key| before_start(key, range.start_bound()));
+        Iter {
+            map: self,
+            leaf,
+            index,
+            end: self.position(|key| !past_end(key, range.end_bound())),
+        }
+    }
+
+    /// Check the B+ tree invariants, panicking on the first violation.
+    ///
+    /// Checks that keys are strictly increasing and lie between the
+    /// separators above them, that every node but the root holds between
+    /// the minimum and maximum number of keys, that all leaves sit at the
+    /// same depth, and that the leaf chain visits every entry in order.
+    pub fn debug_validate(&self) {
+        fn walk<K: Ord, V>(
+            map: &BPlusTreeMap<K, V>,
+            index: usize,
+            bounds: (Option<&K>, Option<&K>),
+            depth: usize,
+            leaf_depth: &mut Option<usize>,
+        ) {
+            let node = &map.nodes[index];
+            assert!(node.len() <= map.max_keys(), "node has too many keys");
+            assert!(
+                index == map.root || node.len() >= map.min_keys(),
+                "node has too few keys"
+            );
+            let keys = match node {
+                Node::Leaf(leaf) => &leaf.keys,
+                Node::Internal(node) => &node.keys,
+            };
+            assert!(
+                keys.windows(2).all(|pair| pair[0] < pair[1]),
+                "keys out of order"
+            );
+            let (lower, upper) = bounds;
+            if let (Some(lower), Some(first)) = (lower, keys.first()) {
+                assert!(first >= lower, "key below separator");
+            }
+            if let (Some(upper), Some(last)) = (upper, keys.last()) {
+                assert!(last < upper, "key above separator");
+            }
+            match node {
+                Node::Leaf(leaf) => {
+                    assert_eq!(leaf.keys.len(), leaf.values.len(), "keys and values differ");
+                    assert_eq!(
+                        *leaf_depth.get_or_insert(depth),
+                        depth,
+                        "leaves at different depths"
+                    );
+                }
+                Node::Internal(node) => {
+                    assert_eq!(
+                        node.children.len(),
+                        node.keys.len() + 1,
+                        "wrong child count"
+                    );
+                    for (i, &child) in node.children.iter().enumerate() {
+                        let lower = if i == 0 {
+                            lower
+                        } else {
+                            Some(&node.keys[i - 1])
+                        };
+                        let upper = node.keys.get(i).or(upper);
+                        walk(map, child, (lower, upper), depth + 1, leaf_depth);
+                    }
+                }
+            }
+        }
+
+        walk(self, self.root, (None, None), 0, &mut None);
+
+        let mut leaf = self.root;
+        while let Node::Internal(node) = &self.nodes[leaf] {
+            leaf = node.children[0];
+        }
+        let mut count = 0;
+        let mut previous: Option<&K> = None;
+        while leaf != NIL {
+            for key in &self.nodes[leaf].leaf().keys {
+                assert!(
+                    previous.map_or(true, |previous| previous < key),
+                    "leaf chain out of order"
+                );
+                previous = Some(key);
+                count += 1;
+            }
+            leaf = self.nodes[leaf].leaf().next;
+        }
+        assert_eq!(count, self.len, "len does not match entry count");
+    }
+}
+
+/// An iterator over a key range of a [`BPlusTreeMap`], in key order.
+pub struct Iter<'a, K, V> {
+    map: &'a BPlusTreeMap<K, V>,
+    /// The leaf and index of the next entry, or `NIL` when exhausted.
+    leaf: usize,
+    index: usize,
+    /// The position of the first entry past the range.
+    end: (usize, usize),
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.leaf == NIL || (self.leaf, self.index) == self.end {
+            return None;
+        }
+        let leaf = self.map.nodes[self.leaf].leaf();
+        let entry = (&leaf.keys[self.index], &leaf.values[self.index]);
+        (self.leaf, self.index) = self.map.normalize(self.leaf, self.index + 1);
+        Some(entry)
+    }
+}
+
+/// Sizes of `parts` chunks covering `total` items, differing by at most one.
+pub(crate) fn even_chunks(total: usize, parts: usize) -> impl Iterator<Item = usize> {
+    (0..parts).map(move |i| total / parts + usize::from(i < total % parts))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_get_remove_small_fanout() {
+        let mut map = BPlusTreeMap::with_fanout(3);
+        for i in (0..500).rev() {
+            assert_eq!(map.insert(i, i * 10), None);
+            map.debug_validate();
+        }
+        assert_eq!(map.insert(7, 0), Some(70));
+        for i in (0..500).step_by(2) {
+            assert_eq!(map.remove(&i), Some(i * 10));
+            map.debug_validate();
+        }
+        assert_eq!(map.remove(&0), None);
+        assert_eq!(map.len(), 250);
+        assert!(map.iter().map(|(k, _)| *k).eq((1..500).step_by(2)));
+    }
+
+    #[test]
+    fn test_from_sorted_matches_inserts() {
+        for fanout in [3, 4, 16] {
+            for n in [0, 1, 15, 16, 17, 1000] {
+                let map = BPlusTreeMap::from_sorted_with_fanout(fanout, (0..n).map(|i| (i, i)));
+                map.debug_validate();
+                assert_eq!(map.len(), n);
+                assert!(map.iter().map(|(k, _)| *k).eq(0..n));
+                assert_eq!(map.range(10..20).count(), n.clamp(10, 20) - 10);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "bulk load keys must be strictly increasing")]
+    fn test_from_sorted_rejects_unsorted() {
+        BPlusTreeMap::from_sorted([(2, ()), (1, ())]);
+    }
+}
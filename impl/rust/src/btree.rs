@@ -0,0 +1,515 @@
+//! B-tree ordered map with a configurable fanout.
+
+use std::fmt;
+use std::ops::{Bound, RangeBounds};
+
+const DEFAULT_FANOUT: usize = 16;
+
+#[derive(Debug, Clone)]
+struct Node<K, V> {
+    keys: Vec<K>,
+    values: Vec<V>,
+    /// Empty for a leaf, otherwise one more than `keys`.
+    children: Vec<Node<K, V>>,
+}
+
+impl<K, V> Node<K, V> {
+    fn leaf() -> Self {
+        Self {
+            keys: Vec::new(),
+            values: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+}
+
+/// Returns true if `key` comes before a range starting at `start`.
+pub(crate) fn before_start<K: Ord>(key: &K, start: Bound<&K>) -> bool {
+    match start {
+        Bound::Included(start) => key < start,
+        Bound::Excluded(start) => key <= start,
+        Bound::Unbounded => false,
+    }
+}
+
+/// Returns true if `key` comes after a range ending at `end`.
+pub(crate) fn past_end<K: Ord>(key: &K, end: Bound<&K>) -> bool {
+    match end {
+        Bound::Included(end) => key > end,
+        Bound::Excluded(end) => key >= end,
+        Bound::Unbounded => false,
+    }
+}
+
+/// Panics if `range` starts after it ends, as `std`'s ordered maps do.
+pub(crate) fn check_range<K: Ord, R: RangeBounds<K>>(range: &R) {
+    match (range.start_bound(), range.end_bound()) {
+        (Bound::Excluded(start), Bound::Excluded(end)) if start == end => {
+            panic!("range start and end are equal and excluded")
+        }
+        (
+            Bound::Included(start) | Bound::Excluded(start),
+            Bound::Included(end) | Bound::Excluded(end),
+        ) if start > end => panic!("range start is greater than range end"),
+        _ => {}
+    }
+}
+
+impl<K: Ord, V> Node<K, V> {
+    /// Insert into this subtree. Returns the old value, and the median and
+    /// right half if this node overflowed and split.
+    fn insert(&mut self, key: K, value: V, fanout: usize) -> (Option<V>, Option<(K, V, Self)>) {
+        let index = match self.keys.binary_search(&key) {
+            Ok(index) => {
+                return (
+                    Some(std::mem::replace(&mut self.values[index], value)),
+                    None,
+                )
+            }
+            Err(index) => index,
+        };
+        if self.is_leaf() {
+            self.keys.insert(index, key);
+            self.values.insert(index, value);
+        } else {
+            let (old, split) = self.children[index].insert(key, value, fanout);
+            let Some((key, value, right)) = split else {
+                return (old, None);
+            };
+            self.keys.insert(index, key);
+            self.values.insert(index, value);
+            self.children.insert(index + 1, right);
+        }
+
+        if self.keys.len() < fanout {
+            return (None, None);
+        }
+        let middle = self.keys.len() / 2;
+        let right = Self {
+            keys: self.keys.split_off(middle + 1),
+            values: self.values.split_off(middle + 1),
+            children: if self.is_leaf() {
+                Vec::new()
+            } else {
+                self.children.split_off(middle + 1)
+            },
+        };
+        let key = self.keys.pop().expect("split node has a median");
+        let value = self.values.pop().expect("split node has a median");
+        (None, Some((key, value, right)))
+    }
+
+    /// Remove `key` from this subtree, leaving children with at least
+    /// `min_keys` keys but possibly this node with fewer.
+    fn remove(&mut self, key: &K, min_keys: usize) -> Option<(K, V)> {
+        match self.keys.binary_search(key) {
+            Ok(index) if self.is_leaf() => {
+                Some((self.keys.remove(index), self.values.remove(index)))
+            }
+            Ok(index) => {
+                // Swap in the predecessor, which always lives in a leaf.
+                let (key, value) = self.children[index].remove_max(min_keys);
+                let key = std::mem::replace(&mut self.keys[index], key);
+                let value = std::mem::replace(&mut self.values[index], value);
+                self.rebalance(index, min_keys);
+                Some((key, value))
+            }
+            Err(_) if self.is_leaf() => None,
+            Err(index) => {
+                let removed = self.children[index].remove(key, min_keys)?;
+                self.rebalance(index, min_keys);
+                Some(removed)
+            }
+        }
+    }
+
+    fn remove_max(&mut self, min_keys: usize) -> (K, V) {
+        if self.is_leaf() {
+            let key = self.keys.pop().expect("non-root nodes are not empty");
+            let value = self.values.pop().expect("non-root nodes are not empty");
+            return (key, value);
+        }
+        let last = self.children.len() - 1;
+        let max = self.children[last].remove_max(min_keys);
+        self.rebalance(last, min_keys);
+        max
+    }
+
+    /// Restore the minimum key count of `children[index]` by borrowing a
+    /// key through this node from a sibling, or by merging with one.
+    fn rebalance(&mut self, index: usize, min_keys: usize) {
+        if self.children[index].keys.len() >= min_keys {
+            return;
+        }
+        if index > 0 && self.children[index - 1].keys.len() > min_keys {
+            let (left, right) = self.children.split_at_mut(index);
+            let (left, child) = (&mut left[index - 1], &mut right[0]);
+            let key = std::mem::replace(&mut self.keys[index - 1], left.keys.pop().unwrap());
+            let value = std::mem::replace(&mut self.values[index - 1], left.values.pop().unwrap());
+            child.keys.insert(0, key);
+            child.values.insert(0, value);
+            if let Some(grandchild) = left.children.pop() {
+                child.children.insert(0, grandchild);
+            }
+        } else if index + 1 < self.children.len() && self.children[index + 1].keys.len() > min_keys
+        {
+            let (left, right) = self.children.split_at_mut(index + 1);
+            let (child, right) = (&mut left[index], &mut right[0]);
+            let key = std::mem::replace(&mut self.keys[index], right.keys.remove(0));
+            let value = std::mem::replace(&mut self.values[index], right.values.remove(0));
+            child.keys.push(key);
+            child.values.push(value);
+            if !right.is_leaf() {
+                child.children.push(right.children.remove(0));
+            }
+        } else {
+            let left = if index > 0 { index - 1 } else { index };
+            let right = self.children.remove(left + 1);
+            let key = self.keys.remove(left);
+            let value = self.values.remove(left);
+            let node = &mut self.children[left];
+            node.keys.push(key);
+            node.values.push(value);
+            node.keys.extend(right.keys);
+            node.values.extend(right.values);
+            node.children.extend(right.children);
+        }
+    }
+}
+
+/// An ordered map stored in a B-tree.
+///
+/// Each node holds a sorted run of up to `fanout - 1` keys and, unless it is
+/// a leaf, one more child than keys. Inserts split full nodes on the way
+/// back up and removals borrow from or merge with a sibling, so every leaf
+/// stays at the same depth and every node except the root stays at least
+/// half full. Wide nodes keep the tree shallow and make each step a
+/// cache-friendly scan of a contiguous key array.
+#[derive(Clone)]
+pub struct BTreeMap<K, V> {
+    root: Node<K, V>,
+    len: usize,
+    fanout: usize,
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for BTreeMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V> Default for BTreeMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> BTreeMap<K, V> {
+    /// Create a new empty BTreeMap with a fanout of 16.
+    pub fn new() -> Self {
+        Self::with_fanout(DEFAULT_FANOUT)
+    }
+
+    /// Create a new empty BTreeMap whose nodes have at most `fanout`
+    /// children.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fanout` is less than 3.
+    pub fn with_fanout(fanout: usize) -> Self {
+        assert!(fanout >= 3, "B-tree fanout must be at least 3");
+        Self {
+            root: Node::leaf(),
+            len: 0,
+            fanout,
+        }
+    }
+
+    /// Returns the maximum number of children per node.
+    pub fn fanout(&self) -> usize {
+        self.fanout
+    }
+
+    /// Returns the number of elements in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the map contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Minimum number of keys in a node other than the root.
+    fn min_keys(&self) -> usize {
+        (self.fanout + 1) / 2 - 1
+    }
+
+    /// Clear all entries from the map.
+    pub fn clear(&mut self) {
+        self.root = Node::leaf();
+        self.len = 0;
+    }
+}
+
+impl<K: Ord, V> BTreeMap<K, V> {
+    /// Insert a key-value pair into the map.
+    ///
+    /// Returns the previous value if the key existed.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let (old, split) = self.root.insert(key, value, self.fanout);
+        if let Some((key, value, right)) = split {
+            let left = std::mem::replace(&mut self.root, Node::leaf());
+            self.root = Node {
+                keys: vec![key],
+                values: vec![value],
+                children: vec![left, right],
+            };
+        }
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    /// Get a reference to the value associated with the key.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let mut node = &self.root;
+        loop {
+            match node.keys.binary_search(key) {
+                Ok(index) => return Some(&node.values[index]),
+                Err(_) if node.is_leaf() => return None,
+                Err(index) => node = &node.children[index],
+            }
+        }
+    }
+
+    /// Check if the map contains the given key.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Remove a key-value pair from the map.
+    ///
+    /// Returns the removed value if the key existed.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let min_keys = self.min_keys();
+        let (_, value) = self.root.remove(key, min_keys)?;
+        if self.root.keys.is_empty() && !self.root.is_leaf() {
+            self.root = self.root.children.pop().expect("internal node has a child");
+        }
+        self.len -= 1;
+        Some(value)
+    }
+
+    /// Iterate over all key-value pairs in key order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.range(..)
+    }
+
+    /// Iterate over the key-value pairs whose keys fall in `range`, in key
+    /// order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range starts after it ends, or starts and ends at the
+    /// same excluded key.
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Iter<'_, K, V> {
+        check_range(&range);
+        let mut iter = Iter {
+            stack: Vec::new(),
+            end: None,
+        };
+        // The first position at or after the start, and the first past the
+        // end. Deeper matches come earlier in key order.
+        let mut node = &self.root;
+        loop {
+            let index = node
+                .keys
+                .partition_point(|key| before_start(key, range.start_bound()));
+            iter.stack.push((node, index));
+            if node.is_leaf() {
+                break;
+            }
+            node = &node.children[index];
+        }
+        let mut node = &self.root;
+        loop {
+            let index = node
+                .keys
+                .partition_point(|key| !past_end(key, range.end_bound()));
+            if index < node.keys.len() {
+                iter.end = Some((node, index));
+            }
+            if node.is_leaf() {
+                break;
+            }
+            node = &node.children[index];
+        }
+        iter
+    }
+
+    /// Check the B-tree invariants, panicking on the first violation.
+    ///
+    /// Checks that keys are strictly increasing in order, that every node
+    /// but the root holds between the minimum and maximum number of keys,
+    /// that internal nodes have one more child than keys, and that all
+    /// leaves sit at the same depth.
+    pub fn debug_validate(&self) {
+        fn walk<K: Ord, V>(
+            node: &Node<K, V>,
+            is_root: bool,
+            depth: usize,
+            leaf_depth: &mut Option<usize>,
+            bounds: (usize, usize),
+        ) -> usize {
+            let (min_keys, max_keys) = bounds;
+            assert!(node.keys.len() <= max_keys, "node has too many keys");
+            assert!(
+                is_root || node.keys.len() >= min_keys,
+                "node has too few keys"
+            );
+            assert_eq!(node.keys.len(), node.values.len(), "keys and values differ");
+            assert!(
+                node.keys.windows(2).all(|pair| pair[0] < pair[1]),
+                "keys out of order"
+            );
+            if node.is_leaf() {
+                assert_eq!(
+                    *leaf_depth.get_or_insert(depth),
+                    depth,
+                    "leaves at different depths"
+                );
+                return node.keys.len();
+            }
+            assert_eq!(
+                node.children.len(),
+                node.keys.len() + 1,
+                "wrong child count"
+            );
+            for (i, child) in node.children.iter().enumerate() {
+                if i > 0 {
+                    assert!(child.keys[0] > node.keys[i - 1], "child below separator");
+                }
+                if i < node.keys.len() {
+                    assert!(
+                        *child.keys.last().unwrap() < node.keys[i],
+                        "child above separator"
+                    );
+                }
+            }
+            node.keys.len()
+                + node
+                    .children
+                    .iter()
+                    .map(|child| walk(child, false, depth + 1, leaf_depth, bounds))
+                    .sum::<usize>()
+        }
+
+        let bounds = (self.min_keys(), self.fanout - 1);
+        let count = walk(&self.root, true, 0, &mut None, bounds);
+        assert_eq!(count, self.len, "len does not match entry count");
+    }
+}
+
+/// An iterator over a key range of a [`BTreeMap`], in key order.
+pub struct Iter<'a, K, V> {
+    /// Nodes on the path to the next entry, each with the index of its next
+    /// key; a node's child at that index has already been descended into.
+    stack: Vec<(&'a Node<K, V>, usize)>,
+    /// The position of the first entry past the range, if there is one.
+    end: Option<(&'a Node<K, V>, usize)>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (node, index) = self.stack.pop()?;
+            if index == node.keys.len() {
+                continue;
+            }
+            if self
+                .end
+                .is_some_and(|(end, end_index)| std::ptr::eq(end, node) && end_index == index)
+            {
+                self.stack.clear();
+                return None;
+            }
+            self.stack.push((node, index + 1));
+            if !node.is_leaf() {
+                let mut child = &node.children[index + 1];
+                loop {
+                    self.stack.push((child, 0));
+                    if child.is_leaf() {
+                        break;
+                    }
+                    child = &child.children[0];
+                }
+            }
+            return Some((&node.keys[index], &node.values[index]));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_get_remove_small_fanout() {
+        let mut map = BTreeMap::with_fanout(3);
+        for i in (0..200).rev() {
+            assert_eq!(map.insert(i, i * 10), None);
+            map.debug_validate();
+        }
+        assert_eq!(map.insert(7, 0), Some(70));
+        assert_eq!(map.get(&150), Some(&1500));
+        for i in (0..200).step_by(3) {
+            assert_eq!(map.remove(&i), Some(if i == 7 { 0 } else { i * 10 }));
+            map.debug_validate();
+        }
+        assert_eq!(map.remove(&0), None);
+        assert_eq!(map.len(), 200 - 67);
+    }
+
+    #[test]
+    fn test_range_bounds() {
+        let mut map = BTreeMap::with_fanout(4);
+        for i in 0..100 {
+            map.insert(i * 2, ());
+        }
+        let keys = |iter: Iter<'_, i32, ()>| iter.map(|(k, _)| *k).collect::<Vec<_>>();
+        assert_eq!(keys(map.range(10..16)), [10, 12, 14]);
+        assert_eq!(keys(map.range(9..=16)), [10, 12, 14, 16]);
+        assert_eq!(
+            keys(map.range((Bound::Excluded(10), Bound::Included(14)))),
+            [12, 14]
+        );
+        assert_eq!(keys(map.range(195..)), [196, 198]);
+        assert!(keys(map.range(500..)).is_empty());
+        assert!(keys(map.range(5..5)).is_empty());
+        assert_eq!(map.iter().count(), 100);
+    }
+
+    #[test]
+    fn test_remove_down_to_empty() {
+        for fanout in [3, 4, 5, 16] {
+            let mut map = BTreeMap::with_fanout(fanout);
+            for i in 0..500 {
+                map.insert((i * 37) % 500, i);
+            }
+            for i in 0..500 {
+                assert!(map.remove(&((i * 91) % 500)).is_some());
+            }
+            map.debug_validate();
+            assert!(map.is_empty());
+            assert!(map.root.is_leaf());
+        }
+    }
+}
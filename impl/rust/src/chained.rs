@@ -0,0 +1,283 @@
+//! Hash Map implementation using separate chaining.
+
+use crate::growth::GrowthPolicy;
+use crate::hashmap::TableStats;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+const DEFAULT_CAPACITY: usize = 16;
+const MAX_LOAD_FACTOR: f64 = 0.75;
+
+/// A hash map using separate chaining with a vector per bucket.
+///
+/// Colliding entries share a bucket instead of spilling into neighboring
+/// slots, so deletion needs no tombstones and performance stays stable at
+/// high load factors, at the cost of an allocation per non-empty bucket.
+#[derive(Debug, Clone)]
+pub struct ChainedHashMap<K, V> {
+    buckets: Vec<Vec<(K, V)>>,
+    size: usize,
+    growth_policy: GrowthPolicy,
+}
+
+impl<K, V> Default for ChainedHashMap<K, V>
+where
+    K: Hash + Eq,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> ChainedHashMap<K, V>
+where
+    K: Hash + Eq,
+{
+    /// Create a new empty ChainedHashMap.
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// Create a new ChainedHashMap with the specified number of buckets.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_growth_policy(capacity, GrowthPolicy::default())
+    }
+
+    /// Create a new empty ChainedHashMap that grows according to `policy`.
+    pub fn with_growth_policy(policy: GrowthPolicy) -> Self {
+        Self::with_capacity_and_growth_policy(DEFAULT_CAPACITY, policy)
+    }
+
+    /// Create a new ChainedHashMap with at least the specified number of
+    /// buckets that grows according to `policy`.
+    pub fn with_capacity_and_growth_policy(capacity: usize, policy: GrowthPolicy) -> Self {
+        let capacity = policy.initial_capacity(capacity.max(DEFAULT_CAPACITY));
+        let mut buckets = Vec::with_capacity(capacity);
+        buckets.resize_with(capacity, Vec::new);
+        Self {
+            buckets,
+            size: 0,
+            growth_policy: policy,
+        }
+    }
+
+    /// Returns the policy used to pick the next capacity on growth.
+    pub fn growth_policy(&self) -> GrowthPolicy {
+        self.growth_policy
+    }
+
+    /// Returns the number of elements in the map.
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Returns true if the map contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Returns the current number of buckets.
+    pub fn capacity(&self) -> usize {
+        self.buckets.len()
+    }
+
+    fn hash_key(&self, key: &K) -> usize {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        hasher.finish() as usize
+    }
+
+    fn bucket_index(&self, key: &K) -> usize {
+        self.hash_key(key) % self.buckets.len()
+    }
+
+    fn load_factor(&self) -> f64 {
+        self.size as f64 / self.buckets.len() as f64
+    }
+
+    fn resize(&mut self) {
+        let new_capacity =
+            self.growth_policy
+                .next_capacity(self.buckets.len(), self.size + 1, MAX_LOAD_FACTOR);
+        let old_buckets = std::mem::replace(&mut self.buckets, {
+            let mut v = Vec::with_capacity(new_capacity);
+            v.resize_with(new_capacity, Vec::new);
+            v
+        });
+
+        for (key, value) in old_buckets.into_iter().flatten() {
+            let index = self.bucket_index(&key);
+            self.buckets[index].push((key, value));
+        }
+    }
+
+    /// Insert a key-value pair into the map.
+    ///
+    /// Returns the previous value if the key existed.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let index = self.bucket_index(&key);
+        if let Some((_, old)) = self.buckets[index].iter_mut().find(|(k, _)| *k == key) {
+            return Some(std::mem::replace(old, value));
+        }
+
+        if self.load_factor() >= MAX_LOAD_FACTOR {
+            self.resize();
+        }
+
+        let index = self.bucket_index(&key);
+        self.buckets[index].push((key, value));
+        self.size += 1;
+        None
+    }
+
+    /// Get a reference to the value associated with the key.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.buckets[self.bucket_index(key)]
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    /// Get a mutable reference to the value associated with the key.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let index = self.bucket_index(key);
+        self.buckets[index]
+            .iter_mut()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    /// Remove a key-value pair from the map.
+    ///
+    /// Returns the removed value if the key existed.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let index = self.bucket_index(key);
+        let bucket = &mut self.buckets[index];
+        let position = bucket.iter().position(|(k, _)| k == key)?;
+        self.size -= 1;
+        Some(bucket.swap_remove(position).1)
+    }
+
+    /// Check if the map contains the given key.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Clear all entries from the map.
+    pub fn clear(&mut self) {
+        for bucket in &mut self.buckets {
+            bucket.clear();
+        }
+        self.size = 0;
+    }
+
+    /// Iterate over all key-value pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.buckets.iter().flatten().map(|(k, v)| (k, v))
+    }
+
+    /// Iterate over all keys.
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.iter().map(|(k, _)| k)
+    }
+
+    /// Iterate over all values.
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.iter().map(|(_, v)| v)
+    }
+
+    /// Collect occupancy and chain-length statistics for the table.
+    ///
+    /// The probe length of an entry is its position in its bucket's chain
+    /// plus one, and `capacity` is the number of buckets.
+    pub fn stats(&self) -> TableStats {
+        let mut histogram = Vec::new();
+        let mut total_probe_length = 0;
+
+        for bucket in &self.buckets {
+            if histogram.len() < bucket.len() {
+                histogram.resize(bucket.len(), 0);
+            }
+            for (position, count) in histogram.iter_mut().take(bucket.len()).enumerate() {
+                *count += 1;
+                total_probe_length += position + 1;
+            }
+        }
+
+        TableStats {
+            occupied: self.size,
+            tombstones: 0,
+            capacity: self.buckets.len(),
+            load_factor: self.load_factor(),
+            avg_probe_length: if self.size == 0 {
+                0.0
+            } else {
+                total_probe_length as f64 / self.size as f64
+            },
+            max_probe_length: histogram.len(),
+            probe_length_histogram: histogram,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_get_overwrite() {
+        let mut map = ChainedHashMap::new();
+        assert!(map.insert("key".to_string(), 1).is_none());
+        assert_eq!(map.insert("key".to_string(), 2), Some(1));
+        assert_eq!(map.get(&"key".to_string()), Some(&2));
+        *map.get_mut(&"key".to_string()).unwrap() = 3;
+        assert_eq!(map.get(&"key".to_string()), Some(&3));
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn test_remove_and_clear() {
+        let mut map = ChainedHashMap::new();
+        for i in 0..200 {
+            map.insert(i, i);
+        }
+        for i in 0..100 {
+            assert_eq!(map.remove(&i), Some(i));
+        }
+        assert_eq!(map.remove(&0), None);
+        assert_eq!(map.len(), 100);
+        assert_eq!(map.iter().count(), 100);
+        map.clear();
+        assert!(map.is_empty());
+        assert!(!map.contains_key(&150));
+    }
+
+    #[test]
+    fn test_growth_policy_one_and_half() {
+        let mut map = ChainedHashMap::with_growth_policy(GrowthPolicy::OneAndHalf);
+        let mut capacities = vec![map.capacity()];
+        for i in 0..100 {
+            map.insert(i, i);
+            if map.capacity() != *capacities.last().unwrap() {
+                capacities.push(map.capacity());
+            }
+        }
+        assert_eq!(capacities, [16, 24, 36, 54, 81, 121, 181]);
+        assert!((0..100).all(|i| map.get(&i) == Some(&i)));
+    }
+
+    #[test]
+    fn test_stats_chain_lengths() {
+        let mut map = ChainedHashMap::with_capacity(16);
+        for i in 0..10 {
+            map.insert(i, ());
+        }
+        let stats = map.stats();
+        assert_eq!(stats.tombstones, 0);
+        assert_eq!(stats.probe_length_histogram.iter().sum::<usize>(), 10);
+        assert_eq!(
+            stats.probe_length_histogram[0],
+            map.buckets.iter().filter(|b| !b.is_empty()).count()
+        );
+    }
+}
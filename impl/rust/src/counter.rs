@@ -0,0 +1,168 @@
+//! Frequency counter built on the lab hash map.
+
+use crate::hashmap::HashMap;
+use std::hash::Hash;
+
+/// A map from keys to occurrence counts.
+///
+/// Only keys with a positive count are stored: a key whose count drops to
+/// zero is removed, so [`len`](Self::len) is the number of distinct keys
+/// seen and not yet subtracted away.
+#[derive(Debug, Clone)]
+pub struct Counter<K> {
+    counts: HashMap<K, u64>,
+    total: u64,
+}
+
+impl<K> Default for Counter<K>
+where
+    K: Hash + Eq,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K> Counter<K>
+where
+    K: Hash + Eq,
+{
+    /// Create a new empty Counter.
+    pub fn new() -> Self {
+        Self {
+            counts: HashMap::new(),
+            total: 0,
+        }
+    }
+
+    /// Returns the number of keys with a positive count.
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Returns true if no key has a positive count.
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Returns the sum of all counts.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Add `n` occurrences of `key`.
+    pub fn add(&mut self, key: K, n: u64) {
+        if n == 0 {
+            return;
+        }
+        *self.counts.entry(key).or_insert(0) += n;
+        self.total += n;
+    }
+
+    /// Returns the count of `key`, zero if it was never added.
+    pub fn count(&self, key: &K) -> u64 {
+        self.counts.get(key).copied().unwrap_or(0)
+    }
+
+    /// Remove up to `n` occurrences of `key`, saturating at zero.
+    ///
+    /// Returns the number of occurrences removed.
+    pub fn remove(&mut self, key: &K, n: u64) -> u64 {
+        let Some(count) = self.counts.get_mut(key) else {
+            return 0;
+        };
+        let removed = n.min(*count);
+        *count -= removed;
+        if *count == 0 {
+            self.counts.remove(key);
+        }
+        self.total -= removed;
+        removed
+    }
+
+    /// Returns the `k` keys with the highest counts, highest first.
+    ///
+    /// Ties are broken arbitrarily. Fewer than `k` pairs are returned if the
+    /// counter holds fewer keys.
+    pub fn most_common(&self, k: usize) -> Vec<(&K, u64)> {
+        let mut pairs: Vec<(&K, u64)> = self.iter().collect();
+        let by_count_desc = |a: &(&K, u64), b: &(&K, u64)| b.1.cmp(&a.1);
+        if k < pairs.len() {
+            // Partition out the top k before sorting only those.
+            pairs.select_nth_unstable_by(k, by_count_desc);
+            pairs.truncate(k);
+        }
+        pairs.sort_unstable_by(by_count_desc);
+        pairs
+    }
+
+    /// Add every count of `other` into this counter.
+    pub fn merge(&mut self, other: &Counter<K>)
+    where
+        K: Clone,
+    {
+        for (key, n) in other.iter() {
+            self.add(key.clone(), n);
+        }
+    }
+
+    /// Subtract every count of `other` from this counter, saturating at zero.
+    pub fn subtract(&mut self, other: &Counter<K>) {
+        for (key, n) in other.iter() {
+            self.remove(key, n);
+        }
+    }
+
+    /// Iterate over all keys and their counts.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, u64)> {
+        self.counts.iter().map(|(key, &n)| (key, n))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_and_count() {
+        let mut counter = Counter::new();
+        for word in "a b a c a b".split(' ') {
+            counter.add(word, 1);
+        }
+        counter.add("d", 0);
+        assert_eq!(counter.count(&"a"), 3);
+        assert_eq!(counter.count(&"d"), 0);
+        assert_eq!(counter.len(), 3);
+        assert_eq!(counter.total(), 6);
+    }
+
+    #[test]
+    fn test_most_common() {
+        let mut counter = Counter::new();
+        for (key, n) in [(1, 5), (2, 9), (3, 1), (4, 7)] {
+            counter.add(key, n);
+        }
+        assert_eq!(counter.most_common(2), [(&2, 9), (&4, 7)]);
+        assert_eq!(counter.most_common(10).len(), 4);
+        assert!(counter.most_common(0).is_empty());
+    }
+
+    #[test]
+    fn test_merge_and_subtract() {
+        let mut a = Counter::new();
+        a.add('x', 3);
+        a.add('y', 1);
+        let mut b = Counter::new();
+        b.add('x', 2);
+        b.add('z', 4);
+
+        a.merge(&b);
+        assert_eq!((a.count(&'x'), a.count(&'z'), a.total()), (5, 4, 10));
+
+        a.subtract(&b);
+        a.subtract(&b);
+        assert_eq!(a.count(&'x'), 1);
+        assert!(!a.iter().any(|(&key, _)| key == 'z'));
+        assert_eq!((a.len(), a.total()), (2, 2));
+    }
+}
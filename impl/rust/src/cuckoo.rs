@@ -0,0 +1,331 @@
+//! Hash Map implementation using cuckoo hashing.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+const DEFAULT_CAPACITY: usize = 16;
+const MAX_LOAD_FACTOR: f64 = 0.45;
+const MAX_RELOCATIONS: usize = 64;
+const STASH_CAPACITY: usize = 4;
+const REBUILDS_BEFORE_GROWTH: usize = 3;
+
+/// A hash map using two-table cuckoo hashing with a small stash.
+///
+/// Every key lives either in its slot of the first table, its slot of the
+/// second table, or the stash, so a lookup inspects at most
+/// `2 + STASH_CAPACITY` entries. Inserts evict residents back and forth
+/// between the tables; when the relocation bound is hit the displaced entry
+/// goes to the stash, and when the stash is full the table is rebuilt with
+/// fresh hash seeds.
+#[derive(Debug, Clone)]
+pub struct CuckooHashMap<K, V> {
+    tables: [Vec<Option<(K, V)>>; 2],
+    stash: Vec<(K, V)>,
+    seeds: [u64; 2],
+    size: usize,
+    rebuilds: usize,
+}
+
+impl<K, V> Default for CuckooHashMap<K, V>
+where
+    K: Hash + Eq,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn empty_table<K, V>(capacity: usize) -> Vec<Option<(K, V)>> {
+    let mut table = Vec::with_capacity(capacity);
+    table.resize_with(capacity, || None);
+    table
+}
+
+/// SplitMix64 step, used to derive fresh seeds on rebuild.
+fn next_seed(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+impl<K, V> CuckooHashMap<K, V>
+where
+    K: Hash + Eq,
+{
+    /// Create a new empty CuckooHashMap.
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// Create a new CuckooHashMap with the specified number of slots per table.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let capacity = capacity.max(DEFAULT_CAPACITY);
+        Self {
+            tables: [empty_table(capacity), empty_table(capacity)],
+            stash: Vec::with_capacity(STASH_CAPACITY),
+            seeds: [next_seed(1), next_seed(2)],
+            size: 0,
+            rebuilds: 0,
+        }
+    }
+
+    /// Returns the number of elements in the map.
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Returns true if the map contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Returns the total number of slots across both tables.
+    pub fn capacity(&self) -> usize {
+        self.tables[0].len() * 2
+    }
+
+    /// Returns the number of full-table rebuilds performed so far.
+    pub fn rebuilds(&self) -> usize {
+        self.rebuilds
+    }
+
+    fn slot(&self, table: usize, key: &K) -> usize {
+        let mut hasher = DefaultHasher::new();
+        self.seeds[table].hash(&mut hasher);
+        key.hash(&mut hasher);
+        hasher.finish() as usize % self.tables[table].len()
+    }
+
+    fn locate(&self, key: &K) -> Option<Location> {
+        for table in 0..2 {
+            let index = self.slot(table, key);
+            if matches!(&self.tables[table][index], Some((k, _)) if k == key) {
+                return Some(Location::Table(table, index));
+            }
+        }
+        self.stash
+            .iter()
+            .position(|(k, _)| k == key)
+            .map(Location::Stash)
+    }
+
+    /// Place a new entry, evicting residents up to the relocation bound.
+    ///
+    /// Returns the entry left homeless if both the relocation bound and the
+    /// stash are exhausted.
+    fn try_place(&mut self, entry: (K, V)) -> Result<(), (K, V)> {
+        let mut carried = entry;
+        for relocation in 0..MAX_RELOCATIONS {
+            let table = relocation % 2;
+            let index = self.slot(table, &carried.0);
+            match self.tables[table][index].replace(carried) {
+                None => return Ok(()),
+                Some(evicted) => carried = evicted,
+            }
+        }
+
+        if self.stash.len() < STASH_CAPACITY {
+            self.stash.push(carried);
+            Ok(())
+        } else {
+            Err(carried)
+        }
+    }
+
+    fn drain_entries(&mut self) -> Vec<(K, V)> {
+        let mut entries: Vec<(K, V)> = self.stash.drain(..).collect();
+        for table in &mut self.tables {
+            entries.extend(table.iter_mut().filter_map(Option::take));
+        }
+        entries
+    }
+
+    /// Rebuild with fresh seeds, growing after repeated failures.
+    fn rebuild(&mut self, capacity: usize, mut entries: Vec<(K, V)>) {
+        let mut capacity = capacity;
+        let mut attempts = 0;
+
+        'retry: loop {
+            attempts += 1;
+            self.rebuilds += 1;
+            if attempts > REBUILDS_BEFORE_GROWTH {
+                capacity *= 2;
+                attempts = 0;
+            }
+            self.seeds = [next_seed(self.seeds[0]), next_seed(self.seeds[1])];
+            self.tables = [empty_table(capacity), empty_table(capacity)];
+
+            while let Some(entry) = entries.pop() {
+                if let Err(homeless) = self.try_place(entry) {
+                    entries.push(homeless);
+                    entries.extend(self.drain_entries());
+                    continue 'retry;
+                }
+            }
+            return;
+        }
+    }
+
+    /// Insert a key-value pair into the map.
+    ///
+    /// Returns the previous value if the key existed.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(location) = self.locate(&key) {
+            let (_, old) = self.entry_mut(location);
+            return Some(std::mem::replace(old, value));
+        }
+
+        self.size += 1;
+        if self.size as f64 > self.capacity() as f64 * MAX_LOAD_FACTOR {
+            let mut entries = self.drain_entries();
+            entries.push((key, value));
+            self.rebuild(self.tables[0].len() * 2, entries);
+            return None;
+        }
+
+        if let Err(homeless) = self.try_place((key, value)) {
+            let mut entries = self.drain_entries();
+            entries.push(homeless);
+            self.rebuild(self.tables[0].len(), entries);
+        }
+        None
+    }
+
+    fn entry_mut(&mut self, location: Location) -> (&K, &mut V) {
+        let (key, value) = match location {
+            Location::Table(table, index) => self.tables[table][index].as_mut().unwrap(),
+            Location::Stash(index) => &mut self.stash[index],
+        };
+        (&*key, value)
+    }
+
+    /// Get a reference to the value associated with the key.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        match self.locate(key)? {
+            Location::Table(table, index) => self.tables[table][index].as_ref().map(|(_, v)| v),
+            Location::Stash(index) => Some(&self.stash[index].1),
+        }
+    }
+
+    /// Get a mutable reference to the value associated with the key.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let location = self.locate(key)?;
+        Some(self.entry_mut(location).1)
+    }
+
+    /// Remove a key-value pair from the map.
+    ///
+    /// Returns the removed value if the key existed.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let removed = match self.locate(key)? {
+            Location::Table(table, index) => self.tables[table][index].take()?,
+            Location::Stash(index) => self.stash.swap_remove(index),
+        };
+        self.size -= 1;
+        Some(removed.1)
+    }
+
+    /// Check if the map contains the given key.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.locate(key).is_some()
+    }
+
+    /// Clear all entries from the map.
+    pub fn clear(&mut self) {
+        for table in &mut self.tables {
+            for slot in table.iter_mut() {
+                *slot = None;
+            }
+        }
+        self.stash.clear();
+        self.size = 0;
+    }
+
+    /// Iterate over all key-value pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.tables
+            .iter()
+            .flat_map(|table| table.iter().flatten())
+            .chain(self.stash.iter())
+            .map(|(k, v)| (k, v))
+    }
+
+    /// Iterate over all keys.
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.iter().map(|(k, _)| k)
+    }
+
+    /// Iterate over all values.
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.iter().map(|(_, v)| v)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Location {
+    Table(usize, usize),
+    Stash(usize),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_get_overwrite() {
+        let mut map = CuckooHashMap::new();
+        assert!(map.insert("key".to_string(), 1).is_none());
+        assert_eq!(map.insert("key".to_string(), 2), Some(1));
+        assert_eq!(map.get(&"key".to_string()), Some(&2));
+        *map.get_mut(&"key".to_string()).unwrap() += 1;
+        assert_eq!(map.get(&"key".to_string()), Some(&3));
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn test_many_inserts_and_removes() {
+        let mut map = CuckooHashMap::new();
+        for i in 0..5000 {
+            assert!(map.insert(i, i * 2).is_none());
+        }
+        assert_eq!(map.len(), 5000);
+        assert_eq!(map.iter().count(), 5000);
+        for i in 0..5000 {
+            assert_eq!(map.get(&i), Some(&(i * 2)));
+        }
+        for i in (0..5000).step_by(2) {
+            assert_eq!(map.remove(&i), Some(i * 2));
+        }
+        assert_eq!(map.len(), 2500);
+        for i in 0..5000 {
+            assert_eq!(map.contains_key(&i), i % 2 == 1);
+        }
+        assert!(map.len() as f64 <= map.capacity() as f64 * MAX_LOAD_FACTOR);
+    }
+
+    #[test]
+    fn test_every_key_in_candidate_slot_or_stash() {
+        let mut map = CuckooHashMap::new();
+        for i in 0..1000 {
+            map.insert(format!("key{}", i), i);
+        }
+        assert!(map.stash.len() <= STASH_CAPACITY);
+        for table in 0..2 {
+            for (index, slot) in map.tables[table].iter().enumerate() {
+                if let Some((key, _)) = slot {
+                    assert_eq!(map.slot(table, key), index);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut map = CuckooHashMap::new();
+        map.insert(1, 1);
+        map.clear();
+        assert!(map.is_empty());
+        assert!(map.get(&1).is_none());
+    }
+}
@@ -0,0 +1,254 @@
+//! Growable double-ended queue on a ring buffer.
+
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+const MIN_CAPACITY: usize = 4;
+
+/// A double-ended queue with amortized O(1) push and pop at both ends and
+/// O(1) indexing.
+///
+/// Elements live in a power-of-two buffer viewed as a ring: `head` is the
+/// slot of the front element and the rest follow it, wrapping past the end
+/// of the buffer, so both ends move by stepping `head` or the length and
+/// masking. When the ring fills, the elements are moved in order into a
+/// buffer twice the size, starting again at slot 0.
+#[derive(Clone)]
+pub struct Deque<T> {
+    /// Ring of slots; occupied exactly for the `len` slots from `head`.
+    buf: Vec<Option<T>>,
+    head: usize,
+    len: usize,
+}
+
+impl<T> Default for Deque<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Deque<T> {
+    /// Create a new empty Deque.
+    pub fn new() -> Self {
+        Self {
+            buf: Vec::new(),
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// Create a new empty Deque with room for at least `capacity` elements.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut deque = Self::new();
+        if capacity > 0 {
+            deque.buf = empty_ring(capacity.max(MIN_CAPACITY).next_power_of_two());
+        }
+        deque
+    }
+
+    /// Returns the number of elements in the deque.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the deque contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of elements the deque can hold without growing.
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Buffer slot of the element at `index`, which may be one past the
+    /// back.
+    fn slot(&self, index: usize) -> usize {
+        (self.head + index) & (self.buf.len() - 1)
+    }
+
+    /// Double the ring, unwrapping the elements to start at slot 0.
+    fn grow(&mut self) {
+        let capacity = (self.buf.len() * 2).max(MIN_CAPACITY);
+        let mut buf = empty_ring(capacity);
+        for (index, slot) in buf.iter_mut().take(self.len).enumerate() {
+            let from = self.slot(index);
+            *slot = self.buf[from].take();
+        }
+        self.buf = buf;
+        self.head = 0;
+    }
+
+    /// Add an element to the back.
+    pub fn push_back(&mut self, item: T) {
+        if self.len == self.buf.len() {
+            self.grow();
+        }
+        let slot = self.slot(self.len);
+        self.buf[slot] = Some(item);
+        self.len += 1;
+    }
+
+    /// Add an element to the front.
+    pub fn push_front(&mut self, item: T) {
+        if self.len == self.buf.len() {
+            self.grow();
+        }
+        self.head = self.slot(self.buf.len() - 1);
+        self.buf[self.head] = Some(item);
+        self.len += 1;
+    }
+
+    /// Remove and return the back element, or `None` if the deque is empty.
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        let slot = self.slot(self.len);
+        self.buf[slot].take()
+    }
+
+    /// Remove and return the front element, or `None` if the deque is
+    /// empty.
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        let item = self.buf[self.head].take();
+        self.head = self.slot(1);
+        self.len -= 1;
+        item
+    }
+
+    /// Get a reference to the front element.
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    /// Get a reference to the back element.
+    pub fn back(&self) -> Option<&T> {
+        self.get(self.len.checked_sub(1)?)
+    }
+
+    /// Get a reference to the element at `index`, counting from the front.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        self.buf[self.slot(index)].as_ref()
+    }
+
+    /// Get a mutable reference to the element at `index`, counting from the
+    /// front.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index >= self.len {
+            return None;
+        }
+        let slot = self.slot(index);
+        self.buf[slot].as_mut()
+    }
+
+    /// Iterate over the elements from front to back.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        (0..self.len).map(move |index| self.buf[self.slot(index)].as_ref().expect("occupied slot"))
+    }
+
+    /// Clear all elements from the deque, keeping its capacity.
+    pub fn clear(&mut self) {
+        while self.pop_back().is_some() {}
+        self.head = 0;
+    }
+}
+
+fn empty_ring<T>(capacity: usize) -> Vec<Option<T>> {
+    let mut buf = Vec::with_capacity(capacity);
+    buf.resize_with(capacity, || None);
+    buf
+}
+
+impl<T> Index<usize> for Deque<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        let len = self.len;
+        self.get(index)
+            .unwrap_or_else(|| panic!("index {} out of bounds for length {}", index, len))
+    }
+}
+
+impl<T> IndexMut<usize> for Deque<T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        let len = self.len;
+        self.get_mut(index)
+            .unwrap_or_else(|| panic!("index {} out of bounds for length {}", index, len))
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Deque<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T> Extend<T> for Deque<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push_back(item);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for Deque<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut deque = Self::new();
+        deque.extend(iter);
+        deque
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_pop_both_ends() {
+        let mut deque = Deque::new();
+        deque.push_back(2);
+        deque.push_back(3);
+        deque.push_front(1);
+        deque.push_front(0);
+        assert_eq!(deque.iter().copied().collect::<Vec<_>>(), [0, 1, 2, 3]);
+        assert_eq!(deque.front(), Some(&0));
+        assert_eq!(deque.back(), Some(&3));
+        assert_eq!(deque.pop_front(), Some(0));
+        assert_eq!(deque.pop_back(), Some(3));
+        assert_eq!(deque.len(), 2);
+        deque.clear();
+        assert_eq!(deque.pop_front(), None);
+        assert_eq!(deque.back(), None);
+    }
+
+    #[test]
+    fn test_grow_unwraps_the_ring() {
+        let mut deque = Deque::with_capacity(4);
+        assert_eq!(deque.capacity(), 4);
+        // Wrap the head around the end of the buffer before growing.
+        deque.extend([2, 3]);
+        deque.push_front(1);
+        deque.push_front(0);
+        deque.push_back(4);
+        assert_eq!(deque.capacity(), 8);
+        assert_eq!(deque.iter().copied().collect::<Vec<_>>(), [0, 1, 2, 3, 4]);
+        deque[4] = 40;
+        assert_eq!(deque.back(), Some(&40));
+        assert_eq!(deque.get(5), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "index 3 out of bounds for length 3")]
+    fn test_index_out_of_bounds() {
+        let deque: Deque<i32> = (0..3).collect();
+        let _ = deque[3];
+    }
+}
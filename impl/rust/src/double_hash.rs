@@ -0,0 +1,52 @@
+//! Hash Map implementation using open addressing with double hashing.
+
+use crate::hashmap::{DefaultHashBuilder, OpenAddressingMap};
+use crate::probe::DoubleHashProbe;
+
+/// A hash map using open addressing with double hashing.
+///
+/// The home bucket comes from the low bits of the hash and the probe step from
+/// the high bits, forced odd so that it is coprime with the power-of-two
+/// capacity and the sequence covers every slot. Keys that collide on their
+/// home bucket usually still follow different sequences, which avoids the
+/// secondary clustering of quadratic probing.
+pub type DoubleHashMap<K, V, S = DefaultHashBuilder> = OpenAddressingMap<K, V, S, DoubleHashProbe>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_get_remove() {
+        let mut map = DoubleHashMap::new();
+        for i in 0..500 {
+            assert!(map.insert(format!("key{}", i), i).is_none());
+        }
+        assert_eq!(map.insert("key7".to_string(), 70), Some(7));
+        assert_eq!(map.len(), 500);
+        for i in (0..500).step_by(2) {
+            assert!(map.remove(&format!("key{}", i)).is_some());
+        }
+        assert_eq!(map.len(), 250);
+        assert_eq!(map.get(&"key7".to_string()), Some(&70));
+        assert!(!map.contains_key(&"key8".to_string()));
+        assert_eq!(
+            map.stats().probe_length_histogram.iter().sum::<usize>(),
+            250
+        );
+        map.debug_validate();
+    }
+
+    #[test]
+    fn test_delete_churn_does_not_grow() {
+        let mut map = DoubleHashMap::new();
+        for i in 0..10_000 {
+            map.insert(i, i);
+            if i >= 4 {
+                map.remove(&(i - 4));
+            }
+        }
+        assert_eq!(map.len(), 4);
+        assert_eq!(map.capacity(), 16);
+    }
+}
@@ -0,0 +1,226 @@
+//! Growable array with a pluggable growth policy and copy counters.
+
+use crate::growth::GrowthPolicy;
+use std::fmt;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+
+/// A `Vec`-like array whose growth schedule is chosen at construction and
+/// whose reallocation cost is counted.
+///
+/// When a push finds the array full, it allocates a buffer of
+/// [`GrowthPolicy::next_capacity`] elements and moves every element into
+/// it. A geometric policy makes that O(1) amortized per push, at the price
+/// of unused capacity up to its factor; exact fit never over-allocates but
+/// copies the whole array on every push once full. Unlike `realloc`, which
+/// can sometimes extend a buffer in place, every reallocation here moves
+/// all elements, so [`bytes_copied`](Self::bytes_copied) is the textbook
+/// cost model. The array dereferences to a slice for indexing and
+/// iteration.
+#[derive(Clone)]
+pub struct DynArray<T> {
+    buf: Vec<T>,
+    /// Capacity by the growth policy; `buf` may have reserved more.
+    capacity: usize,
+    growth_policy: GrowthPolicy,
+    reallocations: usize,
+    bytes_copied: usize,
+}
+
+impl<T> Default for DynArray<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> DynArray<T> {
+    /// Create a new empty DynArray.
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Create a new empty DynArray with room for `capacity` elements.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_growth_policy(capacity, GrowthPolicy::default())
+    }
+
+    /// Create a new empty DynArray that grows according to `policy`.
+    pub fn with_growth_policy(policy: GrowthPolicy) -> Self {
+        Self::with_capacity_and_growth_policy(0, policy)
+    }
+
+    /// Create a new empty DynArray with room for at least `capacity`
+    /// elements that grows according to `policy`.
+    pub fn with_capacity_and_growth_policy(capacity: usize, policy: GrowthPolicy) -> Self {
+        let capacity = if capacity == 0 {
+            0
+        } else {
+            policy.initial_capacity(capacity)
+        };
+        Self {
+            buf: Vec::with_capacity(capacity),
+            capacity,
+            growth_policy: policy,
+            reallocations: 0,
+            bytes_copied: 0,
+        }
+    }
+
+    /// Returns the policy used to pick the next capacity on growth.
+    pub fn growth_policy(&self) -> GrowthPolicy {
+        self.growth_policy
+    }
+
+    /// Returns the number of elements in the array.
+    pub fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Returns true if the array contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    /// Returns the number of elements the array can hold without growing.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns how many times the array has moved to a new buffer.
+    pub fn reallocations(&self) -> usize {
+        self.reallocations
+    }
+
+    /// Returns the total bytes of elements moved by reallocations.
+    pub fn bytes_copied(&self) -> usize {
+        self.bytes_copied
+    }
+
+    /// Move the elements to a buffer of `capacity` elements.
+    fn reallocate(&mut self, capacity: usize) {
+        let mut buf = Vec::with_capacity(capacity);
+        self.bytes_copied += self.buf.len() * mem::size_of::<T>();
+        buf.append(&mut self.buf);
+        self.buf = buf;
+        self.capacity = capacity;
+        self.reallocations += 1;
+    }
+
+    /// Add an element to the end of the array.
+    pub fn push(&mut self, item: T) {
+        if self.buf.len() == self.capacity {
+            let capacity = self
+                .growth_policy
+                .next_capacity(self.capacity, self.buf.len() + 1, 1.0);
+            self.reallocate(capacity);
+        }
+        self.buf.push(item);
+    }
+
+    /// Remove and return the last element, or `None` if the array is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        self.buf.pop()
+    }
+
+    /// Clear all elements from the array, keeping its capacity.
+    pub fn clear(&mut self) {
+        self.buf.clear();
+    }
+
+    /// Shrink the capacity to the number of elements.
+    ///
+    /// Counts as a reallocation when the capacity changes.
+    pub fn shrink_to_fit(&mut self) {
+        if self.capacity > self.buf.len() {
+            self.reallocate(self.buf.len());
+        }
+    }
+}
+
+impl<T> Deref for DynArray<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.buf
+    }
+}
+
+impl<T> DerefMut for DynArray<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        &mut self.buf
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for DynArray<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T> Extend<T> for DynArray<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for DynArray<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut array = Self::new();
+        array.extend(iter);
+        array
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_pop_and_index() {
+        let mut array: DynArray<i32> = (0..10).collect();
+        assert_eq!(array.len(), 10);
+        array[3] = 30;
+        assert_eq!(array[3], 30);
+        assert_eq!(array.pop(), Some(9));
+        assert_eq!(array.iter().sum::<i32>(), 63);
+        array.clear();
+        assert!(array.is_empty());
+        assert_eq!(array.pop(), None);
+    }
+
+    #[test]
+    fn test_counts_per_policy() {
+        let mut doubling = DynArray::with_growth_policy(GrowthPolicy::Doubling);
+        doubling.extend(0..100u64);
+        // 1, 2, 4, ..., 128.
+        assert_eq!(doubling.reallocations(), 8);
+        assert_eq!(doubling.capacity(), 128);
+        assert_eq!(doubling.bytes_copied(), (1 + 2 + 4 + 8 + 16 + 32 + 64) * 8);
+
+        let mut exact = DynArray::with_growth_policy(GrowthPolicy::ExactFit);
+        exact.extend(0..100u64);
+        assert_eq!(exact.reallocations(), 100);
+        assert_eq!(exact.capacity(), 100);
+        assert_eq!(exact.bytes_copied(), (0..100).sum::<usize>() * 8);
+
+        let mut golden = DynArray::with_growth_policy(GrowthPolicy::GoldenRatio);
+        golden.extend(0..100u64);
+        assert!(golden.reallocations() > doubling.reallocations());
+        assert!(golden.capacity() < 100 * 162 / 100);
+    }
+
+    #[test]
+    fn test_with_capacity_and_shrink() {
+        let mut array = DynArray::with_capacity_and_growth_policy(50, GrowthPolicy::OneAndHalf);
+        array.extend(0..50u32);
+        assert_eq!(array.reallocations(), 0);
+        array.push(50);
+        assert_eq!(array.capacity(), 75);
+        array.shrink_to_fit();
+        assert_eq!(array.capacity(), 51);
+        assert_eq!(array.reallocations(), 2);
+        assert_eq!(array.bytes_copied(), (50 + 51) * 4);
+    }
+}
@@ -0,0 +1,425 @@
+//! Euler tour trees: dynamic connectivity on a forest.
+
+use crate::skip_list::xorshift64;
+use crate::HashMap;
+use std::fmt;
+
+const DEFAULT_SEED: u64 = 0xE7_0E;
+
+/// Marks a missing parent or child.
+const NIL: usize = usize::MAX;
+
+#[derive(Debug, Clone)]
+struct Node {
+    /// Heap priority; every node outranks its children.
+    priority: u64,
+    parent: usize,
+    left: usize,
+    right: usize,
+    /// Number of nodes in this subtree.
+    size: usize,
+}
+
+/// A forest over vertices `0..n` that supports adding and removing edges
+/// and asking whether two vertices are connected, each in O(log n)
+/// expected time.
+///
+/// Each tree is stored as its Euler tour: the walk that starts at a vertex,
+/// crosses every edge once in each direction and comes back. The tour lists
+/// every vertex once, the first time it is reached, and every directed edge
+/// where it is crossed, so a tree of k vertices has a tour of 3k - 2
+/// entries. The tour is kept in a treap ordered by position, whose nodes
+/// live in one arena and point to their parents: vertex `v` is node `v`,
+/// and a map from each directed edge to its node finds the rest. Two
+/// vertices are connected when their nodes share a treap root. Every
+/// update is a few splits and concatenations of tours. Rerooting a tree at
+/// `v` rotates its tour to start at `v`. Linking `u` below `v` reroots
+/// both and splices the edge crossings in between, and cutting an edge
+/// removes the part of the tour between its two crossings, which is the
+/// tour of the subtree cut off.
+#[derive(Clone)]
+pub struct EulerTourTree {
+    /// Vertices first, then edge crossings.
+    nodes: Vec<Node>,
+    vertices: usize,
+    /// The node for each edge crossing, by (from, to).
+    arcs: HashMap<(usize, usize), usize>,
+    /// Arena slots of removed edge crossings, for reuse.
+    free: Vec<usize>,
+    rng: u64,
+}
+
+impl fmt::Debug for EulerTourTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EulerTourTree")
+            .field("vertices", &self.vertices)
+            .field("edges", &self.edge_count())
+            .finish()
+    }
+}
+
+impl EulerTourTree {
+    /// Create a new forest of `len` isolated vertices.
+    pub fn new(len: usize) -> Self {
+        let mut forest = Self {
+            nodes: Vec::with_capacity(len),
+            vertices: len,
+            arcs: HashMap::new(),
+            free: Vec::new(),
+            // xorshift gets stuck at zero.
+            rng: DEFAULT_SEED | 1,
+        };
+        for _ in 0..len {
+            let node = forest.new_node();
+            forest.nodes.push(node);
+        }
+        forest
+    }
+
+    /// Returns the number of vertices.
+    pub fn len(&self) -> usize {
+        self.vertices
+    }
+
+    /// Returns true if there are no vertices.
+    pub fn is_empty(&self) -> bool {
+        self.vertices == 0
+    }
+
+    /// Returns the number of edges.
+    pub fn edge_count(&self) -> usize {
+        self.arcs.len() / 2
+    }
+
+    /// Check if there is an edge between `u` and `v`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either vertex is out of bounds.
+    pub fn contains_edge(&self, u: usize, v: usize) -> bool {
+        self.check(u);
+        self.check(v);
+        self.arcs.contains_key(&(u, v))
+    }
+
+    /// Check if `u` and `v` are in the same tree.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either vertex is out of bounds.
+    pub fn connected(&self, u: usize, v: usize) -> bool {
+        self.check(u);
+        self.check(v);
+        self.root(u) == self.root(v)
+    }
+
+    /// Returns the number of vertices in the tree containing `v`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `v` is out of bounds.
+    pub fn tree_size(&self, v: usize) -> usize {
+        self.check(v);
+        (self.size(self.root(v)) + 2) / 3
+    }
+
+    /// Add an edge between `u` and `v`.
+    ///
+    /// Returns false, changing nothing, if they are already connected.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either vertex is out of bounds.
+    pub fn link(&mut self, u: usize, v: usize) -> bool {
+        if self.connected(u, v) {
+            return false;
+        }
+        let u_tour = self.reroot(u);
+        let v_tour = self.reroot(v);
+        let down = self.alloc();
+        let up = self.alloc();
+        self.arcs.insert((u, v), down);
+        self.arcs.insert((v, u), up);
+        let tour = self.concat(u_tour, down);
+        let tour = self.concat(tour, v_tour);
+        self.concat(tour, up);
+        true
+    }
+
+    /// Remove the edge between `u` and `v`.
+    ///
+    /// Returns false if there is no such edge.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either vertex is out of bounds.
+    pub fn cut(&mut self, u: usize, v: usize) -> bool {
+        self.check(u);
+        self.check(v);
+        let Some(down) = self.arcs.remove(&(u, v)) else {
+            return false;
+        };
+        let up = self
+            .arcs
+            .remove(&(v, u))
+            .expect("edges are stored both ways");
+        let (mut first, mut second) = (self.position(down), self.position(up));
+        if first > second {
+            std::mem::swap(&mut first, &mut second);
+        }
+        // The tour is: before, one crossing, the tour of the far side,
+        // the other crossing, after.
+        let (before, rest) = self.split(self.root(down), first);
+        let (_, rest) = self.split(rest, 1);
+        let (_, rest) = self.split(rest, second - first - 1);
+        let (_, after) = self.split(rest, 1);
+        self.concat(before, after);
+        self.free.extend([down, up]);
+        true
+    }
+
+    /// Check the treap invariants, panicking on the first violation.
+    ///
+    /// Checks that child and parent pointers agree, that subtree sizes are
+    /// right, that priorities are heap-ordered, that every tour has 3k - 2
+    /// entries for its k vertices, and that the edge map covers exactly
+    /// the live edge crossings.
+    pub fn debug_validate(&self) {
+        let live = self.nodes.len() - self.free.len();
+        assert_eq!(live, self.vertices + self.arcs.len(), "arena leaks nodes");
+        for (&(u, v), &arc) in self.arcs.iter() {
+            assert!(self.arcs.contains_key(&(v, u)), "edge stored one way");
+            assert!(self.root(arc) == self.root(u), "edge outside its tour");
+        }
+        let mut tour_vertices = HashMap::new();
+        for v in 0..self.vertices {
+            *tour_vertices.entry(self.root(v)).or_insert(0) += 1;
+        }
+        for (&root, &count) in tour_vertices.iter() {
+            assert_eq!(self.nodes[root].parent, NIL, "root has a parent");
+            let size = self.validate(root);
+            assert_eq!(size, 3 * count - 2, "tour length does not match vertices");
+        }
+    }
+
+    /// Walk a subtree checking links, sizes and priorities, and return its
+    /// size.
+    fn validate(&self, x: usize) -> usize {
+        if x == NIL {
+            return 0;
+        }
+        let node = &self.nodes[x];
+        for child in [node.left, node.right] {
+            if child != NIL {
+                assert_eq!(self.nodes[child].parent, x, "child does not point back");
+                assert!(
+                    self.nodes[child].priority <= node.priority,
+                    "child outranks parent"
+                );
+            }
+        }
+        let size = 1 + self.validate(node.left) + self.validate(node.right);
+        assert_eq!(size, node.size, "size does not match subtree");
+        size
+    }
+
+    fn check(&self, v: usize) {
+        assert!(v < self.vertices, "vertex {} out of bounds", v);
+    }
+
+    fn new_node(&mut self) -> Node {
+        Node {
+            priority: xorshift64(&mut self.rng),
+            parent: NIL,
+            left: NIL,
+            right: NIL,
+            size: 1,
+        }
+    }
+
+    /// Get a fresh single-node tour for an edge crossing.
+    fn alloc(&mut self) -> usize {
+        let node = self.new_node();
+        match self.free.pop() {
+            Some(x) => {
+                self.nodes[x] = node;
+                x
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    fn size(&self, x: usize) -> usize {
+        if x == NIL {
+            0
+        } else {
+            self.nodes[x].size
+        }
+    }
+
+    fn root(&self, mut x: usize) -> usize {
+        while self.nodes[x].parent != NIL {
+            x = self.nodes[x].parent;
+        }
+        x
+    }
+
+    /// Returns the index of `x` in its tour.
+    fn position(&self, mut x: usize) -> usize {
+        let mut position = self.size(self.nodes[x].left);
+        while self.nodes[x].parent != NIL {
+            let parent = self.nodes[x].parent;
+            if self.nodes[parent].right == x {
+                position += self.size(self.nodes[parent].left) + 1;
+            }
+            x = parent;
+        }
+        position
+    }
+
+    /// Recompute the size of `x` and point its children back at it.
+    fn update(&mut self, x: usize) {
+        let (left, right) = (self.nodes[x].left, self.nodes[x].right);
+        for child in [left, right] {
+            if child != NIL {
+                self.nodes[child].parent = x;
+            }
+        }
+        self.nodes[x].size = 1 + self.size(left) + self.size(right);
+    }
+
+    /// Join two tours given by their roots into one, returning its root.
+    fn concat(&mut self, left: usize, right: usize) -> usize {
+        let root = self.merge(left, right);
+        if root != NIL {
+            self.nodes[root].parent = NIL;
+        }
+        root
+    }
+
+    fn merge(&mut self, left: usize, right: usize) -> usize {
+        if left == NIL {
+            return right;
+        }
+        if right == NIL {
+            return left;
+        }
+        if self.nodes[left].priority > self.nodes[right].priority {
+            let merged = self.merge(self.nodes[left].right, right);
+            self.nodes[left].right = merged;
+            self.update(left);
+            left
+        } else {
+            let merged = self.merge(left, self.nodes[right].left);
+            self.nodes[right].left = merged;
+            self.update(right);
+            right
+        }
+    }
+
+    /// Split a tour given by its root into the first `index` entries and
+    /// the rest, returning both roots.
+    fn split(&mut self, root: usize, index: usize) -> (usize, usize) {
+        let (left, right) = self.split_inner(root, index);
+        for part in [left, right] {
+            if part != NIL {
+                self.nodes[part].parent = NIL;
+            }
+        }
+        (left, right)
+    }
+
+    fn split_inner(&mut self, x: usize, index: usize) -> (usize, usize) {
+        if x == NIL {
+            return (NIL, NIL);
+        }
+        let left_size = self.size(self.nodes[x].left);
+        if index <= left_size {
+            let (left, middle) = self.split_inner(self.nodes[x].left, index);
+            self.nodes[x].left = middle;
+            self.update(x);
+            (left, x)
+        } else {
+            let (middle, right) = self.split_inner(self.nodes[x].right, index - left_size - 1);
+            self.nodes[x].right = middle;
+            self.update(x);
+            (x, right)
+        }
+    }
+
+    /// Rotate the tour containing `v` to start at `v`, returning its root.
+    fn reroot(&mut self, v: usize) -> usize {
+        let (before, from_v) = self.split(self.root(v), self.position(v));
+        self.concat(from_v, before)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_cut_connected() {
+        let mut forest = EulerTourTree::new(6);
+        assert!(forest.link(0, 1));
+        assert!(forest.link(1, 2));
+        assert!(forest.link(3, 4));
+        assert!(!forest.link(2, 0), "would close a cycle");
+        assert!(forest.connected(0, 2));
+        assert!(!forest.connected(2, 3));
+        assert_eq!(forest.tree_size(1), 3);
+        assert_eq!(forest.tree_size(5), 1);
+        assert!(forest.link(2, 4));
+        assert!(forest.connected(0, 3));
+        assert!(forest.cut(1, 2));
+        assert!(!forest.cut(1, 2), "already cut");
+        assert!(!forest.connected(0, 3));
+        assert!(forest.connected(2, 3));
+        assert_eq!(forest.edge_count(), 3);
+        forest.debug_validate();
+    }
+
+    #[test]
+    fn test_cut_splits_subtree_tours() {
+        // A star around 0, each leaf with a tail of two vertices.
+        let mut forest = EulerTourTree::new(31);
+        for leaf in 1..=10 {
+            forest.link(0, leaf);
+            forest.link(leaf, leaf + 10);
+            forest.link(leaf + 10, leaf + 20);
+        }
+        forest.debug_validate();
+        assert_eq!(forest.tree_size(30), 31);
+        for leaf in (1..=10).step_by(2) {
+            // Cut with the endpoints in either order.
+            assert!(forest.cut(leaf + 10, leaf));
+            assert!(forest.cut(0, leaf + 1));
+            forest.debug_validate();
+        }
+        // 0 keeps the odd leaves, which lost their tails.
+        assert_eq!(forest.tree_size(0), 6);
+        assert!(forest.connected(1, 9));
+        assert_eq!(forest.tree_size(11), 2);
+        assert_eq!(forest.tree_size(2), 3);
+        assert!(forest.connected(2, 22));
+    }
+
+    #[test]
+    fn test_reuses_freed_edge_slots() {
+        let mut forest = EulerTourTree::new(100);
+        for _ in 0..10 {
+            for v in 1..100 {
+                assert!(forest.link(v - 1, v));
+            }
+            assert_eq!(forest.tree_size(0), 100);
+            for v in 1..100 {
+                assert!(forest.cut(v, v - 1));
+            }
+        }
+        forest.debug_validate();
+        assert_eq!(forest.nodes.len(), 100 + 2 * 99);
+    }
+}
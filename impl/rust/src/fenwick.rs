@@ -0,0 +1,315 @@
+//! Fenwick trees (binary indexed trees) for prefix sums.
+
+use crate::segment_tree::resolve_range;
+use std::ops::{AddAssign, RangeBounds, Sub};
+
+/// The lowest set bit of `i`.
+fn lowbit(i: usize) -> usize {
+    i & i.wrapping_neg()
+}
+
+/// A sequence of numbers with O(log n) point updates and prefix sums.
+///
+/// Slot `i` (counting from 1) holds the sum of the `lowbit(i)` values
+/// ending at `i`, where `lowbit` is the lowest set bit. A prefix sum adds
+/// up one slot per set bit of its length, and an update touches one slot
+/// per level above it, so both are O(log n) over a plain array of n
+/// numbers, half the memory of a [`SegmentTree`](crate::SegmentTree) and
+/// with a shorter loop. The price is generality: range sums are taken as
+/// the difference of two prefix sums, so the operation has to be
+/// invertible, which rules out min and max.
+#[derive(Debug, Clone)]
+pub struct FenwickTree<T> {
+    /// Slot 0 is unused.
+    tree: Vec<T>,
+}
+
+impl<T> FenwickTree<T>
+where
+    T: Copy + Default + AddAssign + Sub<Output = T>,
+{
+    /// Create a new FenwickTree of `len` zeros.
+    pub fn new(len: usize) -> Self {
+        Self {
+            tree: vec![T::default(); len + 1],
+        }
+    }
+
+    /// Build a FenwickTree over the given values in O(n).
+    pub fn from_vec(values: Vec<T>) -> Self {
+        let mut tree = Vec::with_capacity(values.len() + 1);
+        tree.push(T::default());
+        tree.extend(values);
+        for i in 1..tree.len() {
+            let parent = i + lowbit(i);
+            if parent < tree.len() {
+                let value = tree[i];
+                tree[parent] += value;
+            }
+        }
+        Self { tree }
+    }
+
+    /// Returns the number of values.
+    pub fn len(&self) -> usize {
+        self.tree.len() - 1
+    }
+
+    /// Returns true if there are no values.
+    pub fn is_empty(&self) -> bool {
+        self.tree.len() == 1
+    }
+
+    /// Add `delta` to the value at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn add(&mut self, index: usize, delta: T) {
+        assert!(index < self.len(), "index {} out of bounds", index);
+        let mut i = index + 1;
+        while i < self.tree.len() {
+            self.tree[i] += delta;
+            i += lowbit(i);
+        }
+    }
+
+    /// Returns the sum of the first `end` values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `end` is greater than the length.
+    pub fn prefix_sum(&self, end: usize) -> T {
+        assert!(
+            end <= self.len(),
+            "range end {} out of bounds for length {}",
+            end,
+            self.len()
+        );
+        let mut sum = T::default();
+        let mut i = end;
+        while i > 0 {
+            sum += self.tree[i];
+            i -= lowbit(i);
+        }
+        sum
+    }
+
+    /// Returns the sum of the values in `range`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is reversed or runs past the end.
+    pub fn range_sum<R: RangeBounds<usize>>(&self, range: R) -> T {
+        let range = resolve_range(range, self.len());
+        self.prefix_sum(range.end) - self.prefix_sum(range.start)
+    }
+
+    /// Returns the value at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn get(&self, index: usize) -> T {
+        assert!(index < self.len(), "index {} out of bounds", index);
+        self.range_sum(index..=index)
+    }
+}
+
+impl<T> FenwickTree<T>
+where
+    T: Copy + Default + AddAssign + Sub<Output = T> + PartialOrd,
+{
+    /// Returns the smallest index whose prefix sum, through and including
+    /// it, reaches `target`, or `None` if the total falls short.
+    ///
+    /// With values counting occurrences, this finds the position of the
+    /// `target`th element in O(log n) by descending the implicit tree
+    /// instead of binary searching over prefix sums. Values must be
+    /// non-negative, so that prefix sums never decrease.
+    pub fn kth(&self, target: T) -> Option<usize> {
+        let mut position = 0;
+        let mut remaining = target;
+        let mut step = (self.len() + 1).next_power_of_two() / 2;
+        while step > 0 {
+            let next = position + step;
+            if next < self.tree.len() && self.tree[next] < remaining {
+                position = next;
+                remaining = remaining - self.tree[next];
+            }
+            step /= 2;
+        }
+        // `position` values sum to less than the target; the next reaches it.
+        (position < self.len()).then_some(position)
+    }
+}
+
+/// A grid of numbers with O(log rows * log cols) point updates and
+/// rectangle sums.
+///
+/// A Fenwick tree of Fenwick trees: the outer structure runs over rows and
+/// each of its slots is a Fenwick tree over columns, all stored in one
+/// row-major array.
+#[derive(Debug, Clone)]
+pub struct FenwickTree2D<T> {
+    /// `(rows + 1) * (cols + 1)` slots; row 0 and column 0 are unused.
+    tree: Vec<T>,
+    rows: usize,
+    cols: usize,
+}
+
+impl<T> FenwickTree2D<T>
+where
+    T: Copy + Default + AddAssign + Sub<Output = T>,
+{
+    /// Create a new FenwickTree2D of `rows` by `cols` zeros.
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self {
+            tree: vec![T::default(); (rows + 1) * (cols + 1)],
+            rows,
+            cols,
+        }
+    }
+
+    /// Returns the number of rows.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the number of columns.
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Add `delta` to the value at `(row, col)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `row` or `col` is out of bounds.
+    pub fn add(&mut self, row: usize, col: usize, delta: T) {
+        assert!(
+            row < self.rows && col < self.cols,
+            "cell ({}, {}) out of bounds",
+            row,
+            col
+        );
+        let mut i = row + 1;
+        while i <= self.rows {
+            let mut j = col + 1;
+            while j <= self.cols {
+                self.tree[i * (self.cols + 1) + j] += delta;
+                j += lowbit(j);
+            }
+            i += lowbit(i);
+        }
+    }
+
+    /// Returns the sum of the values in the first `rows` rows and first
+    /// `cols` columns.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rows` or `cols` is out of bounds.
+    pub fn prefix_sum(&self, rows: usize, cols: usize) -> T {
+        assert!(
+            rows <= self.rows && cols <= self.cols,
+            "prefix ({}, {}) out of bounds",
+            rows,
+            cols
+        );
+        let mut sum = T::default();
+        let mut i = rows;
+        while i > 0 {
+            let mut j = cols;
+            while j > 0 {
+                sum += self.tree[i * (self.cols + 1) + j];
+                j -= lowbit(j);
+            }
+            i -= lowbit(i);
+        }
+        sum
+    }
+
+    /// Returns the sum of the values in the rectangle `rows` by `cols`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either range is reversed or runs past the end.
+    pub fn rect_sum<R, C>(&self, rows: R, cols: C) -> T
+    where
+        R: RangeBounds<usize>,
+        C: RangeBounds<usize>,
+    {
+        let rows = resolve_range(rows, self.rows);
+        let cols = resolve_range(cols, self.cols);
+        // Inclusion-exclusion, ordered so unsigned types never go negative.
+        let mut sum = self.prefix_sum(rows.end, cols.end);
+        sum += self.prefix_sum(rows.start, cols.start);
+        sum - self.prefix_sum(rows.start, cols.end) - self.prefix_sum(rows.end, cols.start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_and_range_sums() {
+        let values = vec![3i64, -1, 4, 1, -5, 9, 2, 6];
+        let mut built = FenwickTree::from_vec(values.clone());
+        let mut added = FenwickTree::new(values.len());
+        for (i, &value) in values.iter().enumerate() {
+            added.add(i, value);
+        }
+        for end in 0..=values.len() {
+            let expected: i64 = values[..end].iter().sum();
+            assert_eq!(built.prefix_sum(end), expected);
+            assert_eq!(added.prefix_sum(end), expected);
+        }
+        assert_eq!(built.range_sum(2..5), 0);
+        assert_eq!(built.range_sum(5..=5), 9);
+        built.add(4, 10);
+        assert_eq!(built.get(4), 5);
+        assert_eq!(built.range_sum(..), 29);
+    }
+
+    #[test]
+    fn test_kth_finds_position_by_count() {
+        // Occurrence counts of the values 0..8.
+        let counts = FenwickTree::from_vec(vec![0u32, 2, 0, 1, 3, 0, 0, 1]);
+        let positions: Vec<Option<usize>> = (1..=8).map(|k| counts.kth(k)).collect();
+        assert_eq!(
+            positions,
+            [
+                Some(1),
+                Some(1),
+                Some(3),
+                Some(4),
+                Some(4),
+                Some(4),
+                Some(7),
+                None
+            ]
+        );
+        assert_eq!(counts.kth(0), Some(0));
+        assert_eq!(FenwickTree::<u32>::new(0).kth(1), None);
+    }
+
+    #[test]
+    fn test_2d_rect_sums() {
+        let mut grid = FenwickTree2D::new(5, 7);
+        let mut cells = [[0u64; 7]; 5];
+        for (step, (row, col)) in (0..5).flat_map(|r| (0..7).map(move |c| (r, c))).enumerate() {
+            let value = (step * 37 % 11) as u64;
+            grid.add(row, col, value);
+            cells[row][col] += value;
+        }
+        for (rows, cols) in [(0..5, 0..7), (1..4, 2..6), (2..2, 0..7), (4..5, 6..7)] {
+            let expected: u64 = cells[rows.clone()]
+                .iter()
+                .map(|row| row[cols.clone()].iter().sum::<u64>())
+                .sum();
+            assert_eq!(grid.rect_sum(rows, cols), expected);
+        }
+    }
+}
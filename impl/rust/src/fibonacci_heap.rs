@@ -0,0 +1,494 @@
+//! Fibonacci heap with decrease-key and meld.
+
+/// Index of a missing node: no parent, no child, or an empty heap.
+const NIL: usize = usize::MAX;
+
+/// Refers to an element pushed onto a [`FibonacciHeap`], for
+/// [`decrease_key`](FibonacciHeap::decrease_key).
+///
+/// A handle goes stale once its element is popped; using it afterwards
+/// panics, even if the slot has been reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HeapHandle {
+    index: usize,
+    generation: u32,
+}
+
+#[derive(Debug, Clone)]
+struct Node<T> {
+    /// `None` while the slot is on the free list.
+    item: Option<T>,
+    generation: u32,
+    parent: usize,
+    /// Any one child; the children form a circular list through
+    /// `left`/`right`.
+    child: usize,
+    left: usize,
+    right: usize,
+    degree: usize,
+    /// Lost a child since it last became a child itself.
+    marked: bool,
+}
+
+/// A min-heap that defers all restructuring until a pop.
+///
+/// The heap is a circular list of heap-ordered trees. Push and meld only
+/// splice into the root list, O(1). Pop removes the minimum root, promotes
+/// its children to roots, then consolidates, linking roots of equal degree
+/// until every degree is distinct, O(log n) amortized. Decrease-key cuts
+/// the node out to the root list if it now beats its parent, and a parent
+/// that loses a second child is cut in turn; that cascading cut keeps every
+/// subtree of degree `d` at least Fibonacci(d + 2) nodes in size, and makes
+/// decrease-key O(1) amortized. Those bounds are why Dijkstra's algorithm
+/// is O(E + V log V) on paper; in practice the pointer chasing usually
+/// loses to a [`BinaryHeap`](crate::BinaryHeap).
+///
+/// Nodes live in an arena indexed by position, so handles are plain
+/// indices checked by a per-slot generation.
+#[derive(Debug, Clone)]
+pub struct FibonacciHeap<T> {
+    nodes: Vec<Node<T>>,
+    free: Vec<usize>,
+    min: usize,
+    len: usize,
+}
+
+impl<T: Ord> Default for FibonacciHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> FibonacciHeap<T> {
+    /// Create a new empty FibonacciHeap.
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            free: Vec::new(),
+            min: NIL,
+            len: 0,
+        }
+    }
+
+    /// Returns the number of elements in the heap.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the heap contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn item(&self, node: usize) -> &T {
+        self.nodes[node].item.as_ref().expect("live node")
+    }
+
+    fn check(&self, handle: HeapHandle) -> usize {
+        let live = self
+            .nodes
+            .get(handle.index)
+            .is_some_and(|node| node.generation == handle.generation && node.item.is_some());
+        assert!(live, "stale heap handle");
+        handle.index
+    }
+
+    /// Insert `node` into the circular list next to `anchor`.
+    fn splice(&mut self, anchor: usize, node: usize) {
+        let right = self.nodes[anchor].right;
+        self.nodes[node].left = anchor;
+        self.nodes[node].right = right;
+        self.nodes[anchor].right = node;
+        self.nodes[right].left = node;
+    }
+
+    /// Take `node` out of its circular list, leaving it a list of one.
+    fn unlink(&mut self, node: usize) {
+        let Node { left, right, .. } = self.nodes[node];
+        self.nodes[left].right = right;
+        self.nodes[right].left = left;
+        self.nodes[node].left = node;
+        self.nodes[node].right = node;
+    }
+
+    /// Add a lone node to the root list, updating the minimum.
+    fn add_root(&mut self, node: usize) {
+        self.nodes[node].parent = NIL;
+        if self.min == NIL {
+            self.min = node;
+        } else {
+            self.splice(self.min, node);
+            if self.item(node) < self.item(self.min) {
+                self.min = node;
+            }
+        }
+    }
+
+    /// Push an element onto the heap, returning a handle to it.
+    pub fn push(&mut self, item: T) -> HeapHandle {
+        let index = match self.free.pop() {
+            Some(index) => {
+                let node = &mut self.nodes[index];
+                node.item = Some(item);
+                node.generation = node.generation.wrapping_add(1);
+                index
+            }
+            None => {
+                self.nodes.push(Node {
+                    item: Some(item),
+                    generation: 0,
+                    parent: NIL,
+                    child: NIL,
+                    left: NIL,
+                    right: NIL,
+                    degree: 0,
+                    marked: false,
+                });
+                self.nodes.len() - 1
+            }
+        };
+        let node = &mut self.nodes[index];
+        node.parent = NIL;
+        node.child = NIL;
+        node.left = index;
+        node.right = index;
+        node.degree = 0;
+        node.marked = false;
+        self.add_root(index);
+        self.len += 1;
+        HeapHandle {
+            index,
+            generation: self.nodes[index].generation,
+        }
+    }
+
+    /// Get a reference to the smallest element, or `None` if the heap is
+    /// empty.
+    pub fn peek(&self) -> Option<&T> {
+        (self.min != NIL).then(|| self.item(self.min))
+    }
+
+    /// Get a reference to the element behind `handle`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the handle is stale.
+    pub fn get(&self, handle: HeapHandle) -> &T {
+        self.item(self.check(handle))
+    }
+
+    /// Remove and return the smallest element, or `None` if the heap is
+    /// empty.
+    pub fn pop(&mut self) -> Option<T> {
+        if self.min == NIL {
+            return None;
+        }
+        let min = self.min;
+        // Promote the children to roots.
+        let mut child = self.nodes[min].child;
+        while child != NIL {
+            let next = self.nodes[child].right;
+            let last = next == child;
+            self.unlink(child);
+            self.nodes[child].parent = NIL;
+            self.nodes[child].marked = false;
+            self.splice(min, child);
+            child = if last { NIL } else { next };
+        }
+        let next_root = self.nodes[min].right;
+        self.unlink(min);
+        self.min = if next_root == min { NIL } else { next_root };
+        if self.min != NIL {
+            self.consolidate();
+        }
+        self.len -= 1;
+        let node = &mut self.nodes[min];
+        node.child = NIL;
+        self.free.push(min);
+        node.item.take()
+    }
+
+    /// Link roots of equal degree until all degrees differ, then find the
+    /// new minimum.
+    fn consolidate(&mut self) {
+        let mut roots = Vec::new();
+        let mut root = self.min;
+        loop {
+            roots.push(root);
+            root = self.nodes[root].right;
+            if root == self.min {
+                break;
+            }
+        }
+        let mut by_degree: Vec<usize> = Vec::new();
+        for mut root in roots {
+            let mut degree = self.nodes[root].degree;
+            loop {
+                if degree >= by_degree.len() {
+                    by_degree.resize(degree + 1, NIL);
+                }
+                let other = by_degree[degree];
+                if other == NIL {
+                    break;
+                }
+                by_degree[degree] = NIL;
+                let (parent, child) = if self.item(other) < self.item(root) {
+                    (other, root)
+                } else {
+                    (root, other)
+                };
+                self.link(child, parent);
+                root = parent;
+                degree += 1;
+            }
+            by_degree[degree] = root;
+        }
+        // Degrees are bounded by log_phi(n), about 1.44 log2(n).
+        debug_assert!(
+            by_degree.len() <= 2 + 3 * (usize::BITS - self.len.leading_zeros()) as usize / 2
+        );
+        self.min = NIL;
+        for root in by_degree.into_iter().filter(|&root| root != NIL) {
+            if self.min == NIL || self.item(root) < self.item(self.min) {
+                self.min = root;
+            }
+        }
+    }
+
+    /// Make root `child` a child of root `parent`.
+    fn link(&mut self, child: usize, parent: usize) {
+        self.unlink(child);
+        let first = self.nodes[parent].child;
+        if first == NIL {
+            self.nodes[parent].child = child;
+        } else {
+            self.splice(first, child);
+        }
+        self.nodes[child].parent = parent;
+        self.nodes[child].marked = false;
+        self.nodes[parent].degree += 1;
+    }
+
+    /// Lower the element behind `handle` to `item`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the handle is stale or `item` is greater than the current
+    /// element.
+    pub fn decrease_key(&mut self, handle: HeapHandle, item: T) {
+        let node = self.check(handle);
+        assert!(
+            item <= *self.item(node),
+            "decrease_key cannot increase an element"
+        );
+        self.nodes[node].item = Some(item);
+        let parent = self.nodes[node].parent;
+        if parent != NIL && self.item(node) < self.item(parent) {
+            self.cut(node);
+            self.cascading_cut(parent);
+        }
+        if self.item(node) < self.item(self.min) {
+            self.min = node;
+        }
+    }
+
+    /// Move `node` from its parent's child list to the root list.
+    fn cut(&mut self, node: usize) {
+        let parent = self.nodes[node].parent;
+        if self.nodes[parent].child == node {
+            let next = self.nodes[node].right;
+            self.nodes[parent].child = if next == node { NIL } else { next };
+        }
+        self.unlink(node);
+        self.nodes[parent].degree -= 1;
+        self.nodes[node].marked = false;
+        self.add_root(node);
+    }
+
+    /// Cut marked ancestors, stopping at the first unmarked one, which
+    /// becomes marked.
+    fn cascading_cut(&mut self, mut node: usize) {
+        while self.nodes[node].parent != NIL {
+            if !self.nodes[node].marked {
+                self.nodes[node].marked = true;
+                return;
+            }
+            let parent = self.nodes[node].parent;
+            self.cut(node);
+            node = parent;
+        }
+    }
+
+    /// Move every element of `other` into this heap.
+    ///
+    /// The root lists are spliced in O(1), but `other`'s arena is copied
+    /// across, so this costs O(other.len()). Handles from `other` are not
+    /// valid for the melded heap.
+    pub fn meld(&mut self, other: Self) {
+        if other.min == NIL {
+            return;
+        }
+        let offset = self.nodes.len();
+        let shift = |index: usize| if index == NIL { NIL } else { index + offset };
+        self.nodes.extend(other.nodes.into_iter().map(|node| Node {
+            parent: shift(node.parent),
+            child: shift(node.child),
+            left: shift(node.left),
+            right: shift(node.right),
+            ..node
+        }));
+        self.free.extend(other.free.into_iter().map(shift));
+        self.len += other.len;
+        let other_min = shift(other.min);
+        if self.min == NIL {
+            self.min = other_min;
+            return;
+        }
+        // Join the two circular root lists between each minimum and its
+        // right neighbour.
+        let self_right = self.nodes[self.min].right;
+        let other_right = self.nodes[other_min].right;
+        self.nodes[self.min].right = other_right;
+        self.nodes[other_right].left = self.min;
+        self.nodes[other_min].right = self_right;
+        self.nodes[self_right].left = other_min;
+        if self.item(other_min) < self.item(self.min) {
+            self.min = other_min;
+        }
+    }
+
+    /// Check the Fibonacci heap invariants, panicking on the first
+    /// violation.
+    ///
+    /// Checks that the circular lists are consistently linked, that every
+    /// child names its parent and no root has one, that every node is heap
+    /// ordered and no smaller than the minimum root, that each degree
+    /// counts its children and its subtree holds at least Fibonacci(d + 2)
+    /// nodes, that no root is marked, and that the node count matches `len`.
+    pub fn debug_validate(&self) {
+        fn siblings<T>(nodes: &[Node<T>], first: usize) -> Vec<usize> {
+            let mut list = Vec::new();
+            let mut node = first;
+            loop {
+                assert_eq!(nodes[nodes[node].right].left, node, "broken sibling links");
+                list.push(node);
+                node = nodes[node].right;
+                if node == first {
+                    return list;
+                }
+            }
+        }
+
+        fn walk<T: Ord>(heap: &FibonacciHeap<T>, node: usize) -> usize {
+            let entry = &heap.nodes[node];
+            let mut size = 1;
+            let children = if entry.child == NIL {
+                Vec::new()
+            } else {
+                siblings(&heap.nodes, entry.child)
+            };
+            assert_eq!(
+                children.len(),
+                entry.degree,
+                "degree does not match children"
+            );
+            for child in children {
+                assert_eq!(heap.nodes[child].parent, node, "child with wrong parent");
+                assert!(heap.item(node) <= heap.item(child), "heap order violated");
+                size += walk(heap, child);
+            }
+            // Fibonacci(d + 2), the smallest subtree a degree-d node can have.
+            let (mut a, mut b) = (1usize, 1usize);
+            for _ in 0..entry.degree {
+                (a, b) = (b, a + b);
+            }
+            assert!(size >= b, "subtree too small for its degree");
+            size
+        }
+
+        if self.min == NIL {
+            assert_eq!(self.len, 0, "len does not match node count");
+            return;
+        }
+        let mut count = 0;
+        for root in siblings(&self.nodes, self.min) {
+            assert_eq!(self.nodes[root].parent, NIL, "root with a parent");
+            assert!(!self.nodes[root].marked, "marked root");
+            assert!(
+                self.item(self.min) <= self.item(root),
+                "min is not the smallest root"
+            );
+            count += walk(self, root);
+        }
+        assert_eq!(count, self.len, "len does not match node count");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate<T: Ord>(heap: &FibonacciHeap<T>) {
+        if cfg!(debug_assertions) {
+            heap.debug_validate();
+        }
+    }
+
+    #[test]
+    fn test_push_pop_sorted() {
+        let mut heap = FibonacciHeap::new();
+        for i in 0..500u32 {
+            heap.push(i * 7919 % 503);
+        }
+        validate(&heap);
+        let mut previous = None;
+        while let Some(item) = heap.pop() {
+            assert!(previous <= Some(item));
+            previous = Some(item);
+            if heap.len() % 50 == 0 {
+                validate(&heap);
+            }
+        }
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn test_decrease_key_cascades() {
+        let mut heap = FibonacciHeap::new();
+        let handles: Vec<HeapHandle> = (0..64).map(|i| heap.push(100 + i)).collect();
+        // One pop consolidates everything into a few deep trees.
+        assert_eq!(heap.pop(), Some(100));
+        validate(&heap);
+        for (step, &handle) in handles[1..].iter().rev().enumerate() {
+            heap.decrease_key(handle, 99 - step as i32);
+            validate(&heap);
+        }
+        assert_eq!(heap.peek(), Some(&37));
+        assert_eq!(*heap.get(handles[63]), 99);
+        let drained: Vec<i32> = std::iter::from_fn(|| heap.pop()).collect();
+        assert_eq!(drained, (37..100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_meld_and_stale_handles() {
+        let mut left = FibonacciHeap::new();
+        let mut right = FibonacciHeap::new();
+        for i in 0..20 {
+            left.push(2 * i);
+            right.push(2 * i + 1);
+        }
+        right.pop();
+        left.meld(right);
+        validate(&left);
+        assert_eq!(left.len(), 39);
+        let drained: Vec<i32> = std::iter::from_fn(|| left.pop()).collect();
+        assert_eq!(drained[..3], [0, 2, 3]);
+
+        let mut heap = FibonacciHeap::new();
+        let handle = heap.push(1);
+        heap.pop();
+        heap.push(2);
+        let stale = std::panic::catch_unwind(move || heap.decrease_key(handle, 0));
+        assert!(stale.is_err());
+    }
+}
@@ -0,0 +1,294 @@
+//! All-pairs shortest paths on a [`Graph`] by Floyd-Warshall, plain and
+//! cache-blocked.
+
+use crate::graph::{Graph, NodeId};
+use crate::matrix::Matrix;
+use crate::shortest_path::{bellman_ford, NegativeCycle, Relaxation};
+use std::ops::{Add, Range};
+
+/// Marks a missing hop in the path table.
+const NIL: usize = usize::MAX;
+
+/// Shortest distances and paths between every pair of nodes, as dense
+/// V x V matrices.
+///
+/// Lookups take ids of the graph as it was when the paths were computed.
+#[derive(Debug, Clone)]
+pub struct AllPairsPaths<W> {
+    /// The node at each matrix position.
+    ids: Vec<NodeId>,
+    /// Each node's matrix position by [`NodeId::index`].
+    positions: Vec<usize>,
+    /// Distances by matrix position, `None` where unreachable.
+    distances: Matrix<Option<W>>,
+    /// The position of the node before the column's on a shortest path
+    /// from the row's, `NIL` on the diagonal and where unreachable.
+    parents: Matrix<usize>,
+}
+
+impl<W: Copy> AllPairsPaths<W> {
+    fn position(&self, node: NodeId) -> Option<usize> {
+        self.positions
+            .get(node.index())
+            .copied()
+            .filter(|&position| position != NIL)
+    }
+
+    /// Returns the length of a shortest path from `source` to `target`, or
+    /// `None` if there is none.
+    pub fn distance(&self, source: NodeId, target: NodeId) -> Option<W> {
+        let (i, j) = (self.position(source)?, self.position(target)?);
+        self.distances[(i, j)]
+    }
+
+    /// Returns the nodes of a shortest path from `source` to `target`,
+    /// both included, or `None` if there is none.
+    pub fn path(&self, source: NodeId, target: NodeId) -> Option<Vec<NodeId>> {
+        self.distance(source, target)?;
+        let (i, mut j) = (self.position(source)?, self.position(target)?);
+        let mut path = vec![target];
+        while j != i {
+            j = self.parents[(i, j)];
+            path.push(self.ids[j]);
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// Returns shortest paths between every pair of nodes, or a negative cycle
+/// if the graph has one. `W::default()` is taken as zero.
+///
+/// Considers each node in turn as a possible midpoint of every path, in
+/// Θ(V³) time and Θ(V²) space whatever the number of edges. That suits
+/// dense graphs; on sparse ones a [`dijkstra`](crate::dijkstra) from each
+/// node is faster. In an undirected graph a single negative edge is a
+/// negative cycle.
+///
+/// The paths come from a shortest-path tree per source, grown over the
+/// edges that the final distances show to be tight. Midpoint pointers
+/// kept during the loop would be cheaper, but under the blocked order
+/// they can chase each other around a cycle of zero weight.
+pub fn floyd_warshall<N, W>(graph: &Graph<N, W>) -> Result<AllPairsPaths<W>, NegativeCycle>
+where
+    W: Copy + Ord + Add<Output = W> + Default,
+{
+    let mut paths = initial(graph);
+    let n = paths.ids.len();
+    relax_block(&mut paths, 0..n, 0..n, 0..n);
+    finish(graph, paths)
+}
+
+/// Returns the same as [`floyd_warshall`], working through the matrices in
+/// `block` x `block` tiles.
+///
+/// The plain loop streams whole rows of the V x V matrices once per
+/// midpoint, which misses the cache once a matrix outgrows it. The blocked
+/// loop runs every midpoint of one block of rows and columns over one tile
+/// at a time, in the three phases that respect Floyd-Warshall's order:
+/// the diagonal tile first, then the tiles sharing its rows or columns,
+/// then the rest. Each tile is small enough to stay cached for all of its
+/// midpoints. The distances come out identical.
+///
+/// # Panics
+///
+/// Panics if `block` is zero.
+pub fn floyd_warshall_blocked<N, W>(
+    graph: &Graph<N, W>,
+    block: usize,
+) -> Result<AllPairsPaths<W>, NegativeCycle>
+where
+    W: Copy + Ord + Add<Output = W> + Default,
+{
+    assert!(block > 0, "block size must be positive");
+    let mut paths = initial(graph);
+    let n = paths.ids.len();
+    let tile = |b: usize| b * block..((b + 1) * block).min(n);
+    let blocks = (n + block - 1) / block;
+    for k in 0..blocks {
+        relax_block(&mut paths, tile(k), tile(k), tile(k));
+        for b in (0..blocks).filter(|&b| b != k) {
+            relax_block(&mut paths, tile(k), tile(k), tile(b));
+            relax_block(&mut paths, tile(k), tile(b), tile(k));
+        }
+        for i in (0..blocks).filter(|&i| i != k) {
+            for j in (0..blocks).filter(|&j| j != k) {
+                relax_block(&mut paths, tile(k), tile(i), tile(j));
+            }
+        }
+    }
+    finish(graph, paths)
+}
+
+/// Build the distances of paths of at most one edge.
+fn initial<N, W>(graph: &Graph<N, W>) -> AllPairsPaths<W>
+where
+    W: Copy + Ord + Default,
+{
+    let ids: Vec<NodeId> = graph.nodes().map(|(id, _)| id).collect();
+    let n = ids.len();
+    let mut positions = vec![NIL; graph.node_bound()];
+    for (position, id) in ids.iter().enumerate() {
+        positions[id.index()] = position;
+    }
+    let mut distances = Matrix::filled(n, n, None);
+    for i in 0..n {
+        distances[(i, i)] = Some(W::default());
+    }
+    for edge in graph.edges() {
+        let (i, j) = (
+            positions[edge.source.index()],
+            positions[edge.target.index()],
+        );
+        let reverse = (!graph.is_directed()).then_some((j, i));
+        for (i, j) in std::iter::once((i, j)).chain(reverse) {
+            if distances[(i, j)].map_or(true, |d| *edge.weight < d) {
+                distances[(i, j)] = Some(*edge.weight);
+            }
+        }
+    }
+    AllPairsPaths {
+        ids,
+        positions,
+        distances,
+        parents: Matrix::filled(n, n, NIL),
+    }
+}
+
+/// Relax every path from a row in `rows` to a column in `columns` through
+/// each midpoint in `midpoints`, taken in order.
+fn relax_block<W>(
+    paths: &mut AllPairsPaths<W>,
+    midpoints: Range<usize>,
+    rows: Range<usize>,
+    columns: Range<usize>,
+) where
+    W: Copy + Ord + Add<Output = W>,
+{
+    // The midpoint's own row cannot change while it is the midpoint, short
+    // of a negative cycle, so a copy of it lets each row be walked as a
+    // plain slice.
+    let mut from_k = Vec::with_capacity(columns.len());
+    for k in midpoints {
+        from_k.clear();
+        from_k.extend_from_slice(&paths.distances.row(k)[columns.clone()]);
+        for i in rows.clone() {
+            let Some(to_k) = paths.distances[(i, k)] else {
+                continue;
+            };
+            let row = &mut paths.distances.row_mut(i)[columns.clone()];
+            for (cell, from_k) in row.iter_mut().zip(&from_k) {
+                let Some(from_k) = *from_k else {
+                    continue;
+                };
+                let through = to_k + from_k;
+                if cell.map_or(true, |d| through < d) {
+                    *cell = Some(through);
+                }
+            }
+        }
+    }
+}
+
+/// Fill in the shortest-path trees, unless a node has a negative path to
+/// itself, in which case find the cycle it lies on.
+fn finish<N, W>(
+    graph: &Graph<N, W>,
+    mut paths: AllPairsPaths<W>,
+) -> Result<AllPairsPaths<W>, NegativeCycle>
+where
+    W: Copy + Ord + Add<Output = W> + Default,
+{
+    let n = paths.ids.len();
+    let zero = W::default();
+    if let Some(i) = (0..n).find(|&i| paths.distances[(i, i)].is_some_and(|d| d < zero)) {
+        return match bellman_ford(graph, paths.ids[i], Relaxation::Rounds) {
+            Err(cycle) => Err(cycle),
+            Ok(_) => unreachable!("a negative cycle passes through the node"),
+        };
+    }
+    let mut adjacency = vec![Vec::new(); n];
+    for edge in graph.edges() {
+        let (i, j) = (
+            paths.positions[edge.source.index()],
+            paths.positions[edge.target.index()],
+        );
+        adjacency[i].push((j, *edge.weight));
+        if !graph.is_directed() {
+            adjacency[j].push((i, *edge.weight));
+        }
+    }
+    // Every edge on a shortest path is tight, so a search over tight edges
+    // reaches each reachable node, and visiting each once makes a tree.
+    let mut stack = Vec::new();
+    for i in 0..n {
+        stack.push(i);
+        while let Some(u) = stack.pop() {
+            let to_u = paths.distances[(i, u)].expect("searched nodes are reachable");
+            for &(v, weight) in &adjacency[u] {
+                if v != i
+                    && paths.parents[(i, v)] == NIL
+                    && paths.distances[(i, v)] == Some(to_u + weight)
+                {
+                    paths.parents[(i, v)] = u;
+                    stack.push(v);
+                }
+            }
+        }
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paths_through_midpoints() {
+        let mut graph = Graph::directed();
+        let n: Vec<_> = (0..4).map(|_| graph.add_node(())).collect();
+        for (source, target, weight) in [(0, 1, 3), (1, 2, -2), (0, 2, 4), (2, 3, 1), (3, 0, 2)] {
+            graph.add_edge(n[source], n[target], weight);
+        }
+        for paths in [
+            floyd_warshall(&graph).unwrap(),
+            floyd_warshall_blocked(&graph, 3).unwrap(),
+        ] {
+            assert_eq!(paths.distance(n[0], n[3]), Some(2));
+            assert_eq!(paths.path(n[0], n[3]), Some(vec![n[0], n[1], n[2], n[3]]));
+            assert_eq!(paths.distance(n[3], n[2]), Some(3));
+            assert_eq!(paths.path(n[2], n[2]), Some(vec![n[2]]));
+        }
+    }
+
+    #[test]
+    fn test_unreachable_and_removed_nodes() {
+        let mut graph = Graph::undirected();
+        let a = graph.add_node(());
+        let gone = graph.add_node(());
+        let b = graph.add_node(());
+        let c = graph.add_node(());
+        graph.add_edge(a, b, 5u32);
+        graph.add_edge(b, gone, 1);
+        graph.remove_node(gone);
+        let paths = floyd_warshall(&graph).unwrap();
+        assert_eq!(paths.distance(b, a), Some(5));
+        assert_eq!(paths.path(b, a), Some(vec![b, a]));
+        assert_eq!(paths.distance(a, c), None);
+        assert_eq!(paths.path(a, c), None);
+        assert_eq!(paths.distance(a, gone), None);
+    }
+
+    #[test]
+    fn test_negative_cycle() {
+        let mut graph = Graph::directed();
+        let n: Vec<_> = (0..3).map(|_| graph.add_node(())).collect();
+        graph.add_edge(n[0], n[1], 1);
+        graph.add_edge(n[1], n[2], -3);
+        graph.add_edge(n[2], n[1], 2);
+        let cycle = floyd_warshall_blocked(&graph, 2).unwrap_err();
+        let mut nodes = cycle.nodes;
+        nodes.sort();
+        assert_eq!(nodes, [n[1], n[2]]);
+    }
+}
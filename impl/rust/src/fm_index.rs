@@ -0,0 +1,301 @@
+//! Burrows-Wheeler transform and the FM-index built on it.
+//!
+//! The transform sorts every rotation of the text followed by a sentinel
+//! that sorts below every byte, and keeps the last column. Rows are
+//! numbered in sorted order, so row 0 is the rotation starting with the
+//! sentinel. The sentinel is not a byte, so the last column is returned
+//! without it, together with the row it occupied.
+
+use crate::suffix_array::suffix_array;
+use std::fmt;
+
+/// Rows between occurrence checkpoints of an [`FmIndex`].
+const CHECKPOINT_INTERVAL: usize = 128;
+/// Text positions between suffix array samples of an [`FmIndex`].
+const SAMPLE_INTERVAL: usize = 32;
+
+/// The start of the suffix each row of the sorted rotations begins with:
+/// the empty suffix, then the suffix array.
+fn sorted_rows(text: &[u8]) -> Vec<usize> {
+    let mut rows = Vec::with_capacity(text.len() + 1);
+    rows.push(text.len());
+    rows.extend(suffix_array(text));
+    rows
+}
+
+/// Returns the Burrows-Wheeler transform of `text`: the last column without
+/// the sentinel, and the row the sentinel was in.
+///
+/// Built from the suffix array in O(n log² n).
+pub fn bwt(text: &[u8]) -> (Vec<u8>, usize) {
+    let mut last = Vec::with_capacity(text.len());
+    let mut sentinel = 0;
+    for (row, start) in sorted_rows(text).into_iter().enumerate() {
+        match start.checked_sub(1) {
+            Some(before) => last.push(text[before]),
+            None => sentinel = row,
+        }
+    }
+    (last, sentinel)
+}
+
+/// Returns the text whose Burrows-Wheeler transform is `last` with the
+/// sentinel at row `sentinel`, in O(n).
+///
+/// Walks the LF mapping: the `k`th occurrence of a byte in the last column
+/// and its `k`th occurrence in the first column are the same text byte, so
+/// stepping from a row to the row of its last byte moves one byte back
+/// through the text.
+///
+/// # Panics
+///
+/// Panics if `sentinel` is greater than `last.len()`. The result is
+/// meaningless if the input is not a transform.
+pub fn inverse_bwt(last: &[u8], sentinel: usize) -> Vec<u8> {
+    assert!(
+        sentinel <= last.len(),
+        "sentinel row {} out of bounds for length {}",
+        sentinel,
+        last.len() + 1
+    );
+    let starts = first_rows(last);
+    let mut seen = [0; 256];
+    // lf[row] for every row but the sentinel's, which maps to row 0.
+    let mut lf = vec![0; last.len() + 1];
+    for (i, &byte) in last.iter().enumerate() {
+        let row = if i < sentinel { i } else { i + 1 };
+        lf[row] = starts[byte as usize] + seen[byte as usize];
+        seen[byte as usize] += 1;
+    }
+    // Row 0 begins with the sentinel, so it ends with the text's last byte.
+    let mut text = vec![0; last.len()];
+    let mut row = 0;
+    for i in (0..last.len()).rev() {
+        text[i] = last[if row < sentinel { row } else { row - 1 }];
+        row = lf[row];
+    }
+    text
+}
+
+/// The first row beginning with each byte, given the bytes of the last
+/// column. Row 0 begins with the sentinel.
+fn first_rows(last: &[u8]) -> [usize; 256] {
+    let mut counts = [0; 256];
+    for &byte in last {
+        counts[byte as usize] += 1;
+    }
+    let mut starts = [0; 256];
+    let mut row = 1;
+    for (start, count) in starts.iter_mut().zip(counts) {
+        *start = row;
+        row += count;
+    }
+    starts
+}
+
+/// A compressed-suffix-array index over a byte string: counts and locates
+/// every occurrence of a pattern without scanning the text.
+///
+/// The index keeps the Burrows-Wheeler transform of the text. The rows
+/// beginning with a pattern form one contiguous range, and backward search
+/// narrows it one pattern byte at a time, from the last, using only the
+/// rank of that byte in the last column. Ranks come from per-byte counts
+/// checkpointed every 128 rows plus a scan of at most 127 bytes, so
+/// [`count`](Self::count) takes O(m) rank queries whatever the text's
+/// length. [`locate`](Self::locate) then walks each matching row back
+/// through the text to the nearest sampled suffix array entry, one every
+/// 32 text positions, for at most 31 steps per occurrence. Construction
+/// sorts the suffixes in O(n log² n). The index holds about 9.5 bytes per
+/// text byte and no copy of the text, where a plain suffix array takes 8
+/// bytes per text byte and needs the text beside it.
+#[derive(Clone)]
+pub struct FmIndex {
+    /// The last column, with a 0 byte standing in for the sentinel.
+    last: Vec<u8>,
+    sentinel: usize,
+    /// `starts[c]` is the first row beginning with byte `c`.
+    starts: [usize; 256],
+    /// `checkpoints[i][c]` counts byte `c` in the last column's first
+    /// `i * CHECKPOINT_INTERVAL` rows, the sentinel excluded.
+    checkpoints: Vec<[u32; 256]>,
+    /// `(row, start)` for every row whose suffix starts at a multiple of
+    /// `SAMPLE_INTERVAL`, ordered by row.
+    samples: Vec<(usize, usize)>,
+}
+
+impl FmIndex {
+    /// Build an index over `text`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `text` is `u32::MAX` bytes or longer.
+    pub fn new(text: &[u8]) -> Self {
+        assert!(
+            text.len() < u32::MAX as usize,
+            "FM-index text must be shorter than u32::MAX bytes"
+        );
+        let rows = sorted_rows(text);
+        let mut last = Vec::with_capacity(rows.len());
+        let mut sentinel = 0;
+        let mut samples = Vec::new();
+        for (row, &start) in rows.iter().enumerate() {
+            match start.checked_sub(1) {
+                Some(before) => last.push(text[before]),
+                None => {
+                    sentinel = row;
+                    last.push(0);
+                }
+            }
+            if start % SAMPLE_INTERVAL == 0 {
+                samples.push((row, start));
+            }
+        }
+        let mut checkpoints = Vec::with_capacity(last.len() / CHECKPOINT_INTERVAL + 1);
+        let mut counts = [0u32; 256];
+        for (row, &byte) in last.iter().enumerate() {
+            if row % CHECKPOINT_INTERVAL == 0 {
+                checkpoints.push(counts);
+            }
+            if row != sentinel {
+                counts[byte as usize] += 1;
+            }
+        }
+        if last.len() % CHECKPOINT_INTERVAL == 0 {
+            checkpoints.push(counts);
+        }
+        Self {
+            starts: first_rows(text),
+            last,
+            sentinel,
+            checkpoints,
+            samples,
+        }
+    }
+
+    /// Returns the length of the indexed text.
+    pub fn len(&self) -> usize {
+        self.last.len() - 1
+    }
+
+    /// Returns true if the indexed text is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the Burrows-Wheeler transform of the text, as [`bwt`] does.
+    pub fn bwt(&self) -> (Vec<u8>, usize) {
+        let mut last = self.last.clone();
+        last.remove(self.sentinel);
+        (last, self.sentinel)
+    }
+
+    /// Returns the number of occurrences of `byte` in the last column's
+    /// first `row` rows.
+    fn rank(&self, byte: u8, row: usize) -> usize {
+        let checkpoint = row / CHECKPOINT_INTERVAL;
+        let from = checkpoint * CHECKPOINT_INTERVAL;
+        let mut rank = self.checkpoints[checkpoint][byte as usize] as usize
+            + self.last[from..row].iter().filter(|&&b| b == byte).count();
+        // The sentinel's stand-in byte is not an occurrence.
+        if byte == 0 && (from..row).contains(&self.sentinel) {
+            rank -= 1;
+        }
+        rank
+    }
+
+    /// The row beginning with the rotation one byte earlier in the text.
+    fn lf(&self, row: usize) -> usize {
+        let byte = self.last[row];
+        self.starts[byte as usize] + self.rank(byte, row)
+    }
+
+    /// The rows whose rotations begin with `pattern`.
+    fn rows(&self, pattern: &[u8]) -> std::ops::Range<usize> {
+        let (mut start, mut end) = (0, self.last.len());
+        for &byte in pattern.iter().rev() {
+            start = self.starts[byte as usize] + self.rank(byte, start);
+            end = self.starts[byte as usize] + self.rank(byte, end);
+            if start >= end {
+                return 0..0;
+            }
+        }
+        start..end
+    }
+
+    /// Returns the number of occurrences of `pattern`, overlapping ones
+    /// included, in O(m).
+    ///
+    /// An empty pattern occurs at every offset from 0 to the text's length.
+    pub fn count(&self, pattern: &[u8]) -> usize {
+        self.rows(pattern).len()
+    }
+
+    /// Returns the starting offset of every occurrence of `pattern`, in
+    /// increasing order, like [`strsearch::find_all`](crate::strsearch::find_all).
+    pub fn locate(&self, pattern: &[u8]) -> Vec<usize> {
+        let mut starts: Vec<usize> = self.rows(pattern).map(|row| self.start(row)).collect();
+        starts.sort_unstable();
+        starts
+    }
+
+    /// The text offset of the suffix `row` begins with.
+    fn start(&self, mut row: usize) -> usize {
+        let mut steps = 0;
+        loop {
+            if let Ok(i) = self.samples.binary_search_by_key(&row, |&(row, _)| row) {
+                return self.samples[i].1 + steps;
+            }
+            // Offset 0 is sampled, so the sentinel's row is never stepped
+            // from.
+            row = self.lf(row);
+            steps += 1;
+        }
+    }
+}
+
+impl fmt::Debug for FmIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FmIndex").field("len", &self.len()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bwt_round_trip() {
+        // Rows: $banana, a$banan, ana$ban, anana$b, banana$, na$bana, nana$ba.
+        assert_eq!(bwt(b"banana"), (b"annbaa".to_vec(), 4));
+        assert_eq!(inverse_bwt(b"annbaa", 4), b"banana");
+        assert_eq!(bwt(b""), (Vec::new(), 0));
+        assert_eq!(inverse_bwt(b"", 0), b"");
+        let text = b"\0\xff\0abracadabra\0";
+        let (last, sentinel) = bwt(text);
+        assert_eq!(inverse_bwt(&last, sentinel), text);
+    }
+
+    #[test]
+    fn test_count_and_locate() {
+        let index = FmIndex::new(b"banana");
+        assert_eq!(index.len(), 6);
+        assert_eq!(index.count(b"ana"), 2);
+        assert_eq!(index.locate(b"ana"), [1, 3]);
+        assert_eq!(index.locate(b"a"), [1, 3, 5]);
+        assert_eq!(index.locate(b"banana"), [0]);
+        assert_eq!(index.count(b"nab"), 0);
+        assert_eq!(index.count(b"bananas"), 0);
+        assert_eq!(index.locate(b""), [0, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(index.bwt(), bwt(b"banana"));
+    }
+
+    #[test]
+    fn test_spans_checkpoints_and_samples() {
+        let text: Vec<u8> = b"\0ab".iter().copied().cycle().take(1000).collect();
+        let index = FmIndex::new(&text);
+        let expected: Vec<usize> = (0..999).step_by(3).map(|i| i + 1).collect();
+        assert_eq!(index.locate(b"ab"), expected);
+        assert_eq!(index.count(b"\0"), 334);
+        assert_eq!(index.count(b"b\0a"), 332);
+    }
+}
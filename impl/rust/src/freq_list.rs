@@ -0,0 +1,232 @@
+//! The frequency-bucket list shared by `LfuCache` and `SpaceSaving`.
+
+/// Index of a missing neighbor.
+const NIL: usize = usize::MAX;
+
+#[derive(Debug, Clone)]
+struct Entry<T> {
+    value: T,
+    /// The frequency bucket holding this entry.
+    bucket: usize,
+    /// More recently placed neighbor within the bucket.
+    prev: usize,
+    /// Less recently placed neighbor within the bucket.
+    next: usize,
+}
+
+/// All entries counted exactly `freq` times, most recently placed first.
+#[derive(Debug, Clone)]
+struct Bucket {
+    freq: u64,
+    head: usize,
+    tail: usize,
+    /// Bucket with the next lower frequency.
+    prev: usize,
+    /// Bucket with the next higher frequency.
+    next: usize,
+}
+
+/// Store `value` in a free arena slot, returning its index.
+fn alloc<T>(arena: &mut Vec<Option<T>>, free: &mut Vec<usize>, value: T) -> usize {
+    match free.pop() {
+        Some(index) => {
+            arena[index] = Some(value);
+            index
+        }
+        None => {
+            arena.push(Some(value));
+            arena.len() - 1
+        }
+    }
+}
+
+/// Values with use counts, kept ordered by count in O(1) per operation.
+///
+/// Buckets of equal count form a linked list in increasing frequency, and
+/// each bucket links its entries from most to least recently placed. A
+/// [`touch`](Self::touch) moves an entry into the neighboring bucket, and
+/// [`least`](Self::least) is the oldest entry of the first bucket. Entries
+/// are addressed by the index [`insert`](Self::insert) returns, which stays
+/// valid until the entry is removed.
+#[derive(Debug, Clone)]
+pub(crate) struct FrequencyList<T> {
+    entries: Vec<Option<Entry<T>>>,
+    free_entries: Vec<usize>,
+    buckets: Vec<Option<Bucket>>,
+    free_buckets: Vec<usize>,
+    /// The lowest-frequency bucket.
+    head: usize,
+}
+
+impl<T> FrequencyList<T> {
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+            free_entries: Vec::new(),
+            buckets: Vec::new(),
+            free_buckets: Vec::new(),
+            head: NIL,
+        }
+    }
+
+    fn entry(&self, index: usize) -> &Entry<T> {
+        self.entries[index].as_ref().expect("linked entry is live")
+    }
+
+    fn entry_mut(&mut self, index: usize) -> &mut Entry<T> {
+        self.entries[index].as_mut().expect("linked entry is live")
+    }
+
+    fn bucket(&self, index: usize) -> &Bucket {
+        self.buckets[index].as_ref().expect("linked bucket is live")
+    }
+
+    fn bucket_mut(&mut self, index: usize) -> &mut Bucket {
+        self.buckets[index].as_mut().expect("linked bucket is live")
+    }
+
+    /// Returns the value at `index`.
+    pub(crate) fn get(&self, index: usize) -> &T {
+        &self.entry(index).value
+    }
+
+    /// Returns the value at `index` mutably.
+    pub(crate) fn get_mut(&mut self, index: usize) -> &mut T {
+        &mut self.entry_mut(index).value
+    }
+
+    /// Returns the count of the entry at `index`.
+    pub(crate) fn freq(&self, index: usize) -> u64 {
+        self.bucket(self.entry(index).bucket).freq
+    }
+
+    /// Returns the index of the least recently placed entry with the lowest
+    /// count, or `None` if the list is empty.
+    pub(crate) fn least(&self) -> Option<usize> {
+        (self.head != NIL).then(|| self.bucket(self.head).tail)
+    }
+
+    /// Iterate over the live values with their counts, in no fixed order.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&T, u64)> {
+        self.entries
+            .iter()
+            .flatten()
+            .map(|entry| (&entry.value, self.bucket(entry.bucket).freq))
+    }
+
+    /// Create an empty bucket for `freq` between `prev` and `next`.
+    fn insert_bucket(&mut self, freq: u64, prev: usize, next: usize) -> usize {
+        let bucket = Bucket {
+            freq,
+            head: NIL,
+            tail: NIL,
+            prev,
+            next,
+        };
+        let index = alloc(&mut self.buckets, &mut self.free_buckets, bucket);
+        match prev {
+            NIL => self.head = index,
+            prev => self.bucket_mut(prev).next = index,
+        }
+        if next != NIL {
+            self.bucket_mut(next).prev = index;
+        }
+        index
+    }
+
+    fn remove_bucket(&mut self, index: usize) {
+        let bucket = self.buckets[index].take().expect("linked bucket is live");
+        match bucket.prev {
+            NIL => self.head = bucket.next,
+            prev => self.bucket_mut(prev).next = bucket.next,
+        }
+        if bucket.next != NIL {
+            self.bucket_mut(bucket.next).prev = bucket.prev;
+        }
+        self.free_buckets.push(index);
+    }
+
+    /// Unlink an entry from its bucket, dropping the bucket if it empties.
+    fn unlink(&mut self, index: usize) {
+        let (bucket, prev, next) = {
+            let entry = self.entry(index);
+            (entry.bucket, entry.prev, entry.next)
+        };
+        match prev {
+            NIL => self.bucket_mut(bucket).head = next,
+            prev => self.entry_mut(prev).next = next,
+        }
+        match next {
+            NIL => self.bucket_mut(bucket).tail = prev,
+            next => self.entry_mut(next).prev = prev,
+        }
+        if self.bucket(bucket).head == NIL {
+            self.remove_bucket(bucket);
+        }
+    }
+
+    fn push_front(&mut self, index: usize, bucket: usize) {
+        let head = self.bucket(bucket).head;
+        {
+            let entry = self.entry_mut(index);
+            entry.bucket = bucket;
+            entry.prev = NIL;
+            entry.next = head;
+        }
+        match head {
+            NIL => self.bucket_mut(bucket).tail = index,
+            head => self.entry_mut(head).prev = index,
+        }
+        self.bucket_mut(bucket).head = index;
+    }
+
+    /// Add `value` with a count of one, returning its index.
+    pub(crate) fn insert(&mut self, value: T) -> usize {
+        let bucket = if self.head != NIL && self.bucket(self.head).freq == 1 {
+            self.head
+        } else {
+            self.insert_bucket(1, NIL, self.head)
+        };
+        let entry = Entry {
+            value,
+            bucket,
+            prev: NIL,
+            next: NIL,
+        };
+        let index = alloc(&mut self.entries, &mut self.free_entries, entry);
+        self.push_front(index, bucket);
+        index
+    }
+
+    /// Add one to the count of the entry at `index`, moving it to the next
+    /// frequency bucket.
+    pub(crate) fn touch(&mut self, index: usize) {
+        let bucket = self.entry(index).bucket;
+        let (freq, next) = {
+            let bucket = self.bucket(bucket);
+            (bucket.freq, bucket.next)
+        };
+        // Link the target before unlinking, which may drop `bucket`.
+        let target = if next != NIL && self.bucket(next).freq == freq + 1 {
+            next
+        } else {
+            self.insert_bucket(freq + 1, bucket, next)
+        };
+        self.unlink(index);
+        self.push_front(index, target);
+    }
+
+    /// Remove the entry at `index`, returning its value.
+    pub(crate) fn remove(&mut self, index: usize) -> T {
+        self.unlink(index);
+        let entry = self.entries[index].take().expect("linked entry is live");
+        self.free_entries.push(index);
+        entry.value
+    }
+
+    /// Returns the number of arena slots and of live buckets.
+    #[cfg(test)]
+    pub(crate) fn footprint(&self) -> (usize, usize) {
+        (self.entries.len(), self.buckets.iter().flatten().count())
+    }
+}
@@ -0,0 +1,273 @@
+//! Planar computational geometry: orientation tests, convex hulls, the
+//! closest pair of points, and segment intersection.
+//!
+//! Points are `[x, y]` pairs of `f64`, as in the spatial indexes. Every
+//! predicate comes down to the sign of a cross product, which is exact
+//! when the coordinates are integers of magnitude below 2^26, since the
+//! products then fit in a float's 53-bit mantissa. For other inputs,
+//! points that are nearly collinear may be classified either way.
+//! Coordinates must not be NaN.
+
+use std::cmp::Ordering;
+
+/// A point in the plane.
+pub type Point = [f64; 2];
+
+/// Which way a path turns at its middle point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Orientation {
+    /// A right turn.
+    Clockwise,
+    /// A left turn.
+    Counterclockwise,
+    /// No turn: the three points lie on a line.
+    Collinear,
+}
+
+/// Returns the cross product of `a - o` and `b - o`: twice the signed
+/// area of the triangle `o a b`, positive if it turns counterclockwise.
+pub fn cross(o: Point, a: Point, b: Point) -> f64 {
+    (a[0] - o[0]) * (b[1] - o[1]) - (a[1] - o[1]) * (b[0] - o[0])
+}
+
+/// Returns which way the path `a b c` turns at `b`.
+pub fn orientation(a: Point, b: Point, c: Point) -> Orientation {
+    let turn = cross(a, b, c);
+    if turn > 0.0 {
+        Orientation::Counterclockwise
+    } else if turn < 0.0 {
+        Orientation::Clockwise
+    } else {
+        Orientation::Collinear
+    }
+}
+
+/// Returns the squared distance between `a` and `b`.
+pub fn distance_squared(a: Point, b: Point) -> f64 {
+    let (dx, dy) = (a[0] - b[0], a[1] - b[1]);
+    dx * dx + dy * dy
+}
+
+fn by_x(a: &Point, b: &Point) -> Ordering {
+    a[0].total_cmp(&b[0]).then(a[1].total_cmp(&b[1]))
+}
+
+/// Returns the vertices of the convex hull of `points`, counterclockwise
+/// from the lowest of the leftmost points.
+///
+/// Andrew's monotone chain: sort the points by x, then sweep left to
+/// right building the lower hull and right to left building the upper,
+/// popping the last point of the chain whenever it does not make a left
+/// turn. O(n log n) for the sort and O(n) for the sweeps. Points in the
+/// middle of a hull edge are not vertices and are left out, as are
+/// duplicates. If all the points are collinear, the hull is the two ends
+/// of their segment, and fewer than two distinct points are returned as
+/// they are.
+pub fn convex_hull(points: &[Point]) -> Vec<Point> {
+    let mut sorted = points.to_vec();
+    sorted.sort_by(by_x);
+    sorted.dedup();
+    if sorted.len() < 3 {
+        return sorted;
+    }
+    let mut hull: Vec<Point> = Vec::with_capacity(sorted.len() + 1);
+    for &p in &sorted {
+        while hull.len() >= 2 && cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0.0 {
+            hull.pop();
+        }
+        hull.push(p);
+    }
+    // The upper chain must not pop back into the lower one.
+    let lower = hull.len() + 1;
+    for &p in sorted.iter().rev().skip(1) {
+        while hull.len() >= lower && cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0.0 {
+            hull.pop();
+        }
+        hull.push(p);
+    }
+    // The sweep ends back at the first point.
+    hull.pop();
+    hull
+}
+
+/// Returns the indices `(i, j)`, `i < j`, of two points of `points` at the
+/// least distance apart, or `None` if there are fewer than two.
+///
+/// Divide and conquer: split the points, sorted by x, at the median, and
+/// find the closest pair on each side. A closer pair must straddle the
+/// split within the best distance `d` so far, and in that strip, taken in
+/// order of y, each point need only be compared with those less than `d`
+/// above it, of which there are at most a handful because they are at
+/// least `d` apart on each side. Each level sorts its strip by y by
+/// merging the two halves' orders, so the whole runs in O(n log n).
+pub fn closest_pair(points: &[Point]) -> Option<(usize, usize)> {
+    if points.len() < 2 {
+        return None;
+    }
+    let mut ids: Vec<usize> = (0..points.len()).collect();
+    ids.sort_by(|&a, &b| by_x(&points[a], &points[b]));
+    let mut strip = Vec::new();
+    let (_, i, j) = closest_in(points, &mut ids, &mut strip);
+    Some((i.min(j), i.max(j)))
+}
+
+/// The closest pair among `ids`, sorted by x, as its squared distance and
+/// indices; leaves `ids` sorted by y.
+fn closest_in(points: &[Point], ids: &mut [usize], strip: &mut Vec<usize>) -> (f64, usize, usize) {
+    let by_y = |a: &usize, b: &usize| points[*a][1].total_cmp(&points[*b][1]);
+    let n = ids.len();
+    if n <= 3 {
+        let mut best = (f64::INFINITY, ids[0], ids[0]);
+        for a in 0..n {
+            for b in a + 1..n {
+                let d = distance_squared(points[ids[a]], points[ids[b]]);
+                if d < best.0 {
+                    best = (d, ids[a], ids[b]);
+                }
+            }
+        }
+        ids.sort_by(by_y);
+        return best;
+    }
+
+    let mid = n / 2;
+    let split = points[ids[mid]][0];
+    let (left, right) = ids.split_at_mut(mid);
+    let left = closest_in(points, left, strip);
+    let right = closest_in(points, right, strip);
+    let mut best = if right.0 < left.0 { right } else { left };
+    // Two runs sorted by y: the stable sort finds and merges them in O(n).
+    ids.sort_by(by_y);
+
+    strip.clear();
+    strip.extend(ids.iter().filter(|&&id| {
+        let dx = points[id][0] - split;
+        dx * dx < best.0
+    }));
+    for (k, &a) in strip.iter().enumerate() {
+        for &b in &strip[k + 1..] {
+            let dy = points[b][1] - points[a][1];
+            if dy * dy >= best.0 {
+                break;
+            }
+            let d = distance_squared(points[a], points[b]);
+            if d < best.0 {
+                best = (d, a, b);
+            }
+        }
+    }
+    best
+}
+
+/// Returns true if `p`, known to be collinear with `a` and `b`, lies on
+/// the segment between them.
+fn within(a: Point, b: Point, p: Point) -> bool {
+    a[0].min(b[0]) <= p[0]
+        && p[0] <= a[0].max(b[0])
+        && a[1].min(b[1]) <= p[1]
+        && p[1] <= a[1].max(b[1])
+}
+
+/// Returns true if the closed segments `p` and `q` share at least one
+/// point, including an endpoint or a collinear overlap.
+///
+/// In general position the segments cross exactly when each one's ends lie
+/// on opposite sides of the other's line. Otherwise some end is collinear
+/// with the other segment, and they meet only if that end lies within it.
+pub fn segments_intersect(p: [Point; 2], q: [Point; 2]) -> bool {
+    let o1 = orientation(p[0], p[1], q[0]);
+    let o2 = orientation(p[0], p[1], q[1]);
+    let o3 = orientation(q[0], q[1], p[0]);
+    let o4 = orientation(q[0], q[1], p[1]);
+    if o1 != o2 && o3 != o4 {
+        return true;
+    }
+    use Orientation::Collinear;
+    (o1 == Collinear && within(p[0], p[1], q[0]))
+        || (o2 == Collinear && within(p[0], p[1], q[1]))
+        || (o3 == Collinear && within(q[0], q[1], p[0]))
+        || (o4 == Collinear && within(q[0], q[1], p[1]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convex_hull() {
+        let points = [
+            [0.0, 0.0],
+            [2.0, 0.0],
+            [1.0, 0.0],
+            [2.0, 2.0],
+            [1.0, 1.0],
+            [0.0, 2.0],
+            [0.0, 2.0],
+            [1.0, 3.0],
+        ];
+        assert_eq!(
+            convex_hull(&points),
+            [[0.0, 0.0], [2.0, 0.0], [2.0, 2.0], [1.0, 3.0], [0.0, 2.0]]
+        );
+        let line = [[2.0, 2.0], [0.0, 0.0], [1.0, 1.0], [3.0, 3.0]];
+        assert_eq!(convex_hull(&line), [[0.0, 0.0], [3.0, 3.0]]);
+        assert_eq!(convex_hull(&[[1.0, 1.0], [1.0, 1.0]]), [[1.0, 1.0]]);
+        assert!(convex_hull(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_closest_pair() {
+        let points = [
+            [0.0, 0.0],
+            [10.0, 10.0],
+            [4.0, 4.0],
+            [-3.0, 8.0],
+            [5.0, 5.5],
+            [9.0, -2.0],
+            [20.0, 0.0],
+        ];
+        assert_eq!(closest_pair(&points), Some((2, 4)));
+        assert_eq!(closest_pair(&[[1.0, 1.0], [1.0, 1.0]]), Some((0, 1)));
+        assert_eq!(closest_pair(&[[1.0, 1.0]]), None);
+    }
+
+    #[test]
+    fn test_segments_intersect() {
+        let s = |a: [f64; 2], b: [f64; 2]| [a, b];
+        // Crossing, touching at an end, and in a T.
+        assert!(segments_intersect(
+            s([0.0, 0.0], [2.0, 2.0]),
+            s([0.0, 2.0], [2.0, 0.0])
+        ));
+        assert!(segments_intersect(
+            s([0.0, 0.0], [1.0, 1.0]),
+            s([1.0, 1.0], [2.0, 0.0])
+        ));
+        assert!(segments_intersect(
+            s([0.0, 0.0], [2.0, 0.0]),
+            s([1.0, 0.0], [1.0, 5.0])
+        ));
+        // Collinear, overlapping and apart.
+        assert!(segments_intersect(
+            s([0.0, 0.0], [2.0, 0.0]),
+            s([1.0, 0.0], [3.0, 0.0])
+        ));
+        assert!(!segments_intersect(
+            s([0.0, 0.0], [1.0, 0.0]),
+            s([2.0, 0.0], [3.0, 0.0])
+        ));
+        // Parallel, and one that stops short of the other's line.
+        assert!(!segments_intersect(
+            s([0.0, 0.0], [2.0, 0.0]),
+            s([0.0, 1.0], [2.0, 1.0])
+        ));
+        assert!(!segments_intersect(
+            s([0.0, 0.0], [2.0, 0.0]),
+            s([1.0, 1.0], [1.0, 0.5])
+        ));
+        assert_eq!(
+            orientation([0.0, 0.0], [1.0, 0.0], [1.0, 1.0]),
+            Orientation::Counterclockwise
+        );
+    }
+}
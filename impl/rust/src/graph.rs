@@ -0,0 +1,418 @@
+//! Adjacency-list graph with generational node and edge ids.
+
+use crate::slot_map::{SlotKey, SlotMap};
+
+/// Refers to a node of a [`Graph`].
+///
+/// An id goes stale once its node is removed; lookups with it return
+/// `None` from then on, even after a new node takes over its slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(SlotKey);
+
+impl NodeId {
+    /// Returns a dense index for the node, below the graph's
+    /// [`node_bound`](Graph::node_bound), so algorithms can keep per-node
+    /// data in plain vectors.
+    pub fn index(self) -> usize {
+        self.0.index()
+    }
+}
+
+/// Refers to an edge of a [`Graph`], going stale like a [`NodeId`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EdgeId(SlotKey);
+
+impl EdgeId {
+    /// Returns a dense index for the edge, below the graph's
+    /// [`edge_bound`](Graph::edge_bound).
+    pub fn index(self) -> usize {
+        self.0.index()
+    }
+}
+
+/// An edge seen from one of its endpoints, or from the edge list.
+#[derive(Debug, PartialEq, Eq)]
+pub struct EdgeRef<'a, E> {
+    pub id: EdgeId,
+    pub source: NodeId,
+    pub target: NodeId,
+    pub weight: &'a E,
+}
+
+impl<E> Clone for EdgeRef<'_, E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<E> Copy for EdgeRef<'_, E> {}
+
+#[derive(Debug, Clone)]
+struct Node<N> {
+    weight: N,
+    /// Edges leaving the node, or every incident edge if undirected, in
+    /// insertion order.
+    outgoing: Vec<EdgeId>,
+    /// Edges entering the node; unused if undirected.
+    incoming: Vec<EdgeId>,
+}
+
+#[derive(Debug, Clone)]
+struct Edge<E> {
+    source: NodeId,
+    target: NodeId,
+    weight: E,
+}
+
+/// A directed or undirected graph with a weight of type `N` on every node
+/// and `E` on every edge.
+///
+/// Nodes and edges each live in a [`SlotMap`], so their ids stay valid
+/// through any other insertions and removals, and a removed node's id can
+/// never reach a node added later. Each node keeps the ids of its
+/// outgoing edges and, if directed, its incoming ones, which makes
+/// neighbor iteration proportional to degree. Adding a node or edge is
+/// O(1); removing an edge is O(degree) of its endpoints, and removing a
+/// node removes every edge touching it. An undirected edge is stored once
+/// and listed by both endpoints. Parallel edges and self-loops are
+/// allowed.
+#[derive(Debug, Clone)]
+pub struct Graph<N, E> {
+    nodes: SlotMap<Node<N>>,
+    edges: SlotMap<Edge<E>>,
+    directed: bool,
+}
+
+impl<N, E> Graph<N, E> {
+    /// Create a new empty directed graph.
+    pub fn directed() -> Self {
+        Self {
+            nodes: SlotMap::new(),
+            edges: SlotMap::new(),
+            directed: true,
+        }
+    }
+
+    /// Create a new empty undirected graph.
+    pub fn undirected() -> Self {
+        Self {
+            directed: false,
+            ..Self::directed()
+        }
+    }
+
+    /// Returns true if edges have a direction.
+    pub fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    /// Returns the number of nodes.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns the number of edges.
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Returns an upper bound on [`NodeId::index`] for this graph.
+    pub fn node_bound(&self) -> usize {
+        self.nodes.index_bound()
+    }
+
+    /// Returns an upper bound on [`EdgeId::index`] for this graph.
+    pub fn edge_bound(&self) -> usize {
+        self.edges.index_bound()
+    }
+
+    /// Add a node, returning its id.
+    pub fn add_node(&mut self, weight: N) -> NodeId {
+        NodeId(self.nodes.insert(Node {
+            weight,
+            outgoing: Vec::new(),
+            incoming: Vec::new(),
+        }))
+    }
+
+    /// Remove a node and every edge touching it.
+    ///
+    /// Returns the node's weight if the id was live.
+    pub fn remove_node(&mut self, id: NodeId) -> Option<N> {
+        let node = self.nodes.get(id.0)?;
+        let incident: Vec<EdgeId> = node
+            .outgoing
+            .iter()
+            .chain(&node.incoming)
+            .copied()
+            .collect();
+        for edge in incident {
+            // A directed self-loop is listed twice; the second call is a
+            // no-op.
+            self.remove_edge(edge);
+        }
+        self.nodes.remove(id.0).map(|node| node.weight)
+    }
+
+    /// Check if `id` refers to a node in the graph.
+    pub fn contains_node(&self, id: NodeId) -> bool {
+        self.nodes.contains_key(id.0)
+    }
+
+    /// Get a reference to a node's weight.
+    pub fn node(&self, id: NodeId) -> Option<&N> {
+        self.nodes.get(id.0).map(|node| &node.weight)
+    }
+
+    /// Get a mutable reference to a node's weight.
+    pub fn node_mut(&mut self, id: NodeId) -> Option<&mut N> {
+        self.nodes.get_mut(id.0).map(|node| &mut node.weight)
+    }
+
+    /// Add an edge from `source` to `target`, returning its id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either node is not in the graph.
+    pub fn add_edge(&mut self, source: NodeId, target: NodeId, weight: E) -> EdgeId {
+        assert!(self.contains_node(source), "source node not in graph");
+        assert!(self.contains_node(target), "target node not in graph");
+        let id = EdgeId(self.edges.insert(Edge {
+            source,
+            target,
+            weight,
+        }));
+        self.node_entry(source).outgoing.push(id);
+        if self.directed {
+            self.node_entry(target).incoming.push(id);
+        } else if target != source {
+            self.node_entry(target).outgoing.push(id);
+        }
+        id
+    }
+
+    /// Remove an edge.
+    ///
+    /// Returns the edge's weight if the id was live.
+    pub fn remove_edge(&mut self, id: EdgeId) -> Option<E> {
+        let edge = self.edges.remove(id.0)?;
+        let unlink = |list: &mut Vec<EdgeId>| {
+            let position = list.iter().position(|&e| e == id).expect("edge is listed");
+            list.remove(position);
+        };
+        unlink(&mut self.node_entry(edge.source).outgoing);
+        if self.directed {
+            unlink(&mut self.node_entry(edge.target).incoming);
+        } else if edge.target != edge.source {
+            unlink(&mut self.node_entry(edge.target).outgoing);
+        }
+        Some(edge.weight)
+    }
+
+    /// Check if `id` refers to an edge in the graph.
+    pub fn contains_edge(&self, id: EdgeId) -> bool {
+        self.edges.contains_key(id.0)
+    }
+
+    /// Get a reference to an edge's weight.
+    pub fn edge(&self, id: EdgeId) -> Option<&E> {
+        self.edges.get(id.0).map(|edge| &edge.weight)
+    }
+
+    /// Get a mutable reference to an edge's weight.
+    pub fn edge_mut(&mut self, id: EdgeId) -> Option<&mut E> {
+        self.edges.get_mut(id.0).map(|edge| &mut edge.weight)
+    }
+
+    /// Returns the source and target of an edge.
+    pub fn endpoints(&self, id: EdgeId) -> Option<(NodeId, NodeId)> {
+        self.edges.get(id.0).map(|edge| (edge.source, edge.target))
+    }
+
+    /// Returns an edge from `source` to `target`, either way round if
+    /// undirected. Takes O(degree) of `source`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `source` is not in the graph.
+    pub fn find_edge(&self, source: NodeId, target: NodeId) -> Option<EdgeId> {
+        self.outgoing(source)
+            .find(|edge| edge.target == target)
+            .map(|edge| edge.id)
+    }
+
+    /// Iterate over the nodes and their weights, in id order.
+    pub fn nodes(&self) -> impl Iterator<Item = (NodeId, &N)> {
+        self.nodes
+            .iter()
+            .map(|(key, node)| (NodeId(key), &node.weight))
+    }
+
+    /// Iterate over the edges, in id order.
+    pub fn edges(&self) -> impl Iterator<Item = EdgeRef<'_, E>> {
+        self.edges.iter().map(|(key, edge)| EdgeRef {
+            id: EdgeId(key),
+            source: edge.source,
+            target: edge.target,
+            weight: &edge.weight,
+        })
+    }
+
+    /// Iterate over the edges leaving `id`, in insertion order. If
+    /// undirected, this is every edge touching `id`, oriented with `id`
+    /// as the source.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is not in the graph.
+    pub fn outgoing(&self, id: NodeId) -> impl Iterator<Item = EdgeRef<'_, E>> {
+        self.node_ref(id).outgoing.iter().map(move |&edge_id| {
+            let edge = self.edge_ref(edge_id);
+            let target = if edge.source == id {
+                edge.target
+            } else {
+                edge.source
+            };
+            EdgeRef {
+                id: edge_id,
+                source: id,
+                target,
+                weight: &edge.weight,
+            }
+        })
+    }
+
+    /// Iterate over the edges entering `id`, in insertion order. If
+    /// undirected, this is every edge touching `id`, oriented with `id`
+    /// as the target.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is not in the graph.
+    pub fn incoming(&self, id: NodeId) -> impl Iterator<Item = EdgeRef<'_, E>> {
+        let node = self.node_ref(id);
+        let list = if self.directed {
+            &node.incoming
+        } else {
+            &node.outgoing
+        };
+        list.iter().map(move |&edge_id| {
+            let edge = self.edge_ref(edge_id);
+            let source = if edge.target == id {
+                edge.source
+            } else {
+                edge.target
+            };
+            EdgeRef {
+                id: edge_id,
+                source,
+                target: id,
+                weight: &edge.weight,
+            }
+        })
+    }
+
+    /// Iterate over the targets of the edges leaving `id`, once per edge.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is not in the graph.
+    pub fn neighbors(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.outgoing(id).map(|edge| edge.target)
+    }
+
+    /// Returns the number of edges leaving `id`, counting a self-loop
+    /// once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is not in the graph.
+    pub fn out_degree(&self, id: NodeId) -> usize {
+        self.node_ref(id).outgoing.len()
+    }
+
+    fn node_ref(&self, id: NodeId) -> &Node<N> {
+        self.nodes.get(id.0).expect("node not in graph")
+    }
+
+    fn node_entry(&mut self, id: NodeId) -> &mut Node<N> {
+        self.nodes.get_mut(id.0).expect("node not in graph")
+    }
+
+    fn edge_ref(&self, id: EdgeId) -> &Edge<E> {
+        self.edges.get(id.0).expect("listed edges are live")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_directed_adjacency() {
+        let mut graph = Graph::directed();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        let c = graph.add_node("c");
+        let ab = graph.add_edge(a, b, 1);
+        graph.add_edge(a, c, 2);
+        graph.add_edge(c, b, 3);
+        graph.add_edge(b, b, 4);
+
+        assert!(graph.neighbors(a).eq([b, c]));
+        assert!(graph.incoming(b).map(|edge| edge.source).eq([a, c, b]));
+        assert_eq!(graph.find_edge(a, b), Some(ab));
+        assert_eq!(graph.find_edge(b, a), None);
+        assert_eq!(graph.endpoints(ab), Some((a, b)));
+        *graph.edge_mut(ab).unwrap() = 10;
+        assert_eq!(graph.remove_edge(ab), Some(10));
+        assert!(graph.neighbors(a).eq([c]));
+        assert!(graph.incoming(b).map(|edge| edge.source).eq([c, b]));
+        assert_eq!((graph.node_count(), graph.edge_count()), (3, 3));
+    }
+
+    #[test]
+    fn test_undirected_edges_listed_at_both_ends() {
+        let mut graph = Graph::undirected();
+        let nodes: Vec<_> = (0..4).map(|i| graph.add_node(i)).collect();
+        let e = graph.add_edge(nodes[0], nodes[1], "01");
+        graph.add_edge(nodes[1], nodes[2], "12");
+        graph.add_edge(nodes[2], nodes[2], "22");
+
+        assert!(graph.neighbors(nodes[1]).eq([nodes[0], nodes[2]]));
+        assert!(graph.neighbors(nodes[2]).eq([nodes[1], nodes[2]]));
+        assert_eq!(graph.find_edge(nodes[1], nodes[0]), Some(e));
+        let incoming: Vec<_> = graph.incoming(nodes[0]).collect();
+        assert_eq!(
+            (incoming[0].source, incoming[0].target, incoming[0].weight),
+            (nodes[1], nodes[0], &"01")
+        );
+        assert_eq!(graph.remove_node(nodes[2]), Some(2));
+        assert!(graph.neighbors(nodes[1]).eq([nodes[0]]));
+        assert_eq!(graph.edge_count(), 1);
+        assert_eq!(graph.out_degree(nodes[3]), 0);
+    }
+
+    #[test]
+    fn test_ids_stay_stable_across_removals() {
+        let mut graph: Graph<u32, ()> = Graph::directed();
+        let a = graph.add_node(1);
+        let b = graph.add_node(2);
+        let ab = graph.add_edge(a, b, ());
+        graph.add_edge(b, a, ());
+        graph.add_edge(a, a, ());
+        assert_eq!(graph.remove_node(a), Some(1));
+        assert_eq!(graph.edge_count(), 0);
+        assert_eq!(graph.out_degree(b), 0);
+
+        // The new node reuses a's slot but not its id.
+        let c = graph.add_node(3);
+        assert_eq!(c.index(), a.index());
+        assert_eq!(graph.node(a), None);
+        assert_eq!(graph.node(c), Some(&3));
+        assert!(!graph.contains_edge(ab));
+        assert_eq!(graph.remove_node(a), None);
+        assert!(graph.nodes().map(|(id, _)| id).eq([c, b]));
+        assert!(c.index() < graph.node_bound());
+    }
+}
@@ -0,0 +1,318 @@
+//! Seeded random graph generators for tests and benchmarks.
+//!
+//! Every generator takes a `seed` and always builds the same graph from
+//! it, and a `weight` function that turns a fresh random `u64` into each
+//! edge's weight: `|_| ()` for an unweighted graph, `|r| r % 100 + 1` for
+//! weights in 1..=100. Nodes are added first, in order, and carry `()`.
+
+use crate::graph::{Graph, NodeId};
+use crate::perfect::reduce;
+use crate::reservoir::{rng, unit};
+use crate::skip_list::xorshift64;
+
+/// Call `visit` with each slot below `total` independently with
+/// probability `p`, drawing the gaps between chosen slots from the
+/// geometric distribution so the cost is proportional to the slots
+/// chosen, not to `total`.
+fn bernoulli_slots(total: usize, p: f64, state: &mut u64, mut visit: impl FnMut(usize)) {
+    assert!(
+        (0.0..=1.0).contains(&p),
+        "edge probability must be in [0, 1]"
+    );
+    let log_miss = (-p).ln_1p();
+    let mut slot = 0usize;
+    loop {
+        // A gap past usize::MAX saturates, which is the same as never.
+        let gap = (unit(state).ln() / log_miss).floor();
+        slot = slot.saturating_add(gap as usize);
+        if slot >= total {
+            return;
+        }
+        visit(slot);
+        slot += 1;
+    }
+}
+
+/// The unordered pair `(v, w)` with `w < v` at position `k` when pairs
+/// are listed by `v`, then `w`.
+fn unordered_pair(k: usize) -> (usize, usize) {
+    let mut v = ((1.0 + (1.0 + 8.0 * k as f64).sqrt()) / 2.0) as usize;
+    // Correct the float estimate, which can be off by one for large k.
+    while v * (v - 1) / 2 > k {
+        v -= 1;
+    }
+    while v * (v + 1) / 2 <= k {
+        v += 1;
+    }
+    (v, k - v * (v - 1) / 2)
+}
+
+fn add_nodes<E>(graph: &mut Graph<(), E>, n: usize) -> Vec<NodeId> {
+    (0..n).map(|_| graph.add_node(())).collect()
+}
+
+/// Returns a G(n, p) graph: each possible edge, without self-loops, is
+/// present independently with probability `p`. If directed, each ordered
+/// pair is considered separately.
+///
+/// Takes O(n + m) for m edges rather than O(n²), by jumping straight from
+/// one present edge to the next.
+///
+/// # Panics
+///
+/// Panics if `p` is not in [0, 1].
+pub fn erdos_renyi<E>(
+    n: usize,
+    p: f64,
+    directed: bool,
+    seed: u64,
+    mut weight: impl FnMut(u64) -> E,
+) -> Graph<(), E> {
+    let mut state = rng(seed);
+    let mut graph = if directed {
+        Graph::directed()
+    } else {
+        Graph::undirected()
+    };
+    let nodes = add_nodes(&mut graph, n);
+    let pairs = n * n.saturating_sub(1);
+    let mut edges = Vec::new();
+    if directed {
+        bernoulli_slots(pairs, p, &mut state, |k| {
+            let (source, target) = (k / (n - 1), k % (n - 1));
+            // Skip the self-loop.
+            let target = if target >= source { target + 1 } else { target };
+            edges.push((source, target));
+        });
+    } else {
+        bernoulli_slots(pairs / 2, p, &mut state, |k| {
+            edges.push(unordered_pair(k));
+        });
+    }
+    for (source, target) in edges {
+        graph.add_edge(nodes[source], nodes[target], weight(xorshift64(&mut state)));
+    }
+    graph
+}
+
+/// Returns an undirected scale-free graph on `n` nodes by preferential
+/// attachment (Barabási-Albert): starting from a clique on `m + 1` nodes,
+/// each later node links to `m` distinct earlier ones, picked with
+/// probability proportional to their degree.
+///
+/// A few hubs end up with degree around √n while most nodes keep about
+/// `m`, the heavy tail of many real networks. Every node but the first
+/// `m + 1` has exactly `m` edges to earlier nodes.
+///
+/// # Panics
+///
+/// Panics if `m` is zero.
+pub fn barabasi_albert<E>(
+    n: usize,
+    m: usize,
+    seed: u64,
+    mut weight: impl FnMut(u64) -> E,
+) -> Graph<(), E> {
+    assert!(m > 0, "each node must attach at least one edge");
+    let mut state = rng(seed);
+    let mut graph = Graph::undirected();
+    let nodes = add_nodes(&mut graph, n);
+    let core = (m + 1).min(n);
+    // Each node appears once per edge end, so a uniform pick from this
+    // list is a pick proportional to degree.
+    let mut ends = Vec::new();
+    for v in 0..core {
+        for w in 0..v {
+            graph.add_edge(nodes[v], nodes[w], weight(xorshift64(&mut state)));
+            ends.extend([v, w]);
+        }
+    }
+    let mut targets = Vec::with_capacity(m);
+    for v in core..n {
+        targets.clear();
+        while targets.len() < m {
+            let target = ends[reduce(xorshift64(&mut state), ends.len())];
+            if !targets.contains(&target) {
+                targets.push(target);
+            }
+        }
+        for &target in &targets {
+            graph.add_edge(nodes[v], nodes[target], weight(xorshift64(&mut state)));
+            ends.extend([v, target]);
+        }
+    }
+    graph
+}
+
+/// Returns an undirected `rows` x `columns` grid, each node linked to the
+/// ones above, below, left and right of it. Node `r * columns + c` is at
+/// row `r` and column `c`; the seed only drives the weights.
+///
+/// Road networks and meshes look like this: degree at most 4 and a
+/// diameter of `rows + columns - 2`, far longer than a random graph's.
+pub fn grid<E>(
+    rows: usize,
+    columns: usize,
+    seed: u64,
+    mut weight: impl FnMut(u64) -> E,
+) -> Graph<(), E> {
+    let mut state = rng(seed);
+    let mut graph = Graph::undirected();
+    let nodes = add_nodes(&mut graph, rows * columns);
+    for r in 0..rows {
+        for c in 0..columns {
+            let here = nodes[r * columns + c];
+            if c + 1 < columns {
+                graph.add_edge(
+                    here,
+                    nodes[r * columns + c + 1],
+                    weight(xorshift64(&mut state)),
+                );
+            }
+            if r + 1 < rows {
+                graph.add_edge(
+                    here,
+                    nodes[(r + 1) * columns + c],
+                    weight(xorshift64(&mut state)),
+                );
+            }
+        }
+    }
+    graph
+}
+
+/// Returns a random directed acyclic graph: the nodes are put in a random
+/// order, and each pair is joined from the earlier to the later with
+/// probability `p`.
+///
+/// Since the order is shuffled, node ids say nothing about it, so a
+/// topological sort has real work to do. Takes O(n + m), like
+/// [`erdos_renyi`].
+///
+/// # Panics
+///
+/// Panics if `p` is not in [0, 1].
+pub fn random_dag<E>(
+    n: usize,
+    p: f64,
+    seed: u64,
+    mut weight: impl FnMut(u64) -> E,
+) -> Graph<(), E> {
+    let mut state = rng(seed);
+    let mut graph = Graph::directed();
+    let mut order = add_nodes(&mut graph, n);
+    for i in (1..n).rev() {
+        order.swap(i, reduce(xorshift64(&mut state), i + 1));
+    }
+    let mut edges = Vec::new();
+    bernoulli_slots(n * n.saturating_sub(1) / 2, p, &mut state, |k| {
+        let (later, earlier) = unordered_pair(k);
+        edges.push((order[earlier], order[later]));
+    });
+    for (source, target) in edges {
+        graph.add_edge(source, target, weight(xorshift64(&mut state)));
+    }
+    graph
+}
+
+/// Returns an undirected tree on `n` nodes, drawn uniformly from all
+/// `n^(n - 2)` labelled trees by decoding a random Prüfer sequence.
+///
+/// Uniform random trees are shallow, with a diameter around √n; a random
+/// parent for each node in turn would give depth around log n instead,
+/// and a very different shape.
+pub fn random_tree<E>(n: usize, seed: u64, mut weight: impl FnMut(u64) -> E) -> Graph<(), E> {
+    let mut state = rng(seed);
+    let mut graph = Graph::undirected();
+    let nodes = add_nodes(&mut graph, n);
+    if n < 2 {
+        return graph;
+    }
+    let sequence: Vec<usize> = (0..n - 2)
+        .map(|_| reduce(xorshift64(&mut state), n))
+        .collect();
+    // Each node's degree in the tree; the leaves are those at 1.
+    let mut degree = vec![1; n];
+    for &v in &sequence {
+        degree[v] += 1;
+    }
+    // The smallest leaf not yet used is always `leaf`: either the
+    // sequence just made a smaller node a leaf, or the scan moves on.
+    let mut scan = degree
+        .iter()
+        .position(|&d| d == 1)
+        .expect("a tree has leaves");
+    let mut leaf = scan;
+    for &v in &sequence {
+        graph.add_edge(nodes[leaf], nodes[v], weight(xorshift64(&mut state)));
+        degree[v] -= 1;
+        if degree[v] == 1 && v < scan {
+            leaf = v;
+        } else {
+            scan += 1;
+            while degree[scan] != 1 {
+                scan += 1;
+            }
+            leaf = scan;
+        }
+    }
+    graph.add_edge(nodes[leaf], nodes[n - 1], weight(xorshift64(&mut state)));
+    graph
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scc::tarjan_scc;
+
+    /// The edges as pairs of node positions, in edge order.
+    fn edge_list<E: Copy>(graph: &Graph<(), E>) -> Vec<(usize, usize, E)> {
+        graph
+            .edges()
+            .map(|edge| (edge.source.index(), edge.target.index(), *edge.weight))
+            .collect()
+    }
+
+    #[test]
+    fn test_seeds_reproduce() {
+        let weight = |r: u64| r % 100;
+        for seed in [1, 2] {
+            let a = erdos_renyi(50, 0.1, true, seed, weight);
+            let b = erdos_renyi(50, 0.1, true, seed, weight);
+            assert_eq!(edge_list(&a), edge_list(&b));
+            assert!(a.edges().all(|edge| edge.source != edge.target));
+        }
+        let (a, b) = (random_tree(30, 1, weight), random_tree(30, 2, weight));
+        assert_ne!(edge_list(&a), edge_list(&b));
+        assert_eq!(erdos_renyi(20, 0.0, false, 3, |_| ()).edge_count(), 0);
+        assert_eq!(erdos_renyi(20, 1.0, false, 3, |_| ()).edge_count(), 190);
+        assert_eq!(erdos_renyi(20, 1.0, true, 3, |_| ()).edge_count(), 380);
+    }
+
+    #[test]
+    fn test_shapes() {
+        let grid = grid(3, 4, 0, |_| ());
+        assert_eq!((grid.node_count(), grid.edge_count()), (12, 17));
+
+        let scale_free = barabasi_albert(100, 3, 5, |_| ());
+        assert_eq!(scale_free.edge_count(), 6 + 96 * 3);
+        assert!(scale_free.edges().all(|edge| edge.source != edge.target));
+
+        let dag = random_dag(60, 0.2, 6, |_| ());
+        assert!(dag.edge_count() > 0);
+        // Acyclic: every strongly connected component is a single node.
+        assert_eq!(tarjan_scc(&dag).len(), 60);
+    }
+
+    #[test]
+    fn test_random_tree_spans() {
+        for n in [0, 1, 2, 3, 10, 200] {
+            let tree = random_tree(n, n as u64, |_| ());
+            assert_eq!(tree.node_count(), n);
+            assert_eq!(tree.edge_count(), n.saturating_sub(1));
+            if n > 0 {
+                assert_eq!(tarjan_scc(&tree).len(), 1, "a tree is connected");
+            }
+        }
+    }
+}
@@ -0,0 +1,110 @@
+//! Capacity schedules for growing hash tables and arrays.
+
+const GOLDEN_RATIO: f64 = 1.618_033_988_749_895;
+
+/// How a table chooses its next capacity when it outgrows the current one.
+///
+/// Geometric policies keep the amortized cost of an insert constant; smaller
+/// factors leave less empty space behind at the cost of more frequent
+/// resizes. Exact fit never over-allocates but resizes on almost every
+/// insert once full, which makes a long insert run quadratic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GrowthPolicy {
+    /// Double the capacity.
+    #[default]
+    Doubling,
+    /// Grow the capacity by half.
+    OneAndHalf,
+    /// Grow the capacity by the golden ratio, about 1.618. Below this
+    /// factor, a new buffer eventually fits in the space freed by the ones
+    /// before it.
+    GoldenRatio,
+    /// Grow to the smallest capacity that stays within the load factor.
+    ExactFit,
+    /// Roughly double, rounding up to a prime.
+    Primes,
+}
+
+impl GrowthPolicy {
+    /// Returns the capacity a table should start with when asked for at
+    /// least `capacity` slots.
+    pub fn initial_capacity(self, capacity: usize) -> usize {
+        match self {
+            GrowthPolicy::Primes => next_prime(capacity),
+            _ => capacity,
+        }
+    }
+
+    /// Returns the capacity to grow to from `capacity` so that `len` entries
+    /// fit at or below `max_load_factor`.
+    ///
+    /// The result is always larger than `capacity`.
+    pub fn next_capacity(self, capacity: usize, len: usize, max_load_factor: f64) -> usize {
+        let required = (len as f64 / max_load_factor).ceil() as usize;
+        let grown = match self {
+            GrowthPolicy::Doubling => capacity * 2,
+            GrowthPolicy::OneAndHalf => capacity + capacity / 2,
+            GrowthPolicy::GoldenRatio => (capacity as f64 * GOLDEN_RATIO) as usize,
+            GrowthPolicy::ExactFit => required,
+            GrowthPolicy::Primes => next_prime(capacity * 2),
+        };
+        grown.max(required).max(capacity + 1)
+    }
+}
+
+fn is_prime(n: usize) -> bool {
+    if n < 2 {
+        return false;
+    }
+    let mut divisor = 2;
+    while divisor * divisor <= n {
+        if n % divisor == 0 {
+            return false;
+        }
+        divisor += 1;
+    }
+    true
+}
+
+fn next_prime(n: usize) -> usize {
+    let mut candidate = n.max(2);
+    while !is_prime(candidate) {
+        candidate += 1;
+    }
+    candidate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_capacity_per_policy() {
+        assert_eq!(GrowthPolicy::Doubling.next_capacity(16, 12, 0.75), 32);
+        assert_eq!(GrowthPolicy::OneAndHalf.next_capacity(16, 12, 0.75), 24);
+        assert_eq!(GrowthPolicy::GoldenRatio.next_capacity(16, 12, 0.75), 25);
+        assert_eq!(GrowthPolicy::ExactFit.next_capacity(16, 13, 0.75), 18);
+        assert_eq!(GrowthPolicy::Primes.next_capacity(17, 13, 0.75), 37);
+    }
+
+    #[test]
+    fn test_next_capacity_always_grows() {
+        for policy in [
+            GrowthPolicy::Doubling,
+            GrowthPolicy::OneAndHalf,
+            GrowthPolicy::GoldenRatio,
+            GrowthPolicy::ExactFit,
+            GrowthPolicy::Primes,
+        ] {
+            assert!(policy.next_capacity(1, 0, 0.75) > 1);
+            assert!(policy.next_capacity(100, 76, 0.75) * 3 >= 76 * 4);
+        }
+    }
+
+    #[test]
+    fn test_primes() {
+        assert_eq!(GrowthPolicy::Primes.initial_capacity(16), 17);
+        assert_eq!(next_prime(90), 97);
+        assert!(!is_prime(91));
+    }
+}
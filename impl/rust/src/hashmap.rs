@@ -1,65 +1,443 @@
-//! Hash Map implementation using open addressing with linear probing.
+//! Hash Map implementation using open addressing with a pluggable probe sequence.
 
+use crate::metrics::Counters;
+#[cfg(feature = "metrics")]
+use crate::metrics::Metrics;
+use crate::probe::{LinearProbe, ProbeStrategy};
 use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
+use std::fmt;
+use std::hash::{BuildHasher, BuildHasherDefault, Hash};
+use std::iter::{FusedIterator, Zip};
+use std::marker::PhantomData;
+use std::mem::MaybeUninit;
 
 const DEFAULT_CAPACITY: usize = 16;
 const MAX_LOAD_FACTOR: f64 = 0.75;
 
-#[derive(Debug, Clone)]
-enum Entry<K, V> {
-    Empty,
-    Tombstone,
-    Occupied { key: K, value: V },
+/// Control byte of a slot that has never held an entry.
+const EMPTY: u8 = 0xFF;
+/// Control byte of a slot whose entry was removed (a tombstone).
+const DELETED: u8 = 0x80;
+
+/// Control byte of an occupied slot: the top 7 bits of the entry's hash.
+fn h2(hash: u64) -> u8 {
+    (hash >> 57) as u8
+}
+
+fn is_full(ctrl: u8) -> bool {
+    ctrl & 0x80 == 0
+}
+
+/// Storage for one entry and its cached full hash.
+///
+/// A slot is initialized exactly when its control byte is full.
+type Slot<K, V> = MaybeUninit<(u64, K, V)>;
+
+/// Hint the CPU to start loading the cache line holding `ptr`.
+///
+/// Compiles to nothing unless the `prefetch` feature is enabled on x86_64.
+#[inline(always)]
+fn prefetch<T>(ptr: *const T) {
+    #[cfg(all(feature = "prefetch", target_arch = "x86_64"))]
+    // SAFETY: prefetching is a hint and never faults, even on invalid addresses.
+    unsafe {
+        use std::arch::x86_64::{_mm_prefetch, _MM_HINT_T0};
+        _mm_prefetch::<_MM_HINT_T0>(ptr as *const i8);
+    }
+    #[cfg(not(all(feature = "prefetch", target_arch = "x86_64")))]
+    let _ = ptr;
+}
+
+fn empty_table<K, V>(capacity: usize) -> (Vec<u8>, Vec<Slot<K, V>>) {
+    let mut slots = Vec::with_capacity(capacity);
+    slots.resize_with(capacity, MaybeUninit::uninit);
+    (vec![EMPTY; capacity], slots)
+}
+
+/// A run of consecutive slots that one thread fills during a parallel
+/// build, while other threads fill the runs beside it.
+#[cfg(feature = "rayon")]
+pub(crate) struct SlotRange<'a, K, V, P> {
+    /// Index in the table of the first slot of the run.
+    start: usize,
+    /// The whole table's mask, which probe sequences are computed over.
+    mask: usize,
+    ctrl: &'a mut [u8],
+    slots: &'a mut [Slot<K, V>],
+    placed: usize,
+    strategy: PhantomData<fn() -> P>,
+}
+
+#[cfg(feature = "rayon")]
+impl<K: Eq, V, P: ProbeStrategy> SlotRange<'_, K, V, P> {
+    /// Place an entry if its probe sequence reaches its key or an empty slot
+    /// before leaving the run, or else hand it back.
+    ///
+    /// Tombstones are passed over rather than reused, and slots only ever
+    /// fill, so every entry placed here is still found once the entries
+    /// handed back have been inserted through the whole table.
+    pub(crate) fn insert(&mut self, hash: u64, key: K, value: V) -> Option<(u64, K, V)> {
+        let tag = h2(hash);
+        for i in 0..=self.mask {
+            let index = P::probe(hash, i, self.mask).wrapping_sub(self.start);
+            if index >= self.ctrl.len() {
+                break;
+            }
+            match self.ctrl[index] {
+                EMPTY => {
+                    self.slots[index].write((hash, key, value));
+                    self.ctrl[index] = tag;
+                    self.placed += 1;
+                    return None;
+                }
+                ctrl if ctrl == tag => {
+                    // SAFETY: a full control byte marks an initialized slot.
+                    let (h, k, v) = unsafe { self.slots[index].assume_init_mut() };
+                    if *h == hash && *k == key {
+                        *v = value;
+                        return None;
+                    }
+                }
+                _ => {}
+            }
+        }
+        Some((hash, key, value))
+    }
+
+    /// Returns the number of new entries placed in the run.
+    pub(crate) fn placed(&self) -> usize {
+        self.placed
+    }
+}
+
+/// A snapshot of the internal layout of a hash table.
+///
+/// The probe length of an entry is the number of slots inspected to find it,
+/// so an entry sitting in its home bucket has probe length 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableStats {
+    /// Number of occupied slots.
+    pub occupied: usize,
+    /// Number of tombstone slots.
+    pub tombstones: usize,
+    /// Total number of slots.
+    pub capacity: usize,
+    /// Fraction of slots that are occupied or tombstones.
+    pub load_factor: f64,
+    /// Mean probe length over all occupied slots.
+    pub avg_probe_length: f64,
+    /// Longest probe length of any occupied slot.
+    pub max_probe_length: usize,
+    /// `probe_length_histogram[i]` counts entries with probe length `i + 1`.
+    pub probe_length_histogram: Vec<usize>,
 }
 
-impl<K, V> Entry<K, V> {
-    fn is_tombstone(&self) -> bool {
-        matches!(self, Entry::Tombstone)
+impl TableStats {
+    /// Variance of the probe length over all occupied slots.
+    pub fn probe_length_variance(&self) -> f64 {
+        if self.occupied == 0 {
+            return 0.0;
+        }
+        let squared_deviation: f64 = self
+            .probe_length_histogram
+            .iter()
+            .enumerate()
+            .map(|(i, &count)| {
+                let deviation = (i + 1) as f64 - self.avg_probe_length;
+                count as f64 * deviation * deviation
+            })
+            .sum();
+        squared_deviation / self.occupied as f64
     }
 }
 
-/// A hash map implementation using open addressing with linear probing.
+/// The default hasher: SipHash with fixed keys, so runs are reproducible.
+pub type DefaultHashBuilder = BuildHasherDefault<DefaultHasher>;
+
+/// A [`HashMap`] using FxHash, a fast non-cryptographic hash for small keys.
+#[cfg(feature = "fxhash")]
+pub type FxHashMap<K, V> = HashMap<K, V, fxhash::FxBuildHasher>;
+
+/// A [`HashMap`] using AHash with fixed keys.
+#[cfg(feature = "ahash")]
+pub type AHashMap<K, V> = HashMap<K, V, BuildHasherDefault<ahash::AHasher>>;
+
+/// A hash map using open addressing with linear probing.
+pub type HashMap<K, V, S = DefaultHashBuilder> = OpenAddressingMap<K, V, S, LinearProbe>;
+
+/// A hash map implementation using open addressing.
 ///
 /// This implementation provides O(1) average-case complexity for insert, get,
-/// and remove operations.
-#[derive(Debug, Clone)]
-pub struct HashMap<K, V> {
-    entries: Vec<Entry<K, V>>,
+/// and remove operations. Keys are hashed with the `S` hasher builder, and
+/// collisions are resolved along the probe sequence of the `P` strategy over
+/// a power-of-two capacity.
+///
+/// Slot state lives in a separate array of control bytes, one per slot, and
+/// entries live in an array of uninitialized slots. Probing mostly scans the
+/// dense control bytes, whose 7-bit hash fragments rule out nearly every
+/// mismatched slot before its key is touched.
+pub struct OpenAddressingMap<K, V, S = DefaultHashBuilder, P = LinearProbe> {
+    /// `EMPTY`, `DELETED`, or the `h2` of the slot's hash.
+    ctrl: Vec<u8>,
+    slots: Vec<Slot<K, V>>,
     size: usize,
     tombstones: usize,
+    /// Occupancy below which a removal halves the table; `None` never shrinks.
+    shrink_threshold: Option<f64>,
+    /// Probe length above which an insert rebuilds the table; `None` for no
+    /// limit.
+    probe_length_limit: Option<usize>,
+    hash_builder: S,
+    strategy: PhantomData<P>,
+    counters: Counters,
+}
+
+impl<K, V, S, P> OpenAddressingMap<K, V, S, P> {
+    pub(crate) fn slot(&self, index: usize) -> Option<&(u64, K, V)> {
+        if is_full(self.ctrl[index]) {
+            // SAFETY: a full control byte marks an initialized slot.
+            Some(unsafe { self.slots[index].assume_init_ref() })
+        } else {
+            None
+        }
+    }
+
+    fn slot_mut(&mut self, index: usize) -> Option<&mut (u64, K, V)> {
+        if is_full(self.ctrl[index]) {
+            // SAFETY: a full control byte marks an initialized slot.
+            Some(unsafe { self.slots[index].assume_init_mut() })
+        } else {
+            None
+        }
+    }
+
+    /// Move the entry out of a slot, leaving a tombstone.
+    ///
+    /// The caller is responsible for updating `size` and `tombstones`.
+    fn take_slot(&mut self, index: usize) -> Option<(u64, K, V)> {
+        if is_full(self.ctrl[index]) {
+            self.ctrl[index] = DELETED;
+            // SAFETY: the slot was initialized, and marking it deleted first
+            // ensures it is never read again.
+            Some(unsafe { self.slots[index].assume_init_read() })
+        } else {
+            None
+        }
+    }
+
+    fn occupy(&mut self, index: usize, hash: u64, key: K, value: V) {
+        debug_assert!(!is_full(self.ctrl[index]));
+        self.slots[index].write((hash, key, value));
+        self.ctrl[index] = h2(hash);
+        self.size += 1;
+    }
+
+    fn drop_entries(&mut self) {
+        for index in 0..self.ctrl.len() {
+            if is_full(self.ctrl[index]) {
+                self.ctrl[index] = EMPTY;
+                // SAFETY: the slot was initialized and is now marked empty.
+                unsafe { self.slots[index].assume_init_drop() };
+            }
+        }
+    }
+
+    fn into_entries(mut self) -> IntoEntries<K, V> {
+        let ctrl = std::mem::take(&mut self.ctrl);
+        let slots = std::mem::take(&mut self.slots);
+        IntoEntries {
+            inner: ctrl.into_iter().zip(slots),
+            remaining: self.size,
+        }
+    }
+}
+
+impl<K, V, S, P> Drop for OpenAddressingMap<K, V, S, P> {
+    fn drop(&mut self) {
+        self.drop_entries();
+    }
+}
+
+impl<K: Clone, V: Clone, S: Clone, P> Clone for OpenAddressingMap<K, V, S, P> {
+    fn clone(&self) -> Self {
+        let (ctrl, slots) = empty_table(self.ctrl.len());
+        let mut clone = Self {
+            ctrl,
+            slots,
+            size: 0,
+            tombstones: 0,
+            shrink_threshold: self.shrink_threshold,
+            probe_length_limit: self.probe_length_limit,
+            hash_builder: self.hash_builder.clone(),
+            strategy: PhantomData,
+            counters: Counters::default(),
+        };
+        for index in 0..self.ctrl.len() {
+            if let Some((hash, key, value)) = self.slot(index) {
+                clone.occupy(index, *hash, key.clone(), value.clone());
+            }
+        }
+        clone.ctrl.copy_from_slice(&self.ctrl);
+        clone.tombstones = self.tombstones;
+        clone
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, S, P> fmt::Debug for OpenAddressingMap<K, V, S, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let iter = Iter {
+            inner: self.ctrl.iter().zip(self.slots.iter()),
+            remaining: self.size,
+        };
+        f.debug_map().entries(iter).finish()
+    }
 }
 
-impl<K, V> Default for HashMap<K, V>
+impl<K, V, S, P> Default for OpenAddressingMap<K, V, S, P>
 where
-    K: Hash + Eq + Clone,
-    V: Clone,
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+    P: ProbeStrategy,
 {
     fn default() -> Self {
-        Self::new()
+        Self::with_hasher(S::default())
     }
 }
 
-impl<K, V> HashMap<K, V>
+impl<K, V, P> OpenAddressingMap<K, V, DefaultHashBuilder, P>
 where
-    K: Hash + Eq + Clone,
-    V: Clone,
+    K: Hash + Eq,
+    P: ProbeStrategy,
 {
-    /// Create a new empty HashMap.
+    /// Create a new empty map.
     pub fn new() -> Self {
         Self::with_capacity(DEFAULT_CAPACITY)
     }
 
-    /// Create a new HashMap with the specified capacity.
+    /// Create a new map with at least the specified capacity.
+    ///
+    /// The capacity is rounded up to a power of two.
     pub fn with_capacity(capacity: usize) -> Self {
-        let capacity = capacity.max(DEFAULT_CAPACITY);
-        let mut entries = Vec::with_capacity(capacity);
-        entries.resize_with(capacity, || Entry::Empty);
+        Self::with_capacity_and_hasher(capacity, DefaultHashBuilder::default())
+    }
+}
+
+impl<K, V, S, P> OpenAddressingMap<K, V, S, P>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    P: ProbeStrategy,
+{
+    /// Create a new empty map using the given hasher builder.
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self::with_capacity_and_hasher(DEFAULT_CAPACITY, hash_builder)
+    }
+
+    /// Create a new map with at least the specified capacity, rounded up to a
+    /// power of two, and the given hasher builder.
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
+        let (ctrl, slots) = empty_table(capacity.max(DEFAULT_CAPACITY).next_power_of_two());
         Self {
-            entries,
+            ctrl,
+            slots,
             size: 0,
             tombstones: 0,
+            shrink_threshold: None,
+            probe_length_limit: None,
+            hash_builder,
+            strategy: PhantomData,
+            counters: Counters::default(),
+        }
+    }
+
+    /// Returns the occupancy below which removals shrink the table.
+    pub fn shrink_threshold(&self) -> Option<f64> {
+        self.shrink_threshold
+    }
+
+    /// Opt in to shrinking: after a removal leaves fewer than
+    /// `threshold * capacity` entries, the capacity is halved.
+    ///
+    /// Shrinking is off by default. Capacity never drops below the default
+    /// capacity, and removals through a [`CursorMut`] never shrink.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `threshold` is not in `(0, MAX_LOAD_FACTOR / 2)`, which keeps
+    /// the halved table below the growth threshold.
+    pub fn set_shrink_threshold(&mut self, threshold: Option<f64>) {
+        if let Some(threshold) = threshold {
+            assert!(
+                threshold > 0.0 && threshold < MAX_LOAD_FACTOR / 2.0,
+                "shrink threshold must be in (0, {})",
+                MAX_LOAD_FACTOR / 2.0
+            );
+        }
+        self.shrink_threshold = threshold;
+    }
+
+    fn maybe_shrink(&mut self) {
+        let Some(threshold) = self.shrink_threshold else {
+            return;
+        };
+        let capacity = self.ctrl.len();
+        if capacity > DEFAULT_CAPACITY && (self.size as f64) < capacity as f64 * threshold {
+            self.resize_to((capacity / 2).max(DEFAULT_CAPACITY));
+        }
+    }
+
+    /// Returns the probe length above which inserts rebuild the table.
+    pub fn probe_length_limit(&self) -> Option<usize> {
+        self.probe_length_limit
+    }
+
+    /// Rebuild the table whenever an insert places an entry more than
+    /// `limit` slots along its probe sequence, whatever the load factor.
+    ///
+    /// The rebuild purges tombstones if there are any and otherwise doubles
+    /// the capacity. Below a quarter of the growth threshold the table is
+    /// left alone: chains that long come from colliding hashes, which no
+    /// capacity separates. Off by default.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limit` is `Some(0)`.
+    pub fn set_probe_length_limit(&mut self, limit: Option<usize>) {
+        assert!(limit != Some(0), "probe length limit must be at least 1");
+        self.probe_length_limit = limit;
+    }
+
+    /// Rebuild the table if an entry placed at `index` would break the probe
+    /// length limit. Returns true if the table was rebuilt.
+    fn enforce_probe_length_limit(&mut self, hash: u64, index: usize) -> bool {
+        let Some(limit) = self.probe_length_limit else {
+            return false;
+        };
+        if self.probe_length(hash, index) <= limit {
+            return false;
+        }
+        let capacity = self.ctrl.len();
+        if self.tombstones > 0 {
+            self.resize_to(capacity);
+        } else if self.load_factor() >= MAX_LOAD_FACTOR / 4.0 {
+            self.resize_to(capacity * 2);
+        } else {
+            return false;
         }
+        true
+    }
+
+    /// Returns a reference to the map's hasher builder.
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+
+    /// Returns the operation counts accumulated since the map was created.
+    ///
+    /// A clone starts its counts from zero.
+    #[cfg(feature = "metrics")]
+    pub fn metrics(&self) -> Metrics {
+        self.counters.snapshot()
     }
 
     /// Returns the number of elements in the map.
@@ -74,64 +452,124 @@ where
 
     /// Returns the current capacity of the map.
     pub fn capacity(&self) -> usize {
-        self.entries.len()
+        self.ctrl.len()
+    }
+
+    fn hash_key(&self, key: &K) -> u64 {
+        self.hash_builder.hash_one(key)
+    }
+
+    /// Returns the slot at step `i` of the probe sequence for `hash`.
+    fn probe(&self, hash: u64, i: usize) -> usize {
+        P::probe(hash, i, self.ctrl.len() - 1)
     }
 
-    fn hash_key(&self, key: &K) -> usize {
-        let mut hasher = DefaultHasher::new();
-        key.hash(&mut hasher);
-        hasher.finish() as usize
+    /// Returns the number of slots inspected to reach `index` from `hash`.
+    fn probe_length(&self, hash: u64, index: usize) -> usize {
+        (0..self.ctrl.len())
+            .position(|i| self.probe(hash, i) == index)
+            .expect("every slot is on every probe sequence")
+            + 1
     }
 
     fn load_factor(&self) -> f64 {
-        (self.size + self.tombstones) as f64 / self.entries.len() as f64
+        (self.size + self.tombstones) as f64 / self.ctrl.len() as f64
     }
 
     fn find_slot(&self, key: &K) -> (usize, bool) {
-        let hash = self.hash_key(key);
-        let capacity = self.entries.len();
-        let mut index = hash % capacity;
+        self.find_slot_hashed(self.hash_key(key), key)
+    }
+
+    fn find_slot_hashed(&self, hash: u64, key: &K) -> (usize, bool) {
+        self.find_slot_by(hash, |k| k == key)
+    }
+
+    /// Probe for the entry with `hash` whose key satisfies `is_match`.
+    ///
+    /// Returns the matching slot, or else the slot an insert should use.
+    fn find_slot_by<F>(&self, hash: u64, mut is_match: F) -> (usize, bool)
+    where
+        F: FnMut(&K) -> bool,
+    {
+        let mut index = self.probe(hash, 0);
         let mut first_tombstone: Option<usize> = None;
+        let tag = h2(hash);
+        // Overlap the entry's cache miss with the control byte's.
+        prefetch(self.slots.as_ptr().wrapping_add(index));
 
-        for _ in 0..capacity {
-            match &self.entries[index] {
-                Entry::Empty => {
+        for i in 1..=self.ctrl.len() {
+            self.counters.probe();
+            match self.ctrl[index] {
+                EMPTY => {
                     return (first_tombstone.unwrap_or(index), false);
                 }
-                Entry::Tombstone => {
-                    if first_tombstone.is_none() {
-                        first_tombstone = Some(index);
-                    }
+                DELETED => {
+                    self.counters.tombstone_hit();
+                    first_tombstone.get_or_insert(index);
                 }
-                Entry::Occupied { key: k, .. } if k == key => {
+                ctrl if ctrl == tag
+                    && self.slot(index).is_some_and(|(h, k, _)| {
+                        *h == hash && {
+                            self.counters.comparison();
+                            is_match(k)
+                        }
+                    }) =>
+                {
                     return (index, true);
                 }
-                Entry::Occupied { .. } => {}
+                _ => {}
             }
-            index = (index + 1) % capacity;
+            index = self.probe(hash, i);
+            prefetch(self.slots.as_ptr().wrapping_add(index));
         }
 
         (first_tombstone.unwrap_or(0), false)
     }
 
     fn resize(&mut self) {
-        let new_capacity = self.entries.len() * 2;
-        let old_entries = std::mem::replace(&mut self.entries, {
-            let mut v = Vec::with_capacity(new_capacity);
-            v.resize_with(new_capacity, || Entry::Empty);
-            v
-        });
+        let capacity = self.ctrl.len();
+        // When tombstones rather than live entries pushed the table over the
+        // load threshold, rehashing at the same capacity is enough.
+        if (self.size as f64) < capacity as f64 * MAX_LOAD_FACTOR / 2.0 {
+            self.resize_to(capacity);
+        } else {
+            self.resize_to(capacity * 2);
+        }
+    }
+
+    fn resize_to(&mut self, new_capacity: usize) {
+        self.counters.resize();
+        let (ctrl, slots) = empty_table(new_capacity);
+        let old_ctrl = std::mem::replace(&mut self.ctrl, ctrl);
+        let old_slots = std::mem::replace(&mut self.slots, slots);
 
         self.size = 0;
         self.tombstones = 0;
 
-        for entry in old_entries {
-            if let Entry::Occupied { key, value } = entry {
-                self.insert(key, value);
+        for (ctrl, slot) in old_ctrl.into_iter().zip(old_slots) {
+            if is_full(ctrl) {
+                // SAFETY: a full control byte marks an initialized slot, and
+                // the old table is consumed so each slot is read only once.
+                let (hash, key, value) = unsafe { slot.assume_init() };
+                self.place_unique(hash, key, value);
             }
         }
     }
 
+    /// Place an entry known not to be in the table, reusing its cached hash.
+    ///
+    /// Used when rehashing: no key comparisons are needed since keys are
+    /// already unique, and the target table has no tombstones.
+    fn place_unique(&mut self, hash: u64, key: K, value: V) {
+        let mut i = 0;
+        let mut index = self.probe(hash, 0);
+        while self.ctrl[index] != EMPTY {
+            i += 1;
+            index = self.probe(hash, i);
+        }
+        self.occupy(index, hash, key, value);
+    }
+
     /// Insert a key-value pair into the map.
     ///
     /// Returns the previous value if the key existed.
@@ -140,35 +578,137 @@ where
             self.resize();
         }
 
-        let (index, found) = self.find_slot(&key);
+        let hash = self.hash_key(&key);
+        self.insert_hashed(hash, key, value)
+    }
+
+    /// Insert a batch of key-value pairs.
+    ///
+    /// Capacity is reserved for the whole batch up front and the pairs are
+    /// inserted in home-bucket order, so consecutive inserts touch neighboring
+    /// slots. Later pairs win over earlier ones with the same key.
+    pub fn insert_batch<I>(&mut self, items: I)
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let hashed: Vec<(u64, K, V)> = items
+            .into_iter()
+            .map(|(key, value)| (self.hash_key(&key), key, value))
+            .collect();
+        self.insert_hashed_batch(hashed);
+    }
+
+    /// Insert a batch of entries whose hashes are already computed, as
+    /// [`insert_batch`](Self::insert_batch) does.
+    pub(crate) fn insert_hashed_batch(&mut self, mut hashed: Vec<(u64, K, V)>) {
+        self.reserve(hashed.len());
+        // A stable sort, so later duplicates are still inserted last.
+        hashed.sort_by_key(|(hash, _, _)| self.probe(*hash, 0));
+
+        for (hash, key, value) in hashed {
+            self.insert_hashed(hash, key, value);
+        }
+    }
+
+    /// Grow the table, if needed, so `additional` more entries fit under
+    /// the load threshold.
+    pub(crate) fn reserve(&mut self, additional: usize) {
+        let required = self.size + self.tombstones + additional;
+        let mut new_capacity = self.ctrl.len();
+        while required as f64 >= new_capacity as f64 * MAX_LOAD_FACTOR {
+            new_capacity *= 2;
+        }
+        if new_capacity != self.ctrl.len() {
+            self.resize_to(new_capacity);
+        }
+    }
+
+    /// Split the slots into consecutive runs of `len` slots each, for
+    /// threads to fill side by side in a parallel build.
+    ///
+    /// `len` must be a power of two no larger than the capacity. Entries
+    /// placed through the runs are not counted until they are handed to
+    /// [`finish_slot_ranges`](Self::finish_slot_ranges).
+    #[cfg(feature = "rayon")]
+    pub(crate) fn slot_ranges(&mut self, len: usize) -> Vec<SlotRange<'_, K, V, P>> {
+        debug_assert!(len.is_power_of_two() && len <= self.ctrl.len());
+        let mask = self.ctrl.len() - 1;
+        self.ctrl
+            .chunks_mut(len)
+            .zip(self.slots.chunks_mut(len))
+            .enumerate()
+            .map(|(i, (ctrl, slots))| SlotRange {
+                start: i * len,
+                mask,
+                ctrl,
+                slots,
+                placed: 0,
+                strategy: PhantomData,
+            })
+            .collect()
+    }
+
+    /// Count the `placed` entries the slot ranges filled, then insert the
+    /// ones they handed back.
+    #[cfg(feature = "rayon")]
+    pub(crate) fn finish_slot_ranges(&mut self, placed: usize, deferred: Vec<(u64, K, V)>) {
+        self.size += placed;
+        self.insert_hashed_batch(deferred);
+    }
+
+    fn insert_hashed(&mut self, hash: u64, key: K, value: V) -> Option<V> {
+        let (index, found) = self.find_slot_hashed(hash, &key);
 
         if found {
-            if let Entry::Occupied {
-                value: old_value, ..
-            } = &mut self.entries[index]
-            {
-                let prev = old_value.clone();
-                *old_value = value;
-                return Some(prev);
+            if let Some((_, _, old_value)) = self.slot_mut(index) {
+                return Some(std::mem::replace(old_value, value));
             }
         }
 
-        if self.entries[index].is_tombstone() {
+        self.insert_vacant(index, hash, key, value);
+        None
+    }
+
+    /// Insert a key known to be absent into the vacant slot `index` found
+    /// for it, returning the slot it ends up in.
+    fn insert_vacant(&mut self, index: usize, hash: u64, key: K, value: V) -> usize {
+        let index = if self.enforce_probe_length_limit(hash, index) {
+            self.find_slot_by(hash, |_| false).0
+        } else {
+            index
+        };
+        if self.ctrl[index] == DELETED {
             self.tombstones -= 1;
         }
+        self.occupy(index, hash, key, value);
+        index
+    }
 
-        self.entries[index] = Entry::Occupied { key, value };
-        self.size += 1;
-        None
+    /// Get the entry for a key, for in-place insertion or update.
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, S, P> {
+        if self.load_factor() >= MAX_LOAD_FACTOR {
+            self.resize();
+        }
+
+        let hash = self.hash_key(&key);
+        let (index, found) = self.find_slot_hashed(hash, &key);
+        if found {
+            Entry::Occupied(OccupiedEntry { map: self, index })
+        } else {
+            Entry::Vacant(VacantEntry {
+                map: self,
+                hash,
+                key,
+                index,
+            })
+        }
     }
 
     /// Get a reference to the value associated with the key.
     pub fn get(&self, key: &K) -> Option<&V> {
         let (index, found) = self.find_slot(key);
         if found {
-            if let Entry::Occupied { value, .. } = &self.entries[index] {
-                return Some(value);
-            }
+            return self.slot(index).map(|(_, _, value)| value);
         }
         None
     }
@@ -177,29 +717,72 @@ where
     pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
         let (index, found) = self.find_slot(key);
         if found {
-            if let Entry::Occupied { value, .. } = &mut self.entries[index] {
-                return Some(value);
-            }
+            return self.slot_mut(index).map(|(_, _, value)| value);
         }
         None
     }
 
+    /// Find the entry with `hash` whose key satisfies `is_match`.
+    ///
+    /// `hash` must come from this map's [`hasher`](Self::hasher), applied to
+    /// something that hashes the same as the key. This lets callers look
+    /// entries up by a borrowed form, or by data the key only refers to,
+    /// without building a `K`.
+    pub fn raw_get<F>(&self, hash: u64, is_match: F) -> Option<(&K, &V)>
+    where
+        F: FnMut(&K) -> bool,
+    {
+        let (index, found) = self.find_slot_by(hash, is_match);
+        if found {
+            return self.slot(index).map(|(_, key, value)| (key, value));
+        }
+        None
+    }
+
+    /// Get the entry with `hash` whose key satisfies `is_match`, for
+    /// in-place insertion or update.
+    ///
+    /// The same hashing contract as [`raw_get`](Self::raw_get) applies; an
+    /// entry inserted under the wrong hash cannot be found by key.
+    pub fn raw_entry<F>(&mut self, hash: u64, is_match: F) -> RawEntry<'_, K, V, S, P>
+    where
+        F: FnMut(&K) -> bool,
+    {
+        if self.load_factor() >= MAX_LOAD_FACTOR {
+            self.resize();
+        }
+
+        let (index, found) = self.find_slot_by(hash, is_match);
+        if found {
+            RawEntry::Occupied(OccupiedEntry { map: self, index })
+        } else {
+            RawEntry::Vacant(RawVacantEntry {
+                map: self,
+                hash,
+                index,
+            })
+        }
+    }
+
     /// Remove a key-value pair from the map.
     ///
     /// Returns the removed value if the key existed.
     pub fn remove(&mut self, key: &K) -> Option<V> {
         let (index, found) = self.find_slot(key);
         if found {
-            let entry = std::mem::replace(&mut self.entries[index], Entry::Tombstone);
-            if let Entry::Occupied { value, .. } = entry {
-                self.size -= 1;
-                self.tombstones += 1;
-                return Some(value);
-            }
+            return self.remove_at(index).map(|(_, value)| value);
         }
         None
     }
 
+    fn remove_at(&mut self, index: usize) -> Option<(K, V)> {
+        let (_, key, value) = self.take_slot(index)?;
+        self.size -= 1;
+        self.tombstones += 1;
+        self.maybe_shrink();
+        Some((key, value))
+    }
+
     /// Check if the map contains the given key.
     pub fn contains_key(&self, key: &K) -> bool {
         let (_, found) = self.find_slot(key);
@@ -208,38 +791,555 @@ where
 
     /// Clear all entries from the map.
     pub fn clear(&mut self) {
-        for entry in &mut self.entries {
-            *entry = Entry::Empty;
-        }
+        self.drop_entries();
+        self.ctrl.fill(EMPTY);
         self.size = 0;
         self.tombstones = 0;
     }
 
     /// Iterate over all key-value pairs.
-    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
-        self.entries.iter().filter_map(|entry| {
-            if let Entry::Occupied { key, value } = entry {
-                Some((key, value))
-            } else {
-                None
-            }
-        })
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.ctrl.iter().zip(self.slots.iter()),
+            remaining: self.size,
+        }
     }
 
     /// Iterate over all keys.
-    pub fn keys(&self) -> impl Iterator<Item = &K> {
-        self.iter().map(|(k, _)| k)
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys { inner: self.iter() }
     }
 
     /// Iterate over all values.
-    pub fn values(&self) -> impl Iterator<Item = &V> {
-        self.iter().map(|(_, v)| v)
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values { inner: self.iter() }
+    }
+
+    /// Collect occupancy and probe-length statistics for the table.
+    pub fn stats(&self) -> TableStats {
+        let capacity = self.ctrl.len();
+        let mut histogram = Vec::new();
+        let mut total_probe_length = 0;
+
+        for index in 0..capacity {
+            if let Some((hash, _, _)) = self.slot(index) {
+                let probe_length = self.probe_length(*hash, index);
+                if histogram.len() < probe_length {
+                    histogram.resize(probe_length, 0);
+                }
+                histogram[probe_length - 1] += 1;
+                total_probe_length += probe_length;
+            }
+        }
+
+        TableStats {
+            occupied: self.size,
+            tombstones: self.tombstones,
+            capacity,
+            load_factor: self.load_factor(),
+            avg_probe_length: if self.size == 0 {
+                0.0
+            } else {
+                total_probe_length as f64 / self.size as f64
+            },
+            max_probe_length: histogram.len(),
+            probe_length_histogram: histogram,
+        }
+    }
+
+    /// Check the table invariants, panicking with a description on violation.
+    ///
+    /// Verifies that `len()` matches the occupied slots, that the tombstone
+    /// count matches the tombstone slots, that every cached hash and control
+    /// byte matches its key, and that every key is reachable from its home bucket without
+    /// crossing an empty slot.
+    pub fn debug_validate(&self) {
+        let capacity = self.ctrl.len();
+        let occupied = self.ctrl.iter().filter(|&&ctrl| is_full(ctrl)).count();
+        let tombstones = self.ctrl.iter().filter(|&&ctrl| ctrl == DELETED).count();
+        assert_eq!(self.size, occupied, "size does not match occupied slots");
+        assert_eq!(
+            self.tombstones, tombstones,
+            "tombstone count does not match tombstone slots"
+        );
+
+        for index in 0..capacity {
+            if let Some((hash, key, _)) = self.slot(index) {
+                assert_eq!(
+                    *hash,
+                    self.hash_key(key),
+                    "slot {} caches a stale hash",
+                    index
+                );
+                assert_eq!(
+                    self.ctrl[index],
+                    h2(*hash),
+                    "slot {} has a control byte that does not match its hash",
+                    index
+                );
+                let mut i = 0;
+                let mut probe = self.probe(*hash, 0);
+                while probe != index {
+                    assert!(
+                        self.ctrl[probe] != EMPTY,
+                        "slot {} is unreachable: empty slot {} on its probe chain",
+                        index,
+                        probe
+                    );
+                    i += 1;
+                    probe = self.probe(*hash, i);
+                }
+            }
+        }
+    }
+
+    /// Returns a cursor positioned at the first occupied slot.
+    ///
+    /// The cursor can remove or replace the current entry without being
+    /// invalidated, which allows single-pass eviction logic.
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, K, V, S, P> {
+        let mut cursor = CursorMut {
+            map: self,
+            index: 0,
+        };
+        cursor.seek_occupied();
+        cursor
+    }
+
+    /// Consume the map, yielding all keys.
+    pub fn into_keys(self) -> IntoKeys<K, V> {
+        IntoKeys {
+            inner: self.into_entries(),
+        }
+    }
+
+    /// Consume the map, yielding all values.
+    pub fn into_values(self) -> IntoValues<K, V> {
+        IntoValues {
+            inner: self.into_entries(),
+        }
+    }
+}
+
+/// A view into a single key of an [`OpenAddressingMap`], from
+/// [`entry`](OpenAddressingMap::entry).
+pub enum Entry<'a, K, V, S = DefaultHashBuilder, P = LinearProbe> {
+    Occupied(OccupiedEntry<'a, K, V, S, P>),
+    Vacant(VacantEntry<'a, K, V, S, P>),
+}
+
+/// An entry for a key present in the map.
+pub struct OccupiedEntry<'a, K, V, S = DefaultHashBuilder, P = LinearProbe> {
+    map: &'a mut OpenAddressingMap<K, V, S, P>,
+    index: usize,
+}
+
+/// An entry for a key absent from the map, holding its probed slot.
+pub struct VacantEntry<'a, K, V, S = DefaultHashBuilder, P = LinearProbe> {
+    map: &'a mut OpenAddressingMap<K, V, S, P>,
+    hash: u64,
+    key: K,
+    index: usize,
+}
+
+impl<'a, K, V, S, P> Entry<'a, K, V, S, P>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    P: ProbeStrategy,
+{
+    /// Returns the entry's key.
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// Insert `default` if the key is absent, then return the value.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    /// Insert the result of `default` if the key is absent, then return the
+    /// value.
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Insert `V::default()` if the key is absent, then return the value.
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    /// Update the value in place if the key is present.
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
     }
 }
 
+impl<'a, K, V, S, P> OccupiedEntry<'a, K, V, S, P>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    P: ProbeStrategy,
+{
+    fn parts(&self) -> &(u64, K, V) {
+        self.map.slot(self.index).expect("occupied entry is full")
+    }
+
+    fn parts_mut(&mut self) -> &mut (u64, K, V) {
+        self.map
+            .slot_mut(self.index)
+            .expect("occupied entry is full")
+    }
+
+    /// Returns the entry's key.
+    pub fn key(&self) -> &K {
+        &self.parts().1
+    }
+
+    /// Returns the entry's value.
+    pub fn get(&self) -> &V {
+        &self.parts().2
+    }
+
+    /// Returns the entry's value mutably.
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.parts_mut().2
+    }
+
+    /// Converts the entry into a mutable reference to its value, borrowed
+    /// from the map.
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self
+            .map
+            .slot_mut(self.index)
+            .expect("occupied entry is full")
+            .2
+    }
+
+    /// Replace the value, returning the old one.
+    pub fn insert(&mut self, value: V) -> V {
+        std::mem::replace(self.get_mut(), value)
+    }
+
+    /// Remove the entry from the map, returning its key and value.
+    pub fn remove_entry(self) -> (K, V) {
+        self.map
+            .remove_at(self.index)
+            .expect("occupied entry is full")
+    }
+
+    /// Remove the entry from the map, returning its value.
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+}
+
+impl<'a, K, V, S, P> VacantEntry<'a, K, V, S, P>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    P: ProbeStrategy,
+{
+    /// Returns the key that would be inserted.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Take back ownership of the key.
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    /// Insert the value, returning a mutable reference to it.
+    pub fn insert(self, value: V) -> &'a mut V {
+        let index = self
+            .map
+            .insert_vacant(self.index, self.hash, self.key, value);
+        &mut self.map.slot_mut(index).expect("slot was just filled").2
+    }
+}
+
+/// A view into the entry for a hash and match function, from
+/// [`raw_entry`](OpenAddressingMap::raw_entry).
+pub enum RawEntry<'a, K, V, S = DefaultHashBuilder, P = LinearProbe> {
+    Occupied(OccupiedEntry<'a, K, V, S, P>),
+    Vacant(RawVacantEntry<'a, K, V, S, P>),
+}
+
+/// A raw entry with no matching key, holding its hash and probed slot.
+pub struct RawVacantEntry<'a, K, V, S = DefaultHashBuilder, P = LinearProbe> {
+    map: &'a mut OpenAddressingMap<K, V, S, P>,
+    hash: u64,
+    index: usize,
+}
+
+impl<'a, K, V, S, P> RawVacantEntry<'a, K, V, S, P>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    P: ProbeStrategy,
+{
+    /// Insert a key and value under the entry's hash, returning mutable
+    /// references to both.
+    ///
+    /// The key must not already be in the map, and must hash to the entry's
+    /// hash under the map's hasher.
+    pub fn insert(self, key: K, value: V) -> (&'a mut K, &'a mut V) {
+        let index = self.map.insert_vacant(self.index, self.hash, key, value);
+        let (_, key, value) = self.map.slot_mut(index).expect("slot was just filled");
+        (key, value)
+    }
+}
+
+/// A mutable cursor over the occupied slots of an [`OpenAddressingMap`].
+///
+/// Removing an entry leaves a tombstone in place, so the positions of the
+/// remaining entries are unaffected and traversal can continue.
+pub struct CursorMut<'a, K, V, S = DefaultHashBuilder, P = LinearProbe> {
+    map: &'a mut OpenAddressingMap<K, V, S, P>,
+    index: usize,
+}
+
+impl<K, V, S, P> CursorMut<'_, K, V, S, P> {
+    fn seek_occupied(&mut self) {
+        while self.index < self.map.ctrl.len() {
+            if is_full(self.map.ctrl[self.index]) {
+                return;
+            }
+            self.index += 1;
+        }
+    }
+
+    /// Returns the entry under the cursor, or None once traversal is done.
+    pub fn current(&mut self) -> Option<(&K, &mut V)> {
+        if self.index >= self.map.ctrl.len() {
+            return None;
+        }
+        let (_, key, value) = self.map.slot_mut(self.index)?;
+        Some((&*key, value))
+    }
+
+    /// Advance the cursor to the next occupied slot.
+    pub fn move_next(&mut self) {
+        if self.index < self.map.ctrl.len() {
+            self.index += 1;
+            self.seek_occupied();
+        }
+    }
+
+    /// Remove the entry under the cursor and advance to the next one.
+    ///
+    /// Returns the removed key-value pair, or None if the cursor is at the end.
+    pub fn remove_current(&mut self) -> Option<(K, V)> {
+        if self.index >= self.map.ctrl.len() {
+            return None;
+        }
+        let (_, key, value) = self.map.take_slot(self.index)?;
+        self.map.size -= 1;
+        self.map.tombstones += 1;
+        self.move_next();
+        Some((key, value))
+    }
+
+    /// Replace the value under the cursor, returning the previous value.
+    pub fn replace_current(&mut self, value: V) -> Option<V> {
+        if self.index >= self.map.ctrl.len() {
+            return None;
+        }
+        let (_, _, old) = self.map.slot_mut(self.index)?;
+        Some(std::mem::replace(old, value))
+    }
+}
+
+/// An iterator over the key-value pairs of an [`OpenAddressingMap`].
+pub struct Iter<'a, K, V> {
+    inner: Zip<std::slice::Iter<'a, u8>, std::slice::Iter<'a, Slot<K, V>>>,
+    remaining: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        for (&ctrl, slot) in self.inner.by_ref() {
+            if is_full(ctrl) {
+                self.remaining -= 1;
+                // SAFETY: a full control byte marks an initialized slot.
+                let (_, key, value) = unsafe { slot.assume_init_ref() };
+                return Some((key, value));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+impl<K, V> FusedIterator for Iter<'_, K, V> {}
+
+/// An iterator over the keys of an [`OpenAddressingMap`].
+pub struct Keys<'a, K, V> {
+    inner: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, _)| key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for Keys<'_, K, V> {}
+
+impl<K, V> FusedIterator for Keys<'_, K, V> {}
+
+/// An iterator over the values of an [`OpenAddressingMap`].
+pub struct Values<'a, K, V> {
+    inner: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for Values<'_, K, V> {}
+
+impl<K, V> FusedIterator for Values<'_, K, V> {}
+
+struct IntoEntries<K, V> {
+    inner: Zip<std::vec::IntoIter<u8>, std::vec::IntoIter<Slot<K, V>>>,
+    remaining: usize,
+}
+
+impl<K, V> Iterator for IntoEntries<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        for (ctrl, slot) in self.inner.by_ref() {
+            if is_full(ctrl) {
+                self.remaining -= 1;
+                // SAFETY: a full control byte marks an initialized slot, and
+                // the slot is moved out of the table so it is read only once.
+                let (_, key, value) = unsafe { slot.assume_init() };
+                return Some((key, value));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> Drop for IntoEntries<K, V> {
+    fn drop(&mut self) {
+        // Drop the entries that were never yielded.
+        for _ in self.by_ref() {}
+    }
+}
+
+/// An owning iterator over the keys of an [`OpenAddressingMap`].
+pub struct IntoKeys<K, V> {
+    inner: IntoEntries<K, V>,
+}
+
+impl<K, V> Iterator for IntoKeys<K, V> {
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, _)| key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoKeys<K, V> {}
+
+impl<K, V> FusedIterator for IntoKeys<K, V> {}
+
+/// An owning iterator over the values of an [`OpenAddressingMap`].
+pub struct IntoValues<K, V> {
+    inner: IntoEntries<K, V>,
+}
+
+impl<K, V> Iterator for IntoValues<K, V> {
+    type Item = V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoValues<K, V> {}
+
+impl<K, V> FusedIterator for IntoValues<K, V> {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::hash::Hasher;
+
+    /// Sends every key to the same home bucket.
+    #[derive(Default)]
+    struct ConstantHasher;
+
+    impl Hasher for ConstantHasher {
+        fn finish(&self) -> u64 {
+            7
+        }
+        fn write(&mut self, _bytes: &[u8]) {}
+    }
+
+    fn validate<K: Hash + Eq, V, S: BuildHasher>(map: &HashMap<K, V, S>) {
+        if cfg!(debug_assertions) {
+            map.debug_validate();
+        }
+    }
 
     #[test]
     fn test_new() {
@@ -273,6 +1373,7 @@ mod tests {
         let mut map = HashMap::new();
         map.insert("key".to_string(), "value".to_string());
         let removed = map.remove(&"key".to_string());
+        validate(&map);
         assert_eq!(removed, Some("value".to_string()));
         assert!(map.get(&"key".to_string()).is_none());
         assert!(map.is_empty());
@@ -292,6 +1393,7 @@ mod tests {
         map.insert("key1".to_string(), "value1".to_string());
         map.insert("key2".to_string(), "value2".to_string());
         map.clear();
+        validate(&map);
         assert!(map.is_empty());
         assert!(map.get(&"key1".to_string()).is_none());
     }
@@ -302,6 +1404,7 @@ mod tests {
         for i in 0..100 {
             map.insert(format!("key{}", i), format!("value{}", i));
         }
+        validate(&map);
         assert_eq!(map.len(), 100);
         for i in 0..100 {
             assert_eq!(map.get(&format!("key{}", i)), Some(&format!("value{}", i)));
@@ -315,8 +1418,428 @@ mod tests {
         map.insert("key2".to_string(), "value2".to_string());
         map.remove(&"key1".to_string());
         map.insert("key3".to_string(), "value3".to_string());
+        validate(&map);
         assert_eq!(map.len(), 2);
         assert!(map.contains_key(&"key2".to_string()));
         assert!(map.contains_key(&"key3".to_string()));
     }
+
+    #[test]
+    fn test_into_keys_and_values() {
+        let mut map = HashMap::new();
+        for i in 0..10 {
+            map.insert(i, format!("value{}", i));
+        }
+        map.remove(&3);
+
+        let mut keys: Vec<i32> = map.clone().into_keys().collect();
+        keys.sort();
+        assert_eq!(keys, vec![0, 1, 2, 4, 5, 6, 7, 8, 9]);
+
+        let mut values: Vec<String> = map.into_values().collect();
+        values.sort();
+        assert_eq!(values.len(), 9);
+        assert!(!values.contains(&"value3".to_string()));
+    }
+
+    #[test]
+    fn test_cursor_mut_remove_until_budget() {
+        let mut map = HashMap::new();
+        for i in 0..50 {
+            map.insert(i, i * 10);
+        }
+
+        let mut removed = 0;
+        let mut cursor = map.cursor_mut();
+        while let Some((_, value)) = cursor.current() {
+            if removed < 20 && *value % 20 == 0 {
+                cursor.remove_current();
+                removed += 1;
+            } else {
+                *value += 1;
+                cursor.move_next();
+            }
+        }
+
+        assert_eq!(removed, 20);
+        validate(&map);
+        assert_eq!(map.len(), 30);
+        assert_eq!(map.values().filter(|v| **v % 10 == 1).count(), 30);
+        assert_eq!(map.keys().filter(|k| **k % 2 == 0).count(), 5);
+    }
+
+    #[test]
+    fn test_cursor_mut_replace() {
+        let mut map = HashMap::new();
+        map.insert("a".to_string(), 1);
+        map.insert("b".to_string(), 2);
+
+        let mut cursor = map.cursor_mut();
+        while cursor.current().is_some() {
+            let (key, value) = cursor.current().unwrap();
+            let replacement = *value * 100 + key.len() as i32;
+            assert!(cursor.replace_current(replacement).is_some());
+            cursor.move_next();
+        }
+        assert!(cursor.remove_current().is_none());
+        assert!(cursor.replace_current(0).is_none());
+
+        assert_eq!(map.get(&"a".to_string()), Some(&101));
+        assert_eq!(map.get(&"b".to_string()), Some(&201));
+    }
+
+    #[test]
+    fn test_insert_batch() {
+        let mut map = HashMap::new();
+        map.insert(0, "old".to_string());
+        map.insert_batch((0..1000).map(|i| (i, format!("value{}", i))));
+        map.insert_batch(vec![(5, "first".to_string()), (5, "second".to_string())]);
+        validate(&map);
+
+        assert_eq!(map.len(), 1000);
+        assert_eq!(map.get(&0), Some(&"value0".to_string()));
+        assert_eq!(map.get(&5), Some(&"second".to_string()));
+        assert_eq!(map.get(&999), Some(&"value999".to_string()));
+        assert!(map.load_factor() < MAX_LOAD_FACTOR);
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut map = HashMap::new();
+        let stats = map.stats();
+        assert_eq!(stats.occupied, 0);
+        assert_eq!(stats.max_probe_length, 0);
+        assert_eq!(stats.avg_probe_length, 0.0);
+
+        for i in 0..100 {
+            map.insert(i, i);
+        }
+        for i in 0..10 {
+            map.remove(&i);
+        }
+        validate(&map);
+
+        let stats = map.stats();
+        assert_eq!(stats.occupied, 90);
+        assert_eq!(stats.tombstones, 10);
+        assert_eq!(stats.capacity, map.capacity());
+        assert_eq!(stats.probe_length_histogram.iter().sum::<usize>(), 90);
+        assert_eq!(stats.probe_length_histogram.len(), stats.max_probe_length);
+        assert!(stats.avg_probe_length >= 1.0);
+        assert!(stats.avg_probe_length <= stats.max_probe_length as f64);
+    }
+
+    #[test]
+    fn test_debug_validate_churn() {
+        let mut map = HashMap::with_capacity(16);
+        for round in 0..20 {
+            for i in 0..40 {
+                map.insert(i * 7 + round, i);
+            }
+            for i in 0..30 {
+                map.remove(&(i * 7 + round));
+            }
+            validate(&map);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "size does not match")]
+    fn test_debug_validate_detects_size_mismatch() {
+        let mut map = HashMap::new();
+        map.insert(1, 1);
+        map.size = 2;
+        map.debug_validate();
+    }
+
+    #[test]
+    #[should_panic(expected = "unreachable")]
+    fn test_debug_validate_detects_broken_chain() {
+        let mut map = HashMap::new();
+        map.insert(1, 1);
+        let index = map.ctrl.iter().position(|&ctrl| is_full(ctrl)).unwrap();
+        let next = (index + 1) % map.ctrl.len();
+        map.ctrl.swap(index, next);
+        map.slots.swap(index, next);
+        map.debug_validate();
+    }
+
+    #[test]
+    #[should_panic(expected = "stale hash")]
+    fn test_debug_validate_detects_stale_hash() {
+        let mut map = HashMap::new();
+        map.insert(1, 1);
+        for index in 0..map.ctrl.len() {
+            if let Some((hash, _, _)) = map.slot_mut(index) {
+                *hash ^= 1;
+            }
+        }
+        map.debug_validate();
+    }
+
+    #[test]
+    fn test_resize_reuses_cached_hashes() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static HASH_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+        #[derive(Clone, PartialEq, Eq)]
+        struct CountingKey(u32);
+
+        impl Hash for CountingKey {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                HASH_CALLS.fetch_add(1, Ordering::Relaxed);
+                self.0.hash(state);
+            }
+        }
+
+        let mut map = HashMap::new();
+        for i in 0..1000 {
+            map.insert(CountingKey(i), i);
+        }
+        assert_eq!(HASH_CALLS.load(Ordering::Relaxed), 1000);
+        for i in 0..1000 {
+            assert_eq!(map.get(&CountingKey(i)), Some(&i));
+        }
+    }
+
+    #[test]
+    fn test_entries_dropped_exactly_once() {
+        use std::rc::Rc;
+
+        let value = Rc::new(());
+        let mut map = HashMap::new();
+        for i in 0..100 {
+            map.insert(i, Rc::clone(&value));
+        }
+        for i in 0..20 {
+            map.remove(&i);
+        }
+        for i in 20..30 {
+            map.insert(i, Rc::clone(&value));
+        }
+        assert_eq!(Rc::strong_count(&value), 81);
+
+        let clone = map.clone();
+        assert_eq!(Rc::strong_count(&value), 161);
+        drop(clone);
+
+        let mut cursor = map.cursor_mut();
+        cursor.remove_current();
+        assert_eq!(Rc::strong_count(&value), 80);
+
+        let mut values = map.clone().into_values();
+        values.next();
+        drop(values);
+        assert_eq!(Rc::strong_count(&value), 80);
+
+        map.clear();
+        assert_eq!(Rc::strong_count(&value), 1);
+        for i in 0..10 {
+            map.insert(i, Rc::clone(&value));
+        }
+        drop(map);
+        assert_eq!(Rc::strong_count(&value), 1);
+    }
+
+    #[test]
+    fn test_iterators_exact_size() {
+        let mut map = HashMap::new();
+        for i in 0..20 {
+            map.insert(i, i * 2);
+        }
+        map.remove(&7);
+
+        let mut iter = map.iter();
+        assert_eq!(iter.len(), 19);
+        assert_eq!(iter.size_hint(), (19, Some(19)));
+        iter.next();
+        assert_eq!(iter.len(), 18);
+        assert_eq!(iter.by_ref().count(), 18);
+        assert!(iter.next().is_none());
+        assert!(iter.next().is_none());
+
+        assert_eq!(map.keys().len(), 19);
+        assert_eq!(map.values().len(), 19);
+        assert_eq!(map.clone().into_keys().len(), 19);
+        assert_eq!(map.clone().into_values().size_hint(), (19, Some(19)));
+        assert_eq!(map.values().sum::<i32>(), (0..20).sum::<i32>() * 2 - 14);
+    }
+
+    #[test]
+    fn test_custom_hasher() {
+        let mut map: HashMap<i32, i32, BuildHasherDefault<ConstantHasher>> = HashMap::default();
+        for i in 0..100 {
+            map.insert(i, i * 2);
+        }
+        map.remove(&50);
+        validate(&map);
+        assert_eq!(map.len(), 99);
+        for i in 0..100 {
+            assert_eq!(map.get(&i), (i != 50).then_some(&(i * 2)));
+        }
+        assert_eq!(map.stats().max_probe_length, 100);
+    }
+
+    #[test]
+    fn test_shrink_threshold() {
+        let mut map = HashMap::new();
+        for i in 0..10_000 {
+            map.insert(i, i);
+        }
+        let peak = map.capacity();
+
+        for i in 0..5_000 {
+            map.remove(&i);
+        }
+        assert_eq!(map.capacity(), peak);
+
+        map.set_shrink_threshold(Some(0.125));
+        for i in 5_000..9_990 {
+            map.remove(&i);
+            assert!(map.len() as f64 >= map.capacity() as f64 * 0.125 / 2.0);
+        }
+        validate(&map);
+        assert!(map.capacity() < peak / 64);
+        assert!(map.capacity() >= DEFAULT_CAPACITY);
+        for i in 9_990..10_000 {
+            assert_eq!(map.get(&i), Some(&i));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "shrink threshold")]
+    fn test_shrink_threshold_rejects_large_values() {
+        let mut map: HashMap<i32, i32> = HashMap::new();
+        map.set_shrink_threshold(Some(0.5));
+    }
+
+    #[test]
+    fn test_delete_churn_does_not_grow() {
+        let mut map = HashMap::new();
+        let initial_capacity = map.capacity();
+        for i in 0..10_000 {
+            map.insert(i, i);
+            if i >= 4 {
+                map.remove(&(i - 4));
+            }
+        }
+        validate(&map);
+        assert_eq!(map.len(), 4);
+        assert_eq!(map.capacity(), initial_capacity);
+        for i in 9_996..10_000 {
+            assert_eq!(map.get(&i), Some(&i));
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_metrics_count_probes_and_tombstones() {
+        let mut map: HashMap<i32, i32, BuildHasherDefault<ConstantHasher>> = HashMap::default();
+        for i in 0..3 {
+            map.insert(i, i);
+        }
+        map.remove(&0);
+        let before = map.metrics();
+        assert_eq!(map.get(&2), Some(&2));
+        let after = map.metrics();
+        assert_eq!(after.probes - before.probes, 3);
+        assert_eq!(after.comparisons - before.comparisons, 2);
+        assert_eq!(after.tombstone_hits - before.tombstone_hits, 1);
+        assert_eq!(after.resizes, 0);
+
+        for i in 3..20 {
+            map.insert(i, i);
+        }
+        assert_eq!(map.metrics().resizes, 1);
+        assert_eq!(map.clone().metrics(), Metrics::default());
+    }
+
+    #[test]
+    fn test_probe_length_limit_grows_clustered_table() {
+        let mut limited = HashMap::with_capacity(1024);
+        limited.set_probe_length_limit(Some(8));
+        let mut unlimited = HashMap::with_capacity(1024);
+        // Keys whose home buckets all fall in the first 64 slots.
+        let keys: Vec<u64> = (0..)
+            .filter(|&k| unlimited.hash_key(&k) & 1023 < 64)
+            .take(300)
+            .collect();
+        for &key in &keys {
+            limited.insert(key, key);
+            unlimited.insert(key, key);
+        }
+        validate(&limited);
+        assert_eq!(unlimited.capacity(), 1024);
+        assert!(unlimited.stats().max_probe_length > 8);
+        assert!(limited.capacity() > 1024);
+        assert!(keys.iter().all(|k| limited.get(k) == Some(k)));
+    }
+
+    #[test]
+    fn test_probe_length_limit_ignores_colliding_hashes() {
+        let mut map: HashMap<i32, i32, BuildHasherDefault<ConstantHasher>> =
+            HashMap::with_capacity_and_hasher(64, BuildHasherDefault::default());
+        map.set_probe_length_limit(Some(4));
+        for i in 0..8 {
+            map.insert(i, i);
+        }
+        validate(&map);
+        assert_eq!(map.capacity(), 64);
+    }
+
+    #[test]
+    fn test_entry_api() {
+        let mut map = HashMap::new();
+        *map.entry("a").or_insert(0) += 1;
+        *map.entry("a").or_insert(0) += 1;
+        map.entry("b").and_modify(|v| *v = 100).or_default();
+        assert_eq!(map.get(&"a"), Some(&2));
+        assert_eq!(map.get(&"b"), Some(&0));
+
+        match map.entry("a") {
+            Entry::Occupied(entry) => assert_eq!(entry.remove_entry(), ("a", 2)),
+            Entry::Vacant(_) => unreachable!(),
+        }
+        assert_eq!(map.entry("a").key(), &"a");
+        assert_eq!(map.len(), 1);
+        validate(&map);
+    }
+
+    #[test]
+    fn test_entry_insert_after_probe_length_rebuild() {
+        let mut map = HashMap::with_capacity(1024);
+        map.set_probe_length_limit(Some(4));
+        let keys: Vec<u64> = (0..)
+            .filter(|&k| map.hash_key(&k) & 1023 < 16)
+            .take(200)
+            .collect();
+        for &key in &keys {
+            assert_eq!(*map.entry(key).or_insert(key), key);
+        }
+        validate(&map);
+        assert!(keys.iter().all(|k| map.get(k) == Some(k)));
+    }
+
+    #[test]
+    fn test_raw_entry_by_borrowed_key() {
+        let mut map: HashMap<String, usize> = HashMap::new();
+        for word in ["x", "yy", "x", "zzz", "yy", "x"] {
+            let hash = map.hasher().hash_one(word);
+            match map.raw_entry(hash, |k| k == word) {
+                RawEntry::Occupied(mut entry) => *entry.get_mut() += 1,
+                RawEntry::Vacant(entry) => {
+                    entry.insert(word.to_string(), 1);
+                }
+            }
+        }
+        let hash = map.hasher().hash_one("x");
+        assert_eq!(
+            map.raw_get(hash, |k| k == "x"),
+            Some((&"x".to_string(), &3))
+        );
+        assert_eq!(map.get(&"yy".to_string()), Some(&2));
+        assert_eq!(map.len(), 3);
+        validate(&map);
+    }
 }
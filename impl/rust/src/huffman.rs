@@ -0,0 +1,393 @@
+//! Huffman coding: optimal prefix codes built from symbol frequencies,
+//! kept in canonical form, with encoding and decoding of symbol streams
+//! and a byte-stream compressor on top.
+//!
+//! Encoded bits are packed into bytes first bit most significant, and the
+//! last byte is padded with zeros, so a decoder needs the symbol count to
+//! know where the stream ends.
+
+use crate::binary_heap::{BinaryHeap, HeapOrder};
+use crate::counter::Counter;
+use crate::hashmap::HashMap;
+use std::hash::Hash;
+
+/// The longest codeword a [`HuffmanCode`] can hold.
+pub const MAX_CODE_LEN: u8 = 64;
+
+const LENGTHS: usize = MAX_CODE_LEN as usize + 1;
+
+/// Size of the header [`compress`] writes: one code length per byte value,
+/// then the byte count.
+const HEADER_LEN: usize = 256 + 8;
+
+/// One symbol's code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Codeword {
+    /// The code, in the low `len` bits, first bit most significant.
+    pub bits: u64,
+    /// The number of bits.
+    pub len: u8,
+}
+
+/// A canonical Huffman code: a prefix code over a set of symbols, with
+/// shorter codewords for more frequent ones.
+///
+/// Construction repeatedly merges the two lightest subtrees, taken from a
+/// min-heap, into one whose weight is their sum; a symbol's codeword
+/// length is its leaf's depth in the final tree, which minimizes the total
+/// encoded length over all prefix codes. The tree itself is then thrown
+/// away. The codewords are reassigned canonically: in order of length, and
+/// by symbol within a length, each is the previous one plus one, shifted
+/// left when the length grows. The lengths alone thus determine the code,
+/// which is all a compressed stream needs to carry, and the codewords of
+/// each length form a run of consecutive integers, so decoding needs only
+/// the first codeword and count per length rather than a tree walk.
+/// Encoding looks codewords up in a hash map from symbol.
+#[derive(Debug, Clone)]
+pub struct HuffmanCode<S> {
+    /// Every symbol in canonical order: by code length, then by symbol.
+    symbols: Vec<S>,
+    lengths: Vec<u8>,
+    codes: HashMap<S, Codeword>,
+    /// For each length, the first codeword of that length, the index in
+    /// `symbols` of its symbol, and the number of codewords.
+    first_code: [u64; LENGTHS],
+    first_index: [usize; LENGTHS],
+    count: [usize; LENGTHS],
+    max_len: u8,
+}
+
+/// Returns the depth of each leaf in a Huffman tree over `weights`.
+///
+/// # Panics
+///
+/// Panics if a depth exceeds [`MAX_CODE_LEN`].
+fn code_lengths(weights: &[u64]) -> Vec<u8> {
+    let n = weights.len();
+    if n <= 1 {
+        // A lone symbol still needs one bit per occurrence.
+        return vec![1; n];
+    }
+    // Nodes 0..n are the leaves; each merge adds one internal node, and the
+    // last is the root. Ties break on the node index, so the shape is
+    // deterministic.
+    let root = 2 * n - 2;
+    let mut parent = vec![0; root];
+    let mut heap = BinaryHeap::with_capacity(HeapOrder::Min, n);
+    for (i, &weight) in weights.iter().enumerate() {
+        heap.push((u128::from(weight), i));
+    }
+    for node in n..=root {
+        let (a_weight, a) = heap.pop().expect("two subtrees remain");
+        let (b_weight, b) = heap.pop().expect("two subtrees remain");
+        parent[a] = node;
+        parent[b] = node;
+        heap.push((a_weight + b_weight, node));
+    }
+    // Every node comes before its parent, so one pass down from the root
+    // fills in every depth.
+    let mut depth = vec![0; root + 1];
+    for node in (0..root).rev() {
+        depth[node] = depth[parent[node]] + 1;
+    }
+    depth.truncate(n);
+    depth
+        .into_iter()
+        .map(|d| {
+            assert!(
+                d <= MAX_CODE_LEN as usize,
+                "codeword longer than {} bits",
+                MAX_CODE_LEN
+            );
+            d as u8
+        })
+        .collect()
+}
+
+impl<S: Ord + Hash + Clone> HuffmanCode<S> {
+    /// Build the Huffman code for symbols with the given frequencies.
+    ///
+    /// Symbols with frequency zero get no codeword, and repeated symbols
+    /// have their frequencies added.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a codeword would be longer than [`MAX_CODE_LEN`] bits,
+    /// which takes frequencies growing like the Fibonacci numbers and a
+    /// total in the tens of trillions.
+    pub fn from_frequencies<I: IntoIterator<Item = (S, u64)>>(frequencies: I) -> Self {
+        let mut leaves: Vec<(S, u64)> = frequencies
+            .into_iter()
+            .filter(|&(_, frequency)| frequency > 0)
+            .collect();
+        leaves.sort_by(|a, b| a.0.cmp(&b.0));
+        leaves.dedup_by(|later, earlier| {
+            let repeated = later.0 == earlier.0;
+            if repeated {
+                earlier.1 += later.1;
+            }
+            repeated
+        });
+        let weights: Vec<u64> = leaves.iter().map(|&(_, frequency)| frequency).collect();
+        let lengths = code_lengths(&weights);
+        let symbols = leaves.into_iter().map(|(symbol, _)| symbol);
+        Self::from_lengths(symbols.zip(lengths)).expect("Huffman code lengths form a prefix code")
+    }
+
+    /// Build the Huffman code for `symbols`, by how often each occurs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a codeword would be longer than [`MAX_CODE_LEN`] bits.
+    pub fn from_symbols<I: IntoIterator<Item = S>>(symbols: I) -> Self {
+        let mut counter = Counter::new();
+        for symbol in symbols {
+            counter.add(symbol, 1);
+        }
+        Self::from_frequencies(counter.iter().map(|(symbol, n)| (symbol.clone(), n)))
+    }
+
+    /// Rebuild the canonical code with the given codeword lengths, as
+    /// reported by [`lengths`](Self::lengths).
+    ///
+    /// Symbols of length zero get no codeword. Returns `None` if a symbol
+    /// repeats, a length exceeds [`MAX_CODE_LEN`], or the lengths are too
+    /// short to form a prefix code.
+    pub fn from_lengths<I: IntoIterator<Item = (S, u8)>>(lengths: I) -> Option<Self> {
+        let mut entries: Vec<(u8, S)> = lengths
+            .into_iter()
+            .filter(|&(_, len)| len > 0)
+            .map(|(symbol, len)| (len, symbol))
+            .collect();
+        if entries.iter().any(|&(len, _)| len > MAX_CODE_LEN) {
+            return None;
+        }
+        entries.sort();
+
+        let mut codes = HashMap::with_capacity(entries.len());
+        let mut first_code = [0; LENGTHS];
+        let mut first_index = [0; LENGTHS];
+        let mut count = [0; LENGTHS];
+        // Wide enough to shift a 64-bit code left without losing bits.
+        let mut code: u128 = 0;
+        let mut previous_len = 0;
+        for (i, (len, symbol)) in entries.iter().enumerate() {
+            code <<= len - previous_len;
+            if code >> len != 0 {
+                // Every codeword of this length is taken.
+                return None;
+            }
+            let l = *len as usize;
+            if count[l] == 0 {
+                first_code[l] = code as u64;
+                first_index[l] = i;
+            }
+            count[l] += 1;
+            let codeword = Codeword {
+                bits: code as u64,
+                len: *len,
+            };
+            if codes.insert(symbol.clone(), codeword).is_some() {
+                return None;
+            }
+            code += 1;
+            previous_len = *len;
+        }
+        let (lengths, symbols) = entries.into_iter().unzip();
+        Some(Self {
+            symbols,
+            lengths,
+            codes,
+            first_code,
+            first_index,
+            count,
+            max_len: previous_len,
+        })
+    }
+
+    /// Returns the codeword for `symbol`, if it has one.
+    pub fn codeword(&self, symbol: &S) -> Option<Codeword> {
+        self.codes.get(symbol).copied()
+    }
+
+    /// Encode `symbols` as packed bits, zero-padding the last byte.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a symbol has no codeword.
+    pub fn encode<'a, I>(&self, symbols: I) -> Vec<u8>
+    where
+        I: IntoIterator<Item = &'a S>,
+        S: 'a,
+    {
+        let mut bytes = Vec::new();
+        // Bits waiting to be written are the low `pending` bits; anything
+        // above them is stale and masked off by the byte casts.
+        let mut buffer: u128 = 0;
+        let mut pending = 0;
+        for symbol in symbols {
+            let codeword = self.codeword(symbol).expect("symbol has no codeword");
+            buffer = buffer << codeword.len | u128::from(codeword.bits);
+            pending += u32::from(codeword.len);
+            while pending >= 8 {
+                pending -= 8;
+                bytes.push((buffer >> pending) as u8);
+            }
+        }
+        if pending > 0 {
+            bytes.push((buffer << (8 - pending)) as u8);
+        }
+        bytes
+    }
+
+    /// Decode `count` symbols from packed bits.
+    ///
+    /// Returns `None` if the bits run out first or hold a sequence that is
+    /// no codeword. Bits after the last symbol are ignored.
+    pub fn decode(&self, bytes: &[u8], count: usize) -> Option<Vec<S>> {
+        let mut bits = bytes
+            .iter()
+            .flat_map(|&byte| (0..8).rev().map(move |i| u64::from(byte >> i & 1)));
+        // Each symbol takes at least one bit, which bounds a bogus count.
+        let mut symbols = Vec::with_capacity(count.min(bytes.len() * 8));
+        while symbols.len() < count {
+            let mut code = 0;
+            let mut len = 0;
+            loop {
+                if len == self.max_len as usize {
+                    return None;
+                }
+                code = code << 1 | bits.next()?;
+                len += 1;
+                let offset = code.wrapping_sub(self.first_code[len]);
+                if code >= self.first_code[len] && offset < self.count[len] as u64 {
+                    let index = self.first_index[len] + offset as usize;
+                    symbols.push(self.symbols[index].clone());
+                    break;
+                }
+            }
+        }
+        Some(symbols)
+    }
+}
+
+impl<S> HuffmanCode<S> {
+    /// Returns the number of symbols with a codeword.
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    /// Returns true if no symbol has a codeword.
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    /// Iterate over each symbol and its codeword length, in canonical
+    /// order: by length, then by symbol.
+    pub fn lengths(&self) -> impl Iterator<Item = (&S, u8)> {
+        self.symbols.iter().zip(self.lengths.iter().copied())
+    }
+}
+
+/// Compress `data` with the Huffman code for its bytes.
+///
+/// The output starts with a fixed 264-byte header, the codeword length of
+/// each byte value and then the byte count, so it only comes out smaller
+/// for inputs of a few hundred bytes or more.
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    let code = HuffmanCode::from_symbols(data.iter().copied());
+    let mut lengths = [0; 256];
+    for (&byte, len) in code.lengths() {
+        lengths[byte as usize] = len;
+    }
+    let mut output = Vec::with_capacity(HEADER_LEN + data.len());
+    output.extend_from_slice(&lengths);
+    output.extend_from_slice(&(data.len() as u64).to_le_bytes());
+    output.extend(code.encode(data));
+    output
+}
+
+/// Decompress the output of [`compress`].
+///
+/// Returns `None` if `data` is not a valid compressed stream.
+pub fn decompress(data: &[u8]) -> Option<Vec<u8>> {
+    if data.len() < HEADER_LEN {
+        return None;
+    }
+    let (lengths, rest) = data.split_at(256);
+    let (count, bits) = rest.split_at(8);
+    let code = HuffmanCode::from_lengths((0..=255).zip(lengths.iter().copied()))?;
+    let count = u64::from_le_bytes(count.try_into().unwrap());
+    code.decode(bits, usize::try_from(count).ok()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_codewords() {
+        // The textbook example: a is far more frequent than the rest.
+        let code = HuffmanCode::from_frequencies([
+            ('a', 45),
+            ('b', 13),
+            ('c', 12),
+            ('d', 16),
+            ('e', 9),
+            ('f', 5),
+            ('g', 0),
+        ]);
+        let codewords: Vec<String> = "abcdef"
+            .chars()
+            .map(|c| {
+                let Codeword { bits, len } = code.codeword(&c).unwrap();
+                format!("{:0width$b}", bits, width = len as usize)
+            })
+            .collect();
+        assert_eq!(codewords, ["0", "100", "101", "110", "1110", "1111"]);
+        assert_eq!(code.codeword(&'g'), None);
+        assert_eq!(code.len(), 6);
+
+        let text: Vec<char> = "facade".chars().collect();
+        let bits = code.encode(&text);
+        // 1111 0 101 0 110 1110, then "bad" to show the padding.
+        assert_eq!(bits, [0b1111_0101, 0b0110_1110]);
+        let bad: Vec<char> = "bad".chars().collect();
+        assert_eq!(code.encode(&bad), [0b1000_1100]);
+        assert_eq!(code.decode(&bits, text.len()), Some(text));
+    }
+
+    #[test]
+    fn test_degenerate_codes() {
+        let empty = HuffmanCode::<u8>::from_symbols([]);
+        assert!(empty.is_empty());
+        assert_eq!(empty.encode(&[]), []);
+        assert_eq!(empty.decode(&[], 0), Some(vec![]));
+        assert_eq!(empty.decode(&[0], 1), None);
+
+        let single = HuffmanCode::from_symbols([7u8; 10]);
+        assert_eq!(single.codeword(&7), Some(Codeword { bits: 0, len: 1 }));
+        assert_eq!(single.decode(&[0, 0], 10), Some(vec![7; 10]));
+        // Out of bits, and a bit sequence that is no codeword.
+        assert_eq!(single.decode(&[0], 10), None);
+        assert_eq!(single.decode(&[0x80], 1), None);
+
+        assert_eq!(decompress(&compress(b"")), Some(vec![]));
+        assert_eq!(decompress(b"short"), None);
+    }
+
+    #[test]
+    fn test_from_lengths_validates() {
+        let code = HuffmanCode::from_frequencies([(1, 1), (2, 1), (3, 2)]);
+        let lengths: Vec<(i32, u8)> = code.lengths().map(|(&s, len)| (s, len)).collect();
+        assert_eq!(lengths, [(3, 1), (1, 2), (2, 2)]);
+        let rebuilt = HuffmanCode::from_lengths(lengths).unwrap();
+        assert_eq!(rebuilt.codeword(&2), code.codeword(&2));
+        // Three one-bit codewords cannot all be distinct.
+        assert!(HuffmanCode::from_lengths([(1, 1), (2, 1), (3, 1)]).is_none());
+        assert!(HuffmanCode::from_lengths([(1, 1), (1, 2)]).is_none());
+        assert!(HuffmanCode::from_lengths([(1, 65)]).is_none());
+        // Gaps in the code are allowed.
+        assert!(HuffmanCode::from_lengths([(1, 2), (2, 3)]).is_some());
+    }
+}
@@ -0,0 +1,313 @@
+//! Hash Map implementation with incremental (gradual) rehashing.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+const DEFAULT_CAPACITY: usize = 16;
+const MAX_LOAD_FACTOR: f64 = 0.75;
+/// Old buckets migrated per mutating operation while a resize is in flight.
+const MIGRATE_PER_OP: usize = 4;
+
+#[derive(Debug, Clone)]
+struct Node<K, V> {
+    key: K,
+    value: V,
+    next: Link<K, V>,
+}
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+type Buckets<K, V> = Vec<Link<K, V>>;
+
+fn empty_buckets<K, V>(capacity: usize) -> Buckets<K, V> {
+    (0..capacity).map(|_| None).collect()
+}
+
+fn chain<K, V>(link: &Link<K, V>) -> impl Iterator<Item = &Node<K, V>> {
+    std::iter::successors(link.as_deref(), |node| node.next.as_deref())
+}
+
+/// A chained hash map that spreads each resize over subsequent operations.
+///
+/// When the load factor is exceeded a table of twice the size is allocated
+/// and becomes the target for new entries, while the old table is kept
+/// around. Every insert or remove then migrates a few old buckets, so no
+/// single operation pays for rehashing the whole map. Lookups consult both
+/// tables until the migration completes.
+///
+/// Buckets are singly linked lists of nodes: an empty bucket array is cheap
+/// to allocate, and migrating a bucket relinks its nodes without copying.
+#[derive(Debug, Clone)]
+pub struct IncrementalHashMap<K, V> {
+    table: Buckets<K, V>,
+    /// Buckets still to migrate; they are popped from the back.
+    old: Buckets<K, V>,
+    /// Bucket count the old table was hashed with.
+    old_capacity: usize,
+    size: usize,
+}
+
+impl<K, V> Default for IncrementalHashMap<K, V>
+where
+    K: Hash + Eq,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> IncrementalHashMap<K, V>
+where
+    K: Hash + Eq,
+{
+    /// Create a new empty IncrementalHashMap.
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// Create a new IncrementalHashMap with the specified number of buckets.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            table: empty_buckets(capacity.max(DEFAULT_CAPACITY)),
+            old: Vec::new(),
+            old_capacity: 0,
+            size: 0,
+        }
+    }
+
+    /// Returns the number of elements in the map.
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Returns true if the map contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Returns the number of buckets in the current (target) table.
+    pub fn capacity(&self) -> usize {
+        self.table.len()
+    }
+
+    /// Returns true while entries are still being migrated from an old table.
+    pub fn is_rehashing(&self) -> bool {
+        !self.old.is_empty()
+    }
+
+    fn hash_key(key: &K) -> usize {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        hasher.finish() as usize
+    }
+
+    /// Index of the old bucket holding `key`, if it has not migrated yet.
+    fn old_index(&self, key: &K) -> Option<usize> {
+        if self.old.is_empty() {
+            return None;
+        }
+        let index = Self::hash_key(key) % self.old_capacity;
+        (index < self.old.len()).then_some(index)
+    }
+
+    /// Move up to `count` old buckets into the current table.
+    fn migrate(&mut self, count: usize) {
+        for _ in 0..count {
+            let Some(mut link) = self.old.pop() else {
+                break;
+            };
+            while let Some(mut node) = link {
+                link = node.next.take();
+                let index = Self::hash_key(&node.key) % self.table.len();
+                node.next = self.table[index].take();
+                self.table[index] = Some(node);
+            }
+        }
+        if self.old.is_empty() {
+            // Release the drained table's allocation.
+            self.old = Vec::new();
+        }
+    }
+
+    fn start_resize(&mut self) {
+        // Finish any migration in flight before starting the next one.
+        self.migrate(usize::MAX);
+        let new_table = empty_buckets(self.table.len() * 2);
+        self.old = std::mem::replace(&mut self.table, new_table);
+        self.old_capacity = self.old.len();
+    }
+
+    fn find_mut(&mut self, key: &K) -> Option<&mut V> {
+        let mut link = match self.old_index(key) {
+            Some(index) => self.old[index].as_deref_mut(),
+            None => None,
+        };
+        let index = Self::hash_key(key) % self.table.len();
+        let mut table_link = self.table[index].as_deref_mut();
+        loop {
+            match link.take() {
+                Some(node) if node.key == *key => return Some(&mut node.value),
+                Some(node) => link = node.next.as_deref_mut(),
+                None => match table_link.take() {
+                    Some(node) => link = Some(node),
+                    None => return None,
+                },
+            }
+        }
+    }
+
+    /// Insert a key-value pair into the map.
+    ///
+    /// Returns the previous value if the key existed.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.migrate(MIGRATE_PER_OP);
+
+        if let Some(old) = self.find_mut(&key) {
+            return Some(std::mem::replace(old, value));
+        }
+
+        if (self.size + 1) as f64 > self.table.len() as f64 * MAX_LOAD_FACTOR {
+            self.start_resize();
+        }
+
+        let index = Self::hash_key(&key) % self.table.len();
+        let next = self.table[index].take();
+        self.table[index] = Some(Box::new(Node { key, value, next }));
+        self.size += 1;
+        None
+    }
+
+    /// Get a reference to the value associated with the key.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let old = self.old_index(key).map(|index| &self.old[index]);
+        let current = &self.table[Self::hash_key(key) % self.table.len()];
+        old.into_iter()
+            .chain(std::iter::once(current))
+            .flat_map(chain)
+            .find(|node| node.key == *key)
+            .map(|node| &node.value)
+    }
+
+    /// Get a mutable reference to the value associated with the key.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.find_mut(key)
+    }
+
+    /// Remove a key-value pair from the map.
+    ///
+    /// Returns the removed value if the key existed.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        self.migrate(MIGRATE_PER_OP);
+
+        let mut candidates = Vec::with_capacity(2);
+        if let Some(index) = self.old_index(key) {
+            candidates.push(&mut self.old[index]);
+        }
+        let index = Self::hash_key(key) % self.table.len();
+        candidates.push(&mut self.table[index]);
+
+        for mut link in candidates {
+            while link.as_ref().is_some_and(|node| node.key != *key) {
+                link = &mut link.as_mut().unwrap().next;
+            }
+            if let Some(mut node) = link.take() {
+                *link = node.next.take();
+                self.size -= 1;
+                return Some(node.value);
+            }
+        }
+        None
+    }
+
+    /// Check if the map contains the given key.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Clear all entries from the map.
+    pub fn clear(&mut self) {
+        self.old = Vec::new();
+        for bucket in &mut self.table {
+            *bucket = None;
+        }
+        self.size = 0;
+    }
+
+    /// Iterate over all key-value pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.old
+            .iter()
+            .chain(self.table.iter())
+            .flat_map(chain)
+            .map(|node| (&node.key, &node.value))
+    }
+
+    /// Iterate over all keys.
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.iter().map(|(k, _)| k)
+    }
+
+    /// Iterate over all values.
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.iter().map(|(_, v)| v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migration_spreads_over_operations() {
+        let mut map = IncrementalHashMap::new();
+        for i in 0..12 {
+            map.insert(i, i);
+        }
+        assert!(!map.is_rehashing());
+
+        map.insert(12, 12);
+        assert!(map.is_rehashing());
+        assert_eq!(map.capacity(), 32);
+        for i in 0..=12 {
+            assert_eq!(map.get(&i), Some(&i));
+        }
+
+        for i in 13..20 {
+            map.insert(i, i);
+        }
+        assert!(!map.is_rehashing());
+        assert_eq!(map.iter().count(), 20);
+    }
+
+    #[test]
+    fn test_operations_during_rehash() {
+        let mut map = IncrementalHashMap::new();
+        for i in 0..13 {
+            map.insert(i, i);
+        }
+        assert!(map.is_rehashing());
+
+        assert_eq!(map.insert(3, 30), Some(3));
+        assert_eq!(map.remove(&5), Some(5));
+        assert_eq!(map.remove(&5), None);
+        *map.get_mut(&7).unwrap() = 70;
+        assert_eq!(map.get(&3), Some(&30));
+        assert_eq!(map.get(&7), Some(&70));
+        assert_eq!(map.len(), 12);
+    }
+
+    #[test]
+    fn test_large_insert_and_clear() {
+        let mut map = IncrementalHashMap::new();
+        for i in 0..10_000 {
+            map.insert(format!("key{}", i), i);
+        }
+        assert_eq!(map.len(), 10_000);
+        for i in 0..10_000 {
+            assert!(map.contains_key(&format!("key{}", i)));
+        }
+        map.clear();
+        assert!(map.is_empty());
+        assert!(!map.is_rehashing());
+        assert!(map.get(&"key1".to_string()).is_none());
+    }
+}
@@ -0,0 +1,281 @@
+//! Insertion-ordered hash map with dense entry storage.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+const DEFAULT_CAPACITY: usize = 16;
+const MAX_LOAD_FACTOR: f64 = 0.75;
+
+/// Index table slot that holds no entry.
+const EMPTY: usize = usize::MAX;
+
+#[derive(Debug, Clone)]
+struct Entry<K, V> {
+    hash: u64,
+    key: K,
+    value: V,
+}
+
+/// A hash map that iterates in insertion order.
+///
+/// Entries live contiguously in a `Vec` in the order they were inserted, and
+/// a linear-probing table of positions into that `Vec` serves lookups.
+/// Iteration walks the dense `Vec` and never touches the table. Removal is
+/// [`swap_remove`](Self::swap_remove): the last entry fills the gap, which
+/// keeps the storage dense at the cost of moving that one entry. Deleting
+/// from the table uses backward shifting, so there are no tombstones.
+#[derive(Debug, Clone)]
+pub struct IndexMap<K, V> {
+    entries: Vec<Entry<K, V>>,
+    indices: Vec<usize>,
+}
+
+impl<K, V> Default for IndexMap<K, V>
+where
+    K: Hash + Eq,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> IndexMap<K, V>
+where
+    K: Hash + Eq,
+{
+    /// Create a new empty IndexMap.
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// Create a new IndexMap with at least the specified capacity.
+    ///
+    /// The capacity is rounded up to a power of two.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let capacity = capacity.max(DEFAULT_CAPACITY).next_power_of_two();
+        Self {
+            entries: Vec::new(),
+            indices: vec![EMPTY; capacity],
+        }
+    }
+
+    /// Returns the number of elements in the map.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the map contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the current capacity of the index table.
+    pub fn capacity(&self) -> usize {
+        self.indices.len()
+    }
+
+    fn hash_key(key: &K) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn mask(&self) -> usize {
+        self.indices.len() - 1
+    }
+
+    /// Find the table slot of `key`, or the empty slot where it would go.
+    fn find_slot(&self, hash: u64, key: &K) -> Result<usize, usize> {
+        let mask = self.mask();
+        let mut slot = hash as usize & mask;
+        loop {
+            match self.indices[slot] {
+                EMPTY => return Err(slot),
+                index if self.entries[index].hash == hash && self.entries[index].key == *key => {
+                    return Ok(slot);
+                }
+                _ => slot = (slot + 1) & mask,
+            }
+        }
+    }
+
+    /// Find the table slot pointing at entry `index`.
+    fn slot_of(&self, index: usize) -> usize {
+        let mask = self.mask();
+        let mut slot = self.entries[index].hash as usize & mask;
+        while self.indices[slot] != index {
+            slot = (slot + 1) & mask;
+        }
+        slot
+    }
+
+    fn resize(&mut self) {
+        let capacity = self.indices.len() * 2;
+        let mask = capacity - 1;
+        self.indices = vec![EMPTY; capacity];
+        for (index, entry) in self.entries.iter().enumerate() {
+            let mut slot = entry.hash as usize & mask;
+            while self.indices[slot] != EMPTY {
+                slot = (slot + 1) & mask;
+            }
+            self.indices[slot] = index;
+        }
+    }
+
+    /// Insert a key-value pair into the map.
+    ///
+    /// A new key goes to the end of the order; updating an existing key keeps
+    /// its position. Returns the old value if the key was present.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if (self.entries.len() + 1) as f64 > self.indices.len() as f64 * MAX_LOAD_FACTOR {
+            self.resize();
+        }
+        let hash = Self::hash_key(&key);
+        match self.find_slot(hash, &key) {
+            Ok(slot) => {
+                let entry = &mut self.entries[self.indices[slot]];
+                Some(std::mem::replace(&mut entry.value, value))
+            }
+            Err(slot) => {
+                self.indices[slot] = self.entries.len();
+                self.entries.push(Entry { hash, key, value });
+                None
+            }
+        }
+    }
+
+    /// Returns the position of `key` in insertion order.
+    pub fn get_index_of(&self, key: &K) -> Option<usize> {
+        let slot = self.find_slot(Self::hash_key(key), key).ok()?;
+        Some(self.indices[slot])
+    }
+
+    /// Get a reference to the value associated with the key.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let index = self.get_index_of(key)?;
+        Some(&self.entries[index].value)
+    }
+
+    /// Get a mutable reference to the value associated with the key.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let index = self.get_index_of(key)?;
+        Some(&mut self.entries[index].value)
+    }
+
+    /// Get the entry at position `index` in insertion order.
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        let entry = self.entries.get(index)?;
+        Some((&entry.key, &entry.value))
+    }
+
+    /// Check if the map contains the key.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get_index_of(key).is_some()
+    }
+
+    /// Remove a key from the map, moving the last entry into its position.
+    ///
+    /// This is O(1) but perturbs the order: the last entry takes the removed
+    /// entry's place. Returns the removed value if the key was present.
+    pub fn swap_remove(&mut self, key: &K) -> Option<V> {
+        let slot = self.find_slot(Self::hash_key(key), key).ok()?;
+        let index = self.indices[slot];
+        self.erase_slot(slot);
+
+        let last = self.entries.len() - 1;
+        if index != last {
+            let moved = self.slot_of(last);
+            self.indices[moved] = index;
+        }
+        Some(self.entries.swap_remove(index).value)
+    }
+
+    /// Empty a table slot, shifting later entries of the probe run back so
+    /// lookups never stop early.
+    fn erase_slot(&mut self, slot: usize) {
+        let mask = self.mask();
+        let mut hole = slot;
+        let mut next = (slot + 1) & mask;
+        while self.indices[next] != EMPTY {
+            let index = self.indices[next];
+            let home = self.entries[index].hash as usize & mask;
+            // The entry may move back only if the hole lies on its probe path.
+            if next.wrapping_sub(hole) & mask <= next.wrapping_sub(home) & mask {
+                self.indices[hole] = index;
+                hole = next;
+            }
+            next = (next + 1) & mask;
+        }
+        self.indices[hole] = EMPTY;
+    }
+
+    /// Clear all entries, keeping the allocated capacity.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.indices.fill(EMPTY);
+    }
+
+    /// Iterate over all key-value pairs in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.entries.iter().map(|entry| (&entry.key, &entry.value))
+    }
+
+    /// Iterate over all keys in insertion order.
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.entries.iter().map(|entry| &entry.key)
+    }
+
+    /// Iterate over all values in insertion order.
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.entries.iter().map(|entry| &entry.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iterates_in_insertion_order() {
+        let mut map = IndexMap::new();
+        for key in [5, 3, 9, 1, 7] {
+            map.insert(key, key * 10);
+        }
+        assert_eq!(map.insert(3, 31), Some(30));
+        assert_eq!(map.keys().copied().collect::<Vec<_>>(), [5, 3, 9, 1, 7]);
+        assert_eq!(map.get_index(1), Some((&3, &31)));
+        assert_eq!(map.get_index(5), None);
+        assert_eq!(map.get_index_of(&9), Some(2));
+    }
+
+    #[test]
+    fn test_swap_remove_moves_last_entry() {
+        let mut map = IndexMap::new();
+        for key in 0..5 {
+            map.insert(key, key);
+        }
+        assert_eq!(map.swap_remove(&1), Some(1));
+        assert_eq!(map.swap_remove(&1), None);
+        assert_eq!(map.keys().copied().collect::<Vec<_>>(), [0, 4, 2, 3]);
+        assert_eq!(map.get_index_of(&4), Some(1));
+        assert_eq!(map.swap_remove(&3), Some(3));
+        assert_eq!(map.keys().copied().collect::<Vec<_>>(), [0, 4, 2]);
+    }
+
+    #[test]
+    fn test_churn_matches_std() {
+        let mut map = IndexMap::new();
+        let mut model = std::collections::HashMap::new();
+        for i in 0..2000 {
+            assert_eq!(map.insert(i % 700, i), model.insert(i % 700, i));
+            if i % 3 == 0 {
+                assert_eq!(map.swap_remove(&(i / 2)), model.remove(&(i / 2)));
+            }
+        }
+        assert_eq!(map.len(), model.len());
+        for (index, (key, value)) in map.iter().enumerate() {
+            assert_eq!(model.get(key), Some(value));
+            assert_eq!(map.get_index_of(key), Some(index));
+        }
+    }
+}
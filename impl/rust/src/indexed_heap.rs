@@ -0,0 +1,250 @@
+//! Indexed min-priority queue with decrease-key, increase-key and remove.
+
+/// Position of an id that is not in the queue.
+const NIL: usize = usize::MAX;
+
+/// A min-priority queue over small integer ids, where any id's priority can
+/// be changed or removed in O(log n).
+///
+/// A binary heap of ids is paired with a table from each id to its position
+/// in the heap, kept up to date on every swap, so an update can find its
+/// element and sift it from there. Ids index the tables directly and they
+/// grow to the largest id pushed, which suits graph algorithms like
+/// Dijkstra and A* whose ids are vertex numbers.
+#[derive(Debug, Clone)]
+pub struct IndexedPriorityQueue<P> {
+    /// Ids in heap order.
+    heap: Vec<usize>,
+    /// Each id's index in `heap`, or NIL.
+    positions: Vec<usize>,
+    /// Each id's priority, if queued.
+    priorities: Vec<Option<P>>,
+}
+
+impl<P: Ord> Default for IndexedPriorityQueue<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P: Ord> IndexedPriorityQueue<P> {
+    /// Create a new empty IndexedPriorityQueue.
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Create a new empty IndexedPriorityQueue with room for ids below
+    /// `capacity`.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut priorities = Vec::with_capacity(capacity);
+        priorities.resize_with(capacity, || None);
+        Self {
+            heap: Vec::with_capacity(capacity),
+            positions: vec![NIL; capacity],
+            priorities,
+        }
+    }
+
+    /// Returns the number of ids in the queue.
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    /// Returns true if the queue contains no ids.
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Check if `id` is in the queue.
+    pub fn contains(&self, id: usize) -> bool {
+        self.positions
+            .get(id)
+            .is_some_and(|&position| position != NIL)
+    }
+
+    /// Get a reference to the priority of `id`, if it is queued.
+    pub fn priority(&self, id: usize) -> Option<&P> {
+        self.priorities.get(id)?.as_ref()
+    }
+
+    /// Get the id with the smallest priority, and that priority.
+    pub fn peek(&self) -> Option<(usize, &P)> {
+        let &id = self.heap.first()?;
+        Some((id, self.priorities[id].as_ref().expect("queued id")))
+    }
+
+    /// Add `id` with the given priority.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is already in the queue.
+    pub fn push(&mut self, id: usize, priority: P) {
+        assert!(!self.contains(id), "id {} is already in the queue", id);
+        if id >= self.positions.len() {
+            self.positions.resize(id + 1, NIL);
+            self.priorities.resize_with(id + 1, || None);
+        }
+        self.priorities[id] = Some(priority);
+        self.positions[id] = self.heap.len();
+        self.heap.push(id);
+        self.sift_up(self.heap.len() - 1);
+    }
+
+    /// Remove and return the id with the smallest priority, and that
+    /// priority.
+    pub fn pop(&mut self) -> Option<(usize, P)> {
+        let &id = self.heap.first()?;
+        let priority = self.remove(id).expect("queued id");
+        Some((id, priority))
+    }
+
+    /// Lower the priority of `id`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is not in the queue or `priority` is greater than
+    /// its current one.
+    pub fn decrease_key(&mut self, id: usize, priority: P) {
+        let position = self.position(id);
+        assert!(
+            Some(&priority) <= self.priorities[id].as_ref(),
+            "decrease_key cannot increase a priority"
+        );
+        self.priorities[id] = Some(priority);
+        self.sift_up(position);
+    }
+
+    /// Raise the priority of `id`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is not in the queue or `priority` is less than its
+    /// current one.
+    pub fn increase_key(&mut self, id: usize, priority: P) {
+        let position = self.position(id);
+        assert!(
+            Some(&priority) >= self.priorities[id].as_ref(),
+            "increase_key cannot decrease a priority"
+        );
+        self.priorities[id] = Some(priority);
+        self.sift_down(position);
+    }
+
+    /// Remove `id` from the queue.
+    ///
+    /// Returns its priority if it was queued.
+    pub fn remove(&mut self, id: usize) -> Option<P> {
+        if !self.contains(id) {
+            return None;
+        }
+        let position = self.positions[id];
+        let last = self.heap.len() - 1;
+        self.swap(position, last);
+        self.heap.pop();
+        self.positions[id] = NIL;
+        if position < self.heap.len() {
+            // The moved element may belong either above or below.
+            self.sift_up(position);
+            self.sift_down(position);
+        }
+        self.priorities[id].take()
+    }
+
+    /// Clear all ids from the queue.
+    pub fn clear(&mut self) {
+        for id in self.heap.drain(..) {
+            self.positions[id] = NIL;
+            self.priorities[id] = None;
+        }
+    }
+
+    fn position(&self, id: usize) -> usize {
+        assert!(self.contains(id), "id {} is not in the queue", id);
+        self.positions[id]
+    }
+
+    fn less(&self, a: usize, b: usize) -> bool {
+        self.priorities[self.heap[a]] < self.priorities[self.heap[b]]
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        self.heap.swap(a, b);
+        self.positions[self.heap[a]] = a;
+        self.positions[self.heap[b]] = b;
+    }
+
+    fn sift_up(&mut self, mut position: usize) {
+        while position > 0 {
+            let parent = (position - 1) / 2;
+            if !self.less(position, parent) {
+                break;
+            }
+            self.swap(position, parent);
+            position = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut position: usize) {
+        loop {
+            let left = 2 * position + 1;
+            if left >= self.heap.len() {
+                break;
+            }
+            let right = left + 1;
+            let child = if right < self.heap.len() && self.less(right, left) {
+                right
+            } else {
+                left
+            };
+            if !self.less(child, position) {
+                break;
+            }
+            self.swap(position, child);
+            position = child;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_pop_in_priority_order() {
+        let mut queue = IndexedPriorityQueue::new();
+        for (id, priority) in [(4, 40), (0, 15), (7, 30), (2, 5)] {
+            queue.push(id, priority);
+        }
+        assert_eq!(queue.peek(), Some((2, &5)));
+        assert_eq!(queue.priority(7), Some(&30));
+        assert_eq!(queue.priority(5), None);
+        let drained: Vec<(usize, i32)> = std::iter::from_fn(|| queue.pop()).collect();
+        assert_eq!(drained, [(2, 5), (0, 15), (7, 30), (4, 40)]);
+        assert!(!queue.contains(2));
+    }
+
+    #[test]
+    fn test_change_and_remove() {
+        let mut queue = IndexedPriorityQueue::with_capacity(10);
+        for id in 0..10 {
+            queue.push(id, 100 + id);
+        }
+        queue.decrease_key(9, 1);
+        queue.increase_key(0, 500);
+        assert_eq!(queue.remove(4), Some(104));
+        assert_eq!(queue.remove(4), None);
+        let order: Vec<usize> = std::iter::from_fn(|| queue.pop().map(|(id, _)| id)).collect();
+        assert_eq!(order, [9, 1, 2, 3, 5, 6, 7, 8, 0]);
+        // Ids can be reused once popped.
+        queue.push(9, 0);
+        assert_eq!(queue.len(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "decrease_key cannot increase a priority")]
+    fn test_decrease_key_rejects_increase() {
+        let mut queue = IndexedPriorityQueue::new();
+        queue.push(3, 10);
+        queue.decrease_key(3, 11);
+    }
+}
@@ -0,0 +1,128 @@
+//! String interner built on the lab hash map's raw entry API.
+
+use crate::hashmap::{HashMap, RawEntry};
+use std::hash::BuildHasher;
+
+/// A stable id for a string interned in an [`Interner`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    /// Returns the symbol's id: the number of distinct strings interned
+    /// before it.
+    pub fn as_u32(self) -> u32 {
+        self.0
+    }
+}
+
+/// Returns the string `symbol` stands for.
+fn resolve_in<'a>(arena: &'a str, ends: &[usize], symbol: Symbol) -> &'a str {
+    let index = symbol.0 as usize;
+    let start = if index == 0 { 0 } else { ends[index - 1] };
+    &arena[start..ends[index]]
+}
+
+/// Deduplicates strings, handing out a [`Symbol`] per distinct string.
+///
+/// The strings are appended to a single arena buffer and never move out of
+/// it, so a symbol stays valid for the life of the interner. The table maps
+/// symbols to nothing: it is keyed by the string's hash through
+/// [`raw_entry`](crate::hashmap::OpenAddressingMap::raw_entry) and compares
+/// candidates by reading the arena, so each string is stored once and
+/// hashed once per lookup.
+#[derive(Debug, Clone, Default)]
+pub struct Interner {
+    map: HashMap<Symbol, ()>,
+    arena: String,
+    /// End offset in `arena` of each symbol's string.
+    ends: Vec<usize>,
+}
+
+impl Interner {
+    /// Create a new empty Interner.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of distinct strings interned.
+    pub fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    /// Returns true if no strings have been interned.
+    pub fn is_empty(&self) -> bool {
+        self.ends.is_empty()
+    }
+
+    /// Returns the symbol for `s`, interning it if it is new.
+    ///
+    /// # Panics
+    ///
+    /// Panics if more than `u32::MAX` distinct strings are interned.
+    pub fn intern(&mut self, s: &str) -> Symbol {
+        let hash = self.map.hasher().hash_one(s);
+        let (arena, ends) = (&self.arena, &self.ends);
+        match self
+            .map
+            .raw_entry(hash, |&symbol| resolve_in(arena, ends, symbol) == s)
+        {
+            RawEntry::Occupied(entry) => *entry.key(),
+            RawEntry::Vacant(entry) => {
+                let id = u32::try_from(self.ends.len()).expect("too many interned strings");
+                self.arena.push_str(s);
+                self.ends.push(self.arena.len());
+                *entry.insert(Symbol(id), ()).0
+            }
+        }
+    }
+
+    /// Returns the symbol for `s` if it has been interned.
+    pub fn get(&self, s: &str) -> Option<Symbol> {
+        let hash = self.map.hasher().hash_one(s);
+        self.map
+            .raw_get(hash, |&symbol| self.resolve(symbol) == s)
+            .map(|(&symbol, _)| symbol)
+    }
+
+    /// Returns the string `symbol` stands for.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `symbol` came from a different interner with more strings.
+    pub fn resolve(&self, symbol: Symbol) -> &str {
+        resolve_in(&self.arena, &self.ends, symbol)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intern_deduplicates() {
+        let mut interner = Interner::new();
+        let a = interner.intern("alpha");
+        let b = interner.intern("beta");
+        assert_eq!(interner.intern("alpha"), a);
+        assert_ne!(a, b);
+        assert_eq!((a.as_u32(), b.as_u32()), (0, 1));
+        assert_eq!(interner.len(), 2);
+        assert_eq!(interner.get("beta"), Some(b));
+        assert_eq!(interner.get("gamma"), None);
+    }
+
+    #[test]
+    fn test_resolve_survives_growth() {
+        let mut interner = Interner::new();
+        let symbols: Vec<Symbol> = (0..5000)
+            .map(|i| interner.intern(&format!("key_{}", i)))
+            .collect();
+        interner.intern("");
+        for (i, &symbol) in symbols.iter().enumerate() {
+            assert_eq!(interner.resolve(symbol), format!("key_{}", i));
+            assert_eq!(interner.intern(&format!("key_{}", i)), symbol);
+        }
+        assert_eq!(interner.resolve(interner.get("").unwrap()), "");
+        assert_eq!(interner.len(), 5001);
+    }
+}
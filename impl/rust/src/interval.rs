@@ -0,0 +1,447 @@
+//! Intervals on a line: merging, scheduling, sweep-line concurrency
+//! counts, a map assigning values to ranges, and a set of ranges.
+//!
+//! Intervals are half-open `Range`s, so `1..3` and `3..5` touch without
+//! overlapping, as back-to-back time slots do. Empty ranges contain no
+//! point and are ignored throughout.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::Range;
+
+/// Returns the union of `intervals` as disjoint ranges in increasing order,
+/// merging any that overlap or touch.
+///
+/// Sorts by start and sweeps once, extending the current range while the
+/// next one starts no later than its end; O(n log n).
+pub fn merge_overlapping<T: Ord + Clone>(intervals: &[Range<T>]) -> Vec<Range<T>> {
+    let mut sorted: Vec<&Range<T>> = intervals.iter().filter(|r| r.start < r.end).collect();
+    sorted.sort_by(|a, b| a.start.cmp(&b.start));
+    let mut merged: Vec<Range<T>> = Vec::new();
+    for range in sorted {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => {
+                if range.end > last.end {
+                    last.end = range.end.clone();
+                }
+            }
+            _ => merged.push(range.clone()),
+        }
+    }
+    merged
+}
+
+/// Returns the indices of a largest set of pairwise disjoint intervals, in
+/// increasing order of their ends.
+///
+/// The earliest-finish greedy choice: sorted by end, take each interval
+/// that starts no earlier than the last one taken ends. Some maximum set
+/// begins with the interval that ends first, since swapping it in for
+/// that set's first interval can only leave more room, and the argument
+/// repeats on what remains. O(n log n).
+pub fn max_non_overlapping<T: Ord>(intervals: &[Range<T>]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..intervals.len())
+        .filter(|&i| intervals[i].start < intervals[i].end)
+        .collect();
+    order.sort_by(|&a, &b| intervals[a].end.cmp(&intervals[b].end));
+    let mut chosen: Vec<usize> = Vec::new();
+    for i in order {
+        if chosen
+            .last()
+            .map_or(true, |&last| intervals[i].start >= intervals[last].end)
+        {
+            chosen.push(i);
+        }
+    }
+    chosen
+}
+
+/// Returns how many intervals contain each point, as a step function:
+/// `(t, n)` means `n` intervals contain every point from `t` up to the
+/// next step. The last step is always back to zero.
+///
+/// A sweep line over the sorted starts and ends, adding one at each start
+/// and removing one at each end. At a time where some intervals end and
+/// others start, the ends go first, since a half-open interval no longer
+/// contains its end. Only times where the count changes become steps.
+/// O(n log n).
+pub fn concurrency<T: Ord + Clone>(intervals: &[Range<T>]) -> Vec<(T, usize)> {
+    // An end sorts before a start at the same time: false < true.
+    let mut events: Vec<(&T, bool)> = Vec::with_capacity(2 * intervals.len());
+    for range in intervals.iter().filter(|r| r.start < r.end) {
+        events.push((&range.start, true));
+        events.push((&range.end, false));
+    }
+    events.sort();
+    let mut steps: Vec<(T, usize)> = Vec::new();
+    let mut count = 0;
+    let mut i = 0;
+    while i < events.len() {
+        let time = events[i].0;
+        while i < events.len() && events[i].0 == time {
+            if events[i].1 {
+                count += 1;
+            } else {
+                count -= 1;
+            }
+            i += 1;
+        }
+        if steps.last().map_or(true, |&(_, n)| n != count) {
+            steps.push((time.clone(), count));
+        }
+    }
+    steps
+}
+
+/// Returns the most intervals that contain any one point.
+pub fn max_concurrent<T: Ord + Clone>(intervals: &[Range<T>]) -> usize {
+    concurrency(intervals)
+        .into_iter()
+        .map(|(_, n)| n)
+        .max()
+        .unwrap_or(0)
+}
+
+/// A map from disjoint ranges of keys to values.
+///
+/// Each range is stored under its start in an ordered map, so the range
+/// holding a key is the one with the greatest start not above it, found
+/// in O(log n). Assigning a value to a range overwrites whatever it
+/// covers: ranges it covers wholly are removed, and ones it covers in part
+/// are trimmed, or split in two if it falls inside one. Neighbours that
+/// touch and hold equal values are coalesced, so the map holds the fewest
+/// ranges that describe the assignment.
+#[derive(Clone, PartialEq, Eq)]
+pub struct IntervalMap<K, V> {
+    /// Each range's start, with its end and value.
+    ranges: BTreeMap<K, (K, V)>,
+}
+
+impl<K: Ord + Clone + fmt::Debug, V: fmt::Debug> fmt::Debug for IntervalMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V> Default for IntervalMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> IntervalMap<K, V> {
+    /// Create a new empty IntervalMap.
+    pub fn new() -> Self {
+        Self {
+            ranges: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the number of ranges in the map.
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    /// Returns true if the map contains no ranges.
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Clear all ranges from the map.
+    pub fn clear(&mut self) {
+        self.ranges.clear();
+    }
+}
+
+impl<K: Ord + Clone, V> IntervalMap<K, V> {
+    /// Returns the start of the range holding `key`, if any.
+    fn start_of(&self, key: &K) -> Option<&K> {
+        let (start, (end, _)) = self.ranges.range(..=key).next_back()?;
+        (key < end).then_some(start)
+    }
+
+    /// Get the range holding `key` and its value.
+    pub fn get_key_value(&self, key: &K) -> Option<(Range<K>, &V)> {
+        let start = self.start_of(key)?;
+        let (end, value) = &self.ranges[start];
+        Some((start.clone()..end.clone(), value))
+    }
+
+    /// Get a reference to the value assigned to `key`.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.get_key_value(key).map(|(_, value)| value)
+    }
+
+    /// Check if some range holds `key`.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.start_of(key).is_some()
+    }
+
+    /// Iterate over the ranges and their values in order.
+    pub fn iter(&self) -> impl Iterator<Item = (Range<K>, &V)> {
+        self.ranges
+            .iter()
+            .map(|(start, (end, value))| (start.clone()..end.clone(), value))
+    }
+
+    /// Iterate over the ranges that overlap `range`, in order, untrimmed.
+    pub fn overlapping(&self, range: Range<K>) -> impl Iterator<Item = (Range<K>, &V)> {
+        // An empty range overlaps nothing, not even a range around it.
+        let around = if range.start < range.end {
+            self.start_of(&range.start)
+        } else {
+            None
+        };
+        let first = around.unwrap_or(&range.start).clone();
+        let end = range.end.max(first.clone());
+        self.ranges
+            .range(first..end)
+            .map(|(start, (end, value))| (start.clone()..end.clone(), value))
+    }
+}
+
+impl<K: Ord + Clone, V: Clone + PartialEq> IntervalMap<K, V> {
+    /// Assign `value` to every key in `range`, replacing what was there.
+    pub fn insert(&mut self, range: Range<K>, value: V) {
+        if range.start >= range.end {
+            return;
+        }
+        self.remove(range.clone());
+        let Range { mut start, mut end } = range;
+        // Coalesce with a neighbour that touches and holds the same value.
+        let left = self
+            .ranges
+            .range(..&start)
+            .next_back()
+            .filter(|(_, (left_end, left_value))| *left_end == start && *left_value == value)
+            .map(|(left_start, _)| left_start.clone());
+        if let Some(left_start) = left {
+            self.ranges.remove(&left_start);
+            start = left_start;
+        }
+        let right = self
+            .ranges
+            .get(&end)
+            .filter(|(_, right_value)| *right_value == value)
+            .map(|(right_end, _)| right_end.clone());
+        if let Some(right_end) = right {
+            self.ranges.remove(&end);
+            end = right_end;
+        }
+        self.ranges.insert(start, (end, value));
+    }
+
+    /// Unassign every key in `range`, trimming or splitting the ranges it
+    /// overlaps.
+    pub fn remove(&mut self, range: Range<K>) {
+        if range.start >= range.end {
+            return;
+        }
+        // A range straddling the start keeps its part before it, and its
+        // part after the end if it reaches past that too.
+        let mut tail = None;
+        if let Some((_, (end, value))) = self.ranges.range_mut(..&range.start).next_back() {
+            if *end > range.start {
+                let old_end = std::mem::replace(end, range.start.clone());
+                if old_end > range.end {
+                    tail = Some((old_end, value.clone()));
+                }
+            }
+        }
+        if let Some(tail) = tail {
+            self.ranges.insert(range.end, tail);
+            return;
+        }
+        let starts: Vec<K> = self
+            .ranges
+            .range(&range.start..&range.end)
+            .map(|(start, _)| start.clone())
+            .collect();
+        for start in starts {
+            let (end, value) = self.ranges.remove(&start).expect("start was just found");
+            if end > range.end {
+                self.ranges.insert(range.end.clone(), (end, value));
+            }
+        }
+    }
+}
+
+/// A set of keys stored as disjoint ranges.
+///
+/// An [`IntervalMap`] to `()`, so inserting a range merges it with every
+/// range it overlaps or touches, and removing one trims or splits the
+/// ranges it covers; both take O(log n + k) for k ranges changed. The
+/// ranges stay disjoint and never touch, so the set has exactly one form.
+#[derive(Clone, PartialEq, Eq)]
+pub struct RangeSet<T> {
+    map: IntervalMap<T, ()>,
+}
+
+impl<T: Ord + Clone + fmt::Debug> fmt::Debug for RangeSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T> Default for RangeSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> RangeSet<T> {
+    /// Create a new empty RangeSet.
+    pub fn new() -> Self {
+        Self {
+            map: IntervalMap::new(),
+        }
+    }
+
+    /// Returns the number of disjoint ranges in the set.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if the set contains no keys.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Clear all ranges from the set.
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+}
+
+impl<T: Ord + Clone> RangeSet<T> {
+    /// Add every key in `range` to the set.
+    pub fn insert(&mut self, range: Range<T>) {
+        self.map.insert(range, ());
+    }
+
+    /// Remove every key in `range` from the set.
+    pub fn remove(&mut self, range: Range<T>) {
+        self.map.remove(range);
+    }
+
+    /// Check if the set contains `key`.
+    pub fn contains(&self, key: &T) -> bool {
+        self.map.contains_key(key)
+    }
+
+    /// Check if the set contains every key in `range`. An empty range is
+    /// always covered.
+    pub fn covers(&self, range: &Range<T>) -> bool {
+        range.start >= range.end
+            || self
+                .map
+                .get_key_value(&range.start)
+                .is_some_and(|(stored, _)| stored.end >= range.end)
+    }
+
+    /// Returns the range of the set holding `key`, if any.
+    pub fn range_of(&self, key: &T) -> Option<Range<T>> {
+        self.map.get_key_value(key).map(|(range, _)| range)
+    }
+
+    /// Iterate over the ranges in order.
+    pub fn iter(&self) -> impl Iterator<Item = Range<T>> + '_ {
+        self.map.iter().map(|(range, _)| range)
+    }
+
+    /// Iterate over the ranges that overlap `range`, in order, untrimmed.
+    pub fn overlapping(&self, range: Range<T>) -> impl Iterator<Item = Range<T>> + '_ {
+        self.map.overlapping(range).map(|(range, _)| range)
+    }
+
+    /// Iterate over the gaps between consecutive ranges, in order: the
+    /// keys missing from the set between its least and greatest.
+    pub fn gaps(&self) -> impl Iterator<Item = Range<T>> + '_ {
+        self.iter()
+            .zip(self.iter().skip(1))
+            .map(|(before, after)| before.end..after.start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_and_schedule() {
+        let intervals = [5..8, 1..3, 2..4, 8..9, 10..10, 12..15];
+        assert_eq!(merge_overlapping(&intervals), [1..4, 5..9, 12..15]);
+        // 1..3, then 5..8 and 8..9 back to back, then 12..15.
+        assert_eq!(max_non_overlapping(&intervals), [1, 0, 3, 5]);
+        assert!(merge_overlapping::<u32>(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_concurrency_steps() {
+        let intervals = [0..10, 2..5, 3..5, 5..7, 12..13];
+        assert_eq!(
+            concurrency(&intervals),
+            [
+                (0, 1),
+                (2, 2),
+                (3, 3),
+                (5, 2),
+                (7, 1),
+                (10, 0),
+                (12, 1),
+                (13, 0)
+            ]
+        );
+        assert_eq!(max_concurrent(&intervals), 3);
+        assert_eq!(max_concurrent::<i32>(&[]), 0);
+    }
+
+    #[test]
+    fn test_interval_map_splits_and_coalesces() {
+        let mut map = IntervalMap::new();
+        map.insert(0..10, 'a');
+        map.insert(3..5, 'b');
+        assert_eq!(
+            map.iter().collect::<Vec<_>>(),
+            [(0..3, &'a'), (3..5, &'b'), (5..10, &'a')]
+        );
+        assert_eq!(map.get_key_value(&4), Some((3..5, &'b')));
+        assert_eq!(map.get(&10), None);
+        // Painting the hole back over joins the three into one.
+        map.insert(2..6, 'a');
+        assert_eq!(map.iter().collect::<Vec<_>>(), [(0..10, &'a')]);
+
+        map.remove(4..6);
+        map.insert(12..14, 'c');
+        assert_eq!(map.len(), 3);
+        assert!(!map.contains_key(&5));
+        assert_eq!(
+            map.overlapping(3..13).collect::<Vec<_>>(),
+            [(0..4, &'a'), (6..10, &'a'), (12..14, &'c')]
+        );
+        assert_eq!(map.overlapping(10..12).count(), 0);
+    }
+
+    #[test]
+    fn test_range_set_merges_and_reports_gaps() {
+        let mut set = RangeSet::new();
+        set.insert(10..20);
+        set.insert(30..40);
+        set.insert(20..25);
+        set.insert(5..5);
+        assert_eq!(set.iter().collect::<Vec<_>>(), [10..25, 30..40]);
+        assert_eq!(set.gaps().next(), Some(25..30));
+        assert_eq!(set.gaps().count(), 1);
+        assert!(set.covers(&(12..25)) && !set.covers(&(12..26)));
+        assert!(set.covers(&(50..50)));
+
+        set.remove(15..35);
+        assert_eq!(set.iter().collect::<Vec<_>>(), [10..15, 35..40]);
+        assert!(set.contains(&14) && !set.contains(&15));
+        assert_eq!(set.range_of(&36), Some(35..40));
+        // Filling the gap joins the two into one.
+        set.insert(15..35);
+        assert_eq!(set.len(), 1);
+        assert_eq!(set.gaps().count(), 0);
+    }
+}
@@ -0,0 +1,269 @@
+//! K-d tree: static spatial index for nearest-neighbor and box queries.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fmt;
+
+/// A point with a value, as a query reports it: its coordinates, its value
+/// and, for nearest-neighbor queries, its distance from the query point.
+pub type Neighbor<'a, T, const K: usize> = (&'a [f64; K], &'a T, f64);
+
+/// Points in `K` dimensions, each carrying a value, split recursively on one
+/// coordinate at a time.
+///
+/// Construction puts the point with the median coordinate on the first
+/// axis at the root, the points below it on the left and those above on the
+/// right, and splits each half the same way on the next axis, cycling
+/// through the axes. Medians are found by selection, so building is
+/// O(n log n) and the tree is balanced. It is stored implicitly: the
+/// subtree over `points[lo..hi]` has its root in the middle, so there are
+/// no child pointers at all. A query descends towards the query point and
+/// backtracks into the other side of a split only when the splitting plane
+/// is closer than the best distance found so far, which for low dimensions
+/// and spread-out points visits O(log n) nodes per neighbor. The tree does
+/// not support insertion: changing data means rebuilding it, or using an
+/// [`RTree`](crate::RTree).
+#[derive(Clone)]
+pub struct KdTree<T, const K: usize> {
+    points: Vec<([f64; K], T)>,
+}
+
+/// A point under consideration for the nearest neighbors, ordered so that
+/// `BinaryHeap` keeps the farthest on top.
+struct Candidate {
+    distance_squared: f64,
+    index: usize,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance_squared.total_cmp(&other.distance_squared)
+    }
+}
+
+fn distance_squared<const K: usize>(a: &[f64; K], b: &[f64; K]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+/// Arrange `points` so that every subtree's root is in the middle of its
+/// range, splitting on axis `depth % K`.
+fn arrange<T, const K: usize>(points: &mut [([f64; K], T)], depth: usize) {
+    if points.len() <= 1 {
+        return;
+    }
+    let mid = points.len() / 2;
+    let axis = depth % K;
+    points.select_nth_unstable_by(mid, |a, b| a.0[axis].total_cmp(&b.0[axis]));
+    let (left, right) = points.split_at_mut(mid);
+    arrange(left, depth + 1);
+    arrange(&mut right[1..], depth + 1);
+}
+
+impl<T, const K: usize> KdTree<T, K> {
+    /// Build a tree over `points` in O(n log n).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `K` is 0 or a coordinate is NaN.
+    pub fn build(mut points: Vec<([f64; K], T)>) -> Self {
+        assert!(K > 0, "k-d tree needs at least one dimension");
+        assert!(
+            points
+                .iter()
+                .all(|(point, _)| point.iter().all(|x| !x.is_nan())),
+            "point coordinates must not be NaN"
+        );
+        arrange(&mut points, 0);
+        Self { points }
+    }
+
+    /// Returns the number of points in the tree.
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Returns true if the tree contains no points.
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Iterate over the points and their values, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&[f64; K], &T)> {
+        self.points.iter().map(|(point, value)| (point, value))
+    }
+
+    /// Returns the `k` points nearest to `query` by Euclidean distance,
+    /// nearest first, or every point if there are fewer than `k`.
+    ///
+    /// Points at equal distance are reported in no particular order.
+    pub fn nearest(&self, query: &[f64; K], k: usize) -> Vec<Neighbor<'_, T, K>> {
+        let mut best = BinaryHeap::with_capacity(k + 1);
+        if k > 0 {
+            self.search_nearest(0, self.points.len(), 0, query, k, &mut best);
+        }
+        best.into_sorted_vec()
+            .into_iter()
+            .map(|candidate| {
+                let (point, value) = &self.points[candidate.index];
+                (point, value, candidate.distance_squared.sqrt())
+            })
+            .collect()
+    }
+
+    fn search_nearest(
+        &self,
+        lo: usize,
+        hi: usize,
+        depth: usize,
+        query: &[f64; K],
+        k: usize,
+        best: &mut BinaryHeap<Candidate>,
+    ) {
+        if lo >= hi {
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        let point = &self.points[mid].0;
+        let distance_squared = distance_squared(point, query);
+        let worst = |best: &BinaryHeap<Candidate>| {
+            if best.len() < k {
+                f64::INFINITY
+            } else {
+                best.peek().map_or(f64::INFINITY, |c| c.distance_squared)
+            }
+        };
+        if distance_squared < worst(best) {
+            best.push(Candidate {
+                distance_squared,
+                index: mid,
+            });
+            if best.len() > k {
+                best.pop();
+            }
+        }
+        let axis = depth % K;
+        let offset = query[axis] - point[axis];
+        let (near, far) = if offset < 0.0 {
+            ((lo, mid), (mid + 1, hi))
+        } else {
+            ((mid + 1, hi), (lo, mid))
+        };
+        self.search_nearest(near.0, near.1, depth + 1, query, k, best);
+        // The far side can only help if the splitting plane is closer than
+        // the current k-th best.
+        if offset * offset < worst(best) {
+            self.search_nearest(far.0, far.1, depth + 1, query, k, best);
+        }
+    }
+
+    /// Returns every point inside the box from `min` to `max`, bounds
+    /// included, in no particular order.
+    pub fn within(&self, min: &[f64; K], max: &[f64; K]) -> Vec<(&[f64; K], &T)> {
+        let mut found = Vec::new();
+        self.search_within(0, self.points.len(), 0, min, max, &mut found);
+        found
+    }
+
+    fn search_within<'a>(
+        &'a self,
+        lo: usize,
+        hi: usize,
+        depth: usize,
+        min: &[f64; K],
+        max: &[f64; K],
+        found: &mut Vec<(&'a [f64; K], &'a T)>,
+    ) {
+        if lo >= hi {
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        let (point, value) = &self.points[mid];
+        if (0..K).all(|axis| min[axis] <= point[axis] && point[axis] <= max[axis]) {
+            found.push((point, value));
+        }
+        let axis = depth % K;
+        if min[axis] <= point[axis] {
+            self.search_within(lo, mid, depth + 1, min, max, found);
+        }
+        if point[axis] <= max[axis] {
+            self.search_within(mid + 1, hi, depth + 1, min, max, found);
+        }
+    }
+}
+
+impl<T, const K: usize> FromIterator<([f64; K], T)> for KdTree<T, K> {
+    fn from_iter<I: IntoIterator<Item = ([f64; K], T)>>(iter: I) -> Self {
+        Self::build(iter.into_iter().collect())
+    }
+}
+
+impl<T, const K: usize> fmt::Debug for KdTree<T, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KdTree").field("len", &self.len()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid() -> KdTree<(i32, i32), 2> {
+        (0..10)
+            .flat_map(|x| (0..10).map(move |y| ([x as f64, y as f64], (x, y))))
+            .collect()
+    }
+
+    #[test]
+    fn test_nearest_on_grid() {
+        let tree = grid();
+        assert_eq!(tree.len(), 100);
+        let nearest = tree.nearest(&[3.2, 4.9], 1);
+        assert_eq!(*nearest[0].1, (3, 5));
+        let four = tree.nearest(&[4.5, 4.5], 4);
+        let mut values: Vec<_> = four.iter().map(|&(_, &value, _)| value).collect();
+        values.sort_unstable();
+        assert_eq!(values, [(4, 4), (4, 5), (5, 4), (5, 5)]);
+        assert!(four.iter().all(|n| (n.2 - 0.5f64.sqrt()).abs() < 1e-12));
+        assert_eq!(tree.nearest(&[0.0, 0.0], 0).len(), 0);
+        assert_eq!(tree.nearest(&[0.0, 0.0], 500).len(), 100);
+    }
+
+    #[test]
+    fn test_within_box() {
+        let tree = grid();
+        let mut found: Vec<_> = tree
+            .within(&[2.0, 7.5], &[3.0, 9.0])
+            .into_iter()
+            .map(|(_, &value)| value)
+            .collect();
+        found.sort_unstable();
+        assert_eq!(found, [(2, 8), (2, 9), (3, 8), (3, 9)]);
+        assert!(tree.within(&[20.0, 0.0], &[30.0, 9.0]).is_empty());
+    }
+
+    #[test]
+    fn test_duplicates_and_empty() {
+        let tree: KdTree<char, 1> = [([1.0], 'a'), ([1.0], 'b'), ([1.0], 'c'), ([5.0], 'd')]
+            .into_iter()
+            .collect();
+        assert_eq!(tree.within(&[1.0], &[1.0]).len(), 3);
+        assert_eq!(*tree.nearest(&[4.0], 1)[0].1, 'd');
+        let empty: KdTree<(), 3> = KdTree::build(Vec::new());
+        assert!(empty.is_empty());
+        assert!(empty.nearest(&[0.0; 3], 2).is_empty());
+    }
+}
@@ -0,0 +1,215 @@
+//! Leftist heap with meld.
+
+use std::fmt;
+
+type Link<T> = Option<Box<Node<T>>>;
+
+struct Node<T> {
+    item: T,
+    /// Length of the right spine, counting this node.
+    rank: usize,
+    left: Link<T>,
+    right: Link<T>,
+}
+
+fn rank<T>(link: &Link<T>) -> usize {
+    link.as_ref().map_or(0, |node| node.rank)
+}
+
+/// Merge two heaps along their right spines, keeping the leftist property.
+fn merge<T: Ord>(mut a: Link<T>, mut b: Link<T>) -> Link<T> {
+    // Walk down both right spines taking the smaller root each step; this
+    // is the textbook recursion, unrolled so no spine can overflow the
+    // stack.
+    let mut path = Vec::new();
+    let mut rest = loop {
+        match (a, b) {
+            (None, rest) | (rest, None) => break rest,
+            (Some(x), Some(y)) => {
+                let (mut small, big) = if y.item < x.item { (y, x) } else { (x, y) };
+                a = small.right.take();
+                b = Some(big);
+                path.push(small);
+            }
+        }
+    };
+    // Rebuild bottom-up, swapping children wherever the right side ranks
+    // higher.
+    while let Some(mut node) = path.pop() {
+        node.right = rest;
+        if rank(&node.left) < rank(&node.right) {
+            std::mem::swap(&mut node.left, &mut node.right);
+        }
+        node.rank = rank(&node.right) + 1;
+        rest = Some(node);
+    }
+    rest
+}
+
+/// A mergeable min-heap whose trees lean left.
+///
+/// Each node's rank is the length of its right spine, and every left child
+/// ranks at least as high as its right sibling, so the right spine of a
+/// heap of `n` elements is at most `log2(n + 1)` long. Meld walks the two
+/// right spines together like a sorted-list merge and swaps children on the
+/// way back up wherever the property broke, O(log n). Push and pop are
+/// melds with a single node and with the root's two subtrees.
+pub struct LeftistHeap<T> {
+    root: Link<T>,
+    len: usize,
+}
+
+impl<T: Ord> Default for LeftistHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> LeftistHeap<T> {
+    /// Create a new empty LeftistHeap.
+    pub fn new() -> Self {
+        Self { root: None, len: 0 }
+    }
+
+    /// Returns the number of elements in the heap.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the heap contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Push an element onto the heap.
+    pub fn push(&mut self, item: T) {
+        let node = Box::new(Node {
+            item,
+            rank: 1,
+            left: None,
+            right: None,
+        });
+        self.root = merge(self.root.take(), Some(node));
+        self.len += 1;
+    }
+
+    /// Get a reference to the smallest element, or `None` if the heap is
+    /// empty.
+    pub fn peek(&self) -> Option<&T> {
+        self.root.as_ref().map(|node| &node.item)
+    }
+
+    /// Remove and return the smallest element, or `None` if the heap is
+    /// empty.
+    pub fn pop(&mut self) -> Option<T> {
+        let mut root = self.root.take()?;
+        self.root = merge(root.left.take(), root.right.take());
+        self.len -= 1;
+        Some(root.item)
+    }
+
+    /// Move every element of `other` into this heap, in O(log n).
+    pub fn meld(&mut self, mut other: Self) {
+        self.root = merge(self.root.take(), other.root.take());
+        self.len += other.len;
+    }
+
+    /// Check the leftist heap invariants, panicking on the first violation.
+    ///
+    /// Checks that every node is no larger than its children, that its
+    /// rank is one more than its right child's, that no left child ranks
+    /// below its sibling, and that the node count matches `len`.
+    pub fn debug_validate(&self) {
+        let mut count = 0;
+        let mut stack: Vec<&Node<T>> = self.root.iter().map(|node| &**node).collect();
+        while let Some(node) = stack.pop() {
+            count += 1;
+            assert_eq!(node.rank, rank(&node.right) + 1, "stale rank");
+            assert!(
+                rank(&node.left) >= rank(&node.right),
+                "right child outranks left"
+            );
+            for child in [&node.left, &node.right].into_iter().flatten() {
+                assert!(node.item <= child.item, "heap order violated");
+                stack.push(child);
+            }
+        }
+        assert_eq!(count, self.len, "len does not match node count");
+    }
+}
+
+impl<T> Drop for LeftistHeap<T> {
+    fn drop(&mut self) {
+        // Left spines can be as long as the heap, too deep to drop
+        // recursively.
+        let mut stack: Vec<Box<Node<T>>> = self.root.take().into_iter().collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.left.take());
+            stack.extend(node.right.take());
+        }
+    }
+}
+
+impl<T: Ord + fmt::Debug> fmt::Debug for LeftistHeap<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LeftistHeap")
+            .field("len", &self.len)
+            .field("min", &self.peek())
+            .finish()
+    }
+}
+
+impl<T: Ord> Extend<T> for LeftistHeap<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate<T: Ord>(heap: &LeftistHeap<T>) {
+        if cfg!(debug_assertions) {
+            heap.debug_validate();
+        }
+    }
+
+    #[test]
+    fn test_push_pop_sorted() {
+        let mut heap = LeftistHeap::new();
+        heap.extend((0..500u32).map(|i| i * 7919 % 503));
+        validate(&heap);
+        let mut previous = None;
+        while let Some(item) = heap.pop() {
+            assert!(previous <= Some(item));
+            previous = Some(item);
+        }
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn test_meld_keeps_right_spine_short() {
+        let mut left = LeftistHeap::new();
+        let mut right = LeftistHeap::new();
+        left.extend((0..1000).map(|i| 2 * i));
+        right.extend((0..1000).map(|i| 2 * i + 1));
+        left.meld(right);
+        validate(&left);
+        // Rank is the right spine length, at most log2(n + 1).
+        assert!(left.root.as_ref().unwrap().rank <= 11);
+        let drained: Vec<i32> = std::iter::from_fn(|| left.pop()).take(5).collect();
+        assert_eq!(drained, [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_deep_left_spine_drops() {
+        // Descending pushes build a left spine as long as the heap.
+        let mut heap = LeftistHeap::new();
+        heap.extend((0..200_000).rev());
+        assert_eq!(heap.peek(), Some(&0));
+        drop(heap);
+    }
+}
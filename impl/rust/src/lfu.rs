@@ -0,0 +1,205 @@
+//! Least-frequently-used cache with constant-time eviction.
+
+use crate::freq_list::FrequencyList;
+use crate::hashmap::HashMap;
+use std::hash::Hash;
+
+/// A fixed-capacity cache that evicts the least frequently used entry.
+///
+/// Uses the frequency-list design: buckets of equal use count form a linked
+/// list in increasing frequency, and each bucket links its items from most to
+/// least recently used. A use moves an item into the neighboring bucket, and
+/// eviction takes the oldest item of the first bucket, so every operation is
+/// O(1). Ties in frequency evict the least recently used entry.
+#[derive(Debug, Clone)]
+pub struct LfuCache<K, V> {
+    map: HashMap<K, usize>,
+    items: FrequencyList<(K, V)>,
+    capacity: usize,
+    hits: u64,
+    misses: u64,
+}
+
+impl<K, V> LfuCache<K, V>
+where
+    K: Hash + Eq + Clone,
+{
+    /// Create an empty cache holding at most `capacity` entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "cache capacity must be at least 1");
+        Self {
+            map: HashMap::with_capacity(capacity),
+            items: FrequencyList::with_capacity(capacity),
+            capacity,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Returns the number of cached entries.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if the cache holds no entries.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns the maximum number of entries.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of `get` calls that found their key.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Returns the number of `get` calls that missed.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Fraction of `get` calls that hit, or 0 before the first call.
+    pub fn hit_ratio(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+
+    /// Returns how many times `key` has been used since it was cached.
+    pub fn frequency(&self, key: &K) -> Option<u64> {
+        let &index = self.map.get(key)?;
+        Some(self.items.freq(index))
+    }
+
+    /// Get a reference to the value for `key`, counting a use.
+    ///
+    /// Counts a hit or a miss.
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        let Some(&index) = self.map.get(key) else {
+            self.misses += 1;
+            return None;
+        };
+        self.hits += 1;
+        self.items.touch(index);
+        Some(&self.items.get(index).1)
+    }
+
+    /// Get a reference to the value for `key` without counting a use or
+    /// touching the hit counters.
+    pub fn peek(&self, key: &K) -> Option<&V> {
+        let &index = self.map.get(key)?;
+        Some(&self.items.get(index).1)
+    }
+
+    /// Check if the cache holds `key`, without counting a use.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.map.contains_key(key)
+    }
+
+    /// Insert or update an entry, counting a use.
+    ///
+    /// A new entry starts with one use. Returns the least frequently used
+    /// entry if it was evicted to make room.
+    pub fn put(&mut self, key: K, value: V) -> Option<(K, V)> {
+        if let Some(&index) = self.map.get(&key) {
+            self.items.get_mut(index).1 = value;
+            self.items.touch(index);
+            return None;
+        }
+
+        let evicted = if self.map.len() == self.capacity {
+            let least = self.items.least().expect("a full cache has entries");
+            Some(self.take(least))
+        } else {
+            None
+        };
+
+        let index = self.items.insert((key.clone(), value));
+        self.map.insert(key, index);
+        evicted
+    }
+
+    fn take(&mut self, index: usize) -> (K, V) {
+        let (key, value) = self.items.remove(index);
+        self.map.remove(&key);
+        (key, value)
+    }
+
+    /// Remove an entry from the cache.
+    ///
+    /// Returns the removed value if the key was cached.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let &index = self.map.get(key)?;
+        Some(self.take(index).1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evicts_least_frequently_used() {
+        let mut cache = LfuCache::new(2);
+        cache.put(1, "a");
+        cache.put(2, "b");
+        cache.get(&1);
+        cache.get(&1);
+        cache.get(&2);
+        assert_eq!(cache.frequency(&1), Some(3));
+        assert_eq!(cache.put(3, "c"), Some((2, "b")));
+        // The newcomer has the lowest count, so it goes next.
+        assert_eq!(cache.put(4, "d"), Some((3, "c")));
+        assert_eq!(cache.peek(&1), Some(&"a"));
+    }
+
+    #[test]
+    fn test_ties_evict_least_recently_used() {
+        let mut cache = LfuCache::new(3);
+        for i in 0..3 {
+            cache.put(i, i);
+        }
+        cache.get(&0);
+        cache.get(&1);
+        cache.get(&2);
+        cache.get(&0);
+        assert_eq!(cache.put(3, 3), Some((1, 1)));
+    }
+
+    #[test]
+    fn test_remove_and_reuse() {
+        let mut cache = LfuCache::new(2);
+        cache.put("x", 1);
+        cache.put("y", 2);
+        cache.get(&"x");
+        assert_eq!(cache.remove(&"x"), Some(1));
+        assert_eq!(cache.remove(&"x"), None);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.put("z", 3), None);
+        assert_eq!(cache.put("w", 4), Some(("y", 2)));
+        let (items, buckets) = cache.items.footprint();
+        assert_eq!(items, 2);
+        assert!(buckets <= 2);
+    }
+
+    #[test]
+    fn test_hit_miss_counters() {
+        let mut cache = LfuCache::new(4);
+        cache.put(1, 1);
+        cache.get(&1);
+        cache.get(&2);
+        cache.get(&3);
+        assert_eq!((cache.hits(), cache.misses()), (1, 2));
+        assert_eq!(cache.frequency(&1), Some(2));
+    }
+}
@@ -2,6 +2,187 @@
 //!
 //! Rust implementation of common data structures for benchmarking and learning.
 
+pub mod aa_tree;
+pub mod avl;
+pub mod bigint;
+pub mod bimap;
+pub mod binary_heap;
+pub mod binomial_heap;
+pub mod bit_set;
+pub mod bit_vector;
+pub mod bplus_tree;
+pub mod btree;
+pub mod chained;
+pub mod counter;
+pub mod cuckoo;
+pub mod deque;
+pub mod double_hash;
+pub mod dyn_array;
+pub mod euler_tour;
+pub mod fenwick;
+pub mod fibonacci_heap;
+pub mod floyd_warshall;
+pub mod fm_index;
+mod freq_list;
+pub mod geometry;
+pub mod graph;
+pub mod graph_gen;
+pub mod growth;
 pub mod hashmap;
+pub mod huffman;
+pub mod incremental;
+pub mod index_map;
+pub mod indexed_heap;
+pub mod interner;
+pub mod interval;
+pub mod kd_tree;
+pub mod leftist_heap;
+pub mod lfu;
+pub mod link_cut;
+pub mod lockfree;
+pub mod lockfree_queue;
+pub mod lru;
+pub mod lz77;
+pub mod lzw;
+pub mod matching;
+pub mod matrix;
+pub mod max_flow;
+pub mod mergeable_heap;
+pub mod metrics;
+pub mod minhash;
+pub mod monoid;
+pub mod monotonic;
+pub mod multimap;
+pub mod order_maintenance;
+pub mod order_statistics;
+pub mod palindrome;
+#[cfg(feature = "rayon")]
+pub mod par;
+#[cfg(feature = "rayon")]
+pub mod par_sort;
+pub mod perfect;
+pub mod persistent;
+pub mod persistent_sorted;
+pub mod probe;
+pub mod quadratic;
+pub mod quantile;
+pub mod quotient_filter;
+pub mod r_tree;
+pub mod radix_sort;
+pub mod radix_trie;
+pub mod reservoir;
+pub mod ring;
+pub mod robin_hood;
+pub mod rolling_hash;
+pub mod scc;
+pub mod search;
+pub mod segment_tree;
+pub mod shortest_path;
+pub mod skew_heap;
+pub mod skip_list;
+pub mod slab;
+pub mod sliding_window;
+pub mod slot_map;
+pub mod soa;
+pub mod sort;
+pub mod space_saving;
+pub mod spanning_tree;
+pub mod sqrt_decomposition;
+pub mod strsearch;
+pub mod suffix_array;
+pub mod swiss;
+pub mod toposort;
+pub mod treap;
+pub mod two_three_four;
+pub mod weight_balanced;
+pub mod x_fast_trie;
+pub mod xor_filter;
+pub mod y_fast_trie;
+pub mod zip_tree;
 
-pub use hashmap::HashMap;
+pub use aa_tree::AaTreeMap;
+pub use avl::AvlMap;
+pub use bigint::{BigInt, BigUint};
+pub use bimap::{BiMap, Evicted};
+pub use binary_heap::{BinaryHeap, HeapOrder};
+pub use binomial_heap::BinomialHeap;
+pub use bit_set::BitSet;
+pub use bit_vector::BitVector;
+pub use bplus_tree::BPlusTreeMap;
+pub use btree::BTreeMap;
+pub use chained::ChainedHashMap;
+pub use counter::Counter;
+pub use cuckoo::CuckooHashMap;
+pub use deque::Deque;
+pub use double_hash::DoubleHashMap;
+pub use dyn_array::DynArray;
+pub use euler_tour::EulerTourTree;
+pub use fenwick::{FenwickTree, FenwickTree2D};
+pub use fibonacci_heap::{FibonacciHeap, HeapHandle};
+pub use floyd_warshall::{floyd_warshall, floyd_warshall_blocked, AllPairsPaths};
+pub use fm_index::FmIndex;
+pub use graph::{EdgeId, EdgeRef, Graph, NodeId};
+pub use graph_gen::{barabasi_albert, erdos_renyi, grid, random_dag, random_tree};
+pub use growth::GrowthPolicy;
+#[cfg(feature = "ahash")]
+pub use hashmap::AHashMap;
+#[cfg(feature = "fxhash")]
+pub use hashmap::FxHashMap;
+pub use hashmap::{HashMap, OpenAddressingMap};
+pub use huffman::HuffmanCode;
+pub use incremental::IncrementalHashMap;
+pub use index_map::IndexMap;
+pub use indexed_heap::IndexedPriorityQueue;
+pub use interner::{Interner, Symbol};
+pub use interval::{IntervalMap, RangeSet};
+pub use kd_tree::KdTree;
+pub use leftist_heap::LeftistHeap;
+pub use lfu::LfuCache;
+pub use link_cut::LinkCutTree;
+pub use lockfree::LockFreeMap;
+pub use lockfree_queue::LockFreeQueue;
+pub use lru::LruCache;
+pub use matching::{hopcroft_karp, Matching};
+pub use matrix::{CsrMatrix, Matrix};
+pub use max_flow::{dinic, edmonds_karp, MaxFlow};
+pub use mergeable_heap::MergeableHeap;
+pub use metrics::Metrics;
+pub use minhash::{LshIndex, MinHash, Signature};
+pub use monoid::{Max, Min, Monoid, Sum};
+pub use monotonic::MonotonicDeque;
+pub use multimap::MultiMap;
+pub use order_maintenance::{OrderKey, OrderList};
+pub use order_statistics::OrderStatisticsMap;
+pub use perfect::PerfectHashMap;
+pub use persistent::PersistentMap;
+pub use persistent_sorted::PersistentSortedMap;
+pub use probe::{DoubleHashProbe, LinearProbe, ProbeStrategy, QuadraticProbe};
+pub use quadratic::QuadraticMap;
+pub use quantile::{RunningMedian, WindowQuantiles};
+pub use quotient_filter::QuotientFilter;
+pub use r_tree::{RTree, Rect};
+pub use radix_trie::RadixTrie;
+pub use ring::ConsistentHashRing;
+pub use robin_hood::RobinHoodMap;
+pub use rolling_hash::RollingHash;
+pub use scc::{kosaraju_scc, tarjan_scc, StrongComponents};
+pub use segment_tree::SegmentTree;
+pub use shortest_path::{bellman_ford, dijkstra, NegativeCycle, Relaxation, ShortestPaths};
+pub use skew_heap::SkewHeap;
+pub use skip_list::SkipListMap;
+pub use slab::Slab;
+pub use sliding_window::{SlidingWindowAggregator, SlidingWindowMinMax};
+pub use slot_map::{SlotKey, SlotMap};
+pub use soa::SoaMap;
+pub use space_saving::SpaceSaving;
+pub use spanning_tree::{prim, SpanningForest};
+pub use sqrt_decomposition::SqrtDecomposition;
+pub use swiss::SwissMap;
+pub use toposort::{toposort, Cycle};
+pub use treap::TreapMap;
+pub use two_three_four::TwoThreeFourMap;
+pub use weight_balanced::{WeightBalancedMap, WeightBalancedSeq};
+pub use x_fast_trie::XFastTrie;
+pub use xor_filter::XorFilter;
+pub use y_fast_trie::YFastTrie;
+pub use zip_tree::ZipTreeMap;
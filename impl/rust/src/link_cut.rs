@@ -0,0 +1,424 @@
+//! Link-cut tree: a dynamic forest with path aggregates.
+
+use crate::monoid::Monoid;
+use std::fmt;
+
+/// Marks a missing parent or child.
+const NIL: usize = usize::MAX;
+
+struct Node<V> {
+    value: V,
+    /// Combination of the splay subtree in path order, shallowest first.
+    sum: V,
+    /// The same, deepest first.
+    reverse_sum: V,
+    /// The splay parent, or for the root of a splay tree the path-parent:
+    /// the forest parent of the shallowest vertex on its path.
+    parent: usize,
+    left: usize,
+    right: usize,
+    /// The children of this node still have to be swapped and flipped;
+    /// `sum` and `reverse_sum` already account for it.
+    flipped: bool,
+}
+
+impl<V: Clone> Clone for Node<V> {
+    fn clone(&self) -> Self {
+        Self {
+            value: self.value.clone(),
+            sum: self.sum.clone(),
+            reverse_sum: self.reverse_sum.clone(),
+            parent: self.parent,
+            left: self.left,
+            right: self.right,
+            flipped: self.flipped,
+        }
+    }
+}
+
+/// A forest over vertices `0..n`, each holding a monoid value, that supports
+/// adding and removing edges and combining the values along a path, each in
+/// O(log n) amortized time.
+///
+/// Every tree is rooted, and split into vertex-disjoint *preferred paths*,
+/// each stored in a splay tree ordered by depth. A splay tree's root keeps
+/// a pointer to the forest parent of its path's top, which strings the
+/// paths together. The core operation, `access(v)`, splays its way up from
+/// `v`, re-pointing preferred paths so that the root-to-`v` path becomes a
+/// single splay tree with `v` at its root. Every query is then a lookup at
+/// one node: its subtree is exactly the path, and each node keeps the
+/// combination of its subtree. Rerooting a tree at `v` ([`evert`]) reverses
+/// the root-to-`v` path, done lazily by a flag that swaps children on the
+/// way down, so each node also keeps its path combination in reverse for
+/// non-commutative monoids.
+///
+/// [`evert`]: Self::evert
+pub struct LinkCutTree<M: Monoid> {
+    nodes: Vec<Node<M::Value>>,
+}
+
+impl<M: Monoid> Clone for LinkCutTree<M> {
+    fn clone(&self) -> Self {
+        Self {
+            nodes: self.nodes.clone(),
+        }
+    }
+}
+
+impl<M: Monoid> fmt::Debug for LinkCutTree<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LinkCutTree")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl<M: Monoid> LinkCutTree<M> {
+    /// Create a new forest of `len` isolated vertices holding the identity.
+    pub fn new(len: usize) -> Self {
+        Self::from_vec(vec![M::identity(); len])
+    }
+
+    /// Create a new forest of isolated vertices holding the given values.
+    pub fn from_vec(values: Vec<M::Value>) -> Self {
+        let nodes = values
+            .into_iter()
+            .map(|value| Node {
+                sum: value.clone(),
+                reverse_sum: value.clone(),
+                value,
+                parent: NIL,
+                left: NIL,
+                right: NIL,
+                flipped: false,
+            })
+            .collect();
+        Self { nodes }
+    }
+
+    /// Returns the number of vertices.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns true if there are no vertices.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Get a reference to the value at vertex `v`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `v` is out of bounds.
+    pub fn get(&self, v: usize) -> &M::Value {
+        &self.nodes[v].value
+    }
+
+    /// Replace the value at vertex `v`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `v` is out of bounds.
+    pub fn set(&mut self, v: usize, value: M::Value) {
+        self.splay(v);
+        self.nodes[v].value = value;
+        self.pull(v);
+    }
+
+    /// Returns the root of the tree containing `v`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `v` is out of bounds.
+    pub fn find_root(&mut self, v: usize) -> usize {
+        self.access(v);
+        let mut root = v;
+        loop {
+            self.push(root);
+            match self.nodes[root].left {
+                NIL => break,
+                left => root = left,
+            }
+        }
+        // Keeps repeated calls from walking the same long left spine.
+        self.splay(root);
+        root
+    }
+
+    /// Check if `u` and `v` are in the same tree.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either vertex is out of bounds.
+    pub fn connected(&mut self, u: usize, v: usize) -> bool {
+        u == v || self.find_root(u) == self.find_root(v)
+    }
+
+    /// Make `v` the root of its tree.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `v` is out of bounds.
+    pub fn evert(&mut self, v: usize) {
+        self.access(v);
+        self.flip(v);
+    }
+
+    /// Add an edge between `u` and `v`, rerooting `u`'s tree at `u` and
+    /// hanging it below `v`.
+    ///
+    /// Returns false, changing nothing, if they are already connected.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either vertex is out of bounds.
+    pub fn link(&mut self, u: usize, v: usize) -> bool {
+        if self.connected(u, v) {
+            return false;
+        }
+        self.evert(u);
+        self.nodes[u].parent = v;
+        true
+    }
+
+    /// Remove the edge between `u` and `v`, rerooting their tree at `u`
+    /// first, so `v` is the root of the part cut off.
+    ///
+    /// Returns false, with the tree rerooted but otherwise unchanged, if
+    /// there is no such edge.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either vertex is out of bounds.
+    pub fn cut(&mut self, u: usize, v: usize) -> bool {
+        self.evert(u);
+        self.access(v);
+        // The path from u to v is exactly the edge iff it holds two
+        // vertices: v at the splay root and u alone on its left.
+        if self.nodes[v].left != u {
+            return false;
+        }
+        self.push(u);
+        if self.nodes[u].right != NIL {
+            return false;
+        }
+        self.nodes[v].left = NIL;
+        self.nodes[u].parent = NIL;
+        self.pull(v);
+        true
+    }
+
+    /// Combine the values on the path from `u` to `v`, both included, in
+    /// that order, rerooting their tree at `u`.
+    ///
+    /// Returns `None` if they are in different trees.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either vertex is out of bounds.
+    pub fn path(&mut self, u: usize, v: usize) -> Option<M::Value> {
+        if !self.connected(u, v) {
+            return None;
+        }
+        self.evert(u);
+        self.access(v);
+        Some(self.nodes[v].sum.clone())
+    }
+
+    /// Returns the lowest common ancestor of `u` and `v` under the current
+    /// rooting, or `None` if they are in different trees.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either vertex is out of bounds.
+    pub fn lca(&mut self, u: usize, v: usize) -> Option<usize> {
+        if !self.connected(u, v) {
+            return None;
+        }
+        self.access(u);
+        // The last path `v` joins on its way up is the one through `u`,
+        // and it joins it at their common ancestor.
+        Some(self.access(v))
+    }
+
+    fn is_splay_root(&self, x: usize) -> bool {
+        let parent = self.nodes[x].parent;
+        parent == NIL || (self.nodes[parent].left != x && self.nodes[parent].right != x)
+    }
+
+    /// Reverse the path order of the splay subtree at `x`.
+    fn flip(&mut self, x: usize) {
+        let node = &mut self.nodes[x];
+        std::mem::swap(&mut node.sum, &mut node.reverse_sum);
+        node.flipped = !node.flipped;
+    }
+
+    /// Hand a pending reversal at `x` down to its children.
+    fn push(&mut self, x: usize) {
+        if !self.nodes[x].flipped {
+            return;
+        }
+        let node = &mut self.nodes[x];
+        node.flipped = false;
+        std::mem::swap(&mut node.left, &mut node.right);
+        for child in [node.left, node.right] {
+            if child != NIL {
+                self.flip(child);
+            }
+        }
+    }
+
+    /// Recompute the path combinations at `x` from its children.
+    fn pull(&mut self, x: usize) {
+        let (left, right) = (self.nodes[x].left, self.nodes[x].right);
+        let identity = M::identity();
+        let part = |child: usize, reverse: bool| match child {
+            NIL => &identity,
+            child if reverse => &self.nodes[child].reverse_sum,
+            child => &self.nodes[child].sum,
+        };
+        let value = &self.nodes[x].value;
+        let sum = M::combine(&M::combine(part(left, false), value), part(right, false));
+        let reverse_sum = M::combine(&M::combine(part(right, true), value), part(left, true));
+        let node = &mut self.nodes[x];
+        node.sum = sum;
+        node.reverse_sum = reverse_sum;
+    }
+
+    /// Lift `x` above its splay parent, keeping the path order.
+    fn rotate(&mut self, x: usize) {
+        let parent = self.nodes[x].parent;
+        let grandparent = self.nodes[parent].parent;
+        if self.nodes[parent].left == x {
+            let middle = self.nodes[x].right;
+            self.nodes[parent].left = middle;
+            if middle != NIL {
+                self.nodes[middle].parent = parent;
+            }
+            self.nodes[x].right = parent;
+        } else {
+            let middle = self.nodes[x].left;
+            self.nodes[parent].right = middle;
+            if middle != NIL {
+                self.nodes[middle].parent = parent;
+            }
+            self.nodes[x].left = parent;
+        }
+        // A path-parent pointer moves up to the new splay root unchanged.
+        if grandparent != NIL {
+            if self.nodes[grandparent].left == parent {
+                self.nodes[grandparent].left = x;
+            } else if self.nodes[grandparent].right == parent {
+                self.nodes[grandparent].right = x;
+            }
+        }
+        self.nodes[parent].parent = x;
+        self.nodes[x].parent = grandparent;
+        self.pull(parent);
+        self.pull(x);
+    }
+
+    /// Make `x` the root of its splay tree.
+    fn splay(&mut self, x: usize) {
+        // Settle pending reversals from the splay root down first, so
+        // rotations see children in their true order.
+        let mut path = vec![x];
+        let mut y = x;
+        while !self.is_splay_root(y) {
+            y = self.nodes[y].parent;
+            path.push(y);
+        }
+        for &y in path.iter().rev() {
+            self.push(y);
+        }
+        while !self.is_splay_root(x) {
+            let parent = self.nodes[x].parent;
+            if !self.is_splay_root(parent) {
+                let grandparent = self.nodes[parent].parent;
+                let zig_zig =
+                    (self.nodes[grandparent].left == parent) == (self.nodes[parent].left == x);
+                self.rotate(if zig_zig { parent } else { x });
+            }
+            self.rotate(x);
+        }
+    }
+
+    /// Make the path from the root to `x` preferred, ending at `x`, with
+    /// `x` at the root of its splay tree. Returns the last vertex where
+    /// the walk up joined another path.
+    fn access(&mut self, x: usize) -> usize {
+        let mut last = NIL;
+        let mut y = x;
+        while y != NIL {
+            self.splay(y);
+            self.nodes[y].right = last;
+            self.pull(y);
+            last = y;
+            y = self.nodes[y].parent;
+        }
+        self.splay(x);
+        last
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monoid::{Max, Sum};
+
+    #[test]
+    fn test_link_cut_find_root() {
+        let mut forest: LinkCutTree<Sum<i64>> = LinkCutTree::new(6);
+        assert!(forest.link(1, 0));
+        assert!(forest.link(2, 1));
+        assert!(forest.link(3, 1));
+        assert!(!forest.link(3, 2), "would close a cycle");
+        assert_eq!(forest.find_root(3), 0);
+        assert!(forest.connected(2, 3));
+        assert!(!forest.connected(0, 4));
+        assert!(!forest.cut(2, 3), "not an edge");
+        assert!(forest.cut(1, 0));
+        assert_eq!(forest.find_root(0), 0);
+        assert!(!forest.connected(0, 2));
+        assert!(forest.link(4, 5));
+        assert!(forest.link(5, 3));
+        assert!(forest.connected(4, 2));
+    }
+
+    #[test]
+    fn test_path_aggregates() {
+        // The path 0 - 1 - 2 - 3 - 4 with a branch 5 off 2.
+        let mut forest: LinkCutTree<Sum<i64>> = LinkCutTree::from_vec(vec![1, 2, 4, 8, 16, 32]);
+        for (u, v) in [(1, 0), (2, 1), (3, 2), (4, 3), (5, 2)] {
+            forest.link(u, v);
+        }
+        assert_eq!(forest.path(0, 4), Some(31));
+        assert_eq!(forest.path(5, 3), Some(44));
+        assert_eq!(forest.path(3, 3), Some(8));
+        forest.set(2, 100);
+        assert_eq!(forest.path(0, 5), Some(135));
+        assert_eq!(*forest.get(2), 100);
+        let mut max: LinkCutTree<Max<i64>> = LinkCutTree::from_vec(vec![3, 9, 1]);
+        max.link(0, 1);
+        assert_eq!(max.path(0, 1), Some(9));
+        assert_eq!(max.path(0, 2), None);
+    }
+
+    #[test]
+    fn test_evert_and_lca() {
+        let mut forest: LinkCutTree<Sum<i64>> = LinkCutTree::new(7);
+        // 0 has children 1 and 2; 1 has 3 and 4; 2 has 5; 5 has 6.
+        for (child, parent) in [(1, 0), (2, 0), (3, 1), (4, 1), (5, 2), (6, 5)] {
+            forest.link(child, parent);
+        }
+        forest.evert(0);
+        assert_eq!(forest.lca(3, 4), Some(1));
+        assert_eq!(forest.lca(3, 6), Some(0));
+        assert_eq!(forest.lca(6, 2), Some(2));
+        forest.evert(6);
+        assert_eq!(forest.find_root(3), 6);
+        assert_eq!(forest.lca(3, 4), Some(1));
+        assert_eq!(forest.lca(3, 2), Some(2));
+    }
+}
@@ -0,0 +1,384 @@
+//! Lock-free hash map using open addressing with compare-and-swap slots.
+
+use crate::hashmap::DefaultHashBuilder;
+use crate::probe::{LinearProbe, ProbeStrategy};
+use std::hash::{BuildHasher, Hash};
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+
+/// An immutable version of a slot's contents.
+///
+/// Every version stored in a slot carries the same key: a slot is claimed for
+/// one key forever, and removal stores a version without a value.
+struct Entry<K, V> {
+    key: K,
+    value: Option<V>,
+    /// Link in the stack of replaced versions awaiting reclamation.
+    next_retired: *mut Entry<K, V>,
+}
+
+/// A concurrent hash map whose operations never block.
+///
+/// Each slot is an atomic pointer to an immutable entry. Inserting a new key
+/// claims an empty slot with a single compare-and-swap; updating or removing
+/// swaps in a new version of the slot's entry. Readers load the pointer and
+/// see either the old or the new version, so every operation takes effect at
+/// one atomic step.
+///
+/// This is a teaching structure with two deliberate limits. The capacity is
+/// fixed at construction, since a lock-free resize needs cooperative
+/// migration. And replaced versions are only freed when the map is dropped,
+/// since references returned by [`get`](Self::get) may still point at them;
+/// a production design would use epoch-based reclamation instead.
+pub struct LockFreeMap<K, V> {
+    slots: Box<[AtomicPtr<Entry<K, V>>]>,
+    /// Slots holding a value; approximate while operations are in flight.
+    len: AtomicUsize,
+    /// Slots claimed by a key, live or removed.
+    claimed: AtomicUsize,
+    /// Treiber stack of replaced entries.
+    retired: AtomicPtr<Entry<K, V>>,
+    hash_builder: DefaultHashBuilder,
+}
+
+// SAFETY: entries are created on one thread and dropped on another, and
+// references to keys and values are shared between threads.
+unsafe impl<K: Send + Sync, V: Send + Sync> Sync for LockFreeMap<K, V> {}
+// SAFETY: the map owns its entries.
+unsafe impl<K: Send, V: Send> Send for LockFreeMap<K, V> {}
+
+impl<K, V> LockFreeMap<K, V>
+where
+    K: Hash + Eq,
+{
+    /// Create a map that can hold up to `capacity` distinct keys over its
+    /// lifetime, removed keys included.
+    ///
+    /// The table is sized to at least twice that, rounded up to a power of
+    /// two, so probe chains stay short when it is full.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let slots = (capacity.max(1) * 2).next_power_of_two();
+        Self {
+            slots: (0..slots)
+                .map(|_| AtomicPtr::new(ptr::null_mut()))
+                .collect(),
+            len: AtomicUsize::new(0),
+            claimed: AtomicUsize::new(0),
+            retired: AtomicPtr::new(ptr::null_mut()),
+            hash_builder: DefaultHashBuilder::default(),
+        }
+    }
+
+    /// Returns the number of keys with a value.
+    ///
+    /// While other threads are inserting or removing, the count may lag
+    /// behind operations that have already taken effect.
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    /// Returns true if no key has a value.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of distinct keys the map can hold.
+    pub fn capacity(&self) -> usize {
+        self.slots.len() / 2
+    }
+
+    /// Find the slot claimed by `key`, or the first empty slot after it.
+    fn find_slot(&self, key: &K) -> Option<(usize, *mut Entry<K, V>)> {
+        let hash = self.hash_builder.hash_one(key);
+        let mask = self.slots.len() - 1;
+        for i in 0..self.slots.len() {
+            let index = LinearProbe::probe(hash, i, mask);
+            let current = self.slots[index].load(Ordering::Acquire);
+            // SAFETY: non-null slot pointers stay valid until the map drops.
+            if current.is_null() || unsafe { (*current).key == *key } {
+                return Some((index, current));
+            }
+        }
+        None
+    }
+
+    /// Store a new version of the entry in `index`, returning the value of
+    /// the version it replaced.
+    fn swap(
+        &self,
+        index: usize,
+        mut current: *mut Entry<K, V>,
+        key: K,
+        value: Option<V>,
+    ) -> Option<&V> {
+        let has_value = value.is_some();
+        let new = Box::into_raw(Box::new(Entry {
+            key,
+            value,
+            next_retired: ptr::null_mut(),
+        }));
+        loop {
+            match self.slots[index].compare_exchange_weak(
+                current,
+                new,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => break,
+                Err(actual) => current = actual,
+            }
+        }
+
+        // SAFETY: `current` is a valid entry that is no longer reachable
+        // from the table, and retiring it keeps it alive until drop.
+        let old = unsafe { current.as_ref() }.and_then(|entry| entry.value.as_ref());
+        if !current.is_null() {
+            self.retire(current);
+        }
+        match (old.is_some(), has_value) {
+            (false, true) => {
+                self.len.fetch_add(1, Ordering::Relaxed);
+            }
+            (true, false) => {
+                self.len.fetch_sub(1, Ordering::Relaxed);
+            }
+            _ => {}
+        }
+        old
+    }
+
+    fn retire(&self, entry: *mut Entry<K, V>) {
+        let mut head = self.retired.load(Ordering::Relaxed);
+        loop {
+            // SAFETY: the entry was just unlinked, so only this thread
+            // writes its retirement link.
+            unsafe { (*entry).next_retired = head };
+            match self.retired.compare_exchange_weak(
+                head,
+                entry,
+                Ordering::Release,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return,
+                Err(actual) => head = actual,
+            }
+        }
+    }
+
+    /// Insert a key-value pair into the map.
+    ///
+    /// Returns the previous value if the key had one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is new and the map already holds `capacity` keys.
+    pub fn insert(&self, mut key: K, mut value: V) -> Option<&V> {
+        loop {
+            let (index, current) = self.find_slot(&key).expect("lock-free map is full");
+            if !current.is_null() {
+                return self.swap(index, current, key, Some(value));
+            }
+
+            // Reserve room before claiming so the table never fills up.
+            if self.claimed.fetch_add(1, Ordering::Relaxed) >= self.capacity() {
+                self.claimed.fetch_sub(1, Ordering::Relaxed);
+                panic!("lock-free map is full");
+            }
+            let new = Box::into_raw(Box::new(Entry {
+                key,
+                value: Some(value),
+                next_retired: ptr::null_mut(),
+            }));
+            match self.slots[index].compare_exchange(
+                ptr::null_mut(),
+                new,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => {
+                    self.len.fetch_add(1, Ordering::Relaxed);
+                    return None;
+                }
+                Err(_) => {
+                    // Another key or another insert of this key won the
+                    // slot; take the pair back and probe again.
+                    self.claimed.fetch_sub(1, Ordering::Relaxed);
+                    // SAFETY: `new` was never published.
+                    let entry = unsafe { Box::from_raw(new) };
+                    key = entry.key;
+                    value = entry.value.expect("unpublished entry has a value");
+                }
+            }
+        }
+    }
+
+    /// Get a reference to the value associated with the key.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let (_, current) = self.find_slot(key)?;
+        // SAFETY: non-null slot pointers stay valid until the map drops.
+        unsafe { current.as_ref() }.and_then(|entry| entry.value.as_ref())
+    }
+
+    /// Check if the map contains the given key.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Remove the value associated with the key.
+    ///
+    /// The key keeps its slot, so it still counts towards the capacity.
+    /// Returns the removed value if the key had one.
+    pub fn remove(&self, key: &K) -> Option<&V>
+    where
+        K: Clone,
+    {
+        let (index, current) = self.find_slot(key)?;
+        // SAFETY: non-null slot pointers stay valid until the map drops.
+        let entry = unsafe { current.as_ref() }?;
+        entry.value.as_ref()?;
+        self.swap(index, current, entry.key.clone(), None)
+    }
+}
+
+impl<K, V> Drop for LockFreeMap<K, V> {
+    fn drop(&mut self) {
+        for slot in self.slots.iter_mut() {
+            let entry = *slot.get_mut();
+            if !entry.is_null() {
+                // SAFETY: `&mut self` rules out other users, and each live
+                // entry is owned by exactly one slot.
+                drop(unsafe { Box::from_raw(entry) });
+            }
+        }
+        let mut retired = *self.retired.get_mut();
+        while !retired.is_null() {
+            // SAFETY: each retired entry is on the stack exactly once.
+            let entry = unsafe { Box::from_raw(retired) };
+            retired = entry.next_retired;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicBool;
+    use std::thread;
+
+    const THREADS: u64 = 4;
+
+    #[test]
+    fn test_insert_get_remove() {
+        let map = LockFreeMap::with_capacity(100);
+        assert_eq!(map.insert("a".to_string(), 1), None);
+        assert_eq!(map.insert("a".to_string(), 2), Some(&1));
+        assert_eq!(map.get(&"a".to_string()), Some(&2));
+        assert_eq!(map.remove(&"a".to_string()), Some(&2));
+        assert_eq!(map.remove(&"a".to_string()), None);
+        assert!(map.is_empty());
+        assert_eq!(map.insert("a".to_string(), 3), None);
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "lock-free map is full")]
+    fn test_full_map_panics() {
+        let map = LockFreeMap::with_capacity(4);
+        for i in 0..5 {
+            map.insert(i, i);
+        }
+    }
+
+    #[test]
+    fn test_concurrent_disjoint_inserts() {
+        let map = LockFreeMap::with_capacity(4000);
+        thread::scope(|s| {
+            for t in 0..THREADS {
+                let map = &map;
+                s.spawn(move || {
+                    for i in 0..1000 {
+                        map.insert(t * 1000 + i, i);
+                    }
+                });
+            }
+        });
+        assert_eq!(map.len(), 4000);
+        assert!((0..4000).all(|k| map.get(&k) == Some(&(k % 1000))));
+    }
+
+    #[test]
+    fn test_racing_inserts_have_one_winner_per_key() {
+        let map = LockFreeMap::with_capacity(1000);
+        let winners = AtomicUsize::new(0);
+        thread::scope(|s| {
+            for t in 0..THREADS {
+                let (map, winners) = (&map, &winners);
+                s.spawn(move || {
+                    for key in 0..1000 {
+                        if map.insert(key, t).is_none() {
+                            winners.fetch_add(1, Ordering::Relaxed);
+                        }
+                    }
+                });
+            }
+        });
+        assert_eq!(winners.load(Ordering::Relaxed), 1000);
+        assert_eq!(map.len(), 1000);
+    }
+
+    #[test]
+    fn test_reads_never_go_back_in_time() {
+        // Each writer stores increasing sequence numbers under shared keys.
+        // In a linearizable register, once a reader has seen a writer's
+        // sequence number it can never see an older one from that writer.
+        const KEYS: u64 = 8;
+        let map = LockFreeMap::with_capacity(KEYS as usize);
+        let done = AtomicBool::new(false);
+        thread::scope(|s| {
+            for writer in 0..2usize {
+                let map = &map;
+                s.spawn(move || {
+                    for seq in 1..=20_000u64 {
+                        map.insert(seq % KEYS, (writer, seq));
+                    }
+                });
+            }
+            for _ in 0..2 {
+                let (map, done) = (&map, &done);
+                s.spawn(move || {
+                    let mut last_seen = [[0u64; 2]; KEYS as usize];
+                    while !done.load(Ordering::Relaxed) {
+                        for key in 0..KEYS {
+                            if let Some(&(writer, seq)) = map.get(&key) {
+                                let last = &mut last_seen[key as usize][writer];
+                                assert!(seq >= *last, "read went back in time");
+                                *last = seq;
+                            }
+                        }
+                    }
+                });
+            }
+            thread::sleep(std::time::Duration::from_millis(50));
+            done.store(true, Ordering::Relaxed);
+        });
+    }
+
+    #[test]
+    fn test_concurrent_insert_remove_keeps_count() {
+        let map = LockFreeMap::with_capacity(256);
+        thread::scope(|s| {
+            for t in 0..THREADS {
+                let map = &map;
+                s.spawn(move || {
+                    for round in 0..500 {
+                        let key = (t * 64) + round % 64;
+                        map.insert(key, round);
+                        map.remove(&key);
+                    }
+                });
+            }
+        });
+        assert!(map.is_empty());
+        assert!((0..256).all(|k| map.get(&k).is_none()));
+    }
+}
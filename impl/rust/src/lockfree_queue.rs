@@ -0,0 +1,418 @@
+//! Michael-Scott lock-free queue with hazard-pointer reclamation.
+
+use std::cell::UnsafeCell;
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+
+/// Hazard pointers per record: pop protects the head and its successor.
+const HAZARDS: usize = 2;
+/// Fewest retired nodes a record collects before scanning the hazards.
+const MIN_SCAN_THRESHOLD: usize = 64;
+
+struct Node<T> {
+    /// Uninitialized in the dummy node at the head; a node's value is moved
+    /// out by the pop that makes it the dummy.
+    value: MaybeUninit<T>,
+    next: AtomicPtr<Node<T>>,
+}
+
+impl<T> Node<T> {
+    fn new(value: MaybeUninit<T>) -> *mut Self {
+        Box::into_raw(Box::new(Self {
+            value,
+            next: AtomicPtr::new(ptr::null_mut()),
+        }))
+    }
+}
+
+/// Hazard pointers and retired nodes, owned by one thread at a time for
+/// the length of an operation.
+struct Record<T> {
+    active: AtomicBool,
+    /// Nodes the owner may dereference; no thread frees them while named.
+    hazards: [AtomicPtr<Node<T>>; HAZARDS],
+    /// Nodes unlinked by this record's owners, to free once no hazard
+    /// names them.
+    retired: UnsafeCell<Vec<*mut Node<T>>>,
+    /// Next record in the queue's list; fixed once published.
+    next: *mut Record<T>,
+}
+
+/// A multi-producer, multi-consumer FIFO queue whose operations never
+/// block.
+///
+/// This is the Michael-Scott queue: a singly linked list with a dummy node
+/// at the head. Push links a node after the last one with a compare-and-
+/// swap and then swings `tail` to it; pop swings `head` to the dummy's
+/// successor, which becomes the new dummy, and moves its value out. A
+/// thread that finds `tail` lagging behind a finished link advances it
+/// before trying its own operation, so a stalled thread never blocks the
+/// others.
+///
+/// Popped dummies are reclaimed with hazard pointers. Before dereferencing
+/// a node, a thread publishes its address and re-checks that the node is
+/// still reachable; a node unlinked by pop is only freed once a scan of
+/// every thread's published addresses does not find it. Unlike
+/// [`LockFreeMap`](crate::LockFreeMap), memory stays bounded however many
+/// operations run.
+pub struct LockFreeQueue<T> {
+    head: AtomicPtr<Node<T>>,
+    tail: AtomicPtr<Node<T>>,
+    /// Treiber list of records, only ever pushed onto.
+    records: AtomicPtr<Record<T>>,
+    record_count: AtomicUsize,
+}
+
+// SAFETY: values are pushed on one thread and popped on another, and all
+// shared state is behind atomics or owned by one record holder at a time.
+unsafe impl<T: Send> Sync for LockFreeQueue<T> {}
+// SAFETY: the queue owns its nodes and records.
+unsafe impl<T: Send> Send for LockFreeQueue<T> {}
+
+impl<T> Default for LockFreeQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> LockFreeQueue<T> {
+    /// Create a new empty LockFreeQueue.
+    pub fn new() -> Self {
+        let dummy = Node::new(MaybeUninit::uninit());
+        Self {
+            head: AtomicPtr::new(dummy),
+            tail: AtomicPtr::new(dummy),
+            records: AtomicPtr::new(ptr::null_mut()),
+            record_count: AtomicUsize::new(0),
+        }
+    }
+
+    /// Claim an idle record, or publish a new one if all are in use.
+    fn acquire(&self) -> Guard<'_, T> {
+        let mut current = self.records.load(Ordering::Acquire);
+        // SAFETY: records are freed only when the queue drops.
+        while let Some(record) = unsafe { current.as_ref() } {
+            if !record.active.load(Ordering::Relaxed)
+                && record
+                    .active
+                    .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+                    .is_ok()
+            {
+                return Guard {
+                    queue: self,
+                    record,
+                };
+            }
+            current = record.next;
+        }
+
+        let record = Box::into_raw(Box::new(Record {
+            active: AtomicBool::new(true),
+            hazards: Default::default(),
+            retired: UnsafeCell::new(Vec::new()),
+            next: ptr::null_mut(),
+        }));
+        let mut head = self.records.load(Ordering::Relaxed);
+        loop {
+            // SAFETY: the record is not published yet.
+            unsafe { (*record).next = head };
+            match self.records.compare_exchange_weak(
+                head,
+                record,
+                Ordering::Release,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break,
+                Err(actual) => head = actual,
+            }
+        }
+        self.record_count.fetch_add(1, Ordering::Relaxed);
+        // SAFETY: records are freed only when the queue drops.
+        let record = unsafe { &*record };
+        Guard {
+            queue: self,
+            record,
+        }
+    }
+
+    /// Swing `tail` from `from` to `to`, unless another thread already
+    /// moved it.
+    fn advance_tail(&self, from: *mut Node<T>, to: *mut Node<T>) {
+        let _ = self
+            .tail
+            .compare_exchange(from, to, Ordering::Release, Ordering::Relaxed);
+    }
+
+    /// Add a value to the back of the queue.
+    pub fn push(&self, value: T) {
+        let node = Node::new(MaybeUninit::new(value));
+        let guard = self.acquire();
+        loop {
+            let tail = guard.protect(0, &self.tail);
+            // SAFETY: `tail` is protected, and never null since the list
+            // always holds the dummy.
+            let next = unsafe { (*tail).next.load(Ordering::Acquire) };
+            if !next.is_null() {
+                // Another push linked a node but has not swung the tail yet.
+                self.advance_tail(tail, next);
+                continue;
+            }
+            // SAFETY: as above.
+            let linked = unsafe {
+                (*tail).next.compare_exchange(
+                    ptr::null_mut(),
+                    node,
+                    Ordering::Release,
+                    Ordering::Relaxed,
+                )
+            };
+            if linked.is_ok() {
+                self.advance_tail(tail, node);
+                return;
+            }
+        }
+    }
+
+    /// Remove and return the value at the front of the queue, or `None` if
+    /// the queue is empty.
+    pub fn pop(&self) -> Option<T> {
+        let guard = self.acquire();
+        loop {
+            let head = guard.protect(0, &self.head);
+            // SAFETY: `head` is protected and never null.
+            let next = guard.protect(1, unsafe { &(*head).next });
+            // While `head` is still the head, its successor cannot have been
+            // popped and retired.
+            if self.head.load(Ordering::SeqCst) != head {
+                continue;
+            }
+            if next.is_null() {
+                return None;
+            }
+            let tail = self.tail.load(Ordering::Acquire);
+            if head == tail {
+                // Keep the tail from falling behind the head before it is
+                // retired.
+                self.advance_tail(tail, next);
+                continue;
+            }
+            if self
+                .head
+                .compare_exchange(head, next, Ordering::AcqRel, Ordering::Relaxed)
+                .is_ok()
+            {
+                // SAFETY: winning the swap made `next` the dummy, so this
+                // thread alone moves its value out, exactly once.
+                let value = unsafe { (*next).value.assume_init_read() };
+                guard.clear();
+                guard.retire(head);
+                return Some(value);
+            }
+        }
+    }
+
+    /// Returns true if the queue contains no values.
+    ///
+    /// While other threads are pushing or popping, the answer may be out of
+    /// date by the time it returns.
+    pub fn is_empty(&self) -> bool {
+        let guard = self.acquire();
+        let head = guard.protect(0, &self.head);
+        // SAFETY: `head` is protected and never null.
+        unsafe { (*head).next.load(Ordering::Acquire) }.is_null()
+    }
+
+    /// Free every retired node that no hazard pointer names.
+    fn scan(&self, retired: &mut Vec<*mut Node<T>>) {
+        let mut hazards = Vec::new();
+        let mut current = self.records.load(Ordering::Acquire);
+        // SAFETY: records are freed only when the queue drops.
+        while let Some(record) = unsafe { current.as_ref() } {
+            for hazard in &record.hazards {
+                let node = hazard.load(Ordering::SeqCst);
+                if !node.is_null() {
+                    hazards.push(node);
+                }
+            }
+            current = record.next;
+        }
+        hazards.sort_unstable();
+        retired.retain(|&node| {
+            if hazards.binary_search(&node).is_ok() {
+                return true;
+            }
+            // SAFETY: the node is unlinked, no thread has it protected, and
+            // one that loads it from now on fails its re-check. Its value
+            // was moved out when it became the dummy.
+            drop(unsafe { Box::from_raw(node) });
+            false
+        });
+    }
+}
+
+impl<T> Drop for LockFreeQueue<T> {
+    fn drop(&mut self) {
+        // SAFETY: `&mut self` rules out other users. The list holds the
+        // dummy, whose value is uninitialized, then nodes with values.
+        unsafe {
+            let dummy = Box::from_raw(*self.head.get_mut());
+            let mut node = dummy.next.load(Ordering::Relaxed);
+            while !node.is_null() {
+                let mut boxed = Box::from_raw(node);
+                boxed.value.assume_init_drop();
+                node = boxed.next.load(Ordering::Relaxed);
+            }
+        }
+        let mut record = *self.records.get_mut();
+        while !record.is_null() {
+            // SAFETY: each record is on the list exactly once, and its
+            // retired nodes were unlinked and emptied by a pop.
+            let boxed = unsafe { Box::from_raw(record) };
+            for node in boxed.retired.into_inner() {
+                drop(unsafe { Box::from_raw(node) });
+            }
+            record = boxed.next;
+        }
+    }
+}
+
+/// A claimed record, released with its hazards cleared on drop.
+struct Guard<'a, T> {
+    queue: &'a LockFreeQueue<T>,
+    record: &'a Record<T>,
+}
+
+impl<T> Guard<'_, T> {
+    /// Load `source` into hazard `slot`, retrying until the published
+    /// address is still current, so it cannot be freed while published.
+    fn protect(&self, slot: usize, source: &AtomicPtr<Node<T>>) -> *mut Node<T> {
+        let mut node = source.load(Ordering::Acquire);
+        loop {
+            self.record.hazards[slot].store(node, Ordering::SeqCst);
+            let current = source.load(Ordering::SeqCst);
+            if current == node {
+                return node;
+            }
+            node = current;
+        }
+    }
+
+    fn clear(&self) {
+        for hazard in &self.record.hazards {
+            hazard.store(ptr::null_mut(), Ordering::Release);
+        }
+    }
+
+    /// Hand over an unlinked node, scanning once enough have built up.
+    fn retire(&self, node: *mut Node<T>) {
+        // SAFETY: only the thread holding the record touches its list.
+        let retired = unsafe { &mut *self.record.retired.get() };
+        retired.push(node);
+        // Twice the hazards in use, so each scan frees at least half.
+        let threshold =
+            (2 * HAZARDS * self.queue.record_count.load(Ordering::Relaxed)).max(MIN_SCAN_THRESHOLD);
+        if retired.len() >= threshold {
+            self.queue.scan(retired);
+        }
+    }
+}
+
+impl<T> Drop for Guard<'_, T> {
+    fn drop(&mut self) {
+        self.clear();
+        self.record.active.store(false, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_fifo_order() {
+        let queue = LockFreeQueue::new();
+        assert!(queue.is_empty());
+        assert_eq!(queue.pop(), None);
+        for i in 0..1000 {
+            queue.push(i);
+        }
+        assert!(!queue.is_empty());
+        assert!((0..1000).all(|i| queue.pop() == Some(i)));
+        assert_eq!(queue.pop(), None);
+    }
+
+    #[test]
+    fn test_concurrent_producers_and_consumers() {
+        const PRODUCERS: usize = 4;
+        const CONSUMERS: usize = 4;
+        const PER_PRODUCER: usize = 20_000;
+        let queue = LockFreeQueue::new();
+        let remaining = AtomicUsize::new(PRODUCERS * PER_PRODUCER);
+        let mut received = thread::scope(|s| {
+            for producer in 0..PRODUCERS {
+                let queue = &queue;
+                s.spawn(move || {
+                    for seq in 0..PER_PRODUCER {
+                        queue.push((producer, seq));
+                    }
+                });
+            }
+            let consumers: Vec<_> = (0..CONSUMERS)
+                .map(|_| {
+                    let (queue, remaining) = (&queue, &remaining);
+                    s.spawn(move || {
+                        let mut seen = Vec::new();
+                        let mut last = [None; PRODUCERS];
+                        while remaining.load(Ordering::Relaxed) > 0 {
+                            let Some((producer, seq)) = queue.pop() else {
+                                thread::yield_now();
+                                continue;
+                            };
+                            remaining.fetch_sub(1, Ordering::Relaxed);
+                            // Each producer's values come out in push order.
+                            assert!(last[producer] < Some(seq), "out of order");
+                            last[producer] = Some(seq);
+                            seen.push((producer, seq));
+                        }
+                        seen
+                    })
+                })
+                .collect();
+            consumers
+                .into_iter()
+                .flat_map(|consumer| consumer.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+        received.sort_unstable();
+        let expected: Vec<_> = (0..PRODUCERS)
+            .flat_map(|producer| (0..PER_PRODUCER).map(move |seq| (producer, seq)))
+            .collect();
+        assert_eq!(received, expected);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_values_dropped_exactly_once() {
+        let value = Arc::new(());
+        let queue = LockFreeQueue::new();
+        thread::scope(|s| {
+            for _ in 0..4 {
+                let (queue, value) = (&queue, &value);
+                s.spawn(move || {
+                    for i in 0..2000 {
+                        queue.push(Arc::clone(value));
+                        if i % 3 == 0 {
+                            drop(queue.pop());
+                        }
+                    }
+                });
+            }
+        });
+        assert!(Arc::strong_count(&value) > 1);
+        drop(queue);
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+}
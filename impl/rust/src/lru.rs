@@ -0,0 +1,284 @@
+//! Least-recently-used cache built on the lab hash map.
+
+use crate::hashmap::HashMap;
+use std::hash::Hash;
+
+/// Index of a missing neighbor in the recency list.
+const NIL: usize = usize::MAX;
+
+#[derive(Debug, Clone)]
+struct Node<K, V> {
+    key: K,
+    value: V,
+    /// More recently used neighbor.
+    prev: usize,
+    /// Less recently used neighbor.
+    next: usize,
+}
+
+/// A fixed-capacity cache that evicts the least recently used entry.
+///
+/// Entries live in an arena of nodes threaded into a doubly linked list by
+/// index, most recently used first, and a [`HashMap`] maps each key to its
+/// node. `get` and `put` are O(1): a lookup, plus unlinking and relinking one
+/// node at the head of the list.
+#[derive(Debug, Clone)]
+pub struct LruCache<K, V> {
+    map: HashMap<K, usize>,
+    nodes: Vec<Option<Node<K, V>>>,
+    /// Arena slots freed by `remove`.
+    free: Vec<usize>,
+    head: usize,
+    tail: usize,
+    capacity: usize,
+    hits: u64,
+    misses: u64,
+}
+
+impl<K, V> LruCache<K, V>
+where
+    K: Hash + Eq + Clone,
+{
+    /// Create an empty cache holding at most `capacity` entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "cache capacity must be at least 1");
+        Self {
+            map: HashMap::with_capacity(capacity),
+            nodes: Vec::with_capacity(capacity),
+            free: Vec::new(),
+            head: NIL,
+            tail: NIL,
+            capacity,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Returns the number of cached entries.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if the cache holds no entries.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns the maximum number of entries.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of `get` calls that found their key.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Returns the number of `get` calls that missed.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Fraction of `get` calls that hit, or 0 before the first call.
+    pub fn hit_ratio(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+
+    fn node(&self, index: usize) -> &Node<K, V> {
+        self.nodes[index].as_ref().expect("linked node is live")
+    }
+
+    fn node_mut(&mut self, index: usize) -> &mut Node<K, V> {
+        self.nodes[index].as_mut().expect("linked node is live")
+    }
+
+    fn unlink(&mut self, index: usize) {
+        let (prev, next) = {
+            let node = self.node(index);
+            (node.prev, node.next)
+        };
+        match prev {
+            NIL => self.head = next,
+            prev => self.node_mut(prev).next = next,
+        }
+        match next {
+            NIL => self.tail = prev,
+            next => self.node_mut(next).prev = prev,
+        }
+    }
+
+    fn push_front(&mut self, index: usize) {
+        let head = self.head;
+        {
+            let node = self.node_mut(index);
+            node.prev = NIL;
+            node.next = head;
+        }
+        match head {
+            NIL => self.tail = index,
+            head => self.node_mut(head).prev = index,
+        }
+        self.head = index;
+    }
+
+    /// Get a reference to the value for `key`, marking it most recently used.
+    ///
+    /// Counts a hit or a miss.
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        let Some(&index) = self.map.get(key) else {
+            self.misses += 1;
+            return None;
+        };
+        self.hits += 1;
+        self.unlink(index);
+        self.push_front(index);
+        Some(&self.node(index).value)
+    }
+
+    /// Get a reference to the value for `key` without touching its recency
+    /// or the hit counters.
+    pub fn peek(&self, key: &K) -> Option<&V> {
+        let &index = self.map.get(key)?;
+        Some(&self.node(index).value)
+    }
+
+    /// Check if the cache holds `key`, without touching its recency.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.map.contains_key(key)
+    }
+
+    /// Insert or update an entry and mark it most recently used.
+    ///
+    /// Returns the least recently used entry if it was evicted to make room.
+    pub fn put(&mut self, key: K, value: V) -> Option<(K, V)> {
+        if let Some(&index) = self.map.get(&key) {
+            self.node_mut(index).value = value;
+            self.unlink(index);
+            self.push_front(index);
+            return None;
+        }
+
+        let evicted = if self.map.len() == self.capacity {
+            self.pop_back()
+        } else {
+            None
+        };
+
+        let node = Node {
+            key: key.clone(),
+            value,
+            prev: NIL,
+            next: NIL,
+        };
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = Some(node);
+                index
+            }
+            None => {
+                self.nodes.push(Some(node));
+                self.nodes.len() - 1
+            }
+        };
+        self.push_front(index);
+        self.map.insert(key, index);
+        evicted
+    }
+
+    fn take(&mut self, index: usize) -> (K, V) {
+        self.unlink(index);
+        let node = self.nodes[index].take().expect("linked node is live");
+        self.free.push(index);
+        self.map.remove(&node.key);
+        (node.key, node.value)
+    }
+
+    fn pop_back(&mut self) -> Option<(K, V)> {
+        match self.tail {
+            NIL => None,
+            tail => Some(self.take(tail)),
+        }
+    }
+
+    /// Remove an entry from the cache.
+    ///
+    /// Returns the removed value if the key was cached.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let &index = self.map.get(key)?;
+        Some(self.take(index).1)
+    }
+
+    /// Iterate over entries from most to least recently used.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        let mut index = self.head;
+        std::iter::from_fn(move || {
+            if index == NIL {
+                return None;
+            }
+            let node = self.node(index);
+            index = node.next;
+            Some((&node.key, &node.value))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let mut cache = LruCache::new(2);
+        assert_eq!(cache.put(1, "a"), None);
+        assert_eq!(cache.put(2, "b"), None);
+        assert_eq!(cache.get(&1), Some(&"a"));
+        assert_eq!(cache.put(3, "c"), Some((2, "b")));
+        assert_eq!(cache.get(&2), None);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.iter().map(|(k, _)| *k).collect::<Vec<_>>(), [3, 1]);
+    }
+
+    #[test]
+    fn test_update_promotes_without_evicting() {
+        let mut cache = LruCache::new(2);
+        cache.put(1, 10);
+        cache.put(2, 20);
+        assert_eq!(cache.put(1, 11), None);
+        assert_eq!(cache.put(3, 30), Some((2, 20)));
+        assert_eq!(cache.peek(&1), Some(&11));
+    }
+
+    #[test]
+    fn test_remove_reuses_nodes() {
+        let mut cache = LruCache::new(3);
+        for i in 0..3 {
+            cache.put(i, i);
+        }
+        assert_eq!(cache.remove(&1), Some(1));
+        assert_eq!(cache.remove(&1), None);
+        assert_eq!(cache.put(3, 3), None);
+        assert_eq!(cache.nodes.len(), 3);
+        assert_eq!(cache.put(4, 4), Some((0, 0)));
+        assert_eq!(cache.iter().map(|(k, _)| *k).collect::<Vec<_>>(), [4, 3, 2]);
+    }
+
+    #[test]
+    fn test_hit_miss_counters() {
+        let mut cache = LruCache::new(1);
+        cache.put("x".to_string(), 1);
+        cache.get(&"x".to_string());
+        cache.get(&"y".to_string());
+        cache.peek(&"y".to_string());
+        assert_eq!((cache.hits(), cache.misses()), (1, 1));
+        assert_eq!(cache.hit_ratio(), 0.5);
+    }
+}
@@ -0,0 +1,233 @@
+//! LZ77 compression: every repeat of recent data becomes a back-reference
+//! to it.
+//!
+//! The compressed format groups tokens in eights behind a flag byte whose
+//! bit `i`, least significant first, says whether token `i` is a match or
+//! a literal. A literal is its byte. A match is its offset as a 16-bit
+//! little-endian integer, then its length less [`MIN_MATCH`] in one byte.
+
+use crate::hashmap::HashMap;
+
+/// The furthest back a match can reach.
+pub const WINDOW: usize = u16::MAX as usize;
+/// The shortest match worth a token; a shorter one takes more bytes than
+/// its literals.
+pub const MIN_MATCH: usize = 3;
+/// The longest match a token can hold.
+pub const MAX_MATCH: usize = MIN_MATCH + u8::MAX as usize;
+/// Earlier positions tried per match search, bounding the work on
+/// repetitive input.
+const MAX_CHAIN: usize = 32;
+
+/// One step of an LZ77 parse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Token {
+    /// A byte copied as is.
+    Literal(u8),
+    /// A copy of `len` bytes starting `offset` bytes back. The copy may
+    /// overlap the bytes it produces, so a run of one byte is a literal
+    /// and a single match with offset 1.
+    Match { offset: usize, len: usize },
+}
+
+/// Where earlier occurrences of each three-byte sequence start.
+///
+/// A hash map gives the latest position for each sequence, and a ring
+/// buffer over the window links each position to the one before it with
+/// the same sequence, so the candidates form a chain from newest to
+/// oldest.
+struct MatchTable {
+    latest: HashMap<[u8; MIN_MATCH], usize>,
+    previous: Vec<Option<usize>>,
+}
+
+impl MatchTable {
+    fn new(len: usize) -> Self {
+        Self {
+            latest: HashMap::new(),
+            previous: vec![None; len.min(WINDOW)],
+        }
+    }
+
+    fn key(data: &[u8], i: usize) -> Option<[u8; MIN_MATCH]> {
+        data.get(i..i + MIN_MATCH)?.try_into().ok()
+    }
+
+    /// Record that the sequence at `i` starts there.
+    fn insert(&mut self, data: &[u8], i: usize) {
+        if let Some(key) = Self::key(data, i) {
+            self.previous[i % WINDOW] = self.latest.insert(key, i);
+        }
+    }
+
+    /// Returns the offset and length of the longest match for the data at
+    /// `i` among the candidates tried, or a length of zero.
+    fn longest_match(&self, data: &[u8], i: usize) -> (usize, usize) {
+        let Some(key) = Self::key(data, i) else {
+            return (0, 0);
+        };
+        let limit = MAX_MATCH.min(data.len() - i);
+        let mut best = (0, 0);
+        let mut candidate = self.latest.get(&key).copied();
+        for _ in 0..MAX_CHAIN {
+            // Positions more than a window back may have had their ring
+            // slot reused, so the chain stops there.
+            let Some(start) = candidate.filter(|&start| i - start <= WINDOW) else {
+                break;
+            };
+            let len = data[start..]
+                .iter()
+                .zip(&data[i..i + limit])
+                .take_while(|(a, b)| a == b)
+                .count();
+            if len > best.1 {
+                best = (i - start, len);
+                if len == limit {
+                    break;
+                }
+            }
+            candidate = self.previous[start % WINDOW];
+        }
+        best
+    }
+}
+
+/// Split `data` into literals and matches, taking the longest match found
+/// at each position.
+///
+/// Greedy parsing with hash chains, as in DEFLATE: candidates come from a
+/// table of where each three-byte sequence occurred, newest first, and up
+/// to 32 are compared byte by byte. Every position is entered into the
+/// table, including those inside matches.
+pub fn parse(data: &[u8]) -> Vec<Token> {
+    let mut table = MatchTable::new(data.len());
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < data.len() {
+        let (offset, len) = table.longest_match(data, i);
+        let step = if len >= MIN_MATCH {
+            tokens.push(Token::Match { offset, len });
+            len
+        } else {
+            tokens.push(Token::Literal(data[i]));
+            1
+        };
+        for j in i..i + step {
+            table.insert(data, j);
+        }
+        i += step;
+    }
+    tokens
+}
+
+/// Compress `data` with LZ77.
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    let mut output = Vec::new();
+    for group in parse(data).chunks(8) {
+        let flags_at = output.len();
+        output.push(0);
+        for (bit, &token) in group.iter().enumerate() {
+            match token {
+                Token::Literal(byte) => output.push(byte),
+                Token::Match { offset, len } => {
+                    output[flags_at] |= 1 << bit;
+                    output.extend_from_slice(&(offset as u16).to_le_bytes());
+                    output.push((len - MIN_MATCH) as u8);
+                }
+            }
+        }
+    }
+    output
+}
+
+/// Decompress the output of [`compress`].
+///
+/// Returns `None` if `data` is not a valid compressed stream.
+pub fn decompress(data: &[u8]) -> Option<Vec<u8>> {
+    let mut output: Vec<u8> = Vec::new();
+    let mut i = 0;
+    while i < data.len() {
+        let flags = data[i];
+        i += 1;
+        for bit in 0..8 {
+            if i == data.len() {
+                // Flags for tokens that never came mean a cut-off stream.
+                if flags >> bit != 0 {
+                    return None;
+                }
+                break;
+            }
+            if flags >> bit & 1 == 0 {
+                output.push(data[i]);
+                i += 1;
+                continue;
+            }
+            let token = data.get(i..i + 3)?;
+            i += 3;
+            let offset = usize::from(u16::from_le_bytes([token[0], token[1]]));
+            let len = usize::from(token[2]) + MIN_MATCH;
+            if offset == 0 || offset > output.len() {
+                return None;
+            }
+            // Byte by byte, since the source may run into the copy.
+            let start = output.len() - offset;
+            for k in start..start + len {
+                output.push(output[k]);
+            }
+        }
+    }
+    Some(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_finds_repeats() {
+        let tokens = parse(b"abcabcabcx");
+        assert_eq!(
+            tokens,
+            [
+                Token::Literal(b'a'),
+                Token::Literal(b'b'),
+                Token::Literal(b'c'),
+                Token::Match { offset: 3, len: 6 },
+                Token::Literal(b'x'),
+            ]
+        );
+        // Two bytes repeated are not worth a match.
+        assert!(parse(b"abxab")
+            .iter()
+            .all(|token| matches!(token, Token::Literal(_))));
+    }
+
+    #[test]
+    fn test_runs_and_long_matches() {
+        let data = vec![b'z'; 1000];
+        let tokens = parse(&data);
+        assert_eq!(tokens[0], Token::Literal(b'z'));
+        assert_eq!(
+            tokens[1],
+            Token::Match {
+                offset: 1,
+                len: MAX_MATCH
+            }
+        );
+        let compressed = compress(&data);
+        assert!(compressed.len() < 20);
+        assert_eq!(decompress(&compressed), Some(data));
+        assert_eq!(decompress(&compress(b"")), Some(vec![]));
+    }
+
+    #[test]
+    fn test_rejects_bad_streams() {
+        // A match reaching back before the start.
+        assert_eq!(decompress(&[0b10, b'a', 2, 0, 0]), None);
+        assert_eq!(decompress(&[0b10, b'a', 0, 0, 0]), None);
+        // A match cut off partway, or promised and missing.
+        assert_eq!(decompress(&[0b10, b'a', 1, 0]), None);
+        assert_eq!(decompress(&[0b10, b'a']), None);
+        assert_eq!(decompress(&[0b10, b'a', 1, 0, 1]), Some(b"aaaaa".to_vec()));
+    }
+}
@@ -0,0 +1,176 @@
+//! LZW compression: a dictionary of byte strings seen before, grown by one
+//! entry per code emitted, so the decoder can rebuild it from the codes
+//! alone.
+//!
+//! Codes are packed into bytes first bit most significant, each as wide as
+//! the largest code the dictionary could hold when it was written: 8 bits
+//! for the first, growing to [`MAX_CODE_BITS`]. Once the dictionary has a
+//! code of every width it stops growing.
+
+use crate::radix_trie::RadixTrie;
+
+/// The widest code, and so the dictionary's size limit.
+pub const MAX_CODE_BITS: u32 = 16;
+
+const MAX_CODES: usize = 1 << MAX_CODE_BITS;
+
+/// Returns the width of the `k`-th code of a stream, counting from 0.
+///
+/// Each code but the last adds an entry, so the encoder's dictionary holds
+/// `256 + k` entries, or the limit, when it writes code `k`.
+fn code_width(k: usize) -> u32 {
+    let size = (256 + k).min(MAX_CODES);
+    usize::BITS - (size - 1).leading_zeros()
+}
+
+/// Split `data` into dictionary codes.
+///
+/// The dictionary starts with every single byte as its own code. At each
+/// step, the longest entry that is a prefix of the rest of the input is
+/// found in one descent of a [`RadixTrie`] and its code emitted, and that
+/// entry extended by the next input byte is added under the next code.
+pub fn encode(data: &[u8]) -> Vec<u32> {
+    let mut dictionary = RadixTrie::new();
+    for byte in 0..=u8::MAX {
+        dictionary.insert([byte], u32::from(byte));
+    }
+    let mut codes = Vec::new();
+    let mut i = 0;
+    while i < data.len() {
+        let (len, &code) = dictionary
+            .longest_prefix(&data[i..])
+            .expect("every byte is in the dictionary");
+        codes.push(code);
+        if i + len < data.len() && dictionary.len() < MAX_CODES {
+            let next = dictionary.len() as u32;
+            dictionary.insert(&data[i..=i + len], next);
+        }
+        i += len;
+    }
+    codes
+}
+
+/// Rebuild the data from its codes.
+///
+/// The decoder learns each entry one code late, since an entry's last byte
+/// is the first byte of the string the next code stands for. So the one
+/// code it can meet before knowing it is the entry the encoder had just
+/// added: the previous string followed by that same string's first byte.
+/// Every entry is the previous string plus one byte, and the two sit side
+/// by side in the output, so entries are stored as ranges of the output.
+///
+/// Returns `None` if a code is neither known nor that next entry.
+pub fn decode(codes: &[u32]) -> Option<Vec<u8>> {
+    let mut output: Vec<u8> = Vec::new();
+    // The range of the output for each code from 256 on.
+    let mut entries: Vec<(usize, usize)> = Vec::new();
+    let mut previous: Option<(usize, usize)> = None;
+    for &code in codes {
+        let start = output.len();
+        match (code as usize).checked_sub(256) {
+            None => output.push(code as u8),
+            Some(index) if index < entries.len() => {
+                let (from, len) = entries[index];
+                output.extend_from_within(from..from + len);
+            }
+            Some(index) if index == entries.len() => {
+                let (from, len) = previous?;
+                output.extend_from_within(from..from + len);
+                output.push(output[from]);
+            }
+            Some(_) => return None,
+        }
+        if let Some((from, len)) = previous {
+            if 256 + entries.len() < MAX_CODES {
+                entries.push((from, len + 1));
+            }
+        }
+        previous = Some((start, output.len() - start));
+    }
+    Some(output)
+}
+
+/// Compress `data` with LZW.
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    // Bits waiting to be written are the low `pending` bits; anything
+    // above them is stale and masked off by the byte casts.
+    let mut buffer: u64 = 0;
+    let mut pending = 0;
+    for (k, code) in encode(data).into_iter().enumerate() {
+        let width = code_width(k);
+        buffer = buffer << width | u64::from(code);
+        pending += width;
+        while pending >= 8 {
+            pending -= 8;
+            bytes.push((buffer >> pending) as u8);
+        }
+    }
+    if pending > 0 {
+        bytes.push((buffer << (8 - pending)) as u8);
+    }
+    bytes
+}
+
+/// Decompress the output of [`compress`].
+///
+/// Returns `None` if `data` is not a valid compressed stream.
+pub fn decompress(data: &[u8]) -> Option<Vec<u8>> {
+    let mut codes = Vec::new();
+    let mut buffer: u64 = 0;
+    let mut pending = 0;
+    for &byte in data {
+        buffer = buffer << 8 | u64::from(byte);
+        pending += 8;
+        // Codes are at least a byte wide, so a byte completes at most one.
+        let width = code_width(codes.len());
+        if pending >= width {
+            pending -= width;
+            codes.push((buffer >> pending) as u32 & ((1 << width) - 1));
+        }
+    }
+    // Any bits left over are the final byte's padding.
+    decode(&codes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dictionary_grows() {
+        let codes = encode(b"TOBEORNOTTOBEORTOBEORNOT");
+        let text = |s: &str| s.bytes().map(u32::from).collect::<Vec<_>>();
+        // The classic example: TO, BE, OR and TOB come back as codes.
+        let mut expected = text("TOBEORNOT");
+        expected.extend([256, 258, 260, 265, 259, 261, 263]);
+        assert_eq!(codes, expected);
+        assert_eq!(
+            decode(&codes).as_deref(),
+            Some(&b"TOBEORNOTTOBEORTOBEORNOT"[..])
+        );
+    }
+
+    #[test]
+    fn test_code_not_yet_known() {
+        // "aaaa" is a, then aa before the decoder has added it.
+        let codes = encode(b"aaaa");
+        assert_eq!(codes, [97, 256, 97]);
+        assert_eq!(decode(&codes), Some(b"aaaa".to_vec()));
+        assert_eq!(decode(&[97, 257]), None);
+        assert_eq!(decode(&[256]), None);
+    }
+
+    #[test]
+    fn test_code_widths() {
+        assert_eq!(code_width(0), 8);
+        assert_eq!(code_width(1), 9);
+        assert_eq!(code_width(256), 9);
+        assert_eq!(code_width(257), 10);
+        assert_eq!(code_width(1 << 20), MAX_CODE_BITS);
+        let data: Vec<u8> = (0..5000u32).map(|i| (i * i % 251) as u8).collect();
+        let compressed = compress(&data);
+        assert_eq!(decompress(&compressed), Some(data));
+        assert_eq!(decompress(&compress(b"")), Some(vec![]));
+    }
+}
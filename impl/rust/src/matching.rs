@@ -0,0 +1,263 @@
+//! Maximum matching in a bipartite [`Graph`] by Hopcroft-Karp, with a
+//! minimum vertex cover by König's theorem.
+
+use crate::graph::{Graph, NodeId};
+use std::collections::VecDeque;
+
+/// Marks a node that is unmatched, or not reached by a search.
+const NIL: usize = usize::MAX;
+
+/// A maximum matching between the two sides of a bipartite graph, and a
+/// minimum vertex cover of the same size that proves it maximum.
+///
+/// Lookups take ids of the graph as it was when the matching was computed.
+#[derive(Debug, Clone)]
+pub struct Matching {
+    /// Each node's partner by [`NodeId::index`], if matched.
+    mates: Vec<Option<NodeId>>,
+    /// The matched pairs, left node first.
+    pairs: Vec<(NodeId, NodeId)>,
+    cover: Vec<NodeId>,
+}
+
+impl Matching {
+    /// Returns the number of matched pairs.
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    /// Returns true if nothing is matched.
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    /// Returns the node matched with `node`, if any.
+    pub fn mate(&self, node: NodeId) -> Option<NodeId> {
+        self.mates.get(node.index()).copied().flatten()
+    }
+
+    /// Iterate over the matched pairs, left node first, in the order of the
+    /// left nodes given.
+    pub fn pairs(&self) -> impl Iterator<Item = (NodeId, NodeId)> + '_ {
+        self.pairs.iter().copied()
+    }
+
+    /// Returns a minimum vertex cover: nodes touching every edge, as many
+    /// as there are matched pairs.
+    pub fn vertex_cover(&self) -> &[NodeId] {
+        &self.cover
+    }
+}
+
+/// Returns a maximum matching of a bipartite graph whose `left` nodes are
+/// listed, every other node being on the right. Edge direction and
+/// weights are ignored.
+///
+/// Each phase finds, by breadth-first search from the unmatched left
+/// nodes, the length of the shortest augmenting paths, then augments along
+/// a maximal set of disjoint paths of that length by depth-first search.
+/// There are O(√V) phases, so the whole takes O(E√V).
+///
+/// The last search, which finds no augmenting path, also yields the vertex
+/// cover: the left nodes it did not reach and the right nodes it did.
+///
+/// # Panics
+///
+/// Panics if a left node is not in the graph, or if an edge joins two left
+/// nodes.
+pub fn hopcroft_karp<N, E>(graph: &Graph<N, E>, left: &[NodeId]) -> Matching {
+    let bound = graph.node_bound();
+    let mut position = vec![NIL; bound];
+    for (i, &node) in left.iter().enumerate() {
+        assert!(graph.contains_node(node), "left node not in graph");
+        position[node.index()] = i;
+    }
+    let adjacency: Vec<Vec<NodeId>> = left
+        .iter()
+        .map(|&node| {
+            let mut neighbors: Vec<NodeId> = graph.outgoing(node).map(|edge| edge.target).collect();
+            if graph.is_directed() {
+                neighbors.extend(graph.incoming(node).map(|edge| edge.source));
+            }
+            for neighbor in &neighbors {
+                assert!(
+                    position[neighbor.index()] == NIL,
+                    "edge joins two left nodes"
+                );
+            }
+            neighbors
+        })
+        .collect();
+
+    // The right node matched with each left one, and the left position
+    // matched with each right node by index.
+    let mut left_mate = vec![None; left.len()];
+    let mut right_mate = vec![NIL; bound];
+    let mut layer = vec![NIL; left.len()];
+    let mut current = vec![0; left.len()];
+    let mut queue = VecDeque::new();
+    let mut stack = Vec::new();
+    loop {
+        // Layer the left nodes by alternating distance from the free ones,
+        // stopping at the layer where a free right node first turns up.
+        layer.fill(NIL);
+        queue.clear();
+        for (u, mate) in left_mate.iter().enumerate() {
+            if mate.is_none() {
+                layer[u] = 0;
+                queue.push_back(u);
+            }
+        }
+        let mut shortest = NIL;
+        while let Some(u) = queue.pop_front() {
+            if layer[u] >= shortest {
+                continue;
+            }
+            for &v in &adjacency[u] {
+                let w = right_mate[v.index()];
+                if w == NIL {
+                    shortest = shortest.min(layer[u]);
+                } else if layer[w] == NIL {
+                    layer[w] = layer[u] + 1;
+                    queue.push_back(w);
+                }
+            }
+        }
+        if shortest == NIL {
+            break;
+        }
+
+        current.fill(0);
+        for root in 0..left.len() {
+            if left_mate[root].is_some() {
+                continue;
+            }
+            stack.clear();
+            stack.push(root);
+            while let Some(&u) = stack.last() {
+                let Some(&v) = adjacency[u].get(current[u]) else {
+                    // No augmenting path through `u` this phase.
+                    layer[u] = NIL;
+                    stack.pop();
+                    if let Some(&parent) = stack.last() {
+                        current[parent] += 1;
+                    }
+                    continue;
+                };
+                let w = right_mate[v.index()];
+                if w == NIL && layer[u] == shortest {
+                    // Flip the path: each left node on the stack takes the
+                    // right node its current edge leads to.
+                    for &x in &stack {
+                        let v = adjacency[x][current[x]];
+                        left_mate[x] = Some(v);
+                        right_mate[v.index()] = x;
+                    }
+                    break;
+                }
+                if w != NIL && layer[u] < shortest && layer[w] == layer[u] + 1 {
+                    stack.push(w);
+                } else {
+                    current[u] += 1;
+                }
+            }
+        }
+    }
+
+    let mut mates = vec![None; bound];
+    let mut pairs = Vec::new();
+    let mut cover = Vec::new();
+    for (u, mate) in left_mate.iter().enumerate() {
+        if let Some(v) = *mate {
+            mates[left[u].index()] = Some(v);
+            mates[v.index()] = Some(left[u]);
+            pairs.push((left[u], v));
+        }
+        if layer[u] == NIL {
+            cover.push(left[u]);
+        }
+    }
+    // Right nodes next to a reached left node are all matched, or the last
+    // search would have found an augmenting path.
+    let mut covered = vec![false; bound];
+    for (u, neighbors) in adjacency.iter().enumerate() {
+        if layer[u] == NIL {
+            continue;
+        }
+        for &v in neighbors {
+            if !std::mem::replace(&mut covered[v.index()], true) {
+                cover.push(v);
+            }
+        }
+    }
+    Matching {
+        mates,
+        pairs,
+        cover,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A bipartite graph with `left` and `right` nodes and edges between
+    /// them by position on each side.
+    fn bipartite(
+        left: usize,
+        right: usize,
+        edges: &[(usize, usize)],
+    ) -> (Graph<(), ()>, Vec<NodeId>, Vec<NodeId>) {
+        let mut graph = Graph::undirected();
+        let l: Vec<_> = (0..left).map(|_| graph.add_node(())).collect();
+        let r: Vec<_> = (0..right).map(|_| graph.add_node(())).collect();
+        for &(u, v) in edges {
+            graph.add_edge(l[u], r[v], ());
+        }
+        (graph, l, r)
+    }
+
+    #[test]
+    fn test_augmenting_paths() {
+        // Greedy matching 0-0 and 1-1 leaves 2 stuck; augmenting through
+        // both gives a perfect matching.
+        let (graph, l, r) = bipartite(3, 3, &[(0, 0), (0, 1), (1, 1), (1, 2), (2, 0)]);
+        let matching = hopcroft_karp(&graph, &l);
+        assert_eq!(matching.len(), 3);
+        assert_eq!(matching.mate(l[2]), Some(r[0]));
+        assert_eq!(matching.mate(r[0]), Some(l[2]));
+        assert_eq!(matching.vertex_cover().len(), 3);
+    }
+
+    #[test]
+    fn test_vertex_cover() {
+        // Left 0 is the only way to reach right 0, 1 and 2; a star needs
+        // just its center in the cover.
+        let (graph, l, r) = bipartite(3, 4, &[(0, 0), (0, 1), (0, 2), (1, 3), (2, 3)]);
+        let matching = hopcroft_karp(&graph, &l);
+        assert_eq!(matching.len(), 2);
+        let mut cover = matching.vertex_cover().to_vec();
+        cover.sort();
+        assert_eq!(cover, [l[0], r[3]]);
+        let pairs: Vec<_> = matching.pairs().collect();
+        assert_eq!(pairs.len(), 2);
+        assert_eq!(pairs[0].0, l[0]);
+    }
+
+    #[test]
+    fn test_directed_edges_either_way() {
+        let mut graph = Graph::directed();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        let x = graph.add_node(());
+        let y = graph.add_node(());
+        graph.add_edge(x, a, ());
+        graph.add_edge(a, y, ());
+        graph.add_edge(b, y, ());
+        let matching = hopcroft_karp(&graph, &[a, b]);
+        assert_eq!(matching.len(), 2);
+        assert_eq!(matching.mate(a), Some(x));
+        assert_eq!(matching.mate(b), Some(y));
+        assert!(hopcroft_karp(&graph, &[]).is_empty());
+    }
+}
@@ -0,0 +1,420 @@
+//! Dense and compressed sparse row matrices, and the adjacency matrix of a
+//! [`Graph`].
+//!
+//! The arithmetic takes `T::default()` as zero, as the graph algorithms
+//! do for weights. Vectors are plain slices.
+
+use crate::graph::Graph;
+use std::ops::{Add, Index, IndexMut, Mul};
+
+/// A dense matrix stored row by row in one vector.
+///
+/// Entry `(i, j)` is at `i * cols + j`, so a row is a contiguous slice and
+/// walking along it streams through memory, while walking down a column
+/// strides a whole row per step. Every entry takes space, zero or not:
+/// O(rows x cols) however few are set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Matrix<T> {
+    rows: usize,
+    cols: usize,
+    data: Vec<T>,
+}
+
+impl<T> Matrix<T> {
+    /// Create a matrix from its entries in row-major order.
+    ///
+    /// Returns `None` if `data` does not hold `rows * cols` entries.
+    pub fn from_vec(rows: usize, cols: usize, data: Vec<T>) -> Option<Self> {
+        (rows.checked_mul(cols) == Some(data.len())).then_some(Self { rows, cols, data })
+    }
+
+    /// Create a matrix whose entry `(i, j)` is `f(i, j)`.
+    pub fn from_fn(rows: usize, cols: usize, mut f: impl FnMut(usize, usize) -> T) -> Self {
+        let mut data = Vec::with_capacity(rows * cols);
+        for i in 0..rows {
+            for j in 0..cols {
+                data.push(f(i, j));
+            }
+        }
+        Self { rows, cols, data }
+    }
+
+    /// Returns the number of rows.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the number of columns.
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Get a reference to entry `(i, j)`, or `None` if it is out of bounds.
+    pub fn get(&self, i: usize, j: usize) -> Option<&T> {
+        if i < self.rows && j < self.cols {
+            self.data.get(i * self.cols + j)
+        } else {
+            None
+        }
+    }
+
+    /// Get a mutable reference to entry `(i, j)`, or `None` if it is out of
+    /// bounds.
+    pub fn get_mut(&mut self, i: usize, j: usize) -> Option<&mut T> {
+        if i < self.rows && j < self.cols {
+            self.data.get_mut(i * self.cols + j)
+        } else {
+            None
+        }
+    }
+
+    /// Returns row `i` as a slice.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of bounds.
+    pub fn row(&self, i: usize) -> &[T] {
+        assert!(i < self.rows, "row out of bounds");
+        &self.data[i * self.cols..(i + 1) * self.cols]
+    }
+
+    /// Returns row `i` as a mutable slice.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of bounds.
+    pub fn row_mut(&mut self, i: usize) -> &mut [T] {
+        assert!(i < self.rows, "row out of bounds");
+        &mut self.data[i * self.cols..(i + 1) * self.cols]
+    }
+
+    /// Returns the entries in row-major order.
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+}
+
+impl<T: Clone> Matrix<T> {
+    /// Create a matrix with every entry set to `value`.
+    pub fn filled(rows: usize, cols: usize, value: T) -> Self {
+        Self {
+            rows,
+            cols,
+            data: vec![value; rows * cols],
+        }
+    }
+
+    /// Returns the transpose, with entry `(j, i)` holding this one's `(i, j)`.
+    pub fn transpose(&self) -> Self {
+        Self::from_fn(self.cols, self.rows, |j, i| self[(i, j)].clone())
+    }
+}
+
+impl<T: Copy + Default + Add<Output = T> + Mul<Output = T>> Matrix<T> {
+    /// Returns the product of the matrix and the column vector `x`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` does not have one entry per column.
+    pub fn mul_vec(&self, x: &[T]) -> Vec<T> {
+        assert_eq!(x.len(), self.cols, "vector length must match columns");
+        (0..self.rows)
+            .map(|i| {
+                self.row(i)
+                    .iter()
+                    .zip(x)
+                    .fold(T::default(), |sum, (&a, &b)| sum + a * b)
+            })
+            .collect()
+    }
+}
+
+impl<T> Index<(usize, usize)> for Matrix<T> {
+    type Output = T;
+
+    fn index(&self, (i, j): (usize, usize)) -> &T {
+        self.get(i, j).expect("index out of bounds")
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Matrix<T> {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut T {
+        self.get_mut(i, j).expect("index out of bounds")
+    }
+}
+
+/// A sparse matrix in compressed sparse row form.
+///
+/// Only stored entries take space: their values and column indices in two
+/// vectors, row after row, and for each row the position where its
+/// entries begin. Row `i` is the run from `row_starts[i]` to
+/// `row_starts[i + 1]`, with columns in increasing order, so a
+/// matrix-vector product is one pass over O(rows + nnz) memory and a
+/// lookup is a binary search within one row. The layout is fixed once
+/// built; changing the pattern of stored entries means building anew.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsrMatrix<T> {
+    rows: usize,
+    cols: usize,
+    /// Where each row's entries begin, plus the total at the end.
+    row_starts: Vec<usize>,
+    columns: Vec<usize>,
+    values: Vec<T>,
+}
+
+impl<T> CsrMatrix<T> {
+    /// Create a new matrix with no stored entries.
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self {
+            rows,
+            cols,
+            row_starts: vec![0; rows + 1],
+            columns: Vec::new(),
+            values: Vec::new(),
+        }
+    }
+
+    /// Returns the number of rows.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the number of columns.
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Returns the number of stored entries.
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Get a reference to entry `(i, j)`, or `None` if it is not stored.
+    pub fn get(&self, i: usize, j: usize) -> Option<&T> {
+        if i >= self.rows {
+            return None;
+        }
+        let run = self.row_starts[i]..self.row_starts[i + 1];
+        let k = self.columns[run.clone()].binary_search(&j).ok()?;
+        Some(&self.values[run.start + k])
+    }
+
+    /// Iterate over the stored entries of row `i` as `(column, value)`, in
+    /// column order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of bounds.
+    pub fn row(&self, i: usize) -> impl Iterator<Item = (usize, &T)> {
+        assert!(i < self.rows, "row out of bounds");
+        let run = self.row_starts[i]..self.row_starts[i + 1];
+        self.columns[run.clone()]
+            .iter()
+            .copied()
+            .zip(&self.values[run])
+    }
+
+    /// Iterate over the stored entries as `(row, column, value)`, in
+    /// row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, &T)> {
+        (0..self.rows).flat_map(move |i| self.row(i).map(move |(j, value)| (i, j, value)))
+    }
+}
+
+impl<T: Clone> CsrMatrix<T> {
+    /// Returns the transpose, with entry `(j, i)` holding this one's `(i, j)`.
+    ///
+    /// A counting sort by column: count each column's entries to find where
+    /// its run starts, then deal the entries out row by row, which leaves
+    /// each new row sorted by its column. O(rows + cols + nnz).
+    pub fn transpose(&self) -> Self {
+        let mut row_starts = vec![0; self.cols + 1];
+        for &j in &self.columns {
+            row_starts[j + 1] += 1;
+        }
+        for j in 0..self.cols {
+            row_starts[j + 1] += row_starts[j];
+        }
+        let mut next = row_starts.clone();
+        let mut slots: Vec<Option<(usize, T)>> = vec![None; self.nnz()];
+        for (i, j, value) in self.iter() {
+            slots[next[j]] = Some((i, value.clone()));
+            next[j] += 1;
+        }
+        let (columns, values) = slots
+            .into_iter()
+            .map(|slot| slot.expect("every slot is dealt one entry"))
+            .unzip();
+        Self {
+            rows: self.cols,
+            cols: self.rows,
+            row_starts,
+            columns,
+            values,
+        }
+    }
+}
+
+impl<T: Clone + Default> CsrMatrix<T> {
+    /// Returns the dense form, with zero where no entry is stored.
+    pub fn to_dense(&self) -> Matrix<T> {
+        let mut dense = Matrix::filled(self.rows, self.cols, T::default());
+        for (i, j, value) in self.iter() {
+            dense[(i, j)] = value.clone();
+        }
+        dense
+    }
+}
+
+impl<T: Clone + Default + PartialEq> CsrMatrix<T> {
+    /// Returns the sparse form of `dense`, storing its nonzero entries.
+    pub fn from_dense(dense: &Matrix<T>) -> Self {
+        let zero = T::default();
+        let mut matrix = Self::new(dense.rows(), dense.cols());
+        for i in 0..dense.rows() {
+            for (j, value) in dense.row(i).iter().enumerate() {
+                if *value != zero {
+                    matrix.columns.push(j);
+                    matrix.values.push(value.clone());
+                }
+            }
+            matrix.row_starts[i + 1] = matrix.values.len();
+        }
+        matrix
+    }
+}
+
+impl<T: Copy + Add<Output = T>> CsrMatrix<T> {
+    /// Create a matrix from `(row, column, value)` entries in any order.
+    /// Entries at the same position are added together, and every position
+    /// given is stored, even if its value is zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an entry is out of bounds.
+    pub fn from_triplets(
+        rows: usize,
+        cols: usize,
+        entries: impl IntoIterator<Item = (usize, usize, T)>,
+    ) -> Self {
+        let mut by_row: Vec<Vec<(usize, T)>> = vec![Vec::new(); rows];
+        for (i, j, value) in entries {
+            assert!(i < rows && j < cols, "entry out of bounds");
+            by_row[i].push((j, value));
+        }
+        let mut matrix = Self::new(rows, cols);
+        for (i, mut entries) in by_row.into_iter().enumerate() {
+            entries.sort_by_key(|&(j, _)| j);
+            for (j, value) in entries {
+                if matrix.columns.len() > matrix.row_starts[i] && matrix.columns.last() == Some(&j)
+                {
+                    let last = matrix.values.last_mut().expect("a column was pushed");
+                    *last = *last + value;
+                } else {
+                    matrix.columns.push(j);
+                    matrix.values.push(value);
+                }
+            }
+            matrix.row_starts[i + 1] = matrix.values.len();
+        }
+        matrix
+    }
+
+    /// Returns the weighted adjacency matrix of `graph`: entry `(u, v)` is
+    /// the total weight of the edges from `u` to `v`.
+    ///
+    /// Rows and columns are numbered by [`NodeId::index`](crate::NodeId::index)
+    /// up to the graph's [`node_bound`](Graph::node_bound), so a removed
+    /// node leaves an empty row and column. An undirected edge appears in
+    /// both directions, which makes the matrix symmetric.
+    pub fn from_graph<N>(graph: &Graph<N, T>) -> Self {
+        let n = graph.node_bound();
+        let mut entries = Vec::new();
+        for edge in graph.edges() {
+            let (u, v) = (edge.source.index(), edge.target.index());
+            entries.push((u, v, *edge.weight));
+            if !graph.is_directed() && u != v {
+                entries.push((v, u, *edge.weight));
+            }
+        }
+        Self::from_triplets(n, n, entries)
+    }
+}
+
+impl<T: Copy + Default + Add<Output = T> + Mul<Output = T>> CsrMatrix<T> {
+    /// Returns the product of the matrix and the column vector `x`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` does not have one entry per column.
+    pub fn mul_vec(&self, x: &[T]) -> Vec<T> {
+        assert_eq!(x.len(), self.cols, "vector length must match columns");
+        (0..self.rows)
+            .map(|i| {
+                self.row(i)
+                    .fold(T::default(), |sum, (j, &value)| sum + value * x[j])
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dense_basics() {
+        let m = Matrix::from_vec(2, 3, vec![1, 2, 3, 4, 5, 6]).unwrap();
+        assert_eq!(m[(1, 0)], 4);
+        assert_eq!(m.row(0), [1, 2, 3]);
+        assert_eq!(m.get(2, 0), None);
+        assert_eq!(m.mul_vec(&[1, 0, -1]), [-2, -2]);
+        let t = m.transpose();
+        assert_eq!((t.rows(), t.cols()), (3, 2));
+        assert_eq!(t.as_slice(), [1, 4, 2, 5, 3, 6]);
+        assert_eq!(t.transpose(), m);
+        assert!(Matrix::from_vec(2, 2, vec![1, 2, 3]).is_none());
+    }
+
+    #[test]
+    fn test_sparse_from_triplets() {
+        // Out of order, with a repeated position to sum.
+        let m = CsrMatrix::from_triplets(3, 4, [(2, 3, 7), (0, 1, 2), (2, 0, 1), (0, 1, 3)]);
+        assert_eq!(m.nnz(), 3);
+        assert_eq!(m.get(0, 1), Some(&5));
+        assert_eq!(m.get(1, 1), None);
+        assert_eq!(
+            m.iter().collect::<Vec<_>>(),
+            [(0, 1, &5), (2, 0, &1), (2, 3, &7)]
+        );
+        assert_eq!(m.mul_vec(&[1, 1, 1, 2]), [5, 0, 15]);
+        let t = m.transpose();
+        assert_eq!(
+            t.iter().collect::<Vec<_>>(),
+            [(0, 2, &1), (1, 0, &5), (3, 2, &7)]
+        );
+        assert_eq!(t.to_dense(), m.to_dense().transpose());
+        assert_eq!(CsrMatrix::from_dense(&m.to_dense()), m);
+    }
+
+    #[test]
+    fn test_graph_adjacency() {
+        let mut graph = Graph::undirected();
+        let a = graph.add_node(());
+        let gone = graph.add_node(());
+        let b = graph.add_node(());
+        graph.add_edge(a, b, 2);
+        graph.add_edge(a, b, 3);
+        graph.add_edge(b, b, 1);
+        graph.add_edge(a, gone, 9);
+        graph.remove_node(gone);
+        let m = CsrMatrix::from_graph(&graph);
+        assert_eq!(m.rows(), 3);
+        assert_eq!(
+            m.iter().collect::<Vec<_>>(),
+            [(0, 2, &5), (2, 0, &5), (2, 2, &1)]
+        );
+        // Multiplying by all ones gives each node's weighted degree.
+        assert_eq!(m.mul_vec(&[1, 1, 1]), [5, 0, 6]);
+    }
+}
@@ -0,0 +1,358 @@
+//! Maximum flow and minimum cut on a directed [`Graph`]: Dinic, with
+//! Edmonds-Karp as a simpler reference.
+
+use crate::graph::{EdgeId, Graph, NodeId};
+use std::collections::VecDeque;
+use std::ops::{Add, Sub};
+
+/// Marks a node not yet reached by a search.
+const NIL: usize = usize::MAX;
+
+/// A maximum flow from a source to a sink, and the minimum cut that
+/// proves it maximum.
+///
+/// Lookups take ids of the graph as it was when the flow was computed.
+#[derive(Debug, Clone)]
+pub struct MaxFlow<C> {
+    value: C,
+    /// Flow along each edge by [`EdgeId::index`].
+    flows: Vec<C>,
+    /// Whether each node by [`NodeId::index`] is still reachable from the
+    /// source through edges with capacity to spare.
+    source_side: Vec<bool>,
+}
+
+impl<C: Copy> MaxFlow<C> {
+    /// Returns the total flow out of the source, which equals the capacity
+    /// of a minimum cut.
+    pub fn value(&self) -> C {
+        self.value
+    }
+
+    /// Returns the flow along `edge`, or `None` if it was not in the graph.
+    pub fn flow(&self, edge: EdgeId) -> Option<C> {
+        self.flows.get(edge.index()).copied()
+    }
+
+    /// Check if `node` is on the source side of the minimum cut.
+    pub fn is_source_side(&self, node: NodeId) -> bool {
+        self.source_side.get(node.index()).copied().unwrap_or(false)
+    }
+
+    /// Returns the edges of a minimum cut: those leading from the source
+    /// side to the sink side. Every one of them is saturated, and their
+    /// capacities add up to [`value`](Self::value).
+    pub fn min_cut<N>(&self, graph: &Graph<N, C>) -> Vec<EdgeId> {
+        graph
+            .edges()
+            .filter(|edge| self.is_source_side(edge.source) && !self.is_source_side(edge.target))
+            .map(|edge| edge.id)
+            .collect()
+    }
+}
+
+/// Returns a maximum flow from `source` to `sink`, taking edge weights as
+/// capacities, by Dinic's algorithm. `C::default()` is taken as zero.
+///
+/// Each phase builds the level graph, by breadth-first search over the
+/// residual edges, of the shortest paths left from the source to the
+/// sink. A depth-first search then pushes a blocking flow through it,
+/// remembering at each node the next edge worth trying, so edges that
+/// lead nowhere are passed over once per phase. Every phase lengthens the
+/// shortest path, so there are fewer than V of them, and the whole takes
+/// O(V²E); on unit capacities it is O(E√V).
+///
+/// # Panics
+///
+/// Panics if the graph is undirected, if `source` or `sink` is not in the
+/// graph or they are the same node, or if a capacity is negative.
+pub fn dinic<N, C>(graph: &Graph<N, C>, source: NodeId, sink: NodeId) -> MaxFlow<C>
+where
+    C: Copy + Ord + Add<Output = C> + Sub<Output = C> + Default,
+{
+    let mut network = Network::new(graph, source, sink);
+    let (s, t) = (source.index(), sink.index());
+    let zero = C::default();
+    let mut value = zero;
+    let mut level = vec![NIL; network.first.len() - 1];
+    let mut current = vec![0; level.len()];
+    let mut path = Vec::new();
+    loop {
+        network.levels(s, &mut level);
+        if level[t] == NIL {
+            break;
+        }
+        current.copy_from_slice(&network.first[..level.len()]);
+        let mut node = s;
+        loop {
+            if node == t {
+                let bottleneck = network.augment(&path);
+                value = value + bottleneck;
+                // Back up to the tail of the first arc this saturated.
+                let saturated = path
+                    .iter()
+                    .position(|&arc| network.residual[arc] == zero)
+                    .expect("the bottleneck arc is saturated");
+                path.truncate(saturated);
+                node = path.last().map_or(s, |&arc| network.heads[arc]);
+                continue;
+            }
+            let mut advanced = false;
+            while current[node] < network.first[node + 1] {
+                let arc = network.arcs[current[node]];
+                let head = network.heads[arc];
+                if network.residual[arc] > zero && level[head] == level[node] + 1 {
+                    path.push(arc);
+                    node = head;
+                    advanced = true;
+                    break;
+                }
+                current[node] += 1;
+            }
+            if advanced {
+                continue;
+            }
+            if node == s {
+                break;
+            }
+            // A dead end: drop it from the level graph and retreat.
+            level[node] = NIL;
+            let arc = path.pop().expect("only the source has an empty path");
+            node = network.heads[arc ^ 1];
+            current[node] += 1;
+        }
+    }
+    network.finish(graph, s, value)
+}
+
+/// Returns a maximum flow from `source` to `sink`, taking edge weights as
+/// capacities, by the Edmonds-Karp algorithm. `C::default()` is taken as
+/// zero.
+///
+/// Augments along one shortest residual path at a time, found by
+/// breadth-first search, in O(VE²). Simpler than [`dinic`] and slower;
+/// it is here as a reference to check it against.
+///
+/// # Panics
+///
+/// Panics under the same conditions as [`dinic`].
+pub fn edmonds_karp<N, C>(graph: &Graph<N, C>, source: NodeId, sink: NodeId) -> MaxFlow<C>
+where
+    C: Copy + Ord + Add<Output = C> + Sub<Output = C> + Default,
+{
+    let mut network = Network::new(graph, source, sink);
+    let (s, t) = (source.index(), sink.index());
+    let zero = C::default();
+    let mut value = zero;
+    let mut parent = vec![NIL; network.first.len() - 1];
+    let mut queue = VecDeque::new();
+    let mut path = Vec::new();
+    loop {
+        parent.fill(NIL);
+        queue.clear();
+        queue.push_back(s);
+        while let Some(node) = queue.pop_front() {
+            for &arc in &network.arcs[network.first[node]..network.first[node + 1]] {
+                let head = network.heads[arc];
+                if head != s && parent[head] == NIL && network.residual[arc] > zero {
+                    parent[head] = arc;
+                    queue.push_back(head);
+                }
+            }
+        }
+        if parent[t] == NIL {
+            break;
+        }
+        path.clear();
+        let mut node = t;
+        while node != s {
+            path.push(parent[node]);
+            node = network.heads[parent[node] ^ 1];
+        }
+        value = value + network.augment(&path);
+    }
+    network.finish(graph, s, value)
+}
+
+/// The residual network: edge `k` of the graph's edge list becomes arc
+/// `2k` with its capacity and the reverse arc `2k + 1` with none, so an
+/// arc's partner is `arc ^ 1`.
+struct Network<C> {
+    /// The node index each arc leads to.
+    heads: Vec<usize>,
+    /// The capacity each arc has to spare.
+    residual: Vec<C>,
+    /// The graph's edge behind each pair of arcs.
+    edges: Vec<EdgeId>,
+    /// Where each node's arcs start in `arcs`, by node index, with one
+    /// more entry marking the end.
+    first: Vec<usize>,
+    /// Arc ids grouped by the node they leave.
+    arcs: Vec<usize>,
+}
+
+impl<C> Network<C>
+where
+    C: Copy + Ord + Add<Output = C> + Sub<Output = C> + Default,
+{
+    fn new<N>(graph: &Graph<N, C>, source: NodeId, sink: NodeId) -> Self {
+        assert!(graph.is_directed(), "max flow needs a directed graph");
+        assert!(graph.contains_node(source), "source node not in graph");
+        assert!(graph.contains_node(sink), "sink node not in graph");
+        assert!(source != sink, "source and sink must differ");
+        let zero = C::default();
+        let mut heads = Vec::with_capacity(2 * graph.edge_count());
+        let mut residual = Vec::with_capacity(2 * graph.edge_count());
+        let mut edges = Vec::with_capacity(graph.edge_count());
+        let mut first = vec![0; graph.node_bound() + 1];
+        for edge in graph.edges() {
+            assert!(*edge.weight >= zero, "capacities must not be negative");
+            heads.extend([edge.target.index(), edge.source.index()]);
+            residual.extend([*edge.weight, zero]);
+            edges.push(edge.id);
+            first[edge.source.index() + 1] += 1;
+            first[edge.target.index() + 1] += 1;
+        }
+        for i in 1..first.len() {
+            first[i] += first[i - 1];
+        }
+        let mut fill = first.clone();
+        let mut arcs = vec![0; heads.len()];
+        for arc in 0..heads.len() {
+            let tail = heads[arc ^ 1];
+            arcs[fill[tail]] = arc;
+            fill[tail] += 1;
+        }
+        Self {
+            heads,
+            residual,
+            edges,
+            first,
+            arcs,
+        }
+    }
+
+    /// Label each node with its distance from `source` over arcs with
+    /// capacity to spare, `NIL` if out of reach.
+    fn levels(&self, source: usize, level: &mut [usize]) {
+        let zero = C::default();
+        level.fill(NIL);
+        level[source] = 0;
+        let mut queue = VecDeque::from([source]);
+        while let Some(node) = queue.pop_front() {
+            for &arc in &self.arcs[self.first[node]..self.first[node + 1]] {
+                let head = self.heads[arc];
+                if level[head] == NIL && self.residual[arc] > zero {
+                    level[head] = level[node] + 1;
+                    queue.push_back(head);
+                }
+            }
+        }
+    }
+
+    /// Push as much flow as fits along a path of arcs, returning how much.
+    fn augment(&mut self, path: &[usize]) -> C {
+        let bottleneck = path
+            .iter()
+            .map(|&arc| self.residual[arc])
+            .min()
+            .expect("paths have arcs");
+        for &arc in path {
+            self.residual[arc] = self.residual[arc] - bottleneck;
+            self.residual[arc ^ 1] = self.residual[arc ^ 1] + bottleneck;
+        }
+        bottleneck
+    }
+
+    /// Read the flows off the reverse arcs and the cut off the nodes still
+    /// reachable from the source.
+    fn finish<N>(self, graph: &Graph<N, C>, source: usize, value: C) -> MaxFlow<C> {
+        let mut flows = vec![C::default(); graph.edge_bound()];
+        for (k, edge) in self.edges.iter().enumerate() {
+            flows[edge.index()] = self.residual[2 * k + 1];
+        }
+        let mut level = vec![NIL; self.first.len() - 1];
+        self.levels(source, &mut level);
+        MaxFlow {
+            value,
+            flows,
+            source_side: level.iter().map(|&l| l != NIL).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A directed graph on `n` nodes with the given capacities.
+    fn network(n: usize, edges: &[(usize, usize, u32)]) -> (Graph<(), u32>, Vec<NodeId>) {
+        let mut graph = Graph::directed();
+        let nodes: Vec<_> = (0..n).map(|_| graph.add_node(())).collect();
+        for &(source, target, capacity) in edges {
+            graph.add_edge(nodes[source], nodes[target], capacity);
+        }
+        (graph, nodes)
+    }
+
+    #[test]
+    fn test_classic_network() {
+        // The network from CLRS, figure 26.6, with a maximum flow of 23.
+        let (graph, n) = network(
+            6,
+            &[
+                (0, 1, 16),
+                (0, 2, 13),
+                (2, 1, 4),
+                (1, 3, 12),
+                (3, 2, 9),
+                (2, 4, 14),
+                (4, 3, 7),
+                (3, 5, 20),
+                (4, 5, 4),
+            ],
+        );
+        for flow in [dinic(&graph, n[0], n[5]), edmonds_karp(&graph, n[0], n[5])] {
+            assert_eq!(flow.value(), 23);
+            let cut = flow.min_cut(&graph);
+            let capacity: u32 = cut.iter().map(|&edge| *graph.edge(edge).unwrap()).sum();
+            assert_eq!(capacity, 23);
+            for &edge in &cut {
+                assert_eq!(flow.flow(edge), graph.edge(edge).copied());
+            }
+            assert!(flow.is_source_side(n[0]) && !flow.is_source_side(n[5]));
+        }
+    }
+
+    #[test]
+    fn test_dead_ends_and_parallel_edges() {
+        // Two parallel edges into 1, a dead end at 2 and a self-loop.
+        let (graph, n) = network(
+            5,
+            &[
+                (0, 1, 3),
+                (0, 1, 2),
+                (1, 2, 10),
+                (1, 3, 4),
+                (3, 3, 8),
+                (3, 4, 9),
+            ],
+        );
+        let flow = dinic(&graph, n[0], n[4]);
+        assert_eq!(flow.value(), 4);
+        let cut = flow.min_cut(&graph);
+        assert_eq!(cut.len(), 1);
+        assert_eq!(graph.endpoints(cut[0]), Some((n[1], n[3])));
+        assert!(flow.is_source_side(n[2]));
+    }
+
+    #[test]
+    fn test_unreachable_sink() {
+        let (graph, n) = network(3, &[(0, 1, 5), (2, 1, 5)]);
+        for flow in [dinic(&graph, n[0], n[2]), edmonds_karp(&graph, n[0], n[2])] {
+            assert_eq!(flow.value(), 0);
+            assert!(flow.min_cut(&graph).is_empty());
+            assert!(flow.is_source_side(n[1]));
+        }
+    }
+}
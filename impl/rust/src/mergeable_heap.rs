@@ -0,0 +1,58 @@
+//! A common interface for the min-heaps that support meld.
+
+use crate::{BinomialHeap, FibonacciHeap, LeftistHeap, SkewHeap};
+
+/// A min-priority queue that can absorb another of its own kind.
+///
+/// Implemented by every heap in the crate with a `meld`, so tests and
+/// benchmarks can be written once and run over all of them. Each method
+/// forwards to the inherent method of the same name.
+pub trait MergeableHeap<T: Ord>: Default {
+    /// Returns the number of elements in the heap.
+    fn len(&self) -> usize;
+
+    /// Returns true if the heap contains no elements.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Push an element onto the heap.
+    fn push(&mut self, item: T);
+
+    /// Get a reference to the smallest element, or `None` if the heap is
+    /// empty.
+    fn peek(&self) -> Option<&T>;
+
+    /// Remove and return the smallest element, or `None` if the heap is
+    /// empty.
+    fn pop(&mut self) -> Option<T>;
+
+    /// Move every element of `other` into this heap.
+    fn meld(&mut self, other: Self);
+}
+
+macro_rules! impl_mergeable_heap {
+    ($($heap:ident),*) => {
+        $(
+            impl<T: Ord> MergeableHeap<T> for $heap<T> {
+                fn len(&self) -> usize {
+                    $heap::len(self)
+                }
+                fn push(&mut self, item: T) {
+                    $heap::push(self, item);
+                }
+                fn peek(&self) -> Option<&T> {
+                    $heap::peek(self)
+                }
+                fn pop(&mut self) -> Option<T> {
+                    $heap::pop(self)
+                }
+                fn meld(&mut self, other: Self) {
+                    $heap::meld(self, other);
+                }
+            }
+        )*
+    };
+}
+
+impl_mergeable_heap!(BinomialHeap, FibonacciHeap, LeftistHeap, SkewHeap);
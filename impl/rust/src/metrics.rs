@@ -0,0 +1,66 @@
+//! Operation counters for explaining benchmark results.
+//!
+//! Counting is compiled in only with the `metrics` feature; without it the
+//! counters are zero-sized and every recording call is a no-op.
+
+#[cfg(feature = "metrics")]
+use std::cell::Cell;
+
+/// Operation counts accumulated by one map instance.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Metrics {
+    /// Slots inspected while probing.
+    pub probes: u64,
+    /// Key equality comparisons.
+    pub comparisons: u64,
+    /// Table rebuilds, including shrinks and same-capacity rehashes.
+    pub resizes: u64,
+    /// Tombstones stepped over while probing.
+    pub tombstone_hits: u64,
+}
+
+/// Per-instance counters, updated through shared references so lookups can
+/// record their work.
+#[derive(Debug, Default)]
+pub(crate) struct Counters {
+    #[cfg(feature = "metrics")]
+    metrics: Cell<Metrics>,
+}
+
+impl Counters {
+    #[cfg(feature = "metrics")]
+    fn update(&self, f: impl FnOnce(&mut Metrics)) {
+        let mut metrics = self.metrics.get();
+        f(&mut metrics);
+        self.metrics.set(metrics);
+    }
+
+    #[inline]
+    pub(crate) fn probe(&self) {
+        #[cfg(feature = "metrics")]
+        self.update(|m| m.probes += 1);
+    }
+
+    #[inline]
+    pub(crate) fn comparison(&self) {
+        #[cfg(feature = "metrics")]
+        self.update(|m| m.comparisons += 1);
+    }
+
+    #[inline]
+    pub(crate) fn resize(&self) {
+        #[cfg(feature = "metrics")]
+        self.update(|m| m.resizes += 1);
+    }
+
+    #[inline]
+    pub(crate) fn tombstone_hit(&self) {
+        #[cfg(feature = "metrics")]
+        self.update(|m| m.tombstone_hits += 1);
+    }
+
+    #[cfg(feature = "metrics")]
+    pub(crate) fn snapshot(&self) -> Metrics {
+        self.metrics.get()
+    }
+}
@@ -0,0 +1,216 @@
+//! MinHash signatures for Jaccard similarity, with LSH banding.
+
+use crate::hashmap::{DefaultHashBuilder, HashMap};
+use crate::perfect::displace;
+use std::hash::{BuildHasher, Hash};
+
+/// A family of hash functions that turns sets into comparable signatures.
+///
+/// For each of `k` independent hash functions a signature keeps the
+/// smallest hash of any item in the set. Two sets agree on a given minimum
+/// exactly when the item with the smallest hash over their union lies in
+/// both, which happens with probability equal to their Jaccard similarity
+/// `|A ∩ B| / |A ∪ B|`. So the fraction of matching positions estimates it,
+/// with a standard error of about `1 / sqrt(k)`.
+#[derive(Debug, Clone)]
+pub struct MinHash {
+    num_hashes: usize,
+    hash_builder: DefaultHashBuilder,
+}
+
+/// The per-function minimum hashes of one set, from [`MinHash::signature`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature(Vec<u64>);
+
+impl MinHash {
+    /// Create a new MinHash family of `num_hashes` hash functions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_hashes` is zero.
+    pub fn new(num_hashes: usize) -> Self {
+        assert!(num_hashes > 0, "MinHash needs at least one hash function");
+        Self {
+            num_hashes,
+            hash_builder: DefaultHashBuilder::default(),
+        }
+    }
+
+    /// Returns the number of hash functions, and so the signature length.
+    pub fn num_hashes(&self) -> usize {
+        self.num_hashes
+    }
+
+    /// Compute the signature of a set of items. Repeated items make no
+    /// difference.
+    pub fn signature<T, I>(&self, items: I) -> Signature
+    where
+        T: Hash,
+        I: IntoIterator<Item = T>,
+    {
+        let mut minimums = vec![u64::MAX; self.num_hashes];
+        for item in items {
+            // One real hash per item, remixed once per function.
+            let hash = self.hash_builder.hash_one(&item);
+            for (seed, minimum) in (0..).zip(&mut minimums) {
+                *minimum = (*minimum).min(displace(hash, seed));
+            }
+        }
+        Signature(minimums)
+    }
+}
+
+impl Signature {
+    /// Returns the minimum hashes, one per hash function.
+    pub fn as_slice(&self) -> &[u64] {
+        &self.0
+    }
+
+    /// Estimate the Jaccard similarity of the two sets as the fraction of
+    /// positions where their signatures agree.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the signatures have different lengths.
+    pub fn jaccard(&self, other: &Signature) -> f64 {
+        assert_eq!(
+            self.0.len(),
+            other.0.len(),
+            "signatures must come from the same MinHash"
+        );
+        let matches = self.0.iter().zip(&other.0).filter(|(a, b)| a == b).count();
+        matches as f64 / self.0.len() as f64
+    }
+}
+
+/// A locality-sensitive hashing index over MinHash signatures.
+///
+/// Each signature is cut into `bands` bands of `rows` positions, and each
+/// band is hashed into its own table. Two sets become candidates if they
+/// agree on every row of at least one band, which for similarity `s`
+/// happens with probability `1 - (1 - s^rows)^bands`: an S-curve that is
+/// steepest near `(1 / bands)^(1 / rows)`. More bands catch less similar
+/// pairs; more rows reject them. Candidates are then checked with
+/// [`Signature::jaccard`].
+#[derive(Debug, Clone)]
+pub struct LshIndex {
+    rows: usize,
+    /// One table per band, from band hash to the ids sharing it.
+    tables: Vec<HashMap<u64, Vec<usize>>>,
+    len: usize,
+    hash_builder: DefaultHashBuilder,
+}
+
+impl LshIndex {
+    /// Create a new empty LshIndex for signatures of `bands * rows` hashes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bands` or `rows` is zero.
+    pub fn new(bands: usize, rows: usize) -> Self {
+        assert!(bands > 0 && rows > 0, "LSH needs at least one band and row");
+        Self {
+            rows,
+            tables: (0..bands).map(|_| HashMap::new()).collect(),
+            len: 0,
+            hash_builder: DefaultHashBuilder::default(),
+        }
+    }
+
+    /// Returns the number of signatures in the index.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the index holds no signatures.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn band_hashes<'a>(&'a self, signature: &'a Signature) -> impl Iterator<Item = u64> + 'a {
+        assert_eq!(
+            signature.0.len(),
+            self.tables.len() * self.rows,
+            "signature length must be bands * rows"
+        );
+        signature
+            .0
+            .chunks(self.rows)
+            .map(|band| self.hash_builder.hash_one(band))
+    }
+
+    /// Add a signature to the index, returning its id. Ids count up from
+    /// zero in insertion order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the signature length is not `bands * rows`.
+    pub fn insert(&mut self, signature: &Signature) -> usize {
+        let id = self.len;
+        let hashes: Vec<u64> = self.band_hashes(signature).collect();
+        for (table, hash) in self.tables.iter_mut().zip(hashes) {
+            table.entry(hash).or_default().push(id);
+        }
+        self.len += 1;
+        id
+    }
+
+    /// Returns the sorted ids of every signature sharing at least one band
+    /// with `signature`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the signature length is not `bands * rows`.
+    pub fn candidates(&self, signature: &Signature) -> Vec<usize> {
+        let mut ids: Vec<usize> = self
+            .band_hashes(signature)
+            .zip(&self.tables)
+            .filter_map(|(hash, table)| table.get(&hash))
+            .flatten()
+            .copied()
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jaccard_estimate() {
+        let minhash = MinHash::new(512);
+        let a = minhash.signature(0..1500);
+        let b = minhash.signature(500..2000);
+        // |A ∩ B| = 1000, |A ∪ B| = 2000.
+        let estimate = a.jaccard(&b);
+        assert!((estimate - 0.5).abs() < 0.1, "estimate {}", estimate);
+        assert_eq!(a.jaccard(&minhash.signature((0..1500).rev())), 1.0);
+        assert!(a.jaccard(&minhash.signature(5000..6500)) < 0.05);
+    }
+
+    #[test]
+    fn test_lsh_finds_near_duplicates() {
+        let minhash = MinHash::new(64);
+        let mut index = LshIndex::new(16, 4);
+        let documents: Vec<Signature> = (0..50)
+            .map(|doc| minhash.signature(doc * 1000..doc * 1000 + 200))
+            .collect();
+        for signature in &documents {
+            index.insert(signature);
+        }
+        // 190 of 200 items shared with document 7: similarity 0.9.
+        let query = minhash.signature(7010..7210);
+        assert_eq!(index.candidates(&query), [7]);
+        assert_eq!(index.candidates(&minhash.signature(100_000..100_200)), []);
+    }
+
+    #[test]
+    #[should_panic(expected = "signature length must be bands * rows")]
+    fn test_lsh_rejects_wrong_length() {
+        let mut index = LshIndex::new(4, 4);
+        index.insert(&MinHash::new(8).signature(0..10));
+    }
+}
@@ -0,0 +1,77 @@
+//! Associative operations for the range-query structures.
+
+use std::marker::PhantomData;
+
+/// An associative operation with an identity element.
+///
+/// `combine` must be associative, and `identity` must leave any value
+/// unchanged on either side. It need not be commutative: range queries
+/// combine values strictly in index order. Implementors are marker types,
+/// so one value type can be summarized several ways.
+pub trait Monoid {
+    /// The type of the values being combined.
+    type Value: Clone;
+
+    /// Returns the identity element.
+    fn identity() -> Self::Value;
+
+    /// Combine two values, `a` on the left.
+    fn combine(a: &Self::Value, b: &Self::Value) -> Self::Value;
+}
+
+/// Addition, with identity zero.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sum<T>(PhantomData<T>);
+
+/// Minimum, with identity the type's largest value.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Min<T>(PhantomData<T>);
+
+/// Maximum, with identity the type's smallest value.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Max<T>(PhantomData<T>);
+
+macro_rules! impl_monoids {
+    ($($t:ty: $zero:expr, $min:expr, $max:expr;)*) => {
+        $(
+            impl Monoid for Sum<$t> {
+                type Value = $t;
+                fn identity() -> $t {
+                    $zero
+                }
+                fn combine(a: &$t, b: &$t) -> $t {
+                    a + b
+                }
+            }
+
+            impl Monoid for Min<$t> {
+                type Value = $t;
+                fn identity() -> $t {
+                    $max
+                }
+                fn combine(a: &$t, b: &$t) -> $t {
+                    (*a).min(*b)
+                }
+            }
+
+            impl Monoid for Max<$t> {
+                type Value = $t;
+                fn identity() -> $t {
+                    $min
+                }
+                fn combine(a: &$t, b: &$t) -> $t {
+                    (*a).max(*b)
+                }
+            }
+        )*
+    };
+}
+
+impl_monoids! {
+    i32: 0, i32::MIN, i32::MAX;
+    i64: 0, i64::MIN, i64::MAX;
+    u32: 0, u32::MIN, u32::MAX;
+    u64: 0, u64::MIN, u64::MAX;
+    usize: 0, usize::MIN, usize::MAX;
+    f64: 0.0, f64::NEG_INFINITY, f64::INFINITY;
+}
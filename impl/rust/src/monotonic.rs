@@ -0,0 +1,217 @@
+//! Monotonic stacks and deques: nearest smaller or greater elements, and
+//! the minimum or maximum of a queue.
+
+use crate::binary_heap::HeapOrder;
+use std::collections::VecDeque;
+
+/// For each index of `v`, the nearest index visited before it, in the
+/// order of `indices`, whose element `beats` its own.
+///
+/// The stack holds the indices that could still be the answer for a
+/// later element: each one beats everything pushed after it. An index
+/// popped for failing to beat an element would fail for every later
+/// element too, since that element is in the way. Every index is pushed
+/// and popped at most once, so the whole pass takes O(n).
+fn nearest<T>(
+    v: &[T],
+    indices: impl Iterator<Item = usize>,
+    beats: impl Fn(&T, &T) -> bool,
+) -> Vec<Option<usize>> {
+    let mut result = vec![None; v.len()];
+    let mut stack: Vec<usize> = Vec::new();
+    for i in indices {
+        while let Some(&top) = stack.last() {
+            if beats(&v[top], &v[i]) {
+                break;
+            }
+            stack.pop();
+        }
+        result[i] = stack.last().copied();
+        stack.push(i);
+    }
+    result
+}
+
+/// Returns, for each index `i`, the largest `j < i` with `v[j] < v[i]`.
+pub fn previous_smaller<T: Ord>(v: &[T]) -> Vec<Option<usize>> {
+    nearest(v, 0..v.len(), |a, b| a < b)
+}
+
+/// Returns, for each index `i`, the smallest `j > i` with `v[j] < v[i]`.
+pub fn next_smaller<T: Ord>(v: &[T]) -> Vec<Option<usize>> {
+    nearest(v, (0..v.len()).rev(), |a, b| a < b)
+}
+
+/// Returns, for each index `i`, the largest `j < i` with `v[j] > v[i]`.
+pub fn previous_greater<T: Ord>(v: &[T]) -> Vec<Option<usize>> {
+    nearest(v, 0..v.len(), |a, b| a > b)
+}
+
+/// Returns, for each index `i`, the smallest `j > i` with `v[j] > v[i]`.
+pub fn next_greater<T: Ord>(v: &[T]) -> Vec<Option<usize>> {
+    nearest(v, (0..v.len()).rev(), |a, b| a > b)
+}
+
+/// A FIFO queue that reports its minimum or maximum in O(1).
+///
+/// Only the values that could still become the top are stored: a value
+/// with a newer one at least as good behind it will leave the queue first
+/// and can never be the top, so a push discards such values from the
+/// back. What is stored is then sorted, with the top at the front, and a
+/// pop only has to check whether the value leaving is that front one.
+/// Push is amortized O(1), since each value is discarded at most once.
+/// The values popped are not kept, so `pop_front` cannot return them.
+#[derive(Debug, Clone)]
+pub struct MonotonicDeque<T> {
+    /// The candidates, each with its position in the queue since creation.
+    candidates: VecDeque<(u64, T)>,
+    order: HeapOrder,
+    pushed: u64,
+    popped: u64,
+}
+
+impl<T: Ord> MonotonicDeque<T> {
+    /// Create a new empty MonotonicDeque reporting the minimum or the
+    /// maximum.
+    pub fn new(order: HeapOrder) -> Self {
+        Self {
+            candidates: VecDeque::new(),
+            order,
+            pushed: 0,
+            popped: 0,
+        }
+    }
+
+    /// Returns the number of values in the queue.
+    pub fn len(&self) -> usize {
+        (self.pushed - self.popped) as usize
+    }
+
+    /// Returns true if the queue is empty.
+    pub fn is_empty(&self) -> bool {
+        self.pushed == self.popped
+    }
+
+    /// Returns the minimum or maximum value in the queue, per its order.
+    pub fn top(&self) -> Option<&T> {
+        self.candidates.front().map(|(_, value)| value)
+    }
+
+    /// Add a value at the back of the queue.
+    pub fn push_back(&mut self, value: T) {
+        while let Some((_, last)) = self.candidates.back() {
+            let beaten = match self.order {
+                HeapOrder::Min => *last >= value,
+                HeapOrder::Max => *last <= value,
+            };
+            if !beaten {
+                break;
+            }
+            self.candidates.pop_back();
+        }
+        self.candidates.push_back((self.pushed, value));
+        self.pushed += 1;
+    }
+
+    /// Remove the oldest value from the queue, returning false if it was
+    /// empty.
+    pub fn pop_front(&mut self) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+        if self
+            .candidates
+            .front()
+            .is_some_and(|&(position, _)| position == self.popped)
+        {
+            self.candidates.pop_front();
+        }
+        self.popped += 1;
+        true
+    }
+
+    /// Remove every value.
+    pub fn clear(&mut self) {
+        self.candidates.clear();
+        self.popped = self.pushed;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nearest_elements() {
+        let v = [3, 1, 4, 1, 5, 9, 2, 6];
+        assert_eq!(
+            previous_smaller(&v),
+            [
+                None,
+                None,
+                Some(1),
+                None,
+                Some(3),
+                Some(4),
+                Some(3),
+                Some(6)
+            ]
+        );
+        assert_eq!(
+            next_smaller(&v),
+            [Some(1), None, Some(3), None, Some(6), Some(6), None, None]
+        );
+        assert_eq!(
+            previous_greater(&v),
+            [None, Some(0), None, Some(2), None, None, Some(5), Some(5)]
+        );
+        assert_eq!(
+            next_greater(&v),
+            [
+                Some(2),
+                Some(2),
+                Some(4),
+                Some(4),
+                Some(5),
+                None,
+                Some(7),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn test_deque_tracks_top() {
+        let mut min = MonotonicDeque::new(HeapOrder::Min);
+        let mut max = MonotonicDeque::new(HeapOrder::Max);
+        for x in [5, 2, 7, 2, 9] {
+            min.push_back(x);
+            max.push_back(x);
+        }
+        assert_eq!((min.top(), max.top(), min.len()), (Some(&2), Some(&9), 5));
+        // Leaving: 5, then the first 2; the second 2 is still the minimum.
+        min.pop_front();
+        min.pop_front();
+        assert_eq!(min.top(), Some(&2));
+        min.pop_front();
+        min.pop_front();
+        assert_eq!(min.top(), Some(&9));
+        assert!(min.pop_front());
+        assert!(!min.pop_front());
+        assert_eq!(min.top(), None);
+    }
+
+    #[test]
+    fn test_clear_keeps_positions() {
+        let mut min = MonotonicDeque::new(HeapOrder::Min);
+        min.push_back(1);
+        min.push_back(2);
+        min.clear();
+        assert!(min.is_empty());
+        min.push_back(4);
+        min.push_back(3);
+        assert_eq!(min.top(), Some(&3));
+        min.pop_front();
+        assert_eq!((min.top(), min.len()), (Some(&3), 1));
+    }
+}
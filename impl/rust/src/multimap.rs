@@ -0,0 +1,169 @@
+//! One-to-many map built on the lab hash map.
+
+use crate::hashmap::HashMap;
+use std::hash::Hash;
+
+/// A map from each key to one or more values.
+///
+/// Values for a key keep their insertion order. A key is present exactly
+/// while it has at least one value: removing its last value removes the key,
+/// so [`key_count`](Self::key_count) never counts empty groups and `len`
+/// is always the total number of values.
+#[derive(Debug, Clone)]
+pub struct MultiMap<K, V> {
+    map: HashMap<K, Vec<V>>,
+    len: usize,
+}
+
+impl<K, V> Default for MultiMap<K, V>
+where
+    K: Hash + Eq,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> MultiMap<K, V>
+where
+    K: Hash + Eq,
+{
+    /// Create a new empty MultiMap.
+    pub fn new() -> Self {
+        Self {
+            map: HashMap::new(),
+            len: 0,
+        }
+    }
+
+    /// Returns the total number of values across all keys.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the map contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of distinct keys.
+    pub fn key_count(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Append a value to the key's values.
+    pub fn insert(&mut self, key: K, value: V) {
+        match self.map.get_mut(&key) {
+            Some(values) => values.push(value),
+            None => {
+                self.map.insert(key, vec![value]);
+            }
+        }
+        self.len += 1;
+    }
+
+    /// Returns the key's values in insertion order, or an empty slice.
+    pub fn get_all(&self, key: &K) -> &[V] {
+        self.map.get(key).map_or(&[], Vec::as_slice)
+    }
+
+    /// Check if the map contains at least one value for the key.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.map.contains_key(key)
+    }
+
+    /// Remove the first value equal to `value` under the key.
+    ///
+    /// The remaining values keep their order. Returns the removed value, or
+    /// None if the key had no such value.
+    pub fn remove_one(&mut self, key: &K, value: &V) -> Option<V>
+    where
+        V: PartialEq,
+    {
+        let values = self.map.get_mut(key)?;
+        let position = values.iter().position(|v| v == value)?;
+        let removed = values.remove(position);
+        if values.is_empty() {
+            self.map.remove(key);
+        }
+        self.len -= 1;
+        Some(removed)
+    }
+
+    /// Remove the key and all of its values.
+    ///
+    /// Returns the removed values in insertion order, empty if the key was
+    /// absent.
+    pub fn remove_all(&mut self, key: &K) -> Vec<V> {
+        let values = self.map.remove(key).unwrap_or_default();
+        self.len -= values.len();
+        values
+    }
+
+    /// Clear all keys and values.
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.len = 0;
+    }
+
+    /// Iterate over all key-value pairs, grouped by key.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.map
+            .iter()
+            .flat_map(|(key, values)| values.iter().map(move |value| (key, value)))
+    }
+
+    /// Iterate over the distinct keys.
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.map.keys()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_and_get_all() {
+        let mut map = MultiMap::new();
+        map.insert("a", 1);
+        map.insert("b", 2);
+        map.insert("a", 3);
+        map.insert("a", 1);
+        assert_eq!(map.get_all(&"a"), [1, 3, 1]);
+        assert_eq!(map.get_all(&"b"), [2]);
+        assert!(map.get_all(&"c").is_empty());
+        assert_eq!(map.len(), 4);
+        assert_eq!(map.key_count(), 2);
+        assert_eq!(map.iter().count(), 4);
+    }
+
+    #[test]
+    fn test_remove_one_drops_empty_keys() {
+        let mut map = MultiMap::new();
+        map.insert(1, "x");
+        map.insert(1, "y");
+        map.insert(1, "x");
+        assert_eq!(map.remove_one(&1, &"x"), Some("x"));
+        assert_eq!(map.get_all(&1), ["y", "x"]);
+        assert_eq!(map.remove_one(&1, &"z"), None);
+        assert_eq!(map.remove_one(&2, &"x"), None);
+        map.remove_one(&1, &"y");
+        map.remove_one(&1, &"x");
+        assert!(!map.contains_key(&1));
+        assert_eq!(map.key_count(), 0);
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn test_remove_all() {
+        let mut map = MultiMap::new();
+        for i in 0..10 {
+            map.insert(i % 3, i);
+        }
+        assert_eq!(map.remove_all(&0), [0, 3, 6, 9]);
+        assert!(map.remove_all(&0).is_empty());
+        assert_eq!(map.len(), 6);
+        assert_eq!(map.keys().count(), 2);
+    }
+}
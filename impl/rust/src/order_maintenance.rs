@@ -0,0 +1,327 @@
+//! Order maintenance: a linked list that answers "which comes first" in
+//! O(1) by keeping an integer label on every element.
+
+use crate::slot_map::{SlotKey, SlotMap};
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Labels are below 2^63, so a block of labels is at most half of `u64`.
+const LABEL_BITS: u32 = 63;
+const LABEL_SPACE: u64 = 1 << LABEL_BITS;
+/// A block of 2^i labels may hold fewer than `(2 / T)^i` elements before it
+/// is relabeled; T between 1 and 2 trades relabeling work for capacity.
+/// At 1.3 the whole label space holds over 10^11 elements.
+const DENSITY_BASE: f64 = 1.3;
+
+/// Refers to an element of an [`OrderList`], going stale once it is
+/// removed like a [`SlotKey`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OrderKey(SlotKey);
+
+#[derive(Debug, Clone)]
+struct Item<T> {
+    value: T,
+    label: u64,
+    prev: Option<SlotKey>,
+    next: Option<SlotKey>,
+}
+
+/// A sequence that supports inserting next to any element, removing any
+/// element, and comparing the positions of two elements in O(1).
+///
+/// Elements are a doubly linked list in a [`SlotMap`], and each carries a
+/// label that increases along the list, so comparing positions is
+/// comparing labels. A new element takes the label halfway between its
+/// neighbours'. When they are adjacent there is no room, and the labels
+/// around the spot are spread out again: the smallest aligned block of 2^i
+/// labels around it that holds fewer than `(2 / 1.3)^i` elements is found
+/// by walking outward, and its elements are relabeled evenly across it.
+/// Larger blocks must be sparser, which makes an insertion's relabeling
+/// cost O(log n) amortized (Bender et al., "Two simplified algorithms for
+/// maintaining order in a list"). Removal just unlinks the element.
+#[derive(Clone)]
+pub struct OrderList<T> {
+    items: SlotMap<Item<T>>,
+    head: Option<SlotKey>,
+    tail: Option<SlotKey>,
+    relabels: u64,
+}
+
+impl<T: fmt::Debug> fmt::Debug for OrderList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.iter().map(|(_, value)| value))
+            .finish()
+    }
+}
+
+impl<T> Default for OrderList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> OrderList<T> {
+    /// Create a new empty OrderList.
+    pub fn new() -> Self {
+        Self {
+            items: SlotMap::new(),
+            head: None,
+            tail: None,
+            relabels: 0,
+        }
+    }
+
+    /// Returns the number of elements in the list.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns true if the list contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Returns the total number of labels rewritten by relabeling so far,
+    /// the cost measure of list-labeling experiments.
+    pub fn relabel_count(&self) -> u64 {
+        self.relabels
+    }
+
+    fn item(&self, key: SlotKey) -> &Item<T> {
+        self.items.get(key).expect("linked keys are live")
+    }
+
+    fn item_mut(&mut self, key: SlotKey) -> &mut Item<T> {
+        self.items.get_mut(key).expect("linked keys are live")
+    }
+
+    /// Insert `value` at the front of the list, returning its key.
+    pub fn push_front(&mut self, value: T) -> OrderKey {
+        self.insert_between(None, self.head, value)
+    }
+
+    /// Insert `value` at the back of the list, returning its key.
+    pub fn push_back(&mut self, value: T) -> OrderKey {
+        self.insert_between(self.tail, None, value)
+    }
+
+    /// Insert `value` right after the element at `key`, returning its key,
+    /// or `None` if `key` is stale.
+    pub fn insert_after(&mut self, key: OrderKey, value: T) -> Option<OrderKey> {
+        let next = self.items.get(key.0)?.next;
+        Some(self.insert_between(Some(key.0), next, value))
+    }
+
+    /// Insert `value` right before the element at `key`, returning its
+    /// key, or `None` if `key` is stale.
+    pub fn insert_before(&mut self, key: OrderKey, value: T) -> Option<OrderKey> {
+        let prev = self.items.get(key.0)?.prev;
+        Some(self.insert_between(prev, Some(key.0), value))
+    }
+
+    /// Link a new element between two neighbours and label it.
+    fn insert_between(
+        &mut self,
+        prev: Option<SlotKey>,
+        next: Option<SlotKey>,
+        value: T,
+    ) -> OrderKey {
+        // Free labels are those in low..high.
+        let low = prev.map_or(0, |key| self.item(key).label + 1);
+        let high = next.map_or(LABEL_SPACE, |key| self.item(key).label);
+        let key = self.items.insert(Item {
+            value,
+            label: low + (high - low) / 2,
+            prev,
+            next,
+        });
+        match prev {
+            Some(prev) => self.item_mut(prev).next = Some(key),
+            None => self.head = Some(key),
+        }
+        match next {
+            Some(next) => self.item_mut(next).prev = Some(key),
+            None => self.tail = Some(key),
+        }
+        if low == high {
+            self.relabel_around(key, low.min(LABEL_SPACE - 1));
+        }
+        OrderKey(key)
+    }
+
+    /// Spread out the labels of the smallest sparse enough block around
+    /// the new element at `key`, whose label should be `target`.
+    fn relabel_around(&mut self, key: SlotKey, target: u64) {
+        // The block's elements run from `first` to `last`, the new one
+        // included; `count` of them.
+        let (mut first, mut last) = (key, key);
+        let mut count = 1u64;
+        for i in 1..=LABEL_BITS {
+            let size = 1u64 << i;
+            let base = target & !(size - 1);
+            while let Some(prev) = self.item(first).prev {
+                if self.item(prev).label < base {
+                    break;
+                }
+                first = prev;
+                count += 1;
+            }
+            while let Some(next) = self.item(last).next {
+                if self.item(next).label >= base + size {
+                    break;
+                }
+                last = next;
+                count += 1;
+            }
+            if (count as f64) < (2.0 / DENSITY_BASE).powi(i as i32) {
+                let gap = size / count;
+                let mut current = Some(first);
+                for k in 0..count {
+                    let at = current.expect("the block's elements are linked");
+                    let item = self.item_mut(at);
+                    item.label = base + k * gap;
+                    current = item.next;
+                }
+                self.relabels += count;
+                return;
+            }
+        }
+        panic!("too many elements for the label space");
+    }
+
+    /// Remove the element at `key`, returning its value if the key was live.
+    ///
+    /// The "delete" of order maintenance: the neighbours are linked to each
+    /// other and no labels change.
+    pub fn remove(&mut self, key: OrderKey) -> Option<T> {
+        let item = self.items.remove(key.0)?;
+        match item.prev {
+            Some(prev) => self.item_mut(prev).next = item.next,
+            None => self.head = item.next,
+        }
+        match item.next {
+            Some(next) => self.item_mut(next).prev = item.prev,
+            None => self.tail = item.prev,
+        }
+        Some(item.value)
+    }
+
+    /// Compare the positions of two elements in O(1): `Less` if `a` comes
+    /// before `b`. Returns `None` if either key is stale.
+    pub fn order(&self, a: OrderKey, b: OrderKey) -> Option<Ordering> {
+        let (a, b) = (self.items.get(a.0)?, self.items.get(b.0)?);
+        Some(a.label.cmp(&b.label))
+    }
+
+    /// Get a reference to the value at `key`.
+    pub fn get(&self, key: OrderKey) -> Option<&T> {
+        self.items.get(key.0).map(|item| &item.value)
+    }
+
+    /// Get a mutable reference to the value at `key`.
+    pub fn get_mut(&mut self, key: OrderKey) -> Option<&mut T> {
+        self.items.get_mut(key.0).map(|item| &mut item.value)
+    }
+
+    /// Check if `key` refers to an element of the list.
+    pub fn contains_key(&self, key: OrderKey) -> bool {
+        self.items.contains_key(key.0)
+    }
+
+    /// Returns the key of the first element.
+    pub fn first(&self) -> Option<OrderKey> {
+        self.head.map(OrderKey)
+    }
+
+    /// Returns the key of the last element.
+    pub fn last(&self) -> Option<OrderKey> {
+        self.tail.map(OrderKey)
+    }
+
+    /// Returns the key of the element after the one at `key`.
+    pub fn next(&self, key: OrderKey) -> Option<OrderKey> {
+        self.items.get(key.0)?.next.map(OrderKey)
+    }
+
+    /// Returns the key of the element before the one at `key`.
+    pub fn prev(&self, key: OrderKey) -> Option<OrderKey> {
+        self.items.get(key.0)?.prev.map(OrderKey)
+    }
+
+    /// Iterate over the elements in list order.
+    pub fn iter(&self) -> impl Iterator<Item = (OrderKey, &T)> {
+        let mut current = self.head;
+        std::iter::from_fn(move || {
+            let key = current?;
+            let item = self.item(key);
+            current = item.next;
+            Some((OrderKey(key), &item.value))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_and_order() {
+        let mut list = OrderList::new();
+        let b = list.push_back('b');
+        let d = list.push_back('d');
+        let a = list.push_front('a');
+        let c = list.insert_after(b, 'c').unwrap();
+        assert_eq!(
+            list.insert_before(a, '_').map(|k| list.get(k)),
+            Some(Some(&'_'))
+        );
+        let values: String = list.iter().map(|(_, &v)| v).collect();
+        assert_eq!(values, "_abcd");
+        assert_eq!(list.order(a, d), Some(Ordering::Less));
+        assert_eq!(list.order(d, c), Some(Ordering::Greater));
+        assert_eq!(list.order(c, c), Some(Ordering::Equal));
+        assert_eq!(list.next(b), Some(c));
+        assert_eq!(list.prev(b), Some(a));
+    }
+
+    #[test]
+    fn test_relabels_when_labels_run_out() {
+        // Always inserting right after the first element halves the same
+        // gap until it is gone, then forces a relabel.
+        let mut list = OrderList::new();
+        let first = list.push_back(0);
+        let mut keys = vec![first];
+        for value in 1..1_000 {
+            keys.push(list.insert_after(first, value).unwrap());
+        }
+        assert!(list.relabel_count() > 0);
+        let values: Vec<i32> = list.iter().map(|(_, &v)| v).collect();
+        let mut expected: Vec<i32> = (1..1_000).rev().collect();
+        expected.insert(0, 0);
+        assert_eq!(values, expected);
+        // Later insertions come earlier in the list.
+        for pair in keys[1..].windows(2) {
+            assert_eq!(list.order(pair[1], pair[0]), Some(Ordering::Less));
+        }
+    }
+
+    #[test]
+    fn test_remove_and_stale_keys() {
+        let mut list = OrderList::new();
+        let a = list.push_back(1);
+        let b = list.push_back(2);
+        let c = list.push_back(3);
+        assert_eq!(list.remove(b), Some(2));
+        assert_eq!(list.remove(b), None);
+        assert_eq!(list.order(a, b), None);
+        assert_eq!(list.insert_after(b, 9), None);
+        assert_eq!(list.next(a), Some(c));
+        assert_eq!(list.remove(a), Some(1));
+        assert_eq!(list.remove(c), Some(3));
+        assert!(list.is_empty());
+        assert_eq!((list.first(), list.last()), (None, None));
+        let d = list.push_front(4);
+        assert_eq!(list.first(), Some(d));
+    }
+}
@@ -0,0 +1,519 @@
+//! Order-statistics tree: an ordered map with select and rank by position.
+
+use crate::btree::{before_start, check_range, past_end};
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::RangeBounds;
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+#[derive(Debug, Clone)]
+struct Node<K, V> {
+    key: K,
+    value: V,
+    /// Height of the subtree rooted here; a leaf has height 1.
+    height: usize,
+    /// Number of entries in the subtree rooted here.
+    size: usize,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+fn height<K, V>(link: &Link<K, V>) -> usize {
+    link.as_ref().map_or(0, |node| node.height)
+}
+
+fn size<K, V>(link: &Link<K, V>) -> usize {
+    link.as_ref().map_or(0, |node| node.size)
+}
+
+impl<K, V> Node<K, V> {
+    fn leaf(key: K, value: V) -> Box<Self> {
+        Box::new(Self {
+            key,
+            value,
+            height: 1,
+            size: 1,
+            left: None,
+            right: None,
+        })
+    }
+
+    fn update(&mut self) {
+        self.height = 1 + height(&self.left).max(height(&self.right));
+        self.size = 1 + size(&self.left) + size(&self.right);
+    }
+
+    /// Left height minus right height.
+    fn balance_factor(&self) -> isize {
+        height(&self.left) as isize - height(&self.right) as isize
+    }
+}
+
+/// Lift the left child above `node`.
+fn rotate_right<K, V>(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    let mut pivot = node.left.take().expect("rotate_right needs a left child");
+    node.left = pivot.right.take();
+    node.update();
+    pivot.right = Some(node);
+    pivot.update();
+    pivot
+}
+
+/// Lift the right child above `node`.
+fn rotate_left<K, V>(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    let mut pivot = node.right.take().expect("rotate_left needs a right child");
+    node.right = pivot.left.take();
+    node.update();
+    pivot.left = Some(node);
+    pivot.update();
+    pivot
+}
+
+/// Restore the balance of a node whose subtrees differ in height by at most
+/// two, refreshing its size on the way.
+fn rebalance<K, V>(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    node.update();
+    match node.balance_factor() {
+        2 => {
+            let left = node.left.take().expect("left-heavy node has a left child");
+            node.left = Some(if left.balance_factor() < 0 {
+                rotate_left(left)
+            } else {
+                left
+            });
+            rotate_right(node)
+        }
+        -2 => {
+            let right = node
+                .right
+                .take()
+                .expect("right-heavy node has a right child");
+            node.right = Some(if right.balance_factor() > 0 {
+                rotate_right(right)
+            } else {
+                right
+            });
+            rotate_left(node)
+        }
+        _ => node,
+    }
+}
+
+fn insert<K: Ord, V>(link: Link<K, V>, key: K, value: V) -> (Box<Node<K, V>>, Option<V>) {
+    let Some(mut node) = link else {
+        return (Node::leaf(key, value), None);
+    };
+    let old = match key.cmp(&node.key) {
+        Ordering::Less => {
+            let (left, old) = insert(node.left.take(), key, value);
+            node.left = Some(left);
+            old
+        }
+        Ordering::Greater => {
+            let (right, old) = insert(node.right.take(), key, value);
+            node.right = Some(right);
+            old
+        }
+        Ordering::Equal => {
+            let old = std::mem::replace(&mut node.value, value);
+            return (node, Some(old));
+        }
+    };
+    (rebalance(node), old)
+}
+
+/// Detach the minimum node of a subtree, returning it and the rest.
+fn remove_min<K, V>(mut node: Box<Node<K, V>>) -> (Box<Node<K, V>>, Link<K, V>) {
+    match node.left.take() {
+        None => {
+            let rest = node.right.take();
+            (node, rest)
+        }
+        Some(left) => {
+            let (min, left) = remove_min(left);
+            node.left = left;
+            (min, Some(rebalance(node)))
+        }
+    }
+}
+
+/// Unlink `node` from its subtrees, returning them joined.
+fn unlink<K, V>(mut node: Box<Node<K, V>>) -> (Link<K, V>, Box<Node<K, V>>) {
+    let rest = match node.right.take() {
+        None => node.left.take(),
+        Some(right) => {
+            let (mut successor, right) = remove_min(right);
+            successor.left = node.left.take();
+            successor.right = right;
+            Some(rebalance(successor))
+        }
+    };
+    (rest, node)
+}
+
+fn remove<K: Ord, V>(link: Link<K, V>, key: &K) -> (Link<K, V>, Option<(K, V)>) {
+    let Some(mut node) = link else {
+        return (None, None);
+    };
+    let removed = match key.cmp(&node.key) {
+        Ordering::Less => {
+            let (left, removed) = remove(node.left.take(), key);
+            node.left = left;
+            removed
+        }
+        Ordering::Greater => {
+            let (right, removed) = remove(node.right.take(), key);
+            node.right = right;
+            removed
+        }
+        Ordering::Equal => {
+            let (rest, node) = unlink(node);
+            return (rest, Some((node.key, node.value)));
+        }
+    };
+    (Some(rebalance(node)), removed)
+}
+
+/// Remove the entry at in-order position `index`, which must be in range.
+fn remove_nth<K, V>(mut node: Box<Node<K, V>>, index: usize) -> (Link<K, V>, (K, V)) {
+    let left_size = size(&node.left);
+    let removed = match index.cmp(&left_size) {
+        Ordering::Less => {
+            let left = node.left.take().expect("index within the left subtree");
+            let (left, removed) = remove_nth(left, index);
+            node.left = left;
+            removed
+        }
+        Ordering::Greater => {
+            let right = node.right.take().expect("index within the right subtree");
+            let (right, removed) = remove_nth(right, index - left_size - 1);
+            node.right = right;
+            removed
+        }
+        Ordering::Equal => {
+            let (rest, node) = unlink(node);
+            return (rest, (node.key, node.value));
+        }
+    };
+    (Some(rebalance(node)), removed)
+}
+
+/// An ordered map that also finds entries by their position in key order.
+///
+/// An AVL tree whose nodes each record the size of their subtree as well as
+/// its height. Sizes are refreshed along with heights on the way back up
+/// from every insert, remove and rotation, so they cost nothing extra
+/// asymptotically, and they let [`select`](Self::select) descend straight to
+/// the `k`-th smallest key and [`rank`](Self::rank) count the keys below a
+/// given one by summing left-subtree sizes along a single search path. Both
+/// are O(log n), where a plain ordered map has to iterate. Keys are unique,
+/// so a multiset of samples, such as a sliding window for running
+/// percentiles, keys each sample by its value and a sequence number.
+#[derive(Clone)]
+pub struct OrderStatisticsMap<K, V> {
+    root: Link<K, V>,
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for OrderStatisticsMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V> Default for OrderStatisticsMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> OrderStatisticsMap<K, V> {
+    /// Create a new empty OrderStatisticsMap.
+    pub fn new() -> Self {
+        Self { root: None }
+    }
+
+    /// Returns the number of elements in the map.
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    /// Returns true if the map contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Returns the number of levels in the tree.
+    pub fn height(&self) -> usize {
+        height(&self.root)
+    }
+
+    /// Clear all entries from the map.
+    pub fn clear(&mut self) {
+        self.root = None;
+    }
+
+    /// Returns the entry with the `k`-th smallest key, counting from 0, or
+    /// `None` if the map has no more than `k` entries.
+    pub fn select(&self, k: usize) -> Option<(&K, &V)> {
+        let mut k = k;
+        let mut link = &self.root;
+        while let Some(node) = link {
+            let left_size = size(&node.left);
+            link = match k.cmp(&left_size) {
+                Ordering::Less => &node.left,
+                Ordering::Greater => {
+                    k -= left_size + 1;
+                    &node.right
+                }
+                Ordering::Equal => return Some((&node.key, &node.value)),
+            };
+        }
+        None
+    }
+
+    /// Remove the entry with the `k`-th smallest key, counting from 0.
+    ///
+    /// Returns the removed entry, or `None` if the map has no more than `k`
+    /// entries.
+    pub fn remove_nth(&mut self, k: usize) -> Option<(K, V)> {
+        if k >= self.len() {
+            return None;
+        }
+        let root = self.root.take().expect("non-empty map has a root");
+        let (root, removed) = remove_nth(root, k);
+        self.root = root;
+        Some(removed)
+    }
+}
+
+impl<K: Ord, V> OrderStatisticsMap<K, V> {
+    /// Insert a key-value pair into the map.
+    ///
+    /// Returns the previous value if the key existed.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let (root, old) = insert(self.root.take(), key, value);
+        self.root = Some(root);
+        old
+    }
+
+    /// Get a reference to the value associated with the key.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let mut link = &self.root;
+        while let Some(node) = link {
+            link = match key.cmp(&node.key) {
+                Ordering::Less => &node.left,
+                Ordering::Greater => &node.right,
+                Ordering::Equal => return Some(&node.value),
+            };
+        }
+        None
+    }
+
+    /// Check if the map contains the given key.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Remove a key-value pair from the map.
+    ///
+    /// Returns the removed value if the key existed.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let (root, removed) = remove(self.root.take(), key);
+        self.root = root;
+        removed.map(|(_, value)| value)
+    }
+
+    /// Returns the number of keys less than `key`, which is its position
+    /// in key order if it is present.
+    pub fn rank(&self, key: &K) -> usize {
+        let mut rank = 0;
+        let mut link = &self.root;
+        while let Some(node) = link {
+            link = match key.cmp(&node.key) {
+                Ordering::Less => &node.left,
+                Ordering::Greater => {
+                    rank += size(&node.left) + 1;
+                    &node.right
+                }
+                Ordering::Equal => return rank + size(&node.left),
+            };
+        }
+        rank
+    }
+
+    /// Iterate over all key-value pairs in key order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.range(..)
+    }
+
+    /// Iterate over the key-value pairs whose keys fall in `range`, in key
+    /// order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range starts after it ends, or starts and ends at the
+    /// same excluded key.
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Iter<'_, K, V> {
+        check_range(&range);
+        let mut iter = Iter {
+            stack: Vec::new(),
+            end: None,
+        };
+        let mut link = &self.root;
+        while let Some(node) = link {
+            if before_start(&node.key, range.start_bound()) {
+                link = &node.right;
+            } else {
+                iter.stack.push(node);
+                link = &node.left;
+            }
+        }
+        let mut link = &self.root;
+        while let Some(node) = link {
+            if past_end(&node.key, range.end_bound()) {
+                iter.end = Some(node);
+                link = &node.left;
+            } else {
+                link = &node.right;
+            }
+        }
+        iter
+    }
+
+    /// Check the tree invariants, panicking on the first violation.
+    ///
+    /// Checks that keys are strictly increasing in order, that every stored
+    /// height and size is correct, and that no node's subtrees differ in
+    /// height by more than one.
+    pub fn debug_validate(&self) {
+        /// Returns the height of the subtree.
+        fn walk<K: Ord, V>(link: &Link<K, V>, lower: Option<&K>, upper: Option<&K>) -> usize {
+            let Some(node) = link else {
+                return 0;
+            };
+            assert!(
+                lower.map_or(true, |lower| node.key > *lower),
+                "keys out of order"
+            );
+            assert!(
+                upper.map_or(true, |upper| node.key < *upper),
+                "keys out of order"
+            );
+            let left_height = walk(&node.left, lower, Some(&node.key));
+            let right_height = walk(&node.right, Some(&node.key), upper);
+            assert_eq!(
+                node.height,
+                1 + left_height.max(right_height),
+                "stale height"
+            );
+            assert_eq!(
+                node.size,
+                1 + size(&node.left) + size(&node.right),
+                "stale size"
+            );
+            assert!(
+                left_height.abs_diff(right_height) <= 1,
+                "balance factor out of range"
+            );
+            node.height
+        }
+
+        walk(&self.root, None, None);
+    }
+}
+
+/// An iterator over a key range of an [`OrderStatisticsMap`], in key order.
+pub struct Iter<'a, K, V> {
+    /// Nodes still to visit whose left subtrees are already handled; the
+    /// next entry is on top.
+    stack: Vec<&'a Node<K, V>>,
+    /// The first node past the range, if there is one.
+    end: Option<&'a Node<K, V>>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        if self.end.is_some_and(|end| std::ptr::eq(end, node)) {
+            self.stack.clear();
+            return None;
+        }
+        let mut link = &node.right;
+        while let Some(child) = link {
+            self.stack.push(child);
+            link = &child.left;
+        }
+        Some((&node.key, &node.value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_and_rank() {
+        let mut map = OrderStatisticsMap::new();
+        for i in (0..1000).rev() {
+            map.insert(i * 2, i);
+        }
+        map.debug_validate();
+        assert_eq!(map.len(), 1000);
+        assert_eq!(map.select(0), Some((&0, &0)));
+        assert_eq!(map.select(500), Some((&1000, &500)));
+        assert_eq!(map.select(999), Some((&1998, &999)));
+        assert_eq!(map.select(1000), None);
+        assert_eq!(map.rank(&1000), 500);
+        assert_eq!(map.rank(&1001), 501);
+        assert_eq!(map.rank(&-5), 0);
+        assert_eq!(map.rank(&5000), 1000);
+        for k in 0..1000 {
+            let (key, _) = map.select(k).unwrap();
+            assert_eq!(map.rank(key), k);
+        }
+    }
+
+    #[test]
+    fn test_remove_keeps_sizes() {
+        let mut map = OrderStatisticsMap::new();
+        for i in 0..300 {
+            map.insert(i, ());
+        }
+        for i in (0..300).filter(|i| i % 3 != 0) {
+            assert_eq!(map.remove(&i), Some(()));
+            map.debug_validate();
+        }
+        assert_eq!(map.remove(&1), None);
+        assert_eq!(map.len(), 100);
+        assert_eq!(map.select(10), Some((&30, &())));
+        assert_eq!(map.rank(&31), 11);
+        assert_eq!(map.remove_nth(0), Some((0, ())));
+        assert_eq!(map.remove_nth(98), Some((297, ())));
+        assert_eq!(map.remove_nth(98), None);
+        map.debug_validate();
+        assert!(map.range(10..=20).map(|(k, _)| *k).eq([12, 15, 18]));
+    }
+
+    #[test]
+    fn test_sliding_window_median() {
+        // Duplicate samples stay distinct by their position in the stream.
+        let samples = [5, 1, 4, 4, 9, 2, 2, 7, 3, 8];
+        let window = 5;
+        let mut tree = OrderStatisticsMap::new();
+        let mut medians = Vec::new();
+        for (i, &sample) in samples.iter().enumerate() {
+            tree.insert((sample, i), ());
+            if i >= window {
+                tree.remove(&(samples[i - window], i - window));
+            }
+            if i + 1 >= window {
+                let ((median, _), _) = tree.select(window / 2).unwrap();
+                medians.push(*median);
+            }
+        }
+        assert_eq!(medians, [4, 4, 4, 4, 3, 3]);
+    }
+}
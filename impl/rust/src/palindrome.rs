@@ -0,0 +1,91 @@
+//! Palindromic substrings via Manacher's algorithm.
+//!
+//! A byte string of length n has 2n + 1 palindrome centers: one on each
+//! byte, for odd lengths, and one on each boundary between bytes, for even
+//! lengths. Center `c` sits on byte `(c - 1) / 2` when `c` is odd and on the
+//! boundary before byte `c / 2` when `c` is even.
+
+use std::ops::Range;
+
+/// The length of the longest palindrome around each of the 2n + 1 centers
+/// of `s`, in O(n).
+///
+/// Entry `c` is the length `len` of the palindrome `s[(c - len) / 2..(c +
+/// len) / 2]`. Manacher's algorithm keeps the palindrome reaching furthest
+/// right; a center inside it starts from the length at its mirror image,
+/// so each byte is compared only when it extends that reach.
+pub fn manacher(s: &[u8]) -> Vec<usize> {
+    let centers = 2 * s.len() + 1;
+    let mut lengths = vec![0; centers];
+    // The palindrome around `center` reaches center `right`.
+    let (mut center, mut right) = (0, 0);
+    for c in 0..centers {
+        let mut len = if c < right {
+            lengths[2 * center - c].min(right - c)
+        } else {
+            0
+        };
+        // Step one center outwards on each side: boundaries always agree,
+        // bytes must be equal.
+        while len < c
+            && c + len + 1 < centers
+            && ((c + len + 1) % 2 == 0 || s[(c - len - 1) / 2] == s[(c + len + 1) / 2])
+        {
+            len += 1;
+        }
+        lengths[c] = len;
+        if c + len > right {
+            center = c;
+            right = c + len;
+        }
+    }
+    lengths
+}
+
+/// Returns the range of the leftmost longest palindromic substring of `s`.
+///
+/// The range is empty only when `s` is.
+pub fn longest_palindrome(s: &[u8]) -> Range<usize> {
+    let mut best = 0..0;
+    for (c, len) in manacher(s).into_iter().enumerate() {
+        if len > best.len() {
+            best = (c - len) / 2..(c + len) / 2;
+        }
+    }
+    best
+}
+
+/// Returns the number of non-empty palindromic substrings of `s`, counting
+/// each occurrence separately.
+pub fn count_palindromes(s: &[u8]) -> usize {
+    // A center of longest length `len` holds the palindromes of lengths
+    // len, len - 2, ... down to 1 or 2.
+    manacher(s).into_iter().map(|len| (len + 1) / 2).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manacher_lengths() {
+        // Centers:       | a | b | a | a | b |
+        assert_eq!(manacher(b"abaab"), [0, 1, 0, 3, 0, 1, 4, 1, 0, 1, 0]);
+        assert_eq!(manacher(b""), [0]);
+    }
+
+    #[test]
+    fn test_longest_palindrome() {
+        assert_eq!(longest_palindrome(b"forgeeksskeegfor"), 3..13);
+        assert_eq!(longest_palindrome(b"abacdfgdcaba"), 0..3);
+        assert_eq!(longest_palindrome(b"abcd"), 0..1);
+        assert_eq!(longest_palindrome(b""), 0..0);
+    }
+
+    #[test]
+    fn test_count_palindromes() {
+        assert_eq!(count_palindromes(b"aaa"), 6);
+        assert_eq!(count_palindromes(b"abc"), 3);
+        assert_eq!(count_palindromes(b""), 0);
+    }
+}
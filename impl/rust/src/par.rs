@@ -0,0 +1,222 @@
+//! Rayon parallel iteration and bulk construction for `OpenAddressingMap`.
+//!
+//! Enabled by the `rayon` feature.
+
+use crate::hashmap::OpenAddressingMap;
+use crate::probe::ProbeStrategy;
+use rayon::prelude::*;
+use std::hash::{BuildHasher, Hash};
+
+/// Shards per thread, so a thread that finishes early can take another.
+const SHARDS_PER_THREAD: usize = 4;
+
+/// The fewest slots worth a shard of their own.
+const MIN_SHARD_SLOTS: usize = 4096;
+
+impl<K, V, S, P> OpenAddressingMap<K, V, S, P>
+where
+    K: Hash + Eq + Sync,
+    V: Sync,
+    S: BuildHasher,
+    P: ProbeStrategy,
+    Self: Sync,
+{
+    /// Iterate over all key-value pairs in parallel.
+    ///
+    /// The slot array is split across threads, each skipping the empty and
+    /// deleted slots of its share. Unavailable with the `metrics` feature,
+    /// which makes the map `!Sync`.
+    pub fn par_iter(&self) -> impl ParallelIterator<Item = (&K, &V)> {
+        (0..self.capacity())
+            .into_par_iter()
+            .filter_map(move |index| self.slot(index).map(|(_, key, value)| (key, value)))
+    }
+}
+
+impl<K, V, S, P> ParallelExtend<(K, V)> for OpenAddressingMap<K, V, S, P>
+where
+    K: Hash + Eq + Send,
+    V: Send,
+    S: BuildHasher + Sync,
+    P: ProbeStrategy,
+{
+    /// Hash the pairs in parallel, then place them shard by shard.
+    ///
+    /// After reserving room for every pair, the slot array is cut into runs
+    /// of consecutive slots, several per thread, and each pair goes to the
+    /// shard owning its home slot, keeping input order within the shard.
+    /// Each rayon job then probes, compares and places its shard's pairs
+    /// inside its own run, so later pairs still win over earlier ones with
+    /// the same key. A pair whose probe sequence leaves the run before
+    /// finding a free slot is handed back, and the leftovers are merged in
+    /// afterwards with [`insert_batch`](OpenAddressingMap::insert_batch)'s
+    /// cached-hash path.
+    ///
+    /// Linear and quadratic probing take short steps and rarely leave a
+    /// run, so nearly all of the work is parallel. Double hashing jumps out
+    /// of the run at the first collision, which leaves about a sixth of a
+    /// fresh build to the final merge. A probe length limit, or a pool of
+    /// one thread, falls back to hashing in parallel and placing everything
+    /// on one thread.
+    fn par_extend<I>(&mut self, par_iter: I)
+    where
+        I: IntoParallelIterator<Item = (K, V)>,
+    {
+        let hash_builder = self.hasher();
+        let hashed: Vec<(u64, K, V)> = par_iter
+            .into_par_iter()
+            .map(|(key, value)| (hash_builder.hash_one(&key), key, value))
+            .collect();
+        self.reserve(hashed.len());
+        let capacity = self.capacity();
+        let shards = (rayon::current_num_threads() * SHARDS_PER_THREAD)
+            .next_power_of_two()
+            .min(capacity / MIN_SHARD_SLOTS);
+        if shards <= 1 || self.probe_length_limit().is_some() {
+            self.insert_hashed_batch(hashed);
+            return;
+        }
+
+        let len = capacity / shards;
+        let mask = capacity - 1;
+        let empty = || (0..shards).map(|_| Vec::new()).collect::<Vec<Vec<_>>>();
+        let partitioned = hashed
+            .into_par_iter()
+            .fold(empty, |mut parts, (hash, key, value)| {
+                parts[P::probe(hash, 0, mask) / len].push((hash, key, value));
+                parts
+            })
+            .reduce(empty, |mut left, right| {
+                for (part, rest) in left.iter_mut().zip(right) {
+                    part.extend(rest);
+                }
+                left
+            });
+        let (placed, deferred) = self
+            .slot_ranges(len)
+            .into_par_iter()
+            .zip(partitioned)
+            .map(|(mut range, part)| {
+                let deferred: Vec<_> = part
+                    .into_iter()
+                    .filter_map(|(hash, key, value)| range.insert(hash, key, value))
+                    .collect();
+                (range.placed(), deferred)
+            })
+            .reduce(
+                || (0, Vec::new()),
+                |(placed, mut deferred), (more, rest)| {
+                    deferred.extend(rest);
+                    (placed + more, deferred)
+                },
+            );
+        self.finish_slot_ranges(placed, deferred);
+    }
+}
+
+impl<K, V, S, P> FromParallelIterator<(K, V)> for OpenAddressingMap<K, V, S, P>
+where
+    K: Hash + Eq + Send,
+    V: Send,
+    S: BuildHasher + Default + Sync,
+    P: ProbeStrategy,
+{
+    fn from_par_iter<I>(par_iter: I) -> Self
+    where
+        I: IntoParallelIterator<Item = (K, V)>,
+    {
+        let mut map = Self::with_hasher(S::default());
+        map.par_extend(par_iter);
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hashmap::{DefaultHashBuilder, OpenAddressingMap};
+    use crate::probe::ProbeStrategy;
+    use crate::{DoubleHashMap, HashMap, QuadraticMap};
+    use rayon::prelude::*;
+
+    /// Extend `map` on a pool of four threads, which is enough for the
+    /// sharded path even on a single core, and check it against sequential
+    /// inserts.
+    fn check_sharded<P: ProbeStrategy>(mut map: OpenAddressingMap<u64, u64, DefaultHashBuilder, P>)
+    where
+        OpenAddressingMap<u64, u64, DefaultHashBuilder, P>: Send,
+    {
+        let mut expected = std::collections::HashMap::new();
+        for (key, value) in map.iter() {
+            expected.insert(*key, *value);
+        }
+        // Duplicates in the input, with later values to keep.
+        let pairs: Vec<(u64, u64)> = (0..60_000u64).map(|i| (i * 7 % 50_000, i)).collect();
+        for &(key, value) in &pairs {
+            expected.insert(key, value);
+        }
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(4)
+            .build()
+            .unwrap();
+        pool.install(|| map.par_extend(pairs));
+        map.debug_validate();
+        assert_eq!(map.len(), expected.len());
+        assert!(expected
+            .iter()
+            .all(|(key, value)| map.get(key) == Some(value)));
+    }
+
+    #[test]
+    fn test_from_par_iter_matches_sequential() {
+        let map: HashMap<u64, u64> = (0..10_000u64).into_par_iter().map(|i| (i, i * 2)).collect();
+        assert_eq!(map.len(), 10_000);
+        assert!((0..10_000).all(|i| map.get(&i) == Some(&(i * 2))));
+        map.debug_validate();
+    }
+
+    #[test]
+    fn test_par_extend_later_pairs_win() {
+        let mut map = HashMap::new();
+        map.insert(1, "old");
+        map.par_extend(vec![(1, "a"), (2, "b"), (1, "c")]);
+        assert_eq!(map.get(&1), Some(&"c"));
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    fn test_sharded_build_matches_sequential() {
+        check_sharded(HashMap::default());
+        check_sharded(QuadraticMap::default());
+        check_sharded(DoubleHashMap::default());
+    }
+
+    #[test]
+    fn test_sharded_extend_keeps_existing_entries() {
+        let mut map = HashMap::default();
+        for i in 0..20_000u64 {
+            map.insert(i * 3, u64::MAX);
+        }
+        // Leave tombstones for the shards to step over.
+        for i in (0..20_000u64).step_by(2) {
+            map.remove(&(i * 3));
+        }
+        check_sharded(map);
+    }
+
+    // Metrics counters make the map !Sync, and so unable to share across
+    // threads.
+    #[test]
+    #[cfg(not(feature = "metrics"))]
+    fn test_par_iter_visits_every_entry() {
+        let mut map = HashMap::new();
+        for i in 0..1000u64 {
+            map.insert(i, i);
+        }
+        for i in (0..1000).step_by(3) {
+            map.remove(&i);
+        }
+        let sum: u64 = map.par_iter().map(|(_, v)| *v).sum();
+        assert_eq!(sum, map.iter().map(|(_, v)| *v).sum());
+        assert_eq!(map.par_iter().count(), map.len());
+    }
+}
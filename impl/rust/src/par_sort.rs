@@ -0,0 +1,118 @@
+//! Rayon parallel merge sort and quicksort.
+//!
+//! Enabled by the `rayon` feature. Both split the slice in two and sort the
+//! halves with `rayon::join`, handing slices of `cutoff` elements or fewer
+//! to their sequential counterparts in [`sort`](crate::sort). A cutoff too
+//! small drowns the work in task overhead; one too large leaves threads
+//! idle. Either way the result is the same as the sequential sort's.
+
+use crate::sort::{merge, merge_sort, partition, quick_sort};
+
+/// Sort `v` by merge sort, sorting the two halves in parallel.
+///
+/// Each merge is sequential, so the top-level merge alone takes O(n) on
+/// one thread and the critical path is O(n) overall: the speedup on p
+/// threads levels off near log n / 2 however large p grows. Stable.
+pub fn par_merge_sort<T: Ord + Send>(v: &mut [T], cutoff: usize) {
+    if v.len() <= cutoff.max(1) {
+        merge_sort(v);
+        return;
+    }
+    let mid = v.len() / 2;
+    let (left, right) = v.split_at_mut(mid);
+    rayon::join(
+        || par_merge_sort(left, cutoff),
+        || par_merge_sort(right, cutoff),
+    );
+    if v[mid - 1] > v[mid] {
+        merge(v, mid, &mut Vec::with_capacity(mid));
+    }
+}
+
+/// Sort `v` by quicksort, sorting the two sides of each partition in
+/// parallel.
+///
+/// The first partition is a sequential pass over the whole slice, so as
+/// with [`par_merge_sort`] the critical path is O(n). Pivots and the
+/// three-way partition are those of [`quick_sort`], and so is the O(n²)
+/// worst case. Not stable.
+pub fn par_quick_sort<T: Ord + Send>(v: &mut [T], cutoff: usize) {
+    if v.len() <= cutoff.max(1) {
+        quick_sort(v);
+        return;
+    }
+    let (less, greater) = partition(v);
+    let (left, rest) = v.split_at_mut(less);
+    let right = &mut rest[greater - less..];
+    rayon::join(
+        || par_quick_sort(left, cutoff),
+        || par_quick_sort(right, cutoff),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scrambled(n: u64) -> Vec<u64> {
+        (0..n).map(|i| (i * 7919) % 1009).collect()
+    }
+
+    #[test]
+    fn test_every_cutoff_sorts() {
+        let input = scrambled(5000);
+        let mut expected = input.clone();
+        expected.sort();
+        for cutoff in [0, 1, 2, 100, 5000, 10_000] {
+            let mut v = input.clone();
+            par_merge_sort(&mut v, cutoff);
+            assert_eq!(v, expected, "merge, cutoff {}", cutoff);
+            let mut v = input.clone();
+            par_quick_sort(&mut v, cutoff);
+            assert_eq!(v, expected, "quick, cutoff {}", cutoff);
+        }
+    }
+
+    #[test]
+    fn test_merge_stays_stable() {
+        let input: Vec<(u64, usize)> = scrambled(3000)
+            .into_iter()
+            .map(|x| x % 10)
+            .enumerate()
+            .map(|(i, key)| (key, i))
+            .collect();
+        // Comparing by key alone, so equal keys must keep index order.
+        #[derive(Debug, PartialEq, Eq)]
+        struct ByKey(u64, usize);
+        impl PartialOrd for ByKey {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+        impl Ord for ByKey {
+            fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                self.0.cmp(&other.0)
+            }
+        }
+        let mut v: Vec<ByKey> = input.iter().map(|&(k, i)| ByKey(k, i)).collect();
+        par_merge_sort(&mut v, 64);
+        let mut expected = input;
+        expected.sort();
+        let v: Vec<_> = v.into_iter().map(|ByKey(k, i)| (k, i)).collect();
+        assert_eq!(v, expected);
+    }
+
+    #[test]
+    fn test_runs_in_any_pool() {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(3)
+            .build()
+            .unwrap();
+        let mut v = scrambled(20_000);
+        pool.install(|| par_quick_sort(&mut v, 256));
+        assert!(v.windows(2).all(|w| w[0] <= w[1]));
+        let mut empty: Vec<u64> = Vec::new();
+        par_merge_sort(&mut empty, 0);
+        par_quick_sort(&mut empty, 0);
+    }
+}
@@ -0,0 +1,207 @@
+//! Static hash map built on a minimal perfect hash function.
+
+use crate::hashmap::DefaultHashBuilder;
+use std::hash::{BuildHasher, Hash};
+
+/// Average number of keys per displacement bucket.
+const KEYS_PER_BUCKET: usize = 4;
+
+/// Maps `hash` onto `0..n` with a multiply instead of a division.
+pub(crate) fn reduce(hash: u64, n: usize) -> usize {
+    ((u128::from(hash) * n as u128) >> 64) as usize
+}
+
+/// Mixes a key hash with a displacement seed (the splitmix64 finalizer).
+pub(crate) fn displace(hash: u64, seed: u32) -> u64 {
+    let mut z = hash ^ u64::from(seed).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// A read-only map whose entries are placed by a minimal perfect hash.
+///
+/// Built once with the hash-and-displace scheme of CHD: keys are grouped into
+/// small buckets, and each bucket, largest first, gets the first seed that
+/// sends all of its keys to distinct free slots. A lookup hashes the key,
+/// reads its bucket's seed and compares against exactly one entry, so it is
+/// O(1) in the worst case. The table has exactly one slot per entry, plus
+/// one `u32` seed per `KEYS_PER_BUCKET` entries.
+#[derive(Debug, Clone)]
+pub struct PerfectHashMap<K, V> {
+    seeds: Vec<u32>,
+    entries: Vec<(K, V)>,
+    hash_builder: DefaultHashBuilder,
+}
+
+impl<K, V> PerfectHashMap<K, V>
+where
+    K: Hash + Eq,
+{
+    /// Build a map from the given entries. Later entries win over earlier
+    /// ones with the same key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if two distinct keys have the same 64-bit hash, which no seed
+    /// can separate.
+    pub fn build<I>(entries: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let hash_builder = DefaultHashBuilder::default();
+
+        let mut sorted: Vec<(u64, K, V)> = entries
+            .into_iter()
+            .map(|(key, value)| (hash_builder.hash_one(&key), key, value))
+            .collect();
+        // The sort is stable, so within a run of equal hashes the last of
+        // any duplicate keys comes last and replaces the earlier ones.
+        sorted.sort_by_key(|(hash, _, _)| *hash);
+        let mut hashed: Vec<(u64, K, V)> = Vec::with_capacity(sorted.len());
+        for entry in sorted {
+            let duplicate = hashed
+                .iter_mut()
+                .rev()
+                .take_while(|(hash, _, _)| *hash == entry.0)
+                .find(|(_, key, _)| *key == entry.1);
+            match duplicate {
+                Some(existing) => *existing = entry,
+                None => hashed.push(entry),
+            }
+        }
+
+        let len = hashed.len();
+        let bucket_count = ((len + KEYS_PER_BUCKET - 1) / KEYS_PER_BUCKET).max(1);
+        let mut buckets: Vec<Vec<usize>> = vec![Vec::new(); bucket_count];
+        for (i, (hash, _, _)) in hashed.iter().enumerate() {
+            buckets[reduce(*hash, bucket_count)].push(i);
+        }
+
+        // Crowded buckets are hardest to place, so seed them while most
+        // slots are still free.
+        let mut order: Vec<usize> = (0..bucket_count).collect();
+        order.sort_by_key(|&b| std::cmp::Reverse(buckets[b].len()));
+
+        let mut seeds = vec![0u32; bucket_count];
+        let mut slot_of = vec![usize::MAX; len];
+        let mut taken = vec![false; len];
+        let mut candidate = Vec::new();
+        for bucket in order {
+            let members = &buckets[bucket];
+            if members.is_empty() {
+                break;
+            }
+            let seed = (0..=u32::MAX)
+                .find(|&seed| {
+                    candidate.clear();
+                    for &i in members {
+                        let slot = reduce(displace(hashed[i].0, seed), len);
+                        if taken[slot] || candidate.contains(&slot) {
+                            return false;
+                        }
+                        candidate.push(slot);
+                    }
+                    true
+                })
+                .expect("keys with identical hashes cannot be perfectly hashed");
+            seeds[bucket] = seed;
+            for (&i, &slot) in members.iter().zip(&candidate) {
+                taken[slot] = true;
+                slot_of[i] = slot;
+            }
+        }
+
+        let mut placed: Vec<Option<(K, V)>> = Vec::with_capacity(len);
+        placed.resize_with(len, || None);
+        for ((_, key, value), slot) in hashed.into_iter().zip(slot_of) {
+            placed[slot] = Some((key, value));
+        }
+
+        Self {
+            seeds,
+            entries: placed.into_iter().map(Option::unwrap).collect(),
+            hash_builder,
+        }
+    }
+
+    /// Returns the number of elements in the map.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the map contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn slot(&self, key: &K) -> Option<usize> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        let hash = self.hash_builder.hash_one(key);
+        let seed = self.seeds[reduce(hash, self.seeds.len())];
+        let slot = reduce(displace(hash, seed), self.entries.len());
+        (self.entries[slot].0 == *key).then_some(slot)
+    }
+
+    /// Get a reference to the value associated with the key.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.slot(key).map(|slot| &self.entries[slot].1)
+    }
+
+    /// Check if the map contains the given key.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.slot(key).is_some()
+    }
+
+    /// Iterate over all key-value pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.entries.iter().map(|(k, v)| (k, v))
+    }
+
+    /// Iterate over all keys.
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.entries.iter().map(|(k, _)| k)
+    }
+
+    /// Iterate over all values.
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.entries.iter().map(|(_, v)| v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_and_get() {
+        let map = PerfectHashMap::build((0..10_000).map(|i| (format!("key{}", i), i)));
+        assert_eq!(map.len(), 10_000);
+        for i in 0..10_000 {
+            assert_eq!(map.get(&format!("key{}", i)), Some(&i));
+        }
+        assert!(!map.contains_key(&"key10000".to_string()));
+        assert_eq!(map.seeds.len(), 2_500);
+    }
+
+    #[test]
+    fn test_duplicates_keep_last_value() {
+        let map = PerfectHashMap::build([(1, "a"), (2, "b"), (1, "c")]);
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(&1), Some(&"c"));
+        assert_eq!(map.iter().count(), 2);
+    }
+
+    #[test]
+    fn test_empty_and_single() {
+        let empty: PerfectHashMap<u64, u64> = PerfectHashMap::build([]);
+        assert!(empty.is_empty());
+        assert_eq!(empty.get(&1), None);
+
+        let single = PerfectHashMap::build([(7u64, 70u64)]);
+        assert_eq!(single.get(&7), Some(&70));
+        assert_eq!(single.get(&8), None);
+    }
+}
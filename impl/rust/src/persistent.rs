@@ -0,0 +1,417 @@
+//! Persistent hash array mapped trie (HAMT) with structural sharing.
+
+use crate::hashmap::DefaultHashBuilder;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::sync::Arc;
+
+/// Hash bits consumed per trie level.
+const BITS: u32 = 5;
+const MASK: u64 = (1 << BITS) - 1;
+
+/// The `BITS`-bit fragment of `hash` that picks a child at `shift`.
+fn fragment(hash: u64, shift: u32) -> u32 {
+    ((hash >> shift) & MASK) as u32
+}
+
+#[derive(Debug, Clone)]
+enum Slot<K, V> {
+    Leaf(u64, K, V),
+    /// Entries whose full 64-bit hashes are equal.
+    Collision(u64, Vec<(K, V)>),
+    Branch(Arc<Node<K, V>>),
+}
+
+/// A trie node holding one slot per set bit of `bitmap`, in bit order.
+#[derive(Debug, Clone)]
+struct Node<K, V> {
+    bitmap: u32,
+    slots: Vec<Slot<K, V>>,
+}
+
+impl<K, V> Node<K, V> {
+    fn empty() -> Self {
+        Self {
+            bitmap: 0,
+            slots: Vec::new(),
+        }
+    }
+
+    /// Returns the slot position for `bit`, which need not be set.
+    fn position(&self, bit: u32) -> usize {
+        (self.bitmap & (bit - 1)).count_ones() as usize
+    }
+}
+
+impl<K, V> Slot<K, V> {
+    /// Returns the hash shared by everything in a leaf or collision slot.
+    fn hash(&self) -> u64 {
+        match self {
+            Slot::Leaf(hash, _, _) | Slot::Collision(hash, _) => *hash,
+            Slot::Branch(_) => unreachable!("branches have no single hash"),
+        }
+    }
+}
+
+impl<K: Eq + Clone, V: Clone> Node<K, V> {
+    fn get(&self, hash: u64, shift: u32, key: &K) -> Option<&V> {
+        let bit = 1 << fragment(hash, shift);
+        if self.bitmap & bit == 0 {
+            return None;
+        }
+        match &self.slots[self.position(bit)] {
+            Slot::Leaf(h, k, v) if *h == hash && k == key => Some(v),
+            Slot::Collision(h, pairs) if *h == hash => {
+                pairs.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+            }
+            Slot::Branch(child) => child.get(hash, shift + BITS, key),
+            _ => None,
+        }
+    }
+
+    /// Returns a copy of this node with the entry inserted, and whether the
+    /// key was new. Only the path to the entry is copied.
+    fn insert(&self, hash: u64, shift: u32, key: K, value: V) -> (Self, bool) {
+        let bit = 1 << fragment(hash, shift);
+        let position = self.position(bit);
+        let mut node = self.clone();
+        if self.bitmap & bit == 0 {
+            node.bitmap |= bit;
+            node.slots.insert(position, Slot::Leaf(hash, key, value));
+            return (node, true);
+        }
+
+        let (slot, added) = match &self.slots[position] {
+            Slot::Leaf(h, k, _) if *h == hash && *k == key => (Slot::Leaf(hash, key, value), false),
+            Slot::Leaf(h, k, v) if *h == hash => (
+                Slot::Collision(hash, vec![(k.clone(), v.clone()), (key, value)]),
+                true,
+            ),
+            Slot::Collision(h, pairs) if *h == hash => {
+                let mut pairs = pairs.clone();
+                let added = match pairs.iter_mut().find(|(k, _)| *k == key) {
+                    Some(pair) => {
+                        pair.1 = value;
+                        false
+                    }
+                    None => {
+                        pairs.push((key, value));
+                        true
+                    }
+                };
+                (Slot::Collision(hash, pairs), added)
+            }
+            Slot::Branch(child) => {
+                let (child, added) = child.insert(hash, shift + BITS, key, value);
+                (Slot::Branch(Arc::new(child)), added)
+            }
+            other => {
+                let split = Self::split(shift + BITS, other.clone(), Slot::Leaf(hash, key, value));
+                (Slot::Branch(Arc::new(split)), true)
+            }
+        };
+        node.slots[position] = slot;
+        (node, added)
+    }
+
+    /// Build a node holding two leaf or collision slots with different
+    /// hashes, nesting until their fragments differ.
+    fn split(shift: u32, a: Slot<K, V>, b: Slot<K, V>) -> Self {
+        let (fa, fb) = (fragment(a.hash(), shift), fragment(b.hash(), shift));
+        if fa == fb {
+            let child = Self::split(shift + BITS, a, b);
+            return Self {
+                bitmap: 1 << fa,
+                slots: vec![Slot::Branch(Arc::new(child))],
+            };
+        }
+        let slots = if fa < fb { vec![a, b] } else { vec![b, a] };
+        Self {
+            bitmap: (1 << fa) | (1 << fb),
+            slots,
+        }
+    }
+
+    /// Returns a copy of this node without the key, or None if the key is
+    /// absent.
+    fn remove(&self, hash: u64, shift: u32, key: &K) -> Option<Self> {
+        let bit = 1 << fragment(hash, shift);
+        if self.bitmap & bit == 0 {
+            return None;
+        }
+        let position = self.position(bit);
+        let replacement = match &self.slots[position] {
+            Slot::Leaf(h, k, _) if *h == hash && k == key => None,
+            Slot::Collision(h, pairs) if *h == hash => {
+                let index = pairs.iter().position(|(k, _)| k == key)?;
+                let mut pairs = pairs.clone();
+                pairs.remove(index);
+                if pairs.len() == 1 {
+                    let (k, v) = pairs.pop().expect("one pair left");
+                    Some(Slot::Leaf(hash, k, v))
+                } else {
+                    Some(Slot::Collision(hash, pairs))
+                }
+            }
+            Slot::Branch(child) => {
+                let child = child.remove(hash, shift + BITS, key)?;
+                match child.slots.as_slice() {
+                    [] => None,
+                    // Pull a lone entry up so the trie stays as shallow as
+                    // if it had never been split.
+                    [slot @ (Slot::Leaf(..) | Slot::Collision(..))] => Some(slot.clone()),
+                    _ => Some(Slot::Branch(Arc::new(child))),
+                }
+            }
+            _ => return None,
+        };
+
+        let mut node = self.clone();
+        match replacement {
+            Some(slot) => node.slots[position] = slot,
+            None => {
+                node.bitmap &= !bit;
+                node.slots.remove(position);
+            }
+        }
+        Some(node)
+    }
+}
+
+/// An immutable hash map whose updates return new maps.
+///
+/// A hash array mapped trie: each level consumes 5 bits of the key's hash
+/// to pick one of up to 32 children, stored densely behind a bitmap. An
+/// `insert` or `remove` copies only the O(log32 n) nodes on the path to the
+/// entry and shares every other node with the original map, so keeping old
+/// versions around is cheap and cloning a map is O(1). Keys whose full
+/// hashes collide share a collision slot at the bottom of the trie.
+pub struct PersistentMap<K, V, S = DefaultHashBuilder> {
+    root: Arc<Node<K, V>>,
+    len: usize,
+    hash_builder: S,
+}
+
+impl<K, V, S: Clone> Clone for PersistentMap<K, V, S> {
+    fn clone(&self) -> Self {
+        Self {
+            root: Arc::clone(&self.root),
+            len: self.len,
+            hash_builder: self.hash_builder.clone(),
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, S> fmt::Debug for PersistentMap<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V, S: Default> Default for PersistentMap<K, V, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K, V> PersistentMap<K, V> {
+    /// Create a new empty PersistentMap.
+    pub fn new() -> Self {
+        Self::with_hasher(DefaultHashBuilder::default())
+    }
+}
+
+impl<K, V, S> PersistentMap<K, V, S> {
+    /// Create a new empty map using the given hasher builder.
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self {
+            root: Arc::new(Node::empty()),
+            len: 0,
+            hash_builder,
+        }
+    }
+
+    /// Returns the number of elements in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the map contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Iterate over all key-value pairs.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            stack: vec![self.root.slots.iter()],
+            collision: [].iter(),
+            remaining: self.len,
+        }
+    }
+}
+
+impl<K, V, S> PersistentMap<K, V, S>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    S: BuildHasher + Clone,
+{
+    /// Get a reference to the value associated with the key.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.root.get(self.hash_builder.hash_one(key), 0, key)
+    }
+
+    /// Check if the map contains the given key.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Returns a new map with the key set to `value`.
+    pub fn insert(&self, key: K, value: V) -> Self {
+        let hash = self.hash_builder.hash_one(&key);
+        let (root, added) = self.root.insert(hash, 0, key, value);
+        Self {
+            root: Arc::new(root),
+            len: self.len + added as usize,
+            hash_builder: self.hash_builder.clone(),
+        }
+    }
+
+    /// Returns a new map without the key. The result shares all of its
+    /// structure with `self` if the key was absent.
+    pub fn remove(&self, key: &K) -> Self {
+        let hash = self.hash_builder.hash_one(key);
+        match self.root.remove(hash, 0, key) {
+            Some(root) => Self {
+                root: Arc::new(root),
+                len: self.len - 1,
+                hash_builder: self.hash_builder.clone(),
+            },
+            None => self.clone(),
+        }
+    }
+}
+
+/// An iterator over the key-value pairs of a [`PersistentMap`].
+pub struct Iter<'a, K, V> {
+    stack: Vec<std::slice::Iter<'a, Slot<K, V>>>,
+    collision: std::slice::Iter<'a, (K, V)>,
+    remaining: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((key, value)) = self.collision.next() {
+                self.remaining -= 1;
+                return Some((key, value));
+            }
+            let top = self.stack.last_mut()?;
+            match top.next() {
+                Some(Slot::Leaf(_, key, value)) => {
+                    self.remaining -= 1;
+                    return Some((key, value));
+                }
+                Some(Slot::Collision(_, pairs)) => self.collision = pairs.iter(),
+                Some(Slot::Branch(child)) => self.stack.push(child.slots.iter()),
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::hash::{BuildHasherDefault, Hasher};
+
+    /// A hasher sending every key to the same hash.
+    #[derive(Default)]
+    struct ConstantHasher;
+
+    impl Hasher for ConstantHasher {
+        fn finish(&self) -> u64 {
+            7
+        }
+
+        fn write(&mut self, _: &[u8]) {}
+    }
+
+    #[test]
+    fn test_versions_are_independent() {
+        let empty = PersistentMap::new();
+        let one = empty.insert("a", 1);
+        let two = one.insert("b", 2);
+        let updated = two.insert("a", 10);
+        let removed = updated.remove(&"b");
+
+        assert_eq!(empty.get(&"a"), None);
+        assert_eq!(one.get(&"a"), Some(&1));
+        assert_eq!((two.len(), two.get(&"b")), (2, Some(&2)));
+        assert_eq!((updated.len(), updated.get(&"a")), (2, Some(&10)));
+        assert_eq!((removed.len(), removed.get(&"b")), (1, None));
+        assert_eq!(removed.remove(&"zzz").len(), 1);
+    }
+
+    #[test]
+    fn test_matches_std_and_shares_structure() {
+        let mut map = PersistentMap::new();
+        let mut model = std::collections::HashMap::new();
+        for i in 0..5000u32 {
+            map = map.insert(i % 3000, i);
+            model.insert(i % 3000, i);
+            if i % 4 == 0 {
+                map = map.remove(&(i / 2));
+                model.remove(&(i / 2));
+            }
+        }
+        assert_eq!(map.len(), model.len());
+        assert_eq!(map.iter().len(), model.len());
+        for (key, value) in map.iter() {
+            assert_eq!(model.get(key), Some(value));
+        }
+
+        // A full root: an insert copies one root slot's path only.
+        let next = map.insert(u32::MAX, 0);
+        let shared = map
+            .root
+            .slots
+            .iter()
+            .zip(&next.root.slots)
+            .filter(|pair| match pair {
+                (Slot::Branch(a), Slot::Branch(b)) => Arc::ptr_eq(a, b),
+                _ => false,
+            })
+            .count();
+        assert_eq!(map.root.slots.len(), 32);
+        assert_eq!(shared, 31);
+    }
+
+    #[test]
+    fn test_full_hash_collisions() {
+        let mut map: PersistentMap<i32, i32, BuildHasherDefault<ConstantHasher>> =
+            PersistentMap::default();
+        for i in 0..4 {
+            map = map.insert(i, i);
+        }
+        map = map.insert(2, 20);
+        assert_eq!(map.len(), 4);
+        assert_eq!(map.get(&2), Some(&20));
+        for i in [0, 1, 3] {
+            map = map.remove(&i);
+        }
+        assert!(matches!(map.root.slots[..], [Slot::Leaf(_, 2, 20)]));
+        assert_eq!(map.iter().collect::<Vec<_>>(), [(&2, &20)]);
+    }
+}
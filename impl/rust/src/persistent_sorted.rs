@@ -0,0 +1,488 @@
+//! Persistent weight-balanced tree with path copying.
+
+use crate::btree::{before_start, check_range, past_end};
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::RangeBounds;
+use std::sync::Arc;
+
+/// A subtree may outweigh its sibling by at most this factor.
+const DELTA: usize = 3;
+/// A rebalance uses a single rotation when the inner grandchild weighs less
+/// than this factor times the outer one, and a double rotation otherwise.
+const GAMMA: usize = 2;
+
+type Link<K, V> = Option<Arc<Node<K, V>>>;
+
+#[derive(Debug)]
+struct Node<K, V> {
+    key: K,
+    value: V,
+    /// Number of entries in the subtree rooted here.
+    size: usize,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+fn size<K, V>(link: &Link<K, V>) -> usize {
+    link.as_ref().map_or(0, |node| node.size)
+}
+
+/// The weight of a subtree, one more than its size so that empty subtrees
+/// weigh something.
+fn weight<K, V>(link: &Link<K, V>) -> usize {
+    size(link) + 1
+}
+
+/// True if a subtree weighing `heavy` is too heavy beside one weighing
+/// `light`.
+fn too_heavy(heavy: usize, light: usize) -> bool {
+    heavy > DELTA * light
+}
+
+/// Allocate a node over two subtrees, which must already be in balance.
+fn node<K, V>(key: K, value: V, left: Link<K, V>, right: Link<K, V>) -> Arc<Node<K, V>> {
+    Arc::new(Node {
+        key,
+        value,
+        size: 1 + size(&left) + size(&right),
+        left,
+        right,
+    })
+}
+
+impl<K: Clone, V: Clone> Node<K, V> {
+    /// Returns a copy of this node's entry.
+    fn entry(&self) -> (K, V) {
+        (self.key.clone(), self.value.clone())
+    }
+}
+
+/// Allocate a node over two subtrees that may be out of balance by one
+/// insertion or removal, rotating as needed. Rotations copy the nodes they
+/// move; the subtrees below them stay shared.
+fn balance<K: Clone, V: Clone>(
+    key: K,
+    value: V,
+    left: Link<K, V>,
+    right: Link<K, V>,
+) -> Arc<Node<K, V>> {
+    let (left_weight, right_weight) = (weight(&left), weight(&right));
+    if too_heavy(right_weight, left_weight) {
+        let heavy = right.expect("heavy side is non-empty");
+        if weight(&heavy.left) < GAMMA * weight(&heavy.right) {
+            let (k, v) = heavy.entry();
+            let left = node(key, value, left, heavy.left.clone());
+            node(k, v, Some(left), heavy.right.clone())
+        } else {
+            let inner = heavy.left.as_ref().expect("inner grandchild is heavy");
+            let (k, v) = inner.entry();
+            let (hk, hv) = heavy.entry();
+            let left = node(key, value, left, inner.left.clone());
+            let right = node(hk, hv, inner.right.clone(), heavy.right.clone());
+            node(k, v, Some(left), Some(right))
+        }
+    } else if too_heavy(left_weight, right_weight) {
+        let heavy = left.expect("heavy side is non-empty");
+        if weight(&heavy.right) < GAMMA * weight(&heavy.left) {
+            let (k, v) = heavy.entry();
+            let right = node(key, value, heavy.right.clone(), right);
+            node(k, v, heavy.left.clone(), Some(right))
+        } else {
+            let inner = heavy.right.as_ref().expect("inner grandchild is heavy");
+            let (k, v) = inner.entry();
+            let (hk, hv) = heavy.entry();
+            let left = node(hk, hv, heavy.left.clone(), inner.left.clone());
+            let right = node(key, value, inner.right.clone(), right);
+            node(k, v, Some(left), Some(right))
+        }
+    } else {
+        node(key, value, left, right)
+    }
+}
+
+/// Returns a copy of the subtree with the entry inserted, and whether the
+/// key was new. Only the path to the entry is copied.
+fn insert<K: Ord + Clone, V: Clone>(
+    link: &Link<K, V>,
+    key: K,
+    value: V,
+) -> (Arc<Node<K, V>>, bool) {
+    let Some(top) = link else {
+        return (node(key, value, None, None), true);
+    };
+    let (k, v) = top.entry();
+    match key.cmp(&top.key) {
+        Ordering::Less => {
+            let (left, added) = insert(&top.left, key, value);
+            (balance(k, v, Some(left), top.right.clone()), added)
+        }
+        Ordering::Greater => {
+            let (right, added) = insert(&top.right, key, value);
+            (balance(k, v, top.left.clone(), Some(right)), added)
+        }
+        Ordering::Equal => (node(key, value, top.left.clone(), top.right.clone()), false),
+    }
+}
+
+/// Returns a copy of the subtree without its first entry, and that entry.
+fn remove_first<K: Clone, V: Clone>(top: &Node<K, V>) -> ((K, V), Link<K, V>) {
+    match &top.left {
+        None => (top.entry(), top.right.clone()),
+        Some(left) => {
+            let (first, left) = remove_first(left);
+            let (k, v) = top.entry();
+            (first, Some(balance(k, v, left, top.right.clone())))
+        }
+    }
+}
+
+/// Returns a copy of the subtree without its last entry, and that entry.
+fn remove_last<K: Clone, V: Clone>(top: &Node<K, V>) -> ((K, V), Link<K, V>) {
+    match &top.right {
+        None => (top.entry(), top.left.clone()),
+        Some(right) => {
+            let (last, right) = remove_last(right);
+            let (k, v) = top.entry();
+            (last, Some(balance(k, v, top.left.clone(), right)))
+        }
+    }
+}
+
+/// Returns a copy of the subtree without the key, or None if the key is
+/// absent.
+fn remove<K: Ord + Clone, V: Clone>(link: &Link<K, V>, key: &K) -> Option<Link<K, V>> {
+    let top = link.as_ref()?;
+    let replacement = match key.cmp(&top.key) {
+        Ordering::Less => {
+            let left = remove(&top.left, key)?;
+            let (k, v) = top.entry();
+            balance(k, v, left, top.right.clone())
+        }
+        Ordering::Greater => {
+            let right = remove(&top.right, key)?;
+            let (k, v) = top.entry();
+            balance(k, v, top.left.clone(), right)
+        }
+        Ordering::Equal => {
+            // Replace the entry with its neighbor from the heavier side,
+            // which can spare one without falling out of balance.
+            let (left, right) = (&top.left, &top.right);
+            match (left, right) {
+                (None, _) => return Some(right.clone()),
+                (_, None) => return Some(left.clone()),
+                (Some(l), Some(r)) if l.size > r.size => {
+                    let ((k, v), left) = remove_last(l);
+                    balance(k, v, left, right.clone())
+                }
+                (Some(_), Some(r)) => {
+                    let ((k, v), right) = remove_first(r);
+                    balance(k, v, left.clone(), right)
+                }
+            }
+        }
+    };
+    Some(Some(replacement))
+}
+
+/// An immutable ordered map whose updates return new maps.
+///
+/// A weight-balanced tree, as in [`WeightBalancedMap`](crate::WeightBalancedMap),
+/// whose nodes are shared behind [`Arc`]s and never modified in place. An
+/// `insert` or `remove` copies the O(log n) nodes on the path from the root
+/// to the entry, plus the few that rebalancing rotates, and points the
+/// copies at every untouched subtree of the original, so each new version
+/// costs O(log n) space and cloning a map is O(1). Weight balance suits
+/// path copying: every rebalance is local to the search path, and the
+/// subtree sizes it keeps also give [`select`](Self::select) and
+/// [`rank`](Self::rank). [`PersistentMap`](crate::PersistentMap) is the
+/// unordered counterpart.
+pub struct PersistentSortedMap<K, V> {
+    root: Link<K, V>,
+}
+
+impl<K, V> Clone for PersistentSortedMap<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+        }
+    }
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for PersistentSortedMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V> Default for PersistentSortedMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> PersistentSortedMap<K, V> {
+    /// Create a new empty PersistentSortedMap.
+    pub fn new() -> Self {
+        Self { root: None }
+    }
+
+    /// Returns the number of elements in the map.
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    /// Returns true if the map contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Returns the entry with the `k`th smallest key, counting from zero.
+    pub fn select(&self, mut k: usize) -> Option<(&K, &V)> {
+        let mut link = &self.root;
+        while let Some(node) = link {
+            let left_size = size(&node.left);
+            link = match k.cmp(&left_size) {
+                Ordering::Less => &node.left,
+                Ordering::Greater => {
+                    k -= left_size + 1;
+                    &node.right
+                }
+                Ordering::Equal => return Some((&node.key, &node.value)),
+            };
+        }
+        None
+    }
+}
+
+impl<K: Ord, V> PersistentSortedMap<K, V> {
+    /// Get a reference to the value associated with the key.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let mut link = &self.root;
+        while let Some(node) = link {
+            link = match key.cmp(&node.key) {
+                Ordering::Less => &node.left,
+                Ordering::Greater => &node.right,
+                Ordering::Equal => return Some(&node.value),
+            };
+        }
+        None
+    }
+
+    /// Check if the map contains the given key.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Returns the number of keys less than `key`.
+    pub fn rank(&self, key: &K) -> usize {
+        let mut rank = 0;
+        let mut link = &self.root;
+        while let Some(node) = link {
+            link = match key.cmp(&node.key) {
+                Ordering::Less => &node.left,
+                Ordering::Greater => {
+                    rank += size(&node.left) + 1;
+                    &node.right
+                }
+                Ordering::Equal => return rank + size(&node.left),
+            };
+        }
+        rank
+    }
+
+    /// Iterate over all key-value pairs in key order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.range(..)
+    }
+
+    /// Iterate over the key-value pairs whose keys fall in `range`, in key
+    /// order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range starts after it ends, or starts and ends at the
+    /// same excluded key.
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Iter<'_, K, V> {
+        check_range(&range);
+        let mut iter = Iter {
+            stack: Vec::new(),
+            end: None,
+        };
+        let mut link = &self.root;
+        while let Some(node) = link {
+            if before_start(&node.key, range.start_bound()) {
+                link = &node.right;
+            } else {
+                iter.stack.push(node);
+                link = &node.left;
+            }
+        }
+        let mut link = &self.root;
+        while let Some(node) = link {
+            if past_end(&node.key, range.end_bound()) {
+                iter.end = Some(node);
+                link = &node.left;
+            } else {
+                link = &node.right;
+            }
+        }
+        iter
+    }
+
+    /// Check the weight-balanced tree invariants, panicking on the first
+    /// violation.
+    ///
+    /// Checks that keys are strictly increasing in order, that every stored
+    /// subtree size is right, and that no subtree weighs more than three
+    /// times its sibling.
+    pub fn debug_validate(&self) {
+        fn walk<'a, K: Ord, V>(link: &'a Link<K, V>, last: &mut Option<&'a K>) -> usize {
+            let Some(node) = link else {
+                return 0;
+            };
+            let left = walk(&node.left, last);
+            assert!(
+                last.map_or(true, |last| *last < node.key),
+                "keys out of order"
+            );
+            *last = Some(&node.key);
+            let right = walk(&node.right, last);
+            assert_eq!(node.size, 1 + left + right, "stale subtree size");
+            assert!(
+                !too_heavy(left + 1, right + 1) && !too_heavy(right + 1, left + 1),
+                "subtrees out of weight balance"
+            );
+            node.size
+        }
+
+        walk(&self.root, &mut None);
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> PersistentSortedMap<K, V> {
+    /// Returns a new map with the key set to `value`.
+    pub fn insert(&self, key: K, value: V) -> Self {
+        let (root, _) = insert(&self.root, key, value);
+        Self { root: Some(root) }
+    }
+
+    /// Returns a new map without the key. The result shares all of its
+    /// structure with `self` if the key was absent.
+    pub fn remove(&self, key: &K) -> Self {
+        match remove(&self.root, key) {
+            Some(root) => Self { root },
+            None => self.clone(),
+        }
+    }
+}
+
+/// An iterator over a key range of a [`PersistentSortedMap`], in key order.
+pub struct Iter<'a, K, V> {
+    /// Nodes still to visit whose left subtrees are already handled; the
+    /// next entry is on top.
+    stack: Vec<&'a Node<K, V>>,
+    /// The first node past the range, if there is one.
+    end: Option<&'a Node<K, V>>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        if self.end.is_some_and(|end| std::ptr::eq(end, node)) {
+            self.stack.clear();
+            return None;
+        }
+        let mut link = &node.right;
+        while let Some(child) = link {
+            self.stack.push(child);
+            link = &child.left;
+        }
+        Some((&node.key, &node.value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Count the nodes of `new` that are not also nodes of `old`.
+    fn unshared<K, V>(new: &Link<K, V>, old: &Link<K, V>) -> usize {
+        fn collect<K, V>(link: &Link<K, V>, nodes: &mut Vec<*const Node<K, V>>) {
+            if let Some(node) = link {
+                nodes.push(Arc::as_ptr(node));
+                collect(&node.left, nodes);
+                collect(&node.right, nodes);
+            }
+        }
+
+        fn count<K, V>(link: &Link<K, V>, old: &[*const Node<K, V>]) -> usize {
+            match link {
+                Some(node) if old.binary_search(&Arc::as_ptr(node)).is_err() => {
+                    1 + count(&node.left, old) + count(&node.right, old)
+                }
+                _ => 0,
+            }
+        }
+
+        let mut nodes = Vec::new();
+        collect(old, &mut nodes);
+        nodes.sort_unstable();
+        count(new, &nodes)
+    }
+
+    #[test]
+    fn test_versions_are_independent() {
+        let empty = PersistentSortedMap::new();
+        let one = empty.insert(2, "b");
+        let two = one.insert(1, "a");
+        let updated = two.insert(2, "B");
+        let removed = updated.remove(&1);
+
+        assert_eq!(empty.get(&2), None);
+        assert_eq!(one.iter().collect::<Vec<_>>(), [(&2, &"b")]);
+        assert_eq!(two.iter().collect::<Vec<_>>(), [(&1, &"a"), (&2, &"b")]);
+        assert_eq!(updated.get(&2), Some(&"B"));
+        assert_eq!((removed.len(), removed.get(&1)), (1, None));
+        assert_eq!(removed.remove(&7).len(), 1);
+    }
+
+    #[test]
+    fn test_updates_copy_one_path() {
+        let mut map = PersistentSortedMap::new();
+        for i in 0..10_000 {
+            map = map.insert(i, i);
+        }
+        map.debug_validate();
+        for key in [0, 4_321, 9_999, 20_000] {
+            let next = map.insert(key, 0);
+            next.debug_validate();
+            // Height is at most about 2.5 log2 n for this balance.
+            assert!(unshared(&next.root, &map.root) <= 40);
+            let next = map.remove(&key);
+            next.debug_validate();
+            assert!(unshared(&next.root, &map.root) <= 40);
+        }
+        assert_eq!(map.len(), 10_000);
+    }
+
+    #[test]
+    fn test_select_rank_and_range() {
+        let map = (0..100)
+            .map(|i| i * 3)
+            .fold(PersistentSortedMap::new(), |map, key| map.insert(key, ()));
+        assert_eq!(map.select(10).map(|(k, _)| *k), Some(30));
+        assert_eq!(map.select(100), None);
+        assert_eq!(map.rank(&31), 11);
+        assert_eq!(map.rank(&30), 10);
+        assert!(map.range(10..=20).map(|(k, _)| *k).eq([12, 15, 18]));
+        let evens = (0..100)
+            .filter(|i| i % 2 == 1)
+            .fold(map.clone(), |map, i| map.remove(&(i * 3)));
+        evens.debug_validate();
+        assert_eq!(evens.len(), 50);
+        assert_eq!(map.len(), 100);
+    }
+}
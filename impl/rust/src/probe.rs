@@ -0,0 +1,99 @@
+//! Probe sequences for open addressing.
+
+/// A probe sequence over a power-of-two table.
+///
+/// Strategies are stateless: the slot at each step is a pure function of the
+/// hash, the step number and the table mask, so lookups, statistics and
+/// validation all walk the exact same sequence.
+pub trait ProbeStrategy {
+    /// Returns the slot inspected at step `i` (starting at 0) of the probe
+    /// sequence for `hash`, in a table of `mask + 1` slots.
+    ///
+    /// The first `mask + 1` steps must visit every slot exactly once.
+    fn probe(hash: u64, i: usize, mask: usize) -> usize;
+}
+
+/// Linear probing: `h, h + 1, h + 2, ...`.
+///
+/// The best cache behavior of any sequence, at the cost of primary
+/// clustering: runs of occupied slots merge and grow.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LinearProbe;
+
+impl ProbeStrategy for LinearProbe {
+    #[inline]
+    fn probe(hash: u64, i: usize, mask: usize) -> usize {
+        (hash as usize).wrapping_add(i) & mask
+    }
+}
+
+/// Triangular-number quadratic probing: `h, h + 1, h + 3, h + 6, ...`.
+///
+/// Over a power-of-two capacity this visits every slot. Keys with different
+/// home buckets follow different sequences, so primary clustering does not
+/// form.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct QuadraticProbe;
+
+impl ProbeStrategy for QuadraticProbe {
+    #[inline]
+    fn probe(hash: u64, i: usize, mask: usize) -> usize {
+        (hash as usize).wrapping_add(i.wrapping_mul(i + 1) / 2) & mask
+    }
+}
+
+/// Double hashing: `h, h + s, h + 2s, ...` with the step `s` taken from the
+/// high bits of the hash.
+///
+/// The step is forced odd so that it is coprime with the power-of-two
+/// capacity and the sequence covers every slot. Keys that collide on their
+/// home bucket usually still follow different sequences, which avoids the
+/// secondary clustering of quadratic probing.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DoubleHashProbe;
+
+impl DoubleHashProbe {
+    fn step(hash: u64) -> usize {
+        (hash >> 32) as usize | 1
+    }
+}
+
+impl ProbeStrategy for DoubleHashProbe {
+    #[inline]
+    fn probe(hash: u64, i: usize, mask: usize) -> usize {
+        (hash as usize).wrapping_add(i.wrapping_mul(Self::step(hash))) & mask
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn covers_table<P: ProbeStrategy>(hash: u64) -> bool {
+        let mask = 63;
+        let mut seen = [false; 64];
+        for i in 0..=mask {
+            seen[P::probe(hash, i, mask)] = true;
+        }
+        seen.iter().all(|&s| s)
+    }
+
+    #[test]
+    fn test_sequences_cover_table() {
+        for hash in [0, 17, u64::MAX, 0x9E37_79B9_7F4A_7C15] {
+            assert!(covers_table::<LinearProbe>(hash));
+            assert!(covers_table::<QuadraticProbe>(hash));
+            assert!(covers_table::<DoubleHashProbe>(hash));
+        }
+    }
+
+    #[test]
+    fn test_sequences_start_at_home_bucket() {
+        let hash = 0xDEAD_BEEF_1234_5678;
+        assert_eq!(LinearProbe::probe(hash, 0, 255), 0x78);
+        assert_eq!(QuadraticProbe::probe(hash, 0, 255), 0x78);
+        assert_eq!(DoubleHashProbe::probe(hash, 0, 255), 0x78);
+        assert_eq!(QuadraticProbe::probe(hash, 3, 255), 0x78 + 6);
+        assert_eq!(DoubleHashProbe::probe(hash, 1, 255), (0x78 + 0xEF) & 255);
+    }
+}
@@ -0,0 +1,57 @@
+//! Hash Map implementation using open addressing with quadratic probing.
+
+use crate::hashmap::{DefaultHashBuilder, OpenAddressingMap};
+use crate::probe::QuadraticProbe;
+
+/// A hash map using open addressing with triangular-number quadratic probing.
+///
+/// The probe sequence visits `h, h + 1, h + 3, h + 6, ...` modulo a
+/// power-of-two capacity, which is guaranteed to cover every slot. Keys with
+/// different home buckets follow different sequences, so the primary
+/// clustering of linear probing does not form.
+pub type QuadraticMap<K, V, S = DefaultHashBuilder> = OpenAddressingMap<K, V, S, QuadraticProbe>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capacity_is_power_of_two() {
+        assert_eq!(QuadraticMap::<i32, i32>::with_capacity(100).capacity(), 128);
+        assert_eq!(QuadraticMap::<i32, i32>::new().capacity(), 16);
+    }
+
+    #[test]
+    fn test_insert_get_remove() {
+        let mut map = QuadraticMap::new();
+        for i in 0..500 {
+            assert!(map.insert(format!("key{}", i), i).is_none());
+        }
+        assert_eq!(map.insert("key7".to_string(), 70), Some(7));
+        assert_eq!(map.len(), 500);
+        for i in (0..500).step_by(2) {
+            assert!(map.remove(&format!("key{}", i)).is_some());
+        }
+        assert_eq!(map.len(), 250);
+        assert_eq!(map.get(&"key7".to_string()), Some(&70));
+        assert!(!map.contains_key(&"key8".to_string()));
+        assert_eq!(
+            map.stats().probe_length_histogram.iter().sum::<usize>(),
+            250
+        );
+        map.debug_validate();
+    }
+
+    #[test]
+    fn test_delete_churn_does_not_grow() {
+        let mut map = QuadraticMap::new();
+        for i in 0..10_000 {
+            map.insert(i, i);
+            if i >= 4 {
+                map.remove(&(i - 4));
+            }
+        }
+        assert_eq!(map.len(), 4);
+        assert_eq!(map.capacity(), 16);
+    }
+}
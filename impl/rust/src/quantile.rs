@@ -0,0 +1,250 @@
+//! Streaming medians and quantiles: the median of everything seen so far,
+//! and any quantile of a sliding window.
+
+use crate::binary_heap::{BinaryHeap, HeapOrder};
+use crate::order_statistics::OrderStatisticsMap;
+use std::collections::VecDeque;
+use std::fmt;
+
+/// The median of a stream of values, in O(1), with O(log n) pushes.
+///
+/// Two heaps split the values at the median: a max-heap holds the smaller
+/// half and a min-heap the larger, with the lower half allowed at most one
+/// extra value. A push goes to whichever half it belongs in, and if that
+/// leaves the halves out of balance, the top of the larger one moves
+/// across. The median is then the top of the lower half. Values are never
+/// removed; for a median over recent values only, see [`WindowQuantiles`].
+#[derive(Debug, Clone)]
+pub struct RunningMedian<T> {
+    lower: BinaryHeap<T>,
+    upper: BinaryHeap<T>,
+}
+
+impl<T: Ord> Default for RunningMedian<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> RunningMedian<T> {
+    /// Create a new empty RunningMedian.
+    pub fn new() -> Self {
+        Self {
+            lower: BinaryHeap::new(HeapOrder::Max),
+            upper: BinaryHeap::new(HeapOrder::Min),
+        }
+    }
+
+    /// Returns the number of values pushed.
+    pub fn len(&self) -> usize {
+        self.lower.len() + self.upper.len()
+    }
+
+    /// Returns true if nothing has been pushed.
+    pub fn is_empty(&self) -> bool {
+        self.lower.is_empty()
+    }
+
+    /// Add a value to the stream.
+    pub fn push(&mut self, value: T) {
+        if self.lower.peek().is_some_and(|top| value > *top) {
+            self.upper.push(value);
+        } else {
+            self.lower.push(value);
+        }
+        if self.lower.len() > self.upper.len() + 1 {
+            let top = self.lower.pop().expect("lower half is non-empty");
+            self.upper.push(top);
+        } else if self.upper.len() > self.lower.len() {
+            let top = self.upper.pop().expect("upper half is non-empty");
+            self.lower.push(top);
+        }
+    }
+
+    /// Returns the median: the middle value, or the lower of the two middle
+    /// values if the count is even.
+    pub fn median(&self) -> Option<&T> {
+        self.lower.peek()
+    }
+
+    /// Returns the upper median: the middle value, or the higher of the two
+    /// middle values if the count is even.
+    pub fn upper_median(&self) -> Option<&T> {
+        if self.upper.len() == self.lower.len() {
+            self.upper.peek()
+        } else {
+            self.lower.peek()
+        }
+    }
+
+    /// Remove every value.
+    pub fn clear(&mut self) {
+        self.lower.clear();
+        self.upper.clear();
+    }
+}
+
+/// Any quantile of the last `window` values pushed, in O(log w), with
+/// O(log w) pushes.
+///
+/// The window's values sit in an [`OrderStatisticsMap`], each keyed by the
+/// value and its position in the stream so that duplicates stay distinct,
+/// and a queue remembers the order they arrived in so the oldest can be
+/// found and removed when the window is full. A quantile is then a single
+/// [`select`](OrderStatisticsMap::select) by rank. Unlike a pair of heaps,
+/// the tree can remove any value, and answers every quantile at once
+/// rather than only the one it was built around.
+#[derive(Clone)]
+pub struct WindowQuantiles<T> {
+    sorted: OrderStatisticsMap<(T, u64), ()>,
+    arrivals: VecDeque<T>,
+    window: usize,
+    pushed: u64,
+}
+
+impl<T: fmt::Debug> fmt::Debug for WindowQuantiles<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(&self.arrivals).finish()
+    }
+}
+
+impl<T: Ord + Clone> WindowQuantiles<T> {
+    /// Create a new empty WindowQuantiles over the last `window` values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `window` is zero.
+    pub fn new(window: usize) -> Self {
+        assert!(window > 0, "window must hold at least one value");
+        Self {
+            sorted: OrderStatisticsMap::new(),
+            arrivals: VecDeque::with_capacity(window),
+            window,
+            pushed: 0,
+        }
+    }
+
+    /// Returns the window size.
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Returns the number of values in the window: the number pushed, up
+    /// to the window size.
+    pub fn len(&self) -> usize {
+        self.arrivals.len()
+    }
+
+    /// Returns true if nothing has been pushed.
+    pub fn is_empty(&self) -> bool {
+        self.arrivals.is_empty()
+    }
+
+    /// Push a value, sliding the oldest out if the window is full.
+    pub fn push(&mut self, value: T) {
+        if self.arrivals.len() == self.window {
+            let oldest = self.arrivals.pop_front().expect("window is full");
+            let position = self.pushed - self.window as u64;
+            self.sorted.remove(&(oldest, position));
+        }
+        self.sorted.insert((value.clone(), self.pushed), ());
+        self.arrivals.push_back(value);
+        self.pushed += 1;
+    }
+
+    /// Returns the `q`-quantile of the window, by nearest rank: the value
+    /// at index `round((len - 1) * q)` of the window in sorted order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `q` is not between 0 and 1.
+    pub fn quantile(&self, q: f64) -> Option<&T> {
+        assert!((0.0..=1.0).contains(&q), "quantile must be in [0, 1]");
+        let last = self.len().checked_sub(1)?;
+        let rank = (last as f64 * q).round() as usize;
+        self.select(rank)
+    }
+
+    /// Returns the median of the window: the middle value, or the lower of
+    /// the two middle values if the window holds an even count.
+    pub fn median(&self) -> Option<&T> {
+        self.select(self.len().checked_sub(1)? / 2)
+    }
+
+    /// Returns the `k`-th smallest value in the window, counting from 0.
+    pub fn select(&self, k: usize) -> Option<&T> {
+        self.sorted.select(k).map(|((value, _), _)| value)
+    }
+
+    /// Remove every value.
+    pub fn clear(&mut self) {
+        self.sorted.clear();
+        self.arrivals.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_running_median() {
+        let mut median = RunningMedian::new();
+        assert_eq!(median.median(), None);
+        let mut seen = Vec::new();
+        for x in [5, 15, 1, 3, 8, 7, 9, 10, 20, 2] {
+            median.push(x);
+            seen.push((*median.median().unwrap(), *median.upper_median().unwrap()));
+        }
+        assert_eq!(
+            seen,
+            [
+                (5, 5),
+                (5, 15),
+                (5, 5),
+                (3, 5),
+                (5, 5),
+                (5, 7),
+                (7, 7),
+                (7, 8),
+                (8, 8),
+                (7, 8)
+            ]
+        );
+        assert_eq!(median.len(), 10);
+    }
+
+    #[test]
+    fn test_window_quantiles() {
+        let samples = [5, 1, 4, 4, 9, 2, 2, 7, 3, 8];
+        let mut window = WindowQuantiles::new(5);
+        let mut medians = Vec::new();
+        for &sample in &samples {
+            window.push(sample);
+            medians.push(*window.median().unwrap());
+        }
+        assert_eq!(medians, [5, 1, 4, 4, 4, 4, 4, 4, 3, 3]);
+        // The window now holds 2, 2, 7, 3, 8.
+        assert_eq!(window.quantile(0.0), Some(&2));
+        assert_eq!(window.quantile(0.9), Some(&8));
+        assert_eq!(window.quantile(0.6), Some(&3));
+        assert_eq!((window.len(), window.window()), (5, 5));
+        assert_eq!(format!("{:?}", window), "[2, 2, 7, 3, 8]");
+    }
+
+    #[test]
+    fn test_clear_keeps_positions() {
+        let mut window = WindowQuantiles::new(2);
+        window.push(1);
+        window.push(1);
+        window.push(9);
+        window.clear();
+        assert!(window.is_empty());
+        assert_eq!(window.median(), None);
+        for x in [3, 3, 4] {
+            window.push(x);
+        }
+        assert_eq!((window.select(0), window.select(1)), (Some(&3), Some(&4)));
+        assert_eq!(window.select(2), None);
+    }
+}
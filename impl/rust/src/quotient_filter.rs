@@ -0,0 +1,372 @@
+//! Quotient filter for approximate set membership.
+
+use crate::hashmap::DefaultHashBuilder;
+use std::collections::VecDeque;
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+
+/// The slot's own quotient has a run somewhere in the table.
+const OCCUPIED: u64 = 1;
+/// The remainder here belongs to the same run as the one before it.
+const CONTINUATION: u64 = 2;
+/// The remainder here is not in its canonical slot.
+const SHIFTED: u64 = 4;
+const FLAG_BITS: u32 = 3;
+
+/// A compact approximate membership filter stored in one array of slots.
+///
+/// Each key's fingerprint is split into a quotient, which picks its
+/// canonical slot, and a remainder, which is what gets stored. Remainders
+/// sharing a quotient form a sorted run, and runs that collide push each
+/// other into later slots, linear-probing style, forming clusters. Three
+/// metadata bits per slot let a lookup find the run for any quotient
+/// without storing quotients. Lookups scan one contiguous cluster, the
+/// filter can be walked to recover every fingerprint, and that is what lets
+/// two filters [`merge`](QuotientFilter::merge) without the original keys.
+/// A key outside the set passes with probability about `load * 2^-r` for
+/// `r` remainder bits.
+#[derive(Debug, Clone)]
+pub struct QuotientFilter<K> {
+    /// Each slot holds `remainder << FLAG_BITS | flags`.
+    slots: Vec<u64>,
+    quotient_bits: u32,
+    remainder_bits: u32,
+    len: usize,
+    hash_builder: DefaultHashBuilder,
+    marker: PhantomData<fn(&K)>,
+}
+
+impl<K> QuotientFilter<K> {
+    /// Create a new empty QuotientFilter with `2^quotient_bits` slots,
+    /// each storing a `remainder_bits`-bit remainder.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either bit count is zero, if `quotient_bits` is 32 or
+    /// more, if `remainder_bits` is more than 61, or if together they are
+    /// more than the 64 bits of the hash.
+    pub fn new(quotient_bits: u32, remainder_bits: u32) -> Self {
+        assert!(
+            (1..32).contains(&quotient_bits),
+            "quotient bits must be between 1 and 31"
+        );
+        assert!(
+            (1..=64 - FLAG_BITS).contains(&remainder_bits),
+            "remainder bits must be between 1 and 61"
+        );
+        assert!(
+            quotient_bits + remainder_bits <= 64,
+            "quotient and remainder bits must fit in a 64-bit hash"
+        );
+        Self {
+            slots: vec![0; 1 << quotient_bits],
+            quotient_bits,
+            remainder_bits,
+            len: 0,
+            hash_builder: DefaultHashBuilder::default(),
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of distinct fingerprints in the filter.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the filter contains no fingerprints.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of slots.
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    fn next(&self, slot: usize) -> usize {
+        (slot + 1) & (self.slots.len() - 1)
+    }
+
+    fn prev(&self, slot: usize) -> usize {
+        slot.wrapping_sub(1) & (self.slots.len() - 1)
+    }
+
+    fn has(&self, slot: usize, flag: u64) -> bool {
+        self.slots[slot] & flag != 0
+    }
+
+    fn is_empty_slot(&self, slot: usize) -> bool {
+        self.slots[slot] & (OCCUPIED | CONTINUATION | SHIFTED) == 0
+    }
+
+    fn remainder(&self, slot: usize) -> u64 {
+        self.slots[slot] >> FLAG_BITS
+    }
+
+    /// The slot where the run for `quotient` starts, or would start.
+    fn run_start(&self, quotient: usize) -> usize {
+        // Back up to the start of the cluster, then walk forward run by
+        // run, pairing each occupied quotient with the next run.
+        let mut canonical = quotient;
+        while self.has(canonical, SHIFTED) {
+            canonical = self.prev(canonical);
+        }
+        let mut run = canonical;
+        while canonical != quotient {
+            loop {
+                run = self.next(run);
+                if !self.has(run, CONTINUATION) {
+                    break;
+                }
+            }
+            loop {
+                canonical = self.next(canonical);
+                if self.has(canonical, OCCUPIED) {
+                    break;
+                }
+            }
+        }
+        run
+    }
+
+    /// Split a fingerprint into quotient and remainder.
+    fn split(&self, fingerprint: u64) -> (usize, u64) {
+        let quotient = (fingerprint >> self.remainder_bits) as usize;
+        (quotient, fingerprint & ((1 << self.remainder_bits) - 1))
+    }
+
+    fn contains_fingerprint(&self, fingerprint: u64) -> bool {
+        let (quotient, remainder) = self.split(fingerprint);
+        if !self.has(quotient, OCCUPIED) {
+            return false;
+        }
+        let mut slot = self.run_start(quotient);
+        loop {
+            match self.remainder(slot) {
+                stored if stored == remainder => return true,
+                // Runs are sorted.
+                stored if stored > remainder => return false,
+                _ => {}
+            }
+            slot = self.next(slot);
+            if !self.has(slot, CONTINUATION) {
+                return false;
+            }
+        }
+    }
+
+    /// Insert a fingerprint, returning false if it was already present.
+    fn insert_fingerprint(&mut self, fingerprint: u64) -> bool {
+        if self.len == self.slots.len() {
+            assert!(
+                self.contains_fingerprint(fingerprint),
+                "quotient filter is full"
+            );
+            return false;
+        }
+        let (quotient, remainder) = self.split(fingerprint);
+        if self.is_empty_slot(quotient) {
+            self.slots[quotient] = remainder << FLAG_BITS | OCCUPIED;
+            self.len += 1;
+            return true;
+        }
+        let had_run = self.has(quotient, OCCUPIED);
+        self.slots[quotient] |= OCCUPIED;
+        let start = self.run_start(quotient);
+
+        let mut slot = start;
+        if had_run {
+            // Find the sorted position within the run.
+            loop {
+                let stored = self.remainder(slot);
+                if stored == remainder {
+                    return false;
+                }
+                if stored > remainder {
+                    break;
+                }
+                slot = self.next(slot);
+                if !self.has(slot, CONTINUATION) {
+                    break;
+                }
+            }
+        }
+        let mut entry = remainder << FLAG_BITS;
+        if slot != start {
+            entry |= CONTINUATION;
+        }
+        if slot != quotient {
+            entry |= SHIFTED;
+        }
+        // Shift everything up to the next empty slot along by one. Occupied
+        // bits belong to slots and stay put; the rest moves with its entry.
+        let mut first = true;
+        loop {
+            let was_empty = self.is_empty_slot(slot);
+            let displaced = self.slots[slot] & !OCCUPIED;
+            self.slots[slot] = entry | (self.slots[slot] & OCCUPIED);
+            if was_empty {
+                break;
+            }
+            entry = displaced | SHIFTED;
+            // A new run head demotes the old one to a continuation.
+            if first && had_run && slot == start {
+                entry |= CONTINUATION;
+            }
+            first = false;
+            slot = self.next(slot);
+        }
+        self.len += 1;
+        true
+    }
+
+    /// Every stored fingerprint, in no particular order.
+    fn fingerprints(&self) -> Vec<u64> {
+        let mut fingerprints = Vec::with_capacity(self.len);
+        // Decoding starts at the head of a cluster, where the quotient of
+        // each run is the next occupied slot not yet claimed.
+        let Some(start) = (0..self.slots.len())
+            .find(|&slot| !self.is_empty_slot(slot) && !self.has(slot, SHIFTED))
+        else {
+            return fingerprints;
+        };
+        let mut quotients = VecDeque::new();
+        let mut quotient = 0;
+        let mut slot = start;
+        for _ in 0..self.slots.len() {
+            if self.has(slot, OCCUPIED) {
+                quotients.push_back(slot);
+            }
+            if !self.is_empty_slot(slot) {
+                if !self.has(slot, CONTINUATION) {
+                    quotient = quotients.pop_front().expect("every run has a quotient");
+                }
+                fingerprints.push((quotient as u64) << self.remainder_bits | self.remainder(slot));
+            }
+            slot = self.next(slot);
+        }
+        fingerprints
+    }
+
+    /// Combine two filters built with the same fingerprint size into one
+    /// holding the fingerprints of both.
+    ///
+    /// The result keeps the fingerprint size, so it answers exactly as
+    /// either input would for keys in that input. If the combined count
+    /// would load the current table past three quarters, it gets twice the
+    /// slots and one fewer remainder bit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the filters' fingerprint sizes differ, or if growing would
+    /// leave no remainder bits.
+    pub fn merge(&self, other: &Self) -> Self {
+        let bits = self.quotient_bits + self.remainder_bits;
+        assert_eq!(
+            bits,
+            other.quotient_bits + other.remainder_bits,
+            "merged filters must have the same fingerprint size"
+        );
+        let mut fingerprints = self.fingerprints();
+        fingerprints.extend(other.fingerprints());
+        fingerprints.sort_unstable();
+        fingerprints.dedup();
+
+        let mut quotient_bits = self.quotient_bits.max(other.quotient_bits);
+        if fingerprints.len() * 4 > 3 << quotient_bits {
+            quotient_bits += 1;
+        }
+        let mut merged = Self::new(quotient_bits, bits - quotient_bits);
+        for fingerprint in fingerprints {
+            merged.insert_fingerprint(fingerprint);
+        }
+        merged
+    }
+}
+
+impl<K: Hash> QuotientFilter<K> {
+    fn fingerprint(&self, key: &K) -> u64 {
+        self.hash_builder.hash_one(key) >> (64 - self.quotient_bits - self.remainder_bits)
+    }
+
+    /// Add a key to the filter.
+    ///
+    /// Returns false if the key's fingerprint was already present.
+    ///
+    /// # Panics
+    ///
+    /// Panics if every slot is taken.
+    pub fn insert(&mut self, key: &K) -> bool {
+        self.insert_fingerprint(self.fingerprint(key))
+    }
+
+    /// Returns true if the key may be in the set, and false if it is
+    /// definitely not.
+    pub fn contains(&self, key: &K) -> bool {
+        self.contains_fingerprint(self.fingerprint(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_false_negatives_and_rate() {
+        let mut filter = QuotientFilter::new(14, 8);
+        for key in 0..12_000u64 {
+            filter.insert(&key);
+        }
+        assert!((0..12_000).all(|key| filter.contains(&key)));
+        assert_eq!(filter.fingerprints().len(), filter.len());
+        let false_positives = (12_000..112_000u64)
+            .filter(|key| filter.contains(key))
+            .count();
+        // Expect about 0.73 * 2^-8 of 100,000, or 290.
+        assert!(
+            (150..450).contains(&false_positives),
+            "{} false positives",
+            false_positives
+        );
+    }
+
+    #[test]
+    fn test_full_table_wraps_around() {
+        let mut filter = QuotientFilter::<u64>::new(3, 4);
+        // Every fingerprint in the top quotients forces runs past the end.
+        for fingerprint in [0x7f, 0x7e, 0x70, 0x6f, 0x61, 0x00, 0x7a, 0x6a] {
+            assert!(filter.insert_fingerprint(fingerprint));
+        }
+        assert!(!filter.insert_fingerprint(0x7a));
+        for fingerprint in 0..0x80 {
+            let expected = [0x7f, 0x7e, 0x70, 0x6f, 0x61, 0x00, 0x7a, 0x6a].contains(&fingerprint);
+            assert_eq!(filter.contains_fingerprint(fingerprint), expected);
+        }
+        let mut fingerprints = filter.fingerprints();
+        fingerprints.sort_unstable();
+        assert_eq!(
+            fingerprints,
+            [0x00, 0x61, 0x6a, 0x6f, 0x70, 0x7a, 0x7e, 0x7f]
+        );
+    }
+
+    #[test]
+    fn test_merge_keeps_both_sets() {
+        let mut left = QuotientFilter::new(10, 10);
+        let mut right = QuotientFilter::new(10, 10);
+        for key in 0..600u32 {
+            left.insert(&key);
+            right.insert(&(key + 500));
+        }
+        let merged = left.merge(&right);
+        assert_eq!(merged.capacity(), 2048);
+        assert!((0..1100).all(|key| merged.contains(&key)));
+        assert_eq!(merged.len(), merged.fingerprints().len());
+    }
+
+    #[test]
+    #[should_panic(expected = "64-bit hash")]
+    fn test_fingerprint_wider_than_hash_rejected() {
+        QuotientFilter::<u64>::new(8, 60);
+    }
+}
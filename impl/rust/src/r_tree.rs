@@ -0,0 +1,777 @@
+//! R-tree: dynamic spatial index over axis-aligned boxes.
+
+use crate::bplus_tree::even_chunks;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fmt;
+
+const DEFAULT_FANOUT: usize = 16;
+
+/// An axis-aligned box in `K` dimensions, bounds included. A point is a box
+/// whose corners coincide.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect<const K: usize> {
+    min: [f64; K],
+    max: [f64; K],
+}
+
+impl<const K: usize> Rect<K> {
+    /// Create the box with corners `min` and `max`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a coordinate is NaN or `min` exceeds `max` on some axis.
+    pub fn new(min: [f64; K], max: [f64; K]) -> Self {
+        assert!(
+            (0..K).all(|axis| min[axis] <= max[axis]),
+            "rectangle min must not exceed max or be NaN"
+        );
+        Self { min, max }
+    }
+
+    /// Create the box holding only `point`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a coordinate is NaN.
+    pub fn point(point: [f64; K]) -> Self {
+        Self::new(point, point)
+    }
+
+    /// Returns the lower corner.
+    pub fn min(&self) -> &[f64; K] {
+        &self.min
+    }
+
+    /// Returns the upper corner.
+    pub fn max(&self) -> &[f64; K] {
+        &self.max
+    }
+
+    /// Returns the product of the side lengths.
+    pub fn area(&self) -> f64 {
+        (0..K).map(|axis| self.max[axis] - self.min[axis]).product()
+    }
+
+    /// Returns the smallest box containing both boxes.
+    pub fn union(&self, other: &Self) -> Self {
+        let mut union = *self;
+        for axis in 0..K {
+            union.min[axis] = union.min[axis].min(other.min[axis]);
+            union.max[axis] = union.max[axis].max(other.max[axis]);
+        }
+        union
+    }
+
+    /// Check if the boxes share at least one point.
+    pub fn intersects(&self, other: &Self) -> bool {
+        (0..K).all(|axis| self.min[axis] <= other.max[axis] && other.min[axis] <= self.max[axis])
+    }
+
+    /// Check if `other` lies entirely inside this box.
+    pub fn contains(&self, other: &Self) -> bool {
+        (0..K).all(|axis| self.min[axis] <= other.min[axis] && other.max[axis] <= self.max[axis])
+    }
+
+    /// Returns the Euclidean distance from `point` to the nearest point of
+    /// the box, 0 if the box contains it.
+    pub fn distance_to(&self, point: &[f64; K]) -> f64 {
+        self.distance_squared_to(point).sqrt()
+    }
+
+    fn distance_squared_to(&self, point: &[f64; K]) -> f64 {
+        (0..K)
+            .map(|axis| {
+                let gap = (self.min[axis] - point[axis])
+                    .max(point[axis] - self.max[axis])
+                    .max(0.0);
+                gap * gap
+            })
+            .sum()
+    }
+
+    fn center(&self, axis: usize) -> f64 {
+        (self.min[axis] + self.max[axis]) / 2.0
+    }
+
+    /// How much the area grows to take in `other`.
+    fn enlargement(&self, other: &Self) -> f64 {
+        self.union(other).area() - self.area()
+    }
+}
+
+/// The smallest box containing every entry's box.
+fn bounds<const K: usize, E>(entries: &[(Rect<K>, E)]) -> Rect<K> {
+    let (first, rest) = entries.split_first().expect("bounds of an empty node");
+    rest.iter()
+        .fold(first.0, |bounds, (rect, _)| bounds.union(rect))
+}
+
+/// Split an overflowing node's entries into two groups of at least `min`
+/// each, using Guttman's quadratic split.
+///
+/// The two entries that would waste the most area together seed the
+/// groups. Then the entry with the strongest preference for one group joins
+/// it, until one group needs every remaining entry to reach `min`.
+fn quadratic_split<const K: usize, E>(
+    mut entries: Vec<(Rect<K>, E)>,
+    min: usize,
+) -> [Vec<(Rect<K>, E)>; 2] {
+    let mut seeds = (0, 1);
+    let mut worst = f64::NEG_INFINITY;
+    for i in 0..entries.len() {
+        for j in i + 1..entries.len() {
+            let (a, b) = (&entries[i].0, &entries[j].0);
+            let waste = a.union(b).area() - a.area() - b.area();
+            if waste > worst {
+                worst = waste;
+                seeds = (i, j);
+            }
+        }
+    }
+    // Remove the later seed first so the earlier index stays valid.
+    let second = entries.swap_remove(seeds.1);
+    let first = entries.swap_remove(seeds.0);
+    let mut bounds = [first.0, second.0];
+    let mut groups = [vec![first], vec![second]];
+    while !entries.is_empty() {
+        for group in 0..2 {
+            if groups[group].len() + entries.len() == min {
+                groups[group].append(&mut entries);
+                return groups;
+            }
+        }
+        let growth = |rect: &Rect<K>| [bounds[0].enlargement(rect), bounds[1].enlargement(rect)];
+        let (pick, _) = entries
+            .iter()
+            .enumerate()
+            .map(|(i, (rect, _))| {
+                let [a, b] = growth(rect);
+                (i, (a - b).abs())
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .expect("entries left to assign");
+        let entry = entries.swap_remove(pick);
+        let [a, b] = growth(&entry.0);
+        let group = match a.total_cmp(&b) {
+            Ordering::Less => 0,
+            Ordering::Greater => 1,
+            Ordering::Equal => match bounds[0].area().total_cmp(&bounds[1].area()) {
+                Ordering::Less => 0,
+                Ordering::Greater => 1,
+                Ordering::Equal => usize::from(groups[1].len() < groups[0].len()),
+            },
+        };
+        bounds[group] = bounds[group].union(&entry.0);
+        groups[group].push(entry);
+    }
+    groups
+}
+
+/// Cut `entries` into groups of the given `sizes` by Sort-Tile-Recursive:
+/// sort on the first axis from `axis`, cut into vertical slabs of whole
+/// groups, and tile each slab on the remaining axes.
+fn tile<const K: usize, E>(
+    mut entries: Vec<(Rect<K>, E)>,
+    axis: usize,
+    sizes: &[usize],
+    groups: &mut Vec<Vec<(Rect<K>, E)>>,
+) {
+    if sizes.len() == 1 {
+        groups.push(entries);
+        return;
+    }
+    entries.sort_by(|a, b| a.0.center(axis).total_cmp(&b.0.center(axis)));
+    // The smallest slab count s with s^(axes left) >= groups, so each axis
+    // gets about the same number of cuts.
+    let slab_count = if axis + 1 == K {
+        sizes.len()
+    } else {
+        (1..)
+            .find(|&s: &usize| s.saturating_pow((K - axis) as u32) >= sizes.len())
+            .expect("some slab count suffices")
+    };
+    let mut rest = entries.into_iter();
+    let mut sizes = sizes;
+    for slab_groups in even_chunks(sizes.len(), slab_count) {
+        let (slab_sizes, later) = sizes.split_at(slab_groups);
+        sizes = later;
+        let slab = rest.by_ref().take(slab_sizes.iter().sum()).collect();
+        if axis + 1 == K {
+            groups.push(slab);
+        } else {
+            tile(slab, axis + 1, slab_sizes, groups);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Node<T, const K: usize> {
+    Leaf(Vec<(Rect<K>, T)>),
+    /// Children with their bounding boxes.
+    Internal(Vec<(Rect<K>, usize)>),
+}
+
+impl<T, const K: usize> Node<T, K> {
+    fn len(&self) -> usize {
+        match self {
+            Node::Leaf(entries) => entries.len(),
+            Node::Internal(entries) => entries.len(),
+        }
+    }
+
+    fn bounds(&self) -> Rect<K> {
+        match self {
+            Node::Leaf(entries) => bounds(entries),
+            Node::Internal(entries) => bounds(entries),
+        }
+    }
+}
+
+/// A node, or an entry of a leaf, awaiting a nearest-neighbor search,
+/// ordered so that `BinaryHeap` keeps the closest on top.
+struct Pending {
+    distance_squared: f64,
+    node: usize,
+    /// The entry's position in the leaf, or `None` for the node itself.
+    entry: Option<usize>,
+}
+
+impl PartialEq for Pending {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Pending {}
+
+impl PartialOrd for Pending {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Pending {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.distance_squared.total_cmp(&self.distance_squared)
+    }
+}
+
+/// Boxes in `K` dimensions, each carrying a value, grouped into a balanced
+/// tree of bounding boxes.
+///
+/// Every node holds up to `fanout` entries, and every node but the root at
+/// least 40% of that. An entry is a value's box in a leaf, or a child and
+/// the smallest box around everything under it in an internal node, so a
+/// query descends only into children whose boxes can matter. Insertion
+/// follows the child whose box grows least and splits an overflowing node
+/// with Guttman's quadratic split, pushing the split up like a B-tree.
+/// Removal dissolves nodes left underfull and reinserts their entries.
+/// Boxes of siblings may overlap, so unlike a B-tree a lookup can descend
+/// several paths. [`bulk_load`](Self::bulk_load) packs a known set of boxes
+/// with Sort-Tile-Recursive instead, which gives full nodes with little
+/// overlap. Unlike a [`KdTree`](crate::KdTree), the tree indexes boxes as
+/// well as points and takes updates in place, the way database spatial
+/// indexes do. Nodes live in an arena and refer to each other by index.
+#[derive(Clone)]
+pub struct RTree<T, const K: usize> {
+    nodes: Vec<Node<T, K>>,
+    /// Arena slots freed by removals.
+    free: Vec<usize>,
+    root: usize,
+    len: usize,
+    fanout: usize,
+}
+
+impl<T, const K: usize> Default for RTree<T, K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const K: usize> RTree<T, K> {
+    /// Create a new empty RTree.
+    pub fn new() -> Self {
+        Self::with_fanout(DEFAULT_FANOUT)
+    }
+
+    /// Create a new empty RTree whose nodes hold at most `fanout` entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fanout` is less than 4.
+    pub fn with_fanout(fanout: usize) -> Self {
+        assert!(fanout >= 4, "R-tree fanout must be at least 4");
+        Self {
+            nodes: vec![Node::Leaf(Vec::new())],
+            free: Vec::new(),
+            root: 0,
+            len: 0,
+            fanout,
+        }
+    }
+
+    /// Build a tree over `entries` with Sort-Tile-Recursive packing.
+    pub fn bulk_load(entries: Vec<(Rect<K>, T)>) -> Self {
+        Self::bulk_load_with_fanout(DEFAULT_FANOUT, entries)
+    }
+
+    /// Build a tree over `entries` with Sort-Tile-Recursive packing, with
+    /// nodes of at most `fanout` entries.
+    ///
+    /// Leaves are cut from the entries sorted into slabs along each axis in
+    /// turn, then parents from the leaves the same way, level by level. Each
+    /// level has as few nodes as `fanout` allows, with sizes differing by at
+    /// most one. O(n log n).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fanout` is less than 4.
+    pub fn bulk_load_with_fanout(fanout: usize, entries: Vec<(Rect<K>, T)>) -> Self {
+        let mut tree = Self::with_fanout(fanout);
+        tree.len = entries.len();
+        if entries.is_empty() {
+            return tree;
+        }
+        tree.nodes.clear();
+        let mut leaves = Vec::new();
+        tree.pack(entries, &mut leaves);
+        let mut level: Vec<(Rect<K>, usize)> = leaves
+            .into_iter()
+            .map(|entries| {
+                let bounds = bounds(&entries);
+                (bounds, tree.alloc(Node::Leaf(entries)))
+            })
+            .collect();
+        while level.len() > 1 {
+            let mut parents = Vec::new();
+            tree.pack(level, &mut parents);
+            level = parents
+                .into_iter()
+                .map(|entries| {
+                    let bounds = bounds(&entries);
+                    (bounds, tree.alloc(Node::Internal(entries)))
+                })
+                .collect();
+        }
+        tree.root = level[0].1;
+        tree
+    }
+
+    /// Tile `entries` into as few nodes' worth as the fanout allows.
+    fn pack<E>(&self, entries: Vec<(Rect<K>, E)>, groups: &mut Vec<Vec<(Rect<K>, E)>>) {
+        let count = (entries.len() + self.fanout - 1) / self.fanout;
+        let sizes: Vec<usize> = even_chunks(entries.len(), count).collect();
+        tile(entries, 0, &sizes, groups);
+    }
+
+    /// Returns the maximum number of entries per node.
+    pub fn fanout(&self) -> usize {
+        self.fanout
+    }
+
+    /// Returns the minimum number of entries in every node but the root.
+    fn min_entries(&self) -> usize {
+        self.fanout * 2 / 5
+    }
+
+    /// Returns the number of entries in the tree.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the tree contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Clear all entries from the tree.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.nodes.push(Node::Leaf(Vec::new()));
+        self.free.clear();
+        self.root = 0;
+        self.len = 0;
+    }
+
+    fn alloc(&mut self, node: Node<T, K>) -> usize {
+        match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = node;
+                index
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    fn release(&mut self, index: usize) -> Node<T, K> {
+        self.free.push(index);
+        std::mem::replace(&mut self.nodes[index], Node::Leaf(Vec::new()))
+    }
+
+    /// Insert a value with its box.
+    pub fn insert(&mut self, rect: Rect<K>, value: T) {
+        self.insert_entry(rect, value);
+        self.len += 1;
+    }
+
+    fn insert_entry(&mut self, rect: Rect<K>, value: T) {
+        if let Some(sibling) = self.insert_at(self.root, rect, value) {
+            let root = (self.nodes[self.root].bounds(), self.root);
+            self.root = self.alloc(Node::Internal(vec![root, sibling]));
+        }
+    }
+
+    /// Insert into the subtree at `index`, returning the new sibling and its
+    /// box if the node split.
+    fn insert_at(&mut self, index: usize, rect: Rect<K>, value: T) -> Option<(Rect<K>, usize)> {
+        let min = self.min_entries();
+        let split = match &mut self.nodes[index] {
+            Node::Leaf(entries) => {
+                entries.push((rect, value));
+                if entries.len() <= self.fanout {
+                    return None;
+                }
+                let [kept, moved] = quadratic_split(std::mem::take(entries), min);
+                *entries = kept;
+                Node::Leaf(moved)
+            }
+            Node::Internal(entries) => {
+                // The child whose box grows least, then the smallest.
+                let (i, _) = entries
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (bounds, _))| (i, (bounds.enlargement(&rect), bounds.area())))
+                    .min_by(|(_, a), (_, b)| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)))
+                    .expect("internal nodes have children");
+                let child = entries[i].1;
+                let child_split = self.insert_at(child, rect, value);
+                let child_bounds = self.nodes[child].bounds();
+                let Node::Internal(entries) = &mut self.nodes[index] else {
+                    unreachable!("expected an internal node")
+                };
+                entries[i].0 = child_bounds;
+                entries.extend(child_split);
+                if entries.len() <= self.fanout {
+                    return None;
+                }
+                let [kept, moved] = quadratic_split(std::mem::take(entries), min);
+                *entries = kept;
+                Node::Internal(moved)
+            }
+        };
+        let bounds = split.bounds();
+        Some((bounds, self.alloc(split)))
+    }
+
+    /// Remove an entry whose box equals `rect` and whose value equals
+    /// `value`.
+    ///
+    /// Returns the stored value if such an entry existed.
+    pub fn remove(&mut self, rect: &Rect<K>, value: &T) -> Option<T>
+    where
+        T: PartialEq,
+    {
+        let mut orphans = Vec::new();
+        let removed = self.remove_at(self.root, rect, value, &mut orphans)?;
+        self.len -= 1;
+        // A root left with one child hands the root over to it.
+        while let Node::Internal(entries) = &self.nodes[self.root] {
+            if entries.len() != 1 {
+                break;
+            }
+            let child = entries[0].1;
+            self.release(self.root);
+            self.root = child;
+        }
+        for (rect, value) in orphans {
+            self.insert_entry(rect, value);
+        }
+        Some(removed)
+    }
+
+    /// Remove from the subtree at `index`, collecting the entries of any
+    /// node dissolved for being underfull into `orphans`.
+    fn remove_at(
+        &mut self,
+        index: usize,
+        rect: &Rect<K>,
+        value: &T,
+        orphans: &mut Vec<(Rect<K>, T)>,
+    ) -> Option<T>
+    where
+        T: PartialEq,
+    {
+        let candidates: Vec<(usize, usize)> = match &mut self.nodes[index] {
+            Node::Leaf(entries) => {
+                let i = entries.iter().position(|(r, v)| r == rect && v == value)?;
+                return Some(entries.swap_remove(i).1);
+            }
+            Node::Internal(entries) => entries
+                .iter()
+                .enumerate()
+                .filter(|(_, (bounds, _))| bounds.contains(rect))
+                .map(|(i, &(_, child))| (i, child))
+                .collect(),
+        };
+        for (i, child) in candidates {
+            let Some(removed) = self.remove_at(child, rect, value, orphans) else {
+                continue;
+            };
+            if self.nodes[child].len() < self.min_entries() {
+                self.dissolve(child, orphans);
+                let Node::Internal(entries) = &mut self.nodes[index] else {
+                    unreachable!("expected an internal node")
+                };
+                entries.swap_remove(i);
+            } else {
+                let bounds = self.nodes[child].bounds();
+                let Node::Internal(entries) = &mut self.nodes[index] else {
+                    unreachable!("expected an internal node")
+                };
+                entries[i].0 = bounds;
+            }
+            return Some(removed);
+        }
+        None
+    }
+
+    /// Free the subtree at `index`, collecting its entries into `orphans`.
+    fn dissolve(&mut self, index: usize, orphans: &mut Vec<(Rect<K>, T)>) {
+        match self.release(index) {
+            Node::Leaf(entries) => orphans.extend(entries),
+            Node::Internal(entries) => {
+                for (_, child) in entries {
+                    self.dissolve(child, orphans);
+                }
+            }
+        }
+    }
+
+    /// Iterate over the boxes and their values, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&Rect<K>, &T)> {
+        // Freed slots hold empty leaves, so every leaf entry is live.
+        self.nodes
+            .iter()
+            .flat_map(|node| match node {
+                Node::Leaf(entries) => entries.as_slice(),
+                Node::Internal(_) => &[],
+            })
+            .map(|(rect, value)| (rect, value))
+    }
+
+    /// Returns every entry whose box intersects `window`, in no particular
+    /// order.
+    pub fn intersecting(&self, window: &Rect<K>) -> Vec<(&Rect<K>, &T)> {
+        let mut found = Vec::new();
+        let mut stack = vec![self.root];
+        while let Some(index) = stack.pop() {
+            match &self.nodes[index] {
+                Node::Leaf(entries) => found.extend(
+                    entries
+                        .iter()
+                        .filter(|(rect, _)| rect.intersects(window))
+                        .map(|(rect, value)| (rect, value)),
+                ),
+                Node::Internal(entries) => stack.extend(
+                    entries
+                        .iter()
+                        .filter(|(bounds, _)| bounds.intersects(window))
+                        .map(|&(_, child)| child),
+                ),
+            }
+        }
+        found
+    }
+
+    /// Returns the `k` entries whose boxes are nearest to `point`, nearest
+    /// first, with their distances, or every entry if there are fewer than
+    /// `k`.
+    ///
+    /// Searches best first: nodes and entries wait in one queue ordered by
+    /// their distance to `point`, and since a node's box is never farther
+    /// than anything under it, entries come out in order of distance.
+    /// Entries at equal distance are reported in no particular order.
+    pub fn nearest(&self, point: &[f64; K], k: usize) -> Vec<(&Rect<K>, &T, f64)> {
+        let mut found = Vec::with_capacity(k.min(self.len));
+        let mut queue = BinaryHeap::new();
+        if self.len > 0 {
+            queue.push(Pending {
+                distance_squared: 0.0,
+                node: self.root,
+                entry: None,
+            });
+        }
+        while found.len() < k {
+            let Some(pending) = queue.pop() else {
+                break;
+            };
+            match (&self.nodes[pending.node], pending.entry) {
+                (Node::Leaf(entries), Some(i)) => {
+                    let (rect, value) = &entries[i];
+                    found.push((rect, value, pending.distance_squared.sqrt()));
+                }
+                (Node::Leaf(entries), None) => {
+                    queue.extend(entries.iter().enumerate().map(|(i, (rect, _))| Pending {
+                        distance_squared: rect.distance_squared_to(point),
+                        node: pending.node,
+                        entry: Some(i),
+                    }))
+                }
+                (Node::Internal(entries), _) => {
+                    queue.extend(entries.iter().map(|(bounds, child)| Pending {
+                        distance_squared: bounds.distance_squared_to(point),
+                        node: *child,
+                        entry: None,
+                    }))
+                }
+            }
+        }
+        found
+    }
+
+    /// Checks that every node but the root holds between the minimum and
+    /// maximum number of entries, that every child's box is exactly the
+    /// bounds of its entries, that all leaves sit at the same depth, and
+    /// that the entry count matches `len`.
+    pub fn debug_validate(&self) {
+        fn walk<T, const K: usize>(
+            tree: &RTree<T, K>,
+            index: usize,
+            depth: usize,
+            leaf_depth: &mut Option<usize>,
+        ) -> usize {
+            let node = &tree.nodes[index];
+            assert!(node.len() <= tree.fanout, "node has too many entries");
+            if index == tree.root {
+                assert!(
+                    matches!(node, Node::Leaf(_)) || node.len() >= 2,
+                    "internal root has fewer than two children"
+                );
+            } else {
+                assert!(node.len() >= tree.min_entries(), "node has too few entries");
+            }
+            match node {
+                Node::Leaf(entries) => {
+                    assert_eq!(
+                        *leaf_depth.get_or_insert(depth),
+                        depth,
+                        "leaves at different depths"
+                    );
+                    entries.len()
+                }
+                Node::Internal(entries) => entries
+                    .iter()
+                    .map(|(bounds, child)| {
+                        assert_eq!(
+                            *bounds,
+                            tree.nodes[*child].bounds(),
+                            "child box is not the bounds of its entries"
+                        );
+                        walk(tree, *child, depth + 1, leaf_depth)
+                    })
+                    .sum(),
+            }
+        }
+        let count = walk(self, self.root, 0, &mut None);
+        assert_eq!(count, self.len, "entry count does not match len");
+    }
+}
+
+impl<T, const K: usize> FromIterator<(Rect<K>, T)> for RTree<T, K> {
+    fn from_iter<I: IntoIterator<Item = (Rect<K>, T)>>(iter: I) -> Self {
+        Self::bulk_load(iter.into_iter().collect())
+    }
+}
+
+impl<T, const K: usize> fmt::Debug for RTree<T, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RTree")
+            .field("len", &self.len)
+            .field("fanout", &self.fanout)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate<T, const K: usize>(tree: &RTree<T, K>) {
+        if cfg!(debug_assertions) {
+            tree.debug_validate();
+        }
+    }
+
+    fn cell(x: usize, y: usize) -> Rect<2> {
+        let (x, y) = (x as f64, y as f64);
+        Rect::new([x, y], [x + 0.5, y + 0.5])
+    }
+
+    #[test]
+    fn test_insert_and_query() {
+        let mut tree = RTree::with_fanout(4);
+        for x in 0..10 {
+            for y in 0..10 {
+                tree.insert(cell(x, y), (x, y));
+                validate(&tree);
+            }
+        }
+        assert_eq!(tree.len(), 100);
+        let mut hits: Vec<_> = tree
+            .intersecting(&Rect::new([2.5, 2.5], [3.0, 4.2]))
+            .into_iter()
+            .map(|(_, &value)| value)
+            .collect();
+        hits.sort_unstable();
+        assert_eq!(hits, [(2, 2), (2, 3), (2, 4), (3, 2), (3, 3), (3, 4)]);
+        let nearest = tree.nearest(&[7.2, 1.2], 2);
+        assert_eq!(*nearest[0].1, (7, 1));
+        assert_eq!(nearest[0].2, 0.0);
+        assert!((nearest[1].2 - 0.7).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_remove_condenses() {
+        let mut tree = RTree::with_fanout(4);
+        for i in 0..200 {
+            tree.insert(Rect::point([i as f64, (i * 7 % 13) as f64]), i);
+        }
+        for i in (0..200).filter(|i| i % 10 != 3) {
+            let rect = Rect::point([i as f64, (i * 7 % 13) as f64]);
+            assert_eq!(tree.remove(&rect, &i), Some(i));
+            assert_eq!(tree.remove(&rect, &i), None);
+            validate(&tree);
+        }
+        assert_eq!(tree.len(), 20);
+        let mut left: Vec<_> = tree.iter().map(|(_, &i)| i).collect();
+        left.sort_unstable();
+        assert_eq!(left, (3..200).step_by(10).collect::<Vec<_>>());
+        tree.clear();
+        assert!(tree.is_empty());
+        assert!(tree.nearest(&[0.0, 0.0], 3).is_empty());
+    }
+
+    #[test]
+    fn test_bulk_load_packs_full_nodes() {
+        let entries: Vec<_> = (0..30)
+            .flat_map(|x| (0..30).map(move |y| (cell(x, y), (x, y))))
+            .collect();
+        let tree = RTree::bulk_load(entries);
+        validate(&tree);
+        // 900 entries need 57 leaves of 16, then 4 parents and a root.
+        assert_eq!(tree.nodes.len(), 57 + 4 + 1);
+        assert_eq!(
+            tree.intersecting(&Rect::new([10.2, 0.0], [11.2, 29.5]))
+                .len(),
+            60
+        );
+        let mut tree = tree;
+        tree.insert(cell(40, 40), (40, 40));
+        assert_eq!(*tree.nearest(&[41.0, 41.0], 1)[0].1, (40, 40));
+        validate(&tree);
+    }
+}
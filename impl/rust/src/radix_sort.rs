@@ -0,0 +1,338 @@
+//! Linear-time sorts on integer keys: LSD and MSD radix sort, and counting
+//! sort for keys from a small alphabet.
+//!
+//! Radix sorts never compare two elements. They read each key a byte at a
+//! time and place elements by the bytes' values, so they take O(n·w) for
+//! w-byte keys however the input is ordered. Elements are sorted by the
+//! key [`RadixKey`] extracts from them, which for the primitive integers
+//! is the value itself.
+
+use std::ptr;
+
+/// Buckets of this many elements or fewer are finished by insertion sort in
+/// [`msd_radix_sort`], where a 256-way split would cost more than it saves.
+const SMALL: usize = 32;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// A fixed-width integer key, read a byte at a time from the most
+/// significant end.
+///
+/// Comparing the bytes in order gives the same result as comparing the
+/// keys, so for signed keys the sign bit is flipped first. Implemented for
+/// the primitive integers only: the sorts move elements bitwise between
+/// reading bytes and rely on it never panicking.
+pub trait Radix: Copy + Ord + sealed::Sealed {
+    /// The number of bytes in a key.
+    const BYTES: usize;
+
+    /// Returns byte `i` of the key, 0 being the most significant.
+    fn byte(self, i: usize) -> u8;
+}
+
+/// Types sorted by an integer key, such as a struct by one of its fields.
+///
+/// ```
+/// use dsa_lab::radix_sort::{lsd_radix_sort, RadixKey};
+///
+/// struct Order {
+///     id: u32,
+///     price: i64,
+/// }
+///
+/// impl RadixKey for Order {
+///     type Key = i64;
+///     fn key(&self) -> i64 {
+///         self.price
+///     }
+/// }
+///
+/// let mut orders = vec![Order { id: 1, price: 30 }, Order { id: 2, price: -5 }];
+/// lsd_radix_sort(&mut orders);
+/// assert_eq!(orders[0].id, 2);
+/// ```
+pub trait RadixKey {
+    /// The type of the key.
+    type Key: Radix;
+
+    /// Returns the key to sort by.
+    fn key(&self) -> Self::Key;
+}
+
+macro_rules! impl_radix {
+    ($($t:ty => $bits:ty, $flip:expr;)*) => {
+        $(
+            impl sealed::Sealed for $t {}
+
+            impl Radix for $t {
+                const BYTES: usize = std::mem::size_of::<$t>();
+
+                #[inline]
+                fn byte(self, i: usize) -> u8 {
+                    let bits = self as $bits ^ $flip;
+                    (bits >> (8 * (Self::BYTES - 1 - i))) as u8
+                }
+            }
+
+            impl RadixKey for $t {
+                type Key = $t;
+
+                #[inline]
+                fn key(&self) -> $t {
+                    *self
+                }
+            }
+        )*
+    };
+}
+
+impl_radix! {
+    u8 => u8, 0;
+    u16 => u16, 0;
+    u32 => u32, 0;
+    u64 => u64, 0;
+    u128 => u128, 0;
+    usize => usize, 0;
+    i8 => u8, 1 << 7;
+    i16 => u16, 1 << 15;
+    i32 => u32, 1 << 31;
+    i64 => u64, 1 << 63;
+    i128 => u128, 1 << 127;
+    isize => usize, 1 << (usize::BITS - 1);
+}
+
+/// Sort `v` by least-significant-digit radix sort: a stable counting pass
+/// per key byte, from the lowest byte up.
+///
+/// Each pass moves every element from `v` to a buffer of the same length,
+/// or back, in the order of the byte under consideration; being stable,
+/// it keeps the order the lower bytes gave. A pass is skipped when all
+/// keys share its byte, so small values in a wide key type cost little.
+/// Takes O(n·w) for w-byte keys and n extra elements plus n keys of
+/// space. Stable.
+pub fn lsd_radix_sort<T: RadixKey>(v: &mut [T]) {
+    let n = v.len();
+    if n < 2 {
+        return;
+    }
+    // Read every key once up front; from here on nothing calls user code,
+    // so nothing can panic while elements are moved bitwise.
+    let mut keys: Vec<T::Key> = v.iter().map(RadixKey::key).collect();
+    let mut next_keys = keys.clone();
+    // Its length stays zero, so it never drops what it holds.
+    let mut buffer: Vec<T> = Vec::with_capacity(n);
+    let mut in_buffer = false;
+    for i in (0..T::Key::BYTES).rev() {
+        let mut offsets = [0usize; 256];
+        for key in &keys {
+            offsets[key.byte(i) as usize] += 1;
+        }
+        if offsets.contains(&n) {
+            continue;
+        }
+        let mut start = 0;
+        for offset in offsets.iter_mut() {
+            start += std::mem::replace(offset, start);
+        }
+        let (source, target) = if in_buffer {
+            (buffer.as_ptr(), v.as_mut_ptr())
+        } else {
+            (v.as_ptr(), buffer.as_mut_ptr())
+        };
+        for (j, &key) in keys.iter().enumerate() {
+            let slot = &mut offsets[key.byte(i) as usize];
+            // SAFETY: both allocations hold `n` elements, `j < n`, and the
+            // offsets partition `0..n`, so each target slot is written once
+            // per pass and each source element is read once.
+            unsafe { ptr::copy_nonoverlapping(source.add(j), target.add(*slot), 1) };
+            next_keys[*slot] = key;
+            *slot += 1;
+        }
+        std::mem::swap(&mut keys, &mut next_keys);
+        in_buffer = !in_buffer;
+    }
+    if in_buffer {
+        // SAFETY: the last pass left every element in the buffer, and `v`
+        // holds only stale copies that are overwritten without dropping.
+        unsafe { ptr::copy_nonoverlapping(buffer.as_ptr(), v.as_mut_ptr(), n) };
+    }
+}
+
+/// Sort `v` by most-significant-digit radix sort: split by the top key
+/// byte into 256 buckets, then sort each bucket by the next byte.
+///
+/// The split is done in place, American flag style: each element is
+/// swapped straight into the next free slot of its bucket. Buckets of up
+/// to 32 elements are finished by insertion sort, and recursion stops at
+/// the last byte, so keys that differ early finish early. Takes O(n·w)
+/// for w-byte keys with no buffer, but reads keys far more often than
+/// [`lsd_radix_sort`]. Not stable.
+pub fn msd_radix_sort<T: RadixKey>(v: &mut [T]) {
+    msd_from(v, 0);
+}
+
+fn msd_from<T: RadixKey>(v: &mut [T], mut byte: usize) {
+    let n = v.len();
+    if n <= SMALL {
+        insertion_sort_by_key(v);
+        return;
+    }
+    let mut counts = [0usize; 256];
+    loop {
+        if byte == T::Key::BYTES {
+            return;
+        }
+        counts.fill(0);
+        for x in v.iter() {
+            counts[x.key().byte(byte) as usize] += 1;
+        }
+        // A byte every key shares splits nothing; move on to the next.
+        if !counts.contains(&n) {
+            break;
+        }
+        byte += 1;
+    }
+    // Each bucket's next unfilled slot, and where it ends.
+    let mut heads = [0usize; 256];
+    let mut ends = [0usize; 256];
+    let mut start = 0;
+    for b in 0..256 {
+        heads[b] = start;
+        start += counts[b];
+        ends[b] = start;
+    }
+    for b in 0..256 {
+        while heads[b] < ends[b] {
+            let d = v[heads[b]].key().byte(byte) as usize;
+            if d == b {
+                heads[b] += 1;
+            } else {
+                v.swap(heads[b], heads[d]);
+                heads[d] += 1;
+            }
+        }
+    }
+    let mut start = 0;
+    for count in counts {
+        msd_from(&mut v[start..start + count], byte + 1);
+        start += count;
+    }
+}
+
+fn insertion_sort_by_key<T: RadixKey>(v: &mut [T]) {
+    for i in 1..v.len() {
+        let mut j = i;
+        while j > 0 && v[j].key() < v[j - 1].key() {
+            v.swap(j, j - 1);
+            j -= 1;
+        }
+    }
+}
+
+/// Sort `v` by counting sort on `key`, which maps each element into
+/// `0..alphabet`.
+///
+/// Counts each key's elements, then moves each element straight to its
+/// place through a buffer. Takes O(n + alphabet), so it suits keys drawn
+/// from a small set: bytes, DNA bases, grades, priorities. Stable.
+///
+/// # Panics
+///
+/// Panics if `key` returns a value of `alphabet` or more. It does so before
+/// any element has moved.
+pub fn counting_sort<T>(v: &mut [T], alphabet: usize, mut key: impl FnMut(&T) -> usize) {
+    let n = v.len();
+    let keys: Vec<usize> = v.iter().map(&mut key).collect();
+    let mut offsets = vec![0usize; alphabet];
+    for &k in &keys {
+        assert!(k < alphabet, "key out of the alphabet");
+        offsets[k] += 1;
+    }
+    let mut start = 0;
+    for offset in offsets.iter_mut() {
+        start += std::mem::replace(offset, start);
+    }
+    let mut buffer: Vec<T> = Vec::with_capacity(n);
+    let (source, target) = (v.as_mut_ptr(), buffer.as_mut_ptr());
+    // SAFETY: the offsets partition `0..n`, so every element is moved to a
+    // distinct slot of the buffer and then all of them back, with nothing
+    // in between that could panic. The buffer's length stays zero, so it
+    // drops none of them.
+    unsafe {
+        for (j, &k) in keys.iter().enumerate() {
+            ptr::copy_nonoverlapping(source.add(j), target.add(offsets[k]), 1);
+            offsets[k] += 1;
+        }
+        ptr::copy_nonoverlapping(target, source, n);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signed_keys_order() {
+        let mut v: Vec<i32> = vec![5, -1, i32::MIN, 0, i32::MAX, -300, 300, -1];
+        let mut expected = v.clone();
+        expected.sort();
+        let mut msd = v.clone();
+        lsd_radix_sort(&mut v);
+        msd_radix_sort(&mut msd);
+        assert_eq!(v, expected);
+        assert_eq!(msd, expected);
+        assert!((-1i8).byte(0) < 0i8.byte(0));
+        assert_eq!(0x1234u16.byte(0), 0x12);
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Record {
+        id: usize,
+        score: u16,
+        name: String,
+    }
+
+    impl RadixKey for Record {
+        type Key = u16;
+        fn key(&self) -> u16 {
+            self.score
+        }
+    }
+
+    #[test]
+    fn test_sort_structs_by_field() {
+        let records: Vec<Record> = (0..500)
+            .map(|id| Record {
+                id,
+                score: ((id * 7919) % 1000) as u16,
+                name: format!("r{}", id),
+            })
+            .collect();
+        let mut expected = records.clone();
+        expected.sort_by_key(|r| r.score);
+        let mut lsd = records.clone();
+        lsd_radix_sort(&mut lsd);
+        assert_eq!(lsd, expected, "stable, like sort_by_key");
+        let mut msd = records.clone();
+        msd_radix_sort(&mut msd);
+        assert!(msd.windows(2).all(|w| w[0].score <= w[1].score));
+        let mut counted = records;
+        counting_sort(&mut counted, 1000, |r| r.score as usize);
+        assert_eq!(counted, expected);
+    }
+
+    #[test]
+    fn test_counting_sort_stable() {
+        let mut v: Vec<(u8, usize)> = (0..300).map(|i| ((i * 13 % 7) as u8, i)).collect();
+        counting_sort(&mut v, 7, |&(k, _)| k as usize);
+        for w in v.windows(2) {
+            assert!(w[0].0 < w[1].0 || (w[0].0 == w[1].0 && w[0].1 < w[1].1));
+        }
+        let mut empty: Vec<u8> = Vec::new();
+        counting_sort(&mut empty, 0, |&b| b as usize);
+        lsd_radix_sort(&mut empty);
+        msd_radix_sort(&mut empty);
+    }
+}
@@ -0,0 +1,379 @@
+//! Radix (PATRICIA) trie keyed by byte strings.
+
+#[derive(Debug, Clone)]
+struct Node<V> {
+    /// The bytes on the edge into this node; empty only at the root.
+    label: Vec<u8>,
+    value: Option<V>,
+    /// Sorted by the first byte of their labels, which are all distinct.
+    children: Vec<Node<V>>,
+}
+
+impl<V> Node<V> {
+    fn new(label: Vec<u8>, value: Option<V>) -> Self {
+        Self {
+            label,
+            value,
+            children: Vec::new(),
+        }
+    }
+
+    /// The position of the child whose label starts with `byte`, or where
+    /// it would be inserted.
+    fn find_child(&self, byte: u8) -> Result<usize, usize> {
+        self.children
+            .binary_search_by_key(&byte, |child| child.label[0])
+    }
+}
+
+fn common_prefix_len(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(x, y)| x == y).count()
+}
+
+/// A map from byte strings to values, stored as a compressed trie.
+///
+/// A plain trie spends a node on every byte of every key. The radix trie
+/// collapses each chain of single-child nodes into one edge labelled with
+/// the whole byte run, so it never has more than two nodes per key however
+/// long the keys are, and keys sharing a prefix share its storage. Lookups
+/// take O(key length) whatever the number of keys, iteration runs in
+/// lexicographic byte order, and [`iter_prefix`](RadixTrie::iter_prefix)
+/// visits every key starting with a given prefix.
+#[derive(Debug, Clone)]
+pub struct RadixTrie<V> {
+    root: Node<V>,
+    len: usize,
+}
+
+impl<V> Default for RadixTrie<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> RadixTrie<V> {
+    /// Create a new empty RadixTrie.
+    pub fn new() -> Self {
+        Self {
+            root: Node::new(Vec::new(), None),
+            len: 0,
+        }
+    }
+
+    /// Returns the number of elements in the trie.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the trie contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Clear all entries from the trie.
+    pub fn clear(&mut self) {
+        self.root = Node::new(Vec::new(), None);
+        self.len = 0;
+    }
+
+    /// Insert a key-value pair into the trie.
+    ///
+    /// Returns the previous value if the key existed.
+    pub fn insert<K: AsRef<[u8]>>(&mut self, key: K, value: V) -> Option<V> {
+        let mut node = &mut self.root;
+        let mut rest = key.as_ref();
+        loop {
+            if rest.is_empty() {
+                let old = node.value.replace(value);
+                if old.is_none() {
+                    self.len += 1;
+                }
+                return old;
+            }
+            let index = match node.find_child(rest[0]) {
+                Ok(index) => index,
+                Err(index) => {
+                    node.children
+                        .insert(index, Node::new(rest.to_vec(), Some(value)));
+                    self.len += 1;
+                    return None;
+                }
+            };
+            let child = &mut node.children[index];
+            let common = common_prefix_len(&child.label, rest);
+            if common < child.label.len() {
+                // Split the edge where the key leaves it.
+                let suffix = child.label.split_off(common);
+                let prefix = std::mem::replace(&mut child.label, suffix);
+                let lower = std::mem::replace(child, Node::new(prefix, None));
+                child.children.push(lower);
+            }
+            node = child;
+            rest = &rest[common..];
+        }
+    }
+
+    /// Get a reference to the value associated with the key.
+    pub fn get(&self, key: &[u8]) -> Option<&V> {
+        let mut node = &self.root;
+        let mut rest = key;
+        while !rest.is_empty() {
+            let child = &node.children[node.find_child(rest[0]).ok()?];
+            rest = rest.strip_prefix(child.label.as_slice())?;
+            node = child;
+        }
+        node.value.as_ref()
+    }
+
+    /// Returns the longest key in the trie that is a prefix of `key`, as its
+    /// length and value.
+    ///
+    /// One descent, O(length of `key`), where trying each prefix with
+    /// [`get`](Self::get) would be quadratic. This is the greedy match a
+    /// tokenizer or a dictionary compressor makes at each step.
+    pub fn longest_prefix(&self, key: &[u8]) -> Option<(usize, &V)> {
+        let mut node = &self.root;
+        let mut rest = key;
+        let mut longest = node.value.as_ref().map(|value| (0, value));
+        while !rest.is_empty() {
+            let Ok(index) = node.find_child(rest[0]) else {
+                break;
+            };
+            let child = &node.children[index];
+            let Some(after) = rest.strip_prefix(child.label.as_slice()) else {
+                break;
+            };
+            node = child;
+            rest = after;
+            if let Some(value) = &node.value {
+                longest = Some((key.len() - rest.len(), value));
+            }
+        }
+        longest
+    }
+
+    /// Check if the trie contains the given key.
+    pub fn contains_key(&self, key: &[u8]) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Remove a key-value pair from the trie.
+    ///
+    /// Returns the removed value if the key existed.
+    pub fn remove(&mut self, key: &[u8]) -> Option<V> {
+        fn remove_below<V>(node: &mut Node<V>, rest: &[u8]) -> Option<V> {
+            if rest.is_empty() {
+                return node.value.take();
+            }
+            let index = node.find_child(rest[0]).ok()?;
+            let child = &mut node.children[index];
+            let rest = rest.strip_prefix(child.label.as_slice())?;
+            let removed = remove_below(child, rest)?;
+            // Drop a child left with no entries, and fold one left with a
+            // single grandchild into its edge.
+            if child.value.is_none() {
+                match child.children.len() {
+                    0 => {
+                        node.children.remove(index);
+                    }
+                    1 => {
+                        let grandchild = child.children.pop().expect("one grandchild");
+                        let mut label = std::mem::take(&mut child.label);
+                        label.extend_from_slice(&grandchild.label);
+                        *child = Node {
+                            label,
+                            ..grandchild
+                        };
+                    }
+                    _ => {}
+                }
+            }
+            Some(removed)
+        }
+
+        let removed = remove_below(&mut self.root, key)?;
+        self.len -= 1;
+        Some(removed)
+    }
+
+    /// Iterate over all key-value pairs in lexicographic byte order.
+    pub fn iter(&self) -> Iter<'_, V> {
+        Iter {
+            stack: vec![(&self.root, Vec::new())],
+        }
+    }
+
+    /// Iterate over the key-value pairs whose keys start with `prefix`, in
+    /// lexicographic byte order.
+    pub fn iter_prefix(&self, prefix: &[u8]) -> Iter<'_, V> {
+        let mut node = &self.root;
+        let mut key = Vec::new();
+        let mut rest = prefix;
+        while !rest.is_empty() {
+            let Ok(index) = node.find_child(rest[0]) else {
+                return Iter { stack: Vec::new() };
+            };
+            node = &node.children[index];
+            key.extend_from_slice(&node.label);
+            let common = common_prefix_len(&node.label, rest);
+            if common == rest.len() {
+                // The prefix ends at or inside this edge.
+                break;
+            }
+            if common < node.label.len() {
+                return Iter { stack: Vec::new() };
+            }
+            rest = &rest[common..];
+        }
+        Iter {
+            stack: vec![(node, key)],
+        }
+    }
+
+    /// Check the radix trie invariants, panicking on the first violation.
+    ///
+    /// Checks that only the root has an empty label, that children are
+    /// sorted by distinct first bytes, that no node other than the root is
+    /// an empty leaf or a valueless node with one child, and that the entry
+    /// count matches `len`.
+    pub fn debug_validate(&self) {
+        fn walk<V>(node: &Node<V>, is_root: bool) -> usize {
+            if !is_root {
+                assert!(!node.label.is_empty(), "empty edge label");
+                assert!(
+                    node.value.is_some() || node.children.len() >= 2,
+                    "uncompressed node"
+                );
+            }
+            assert!(
+                node.children
+                    .windows(2)
+                    .all(|pair| pair[0].label[0] < pair[1].label[0]),
+                "children out of order"
+            );
+            usize::from(node.value.is_some())
+                + node
+                    .children
+                    .iter()
+                    .map(|child| walk(child, false))
+                    .sum::<usize>()
+        }
+
+        assert!(self.root.label.is_empty(), "root has a label");
+        assert_eq!(
+            walk(&self.root, true),
+            self.len,
+            "len does not match entry count"
+        );
+    }
+}
+
+/// An iterator over the entries of a [`RadixTrie`], in lexicographic byte
+/// order, yielding each key as an owned byte string.
+pub struct Iter<'a, V> {
+    /// Subtrees still to visit with the key bytes leading to each; the next
+    /// one is on top.
+    stack: Vec<(&'a Node<V>, Vec<u8>)>,
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = (Vec<u8>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((node, key)) = self.stack.pop() {
+            for child in node.children.iter().rev() {
+                let mut child_key = key.clone();
+                child_key.extend_from_slice(&child.label);
+                self.stack.push((child, child_key));
+            }
+            if let Some(value) = &node.value {
+                return Some((key, value));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node_count<V>(node: &Node<V>) -> usize {
+        1 + node.children.iter().map(node_count).sum::<usize>()
+    }
+
+    #[test]
+    fn test_insert_splits_and_remove_merges() {
+        let mut trie = RadixTrie::new();
+        assert_eq!(trie.insert("romane", 1), None);
+        assert_eq!(trie.insert("romanus", 2), None);
+        assert_eq!(trie.insert("roman", 3), None);
+        assert_eq!(trie.insert("rubens", 4), None);
+        assert_eq!(trie.insert("roman", 5), Some(3));
+        trie.debug_validate();
+        // root -> r -> {oman -> {e, us}, ubens}
+        assert_eq!(node_count(&trie.root), 6);
+        assert_eq!(trie.get(b"roman"), Some(&5));
+        assert_eq!(trie.get(b"rom"), None);
+        assert_eq!(trie.get(b"romanesque"), None);
+
+        assert_eq!(trie.remove(b"rom"), None);
+        assert_eq!(trie.remove(b"rubens"), Some(4));
+        assert_eq!(trie.remove(b"romanus"), Some(2));
+        trie.debug_validate();
+        // root -> roman -> e
+        assert_eq!(node_count(&trie.root), 3);
+        assert_eq!(trie.len(), 2);
+    }
+
+    #[test]
+    fn test_iter_prefix_in_byte_order() {
+        let mut trie = RadixTrie::new();
+        for key in ["key_10", "key_1", "key_2", "kez", "k", "key_100"] {
+            trie.insert(key, key.len());
+        }
+        let keys = |iter: Iter<'_, usize>| -> Vec<String> {
+            iter.map(|(key, _)| String::from_utf8(key).unwrap())
+                .collect()
+        };
+        assert_eq!(
+            keys(trie.iter()),
+            ["k", "key_1", "key_10", "key_100", "key_2", "kez"]
+        );
+        assert_eq!(
+            keys(trie.iter_prefix(b"key_1")),
+            ["key_1", "key_10", "key_100"]
+        );
+        // A prefix ending partway along an edge.
+        assert_eq!(
+            keys(trie.iter_prefix(b"ke")),
+            ["key_1", "key_10", "key_100", "key_2", "kez"]
+        );
+        assert_eq!(trie.longest_prefix(b"key_1000"), Some((7, &7)));
+        assert_eq!(trie.longest_prefix(b"key_3"), Some((1, &1)));
+        // "kez" is a key but "ke" is not.
+        assert_eq!(trie.longest_prefix(b"kezz"), Some((3, &3)));
+        assert_eq!(trie.longest_prefix(b"j"), None);
+        assert!(trie.iter_prefix(b"key_3").next().is_none());
+        assert!(trie.iter_prefix(b"kex").next().is_none());
+        assert_eq!(trie.iter_prefix(b"").count(), 6);
+    }
+
+    #[test]
+    fn test_at_most_two_nodes_per_key() {
+        let mut trie = RadixTrie::new();
+        for i in 0..2000 {
+            trie.insert(format!("user/{}/profile", i * 7919 % 10007), i);
+        }
+        trie.debug_validate();
+        assert!(node_count(&trie.root) <= 2 * trie.len());
+        for i in (0..2000).step_by(3) {
+            assert_eq!(
+                trie.remove(format!("user/{}/profile", i * 7919 % 10007).as_bytes()),
+                Some(i)
+            );
+        }
+        trie.debug_validate();
+        assert!(node_count(&trie.root) <= 2 * trie.len() + 1);
+    }
+}
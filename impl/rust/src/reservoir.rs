@@ -0,0 +1,205 @@
+//! Reservoir sampling over iterators of unknown length.
+
+use crate::perfect::{displace, reduce};
+use crate::skip_list::xorshift64;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// A xorshift state for `seed`. Nearby seeds would start with small,
+/// correlated outputs, so the seed is scrambled first.
+pub(crate) fn rng(seed: u64) -> u64 {
+    // xorshift gets stuck at zero.
+    displace(seed, 0) | 1
+}
+
+/// A uniform draw from the open interval (0, 1).
+pub(crate) fn unit(state: &mut u64) -> f64 {
+    ((xorshift64(state) >> 11) as f64 + 0.5) / (1u64 << 53) as f64
+}
+
+/// Draw `k` items uniformly without replacement from `items` in one pass,
+/// using Algorithm R.
+///
+/// The first `k` items fill the reservoir, and each later `i`th item
+/// replaces a random slot with probability `k / i`, which leaves every item
+/// seen so far equally likely to be held. That costs one random number per
+/// item; [`sample_skipping`] draws far fewer when `k` is small next to the
+/// input. Returns every item if there are fewer than `k`. The same `seed`
+/// always picks the same sample, in no particular order.
+pub fn sample<T, I>(items: I, k: usize, seed: u64) -> Vec<T>
+where
+    I: IntoIterator<Item = T>,
+{
+    let mut rng = rng(seed);
+    let mut items = items.into_iter();
+    let mut reservoir: Vec<T> = items.by_ref().take(k).collect();
+    for (seen, item) in (k + 1..).zip(items) {
+        let slot = reduce(xorshift64(&mut rng), seen);
+        if slot < k {
+            reservoir[slot] = item;
+        }
+    }
+    reservoir
+}
+
+/// Draw `k` items uniformly without replacement from `items` in one pass,
+/// using Algorithm L.
+///
+/// Gives the same distribution as [`sample`], but instead of rolling for
+/// every item it draws how many items to skip before the next replacement
+/// directly from the distribution Algorithm R would produce. So it takes
+/// O(k (1 + log(n / k))) random numbers for `n` items, and an iterator with
+/// a cheap `nth` is skipped over without visiting each item.
+pub fn sample_skipping<T, I>(items: I, k: usize, seed: u64) -> Vec<T>
+where
+    I: IntoIterator<Item = T>,
+{
+    let mut rng = rng(seed);
+    let mut items = items.into_iter();
+    let mut reservoir: Vec<T> = items.by_ref().take(k).collect();
+    if k == 0 || reservoir.len() < k {
+        return reservoir;
+    }
+    // The largest of k uniform keys, where each item draws a key and the
+    // reservoir holds the k smallest.
+    let mut threshold = (unit(&mut rng).ln() / k as f64).exp();
+    loop {
+        let skip = (unit(&mut rng).ln() / (-threshold).ln_1p()).floor();
+        // A skip past usize::MAX saturates, which is the same as never.
+        let Some(item) = items.nth(skip as usize) else {
+            return reservoir;
+        };
+        reservoir[reduce(xorshift64(&mut rng), k)] = item;
+        threshold *= (unit(&mut rng).ln() / k as f64).exp();
+    }
+}
+
+/// An item and its sampling key, ordered so that `BinaryHeap` keeps the
+/// smallest key on top.
+struct Keyed<T> {
+    key: f64,
+    item: T,
+}
+
+impl<T> PartialEq for Keyed<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for Keyed<T> {}
+
+impl<T> PartialOrd for Keyed<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Keyed<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.key.total_cmp(&self.key)
+    }
+}
+
+/// Draw `k` items without replacement from `(item, weight)` pairs in one
+/// pass, each pick favouring heavier items.
+///
+/// Uses the Efraimidis-Spirakis scheme: each item draws the key
+/// `u^(1 / weight)` for a uniform `u`, and the sample is the `k` items with
+/// the largest keys, kept in a min-heap. The result is the same as drawing
+/// items one at a time with probability proportional to weight among those
+/// not yet drawn. Keys are compared as `ln(u) / weight`, which orders the
+/// same and does not underflow for small weights.
+///
+/// # Panics
+///
+/// Panics if a weight is not positive and finite.
+pub fn sample_weighted<T, I>(items: I, k: usize, seed: u64) -> Vec<T>
+where
+    I: IntoIterator<Item = (T, f64)>,
+{
+    if k == 0 {
+        return Vec::new();
+    }
+    let mut rng = rng(seed);
+    let mut heap: BinaryHeap<Keyed<T>> = BinaryHeap::with_capacity(k);
+    for (item, weight) in items {
+        assert!(
+            weight > 0.0 && weight.is_finite(),
+            "sample weights must be positive and finite"
+        );
+        let key = unit(&mut rng).ln() / weight;
+        if heap.len() < k {
+            heap.push(Keyed { key, item });
+        } else if let Some(mut smallest) = heap.peek_mut() {
+            if key > smallest.key {
+                *smallest = Keyed { key, item };
+            }
+        }
+    }
+    heap.into_iter().map(|keyed| keyed.item).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// How many times each of `0..n` lands in a sample over many seeds.
+    fn inclusion_counts(n: usize, trials: u64, draw: impl Fn(u64) -> Vec<usize>) -> Vec<u64> {
+        let mut counts = vec![0; n];
+        for seed in 0..trials {
+            for item in draw(seed) {
+                counts[item] += 1;
+            }
+        }
+        counts
+    }
+
+    #[test]
+    fn test_short_input_kept_whole() {
+        assert_eq!(sample(0..3, 5, 1), [0, 1, 2]);
+        assert_eq!(sample_skipping(0..3, 5, 1), [0, 1, 2]);
+        assert!(sample(0..100, 0, 1).is_empty());
+        assert!(sample_skipping(0..100, 0, 1).is_empty());
+        assert!(sample_weighted([("a", 1.0)], 0, 1).is_empty());
+        let mut all = sample_weighted([("a", 1.0), ("b", 2.0)], 5, 1);
+        all.sort_unstable();
+        assert_eq!(all, ["a", "b"]);
+        assert_eq!(sample(0..1000, 10, 7), sample(0..1000, 10, 7));
+    }
+
+    #[test]
+    fn test_uniform_inclusion() {
+        // Each of 50 items should appear in 5 / 50 of 10,000 samples.
+        let r = inclusion_counts(50, 10_000, |seed| sample(0..50, 5, seed));
+        let l = inclusion_counts(50, 10_000, |seed| sample_skipping(0..50, 5, seed));
+        for counts in [r, l] {
+            assert_eq!(counts.iter().sum::<u64>(), 50_000);
+            assert!(
+                counts.iter().all(|&count| (850..1150).contains(&count)),
+                "{:?}",
+                counts
+            );
+        }
+    }
+
+    #[test]
+    fn test_weighted_inclusion() {
+        // With one pick, each item is chosen in proportion to its weight.
+        let counts = inclusion_counts(4, 10_000, |seed| {
+            sample_weighted((0..4).map(|item| (item, (item + 1) as f64)), 1, seed)
+        });
+        for (item, &count) in counts.iter().enumerate() {
+            let expected = 1000 * (item as u64 + 1);
+            assert!(
+                count.abs_diff(expected) < expected / 8,
+                "item {} drawn {} times",
+                item,
+                count
+            );
+        }
+        // Tiny weights still order correctly.
+        let pick = sample_weighted([("light", 1e-300), ("heavy", 1.0)], 1, 3);
+        assert_eq!(pick, ["heavy"]);
+    }
+}
@@ -0,0 +1,181 @@
+//! Consistent hashing ring with virtual nodes.
+
+use crate::hashmap::DefaultHashBuilder;
+use std::hash::{BuildHasher, Hash};
+
+const DEFAULT_VIRTUAL_NODES: usize = 100;
+
+/// Routes keys to nodes so that membership changes move few keys.
+///
+/// Each node is hashed to `virtual_nodes` points on a 64-bit ring, and a key
+/// belongs to the first point at or after its own hash, wrapping around.
+/// Removing a node hands only its own keys to the points that follow, and
+/// adding one takes keys only from its neighbors, so about `1/n` of the keys
+/// move either way. More virtual nodes per node even out the share of the
+/// ring each node owns.
+#[derive(Debug, Clone)]
+pub struct ConsistentHashRing<N> {
+    nodes: Vec<N>,
+    /// Points on the ring as `(hash, index into nodes)`, sorted by hash.
+    ring: Vec<(u64, usize)>,
+    virtual_nodes: usize,
+    hash_builder: DefaultHashBuilder,
+}
+
+impl<N> Default for ConsistentHashRing<N>
+where
+    N: Hash + Eq,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N> ConsistentHashRing<N>
+where
+    N: Hash + Eq,
+{
+    /// Create an empty ring with 100 virtual nodes per node.
+    pub fn new() -> Self {
+        Self::with_virtual_nodes(DEFAULT_VIRTUAL_NODES)
+    }
+
+    /// Create an empty ring placing `virtual_nodes` points per node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `virtual_nodes` is zero.
+    pub fn with_virtual_nodes(virtual_nodes: usize) -> Self {
+        assert!(virtual_nodes > 0, "virtual nodes must be at least 1");
+        Self {
+            nodes: Vec::new(),
+            ring: Vec::new(),
+            virtual_nodes,
+            hash_builder: DefaultHashBuilder::default(),
+        }
+    }
+
+    /// Returns the number of nodes.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns true if the ring has no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns the number of points each node places on the ring.
+    pub fn virtual_nodes(&self) -> usize {
+        self.virtual_nodes
+    }
+
+    /// Add a node to the ring.
+    ///
+    /// Returns false if the node was already present.
+    pub fn add_node(&mut self, node: N) -> bool {
+        if self.nodes.contains(&node) {
+            return false;
+        }
+        let index = self.nodes.len();
+        for replica in 0..self.virtual_nodes {
+            let hash = self.hash_builder.hash_one((&node, replica));
+            self.ring.push((hash, index));
+        }
+        self.ring.sort_unstable();
+        self.nodes.push(node);
+        true
+    }
+
+    /// Remove a node from the ring, returning it if it was present.
+    pub fn remove_node(&mut self, node: &N) -> Option<N> {
+        let index = self.nodes.iter().position(|n| n == node)?;
+        let last = self.nodes.len() - 1;
+        self.ring.retain(|&(_, i)| i != index);
+        // The last node moves into the freed index.
+        for point in &mut self.ring {
+            if point.1 == last {
+                point.1 = index;
+            }
+        }
+        Some(self.nodes.swap_remove(index))
+    }
+
+    /// Returns the node responsible for `key`, or None if the ring is empty.
+    pub fn route<K: Hash + ?Sized>(&self, key: &K) -> Option<&N> {
+        let hash = self.hash_builder.hash_one(key);
+        let position = self.ring.partition_point(|&(point, _)| point < hash);
+        let &(_, index) = self.ring.get(position).or_else(|| self.ring.first())?;
+        Some(&self.nodes[index])
+    }
+
+    /// Iterate over the nodes.
+    pub fn nodes(&self) -> impl Iterator<Item = &N> {
+        self.nodes.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ring_of(count: usize) -> ConsistentHashRing<String> {
+        let mut ring = ConsistentHashRing::new();
+        for i in 0..count {
+            assert!(ring.add_node(format!("node-{}", i)));
+        }
+        ring
+    }
+
+    #[test]
+    fn test_route_is_stable() {
+        let mut ring = ring_of(3);
+        assert!(!ring.add_node("node-0".to_string()));
+        assert_eq!(ring.len(), 3);
+        for key in 0..100 {
+            assert_eq!(ring.route(&key), ring.route(&key));
+        }
+        assert_eq!(ConsistentHashRing::<u32>::new().route("key"), None);
+    }
+
+    #[test]
+    fn test_removal_moves_only_the_removed_nodes_keys() {
+        let mut ring = ring_of(10);
+        let keys: Vec<String> = (0..10_000).map(|i| format!("key_{}", i)).collect();
+        let before: Vec<String> = keys
+            .iter()
+            .map(|k| ring.route(k).unwrap().clone())
+            .collect();
+
+        assert_eq!(
+            ring.remove_node(&"node-3".to_string()).as_deref(),
+            Some("node-3")
+        );
+        assert_eq!(ring.remove_node(&"node-3".to_string()), None);
+
+        let mut moved = 0;
+        for (key, owner) in keys.iter().zip(&before) {
+            let now = ring.route(key).unwrap();
+            if now != owner {
+                assert_eq!(owner, "node-3");
+                moved += 1;
+            }
+        }
+        // Expect about a tenth of the keys to move.
+        let fraction = moved as f64 / keys.len() as f64;
+        assert!((0.05..0.15).contains(&fraction), "moved {}", fraction);
+        assert_eq!(moved, before.iter().filter(|o| *o == "node-3").count());
+    }
+
+    #[test]
+    fn test_virtual_nodes_balance_load() {
+        let ring = ring_of(8);
+        let mut counts = std::collections::HashMap::new();
+        for key in 0..16_000 {
+            *counts.entry(ring.route(&key).unwrap()).or_insert(0) += 1;
+        }
+        assert_eq!(counts.len(), 8);
+        // Within 50% of the fair share of 2000.
+        assert!(counts.values().all(|&n| (1000..3000).contains(&n)));
+    }
+}
@@ -0,0 +1,357 @@
+//! Hash Map implementation using Robin Hood hashing with backward-shift deletion.
+
+use crate::growth::GrowthPolicy;
+use crate::hashmap::TableStats;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+const DEFAULT_CAPACITY: usize = 16;
+const MAX_LOAD_FACTOR: f64 = 0.75;
+
+#[derive(Debug, Clone)]
+struct Bucket<K, V> {
+    key: K,
+    value: V,
+    /// Distance from the home bucket.
+    dist: usize,
+}
+
+/// A hash map using linear probing with Robin Hood displacement.
+///
+/// On insert, an entry that is further from its home bucket than the resident
+/// entry takes the slot, which keeps probe lengths tightly distributed. Removal
+/// shifts the following entries back instead of leaving tombstones.
+#[derive(Debug, Clone)]
+pub struct RobinHoodMap<K, V> {
+    buckets: Vec<Option<Bucket<K, V>>>,
+    size: usize,
+    growth_policy: GrowthPolicy,
+}
+
+impl<K, V> Default for RobinHoodMap<K, V>
+where
+    K: Hash + Eq,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> RobinHoodMap<K, V>
+where
+    K: Hash + Eq,
+{
+    /// Create a new empty RobinHoodMap.
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// Create a new RobinHoodMap with the specified capacity.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_growth_policy(capacity, GrowthPolicy::default())
+    }
+
+    /// Create a new empty RobinHoodMap that grows according to `policy`.
+    pub fn with_growth_policy(policy: GrowthPolicy) -> Self {
+        Self::with_capacity_and_growth_policy(DEFAULT_CAPACITY, policy)
+    }
+
+    /// Create a new RobinHoodMap with at least the specified capacity that
+    /// grows according to `policy`.
+    pub fn with_capacity_and_growth_policy(capacity: usize, policy: GrowthPolicy) -> Self {
+        let capacity = policy.initial_capacity(capacity.max(DEFAULT_CAPACITY));
+        let mut buckets = Vec::with_capacity(capacity);
+        buckets.resize_with(capacity, || None);
+        Self {
+            buckets,
+            size: 0,
+            growth_policy: policy,
+        }
+    }
+
+    /// Returns the policy used to pick the next capacity on growth.
+    pub fn growth_policy(&self) -> GrowthPolicy {
+        self.growth_policy
+    }
+
+    /// Returns the number of elements in the map.
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Returns true if the map contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Returns the current capacity of the map.
+    pub fn capacity(&self) -> usize {
+        self.buckets.len()
+    }
+
+    fn hash_key(&self, key: &K) -> usize {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        hasher.finish() as usize
+    }
+
+    fn load_factor(&self) -> f64 {
+        self.size as f64 / self.buckets.len() as f64
+    }
+
+    fn find_index(&self, key: &K) -> Option<usize> {
+        let capacity = self.buckets.len();
+        let mut index = self.hash_key(key) % capacity;
+
+        for dist in 0..capacity {
+            match &self.buckets[index] {
+                None => return None,
+                // Every entry past this point would have displaced this one.
+                Some(bucket) if bucket.dist < dist => return None,
+                Some(bucket) if bucket.key == *key => return Some(index),
+                Some(_) => {}
+            }
+            index = (index + 1) % capacity;
+        }
+
+        None
+    }
+
+    fn resize(&mut self) {
+        let new_capacity =
+            self.growth_policy
+                .next_capacity(self.buckets.len(), self.size + 1, MAX_LOAD_FACTOR);
+        let old_buckets = std::mem::replace(&mut self.buckets, {
+            let mut v = Vec::with_capacity(new_capacity);
+            v.resize_with(new_capacity, || None);
+            v
+        });
+
+        self.size = 0;
+        for bucket in old_buckets.into_iter().flatten() {
+            self.insert_new(bucket.key, bucket.value);
+        }
+    }
+
+    fn insert_new(&mut self, key: K, value: V) {
+        let capacity = self.buckets.len();
+        let mut index = self.hash_key(&key) % capacity;
+        let mut carried = Bucket {
+            key,
+            value,
+            dist: 0,
+        };
+
+        loop {
+            match &mut self.buckets[index] {
+                slot @ None => {
+                    *slot = Some(carried);
+                    self.size += 1;
+                    return;
+                }
+                Some(resident) => {
+                    if resident.dist < carried.dist {
+                        std::mem::swap(resident, &mut carried);
+                    }
+                }
+            }
+            index = (index + 1) % capacity;
+            carried.dist += 1;
+        }
+    }
+
+    /// Insert a key-value pair into the map.
+    ///
+    /// Returns the previous value if the key existed.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(index) = self.find_index(&key) {
+            let bucket = self.buckets[index].as_mut().unwrap();
+            return Some(std::mem::replace(&mut bucket.value, value));
+        }
+
+        if (self.size + 1) as f64 / self.buckets.len() as f64 > MAX_LOAD_FACTOR {
+            self.resize();
+        }
+
+        self.insert_new(key, value);
+        None
+    }
+
+    /// Get a reference to the value associated with the key.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let index = self.find_index(key)?;
+        self.buckets[index].as_ref().map(|bucket| &bucket.value)
+    }
+
+    /// Get a mutable reference to the value associated with the key.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let index = self.find_index(key)?;
+        self.buckets[index].as_mut().map(|bucket| &mut bucket.value)
+    }
+
+    /// Remove a key-value pair from the map.
+    ///
+    /// Returns the removed value if the key existed.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let index = self.find_index(key)?;
+        let removed = self.buckets[index].take()?;
+        self.size -= 1;
+
+        // Backward-shift: pull displaced successors one slot closer to home.
+        let capacity = self.buckets.len();
+        let mut hole = index;
+        let mut next = (index + 1) % capacity;
+        while let Some(bucket) = &self.buckets[next] {
+            if bucket.dist == 0 {
+                break;
+            }
+            let mut moved = self.buckets[next].take().unwrap();
+            moved.dist -= 1;
+            self.buckets[hole] = Some(moved);
+            hole = next;
+            next = (next + 1) % capacity;
+        }
+
+        Some(removed.value)
+    }
+
+    /// Check if the map contains the given key.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.find_index(key).is_some()
+    }
+
+    /// Clear all entries from the map.
+    pub fn clear(&mut self) {
+        for bucket in &mut self.buckets {
+            *bucket = None;
+        }
+        self.size = 0;
+    }
+
+    /// Iterate over all key-value pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.buckets
+            .iter()
+            .flatten()
+            .map(|bucket| (&bucket.key, &bucket.value))
+    }
+
+    /// Iterate over all keys.
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.iter().map(|(k, _)| k)
+    }
+
+    /// Iterate over all values.
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.iter().map(|(_, v)| v)
+    }
+
+    /// Collect occupancy and probe-length statistics for the table.
+    pub fn stats(&self) -> TableStats {
+        let mut histogram = Vec::new();
+        let mut total_probe_length = 0;
+
+        for bucket in self.buckets.iter().flatten() {
+            let probe_length = bucket.dist + 1;
+            if histogram.len() < probe_length {
+                histogram.resize(probe_length, 0);
+            }
+            histogram[probe_length - 1] += 1;
+            total_probe_length += probe_length;
+        }
+
+        TableStats {
+            occupied: self.size,
+            tombstones: 0,
+            capacity: self.buckets.len(),
+            load_factor: self.load_factor(),
+            avg_probe_length: if self.size == 0 {
+                0.0
+            } else {
+                total_probe_length as f64 / self.size as f64
+            },
+            max_probe_length: histogram.len(),
+            probe_length_histogram: histogram,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HashMap;
+
+    #[test]
+    fn test_insert_get_overwrite() {
+        let mut map = RobinHoodMap::new();
+        assert!(map.is_empty());
+        assert!(map.insert("a".to_string(), 1).is_none());
+        assert_eq!(map.insert("a".to_string(), 2), Some(1));
+        assert_eq!(map.get(&"a".to_string()), Some(&2));
+        assert_eq!(map.len(), 1);
+        *map.get_mut(&"a".to_string()).unwrap() += 1;
+        assert_eq!(map.get(&"a".to_string()), Some(&3));
+    }
+
+    #[test]
+    fn test_remove_backward_shift() {
+        let mut map = RobinHoodMap::with_capacity(16);
+        for i in 0..12 {
+            map.insert(i, i * 10);
+        }
+        for i in (0..12).step_by(3) {
+            assert_eq!(map.remove(&i), Some(i * 10));
+            assert_eq!(map.remove(&i), None);
+        }
+        assert_eq!(map.len(), 8);
+        for i in 0..12 {
+            assert_eq!(map.contains_key(&i), i % 3 != 0);
+        }
+        assert_eq!(map.stats().tombstones, 0);
+    }
+
+    #[test]
+    fn test_resize_and_clear() {
+        let mut map = RobinHoodMap::new();
+        for i in 0..1000 {
+            map.insert(format!("key{}", i), i);
+        }
+        assert_eq!(map.len(), 1000);
+        assert_eq!(map.iter().count(), 1000);
+        for i in 0..1000 {
+            assert_eq!(map.get(&format!("key{}", i)), Some(&i));
+        }
+        map.clear();
+        assert!(map.is_empty());
+        assert!(map.get(&"key1".to_string()).is_none());
+    }
+
+    #[test]
+    fn test_growth_policy_primes() {
+        let mut map = RobinHoodMap::with_growth_policy(GrowthPolicy::Primes);
+        assert_eq!(map.capacity(), 17);
+        for i in 0..1000 {
+            map.insert(i, i);
+        }
+        let capacity = map.capacity();
+        assert!((2..capacity).all(|d| capacity % d != 0 || d * d > capacity));
+        assert!((0..1000).all(|i| map.get(&i) == Some(&i)));
+    }
+
+    #[test]
+    fn test_probe_variance_not_worse_than_linear() {
+        let mut robin_hood = RobinHoodMap::with_capacity(4096);
+        let mut linear = HashMap::with_capacity(4096);
+        for i in 0..3000 {
+            robin_hood.insert(i, i);
+            linear.insert(i, i);
+        }
+
+        let rh = robin_hood.stats();
+        let lp = linear.stats();
+        assert_eq!(rh.capacity, lp.capacity);
+        assert!((rh.avg_probe_length - lp.avg_probe_length).abs() < 1e-9);
+        assert!(rh.probe_length_variance() <= lp.probe_length_variance());
+        assert!(rh.max_probe_length <= lp.max_probe_length);
+    }
+}
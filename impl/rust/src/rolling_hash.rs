@@ -0,0 +1,218 @@
+//! Polynomial rolling hashes with O(1) substring queries, and Rabin-Karp
+//! search built on them.
+
+use crate::segment_tree::resolve_range;
+use std::ops::RangeBounds;
+
+/// The base and modulus of one polynomial hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HashParams {
+    pub base: u64,
+    pub modulus: u64,
+}
+
+/// The hash of a byte string under both of a [`RollingHash`]'s parameter
+/// sets.
+pub type Fingerprint = [u64; 2];
+
+fn mul_mod(a: u64, b: u64, modulus: u64) -> u64 {
+    (a as u128 * b as u128 % modulus as u128) as u64
+}
+
+/// Sums in u128 like [`mul_mod`], so a modulus near 2^64 cannot overflow.
+fn add_mod(a: u64, b: u64, modulus: u64) -> u64 {
+    ((a as u128 + b as u128) % modulus as u128) as u64
+}
+
+/// Hashes of every prefix of a byte string, answering the hash of any
+/// substring in O(1).
+///
+/// A string `s` hashes to `(s[0] + 1) b^(k-1) + ... + (s[k-1] + 1)` modulo
+/// `p`, each byte shifted by one so that zero bytes still count. Storing
+/// the hash of every prefix, and every power of `b`, gives the hash of
+/// `s[i..j]` as `prefix[j] - prefix[i] * b^(j - i)`, so after O(n)
+/// preprocessing two substrings compare in O(1) with high probability.
+/// Two independent parameter sets are used together, so a false match
+/// needs a collision under both: about one in 10^18 for the default
+/// primes. Equal hashes are evidence, not proof; callers that must be
+/// exact compare the bytes after a match, as [`rabin_karp`] does.
+#[derive(Debug, Clone)]
+pub struct RollingHash {
+    params: [HashParams; 2],
+    /// `prefixes[i]` is the fingerprint of the first `i` bytes.
+    prefixes: Vec<Fingerprint>,
+    /// `powers[i]` is each base to the `i`th power.
+    powers: Vec<Fingerprint>,
+}
+
+impl RollingHash {
+    /// Two primes near 10^9 with small bases.
+    pub const DEFAULT_PARAMS: [HashParams; 2] = [
+        HashParams {
+            base: 131,
+            modulus: 1_000_000_007,
+        },
+        HashParams {
+            base: 137,
+            modulus: 998_244_353,
+        },
+    ];
+
+    /// Preprocess `text` with the default parameters.
+    pub fn new(text: &[u8]) -> Self {
+        Self::with_params(text, Self::DEFAULT_PARAMS)
+    }
+
+    /// Preprocess `text` hashing under both `params`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a modulus is below 2.
+    pub fn with_params(text: &[u8], params: [HashParams; 2]) -> Self {
+        for p in params {
+            assert!(p.modulus >= 2, "hash modulus must be at least 2");
+        }
+        let mut prefixes = Vec::with_capacity(text.len() + 1);
+        let mut powers = Vec::with_capacity(text.len() + 1);
+        prefixes.push([0; 2]);
+        powers.push([1 % params[0].modulus, 1 % params[1].modulus]);
+        for &byte in text {
+            let (prefix, power) = (prefixes[prefixes.len() - 1], powers[powers.len() - 1]);
+            prefixes.push([0, 1].map(|k| push_byte(prefix[k], byte, params[k])));
+            powers.push([0, 1].map(|k| mul_mod(power[k], params[k].base, params[k].modulus)));
+        }
+        Self {
+            params,
+            prefixes,
+            powers,
+        }
+    }
+
+    /// Returns the length of the preprocessed text.
+    pub fn len(&self) -> usize {
+        self.prefixes.len() - 1
+    }
+
+    /// Returns true if the preprocessed text is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the parameters in use.
+    pub fn params(&self) -> [HashParams; 2] {
+        self.params
+    }
+
+    /// Returns the fingerprint of the text's bytes in `range`, in O(1).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds or its start exceeds its end.
+    pub fn hash<R: RangeBounds<usize>>(&self, range: R) -> Fingerprint {
+        let range = resolve_range(range, self.len());
+        let (start, end) = (self.prefixes[range.start], self.prefixes[range.end]);
+        let power = self.powers[range.len()];
+        [0, 1].map(|k| {
+            let modulus = self.params[k].modulus;
+            let shifted = mul_mod(start[k], power[k], modulus);
+            add_mod(end[k], modulus - shifted, modulus)
+        })
+    }
+
+    /// Returns the fingerprint of `bytes` under the same parameters, for
+    /// comparing against [`hash`](Self::hash).
+    pub fn hash_bytes(&self, bytes: &[u8]) -> Fingerprint {
+        fingerprint(bytes, self.params)
+    }
+}
+
+fn push_byte(hash: u64, byte: u8, params: HashParams) -> u64 {
+    add_mod(
+        mul_mod(hash, params.base, params.modulus),
+        byte as u64 + 1,
+        params.modulus,
+    )
+}
+
+fn fingerprint(bytes: &[u8], params: [HashParams; 2]) -> Fingerprint {
+    [0, 1].map(|k| {
+        bytes
+            .iter()
+            .fold(0, |hash, &byte| push_byte(hash, byte, params[k]))
+    })
+}
+
+/// Returns the starting offset of every occurrence of `pattern` in `text`,
+/// overlapping ones included, using Rabin-Karp with the given parameters.
+///
+/// Every window of the text is compared with the pattern by fingerprint,
+/// and only windows with a matching fingerprint are compared byte by byte,
+/// so the answer is exact whatever the parameters. Expected O(n + m) plus
+/// the cost of verifying matches.
+pub fn rabin_karp(pattern: &[u8], text: &[u8], params: [HashParams; 2]) -> Vec<usize> {
+    let m = pattern.len();
+    if m > text.len() {
+        return Vec::new();
+    }
+    let hashes = RollingHash::with_params(text, params);
+    let target = hashes.hash_bytes(pattern);
+    (0..=text.len() - m)
+        .filter(|&start| {
+            hashes.hash(start..start + m) == target && &text[start..start + m] == pattern
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_equal_substrings_hash_equal() {
+        let text = b"abracadabra";
+        let hashes = RollingHash::new(text);
+        assert_eq!(hashes.len(), 11);
+        assert_eq!(hashes.hash(0..4), hashes.hash(7..11));
+        assert_eq!(hashes.hash(0..4), hashes.hash_bytes(b"abra"));
+        assert_ne!(hashes.hash(0..4), hashes.hash(1..5));
+        assert_eq!(hashes.hash(3..3), hashes.hash_bytes(b""));
+        assert_eq!(hashes.hash(..), hashes.hash_bytes(text));
+    }
+
+    #[test]
+    fn test_zero_bytes_count() {
+        let hashes = RollingHash::new(&[0, 0, 0]);
+        assert_ne!(hashes.hash(0..1), hashes.hash(0..2));
+        assert_ne!(hashes.hash_bytes(&[0]), hashes.hash_bytes(&[]));
+    }
+
+    #[test]
+    fn test_rabin_karp_exact_under_weak_params() {
+        // Modulus 3 collides constantly; byte verification keeps it exact.
+        let weak = [HashParams {
+            base: 2,
+            modulus: 3,
+        }; 2];
+        let text = b"abaababaabaab";
+        assert_eq!(rabin_karp(b"aba", text, weak), [0, 3, 5, 8]);
+        assert_eq!(
+            rabin_karp(b"aba", text, RollingHash::DEFAULT_PARAMS),
+            [0, 3, 5, 8]
+        );
+        assert!(rabin_karp(b"abc", b"ab", weak).is_empty());
+    }
+
+    #[test]
+    fn test_modulus_near_u64_max() {
+        // 2^64 - 59, the largest prime below 2^64.
+        let wide = [HashParams {
+            base: 257,
+            modulus: u64::MAX - 58,
+        }; 2];
+        let text: Vec<u8> = (0..64).map(|i| (i * 37 % 256) as u8).collect();
+        let hashes = RollingHash::with_params(&text, wide);
+        assert_eq!(hashes.hash(10..40), hashes.hash_bytes(&text[10..40]));
+        assert_eq!(hashes.hash(..), hashes.hash_bytes(&text));
+        assert_eq!(rabin_karp(&text[20..30], &text, wide), [20]);
+    }
+}
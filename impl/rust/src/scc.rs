@@ -0,0 +1,305 @@
+//! Strongly connected components of a [`Graph`]: Tarjan and Kosaraju.
+
+use crate::graph::{Graph, NodeId};
+
+/// Marks a node index with no node behind it.
+const NIL: usize = usize::MAX;
+
+/// A graph's nodes split into strongly connected components: maximal sets
+/// in which every node can reach every other.
+///
+/// Components are numbered in topological order of the condensation, so
+/// every edge between two components goes from the lower number to the
+/// higher. Lookups take ids of the graph as it was when the components
+/// were computed.
+#[derive(Debug, Clone)]
+pub struct StrongComponents {
+    /// Each node's component by [`NodeId::index`], `NIL` for no node.
+    component: Vec<usize>,
+    /// The nodes of each component.
+    members: Vec<Vec<NodeId>>,
+}
+
+impl StrongComponents {
+    fn from_members(bound: usize, members: Vec<Vec<NodeId>>) -> Self {
+        let mut component = vec![NIL; bound];
+        for (c, nodes) in members.iter().enumerate() {
+            for node in nodes {
+                component[node.index()] = c;
+            }
+        }
+        Self { component, members }
+    }
+
+    /// Returns the number of components.
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    /// Returns true if there are no components, which means the graph had
+    /// no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// Returns the number of the component holding `node`, or `None` if
+    /// the node was not in the graph.
+    pub fn component(&self, node: NodeId) -> Option<usize> {
+        self.component
+            .get(node.index())
+            .copied()
+            .filter(|&c| c != NIL)
+    }
+
+    /// Returns the nodes of a component.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `component` is not below [`len`](Self::len).
+    pub fn nodes(&self, component: usize) -> &[NodeId] {
+        &self.members[component]
+    }
+
+    /// Iterate over the components' nodes, in component order.
+    pub fn iter(&self) -> impl Iterator<Item = &[NodeId]> {
+        self.members.iter().map(Vec::as_slice)
+    }
+
+    /// Build the condensation of `graph`: a directed acyclic graph with a
+    /// node per component, weighted by its nodes, and one edge from a
+    /// component to another wherever `graph` has at least one.
+    ///
+    /// The nodes are added in component order, so
+    /// [`Graph::nodes`] lists them topologically sorted. An undirected
+    /// graph has no edges between its components, so its condensation has
+    /// none either.
+    pub fn condensation<N, E>(&self, graph: &Graph<N, E>) -> Graph<Vec<NodeId>, ()> {
+        let mut condensation = Graph::directed();
+        let ids: Vec<NodeId> = self
+            .members
+            .iter()
+            .map(|nodes| condensation.add_node(nodes.clone()))
+            .collect();
+        // The last component to have added an edge to each component, so
+        // parallel edges collapse into one.
+        let mut linked_from = vec![NIL; self.len()];
+        for (c, nodes) in self.members.iter().enumerate() {
+            for &node in nodes {
+                for edge in graph.outgoing(node) {
+                    let target = self.component[edge.target.index()];
+                    if target != c && linked_from[target] != c {
+                        linked_from[target] = c;
+                        condensation.add_edge(ids[c], ids[target], ());
+                    }
+                }
+            }
+        }
+        condensation
+    }
+}
+
+/// Returns the strongly connected components of `graph`, by Tarjan's
+/// algorithm.
+///
+/// One depth-first search numbers the nodes in visiting order and tracks
+/// the lowest number each subtree reaches by an edge back into the stack
+/// of unfinished nodes. A node whose subtree reaches nothing lower roots a
+/// component, which is popped off the stack whole. O(V + E), with an
+/// explicit stack so long paths cannot overflow the call stack. In an
+/// undirected graph the components are the connected components.
+pub fn tarjan_scc<N, E>(graph: &Graph<N, E>) -> StrongComponents {
+    let bound = graph.node_bound();
+    let mut order = vec![NIL; bound];
+    let mut low = vec![NIL; bound];
+    let mut on_stack = vec![false; bound];
+    let mut stack = Vec::new();
+    let mut members = Vec::new();
+    let mut visited = 0;
+    let mut calls = Vec::new();
+    for (root, _) in graph.nodes() {
+        if order[root.index()] != NIL {
+            continue;
+        }
+        order[root.index()] = visited;
+        low[root.index()] = visited;
+        visited += 1;
+        stack.push(root);
+        on_stack[root.index()] = true;
+        calls.push((root, graph.outgoing(root)));
+        while let Some((node, edges)) = calls.last_mut() {
+            let node = *node;
+            if let Some(edge) = edges.next() {
+                let target = edge.target;
+                if order[target.index()] == NIL {
+                    order[target.index()] = visited;
+                    low[target.index()] = visited;
+                    visited += 1;
+                    stack.push(target);
+                    on_stack[target.index()] = true;
+                    calls.push((target, graph.outgoing(target)));
+                } else if on_stack[target.index()] {
+                    low[node.index()] = low[node.index()].min(order[target.index()]);
+                }
+                continue;
+            }
+            calls.pop();
+            if let Some(&(parent, _)) = calls.last() {
+                low[parent.index()] = low[parent.index()].min(low[node.index()]);
+            }
+            if low[node.index()] == order[node.index()] {
+                let mut component = Vec::new();
+                loop {
+                    let member = stack.pop().expect("the root is on the stack");
+                    on_stack[member.index()] = false;
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                members.push(component);
+            }
+        }
+    }
+    // Each component is finished after every component it reaches.
+    members.reverse();
+    StrongComponents::from_members(bound, members)
+}
+
+/// Returns the strongly connected components of `graph`, by Kosaraju's
+/// algorithm.
+///
+/// A first depth-first search records the order in which nodes finish. A
+/// second one runs over the edges reversed, starting from nodes in reverse
+/// finishing order, and each tree it grows is one component. O(V + E),
+/// like [`tarjan_scc`], but it walks every edge twice, once each way.
+pub fn kosaraju_scc<N, E>(graph: &Graph<N, E>) -> StrongComponents {
+    let bound = graph.node_bound();
+    let mut seen = vec![false; bound];
+    let mut finished = Vec::with_capacity(graph.node_count());
+    let mut calls = Vec::new();
+    for (root, _) in graph.nodes() {
+        if seen[root.index()] {
+            continue;
+        }
+        seen[root.index()] = true;
+        calls.push((root, graph.neighbors(root)));
+        while let Some((node, targets)) = calls.last_mut() {
+            let node = *node;
+            match targets.find(|target| !seen[target.index()]) {
+                Some(target) => {
+                    seen[target.index()] = true;
+                    calls.push((target, graph.neighbors(target)));
+                }
+                None => {
+                    calls.pop();
+                    finished.push(node);
+                }
+            }
+        }
+    }
+
+    let mut component = vec![NIL; bound];
+    let mut members = Vec::new();
+    let mut stack = Vec::new();
+    for &root in finished.iter().rev() {
+        if component[root.index()] != NIL {
+            continue;
+        }
+        let c = members.len();
+        let mut nodes = Vec::new();
+        component[root.index()] = c;
+        stack.push(root);
+        while let Some(node) = stack.pop() {
+            nodes.push(node);
+            for edge in graph.incoming(node) {
+                if component[edge.source.index()] == NIL {
+                    component[edge.source.index()] = c;
+                    stack.push(edge.source);
+                }
+            }
+        }
+        members.push(nodes);
+    }
+    StrongComponents { component, members }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A directed graph on `n` nodes with the given edges.
+    fn graph(n: usize, edges: &[(usize, usize)]) -> (Graph<(), ()>, Vec<NodeId>) {
+        let mut graph = Graph::directed();
+        let nodes: Vec<_> = (0..n).map(|_| graph.add_node(())).collect();
+        for &(source, target) in edges {
+            graph.add_edge(nodes[source], nodes[target], ());
+        }
+        (graph, nodes)
+    }
+
+    #[test]
+    fn test_components_in_topological_order() {
+        // {0, 1, 2} -> {3, 4} -> {5}, and 6 alone with a self-loop.
+        let (graph, n) = graph(
+            7,
+            &[
+                (0, 1),
+                (1, 2),
+                (2, 0),
+                (2, 3),
+                (3, 4),
+                (4, 3),
+                (4, 5),
+                (1, 5),
+                (6, 6),
+            ],
+        );
+        for components in [tarjan_scc(&graph), kosaraju_scc(&graph)] {
+            assert_eq!(components.len(), 4);
+            let c: Vec<_> = n
+                .iter()
+                .map(|&node| components.component(node).unwrap())
+                .collect();
+            assert!(c[0] == c[1] && c[1] == c[2]);
+            assert_eq!(c[3], c[4]);
+            assert!(c[0] < c[3] && c[3] < c[5]);
+            let mut first = components.nodes(c[0]).to_vec();
+            first.sort();
+            assert_eq!(first, [n[0], n[1], n[2]]);
+            assert_eq!(components.nodes(c[6]), [n[6]]);
+        }
+    }
+
+    #[test]
+    fn test_condensation() {
+        let (graph, n) = graph(5, &[(0, 1), (1, 0), (0, 2), (1, 2), (2, 3), (3, 2), (4, 0)]);
+        let components = tarjan_scc(&graph);
+        let condensation = components.condensation(&graph);
+        assert_eq!(condensation.node_count(), 3);
+        // The two edges from {0, 1} into {2, 3} collapse into one.
+        assert_eq!(condensation.edge_count(), 2);
+        let ids: Vec<_> = condensation.nodes().map(|(id, _)| id).collect();
+        assert_eq!(condensation.node(ids[0]), Some(&vec![n[4]]));
+        assert!(condensation.find_edge(ids[0], ids[1]).is_some());
+        assert!(condensation.find_edge(ids[1], ids[2]).is_some());
+    }
+
+    #[test]
+    fn test_removed_nodes_and_undirected() {
+        let (mut graph, n) = graph(3, &[(0, 1), (1, 2), (2, 0)]);
+        graph.remove_node(n[1]);
+        let components = kosaraju_scc(&graph);
+        assert_eq!(components.len(), 2);
+        assert_eq!(components.component(n[1]), None);
+
+        let mut undirected = Graph::undirected();
+        let m: Vec<_> = (0..4).map(|_| undirected.add_node(())).collect();
+        undirected.add_edge(m[0], m[1], ());
+        undirected.add_edge(m[2], m[1], ());
+        for components in [tarjan_scc(&undirected), kosaraju_scc(&undirected)] {
+            assert_eq!(components.len(), 2);
+            assert_eq!(components.component(m[0]), components.component(m[2]));
+            assert_eq!(components.condensation(&undirected).edge_count(), 0);
+        }
+    }
+}
@@ -0,0 +1,258 @@
+//! Searching sorted slices and monotone functions: binary search on a
+//! predicate, exponential (galloping) search, interpolation search, and
+//! ternary search for the peak of a unimodal function.
+//!
+//! The slice searches return `Ok` with the first index holding the value,
+//! or `Err` with the index where it would be inserted to keep the slice
+//! sorted. Unlike `slice::binary_search`, which may report any of several
+//! equal elements, they always report the first, so they can be compared
+//! with each other and with a linear scan.
+
+use std::cmp::Ordering;
+use std::ops::Range;
+
+/// Returns the number of leading elements of `v` for which `pred` holds,
+/// given that it holds for a prefix of `v` and fails for the rest.
+///
+/// Binary search, with O(log n) calls to `pred`. If `pred` is not
+/// monotone, the result is some index where it flips from true to false.
+pub fn partition_point<T>(v: &[T], mut pred: impl FnMut(&T) -> bool) -> usize {
+    let (mut low, mut high) = (0, v.len());
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if pred(&v[mid]) {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    low
+}
+
+/// Returns the first `x` in `range` for which `pred` fails, or
+/// `range.end` if it holds throughout, given that it holds for a prefix
+/// of the range and fails for the rest.
+///
+/// Binary search on the answer: the range need not exist in memory, so
+/// this finds the smallest capacity, time or size that meets a monotone
+/// test in O(log n) calls to `pred`.
+pub fn partition_point_range(range: Range<u64>, mut pred: impl FnMut(u64) -> bool) -> u64 {
+    let (mut low, mut high) = (range.start, range.end.max(range.start));
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if pred(mid) {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    low
+}
+
+/// Returns the index of the first element of `v` not less than `x`.
+pub fn lower_bound<T: Ord>(v: &[T], x: &T) -> usize {
+    partition_point(v, |y| y < x)
+}
+
+/// Returns the index of the first element of `v` greater than `x`.
+pub fn upper_bound<T: Ord>(v: &[T], x: &T) -> usize {
+    partition_point(v, |y| y <= x)
+}
+
+/// Turn a lower bound into a search result.
+fn found<T: Ord>(v: &[T], x: &T, index: usize) -> Result<usize, usize> {
+    match v.get(index) {
+        Some(y) if y == x => Ok(index),
+        _ => Err(index),
+    }
+}
+
+/// Search the sorted `v` for `x` by binary search, in O(log n)
+/// comparisons.
+pub fn binary_search<T: Ord>(v: &[T], x: &T) -> Result<usize, usize> {
+    found(v, x, lower_bound(v, x))
+}
+
+/// Search the sorted `v` for `x` by exponential search: probe indices 0,
+/// 1, 3, 7, ... until one holds a value not less than `x`, then binary
+/// search the last gap.
+///
+/// Takes O(log i) comparisons for a result at index `i`, so it beats
+/// [`binary_search`] when the answer is near the front. Mergers use it
+/// to gallop through long runs of one input, as Timsort does.
+pub fn exponential_search<T: Ord>(v: &[T], x: &T) -> Result<usize, usize> {
+    let mut end = 1;
+    while end <= v.len() && v[end - 1] < *x {
+        end *= 2;
+    }
+    let start = end / 2;
+    let end = end.min(v.len());
+    found(v, x, start + lower_bound(&v[start..end], x))
+}
+
+/// Numeric keys that [`interpolation_search`] can measure distances
+/// between.
+pub trait Interpolate: Copy + Ord {
+    /// Returns the key as a float, rounded if it has no exact one.
+    fn to_f64(self) -> f64;
+}
+
+macro_rules! impl_interpolate {
+    ($($t:ty),*) => {
+        $(
+            impl Interpolate for $t {
+                #[inline]
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+            }
+        )*
+    };
+}
+
+impl_interpolate!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// Search the sorted `v` for `x` by interpolation search: guess where `x`
+/// falls between the ends of the range left, by linear interpolation on
+/// their values, and cut the range at the guess.
+///
+/// On keys spread evenly the guesses land close, for O(log log n)
+/// probes in expectation. On skewed keys, say powers of two, each probe
+/// may cut off a single element, for O(n).
+pub fn interpolation_search<T: Interpolate>(v: &[T], x: &T) -> Result<usize, usize> {
+    // Everything before `low` is less than `x`; everything from `high` on
+    // is not.
+    let (mut low, mut high) = (0, v.len());
+    while low < high {
+        if v[low] >= *x {
+            break;
+        }
+        let (first, last) = (v[low], v[high - 1]);
+        if last < *x {
+            low = high;
+            break;
+        }
+        // Here first < x <= last, so the range has two or more elements.
+        let fraction = (x.to_f64() - first.to_f64()) / (last.to_f64() - first.to_f64());
+        let guess = low + (fraction * (high - 1 - low) as f64) as usize;
+        let probe = guess.clamp(low + 1, high - 1);
+        if v[probe] < *x {
+            low = probe + 1;
+        } else {
+            high = probe;
+        }
+    }
+    found(v, x, low)
+}
+
+/// Returns the `x` in `range` where `f` peaks, given that `f` rises
+/// strictly up to its peak and falls strictly after it.
+///
+/// Ternary search: compare `f` at two points a third of the way in from
+/// each end, and drop the third on the lower side, which cannot hold the
+/// peak. Takes O(log n) calls to `f`. On a plateau below the peak the
+/// comparison says nothing, and the result may be wrong.
+///
+/// # Panics
+///
+/// Panics if `range` is empty.
+pub fn ternary_search<T: PartialOrd>(range: Range<u64>, mut f: impl FnMut(u64) -> T) -> u64 {
+    assert!(range.start < range.end, "empty range");
+    let (mut low, mut high) = (range.start, range.end - 1);
+    while high - low > 2 {
+        let third = (high - low) / 3;
+        let (a, b) = (low + third, high - third);
+        if f(a) < f(b) {
+            low = a + 1;
+        } else {
+            high = b;
+        }
+    }
+    let mut best = low;
+    let mut peak = f(low);
+    for x in low + 1..=high {
+        let value = f(x);
+        if value.partial_cmp(&peak) == Some(Ordering::Greater) {
+            best = x;
+            peak = value;
+        }
+    }
+    best
+}
+
+/// Returns a point within `tolerance` of where `f` peaks on
+/// `[low, high]`, given that `f` rises strictly up to its peak and falls
+/// strictly after it.
+///
+/// Each step keeps two thirds of the interval, so it takes about
+/// 3.4 log2((high - low) / tolerance) calls to `f`. It also stops when the
+/// interval is too narrow to split as a float.
+///
+/// # Panics
+///
+/// Panics if `tolerance` is not positive.
+pub fn ternary_search_f64(
+    mut low: f64,
+    mut high: f64,
+    tolerance: f64,
+    mut f: impl FnMut(f64) -> f64,
+) -> f64 {
+    assert!(tolerance > 0.0, "tolerance must be positive");
+    while high - low > tolerance {
+        let third = (high - low) / 3.0;
+        let (a, b) = (low + third, high - third);
+        // A tolerance finer than the floats here can resolve.
+        if !(low < a && b < high) {
+            break;
+        }
+        if f(a) < f(b) {
+            low = a;
+        } else {
+            high = b;
+        }
+    }
+    (low + high) / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_searches_find_first_match() {
+        let v = [1, 3, 3, 3, 5, 8, 8, 13];
+        for search in [binary_search, exponential_search, interpolation_search] {
+            assert_eq!(search(&v, &3), Ok(1));
+            assert_eq!(search(&v, &8), Ok(5));
+            assert_eq!(search(&v, &0), Err(0));
+            assert_eq!(search(&v, &4), Err(4));
+            assert_eq!(search(&v, &20), Err(8));
+            assert_eq!(search(&[], &1), Err(0));
+        }
+        assert_eq!((lower_bound(&v, &3), upper_bound(&v, &3)), (1, 4));
+    }
+
+    #[test]
+    fn test_partition_point_range() {
+        // The smallest x with x² >= 10^12.
+        let root = partition_point_range(0..u64::MAX, |x| x.saturating_mul(x) < 1_000_000_000_000);
+        assert_eq!(root, 1_000_000);
+        assert_eq!(partition_point_range(5..10, |_| true), 10);
+        assert_eq!(partition_point_range(5..10, |_| false), 5);
+        assert_eq!(partition_point_range(7..7, |_| true), 7);
+        assert_eq!(partition_point(&[2, 4, 6, 7, 9], |x| x % 2 == 0), 3);
+    }
+
+    #[test]
+    fn test_ternary_search_peaks() {
+        let f = |x: u64| -((x as i64 - 37).pow(2));
+        assert_eq!(ternary_search(0..100, f), 37);
+        assert_eq!(ternary_search(37..38, f), 37);
+        assert_eq!(ternary_search(50..100, f), 50);
+        assert_eq!(ternary_search(0..10, f), 9);
+        let peak = ternary_search_f64(0.0, 3.0, 1e-9, |x| x.sin());
+        assert!((peak - std::f64::consts::FRAC_PI_2).abs() < 1e-6);
+        let peak = ternary_search_f64(0.0, 3.0, 1e-300, |x| x.sin());
+        assert!((peak - std::f64::consts::FRAC_PI_2).abs() < 1e-6);
+    }
+}
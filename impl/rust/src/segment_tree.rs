@@ -0,0 +1,193 @@
+//! Segment tree for range queries over a monoid.
+
+use crate::monoid::Monoid;
+use std::fmt;
+use std::ops::{Bound, Range, RangeBounds};
+
+/// Resolve a range of indices against a length, panicking like slice
+/// indexing if it is reversed or runs past the end.
+pub(crate) fn resolve_range<R: RangeBounds<usize>>(range: R, len: usize) -> Range<usize> {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start + 1,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end + 1,
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+    assert!(start <= end, "range start {} exceeds end {}", start, end);
+    assert!(
+        end <= len,
+        "range end {} out of bounds for length {}",
+        end,
+        len
+    );
+    start..end
+}
+
+/// A fixed-length sequence that answers "combine everything in this range"
+/// in O(log n), with O(log n) point updates.
+///
+/// The values sit in the leaves of a complete binary tree laid out in one
+/// array, leaves at `n..2n` and the parent of node `i` at `i / 2`, so each
+/// internal node holds the combination of its subtree. A query walks up from
+/// both ends of the range at once, picking up the O(log n) whole subtrees
+/// that tile it, and keeps left and right partial results apart so that
+/// non-commutative operations still combine in index order.
+pub struct SegmentTree<M: Monoid> {
+    /// Node 0 is unused.
+    tree: Vec<M::Value>,
+    len: usize,
+}
+
+impl<M: Monoid> Clone for SegmentTree<M> {
+    fn clone(&self) -> Self {
+        Self {
+            tree: self.tree.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<M: Monoid> fmt::Debug for SegmentTree<M>
+where
+    M::Value: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(&self.tree[self.len..]).finish()
+    }
+}
+
+impl<M: Monoid> SegmentTree<M> {
+    /// Create a new SegmentTree of `len` identity values.
+    pub fn new(len: usize) -> Self {
+        Self {
+            tree: vec![M::identity(); 2 * len],
+            len,
+        }
+    }
+
+    /// Build a SegmentTree over the given values in O(n).
+    pub fn from_vec(values: Vec<M::Value>) -> Self {
+        let len = values.len();
+        let mut tree = Vec::with_capacity(2 * len);
+        tree.resize(len, M::identity());
+        tree.extend(values);
+        for node in (1..len).rev() {
+            tree[node] = M::combine(&tree[2 * node], &tree[2 * node + 1]);
+        }
+        Self { tree, len }
+    }
+
+    /// Returns the number of values.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if there are no values.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get a reference to the value at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn get(&self, index: usize) -> &M::Value {
+        assert!(index < self.len, "index {} out of bounds", index);
+        &self.tree[self.len + index]
+    }
+
+    /// Replace the value at `index`, updating the nodes above it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set(&mut self, index: usize, value: M::Value) {
+        assert!(index < self.len, "index {} out of bounds", index);
+        let mut node = self.len + index;
+        self.tree[node] = value;
+        while node > 1 {
+            node /= 2;
+            self.tree[node] = M::combine(&self.tree[2 * node], &self.tree[2 * node + 1]);
+        }
+    }
+
+    /// Combine the values in `range` in index order. An empty range gives
+    /// the identity.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is reversed or runs past the end.
+    pub fn query<R: RangeBounds<usize>>(&self, range: R) -> M::Value {
+        let Range { start, end } = resolve_range(range, self.len);
+        let mut left = M::identity();
+        let mut right = M::identity();
+        let (mut lo, mut hi) = (start + self.len, end + self.len);
+        while lo < hi {
+            if lo & 1 == 1 {
+                left = M::combine(&left, &self.tree[lo]);
+                lo += 1;
+            }
+            if hi & 1 == 1 {
+                hi -= 1;
+                right = M::combine(&self.tree[hi], &right);
+            }
+            lo /= 2;
+            hi /= 2;
+        }
+        M::combine(&left, &right)
+    }
+}
+
+impl<M: Monoid> FromIterator<M::Value> for SegmentTree<M> {
+    fn from_iter<I: IntoIterator<Item = M::Value>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monoid::{Max, Min, Sum};
+
+    #[test]
+    fn test_sum_min_max_queries() {
+        let values = vec![5i64, -2, 7, 0, 3, 3, -8];
+        let sums: SegmentTree<Sum<i64>> = SegmentTree::from_vec(values.clone());
+        let mins: SegmentTree<Min<i64>> = values.iter().copied().collect();
+        let maxes: SegmentTree<Max<i64>> = values.iter().copied().collect();
+        assert_eq!(sums.query(..), 8);
+        assert_eq!(sums.query(1..4), 5);
+        assert_eq!(sums.query(2..=2), 7);
+        assert_eq!(sums.query(3..3), 0);
+        assert_eq!(mins.query(..6), -2);
+        assert_eq!(mins.query(2..), -8);
+        assert_eq!(maxes.query(3..6), 3);
+        assert_eq!(maxes.query(4..4), i64::MIN);
+    }
+
+    #[test]
+    fn test_point_updates() {
+        let mut tree: SegmentTree<Sum<u64>> = SegmentTree::new(10);
+        assert_eq!(tree.query(..), 0);
+        for i in 0..10 {
+            tree.set(i, i as u64);
+        }
+        assert_eq!(tree.query(..), 45);
+        tree.set(4, 100);
+        assert_eq!(*tree.get(4), 100);
+        assert_eq!(tree.query(3..6), 3 + 100 + 5);
+        assert_eq!(tree.query(5..), 35);
+    }
+
+    #[test]
+    #[should_panic(expected = "range end 6 out of bounds for length 5")]
+    fn test_query_past_end_panics() {
+        let tree: SegmentTree<Sum<i32>> = SegmentTree::new(5);
+        tree.query(2..6);
+    }
+}
@@ -0,0 +1,302 @@
+//! Single-source shortest paths on a [`Graph`]: Dijkstra and Bellman-Ford.
+
+use crate::graph::{Graph, NodeId};
+use crate::indexed_heap::IndexedPriorityQueue;
+use std::collections::VecDeque;
+use std::ops::Add;
+
+/// Shortest distances and a shortest-path tree from one source node.
+///
+/// Lookups take ids of the graph as it was when the paths were computed.
+#[derive(Debug, Clone)]
+pub struct ShortestPaths<W> {
+    source: NodeId,
+    /// Distance to each node by index, if reachable.
+    distances: Vec<Option<W>>,
+    /// The node before each reachable node on its shortest path.
+    parents: Vec<Option<NodeId>>,
+}
+
+impl<W: Copy> ShortestPaths<W> {
+    fn new(source: NodeId, bound: usize, zero: W) -> Self {
+        let mut paths = Self {
+            source,
+            distances: vec![None; bound],
+            parents: vec![None; bound],
+        };
+        paths.distances[source.index()] = Some(zero);
+        paths
+    }
+
+    /// Returns the node the paths start from.
+    pub fn source(&self) -> NodeId {
+        self.source
+    }
+
+    /// Returns the length of a shortest path to `node`, or `None` if it is
+    /// unreachable.
+    pub fn distance(&self, node: NodeId) -> Option<W> {
+        self.distances.get(node.index()).copied().flatten()
+    }
+
+    /// Returns the nodes of a shortest path from the source to `node`,
+    /// both included, or `None` if it is unreachable.
+    pub fn path(&self, node: NodeId) -> Option<Vec<NodeId>> {
+        self.distance(node)?;
+        let mut path = vec![node];
+        let mut current = node;
+        while let Some(parent) = self.parents[current.index()] {
+            path.push(parent);
+            current = parent;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// A cycle of negative total weight reachable from the source, which
+/// leaves some distances unbounded below.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NegativeCycle {
+    /// The nodes around the cycle, each joined to the next by an edge and
+    /// the last to the first.
+    pub nodes: Vec<NodeId>,
+}
+
+/// How [`bellman_ford`] picks the edges to relax.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Relaxation {
+    /// Relax every edge in each of up to V - 1 rounds, stopping early after
+    /// a round that changes nothing.
+    Rounds,
+    /// The Shortest Path Faster Algorithm: keep a FIFO queue of nodes whose
+    /// distance dropped and relax only their outgoing edges. The same
+    /// O(VE) worst case, but usually far fewer relaxations.
+    Spfa,
+}
+
+/// Returns shortest paths from `source`, whose edge weights must not be
+/// negative. `W::default()` is taken as zero.
+///
+/// Settles nodes in order of distance using an [`IndexedPriorityQueue`]
+/// with decrease-key, in O((V + E) log V).
+///
+/// # Panics
+///
+/// Panics if `source` is not in the graph, or if it reaches an edge with a
+/// negative weight.
+pub fn dijkstra<N, W>(graph: &Graph<N, W>, source: NodeId) -> ShortestPaths<W>
+where
+    W: Copy + Ord + Add<Output = W> + Default,
+{
+    assert!(graph.contains_node(source), "source node not in graph");
+    let zero = W::default();
+    let mut paths = ShortestPaths::new(source, graph.node_bound(), zero);
+    let mut ids = vec![source; graph.node_bound()];
+    let mut settled = vec![false; graph.node_bound()];
+    let mut queue = IndexedPriorityQueue::with_capacity(graph.node_bound());
+    queue.push(source.index(), zero);
+    while let Some((index, distance)) = queue.pop() {
+        settled[index] = true;
+        for edge in graph.outgoing(ids[index]) {
+            assert!(*edge.weight >= zero, "dijkstra needs non-negative weights");
+            let target = edge.target.index();
+            let candidate = distance + *edge.weight;
+            if settled[target] || paths.distances[target].is_some_and(|d| d <= candidate) {
+                continue;
+            }
+            if queue.contains(target) {
+                queue.decrease_key(target, candidate);
+            } else {
+                queue.push(target, candidate);
+            }
+            ids[target] = edge.target;
+            paths.distances[target] = Some(candidate);
+            paths.parents[target] = Some(ids[index]);
+        }
+    }
+    paths
+}
+
+/// Returns shortest paths from `source` on a graph whose edge weights may
+/// be negative, or a negative cycle reachable from it. `W::default()` is
+/// taken as zero.
+///
+/// Takes O(VE) in the worst case. In an undirected graph every edge can be
+/// crossed both ways, so a single negative edge is a negative cycle.
+///
+/// # Panics
+///
+/// Panics if `source` is not in the graph.
+pub fn bellman_ford<N, W>(
+    graph: &Graph<N, W>,
+    source: NodeId,
+    relaxation: Relaxation,
+) -> Result<ShortestPaths<W>, NegativeCycle>
+where
+    W: Copy + Ord + Add<Output = W> + Default,
+{
+    assert!(graph.contains_node(source), "source node not in graph");
+    match relaxation {
+        Relaxation::Rounds => rounds(graph, source),
+        Relaxation::Spfa => match spfa(graph, source) {
+            Some(paths) => Ok(paths),
+            // SPFA proves a cycle exists, but its parent pointers need not
+            // trace it out yet; the rounds find one that does.
+            None => match rounds(graph, source) {
+                Err(cycle) => Err(cycle),
+                Ok(_) => unreachable!("SPFA found a negative cycle"),
+            },
+        },
+    }
+}
+
+/// Relax the edge from `source` to `target` if it shortens the path to
+/// `target`, returning true if it did.
+fn relax<W>(paths: &mut ShortestPaths<W>, source: NodeId, target: NodeId, weight: W) -> bool
+where
+    W: Copy + Ord + Add<Output = W>,
+{
+    let Some(distance) = paths.distances[source.index()] else {
+        return false;
+    };
+    let candidate = distance + weight;
+    if paths.distances[target.index()].is_some_and(|d| d <= candidate) {
+        return false;
+    }
+    paths.distances[target.index()] = Some(candidate);
+    paths.parents[target.index()] = Some(source);
+    true
+}
+
+fn rounds<N, W>(graph: &Graph<N, W>, source: NodeId) -> Result<ShortestPaths<W>, NegativeCycle>
+where
+    W: Copy + Ord + Add<Output = W> + Default,
+{
+    let mut paths = ShortestPaths::new(source, graph.node_bound(), W::default());
+    let mut changed = None;
+    // A path without cycles has at most V - 1 edges, so the V-th round
+    // only relaxes something if a negative cycle is reachable.
+    for _ in 0..graph.node_count() {
+        changed = None;
+        for (node, _) in graph.nodes() {
+            for edge in graph.outgoing(node) {
+                if relax(&mut paths, edge.source, edge.target, *edge.weight) {
+                    changed = Some(edge.target);
+                }
+            }
+        }
+        if changed.is_none() {
+            return Ok(paths);
+        }
+    }
+    let mut node = changed.expect("a round changed something");
+    // The node was relaxed in the last round, so its parent chain leads
+    // back into the cycle within V steps.
+    for _ in 0..graph.node_count() {
+        node = paths.parents[node.index()].expect("relaxed nodes have parents");
+    }
+    let mut nodes = vec![node];
+    let mut current = paths.parents[node.index()].expect("cycle nodes have parents");
+    while current != node {
+        nodes.push(current);
+        current = paths.parents[current.index()].expect("cycle nodes have parents");
+    }
+    nodes.reverse();
+    Err(NegativeCycle { nodes })
+}
+
+/// Returns the shortest paths, or `None` if a negative cycle is reachable.
+fn spfa<N, W>(graph: &Graph<N, W>, source: NodeId) -> Option<ShortestPaths<W>>
+where
+    W: Copy + Ord + Add<Output = W> + Default,
+{
+    let bound = graph.node_bound();
+    let mut paths = ShortestPaths::new(source, bound, W::default());
+    // Edges on the path found to each node; reaching V means the path
+    // repeats a node, around a negative cycle.
+    let mut edges = vec![0; bound];
+    let mut queued = vec![false; bound];
+    let mut queue = VecDeque::from([source]);
+    queued[source.index()] = true;
+    while let Some(node) = queue.pop_front() {
+        queued[node.index()] = false;
+        for edge in graph.outgoing(node) {
+            let target = edge.target.index();
+            if relax(&mut paths, node, edge.target, *edge.weight) {
+                edges[target] = edges[node.index()] + 1;
+                if edges[target] >= graph.node_count() {
+                    return None;
+                }
+                if !queued[target] {
+                    queued[target] = true;
+                    queue.push_back(edge.target);
+                }
+            }
+        }
+    }
+    Some(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A directed graph on `n` nodes with the given weighted edges.
+    fn graph(n: usize, edges: &[(usize, usize, i64)]) -> (Graph<(), i64>, Vec<NodeId>) {
+        let mut graph = Graph::directed();
+        let nodes: Vec<_> = (0..n).map(|_| graph.add_node(())).collect();
+        for &(source, target, weight) in edges {
+            graph.add_edge(nodes[source], nodes[target], weight);
+        }
+        (graph, nodes)
+    }
+
+    #[test]
+    fn test_dijkstra_paths() {
+        let (graph, n) = graph(5, &[(0, 1, 4), (0, 2, 1), (2, 1, 2), (1, 3, 1), (2, 3, 5)]);
+        let paths = dijkstra(&graph, n[0]);
+        assert_eq!(paths.distance(n[3]), Some(4));
+        assert_eq!(paths.path(n[3]), Some(vec![n[0], n[2], n[1], n[3]]));
+        assert_eq!(paths.path(n[0]), Some(vec![n[0]]));
+        assert_eq!(paths.distance(n[4]), None);
+        assert_eq!(paths.path(n[4]), None);
+    }
+
+    #[test]
+    fn test_bellman_ford_negative_weights() {
+        let (graph, n) = graph(4, &[(0, 1, 5), (0, 2, 2), (1, 3, -4), (2, 1, -1)]);
+        for relaxation in [Relaxation::Rounds, Relaxation::Spfa] {
+            let paths = bellman_ford(&graph, n[0], relaxation).unwrap();
+            assert_eq!(paths.distance(n[1]), Some(1));
+            assert_eq!(paths.distance(n[3]), Some(-3));
+            assert_eq!(paths.path(n[3]), Some(vec![n[0], n[2], n[1], n[3]]));
+        }
+    }
+
+    #[test]
+    fn test_negative_cycle_witness() {
+        // 1 -> 2 -> 3 -> 1 weighs -1; 4 hangs off it, 5 is unreachable.
+        let (graph, n) = graph(
+            6,
+            &[
+                (0, 1, 1),
+                (1, 2, 1),
+                (2, 3, -4),
+                (3, 1, 2),
+                (3, 4, 1),
+                (5, 0, -9),
+            ],
+        );
+        for relaxation in [Relaxation::Rounds, Relaxation::Spfa] {
+            let cycle = bellman_ford(&graph, n[0], relaxation).unwrap_err();
+            let mut nodes = cycle.nodes.clone();
+            let start = nodes.iter().position(|&node| node == n[1]).unwrap();
+            nodes.rotate_left(start);
+            assert_eq!(nodes, [n[1], n[2], n[3]]);
+        }
+        // Starting past the cycle, nothing negative is reachable.
+        let paths = bellman_ford(&graph, n[4], Relaxation::Spfa).unwrap();
+        assert_eq!(paths.distance(n[1]), None);
+    }
+}
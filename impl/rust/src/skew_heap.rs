@@ -0,0 +1,198 @@
+//! Skew heap, the self-adjusting leftist heap.
+
+use std::fmt;
+
+type Link<T> = Option<Box<Node<T>>>;
+
+struct Node<T> {
+    item: T,
+    left: Link<T>,
+    right: Link<T>,
+}
+
+/// Merge two heaps along their right spines, swapping children at every
+/// node on the way.
+fn merge<T: Ord>(mut a: Link<T>, mut b: Link<T>) -> Link<T> {
+    // The textbook recursion, unrolled: a right spine here can be as long
+    // as the heap.
+    let mut path = Vec::new();
+    let mut rest = loop {
+        match (a, b) {
+            (None, rest) | (rest, None) => break rest,
+            (Some(x), Some(y)) => {
+                let (mut small, big) = if y.item < x.item { (y, x) } else { (x, y) };
+                a = small.right.take();
+                b = Some(big);
+                path.push(small);
+            }
+        }
+    };
+    while let Some(mut node) = path.pop() {
+        node.right = node.left.take();
+        node.left = rest;
+        rest = Some(node);
+    }
+    rest
+}
+
+/// A mergeable min-heap that keeps no balance information at all.
+///
+/// Meld is a [`LeftistHeap`](crate::LeftistHeap) meld that swaps the
+/// children of every node on the merge path unconditionally, instead of
+/// checking ranks. Any single meld can walk a long right spine, but the
+/// swaps move long spines to the left, and the amortized cost of every
+/// operation is O(log n). One less field per node and no rank
+/// bookkeeping make it the simplest mergeable heap to write down.
+pub struct SkewHeap<T> {
+    root: Link<T>,
+    len: usize,
+}
+
+impl<T: Ord> Default for SkewHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> SkewHeap<T> {
+    /// Create a new empty SkewHeap.
+    pub fn new() -> Self {
+        Self { root: None, len: 0 }
+    }
+
+    /// Returns the number of elements in the heap.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the heap contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Push an element onto the heap.
+    pub fn push(&mut self, item: T) {
+        let node = Box::new(Node {
+            item,
+            left: None,
+            right: None,
+        });
+        self.root = merge(self.root.take(), Some(node));
+        self.len += 1;
+    }
+
+    /// Get a reference to the smallest element, or `None` if the heap is
+    /// empty.
+    pub fn peek(&self) -> Option<&T> {
+        self.root.as_ref().map(|node| &node.item)
+    }
+
+    /// Remove and return the smallest element, or `None` if the heap is
+    /// empty.
+    pub fn pop(&mut self) -> Option<T> {
+        let mut root = self.root.take()?;
+        self.root = merge(root.left.take(), root.right.take());
+        self.len -= 1;
+        Some(root.item)
+    }
+
+    /// Move every element of `other` into this heap, in O(log n) amortized.
+    pub fn meld(&mut self, mut other: Self) {
+        self.root = merge(self.root.take(), other.root.take());
+        self.len += other.len;
+    }
+
+    /// Check the skew heap invariants, panicking on the first violation.
+    ///
+    /// Checks that every node is no larger than its children and that the
+    /// node count matches `len`; a skew heap has no shape invariant.
+    pub fn debug_validate(&self) {
+        let mut count = 0;
+        let mut stack: Vec<&Node<T>> = self.root.iter().map(|node| &**node).collect();
+        while let Some(node) = stack.pop() {
+            count += 1;
+            for child in [&node.left, &node.right].into_iter().flatten() {
+                assert!(node.item <= child.item, "heap order violated");
+                stack.push(child);
+            }
+        }
+        assert_eq!(count, self.len, "len does not match node count");
+    }
+}
+
+impl<T> Drop for SkewHeap<T> {
+    fn drop(&mut self) {
+        // Spines can be as long as the heap, too deep to drop recursively.
+        let mut stack: Vec<Box<Node<T>>> = self.root.take().into_iter().collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.left.take());
+            stack.extend(node.right.take());
+        }
+    }
+}
+
+impl<T: Ord + fmt::Debug> fmt::Debug for SkewHeap<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SkewHeap")
+            .field("len", &self.len)
+            .field("min", &self.peek())
+            .finish()
+    }
+}
+
+impl<T: Ord> Extend<T> for SkewHeap<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate<T: Ord>(heap: &SkewHeap<T>) {
+        if cfg!(debug_assertions) {
+            heap.debug_validate();
+        }
+    }
+
+    #[test]
+    fn test_push_pop_sorted() {
+        let mut heap = SkewHeap::new();
+        heap.extend((0..500u32).map(|i| i * 7919 % 503));
+        validate(&heap);
+        let mut previous = None;
+        while let Some(item) = heap.pop() {
+            assert!(previous <= Some(item));
+            previous = Some(item);
+        }
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn test_meld_interleaves() {
+        let mut left = SkewHeap::new();
+        let mut right = SkewHeap::new();
+        left.extend([1, 4, 9, 16]);
+        right.extend([2, 3, 5, 7, 11]);
+        left.meld(right);
+        validate(&left);
+        assert_eq!(left.len(), 9);
+        let drained: Vec<i32> = std::iter::from_fn(|| left.pop()).collect();
+        assert_eq!(drained, [1, 2, 3, 4, 5, 7, 9, 11, 16]);
+    }
+
+    #[test]
+    fn test_long_spines_do_not_overflow() {
+        // Ascending pushes hang every new node off the right of the
+        // spine; the swaps flip it, so spines grow as long as the heap.
+        let mut heap = SkewHeap::new();
+        heap.extend(0..200_000);
+        heap.extend((200_000..400_000).rev());
+        assert_eq!(heap.pop(), Some(0));
+        assert_eq!(heap.len(), 399_999);
+        drop(heap);
+    }
+}
@@ -0,0 +1,323 @@
+//! Probabilistic skip list ordered map.
+
+use crate::btree::{before_start, check_range, past_end};
+use std::fmt;
+use std::ops::RangeBounds;
+
+/// Index of a missing node: the end of a level, or the head as a
+/// predecessor.
+const NIL: usize = usize::MAX;
+const MAX_LEVEL: usize = 32;
+const DEFAULT_SEED: u64 = 0x5EED;
+
+/// Advance a xorshift64 state, which must be nonzero, and return it.
+pub(crate) fn xorshift64(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+#[derive(Debug, Clone)]
+struct Node<K, V> {
+    key: K,
+    value: V,
+    /// The next node at each level this node is linked into.
+    next: Vec<usize>,
+}
+
+/// An ordered map stored in a skip list.
+///
+/// All entries sit in a sorted linked list, and each also appears in the
+/// lists of the levels above with probability 1/2 per level, so every level
+/// skips over about half of the one below. Searches start at the top and
+/// drop a level whenever the next key overshoots, giving expected O(log n)
+/// operations with no rebalancing. Levels are drawn from a seeded generator
+/// so a given sequence of operations always builds the same list.
+#[derive(Clone)]
+pub struct SkipListMap<K, V> {
+    /// Arena of nodes linked by index.
+    nodes: Vec<Option<Node<K, V>>>,
+    /// Arena slots freed by `remove`.
+    free: Vec<usize>,
+    /// The first node at each level.
+    head: [usize; MAX_LEVEL],
+    /// Number of levels with at least one node.
+    level: usize,
+    len: usize,
+    rng: u64,
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for SkipListMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V> Default for SkipListMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> SkipListMap<K, V> {
+    /// Create a new empty SkipListMap with a fixed seed.
+    pub fn new() -> Self {
+        Self::with_seed(DEFAULT_SEED)
+    }
+
+    /// Create a new empty SkipListMap drawing node levels from `seed`.
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            nodes: Vec::new(),
+            free: Vec::new(),
+            head: [NIL; MAX_LEVEL],
+            level: 0,
+            len: 0,
+            // xorshift gets stuck at zero.
+            rng: seed | 1,
+        }
+    }
+
+    /// Returns the number of elements in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the map contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Clear all entries from the map.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.free.clear();
+        self.head = [NIL; MAX_LEVEL];
+        self.level = 0;
+        self.len = 0;
+    }
+
+    fn node(&self, index: usize) -> &Node<K, V> {
+        self.nodes[index].as_ref().expect("linked node is live")
+    }
+
+    /// The node after `prev` at `level`, where `NIL` is the head.
+    fn next(&self, prev: usize, level: usize) -> usize {
+        match prev {
+            NIL => self.head[level],
+            prev => self.node(prev).next[level],
+        }
+    }
+
+    fn set_next(&mut self, prev: usize, level: usize, next: usize) {
+        match prev {
+            NIL => self.head[level] = next,
+            prev => self.nodes[prev].as_mut().expect("linked node is live").next[level] = next,
+        }
+    }
+
+    /// Draw a level count: 1, then one more with probability 1/2 each time.
+    fn random_level(&mut self) -> usize {
+        (xorshift64(&mut self.rng).trailing_ones() as usize + 1).min(MAX_LEVEL)
+    }
+
+    /// Returns, per level, the last node whose key satisfies `is_before`.
+    fn predecessors<F>(&self, mut is_before: F) -> [usize; MAX_LEVEL]
+    where
+        F: FnMut(&K) -> bool,
+    {
+        let mut update = [NIL; MAX_LEVEL];
+        let mut prev = NIL;
+        for level in (0..self.level).rev() {
+            loop {
+                let next = self.next(prev, level);
+                if next == NIL || !is_before(&self.node(next).key) {
+                    break;
+                }
+                prev = next;
+            }
+            update[level] = prev;
+        }
+        update
+    }
+}
+
+impl<K: Ord, V> SkipListMap<K, V> {
+    /// Insert a key-value pair into the map.
+    ///
+    /// Returns the previous value if the key existed.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let mut update = self.predecessors(|k| *k < key);
+        let found = self.next(update[0], 0);
+        if found != NIL && self.node(found).key == key {
+            let node = self.nodes[found].as_mut().expect("linked node is live");
+            return Some(std::mem::replace(&mut node.value, value));
+        }
+
+        let height = self.random_level();
+        if height > self.level {
+            // New levels start from the head.
+            update[self.level..height].fill(NIL);
+            self.level = height;
+        }
+        let next = (0..height)
+            .map(|level| self.next(update[level], level))
+            .collect();
+        let node = Node { key, value, next };
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = Some(node);
+                index
+            }
+            None => {
+                self.nodes.push(Some(node));
+                self.nodes.len() - 1
+            }
+        };
+        for (level, &prev) in update.iter().enumerate().take(height) {
+            self.set_next(prev, level, index);
+        }
+        self.len += 1;
+        None
+    }
+
+    /// Get a reference to the value associated with the key.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let update = self.predecessors(|k| k < key);
+        match self.next(update[0], 0) {
+            NIL => None,
+            found => {
+                let node = self.node(found);
+                (node.key == *key).then_some(&node.value)
+            }
+        }
+    }
+
+    /// Check if the map contains the given key.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Remove a key-value pair from the map.
+    ///
+    /// Returns the removed value if the key existed.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let update = self.predecessors(|k| k < key);
+        let found = self.next(update[0], 0);
+        if found == NIL || self.node(found).key != *key {
+            return None;
+        }
+        let node = self.nodes[found].take().expect("linked node is live");
+        for (level, &next) in node.next.iter().enumerate() {
+            self.set_next(update[level], level, next);
+        }
+        self.free.push(found);
+        while self.level > 0 && self.head[self.level - 1] == NIL {
+            self.level -= 1;
+        }
+        self.len -= 1;
+        Some(node.value)
+    }
+
+    /// Iterate over all key-value pairs in key order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.range(..)
+    }
+
+    /// Iterate over the key-value pairs whose keys fall in `range`, in key
+    /// order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range starts after it ends, or starts and ends at the
+    /// same excluded key.
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Iter<'_, K, V> {
+        check_range(&range);
+        let start = self.predecessors(|k| before_start(k, range.start_bound()))[0];
+        let end = self.predecessors(|k| !past_end(k, range.end_bound()))[0];
+        Iter {
+            map: self,
+            next: self.next(start, 0),
+            end: self.next(end, 0),
+        }
+    }
+}
+
+/// An iterator over a key range of a [`SkipListMap`], in key order.
+pub struct Iter<'a, K, V> {
+    map: &'a SkipListMap<K, V>,
+    next: usize,
+    /// The first node past the range, or `NIL`.
+    end: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next == self.end {
+            return None;
+        }
+        let node = self.map.node(self.next);
+        self.next = node.next[0];
+        Some((&node.key, &node.value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_get_remove() {
+        let mut map = SkipListMap::new();
+        for i in (0..500).rev() {
+            assert_eq!(map.insert(i, i * 10), None);
+        }
+        assert_eq!(map.insert(7, 0), Some(70));
+        assert_eq!(map.get(&499), Some(&4990));
+        assert_eq!(map.get(&500), None);
+        for i in (0..500).step_by(2) {
+            assert!(map.remove(&i).is_some());
+        }
+        assert_eq!(map.remove(&0), None);
+        assert_eq!(map.len(), 250);
+        assert!(map.iter().map(|(k, _)| *k).eq((1..500).step_by(2)));
+    }
+
+    #[test]
+    fn test_same_seed_builds_same_levels() {
+        let build = |seed| {
+            let mut map = SkipListMap::with_seed(seed);
+            for i in 0..1000 {
+                map.insert(i, ());
+            }
+            map.nodes
+                .iter()
+                .map(|node| node.as_ref().unwrap().next.len())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(build(1), build(1));
+        assert_ne!(build(1), build(2));
+        // About half the nodes reach level 2.
+        let tall = build(1).iter().filter(|&&height| height > 1).count();
+        assert!((400..600).contains(&tall), "{} tall nodes", tall);
+    }
+
+    #[test]
+    fn test_remove_reuses_slots_and_drops_levels() {
+        let mut map = SkipListMap::new();
+        for i in 0..100 {
+            map.insert(i, i);
+        }
+        for i in 0..100 {
+            map.remove(&i);
+        }
+        assert_eq!(map.level, 0);
+        assert!(map.iter().next().is_none());
+        map.insert(5, 5);
+        assert_eq!(map.nodes.len(), 100);
+        assert_eq!(map.range(0..10).collect::<Vec<_>>(), [(&5, &5)]);
+    }
+}
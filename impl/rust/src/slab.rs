@@ -0,0 +1,205 @@
+//! Slab: dense storage with reusable `usize` keys.
+
+/// A slot of the slab.
+#[derive(Debug, Clone)]
+enum Entry<T> {
+    Occupied(T),
+    /// Free, linking to the next free slot, or to the end of the entries.
+    Vacant(usize),
+}
+
+/// Stores values in one vector and hands out their indices as keys.
+///
+/// Removed slots form a free list threaded through the vacant entries
+/// themselves, and insert takes the most recently freed slot before
+/// growing the vector, so the storage stays dense and one allocation serves
+/// many values. Insert, get and remove are O(1). Keys are plain indices and
+/// are reused: a key held past its value's removal silently refers to the
+/// next value inserted there. When that matters, use a
+/// [`SlotMap`](crate::SlotMap), which pays a generation per slot to catch
+/// stale keys.
+#[derive(Debug, Clone)]
+pub struct Slab<T> {
+    entries: Vec<Entry<T>>,
+    /// Head of the free list; `entries.len()` when no slot is free.
+    next_free: usize,
+    len: usize,
+}
+
+impl<T> Default for Slab<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Slab<T> {
+    /// Create a new empty Slab.
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Create a new empty Slab with room for `capacity` values.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+            next_free: 0,
+            len: 0,
+        }
+    }
+
+    /// Returns the number of values in the slab.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the slab contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of values the slab can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.entries.capacity()
+    }
+
+    /// Returns the key the next insert will use.
+    pub fn vacant_key(&self) -> usize {
+        self.next_free
+    }
+
+    /// Insert a value, returning its key.
+    pub fn insert(&mut self, value: T) -> usize {
+        let key = self.next_free;
+        if key == self.entries.len() {
+            self.entries.push(Entry::Occupied(value));
+            self.next_free = self.entries.len();
+        } else {
+            match std::mem::replace(&mut self.entries[key], Entry::Occupied(value)) {
+                Entry::Vacant(next) => self.next_free = next,
+                Entry::Occupied(_) => unreachable!("free list points at a value"),
+            }
+        }
+        self.len += 1;
+        key
+    }
+
+    /// Get a reference to the value at `key`.
+    pub fn get(&self, key: usize) -> Option<&T> {
+        match self.entries.get(key)? {
+            Entry::Occupied(value) => Some(value),
+            Entry::Vacant(_) => None,
+        }
+    }
+
+    /// Get a mutable reference to the value at `key`.
+    pub fn get_mut(&mut self, key: usize) -> Option<&mut T> {
+        match self.entries.get_mut(key)? {
+            Entry::Occupied(value) => Some(value),
+            Entry::Vacant(_) => None,
+        }
+    }
+
+    /// Check if a value is stored at `key`.
+    pub fn contains(&self, key: usize) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Remove the value at `key`, freeing the key for reuse.
+    ///
+    /// Returns the value if the slot was occupied.
+    pub fn remove(&mut self, key: usize) -> Option<T> {
+        if !self.contains(key) {
+            return None;
+        }
+        let entry = std::mem::replace(&mut self.entries[key], Entry::Vacant(self.next_free));
+        self.next_free = key;
+        self.len -= 1;
+        match entry {
+            Entry::Occupied(value) => Some(value),
+            Entry::Vacant(_) => unreachable!("checked occupied"),
+        }
+    }
+
+    /// Clear all values from the slab, keeping its capacity.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.next_free = 0;
+        self.len = 0;
+    }
+
+    /// Iterate over the keys and values of the occupied slots, in key order.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &T)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter_map(|(key, entry)| match entry {
+                Entry::Occupied(value) => Some((key, value)),
+                Entry::Vacant(_) => None,
+            })
+    }
+
+    /// Iterate over the keys and mutable values of the occupied slots, in
+    /// key order.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (usize, &mut T)> {
+        self.entries
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(key, entry)| match entry {
+                Entry::Occupied(value) => Some((key, value)),
+                Entry::Vacant(_) => None,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_get_remove() {
+        let mut slab = Slab::new();
+        let a = slab.insert('a');
+        let b = slab.insert('b');
+        assert_eq!((a, b), (0, 1));
+        *slab.get_mut(a).unwrap() = 'A';
+        assert_eq!(slab.get(a), Some(&'A'));
+        assert_eq!(slab.remove(b), Some('b'));
+        assert_eq!(slab.remove(b), None);
+        assert_eq!(slab.get(7), None);
+        assert_eq!(slab.len(), 1);
+    }
+
+    #[test]
+    fn test_freed_keys_reused_last_in_first_out() {
+        let mut slab: Slab<u32> = Slab::new();
+        for i in 0..6 {
+            slab.insert(i);
+        }
+        slab.remove(1);
+        slab.remove(4);
+        assert_eq!(slab.vacant_key(), 4);
+        assert_eq!(slab.insert(40), 4);
+        assert_eq!(slab.insert(10), 1);
+        assert_eq!(slab.insert(6), 6);
+        assert_eq!(slab.len(), 7);
+    }
+
+    #[test]
+    fn test_iter_skips_vacant_slots() {
+        let mut slab = Slab::new();
+        for i in 0..8u32 {
+            slab.insert(i * 10);
+        }
+        for key in [0, 3, 7] {
+            slab.remove(key);
+        }
+        for (_, value) in slab.iter_mut() {
+            *value += 1;
+        }
+        let entries: Vec<(usize, u32)> = slab.iter().map(|(key, &value)| (key, value)).collect();
+        assert_eq!(entries, [(1, 11), (2, 21), (4, 41), (5, 51), (6, 61)]);
+        slab.clear();
+        assert_eq!(slab.iter().count(), 0);
+        assert_eq!(slab.insert(0), 0);
+    }
+}
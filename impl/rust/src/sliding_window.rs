@@ -0,0 +1,255 @@
+//! Aggregates over a sliding window: the minimum and maximum of the last
+//! `w` values, and any monoid over a queue.
+
+use crate::binary_heap::HeapOrder;
+use crate::monoid::Monoid;
+use crate::monotonic::MonotonicDeque;
+use std::fmt;
+
+/// The minimum and maximum of the last `window` values pushed, each in
+/// O(1), with amortized O(1) pushes.
+///
+/// Two [`MonotonicDeque`]s, one per order, each keep only the values that
+/// could still be the extreme once older ones slide out.
+#[derive(Debug, Clone)]
+pub struct SlidingWindowMinMax<T> {
+    min: MonotonicDeque<T>,
+    max: MonotonicDeque<T>,
+    window: usize,
+}
+
+impl<T: Ord + Clone> SlidingWindowMinMax<T> {
+    /// Create a new empty SlidingWindowMinMax over the last `window`
+    /// values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `window` is zero.
+    pub fn new(window: usize) -> Self {
+        assert!(window > 0, "window must hold at least one value");
+        Self {
+            min: MonotonicDeque::new(HeapOrder::Min),
+            max: MonotonicDeque::new(HeapOrder::Max),
+            window,
+        }
+    }
+
+    /// Returns the window size.
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Returns the number of values in the window: the number pushed, up
+    /// to the window size.
+    pub fn len(&self) -> usize {
+        self.min.len()
+    }
+
+    /// Returns true if nothing has been pushed.
+    pub fn is_empty(&self) -> bool {
+        self.min.is_empty()
+    }
+
+    /// Push a value, sliding the oldest out if the window is full.
+    pub fn push(&mut self, value: T) {
+        if self.min.len() == self.window {
+            self.min.pop_front();
+            self.max.pop_front();
+        }
+        self.min.push_back(value.clone());
+        self.max.push_back(value);
+    }
+
+    /// Returns the smallest value in the window.
+    pub fn min(&self) -> Option<&T> {
+        self.min.top()
+    }
+
+    /// Returns the largest value in the window.
+    pub fn max(&self) -> Option<&T> {
+        self.max.top()
+    }
+}
+
+/// A FIFO queue that combines its values under a monoid in O(1), oldest
+/// first, with amortized O(1) push and pop.
+///
+/// The two-stack construction ("SWAG"): new values go on a back stack,
+/// which keeps the combination of all of them, and old values come off a
+/// front stack, where each entry keeps the combination of itself and
+/// everything newer on that stack. The whole queue is then the front
+/// stack's top entry combined with the back stack's total. When the front
+/// runs dry, the back stack is reversed onto it, recomputing each entry's
+/// combination in one pass; each value moves once, hence the amortized
+/// bound. Only `combine` is needed, never an inverse, so it works for
+/// minimum, maximum and non-commutative monoids alike.
+pub struct SlidingWindowAggregator<M: Monoid> {
+    /// Oldest on top: each value with the combination of it and every
+    /// newer value on this stack.
+    front: Vec<(M::Value, M::Value)>,
+    /// Newest on top.
+    back: Vec<M::Value>,
+    back_total: M::Value,
+}
+
+impl<M: Monoid> Clone for SlidingWindowAggregator<M> {
+    fn clone(&self) -> Self {
+        Self {
+            front: self.front.clone(),
+            back: self.back.clone(),
+            back_total: self.back_total.clone(),
+        }
+    }
+}
+
+impl<M: Monoid> fmt::Debug for SlidingWindowAggregator<M>
+where
+    M::Value: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let oldest_first = self.front.iter().rev().map(|(value, _)| value);
+        f.debug_list()
+            .entries(oldest_first.chain(&self.back))
+            .finish()
+    }
+}
+
+impl<M: Monoid> Default for SlidingWindowAggregator<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M: Monoid> SlidingWindowAggregator<M> {
+    /// Create a new empty SlidingWindowAggregator.
+    pub fn new() -> Self {
+        Self {
+            front: Vec::new(),
+            back: Vec::new(),
+            back_total: M::identity(),
+        }
+    }
+
+    /// Returns the number of values in the queue.
+    pub fn len(&self) -> usize {
+        self.front.len() + self.back.len()
+    }
+
+    /// Returns true if the queue is empty.
+    pub fn is_empty(&self) -> bool {
+        self.front.is_empty() && self.back.is_empty()
+    }
+
+    /// Add a value at the back of the queue.
+    pub fn push(&mut self, value: M::Value) {
+        self.back_total = M::combine(&self.back_total, &value);
+        self.back.push(value);
+    }
+
+    /// Remove and return the oldest value.
+    pub fn pop(&mut self) -> Option<M::Value> {
+        if self.front.is_empty() {
+            let mut total = M::identity();
+            while let Some(value) = self.back.pop() {
+                total = M::combine(&value, &total);
+                self.front.push((value, total.clone()));
+            }
+            self.back_total = M::identity();
+        }
+        self.front.pop().map(|(value, _)| value)
+    }
+
+    /// Returns the combination of every value in the queue, oldest first,
+    /// or the identity if it is empty.
+    pub fn query(&self) -> M::Value {
+        match self.front.last() {
+            Some((_, total)) => M::combine(total, &self.back_total),
+            None => self.back_total.clone(),
+        }
+    }
+
+    /// Remove every value.
+    pub fn clear(&mut self) {
+        self.front.clear();
+        self.back.clear();
+        self.back_total = M::identity();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monoid::{Max, Sum};
+
+    #[test]
+    fn test_min_max_slides() {
+        let mut window = SlidingWindowMinMax::new(3);
+        let mut seen = Vec::new();
+        for x in [4, 2, 12, 3, 8, 8, 1, 5] {
+            window.push(x);
+            seen.push((*window.min().unwrap(), *window.max().unwrap()));
+        }
+        assert_eq!(
+            seen,
+            [
+                (4, 4),
+                (2, 4),
+                (2, 12),
+                (2, 12),
+                (3, 12),
+                (3, 8),
+                (1, 8),
+                (1, 8)
+            ]
+        );
+        assert_eq!((window.len(), window.window()), (3, 3));
+    }
+
+    /// Concatenation: associative but not commutative.
+    struct Concat;
+
+    impl Monoid for Concat {
+        type Value = String;
+        fn identity() -> String {
+            String::new()
+        }
+        fn combine(a: &String, b: &String) -> String {
+            format!("{}{}", a, b)
+        }
+    }
+
+    #[test]
+    fn test_aggregator_keeps_order() {
+        let mut queue = SlidingWindowAggregator::<Concat>::new();
+        assert_eq!(queue.query(), "");
+        for s in ["a", "b", "c"] {
+            queue.push(s.to_string());
+        }
+        assert_eq!(queue.query(), "abc");
+        assert_eq!(queue.pop().as_deref(), Some("a"));
+        queue.push("d".to_string());
+        assert_eq!(queue.query(), "bcd");
+        assert_eq!(format!("{:?}", queue), r#"["b", "c", "d"]"#);
+        while queue.pop().is_some() {}
+        assert!(queue.is_empty());
+        assert_eq!(queue.query(), "");
+    }
+
+    #[test]
+    fn test_aggregator_with_lab_monoids() {
+        let mut sum = SlidingWindowAggregator::<Sum<i64>>::new();
+        let mut max = SlidingWindowAggregator::<Max<i64>>::new();
+        for x in 1..=10 {
+            sum.push(x);
+            max.push(-x);
+            if sum.len() > 4 {
+                sum.pop();
+                max.pop();
+            }
+        }
+        assert_eq!(sum.query(), 7 + 8 + 9 + 10);
+        assert_eq!(max.query(), -7);
+        sum.clear();
+        assert_eq!((sum.query(), sum.len()), (0, 0));
+    }
+}
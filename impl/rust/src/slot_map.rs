@@ -0,0 +1,244 @@
+//! Slot map: a generational arena with stable keys.
+
+/// Refers to a value inserted into a [`SlotMap`].
+///
+/// A key goes stale once its value is removed; lookups with it return
+/// `None` from then on, even after the slot holds a new value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SlotKey {
+    index: usize,
+    generation: u32,
+}
+
+impl SlotKey {
+    /// Returns the index of the key's slot.
+    ///
+    /// Indices are dense, below the map's
+    /// [`index_bound`](SlotMap::index_bound), so they can index side tables
+    /// kept in plain vectors. Keys to a slot's earlier occupants share it.
+    pub fn index(self) -> usize {
+        self.index
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Slot<T> {
+    /// `None` while the slot is on the free list.
+    value: Option<T>,
+    /// Bumped on every removal, so keys to earlier occupants never match.
+    generation: u32,
+}
+
+/// An arena handing out keys that stay valid until their own value is
+/// removed.
+///
+/// Values live in a vector of slots and a key is a slot index paired with
+/// the slot's generation at insert time. Insert, get and remove are O(1):
+/// removal bumps the generation and pushes the slot onto a free list for
+/// the next insert to reuse. A key to a removed value can never reach the
+/// slot's next occupant, which rules out the ABA problem that plain reused
+/// indices have. A slot whose generation would wrap is retired instead of
+/// reused. This gives linked structures and graphs stable handles without
+/// references or unsafe pointers.
+#[derive(Debug, Clone)]
+pub struct SlotMap<T> {
+    slots: Vec<Slot<T>>,
+    free: Vec<usize>,
+    len: usize,
+}
+
+impl<T> Default for SlotMap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> SlotMap<T> {
+    /// Create a new empty SlotMap.
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Create a new empty SlotMap with room for `capacity` values.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            slots: Vec::with_capacity(capacity),
+            free: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Returns the number of values in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the map contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of slots ever used; every key's index is below it.
+    pub fn index_bound(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Insert a value, returning its key.
+    pub fn insert(&mut self, value: T) -> SlotKey {
+        self.len += 1;
+        if let Some(index) = self.free.pop() {
+            let slot = &mut self.slots[index];
+            slot.value = Some(value);
+            return SlotKey {
+                index,
+                generation: slot.generation,
+            };
+        }
+        self.slots.push(Slot {
+            value: Some(value),
+            generation: 0,
+        });
+        SlotKey {
+            index: self.slots.len() - 1,
+            generation: 0,
+        }
+    }
+
+    fn slot(&self, key: SlotKey) -> Option<&Slot<T>> {
+        self.slots
+            .get(key.index)
+            .filter(|slot| slot.generation == key.generation)
+    }
+
+    /// Get a reference to the value for `key`, if it has not been removed.
+    pub fn get(&self, key: SlotKey) -> Option<&T> {
+        self.slot(key)?.value.as_ref()
+    }
+
+    /// Get a mutable reference to the value for `key`, if it has not been
+    /// removed.
+    pub fn get_mut(&mut self, key: SlotKey) -> Option<&mut T> {
+        self.slots
+            .get_mut(key.index)
+            .filter(|slot| slot.generation == key.generation)?
+            .value
+            .as_mut()
+    }
+
+    /// Check if `key` refers to a value in the map.
+    pub fn contains_key(&self, key: SlotKey) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Remove the value for `key`.
+    ///
+    /// Returns the value if the key was live.
+    pub fn remove(&mut self, key: SlotKey) -> Option<T> {
+        let slot = self
+            .slots
+            .get_mut(key.index)
+            .filter(|slot| slot.generation == key.generation)?;
+        let value = slot.value.take()?;
+        self.vacate(key.index);
+        Some(value)
+    }
+
+    /// Bump an emptied slot's generation and free it for reuse, unless the
+    /// generation is used up.
+    fn vacate(&mut self, index: usize) {
+        let slot = &mut self.slots[index];
+        self.len -= 1;
+        // A used-up slot stays empty at its last generation for good.
+        if let Some(generation) = slot.generation.checked_add(1) {
+            slot.generation = generation;
+            self.free.push(index);
+        }
+    }
+
+    /// Clear all values from the map, invalidating every key.
+    pub fn clear(&mut self) {
+        for index in 0..self.slots.len() {
+            if self.slots[index].value.take().is_some() {
+                self.vacate(index);
+            }
+        }
+    }
+
+    /// Iterate over the keys and values, in slot order.
+    pub fn iter(&self) -> impl Iterator<Item = (SlotKey, &T)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            let key = SlotKey {
+                index,
+                generation: slot.generation,
+            };
+            slot.value.as_ref().map(|value| (key, value))
+        })
+    }
+
+    /// Iterate over the keys and mutable values, in slot order.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (SlotKey, &mut T)> {
+        self.slots
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, slot)| {
+                let key = SlotKey {
+                    index,
+                    generation: slot.generation,
+                };
+                slot.value.as_mut().map(|value| (key, value))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_get_remove() {
+        let mut map = SlotMap::new();
+        let a = map.insert("a");
+        let b = map.insert("b");
+        assert_eq!(map.get(a), Some(&"a"));
+        *map.get_mut(b).unwrap() = "B";
+        assert_eq!(map.remove(b), Some("B"));
+        assert_eq!(map.remove(b), None);
+        assert_eq!(map.len(), 1);
+        let entries: Vec<_> = map.iter().collect();
+        assert_eq!(entries, [(a, &"a")]);
+    }
+
+    #[test]
+    fn test_reused_slot_rejects_old_key() {
+        let mut map = SlotMap::new();
+        let old = map.insert(1);
+        map.remove(old);
+        let new = map.insert(2);
+        // Same slot, new generation.
+        assert_eq!(old.index, new.index);
+        assert_ne!(old, new);
+        assert_eq!(map.get(old), None);
+        assert!(!map.contains_key(old));
+        assert_eq!(map.remove(old), None);
+        assert_eq!(map.get(new), Some(&2));
+
+        map.clear();
+        assert!(map.is_empty());
+        assert_eq!(map.get(new), None);
+    }
+
+    #[test]
+    fn test_exhausted_slot_is_retired() {
+        let mut map = SlotMap::new();
+        let key = map.insert(0);
+        map.slots[0].generation = u32::MAX;
+        let key = SlotKey {
+            generation: u32::MAX,
+            ..key
+        };
+        assert_eq!(map.remove(key), Some(0));
+        let next = map.insert(1);
+        assert_eq!(next.index, 1);
+        assert_eq!(map.get(key), None);
+    }
+}
@@ -0,0 +1,307 @@
+//! Hash Map implementation using open addressing with a struct-of-arrays layout.
+
+use crate::hashmap::TableStats;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+const DEFAULT_CAPACITY: usize = 16;
+const MAX_LOAD_FACTOR: f64 = 0.75;
+
+#[derive(Debug, Clone)]
+enum KeySlot<K> {
+    Empty,
+    Tombstone,
+    Occupied(K),
+}
+
+/// A linear-probing hash map that keeps keys and values in separate arrays.
+///
+/// Probing only reads the key array, so the cache lines it pulls in are
+/// packed with keys instead of being diluted by values. Values are touched
+/// once, on a hit. The win grows with the size of `V`.
+#[derive(Debug, Clone)]
+pub struct SoaMap<K, V> {
+    keys: Vec<KeySlot<K>>,
+    values: Vec<Option<V>>,
+    size: usize,
+    tombstones: usize,
+}
+
+impl<K, V> Default for SoaMap<K, V>
+where
+    K: Hash + Eq,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> SoaMap<K, V>
+where
+    K: Hash + Eq,
+{
+    /// Create a new empty SoaMap.
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// Create a new SoaMap with at least the specified capacity.
+    ///
+    /// The capacity is rounded up to a power of two.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let capacity = capacity.max(DEFAULT_CAPACITY).next_power_of_two();
+        let mut keys = Vec::with_capacity(capacity);
+        keys.resize_with(capacity, || KeySlot::Empty);
+        let mut values = Vec::with_capacity(capacity);
+        values.resize_with(capacity, || None);
+        Self {
+            keys,
+            values,
+            size: 0,
+            tombstones: 0,
+        }
+    }
+
+    /// Returns the number of elements in the map.
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Returns true if the map contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Returns the current capacity of the map.
+    pub fn capacity(&self) -> usize {
+        self.keys.len()
+    }
+
+    fn hash_key(&self, key: &K) -> usize {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        hasher.finish() as usize
+    }
+
+    fn load_factor(&self) -> f64 {
+        (self.size + self.tombstones) as f64 / self.keys.len() as f64
+    }
+
+    fn find_slot(&self, key: &K) -> (usize, bool) {
+        let mask = self.keys.len() - 1;
+        let mut index = self.hash_key(key) & mask;
+        let mut first_tombstone: Option<usize> = None;
+
+        for _ in 0..self.keys.len() {
+            match &self.keys[index] {
+                KeySlot::Empty => {
+                    return (first_tombstone.unwrap_or(index), false);
+                }
+                KeySlot::Tombstone if first_tombstone.is_none() => {
+                    first_tombstone = Some(index);
+                }
+                KeySlot::Occupied(k) if k == key => {
+                    return (index, true);
+                }
+                _ => {}
+            }
+            index = (index + 1) & mask;
+        }
+
+        (first_tombstone.unwrap_or(0), false)
+    }
+
+    fn resize(&mut self) {
+        let capacity = self.keys.len();
+        let new_capacity = if (self.size as f64) < capacity as f64 * MAX_LOAD_FACTOR / 2.0 {
+            capacity
+        } else {
+            capacity * 2
+        };
+        let old = std::mem::replace(self, Self::with_capacity(new_capacity));
+
+        for (slot, value) in old.keys.into_iter().zip(old.values) {
+            if let (KeySlot::Occupied(key), Some(value)) = (slot, value) {
+                self.insert(key, value);
+            }
+        }
+    }
+
+    /// Insert a key-value pair into the map.
+    ///
+    /// Returns the previous value if the key existed.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if self.load_factor() >= MAX_LOAD_FACTOR {
+            self.resize();
+        }
+
+        let (index, found) = self.find_slot(&key);
+        if found {
+            return self.values[index].replace(value);
+        }
+
+        if matches!(self.keys[index], KeySlot::Tombstone) {
+            self.tombstones -= 1;
+        }
+        self.keys[index] = KeySlot::Occupied(key);
+        self.values[index] = Some(value);
+        self.size += 1;
+        None
+    }
+
+    /// Get a reference to the value associated with the key.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        match self.find_slot(key) {
+            (index, true) => self.values[index].as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Get a mutable reference to the value associated with the key.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        match self.find_slot(key) {
+            (index, true) => self.values[index].as_mut(),
+            _ => None,
+        }
+    }
+
+    /// Remove a key-value pair from the map.
+    ///
+    /// Returns the removed value if the key existed.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let (index, found) = self.find_slot(key);
+        if !found {
+            return None;
+        }
+        self.keys[index] = KeySlot::Tombstone;
+        self.size -= 1;
+        self.tombstones += 1;
+        self.values[index].take()
+    }
+
+    /// Check if the map contains the given key.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.find_slot(key).1
+    }
+
+    /// Clear all entries from the map.
+    pub fn clear(&mut self) {
+        for slot in &mut self.keys {
+            *slot = KeySlot::Empty;
+        }
+        for value in &mut self.values {
+            *value = None;
+        }
+        self.size = 0;
+        self.tombstones = 0;
+    }
+
+    /// Iterate over all key-value pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.keys
+            .iter()
+            .zip(&self.values)
+            .filter_map(|(slot, value)| match (slot, value) {
+                (KeySlot::Occupied(key), Some(value)) => Some((key, value)),
+                _ => None,
+            })
+    }
+
+    /// Iterate over all keys, without touching the value array.
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.keys.iter().filter_map(|slot| match slot {
+            KeySlot::Occupied(key) => Some(key),
+            _ => None,
+        })
+    }
+
+    /// Iterate over all values.
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.values.iter().flatten()
+    }
+
+    /// Collect occupancy and probe-length statistics for the table.
+    pub fn stats(&self) -> TableStats {
+        let capacity = self.keys.len();
+        let mut histogram = Vec::new();
+        let mut total_probe_length = 0;
+
+        for (index, slot) in self.keys.iter().enumerate() {
+            if let KeySlot::Occupied(key) = slot {
+                let home = self.hash_key(key) & (capacity - 1);
+                let probe_length = (index + capacity - home) % capacity + 1;
+                if histogram.len() < probe_length {
+                    histogram.resize(probe_length, 0);
+                }
+                histogram[probe_length - 1] += 1;
+                total_probe_length += probe_length;
+            }
+        }
+
+        TableStats {
+            occupied: self.size,
+            tombstones: self.tombstones,
+            capacity,
+            load_factor: self.load_factor(),
+            avg_probe_length: if self.size == 0 {
+                0.0
+            } else {
+                total_probe_length as f64 / self.size as f64
+            },
+            max_probe_length: histogram.len(),
+            probe_length_histogram: histogram,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_get_remove() {
+        let mut map = SoaMap::new();
+        for i in 0..500 {
+            assert!(map.insert(format!("key{}", i), i).is_none());
+        }
+        assert_eq!(map.insert("key7".to_string(), 70), Some(7));
+        assert_eq!(map.len(), 500);
+        for i in (0..500).step_by(2) {
+            assert_eq!(map.remove(&format!("key{}", i)), Some(i));
+        }
+        assert_eq!(map.len(), 250);
+        assert_eq!(map.get(&"key7".to_string()), Some(&70));
+        assert!(!map.contains_key(&"key8".to_string()));
+        assert_eq!(map.iter().count(), 250);
+        assert_eq!(map.keys().count(), 250);
+        assert_eq!(map.values().count(), 250);
+    }
+
+    #[test]
+    fn test_arrays_stay_in_step() {
+        let mut map = SoaMap::with_capacity(16);
+        for i in 0..100 {
+            map.insert(i, [i; 8]);
+        }
+        for i in 0..50 {
+            map.remove(&i);
+        }
+        for (slot, value) in map.keys.iter().zip(&map.values) {
+            assert_eq!(matches!(slot, KeySlot::Occupied(_)), value.is_some());
+        }
+        assert_eq!(map.get(&75), Some(&[75; 8]));
+    }
+
+    #[test]
+    fn test_delete_churn_does_not_grow() {
+        let mut map = SoaMap::new();
+        for i in 0..10_000 {
+            map.insert(i, i);
+            if i >= 4 {
+                map.remove(&(i - 4));
+            }
+        }
+        assert_eq!(map.len(), 4);
+        assert_eq!(map.capacity(), 16);
+    }
+}
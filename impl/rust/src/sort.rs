@@ -0,0 +1,387 @@
+//! Comparison sorts: insertion, merge, quick, heap and introsort.
+//!
+//! Every algorithm sorts a slice in place into ascending order with the
+//! same signature as [`sort`], so they can stand in for each other and for
+//! `slice::sort_unstable`. Only [`insertion_sort`] and [`merge_sort`] are
+//! stable, keeping equal elements in their original order.
+
+use std::cmp::Ordering;
+use std::ptr;
+
+/// Slices this short are finished by insertion sort, which beats the
+/// divide-and-conquer sorts' overhead on them.
+const SMALL: usize = 16;
+
+/// A comparison sort.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Algorithm {
+    /// Insertion sort: stable, O(n²), O(n) on nearly sorted input.
+    Insertion,
+    /// Top-down merge sort: stable, O(n log n) always, n / 2 extra space.
+    Merge,
+    /// Quicksort with a median-of-three pivot and three-way partitioning:
+    /// O(n log n) expected, O(n²) on inputs built to defeat the pivot.
+    Quick,
+    /// Heapsort: O(n log n) always and in place, but it jumps around the
+    /// slice and is the slowest in practice.
+    Heap,
+    /// Introsort: quicksort that falls back to heapsort when recursion
+    /// runs deep, O(n log n) always.
+    Intro,
+}
+
+impl Algorithm {
+    /// Every algorithm, for tests and benchmarks that run them all.
+    pub const ALL: [Algorithm; 5] = [
+        Algorithm::Insertion,
+        Algorithm::Merge,
+        Algorithm::Quick,
+        Algorithm::Heap,
+        Algorithm::Intro,
+    ];
+
+    /// Sort `v` with this algorithm.
+    pub fn sort<T: Ord>(self, v: &mut [T]) {
+        match self {
+            Algorithm::Insertion => insertion_sort(v),
+            Algorithm::Merge => merge_sort(v),
+            Algorithm::Quick => quick_sort(v),
+            Algorithm::Heap => heap_sort(v),
+            Algorithm::Intro => introsort(v),
+        }
+    }
+}
+
+/// Sort `v` with [`introsort`], the fastest of these with no quadratic
+/// worst case.
+pub fn sort<T: Ord>(v: &mut [T]) {
+    introsort(v);
+}
+
+/// Sort `v` by insertion sort: each element is swapped back past the
+/// larger ones before it.
+///
+/// Stable and in place. O(n²) comparisons in general but O(n + d) for `d`
+/// inversions, so it is quick on short or nearly sorted slices, which is
+/// why the other sorts hand those to it.
+pub fn insertion_sort<T: Ord>(v: &mut [T]) {
+    for i in 1..v.len() {
+        let mut j = i;
+        while j > 0 && v[j] < v[j - 1] {
+            v.swap(j, j - 1);
+            j -= 1;
+        }
+    }
+}
+
+/// Sort `v` by top-down merge sort.
+///
+/// Sorts each half, then merges them by moving the left half out to a
+/// buffer and merging it with the right half back into the slice. Stable,
+/// O(n log n) comparisons whatever the input, with a buffer of n / 2
+/// elements. Halves that are already in order are not merged at all, so
+/// sorted input takes O(n).
+pub fn merge_sort<T: Ord>(v: &mut [T]) {
+    let mut buffer = Vec::with_capacity(v.len() / 2);
+    merge_sort_into(v, &mut buffer);
+}
+
+fn merge_sort_into<T: Ord>(v: &mut [T], buffer: &mut Vec<T>) {
+    if v.len() <= SMALL {
+        insertion_sort(v);
+        return;
+    }
+    let mid = v.len() / 2;
+    merge_sort_into(&mut v[..mid], buffer);
+    merge_sort_into(&mut v[mid..], buffer);
+    if v[mid - 1] > v[mid] {
+        merge(v, mid, buffer);
+    }
+}
+
+/// The elements of the left run still in the buffer, and the gap in the
+/// slice they must fill. Dropping it moves them into the gap, so the slice
+/// is whole again even if a comparison panics part way through a merge.
+struct Hole<T> {
+    start: *mut T,
+    end: *mut T,
+    dest: *mut T,
+}
+
+impl<T> Drop for Hole<T> {
+    fn drop(&mut self) {
+        // SAFETY: `start..end` are the initialized buffer elements not yet
+        // merged, and exactly that many slots from `dest` are vacated.
+        unsafe {
+            let remaining = self.end.offset_from(self.start) as usize;
+            ptr::copy_nonoverlapping(self.start, self.dest, remaining);
+        }
+    }
+}
+
+/// Merge the sorted runs `v[..mid]` and `v[mid..]`.
+pub(crate) fn merge<T: Ord>(v: &mut [T], mid: usize, buffer: &mut Vec<T>) {
+    let len = v.len();
+    buffer.reserve(mid);
+    let v = v.as_mut_ptr();
+    // SAFETY: the buffer has room for `mid` elements and its length stays
+    // zero, so it never drops what it holds. The left run is moved there
+    // bitwise, and from then on every slot of the slice before `dest` is
+    // filled, every slot from the right cursor on still holds its element,
+    // and the gap between them is exactly as long as the part of the left
+    // run left in the buffer. `Hole` fills that gap when it drops.
+    unsafe {
+        ptr::copy_nonoverlapping(v, buffer.as_mut_ptr(), mid);
+        let mut hole = Hole {
+            start: buffer.as_mut_ptr(),
+            end: buffer.as_mut_ptr().add(mid),
+            dest: v,
+        };
+        let mut right = v.add(mid);
+        let right_end = v.add(len);
+        while hole.start < hole.end && right < right_end {
+            // Take from the right only if strictly smaller, for stability.
+            if *right < *hole.start {
+                ptr::copy_nonoverlapping(right, hole.dest, 1);
+                right = right.add(1);
+            } else {
+                ptr::copy_nonoverlapping(hole.start, hole.dest, 1);
+                hole.start = hole.start.add(1);
+            }
+            hole.dest = hole.dest.add(1);
+        }
+    }
+}
+
+/// Sort `v` by quicksort.
+///
+/// The pivot is the median of the first, middle and last elements, which
+/// keeps sorted and reversed input at O(n log n). Partitioning is
+/// three-way, into elements less than, equal to and greater than the
+/// pivot, so runs of duplicates are finished in one pass instead of
+/// degrading to O(n²). Recurses into the smaller side and loops on the
+/// larger, so the stack stays O(log n). Inputs crafted against the
+/// median-of-three still take O(n²); [`introsort`] guards against those.
+pub fn quick_sort<T: Ord>(mut v: &mut [T]) {
+    while v.len() > SMALL {
+        let (less, greater) = partition(v);
+        let (left, rest) = v.split_at_mut(less);
+        let right = &mut rest[greater - less..];
+        if left.len() < right.len() {
+            quick_sort(left);
+            v = right;
+        } else {
+            quick_sort(right);
+            v = left;
+        }
+    }
+    insertion_sort(v);
+}
+
+/// Sort `v` by introsort.
+///
+/// Runs [`quick_sort`] until the recursion is 2 log₂ n deep, which only
+/// happens when pivots keep landing near the ends, and hands any slice
+/// reached at that depth to [`heap_sort`]. So it has quicksort's speed on
+/// ordinary input and heapsort's O(n log n) bound on all of it. Not
+/// stable.
+pub fn introsort<T: Ord>(v: &mut [T]) {
+    let depth = 2 * (usize::BITS - v.len().leading_zeros());
+    introsort_to(v, depth);
+}
+
+fn introsort_to<T: Ord>(mut v: &mut [T], mut depth: u32) {
+    while v.len() > SMALL {
+        if depth == 0 {
+            heap_sort(v);
+            return;
+        }
+        depth -= 1;
+        let (less, greater) = partition(v);
+        let (left, rest) = v.split_at_mut(less);
+        let right = &mut rest[greater - less..];
+        if left.len() < right.len() {
+            introsort_to(left, depth);
+            v = right;
+        } else {
+            introsort_to(right, depth);
+            v = left;
+        }
+    }
+    insertion_sort(v);
+}
+
+/// Partition `v` around the median of its first, middle and last elements,
+/// returning `(less, greater)` such that `v[..less]` is below the pivot,
+/// `v[less..greater]` equal to it and `v[greater..]` above it.
+pub(crate) fn partition<T: Ord>(v: &mut [T]) -> (usize, usize) {
+    let (a, b, c) = (0, v.len() / 2, v.len() - 1);
+    let median = if v[a] < v[b] {
+        if v[b] < v[c] {
+            b
+        } else if v[a] < v[c] {
+            c
+        } else {
+            a
+        }
+    } else if v[a] < v[c] {
+        a
+    } else if v[b] < v[c] {
+        c
+    } else {
+        b
+    };
+    v.swap(0, median);
+    // v[less] is always a copy of the pivot: the first of the equal run.
+    let (mut less, mut i, mut greater) = (0, 1, v.len());
+    while i < greater {
+        match v[i].cmp(&v[less]) {
+            Ordering::Less => {
+                v.swap(less, i);
+                less += 1;
+                i += 1;
+            }
+            Ordering::Greater => {
+                greater -= 1;
+                v.swap(i, greater);
+            }
+            Ordering::Equal => i += 1,
+        }
+    }
+    (less, greater)
+}
+
+/// Sort `v` by heapsort: build a max-heap in place, then repeatedly swap
+/// its root to the end of the shrinking heap.
+///
+/// O(n log n) comparisons on every input with O(1) extra space, but each
+/// sift-down jumps across the slice, so it misses the cache far more than
+/// quicksort. Not stable.
+pub fn heap_sort<T: Ord>(v: &mut [T]) {
+    for root in (0..v.len() / 2).rev() {
+        sift_down(v, root);
+    }
+    for end in (1..v.len()).rev() {
+        v.swap(0, end);
+        sift_down(&mut v[..end], 0);
+    }
+}
+
+/// Move `v[node]` down the max-heap `v` until neither child is larger.
+fn sift_down<T: Ord>(v: &mut [T], mut node: usize) {
+    loop {
+        let mut child = 2 * node + 1;
+        if child >= v.len() {
+            return;
+        }
+        if child + 1 < v.len() && v[child] < v[child + 1] {
+            child += 1;
+        }
+        if v[node] >= v[child] {
+            return;
+        }
+        v.swap(node, child);
+        node = child;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::panic::{self, AssertUnwindSafe};
+    use std::rc::Rc;
+
+    #[test]
+    fn test_every_algorithm_sorts() {
+        let inputs: [Vec<i32>; 5] = [
+            vec![],
+            vec![1],
+            (0..100).rev().collect(),
+            (0..200).map(|i| (i * 37) % 11).collect(),
+            (0..300).map(|i| (i * 7919) % 263 - 131).collect(),
+        ];
+        for input in inputs {
+            let mut expected = input.clone();
+            expected.sort();
+            for algorithm in Algorithm::ALL {
+                let mut v = input.clone();
+                algorithm.sort(&mut v);
+                assert_eq!(v, expected, "{:?}", algorithm);
+            }
+        }
+    }
+
+    #[test]
+    fn test_partition_three_ways() {
+        let mut v = vec![5, 1, 5, 9, 5, 2, 8, 5, 5];
+        let (less, greater) = partition(&mut v);
+        assert!(v[..less].iter().all(|&x| x < 5));
+        assert!(v[less..greater].iter().all(|&x| x == 5));
+        assert!(v[greater..].iter().all(|&x| x > 5));
+        assert_eq!(greater - less, 5);
+    }
+
+    /// Counts live copies, and panics when compared after a set number of
+    /// comparisons.
+    struct Tracked {
+        key: u32,
+        live: Rc<Cell<usize>>,
+        budget: Rc<Cell<usize>>,
+    }
+
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            self.live.set(self.live.get() - 1);
+        }
+    }
+
+    impl PartialEq for Tracked {
+        fn eq(&self, other: &Self) -> bool {
+            self.cmp(other) == Ordering::Equal
+        }
+    }
+
+    impl Eq for Tracked {}
+
+    impl PartialOrd for Tracked {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Tracked {
+        fn cmp(&self, other: &Self) -> Ordering {
+            let budget = self.budget.get();
+            assert!(budget > 0, "comparison budget spent");
+            self.budget.set(budget - 1);
+            self.key.cmp(&other.key)
+        }
+    }
+
+    #[test]
+    fn test_merge_survives_panicking_comparison() {
+        for budget in [50, 200, 350, 500] {
+            let live = Rc::new(Cell::new(0));
+            let budget = Rc::new(Cell::new(budget));
+            let mut v: Vec<Tracked> = (0..100u32)
+                .map(|i| {
+                    live.set(live.get() + 1);
+                    Tracked {
+                        key: (i * 31) % 100,
+                        live: Rc::clone(&live),
+                        budget: Rc::clone(&budget),
+                    }
+                })
+                .collect();
+            let result = panic::catch_unwind(AssertUnwindSafe(|| merge_sort(&mut v)));
+            assert!(result.is_err(), "the budget runs out mid-sort");
+            // Every element is still there exactly once.
+            let mut keys: Vec<u32> = v.iter().map(|t| t.key).collect();
+            keys.sort();
+            assert_eq!(keys, (0..100).collect::<Vec<_>>());
+            drop(v);
+            assert_eq!(live.get(), 0);
+        }
+    }
+}
@@ -0,0 +1,185 @@
+//! Space-Saving top-k heavy hitters over a stream.
+
+use crate::freq_list::FrequencyList;
+use crate::hashmap::HashMap;
+use std::hash::Hash;
+
+#[derive(Debug, Clone)]
+struct Counter<K> {
+    key: K,
+    /// How much of the count may have been inherited from an evicted key.
+    error: u64,
+}
+
+/// Approximate counts of the most frequent keys in a stream, in bounded
+/// memory.
+///
+/// Tracks at most `capacity` keys. A new key arriving when every counter
+/// is taken replaces the key with the smallest count and inherits that
+/// count, recorded as its possible overestimate. So each reported count is
+/// at most `error` above the true one and never below it, and every key
+/// occurring more than `total / capacity` times is guaranteed to be
+/// tracked. Uses the stream-summary design: counters with equal counts
+/// share a bucket, and the buckets form a linked list in increasing count.
+/// An observation moves one counter into the neighboring bucket and an
+/// eviction takes a counter from the first, so each item costs O(1).
+#[derive(Debug, Clone)]
+pub struct SpaceSaving<K> {
+    positions: HashMap<K, usize>,
+    counters: FrequencyList<Counter<K>>,
+    capacity: usize,
+    total: u64,
+}
+
+impl<K> SpaceSaving<K>
+where
+    K: Hash + Eq + Clone,
+{
+    /// Create an empty summary tracking at most `capacity` keys.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "Space-Saving capacity must be at least 1");
+        Self {
+            positions: HashMap::with_capacity(capacity),
+            counters: FrequencyList::with_capacity(capacity),
+            capacity,
+            total: 0,
+        }
+    }
+
+    /// Returns the number of keys being tracked.
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Returns true if no keys have been observed.
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Returns the maximum number of keys tracked.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of items observed.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Count one occurrence of `key`.
+    pub fn observe(&mut self, key: K) {
+        self.total += 1;
+        if let Some(&index) = self.positions.get(&key) {
+            self.counters.touch(index);
+            return;
+        }
+        if self.positions.len() < self.capacity {
+            let index = self.counters.insert(Counter {
+                key: key.clone(),
+                error: 0,
+            });
+            self.positions.insert(key, index);
+            return;
+        }
+        // The oldest counter with the smallest count gives way, in place.
+        let index = self.counters.least().expect("a full summary has counters");
+        let error = self.counters.freq(index);
+        let counter = self.counters.get_mut(index);
+        self.positions.remove(&counter.key);
+        self.positions.insert(key.clone(), index);
+        *counter = Counter { key, error };
+        self.counters.touch(index);
+    }
+
+    /// Returns the estimated count and maximum overestimate for `key`, or
+    /// `None` if it is not tracked.
+    pub fn estimate(&self, key: &K) -> Option<(u64, u64)> {
+        self.positions
+            .get(key)
+            .map(|&index| (self.counters.freq(index), self.counters.get(index).error))
+    }
+
+    /// Returns up to `k` tracked keys with their estimated counts and
+    /// maximum overestimates, most frequent first.
+    pub fn top_k(&self, k: usize) -> Vec<(&K, u64, u64)> {
+        let mut top: Vec<(&K, u64, u64)> = self
+            .counters
+            .iter()
+            .map(|(counter, count)| (&counter.key, count, counter.error))
+            .collect();
+        top.sort_by(|a, b| b.1.cmp(&a.1).then(a.2.cmp(&b.2)));
+        top.truncate(k);
+        top
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exact_under_capacity() {
+        let mut summary = SpaceSaving::new(10);
+        for key in "abracadabra".chars() {
+            summary.observe(key);
+        }
+        assert_eq!(summary.top_k(1), [(&'a', 5, 0)]);
+        assert_eq!(summary.estimate(&'b'), Some((2, 0)));
+        assert_eq!(summary.estimate(&'r'), Some((2, 0)));
+        assert_eq!(summary.estimate(&'d'), Some((1, 0)));
+        assert_eq!(summary.total(), 11);
+    }
+
+    #[test]
+    fn test_heavy_hitters_survive_churn() {
+        let mut summary = SpaceSaving::new(20);
+        let mut truth: HashMap<u64, u64> = HashMap::new();
+        // Three hot keys among a long tail of one-off keys.
+        for i in 0..10_000u64 {
+            let key = match i % 10 {
+                0..=2 => 1,
+                3..=4 => 2,
+                5 => 3,
+                _ => 1000 + i,
+            };
+            summary.observe(key);
+            *truth.entry(key).or_insert(0) += 1;
+        }
+        let top: Vec<u64> = summary.top_k(3).iter().map(|(key, _, _)| **key).collect();
+        assert_eq!(top, [1, 2, 3]);
+        for (key, count, error) in summary.top_k(20) {
+            let actual = truth.get(key).copied().unwrap();
+            assert!(count - error <= actual && actual <= count);
+        }
+        assert_eq!(summary.len(), 20);
+    }
+
+    #[test]
+    fn test_eviction_inherits_minimum() {
+        let mut summary = SpaceSaving::new(2);
+        for key in ["a", "a", "b", "c"] {
+            summary.observe(key);
+        }
+        // "c" replaced "b", the minimum with count 1.
+        assert_eq!(summary.estimate(&"b"), None);
+        assert_eq!(summary.estimate(&"c"), Some((2, 1)));
+        assert_eq!(summary.estimate(&"a"), Some((2, 0)));
+    }
+
+    #[test]
+    fn test_counts_sum_to_total() {
+        // Every observation adds one to exactly one counter, whichever
+        // bucket it moves between.
+        let mut summary = SpaceSaving::new(7);
+        for i in 0..5_000u64 {
+            summary.observe(i * i % 31);
+            let sum: u64 = summary.top_k(7).iter().map(|(_, count, _)| count).sum();
+            assert_eq!(sum, summary.total());
+        }
+        assert_eq!(summary.len(), 7);
+    }
+}
@@ -0,0 +1,128 @@
+//! Minimum spanning forests of an undirected [`Graph`]: Prim.
+
+use crate::graph::{EdgeId, Graph, NodeId};
+use crate::indexed_heap::IndexedPriorityQueue;
+use std::ops::Add;
+
+/// A minimum spanning forest: one minimum spanning tree per connected
+/// component.
+///
+/// Edge ids refer to the graph as it was when the forest was computed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpanningForest<W> {
+    edges: Vec<EdgeId>,
+    weight: W,
+}
+
+impl<W: Copy> SpanningForest<W> {
+    /// Returns the edges of the forest, in the order they were added.
+    pub fn edges(&self) -> &[EdgeId] {
+        &self.edges
+    }
+
+    /// Returns the total weight of the forest's edges.
+    pub fn weight(&self) -> W {
+        self.weight
+    }
+}
+
+/// Returns a minimum spanning forest of an undirected graph.
+/// `W::default()` is taken as zero; weights may be negative.
+///
+/// Grows a tree from each node not yet reached, always adding the lightest
+/// edge leaving it. The cheapest known edge into each outside node sits in
+/// an [`IndexedPriorityQueue`] keyed by node, lowered with decrease-key,
+/// so the whole forest takes O((V + E) log V). Self-loops never join two
+/// trees and are skipped.
+///
+/// # Panics
+///
+/// Panics if the graph is directed.
+pub fn prim<N, W>(graph: &Graph<N, W>) -> SpanningForest<W>
+where
+    W: Copy + Ord + Add<Output = W> + Default,
+{
+    assert!(!graph.is_directed(), "prim needs an undirected graph");
+    let bound = graph.node_bound();
+    let mut forest = SpanningForest {
+        edges: Vec::new(),
+        weight: W::default(),
+    };
+    let mut ids: Vec<Option<NodeId>> = vec![None; bound];
+    for (id, _) in graph.nodes() {
+        ids[id.index()] = Some(id);
+    }
+    let mut in_tree = vec![false; bound];
+    // The cheapest known edge joining each outside node to the tree.
+    let mut best: Vec<Option<(EdgeId, W)>> = vec![None; bound];
+    let mut queue = IndexedPriorityQueue::with_capacity(bound);
+    for root in 0..bound {
+        if ids[root].is_none() || in_tree[root] {
+            continue;
+        }
+        queue.push(root, W::default());
+        while let Some((index, _)) = queue.pop() {
+            in_tree[index] = true;
+            if let Some((edge, weight)) = best[index] {
+                forest.edges.push(edge);
+                forest.weight = forest.weight + weight;
+            }
+            let node = ids[index].expect("queued nodes are in the graph");
+            for edge in graph.outgoing(node) {
+                let target = edge.target.index();
+                let weight = *edge.weight;
+                if in_tree[target] || best[target].is_some_and(|(_, w)| w <= weight) {
+                    continue;
+                }
+                if queue.contains(target) {
+                    queue.decrease_key(target, weight);
+                } else {
+                    queue.push(target, weight);
+                }
+                best[target] = Some((edge.id, weight));
+            }
+        }
+    }
+    forest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prim_picks_lightest_edges() {
+        let mut graph = Graph::undirected();
+        let n: Vec<_> = (0..4).map(|_| graph.add_node(())).collect();
+        graph.add_edge(n[0], n[1], 4);
+        let light = graph.add_edge(n[1], n[2], 1);
+        graph.add_edge(n[0], n[2], 3);
+        graph.add_edge(n[2], n[3], 5);
+        graph.add_edge(n[1], n[3], 2);
+        graph.add_edge(n[3], n[3], -7);
+        let forest = prim(&graph);
+        assert_eq!(forest.weight(), 6);
+        assert_eq!(forest.edges().len(), 3);
+        assert!(forest.edges().contains(&light));
+    }
+
+    #[test]
+    fn test_prim_spans_each_component() {
+        let mut graph = Graph::undirected();
+        let n: Vec<_> = (0..5).map(|_| graph.add_node(())).collect();
+        graph.add_edge(n[0], n[1], -2);
+        graph.add_edge(n[3], n[4], 7);
+        graph.add_edge(n[4], n[3], 6);
+        graph.remove_node(n[2]);
+        let forest = prim(&graph);
+        assert_eq!(forest.weight(), 4);
+        assert_eq!(forest.edges().len(), 2);
+        assert_eq!(prim(&Graph::<(), i32>::undirected()).edges(), &[]);
+    }
+
+    #[test]
+    #[should_panic(expected = "undirected")]
+    fn test_prim_rejects_directed() {
+        prim(&Graph::<(), i32>::directed());
+    }
+}
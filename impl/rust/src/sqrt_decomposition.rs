@@ -0,0 +1,188 @@
+//! Square-root decomposition for range queries over a monoid.
+
+use crate::monoid::Monoid;
+use crate::segment_tree::resolve_range;
+use std::fmt;
+use std::ops::{Range, RangeBounds};
+
+/// A fixed-length sequence split into blocks of about `sqrt(n)` values, each
+/// with a cached combination of its contents.
+///
+/// A query combines the loose values at either end of the range one by one
+/// and the whole blocks in between by their aggregates, so it touches at
+/// most about `3 sqrt(n)` values; a point update recombines one block, about
+/// `sqrt(n)` values. That is asymptotically worse than a
+/// [`SegmentTree`](crate::SegmentTree), but both arrays are scanned
+/// sequentially with no index arithmetic beyond a division, so at small `n`
+/// the simple loops can win.
+pub struct SqrtDecomposition<M: Monoid> {
+    values: Vec<M::Value>,
+    /// The combination of each `block_size` run of `values`.
+    blocks: Vec<M::Value>,
+    block_size: usize,
+}
+
+impl<M: Monoid> Clone for SqrtDecomposition<M> {
+    fn clone(&self) -> Self {
+        Self {
+            values: self.values.clone(),
+            blocks: self.blocks.clone(),
+            block_size: self.block_size,
+        }
+    }
+}
+
+impl<M: Monoid> fmt::Debug for SqrtDecomposition<M>
+where
+    M::Value: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(&self.values).finish()
+    }
+}
+
+fn combine_all<'a, M: Monoid>(values: impl IntoIterator<Item = &'a M::Value>) -> M::Value
+where
+    M::Value: 'a,
+{
+    values
+        .into_iter()
+        .fold(M::identity(), |acc, value| M::combine(&acc, value))
+}
+
+impl<M: Monoid> SqrtDecomposition<M> {
+    /// Create a new SqrtDecomposition of `len` identity values.
+    pub fn new(len: usize) -> Self {
+        Self::from_vec(vec![M::identity(); len])
+    }
+
+    /// Build a SqrtDecomposition over the given values, with blocks of
+    /// about `sqrt(n)` values.
+    pub fn from_vec(values: Vec<M::Value>) -> Self {
+        let block_size = ((values.len() as f64).sqrt() as usize).max(1);
+        Self::with_block_size(values, block_size)
+    }
+
+    /// Build a SqrtDecomposition over the given values with blocks of
+    /// `block_size` values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `block_size` is zero.
+    pub fn with_block_size(values: Vec<M::Value>, block_size: usize) -> Self {
+        assert!(block_size > 0, "block size must be at least 1");
+        let blocks = values.chunks(block_size).map(combine_all::<M>).collect();
+        Self {
+            values,
+            blocks,
+            block_size,
+        }
+    }
+
+    /// Returns the number of values.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns true if there are no values.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns the number of values per block.
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    /// Get a reference to the value at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn get(&self, index: usize) -> &M::Value {
+        assert!(index < self.len(), "index {} out of bounds", index);
+        &self.values[index]
+    }
+
+    /// Replace the value at `index`, recombining its block.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set(&mut self, index: usize, value: M::Value) {
+        assert!(index < self.len(), "index {} out of bounds", index);
+        self.values[index] = value;
+        let block = index / self.block_size;
+        let start = block * self.block_size;
+        let end = (start + self.block_size).min(self.len());
+        self.blocks[block] = combine_all::<M>(&self.values[start..end]);
+    }
+
+    /// Combine the values in `range` in index order. An empty range gives
+    /// the identity.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is reversed or runs past the end.
+    pub fn query<R: RangeBounds<usize>>(&self, range: R) -> M::Value {
+        let Range { start, end } = resolve_range(range, self.len());
+        // The whole blocks inside the range, if any.
+        let first_block = (start + self.block_size - 1) / self.block_size;
+        let last_block = end / self.block_size;
+        if first_block >= last_block {
+            return combine_all::<M>(&self.values[start..end]);
+        }
+        let head = combine_all::<M>(&self.values[start..first_block * self.block_size]);
+        let middle = combine_all::<M>(&self.blocks[first_block..last_block]);
+        let tail = combine_all::<M>(&self.values[last_block * self.block_size..end]);
+        M::combine(&M::combine(&head, &middle), &tail)
+    }
+}
+
+impl<M: Monoid> FromIterator<M::Value> for SqrtDecomposition<M> {
+    fn from_iter<I: IntoIterator<Item = M::Value>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monoid::{Min, Sum};
+
+    #[test]
+    fn test_queries_across_blocks() {
+        let values: Vec<i64> = (0..20).map(|i| i * i % 7 - 3).collect();
+        let tree: SqrtDecomposition<Sum<i64>> = SqrtDecomposition::from_vec(values.clone());
+        assert_eq!(tree.block_size(), 4);
+        for start in 0..=20 {
+            for end in start..=20 {
+                assert_eq!(tree.query(start..end), values[start..end].iter().sum());
+            }
+        }
+    }
+
+    #[test]
+    fn test_set_updates_block() {
+        let mut mins: SqrtDecomposition<Min<u32>> =
+            SqrtDecomposition::with_block_size((10..20).collect(), 3);
+        assert_eq!(mins.query(..), 10);
+        mins.set(0, 50);
+        assert_eq!(mins.query(..3), 11);
+        mins.set(9, 1);
+        assert_eq!(*mins.get(9), 1);
+        assert_eq!(mins.query(4..), 1);
+        assert_eq!(mins.query(4..9), 14);
+        assert_eq!(mins.query(5..5), u32::MAX);
+    }
+
+    #[test]
+    fn test_empty_and_single() {
+        let empty: SqrtDecomposition<Sum<i32>> = SqrtDecomposition::new(0);
+        assert!(empty.is_empty());
+        assert_eq!(empty.query(..), 0);
+        let single: SqrtDecomposition<Sum<i32>> = [7].into_iter().collect();
+        assert_eq!(single.query(..), 7);
+        assert_eq!(single.query(1..), 0);
+    }
+}
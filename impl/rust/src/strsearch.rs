@@ -0,0 +1,196 @@
+//! Single-pattern string search: KMP, the Z-algorithm,
+//! Boyer-Moore-Horspool and Rabin-Karp.
+//!
+//! Every algorithm reports the start of every occurrence, overlapping ones
+//! included, as byte offsets into the text. An empty pattern occurs at every
+//! offset from 0 to the text's length.
+
+use crate::rolling_hash::{rabin_karp, RollingHash};
+
+/// A single-pattern search algorithm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Algorithm {
+    /// Knuth-Morris-Pratt: never re-reads a text byte, O(n + m) worst case.
+    Kmp,
+    /// Z-algorithm over the pattern followed by the text, O(n + m) worst
+    /// case.
+    Z,
+    /// Boyer-Moore-Horspool: skips ahead by a bad-character table, often
+    /// sublinear, O(n m) worst case.
+    Horspool,
+    /// Rabin-Karp: compares rolling-hash fingerprints of every window,
+    /// checking bytes only on a fingerprint match, O(n + m) expected.
+    RabinKarp,
+}
+
+impl Algorithm {
+    /// Every algorithm, for tests and benchmarks that run them all.
+    pub const ALL: [Algorithm; 4] = [
+        Algorithm::Kmp,
+        Algorithm::Z,
+        Algorithm::Horspool,
+        Algorithm::RabinKarp,
+    ];
+
+    /// Returns the starting offset of every occurrence of `pattern` in
+    /// `text`, in increasing order.
+    pub fn find_all(self, pattern: &[u8], text: &[u8]) -> Vec<usize> {
+        if pattern.is_empty() {
+            return (0..=text.len()).collect();
+        }
+        if pattern.len() > text.len() {
+            return Vec::new();
+        }
+        match self {
+            Algorithm::Kmp => kmp(pattern, text),
+            Algorithm::Z => z_search(pattern, text),
+            Algorithm::Horspool => horspool(pattern, text),
+            Algorithm::RabinKarp => rabin_karp(pattern, text, RollingHash::DEFAULT_PARAMS),
+        }
+    }
+}
+
+/// Returns the starting offset of every occurrence of `pattern` in `text`,
+/// in increasing order, using [`Algorithm::Horspool`].
+pub fn find_all(pattern: &[u8], text: &[u8]) -> Vec<usize> {
+    Algorithm::Horspool.find_all(pattern, text)
+}
+
+/// The prefix function of `s`: entry `i` is the length of the longest
+/// proper prefix of `s[..=i]` that is also its suffix.
+pub fn prefix_function(s: &[u8]) -> Vec<usize> {
+    let mut pi = vec![0; s.len()];
+    for i in 1..s.len() {
+        let mut k = pi[i - 1];
+        while k > 0 && s[i] != s[k] {
+            k = pi[k - 1];
+        }
+        if s[i] == s[k] {
+            k += 1;
+        }
+        pi[i] = k;
+    }
+    pi
+}
+
+/// The Z-array of `s`: entry `i` is the length of the longest common
+/// prefix of `s` and `s[i..]`, with entry 0 set to `s.len()`.
+pub fn z_array(s: &[u8]) -> Vec<usize> {
+    let n = s.len();
+    let mut z = vec![0; n];
+    if n == 0 {
+        return z;
+    }
+    z[0] = n;
+    // [left, right) is the rightmost window known to match a prefix.
+    let (mut left, mut right) = (0, 0);
+    for i in 1..n {
+        if i < right {
+            z[i] = z[i - left].min(right - i);
+        }
+        while i + z[i] < n && s[z[i]] == s[i + z[i]] {
+            z[i] += 1;
+        }
+        if i + z[i] > right {
+            left = i;
+            right = i + z[i];
+        }
+    }
+    z
+}
+
+fn kmp(pattern: &[u8], text: &[u8]) -> Vec<usize> {
+    let pi = prefix_function(pattern);
+    let mut matches = Vec::new();
+    // Length of the pattern prefix matched so far.
+    let mut k = 0;
+    for (i, &byte) in text.iter().enumerate() {
+        while k > 0 && byte != pattern[k] {
+            k = pi[k - 1];
+        }
+        if byte == pattern[k] {
+            k += 1;
+        }
+        if k == pattern.len() {
+            matches.push(i + 1 - k);
+            k = pi[k - 1];
+        }
+    }
+    matches
+}
+
+fn z_search(pattern: &[u8], text: &[u8]) -> Vec<usize> {
+    // No separator is needed: a match only asks whether the common prefix
+    // reaches the pattern's length, however much further it runs.
+    let joined = [pattern, text].concat();
+    let m = pattern.len();
+    z_array(&joined)[m..]
+        .iter()
+        .enumerate()
+        .filter(|&(_, &length)| length >= m)
+        .map(|(start, _)| start)
+        .collect()
+}
+
+fn horspool(pattern: &[u8], text: &[u8]) -> Vec<usize> {
+    let m = pattern.len();
+    // How far to slide when a window ends in a byte: its distance from the
+    // end of the pattern at its last occurrence before the final byte.
+    let mut shift = [m; 256];
+    for (i, &byte) in pattern[..m - 1].iter().enumerate() {
+        shift[byte as usize] = m - 1 - i;
+    }
+    let mut matches = Vec::new();
+    let mut start = 0;
+    while start + m <= text.len() {
+        let window = &text[start..start + m];
+        if window == pattern {
+            matches.push(start);
+        }
+        start += shift[window[m - 1] as usize];
+    }
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overlapping_matches() {
+        for algorithm in Algorithm::ALL {
+            assert_eq!(
+                algorithm.find_all(b"aa", b"aaaa"),
+                [0, 1, 2],
+                "{:?}",
+                algorithm
+            );
+            assert_eq!(algorithm.find_all(b"abab", b"xabababx"), [1, 3]);
+            assert_eq!(algorithm.find_all(b"abc", b"ab"), Vec::<usize>::new());
+            assert_eq!(algorithm.find_all(b"", b"ab"), [0, 1, 2]);
+        }
+    }
+
+    #[test]
+    fn test_prefix_function_and_z_array() {
+        assert_eq!(prefix_function(b"aabaaab"), [0, 1, 0, 1, 2, 2, 3]);
+        assert_eq!(z_array(b"aabxaab"), [7, 1, 0, 0, 3, 1, 0]);
+        assert!(z_array(b"").is_empty());
+    }
+
+    #[test]
+    fn test_agrees_with_naive_scan() {
+        let text = b"abracadabra abracadabra cadabra";
+        for pattern in [&b"a"[..], b"abra", b"cadabra", b"ra ", b"zzz"] {
+            let naive: Vec<usize> = text
+                .windows(pattern.len())
+                .enumerate()
+                .filter(|(_, window)| window == &pattern)
+                .map(|(start, _)| start)
+                .collect();
+            for algorithm in Algorithm::ALL {
+                assert_eq!(algorithm.find_all(pattern, text), naive);
+            }
+        }
+    }
+}
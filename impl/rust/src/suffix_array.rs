@@ -0,0 +1,60 @@
+//! Suffix arrays by prefix doubling.
+
+/// The suffix array of `text`: the start of every suffix, in lexicographic
+/// order of the suffixes.
+///
+/// Built by prefix doubling: suffixes are ranked by their first byte, then
+/// by their first 2, 4, 8, ... bytes, each round sorting on the pair of
+/// ranks of the two halves, until every rank is distinct. Each round is
+/// a comparison sort, so construction is O(n log² n). A suffix that is a
+/// prefix of another sorts first.
+pub fn suffix_array(text: &[u8]) -> Vec<usize> {
+    let n = text.len();
+    let mut sa: Vec<usize> = (0..n).collect();
+    if n == 0 {
+        return sa;
+    }
+    let mut rank: Vec<usize> = text.iter().map(|&byte| byte as usize).collect();
+    let mut next_rank = vec![0; n];
+    let mut k = 1;
+    loop {
+        // Rank of the first k bytes, then of the next k, with running off
+        // the end ranking lowest.
+        let key = |i: usize| (rank[i], if i + k < n { rank[i + k] + 1 } else { 0 });
+        sa.sort_unstable_by_key(|&i| key(i));
+        next_rank[sa[0]] = 0;
+        for j in 1..n {
+            next_rank[sa[j]] = next_rank[sa[j - 1]] + usize::from(key(sa[j - 1]) < key(sa[j]));
+        }
+        std::mem::swap(&mut rank, &mut next_rank);
+        if rank[sa[n - 1]] == n - 1 {
+            return sa;
+        }
+        k *= 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_banana() {
+        assert_eq!(suffix_array(b"banana"), [5, 3, 1, 0, 4, 2]);
+    }
+
+    #[test]
+    fn test_repeated_byte() {
+        assert_eq!(suffix_array(b"aaaa"), [3, 2, 1, 0]);
+        assert!(suffix_array(b"").is_empty());
+        assert_eq!(suffix_array(b"x"), [0]);
+    }
+
+    #[test]
+    fn test_matches_sorted_suffixes() {
+        let text = b"mississippi\0\xffmississippi";
+        let mut expected: Vec<usize> = (0..text.len()).collect();
+        expected.sort_by_key(|&i| &text[i..]);
+        assert_eq!(suffix_array(text), expected);
+    }
+}
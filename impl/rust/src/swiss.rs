@@ -0,0 +1,459 @@
+//! Hash Map implementation using SwissTable-style control bytes.
+//!
+//! Slot metadata lives in a separate array of control bytes, one per slot.
+//! A full slot stores the top 7 bits of its key's hash, so a probe compares a
+//! whole group of control bytes against the fragment at once (16 with SSE2,
+//! 8 with NEON or the portable fallback) and only touches the slot array for
+//! likely matches.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+const DEFAULT_CAPACITY: usize = 16;
+const MAX_LOAD_FACTOR: f64 = 0.875;
+
+/// Control byte of a slot that has never been used.
+const EMPTY: u8 = 0b1111_1111;
+/// Control byte of a slot whose entry was removed.
+const DELETED: u8 = 0b1000_0000;
+
+/// Set bits mark the matching bytes of a group.
+#[derive(Clone, Copy)]
+struct BitMask(u64);
+
+impl BitMask {
+    fn any(self) -> bool {
+        self.0 != 0
+    }
+
+    fn lowest(self) -> Option<usize> {
+        if self.0 == 0 {
+            None
+        } else {
+            Some(self.0.trailing_zeros() as usize / group::BITMASK_STRIDE)
+        }
+    }
+}
+
+impl Iterator for BitMask {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let bit = self.lowest()?;
+        self.0 &= self.0 - 1;
+        Some(bit)
+    }
+}
+
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+mod group {
+    use super::{BitMask, EMPTY};
+    use std::arch::x86_64::*;
+
+    pub const WIDTH: usize = 16;
+    pub const BITMASK_STRIDE: usize = 1;
+
+    pub struct Group(__m128i);
+
+    impl Group {
+        pub fn load(bytes: &[u8]) -> Self {
+            assert!(bytes.len() >= WIDTH);
+            // SAFETY: the assertion guarantees 16 readable bytes and SSE2 is
+            // enabled for this target.
+            unsafe { Group(_mm_loadu_si128(bytes.as_ptr() as *const __m128i)) }
+        }
+
+        pub fn match_byte(&self, byte: u8) -> BitMask {
+            // SAFETY: SSE2 is enabled for this target.
+            unsafe {
+                let cmp = _mm_cmpeq_epi8(self.0, _mm_set1_epi8(byte as i8));
+                BitMask(_mm_movemask_epi8(cmp) as u16 as u64)
+            }
+        }
+
+        pub fn match_empty(&self) -> BitMask {
+            self.match_byte(EMPTY)
+        }
+
+        pub fn match_empty_or_deleted(&self) -> BitMask {
+            // SAFETY: SSE2 is enabled for this target.
+            unsafe { BitMask(_mm_movemask_epi8(self.0) as u16 as u64) }
+        }
+    }
+}
+
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+mod group {
+    use super::BitMask;
+    use std::arch::aarch64::*;
+
+    pub const WIDTH: usize = 8;
+    pub const BITMASK_STRIDE: usize = 8;
+
+    const HIGH_BITS: u64 = 0x8080_8080_8080_8080;
+
+    pub struct Group(uint8x8_t);
+
+    impl Group {
+        pub fn load(bytes: &[u8]) -> Self {
+            assert!(bytes.len() >= WIDTH);
+            // SAFETY: the assertion guarantees 8 readable bytes and NEON is
+            // enabled for this target.
+            unsafe { Group(vld1_u8(bytes.as_ptr())) }
+        }
+
+        pub fn match_byte(&self, byte: u8) -> BitMask {
+            // SAFETY: NEON is enabled for this target.
+            unsafe {
+                let cmp = vceq_u8(self.0, vdup_n_u8(byte));
+                BitMask(vget_lane_u64::<0>(vreinterpret_u64_u8(cmp)) & HIGH_BITS)
+            }
+        }
+
+        pub fn match_empty(&self) -> BitMask {
+            self.match_byte(super::EMPTY)
+        }
+
+        pub fn match_empty_or_deleted(&self) -> BitMask {
+            // SAFETY: NEON is enabled for this target.
+            unsafe { BitMask(vget_lane_u64::<0>(vreinterpret_u64_u8(self.0)) & HIGH_BITS) }
+        }
+    }
+}
+
+#[cfg(not(any(
+    all(target_arch = "x86_64", target_feature = "sse2"),
+    all(target_arch = "aarch64", target_feature = "neon")
+)))]
+mod group {
+    use super::BitMask;
+
+    pub const WIDTH: usize = 8;
+    pub const BITMASK_STRIDE: usize = 8;
+
+    const LOW_BITS: u64 = 0x0101_0101_0101_0101;
+    const HIGH_BITS: u64 = 0x8080_8080_8080_8080;
+
+    /// Eight control bytes processed with SWAR arithmetic.
+    pub struct Group(u64);
+
+    impl Group {
+        pub fn load(bytes: &[u8]) -> Self {
+            let mut word = [0u8; WIDTH];
+            word.copy_from_slice(&bytes[..WIDTH]);
+            Group(u64::from_le_bytes(word))
+        }
+
+        /// May report false positives next to a true match; callers compare
+        /// keys anyway.
+        pub fn match_byte(&self, byte: u8) -> BitMask {
+            let cmp = self.0 ^ (LOW_BITS * byte as u64);
+            BitMask(cmp.wrapping_sub(LOW_BITS) & !cmp & HIGH_BITS)
+        }
+
+        pub fn match_empty(&self) -> BitMask {
+            // EMPTY is the only control byte with its top two bits set.
+            BitMask(self.0 & (self.0 << 1) & HIGH_BITS)
+        }
+
+        pub fn match_empty_or_deleted(&self) -> BitMask {
+            BitMask(self.0 & HIGH_BITS)
+        }
+    }
+}
+
+use group::{Group, WIDTH as GROUP_WIDTH};
+
+/// A hash map using open addressing over SwissTable-style control bytes.
+///
+/// The table probes group by group in a triangular sequence over a
+/// power-of-two capacity. The control array carries `GROUP_WIDTH` extra bytes
+/// mirroring the first group so that loads near the end never wrap.
+#[derive(Debug, Clone)]
+pub struct SwissMap<K, V> {
+    ctrl: Vec<u8>,
+    slots: Vec<Option<(K, V)>>,
+    size: usize,
+    deleted: usize,
+}
+
+impl<K, V> Default for SwissMap<K, V>
+where
+    K: Hash + Eq,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn h1(hash: u64) -> usize {
+    hash as usize
+}
+
+fn h2(hash: u64) -> u8 {
+    (hash >> 57) as u8
+}
+
+impl<K, V> SwissMap<K, V>
+where
+    K: Hash + Eq,
+{
+    /// Create a new empty SwissMap.
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// Create a new SwissMap with at least the specified capacity.
+    ///
+    /// The capacity is rounded up to a power of two.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let capacity = capacity
+            .max(DEFAULT_CAPACITY)
+            .max(GROUP_WIDTH)
+            .next_power_of_two();
+        let mut slots = Vec::with_capacity(capacity);
+        slots.resize_with(capacity, || None);
+        Self {
+            ctrl: vec![EMPTY; capacity + GROUP_WIDTH],
+            slots,
+            size: 0,
+            deleted: 0,
+        }
+    }
+
+    /// Returns the number of elements in the map.
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Returns true if the map contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Returns the current capacity of the map.
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    fn hash_key(&self, key: &K) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn set_ctrl(&mut self, index: usize, byte: u8) {
+        let mask = self.slots.len() - 1;
+        self.ctrl[index] = byte;
+        self.ctrl[(index.wrapping_sub(GROUP_WIDTH) & mask) + GROUP_WIDTH] = byte;
+    }
+
+    fn find(&self, hash: u64, key: &K) -> Option<usize> {
+        let mask = self.slots.len() - 1;
+        let fragment = h2(hash);
+        let mut pos = h1(hash) & mask;
+        let mut stride = 0;
+
+        loop {
+            let group = Group::load(&self.ctrl[pos..]);
+            for bit in group.match_byte(fragment) {
+                let index = (pos + bit) & mask;
+                if matches!(&self.slots[index], Some((k, _)) if k == key) {
+                    return Some(index);
+                }
+            }
+            if group.match_empty().any() {
+                return None;
+            }
+            stride += GROUP_WIDTH;
+            if stride > mask {
+                return None;
+            }
+            pos = (pos + stride) & mask;
+        }
+    }
+
+    fn find_insert_slot(&self, hash: u64) -> usize {
+        let mask = self.slots.len() - 1;
+        let mut pos = h1(hash) & mask;
+        let mut stride = 0;
+
+        loop {
+            let group = Group::load(&self.ctrl[pos..]);
+            if let Some(bit) = group.match_empty_or_deleted().lowest() {
+                return (pos + bit) & mask;
+            }
+            stride += GROUP_WIDTH;
+            pos = (pos + stride) & mask;
+        }
+    }
+
+    fn resize(&mut self) {
+        let capacity = self.slots.len();
+        // Rehash in place when deletions, not live entries, filled the table.
+        let new_capacity = if (self.size as f64) < capacity as f64 * MAX_LOAD_FACTOR / 2.0 {
+            capacity
+        } else {
+            capacity * 2
+        };
+        let old_slots = std::mem::replace(&mut self.slots, {
+            let mut v = Vec::with_capacity(new_capacity);
+            v.resize_with(new_capacity, || None);
+            v
+        });
+        self.ctrl = vec![EMPTY; new_capacity + GROUP_WIDTH];
+        self.deleted = 0;
+
+        for (key, value) in old_slots.into_iter().flatten() {
+            let hash = self.hash_key(&key);
+            let index = self.find_insert_slot(hash);
+            self.set_ctrl(index, h2(hash));
+            self.slots[index] = Some((key, value));
+        }
+    }
+
+    /// Insert a key-value pair into the map.
+    ///
+    /// Returns the previous value if the key existed.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let hash = self.hash_key(&key);
+        if let Some(index) = self.find(hash, &key) {
+            let (_, old) = self.slots[index].as_mut().unwrap();
+            return Some(std::mem::replace(old, value));
+        }
+
+        if (self.size + self.deleted + 1) as f64 > self.slots.len() as f64 * MAX_LOAD_FACTOR {
+            self.resize();
+        }
+
+        let index = self.find_insert_slot(hash);
+        if self.ctrl[index] == DELETED {
+            self.deleted -= 1;
+        }
+        self.set_ctrl(index, h2(hash));
+        self.slots[index] = Some((key, value));
+        self.size += 1;
+        None
+    }
+
+    /// Get a reference to the value associated with the key.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let index = self.find(self.hash_key(key), key)?;
+        self.slots[index].as_ref().map(|(_, v)| v)
+    }
+
+    /// Get a mutable reference to the value associated with the key.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let index = self.find(self.hash_key(key), key)?;
+        self.slots[index].as_mut().map(|(_, v)| v)
+    }
+
+    /// Remove a key-value pair from the map.
+    ///
+    /// Returns the removed value if the key existed.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let index = self.find(self.hash_key(key), key)?;
+        self.set_ctrl(index, DELETED);
+        self.size -= 1;
+        self.deleted += 1;
+        self.slots[index].take().map(|(_, v)| v)
+    }
+
+    /// Check if the map contains the given key.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.find(self.hash_key(key), key).is_some()
+    }
+
+    /// Clear all entries from the map.
+    pub fn clear(&mut self) {
+        self.ctrl.fill(EMPTY);
+        for slot in &mut self.slots {
+            *slot = None;
+        }
+        self.size = 0;
+        self.deleted = 0;
+    }
+
+    /// Iterate over all key-value pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.slots.iter().flatten().map(|(k, v)| (k, v))
+    }
+
+    /// Iterate over all keys.
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.iter().map(|(k, _)| k)
+    }
+
+    /// Iterate over all values.
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.iter().map(|(_, v)| v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctrl_bytes(bytes: &[u8]) -> Vec<u8> {
+        let mut padded = bytes.to_vec();
+        padded.resize(GROUP_WIDTH, EMPTY);
+        padded
+    }
+
+    #[test]
+    fn test_group_matching() {
+        let bytes = ctrl_bytes(&[0x12, EMPTY, DELETED, 0x12, 0x7F]);
+        let group = Group::load(&bytes);
+        assert_eq!(group.match_byte(0x12).collect::<Vec<_>>(), vec![0, 3]);
+        assert_eq!(group.match_byte(0x7F).collect::<Vec<_>>(), vec![4]);
+        assert_eq!(group.match_empty().lowest(), Some(1));
+        assert_eq!(group.match_empty_or_deleted().lowest(), Some(1));
+        assert_eq!(group.match_empty().count(), GROUP_WIDTH - 4);
+        assert!(!Group::load(&[0u8; 16]).match_empty_or_deleted().any());
+    }
+
+    #[test]
+    fn test_mirrored_control_bytes() {
+        let mut map: SwissMap<u32, u32> = SwissMap::new();
+        let capacity = map.capacity();
+        map.set_ctrl(0, 0x42);
+        map.set_ctrl(capacity - 1, 0x24);
+        assert_eq!(map.ctrl[capacity], 0x42);
+        assert_eq!(map.ctrl[capacity - 1], 0x24);
+    }
+
+    #[test]
+    fn test_insert_get_remove() {
+        let mut map = SwissMap::new();
+        for i in 0..2000 {
+            assert!(map.insert(format!("key{}", i), i).is_none());
+        }
+        assert_eq!(map.insert("key5".to_string(), 50), Some(5));
+        assert_eq!(map.len(), 2000);
+        for i in (0..2000).step_by(2) {
+            assert!(map.remove(&format!("key{}", i)).is_some());
+        }
+        assert_eq!(map.len(), 1000);
+        assert_eq!(map.iter().count(), 1000);
+        assert_eq!(map.get(&"key5".to_string()), Some(&50));
+        assert!(!map.contains_key(&"key4".to_string()));
+        *map.get_mut(&"key7".to_string()).unwrap() = 0;
+        assert_eq!(map.get(&"key7".to_string()), Some(&0));
+    }
+
+    #[test]
+    fn test_delete_churn_does_not_grow() {
+        let mut map = SwissMap::new();
+        let capacity = map.capacity();
+        for i in 0..10_000 {
+            map.insert(i, i);
+            if i >= 4 {
+                map.remove(&(i - 4));
+            }
+        }
+        assert_eq!(map.len(), 4);
+        assert_eq!(map.capacity(), capacity);
+        map.clear();
+        assert!(map.is_empty());
+    }
+}
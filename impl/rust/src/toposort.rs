@@ -0,0 +1,142 @@
+//! Topological sort of a directed [`Graph`]: Kahn's algorithm.
+
+use crate::graph::{Graph, NodeId};
+
+/// A directed cycle, which leaves a graph with no topological order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    /// The nodes around the cycle, each joined to the next by an edge and
+    /// the last to the first. A self-loop is a cycle of one node.
+    pub nodes: Vec<NodeId>,
+}
+
+/// Returns the nodes of a directed graph in topological order, so that
+/// every edge goes from an earlier node to a later one, or a cycle if
+/// there is no such order.
+///
+/// Kahn's algorithm: count each node's incoming edges, start from the
+/// nodes with none, and release a node once every edge into it has been
+/// taken. O(V + E). If some nodes are never released, each of them still
+/// has an edge in from another, so walking those edges backwards from any
+/// of them must repeat a node, and the walk between the repeats is the
+/// cycle returned.
+///
+/// # Panics
+///
+/// Panics if the graph is undirected.
+pub fn toposort<N, E>(graph: &Graph<N, E>) -> Result<Vec<NodeId>, Cycle> {
+    assert!(graph.is_directed(), "toposort needs a directed graph");
+    let mut pending = vec![0usize; graph.node_bound()];
+    for edge in graph.edges() {
+        pending[edge.target.index()] += 1;
+    }
+    let mut order: Vec<NodeId> = graph
+        .nodes()
+        .map(|(id, _)| id)
+        .filter(|id| pending[id.index()] == 0)
+        .collect();
+    let mut next = 0;
+    while let Some(&node) = order.get(next) {
+        next += 1;
+        for edge in graph.outgoing(node) {
+            let target = edge.target.index();
+            pending[target] -= 1;
+            if pending[target] == 0 {
+                order.push(edge.target);
+            }
+        }
+    }
+    if order.len() == graph.node_count() {
+        return Ok(order);
+    }
+
+    let start = graph
+        .nodes()
+        .map(|(id, _)| id)
+        .find(|id| pending[id.index()] > 0)
+        .expect("some node was never released");
+    // Steps along the walk, so the first repeated node closes the cycle.
+    let mut step = vec![usize::MAX; graph.node_bound()];
+    let mut walk = Vec::new();
+    let mut node = start;
+    while step[node.index()] == usize::MAX {
+        step[node.index()] = walk.len();
+        walk.push(node);
+        node = graph
+            .incoming(node)
+            .map(|edge| edge.source)
+            .find(|source| pending[source.index()] > 0)
+            .expect("unreleased nodes have an unreleased predecessor");
+    }
+    let mut nodes = walk.split_off(step[node.index()]);
+    // The walk followed edges backwards.
+    nodes.reverse();
+    Err(Cycle { nodes })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A directed graph on `n` nodes with the given edges.
+    fn graph(n: usize, edges: &[(usize, usize)]) -> (Graph<(), ()>, Vec<NodeId>) {
+        let mut graph = Graph::directed();
+        let nodes: Vec<_> = (0..n).map(|_| graph.add_node(())).collect();
+        for &(source, target) in edges {
+            graph.add_edge(nodes[source], nodes[target], ());
+        }
+        (graph, nodes)
+    }
+
+    /// Checks that `cycle` is a closed walk along edges of `graph`.
+    fn assert_cycle(graph: &Graph<(), ()>, cycle: &Cycle) {
+        assert!(!cycle.nodes.is_empty());
+        for (i, &node) in cycle.nodes.iter().enumerate() {
+            let next = cycle.nodes[(i + 1) % cycle.nodes.len()];
+            assert!(graph.find_edge(node, next).is_some());
+        }
+    }
+
+    #[test]
+    fn test_toposort_orders_every_edge() {
+        let (graph, n) = graph(6, &[(5, 2), (5, 0), (4, 0), (4, 1), (2, 3), (3, 1), (5, 2)]);
+        let order = toposort(&graph).unwrap();
+        assert_eq!(order.len(), 6);
+        assert_eq!(order[..2], [n[4], n[5]]);
+        let mut rank = vec![0; graph.node_bound()];
+        for (i, node) in order.iter().enumerate() {
+            rank[node.index()] = i;
+        }
+        for edge in graph.edges() {
+            assert!(rank[edge.source.index()] < rank[edge.target.index()]);
+        }
+        assert_eq!(toposort(&Graph::<(), ()>::directed()), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_toposort_finds_cycle() {
+        // 0 -> 1 -> {2 -> 3 -> 4 -> 2}, with 5 hanging off the cycle.
+        let (cyclic, n) = graph(6, &[(0, 1), (1, 2), (2, 3), (3, 4), (4, 2), (4, 5)]);
+        let cycle = toposort(&cyclic).unwrap_err();
+        assert_cycle(&cyclic, &cycle);
+        let mut nodes = cycle.nodes.clone();
+        nodes.sort();
+        assert_eq!(nodes, [n[2], n[3], n[4]]);
+
+        let (looped, n) = graph(2, &[(0, 1), (1, 1)]);
+        assert_eq!(toposort(&looped), Err(Cycle { nodes: vec![n[1]] }));
+    }
+
+    #[test]
+    fn test_toposort_skips_removed_nodes() {
+        let (mut graph, n) = graph(3, &[(0, 1), (1, 2), (2, 0)]);
+        graph.remove_node(n[1]);
+        assert_eq!(toposort(&graph), Ok(vec![n[2], n[0]]));
+    }
+
+    #[test]
+    #[should_panic(expected = "directed")]
+    fn test_toposort_rejects_undirected() {
+        let _ = toposort(&Graph::<(), ()>::undirected());
+    }
+}
@@ -0,0 +1,385 @@
+//! Treap (randomized binary search tree) ordered map with split and merge.
+
+use crate::btree::{before_start, check_range, past_end};
+use crate::skip_list::xorshift64;
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::RangeBounds;
+
+const DEFAULT_SEED: u64 = 0x7EA9;
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+#[derive(Debug, Clone)]
+struct Node<K, V> {
+    key: K,
+    value: V,
+    /// Random heap priority; every node outranks its children.
+    priority: u64,
+    /// Number of entries in the subtree rooted here.
+    size: usize,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+fn size<K, V>(link: &Link<K, V>) -> usize {
+    link.as_ref().map_or(0, |node| node.size)
+}
+
+impl<K, V> Node<K, V> {
+    fn update_size(&mut self) {
+        self.size = 1 + size(&self.left) + size(&self.right);
+    }
+}
+
+/// Split a subtree into the keys for which `goes_left` holds and the rest.
+///
+/// `goes_left` must hold for a prefix of the keys in order.
+fn split<K, V, F>(link: Link<K, V>, goes_left: &F) -> (Link<K, V>, Link<K, V>)
+where
+    F: Fn(&K) -> bool,
+{
+    let Some(mut node) = link else {
+        return (None, None);
+    };
+    if goes_left(&node.key) {
+        let (middle, right) = split(node.right.take(), goes_left);
+        node.right = middle;
+        node.update_size();
+        (Some(node), right)
+    } else {
+        let (left, middle) = split(node.left.take(), goes_left);
+        node.left = middle;
+        node.update_size();
+        (left, Some(node))
+    }
+}
+
+/// Join two subtrees where every key in `left` is below every key in
+/// `right`, keeping the higher priority on top.
+fn merge<K, V>(left: Link<K, V>, right: Link<K, V>) -> Link<K, V> {
+    match (left, right) {
+        (None, right) => right,
+        (left, None) => left,
+        (Some(mut left), Some(mut right)) => {
+            if left.priority > right.priority {
+                left.right = merge(left.right.take(), Some(right));
+                left.update_size();
+                Some(left)
+            } else {
+                right.left = merge(Some(left), right.left.take());
+                right.update_size();
+                Some(right)
+            }
+        }
+    }
+}
+
+/// An ordered map stored in a treap.
+///
+/// A treap is a binary search tree on keys that is also a max-heap on
+/// random priorities, which makes its shape that of a BST built by random
+/// insertion order: expected O(log n) depth whatever order the keys arrive
+/// in. All updates reduce to two primitives, splitting a tree at a key and
+/// merging two trees whose key ranges do not overlap, and both are public
+/// as [`split_off`](TreapMap::split_off) and [`merge`](TreapMap::merge).
+/// Priorities come from a seeded generator, so a given sequence of
+/// operations always builds the same tree.
+#[derive(Clone)]
+pub struct TreapMap<K, V> {
+    root: Link<K, V>,
+    rng: u64,
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for TreapMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V> Default for TreapMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> TreapMap<K, V> {
+    /// Create a new empty TreapMap with a fixed seed.
+    pub fn new() -> Self {
+        Self::with_seed(DEFAULT_SEED)
+    }
+
+    /// Create a new empty TreapMap drawing node priorities from `seed`.
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            root: None,
+            // xorshift gets stuck at zero.
+            rng: seed | 1,
+        }
+    }
+
+    /// Returns the number of elements in the map.
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    /// Returns true if the map contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Clear all entries from the map.
+    pub fn clear(&mut self) {
+        self.root = None;
+    }
+}
+
+impl<K: Ord, V> TreapMap<K, V> {
+    /// Insert a key-value pair into the map.
+    ///
+    /// Returns the previous value if the key existed.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(old) = self.get_mut(&key) {
+            return Some(std::mem::replace(old, value));
+        }
+        let node = Box::new(Node {
+            key,
+            value,
+            priority: xorshift64(&mut self.rng),
+            size: 1,
+            left: None,
+            right: None,
+        });
+        let (left, right) = split(self.root.take(), &|k| *k < node.key);
+        self.root = merge(merge(left, Some(node)), right);
+        None
+    }
+
+    /// Get a reference to the value associated with the key.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let mut link = &self.root;
+        while let Some(node) = link {
+            link = match key.cmp(&node.key) {
+                Ordering::Less => &node.left,
+                Ordering::Greater => &node.right,
+                Ordering::Equal => return Some(&node.value),
+            };
+        }
+        None
+    }
+
+    /// Get a mutable reference to the value associated with the key.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let mut link = &mut self.root;
+        while let Some(node) = link {
+            link = match key.cmp(&node.key) {
+                Ordering::Less => &mut node.left,
+                Ordering::Greater => &mut node.right,
+                Ordering::Equal => return Some(&mut node.value),
+            };
+        }
+        None
+    }
+
+    /// Check if the map contains the given key.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Remove a key-value pair from the map.
+    ///
+    /// Returns the removed value if the key existed.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let (left, rest) = split(self.root.take(), &|k| k < key);
+        let (found, right) = split(rest, &|k| k == key);
+        self.root = merge(left, right);
+        found.map(|node| node.value)
+    }
+
+    /// Split the map in two at `key`, returning everything from `key` up.
+    ///
+    /// Takes expected O(log n) time. The returned map draws its priorities
+    /// from a generator seeded by this one.
+    pub fn split_off(&mut self, key: &K) -> Self {
+        let (left, right) = split(self.root.take(), &|k| k < key);
+        self.root = left;
+        Self {
+            root: right,
+            rng: xorshift64(&mut self.rng),
+        }
+    }
+
+    /// Append `other`, every key of which must be greater than every key in
+    /// this map.
+    ///
+    /// Takes expected O(log n) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key ranges of the two maps overlap.
+    pub fn merge(&mut self, other: Self) {
+        if let (Some((last, _)), Some((first, _))) = (self.last_key_value(), other.iter().next()) {
+            assert!(last < first, "merged keys must all be greater");
+        }
+        self.root = merge(self.root.take(), other.root);
+    }
+
+    /// Returns the entry with the greatest key.
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_ref()?;
+        while let Some(right) = &node.right {
+            node = right;
+        }
+        Some((&node.key, &node.value))
+    }
+
+    /// Iterate over all key-value pairs in key order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.range(..)
+    }
+
+    /// Iterate over the key-value pairs whose keys fall in `range`, in key
+    /// order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range starts after it ends, or starts and ends at the
+    /// same excluded key.
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Iter<'_, K, V> {
+        check_range(&range);
+        let mut iter = Iter {
+            stack: Vec::new(),
+            end: None,
+        };
+        let mut link = &self.root;
+        while let Some(node) = link {
+            if before_start(&node.key, range.start_bound()) {
+                link = &node.right;
+            } else {
+                iter.stack.push(node);
+                link = &node.left;
+            }
+        }
+        let mut link = &self.root;
+        while let Some(node) = link {
+            if past_end(&node.key, range.end_bound()) {
+                iter.end = Some(node);
+                link = &node.left;
+            } else {
+                link = &node.right;
+            }
+        }
+        iter
+    }
+
+    /// Check the treap invariants, panicking on the first violation.
+    ///
+    /// Checks that keys are strictly increasing in order, that no child
+    /// outranks its parent, and that every stored subtree size is correct.
+    pub fn debug_validate(&self) {
+        fn walk<K: Ord, V>(link: &Link<K, V>, lower: Option<&K>, upper: Option<&K>) -> usize {
+            let Some(node) = link else {
+                return 0;
+            };
+            assert!(
+                lower.map_or(true, |lower| node.key > *lower),
+                "keys out of order"
+            );
+            assert!(
+                upper.map_or(true, |upper| node.key < *upper),
+                "keys out of order"
+            );
+            for child in [&node.left, &node.right].into_iter().flatten() {
+                assert!(child.priority <= node.priority, "child outranks parent");
+            }
+            let count = 1
+                + walk(&node.left, lower, Some(&node.key))
+                + walk(&node.right, Some(&node.key), upper);
+            assert_eq!(node.size, count, "stale subtree size");
+            count
+        }
+
+        walk(&self.root, None, None);
+    }
+}
+
+/// An iterator over a key range of a [`TreapMap`], in key order.
+pub struct Iter<'a, K, V> {
+    /// Nodes still to visit whose left subtrees are already handled; the
+    /// next entry is on top.
+    stack: Vec<&'a Node<K, V>>,
+    /// The first node past the range, if there is one.
+    end: Option<&'a Node<K, V>>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        if self.end.is_some_and(|end| std::ptr::eq(end, node)) {
+            self.stack.clear();
+            return None;
+        }
+        let mut link = &node.right;
+        while let Some(child) = link {
+            self.stack.push(child);
+            link = &child.left;
+        }
+        Some((&node.key, &node.value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_off_and_merge_round_trip() {
+        let mut map = TreapMap::new();
+        for i in 0..1000 {
+            map.insert(i, i * 10);
+        }
+        let mut upper = map.split_off(&600);
+        map.debug_validate();
+        upper.debug_validate();
+        assert_eq!((map.len(), upper.len()), (600, 400));
+        assert!(upper.iter().map(|(k, _)| *k).eq(600..1000));
+
+        // The halves are full treaps in their own right.
+        upper.insert(5000, 0);
+        assert_eq!(upper.remove(&600), Some(6000));
+        map.merge(upper);
+        map.debug_validate();
+        assert_eq!(map.len(), 1000);
+        assert_eq!(map.last_key_value(), Some((&5000, &0)));
+        assert_eq!(map.get(&600), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "merged keys must all be greater")]
+    fn test_merge_overlapping_panics() {
+        let mut map = TreapMap::new();
+        map.insert(5, ());
+        let mut other = TreapMap::new();
+        other.insert(3, ());
+        map.merge(other);
+    }
+
+    #[test]
+    fn test_same_seed_builds_same_tree() {
+        let build = |seed| {
+            let mut map = TreapMap::with_seed(seed);
+            for i in 0..1000 {
+                map.insert(i, ());
+            }
+            for i in (0..1000).step_by(7) {
+                map.remove(&i);
+            }
+            map.debug_validate();
+            map.root.as_ref().map(|node| (node.key, node.priority))
+        };
+        assert_eq!(build(1), build(1));
+        assert_ne!(build(1), build(2));
+    }
+}
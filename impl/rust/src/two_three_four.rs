@@ -0,0 +1,595 @@
+//! 2-3-4 tree ordered map with top-down splitting and merging.
+
+use crate::btree::{before_start, check_range, past_end};
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::RangeBounds;
+
+/// Keys in a full node, a 4-node.
+const MAX_KEYS: usize = 3;
+
+#[derive(Debug, Clone)]
+struct Node<K, V> {
+    /// One to three keys; only an empty tree's root has none.
+    keys: Vec<K>,
+    values: Vec<V>,
+    /// Empty for a leaf, otherwise one more than `keys`.
+    children: Vec<Node<K, V>>,
+}
+
+impl<K, V> Node<K, V> {
+    fn leaf() -> Self {
+        Self {
+            keys: Vec::with_capacity(MAX_KEYS),
+            values: Vec::with_capacity(MAX_KEYS),
+            children: Vec::new(),
+        }
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    fn is_full(&self) -> bool {
+        self.keys.len() == MAX_KEYS
+    }
+
+    /// Split the full child at `index` into two 2-nodes, lifting its middle
+    /// key into this node, which must not be full.
+    fn split_child(&mut self, index: usize) {
+        let child = &mut self.children[index];
+        let mut right = Node::leaf();
+        right.keys.push(child.keys.pop().expect("full child"));
+        right.values.push(child.values.pop().expect("full child"));
+        if !child.is_leaf() {
+            right.children = child.children.split_off(2);
+        }
+        let key = child.keys.pop().expect("full child");
+        let value = child.values.pop().expect("full child");
+        self.keys.insert(index, key);
+        self.values.insert(index, value);
+        self.children.insert(index + 1, right);
+    }
+
+    /// Fuse the children at `index` and `index + 1`, both 2-nodes, around
+    /// the key between them into one 4-node.
+    fn merge_children(&mut self, index: usize) {
+        let right = self.children.remove(index + 1);
+        let key = self.keys.remove(index);
+        let value = self.values.remove(index);
+        let left = &mut self.children[index];
+        left.keys.push(key);
+        left.values.push(value);
+        left.keys.extend(right.keys);
+        left.values.extend(right.values);
+        left.children.extend(right.children);
+    }
+
+    /// Make sure the child at `index` has at least two keys before
+    /// descending into it, by borrowing through this node from a sibling
+    /// that can spare one or fusing with a sibling that cannot. Returns the
+    /// index of the child now covering the same keys.
+    fn fill_child(&mut self, index: usize) -> usize {
+        if self.children[index].keys.len() > 1 {
+            return index;
+        }
+        if index > 0 && self.children[index - 1].keys.len() > 1 {
+            // Rotate right: the separator comes down, the sibling's last key
+            // goes up.
+            let left = &mut self.children[index - 1];
+            let key = left.keys.pop().expect("sibling has spare keys");
+            let value = left.values.pop().expect("sibling has spare keys");
+            let child = left.children.pop();
+            let key = std::mem::replace(&mut self.keys[index - 1], key);
+            let value = std::mem::replace(&mut self.values[index - 1], value);
+            let node = &mut self.children[index];
+            node.keys.insert(0, key);
+            node.values.insert(0, value);
+            if let Some(child) = child {
+                node.children.insert(0, child);
+            }
+            index
+        } else if index + 1 < self.children.len() && self.children[index + 1].keys.len() > 1 {
+            // Rotate left, the mirror image.
+            let right = &mut self.children[index + 1];
+            let key = right.keys.remove(0);
+            let value = right.values.remove(0);
+            let child = (!right.is_leaf()).then(|| right.children.remove(0));
+            let key = std::mem::replace(&mut self.keys[index], key);
+            let value = std::mem::replace(&mut self.values[index], value);
+            let node = &mut self.children[index];
+            node.keys.push(key);
+            node.values.push(value);
+            node.children.extend(child);
+            index
+        } else if index + 1 < self.children.len() {
+            self.merge_children(index);
+            index
+        } else {
+            self.merge_children(index - 1);
+            index - 1
+        }
+    }
+
+    /// Remove the last entry of this subtree, which has at least two keys
+    /// unless it is the root.
+    fn remove_last(&mut self) -> (K, V) {
+        if self.is_leaf() {
+            let key = self.keys.pop().expect("non-empty leaf");
+            let value = self.values.pop().expect("non-empty leaf");
+            return (key, value);
+        }
+        let index = self.fill_child(self.children.len() - 1);
+        self.children[index].remove_last()
+    }
+
+    /// Remove the first entry of this subtree, which has at least two keys
+    /// unless it is the root.
+    fn remove_first(&mut self) -> (K, V) {
+        if self.is_leaf() {
+            return (self.keys.remove(0), self.values.remove(0));
+        }
+        let index = self.fill_child(0);
+        self.children[index].remove_first()
+    }
+}
+
+impl<K: Ord, V> Node<K, V> {
+    /// Insert into this subtree, which is not full, splitting every full
+    /// node on the way down so that a split never has to propagate back up.
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let mut index = match self.keys.binary_search(&key) {
+            Ok(index) => return Some(std::mem::replace(&mut self.values[index], value)),
+            Err(index) => index,
+        };
+        if self.is_leaf() {
+            self.keys.insert(index, key);
+            self.values.insert(index, value);
+            return None;
+        }
+        if self.children[index].is_full() {
+            self.split_child(index);
+            match key.cmp(&self.keys[index]) {
+                Ordering::Less => {}
+                Ordering::Greater => index += 1,
+                Ordering::Equal => return Some(std::mem::replace(&mut self.values[index], value)),
+            }
+        }
+        self.children[index].insert(key, value)
+    }
+
+    /// Remove `key` from this subtree, which has at least two keys unless
+    /// it is the root, fixing up every 2-node on the way down so that a
+    /// removal never leaves a node empty.
+    fn remove(&mut self, key: &K) -> Option<V> {
+        match self.keys.binary_search(key) {
+            Ok(index) if self.is_leaf() => {
+                self.keys.remove(index);
+                Some(self.values.remove(index))
+            }
+            Ok(index) => {
+                // Swap in a neighbor from a child that can spare a key, or
+                // fuse the two children around the key and remove it from
+                // the result.
+                if self.children[index].keys.len() > 1 {
+                    let (key, value) = self.children[index].remove_last();
+                    self.keys[index] = key;
+                    Some(std::mem::replace(&mut self.values[index], value))
+                } else if self.children[index + 1].keys.len() > 1 {
+                    let (key, value) = self.children[index + 1].remove_first();
+                    self.keys[index] = key;
+                    Some(std::mem::replace(&mut self.values[index], value))
+                } else {
+                    self.merge_children(index);
+                    self.children[index].remove(key)
+                }
+            }
+            Err(_) if self.is_leaf() => None,
+            Err(index) => {
+                let index = self.fill_child(index);
+                self.children[index].remove(key)
+            }
+        }
+    }
+}
+
+/// A node of a red-black tree, as a 2-3-4 tree maps onto one.
+struct RedBlack<'a, K> {
+    key: &'a K,
+    red: bool,
+    left: Option<Box<RedBlack<'a, K>>>,
+    right: Option<Box<RedBlack<'a, K>>>,
+}
+
+/// The red-black tree equivalent to a 2-3-4 subtree: each node becomes a
+/// black node, with its other keys as red children leaning towards it.
+fn to_red_black<K, V>(node: &Node<K, V>) -> Option<Box<RedBlack<'_, K>>> {
+    if node.keys.is_empty() {
+        return None;
+    }
+    let child = |index: usize| node.children.get(index).and_then(to_red_black);
+    let make = |key, red, left, right| {
+        Some(Box::new(RedBlack {
+            key,
+            red,
+            left,
+            right,
+        }))
+    };
+    match node.keys.len() {
+        1 => make(&node.keys[0], false, child(0), child(1)),
+        2 => make(
+            &node.keys[1],
+            false,
+            make(&node.keys[0], true, child(0), child(1)),
+            child(2),
+        ),
+        _ => make(
+            &node.keys[1],
+            false,
+            make(&node.keys[0], true, child(0), child(1)),
+            make(&node.keys[2], true, child(2), child(3)),
+        ),
+    }
+}
+
+/// An ordered map stored in a 2-3-4 tree.
+///
+/// Every node holds one, two or three keys and, unless it is a leaf, one
+/// more child than keys, and all leaves are at the same depth. It is a
+/// B-tree of order 4, but with the textbook single-pass algorithms: an
+/// insert splits every full node it meets on the way down, so the leaf it
+/// reaches always has room, and a remove tops up every 2-node it is about
+/// to enter by borrowing from a sibling or fusing with one, so the leaf
+/// it reaches can always give up a key. Neither ever walks back up.
+///
+/// Its real interest is as the model behind red-black trees: a 2-node is a
+/// black node, a 3-node a black node with one red child and a 4-node a
+/// black node with two, and each split or fusion here is a recoloring or
+/// rotation there. [`debug_validate`](Self::debug_validate) builds that
+/// red-black tree and checks its coloring, so the correspondence is tested
+/// rather than asserted.
+#[derive(Clone)]
+pub struct TwoThreeFourMap<K, V> {
+    root: Node<K, V>,
+    len: usize,
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for TwoThreeFourMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V> Default for TwoThreeFourMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> TwoThreeFourMap<K, V> {
+    /// Create a new empty TwoThreeFourMap.
+    pub fn new() -> Self {
+        Self {
+            root: Node::leaf(),
+            len: 0,
+        }
+    }
+
+    /// Returns the number of elements in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the map contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of levels in the tree.
+    pub fn height(&self) -> usize {
+        if self.len == 0 {
+            return 0;
+        }
+        let mut height = 1;
+        let mut node = &self.root;
+        while let Some(child) = node.children.first() {
+            node = child;
+            height += 1;
+        }
+        height
+    }
+
+    /// Clear all entries from the map.
+    pub fn clear(&mut self) {
+        self.root = Node::leaf();
+        self.len = 0;
+    }
+}
+
+impl<K: Ord, V> TwoThreeFourMap<K, V> {
+    /// Insert a key-value pair into the map.
+    ///
+    /// Returns the previous value if the key existed.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if self.root.is_full() {
+            // The only split that adds a level.
+            let old_root = std::mem::replace(&mut self.root, Node::leaf());
+            self.root.children.push(old_root);
+            self.root.split_child(0);
+        }
+        let old = self.root.insert(key, value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    /// Get a reference to the value associated with the key.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let mut node = &self.root;
+        loop {
+            match node.keys.binary_search(key) {
+                Ok(index) => return Some(&node.values[index]),
+                Err(index) => node = node.children.get(index)?,
+            }
+        }
+    }
+
+    /// Check if the map contains the given key.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Remove a key-value pair from the map.
+    ///
+    /// Returns the removed value if the key existed.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let removed = self.root.remove(key);
+        if self.root.keys.is_empty() && !self.root.is_leaf() {
+            // The root's last key went down into a fusion: drop a level.
+            self.root = self.root.children.pop().expect("internal root has a child");
+        }
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    /// Iterate over all key-value pairs in key order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.range(..)
+    }
+
+    /// Iterate over the key-value pairs whose keys fall in `range`, in key
+    /// order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range starts after it ends, or starts and ends at the
+    /// same excluded key.
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Iter<'_, K, V> {
+        check_range(&range);
+        let mut iter = Iter {
+            stack: Vec::new(),
+            end: None,
+        };
+        let mut node = &self.root;
+        loop {
+            let index = node
+                .keys
+                .partition_point(|key| before_start(key, range.start_bound()));
+            iter.stack.push((node, index));
+            match node.children.get(index) {
+                Some(child) => node = child,
+                None => break,
+            }
+        }
+        let mut node = &self.root;
+        loop {
+            let index = node
+                .keys
+                .partition_point(|key| !past_end(key, range.end_bound()));
+            if index < node.keys.len() {
+                iter.end = Some((node, index));
+            }
+            match node.children.get(index) {
+                Some(child) => node = child,
+                None => break,
+            }
+        }
+        iter
+    }
+
+    /// Check the 2-3-4 invariants, panicking on the first violation.
+    ///
+    /// Checks that keys are strictly increasing in order, that every node
+    /// but an empty root has one to three keys and, if internal, one more
+    /// child, that all leaves are at one depth and that `len` is right. Then
+    /// converts the tree to a red-black tree and checks that the result is
+    /// validly colored: a black root, no red node with a red child and the
+    /// same number of black nodes on every root-to-leaf path.
+    pub fn debug_validate(&self) {
+        /// Returns the depth of the subtree's leaves and its entry count.
+        fn walk<K: Ord, V>(
+            node: &Node<K, V>,
+            lower: Option<&K>,
+            upper: Option<&K>,
+        ) -> (usize, usize) {
+            assert!(node.keys.len() <= MAX_KEYS, "node has too many keys");
+            assert_eq!(node.keys.len(), node.values.len(), "keys and values differ");
+            assert!(
+                node.keys.windows(2).all(|w| w[0] < w[1]),
+                "keys out of order"
+            );
+            if let (Some(lower), Some(first)) = (lower, node.keys.first()) {
+                assert!(first > lower, "keys out of order");
+            }
+            if let (Some(upper), Some(last)) = (upper, node.keys.last()) {
+                assert!(last < upper, "keys out of order");
+            }
+            if node.is_leaf() {
+                return (1, node.keys.len());
+            }
+            assert_eq!(
+                node.children.len(),
+                node.keys.len() + 1,
+                "internal node has the wrong number of children"
+            );
+            let mut depth = None;
+            let mut count = node.keys.len();
+            for (index, child) in node.children.iter().enumerate() {
+                assert!(!child.keys.is_empty(), "empty non-root node");
+                let lower = if index == 0 {
+                    lower
+                } else {
+                    node.keys.get(index - 1)
+                };
+                let upper = node.keys.get(index).or(upper);
+                let (child_depth, child_count) = walk(child, lower, upper);
+                assert!(
+                    depth.map_or(true, |depth| depth == child_depth),
+                    "leaves at different depths"
+                );
+                depth = Some(child_depth);
+                count += child_count;
+            }
+            (1 + depth.expect("internal node has children"), count)
+        }
+
+        /// Returns the black height of the subtree.
+        fn check_colors<K: Ord>(
+            node: &Option<Box<RedBlack<'_, K>>>,
+            parent_red: bool,
+            lower: Option<&K>,
+            upper: Option<&K>,
+        ) -> usize {
+            let Some(node) = node else {
+                return 1;
+            };
+            assert!(!(parent_red && node.red), "red node with a red child");
+            assert!(
+                lower.map_or(true, |lower| node.key > lower),
+                "keys out of order"
+            );
+            assert!(
+                upper.map_or(true, |upper| node.key < upper),
+                "keys out of order"
+            );
+            let left = check_colors(&node.left, node.red, lower, Some(node.key));
+            let right = check_colors(&node.right, node.red, Some(node.key), upper);
+            assert_eq!(left, right, "black heights differ");
+            left + usize::from(!node.red)
+        }
+
+        let (_, count) = walk(&self.root, None, None);
+        assert_eq!(count, self.len, "len does not match entry count");
+        let red_black = to_red_black(&self.root);
+        assert!(
+            red_black.as_ref().map_or(true, |root| !root.red),
+            "red root"
+        );
+        let black_height = check_colors(&red_black, false, None, None);
+        // Each 2-3-4 level is one black level.
+        assert_eq!(
+            black_height,
+            self.height() + 1,
+            "black height is not the height"
+        );
+    }
+}
+
+/// An iterator over a key range of a [`TwoThreeFourMap`], in key order.
+pub struct Iter<'a, K, V> {
+    /// Nodes on the path to the next entry, each with the index of its next
+    /// key; the next entry is at the top.
+    stack: Vec<(&'a Node<K, V>, usize)>,
+    /// The position of the first entry past the range, if there is one.
+    end: Option<(&'a Node<K, V>, usize)>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (node, index) = self.stack.last_mut()?;
+            let node: &'a Node<K, V> = node;
+            if *index == node.keys.len() {
+                self.stack.pop();
+                continue;
+            }
+            if self
+                .end
+                .is_some_and(|(end, end_index)| std::ptr::eq(end, node) && end_index == *index)
+            {
+                self.stack.clear();
+                return None;
+            }
+            let entry = (&node.keys[*index], &node.values[*index]);
+            *index += 1;
+            // Everything in the child after this key comes next.
+            let mut child = node.children.get(*index);
+            while let Some(node) = child {
+                self.stack.push((node, 0));
+                child = node.children.first();
+            }
+            return Some(entry);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_root_split_grows_height() {
+        let mut map = TwoThreeFourMap::new();
+        for key in [10, 20, 30] {
+            map.insert(key, ());
+        }
+        assert_eq!(map.height(), 1);
+        // The full root splits before the fourth key goes in.
+        map.insert(40, ());
+        map.debug_validate();
+        assert_eq!(map.height(), 2);
+        assert_eq!(map.root.keys, [20]);
+        assert_eq!(map.root.children[1].keys, [30, 40]);
+    }
+
+    #[test]
+    fn test_sorted_inserts_map_onto_red_black() {
+        let mut map = TwoThreeFourMap::new();
+        for i in 0..1000 {
+            assert_eq!(map.insert(i, i), None);
+            map.debug_validate();
+        }
+        assert_eq!(map.insert(500, 0), Some(500));
+        assert!(map.height() <= 10);
+        assert!(map.iter().map(|(k, _)| *k).eq(0..1000));
+        assert!(map.range(995..).map(|(k, _)| *k).eq(995..1000));
+    }
+
+    #[test]
+    fn test_remove_borrows_and_fuses() {
+        let mut map = TwoThreeFourMap::new();
+        for i in 0..500 {
+            map.insert(i, i * 10);
+        }
+        for i in (0..500).filter(|i| i % 3 != 0) {
+            assert_eq!(map.remove(&i), Some(i * 10));
+            map.debug_validate();
+        }
+        assert_eq!(map.remove(&1), None);
+        assert_eq!(map.len(), 167);
+        assert_eq!(map.get(&9), Some(&90));
+        assert!(map.range(10..=20).map(|(k, _)| *k).eq([12, 15, 18]));
+        for i in (0..500).step_by(3) {
+            map.remove(&i);
+            map.debug_validate();
+        }
+        assert!(map.is_empty());
+        assert_eq!(map.height(), 0);
+    }
+}
@@ -0,0 +1,818 @@
+//! Weight-balanced (`BB[α]`) tree as an ordered map and as a sequence.
+
+use crate::btree::{before_start, check_range, past_end};
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::RangeBounds;
+
+/// A subtree may outweigh its sibling by at most this factor.
+const DELTA: usize = 3;
+/// A rebalance uses a single rotation when the inner grandchild weighs less
+/// than this factor times the outer one, and a double rotation otherwise.
+const GAMMA: usize = 2;
+
+type Link<T> = Option<Box<Node<T>>>;
+
+#[derive(Debug, Clone)]
+struct Node<T> {
+    item: T,
+    /// Number of items in the subtree rooted here.
+    size: usize,
+    left: Link<T>,
+    right: Link<T>,
+}
+
+fn size<T>(link: &Link<T>) -> usize {
+    link.as_ref().map_or(0, |node| node.size)
+}
+
+/// The weight of a subtree, one more than its size so that empty subtrees
+/// weigh something.
+fn weight<T>(link: &Link<T>) -> usize {
+    size(link) + 1
+}
+
+/// True if a subtree weighing `heavy` is too heavy beside one weighing
+/// `light`.
+fn too_heavy(heavy: usize, light: usize) -> bool {
+    heavy > DELTA * light
+}
+
+impl<T> Node<T> {
+    fn leaf(item: T) -> Box<Self> {
+        Box::new(Self {
+            item,
+            size: 1,
+            left: None,
+            right: None,
+        })
+    }
+
+    fn update_size(&mut self) {
+        self.size = 1 + size(&self.left) + size(&self.right);
+    }
+}
+
+/// Lift the left child above `node`.
+fn rotate_right<T>(mut node: Box<Node<T>>) -> Box<Node<T>> {
+    let mut pivot = node.left.take().expect("rotate_right needs a left child");
+    node.left = pivot.right.take();
+    node.update_size();
+    pivot.right = Some(node);
+    pivot.update_size();
+    pivot
+}
+
+/// Lift the right child above `node`.
+fn rotate_left<T>(mut node: Box<Node<T>>) -> Box<Node<T>> {
+    let mut pivot = node.right.take().expect("rotate_left needs a right child");
+    node.right = pivot.left.take();
+    node.update_size();
+    pivot.left = Some(node);
+    pivot.update_size();
+    pivot
+}
+
+/// Restore the weight balance of a node after one of its subtrees gained or
+/// lost a little weight, with one or two rotations.
+fn balance<T>(mut node: Box<Node<T>>) -> Box<Node<T>> {
+    node.update_size();
+    let (left, right) = (weight(&node.left), weight(&node.right));
+    if too_heavy(right, left) {
+        let inner = node.right.as_ref().expect("heavy side is non-empty");
+        if weight(&inner.left) >= GAMMA * weight(&inner.right) {
+            node.right = node.right.take().map(rotate_right);
+        }
+        rotate_left(node)
+    } else if too_heavy(left, right) {
+        let inner = node.left.as_ref().expect("heavy side is non-empty");
+        if weight(&inner.right) >= GAMMA * weight(&inner.left) {
+            node.left = node.left.take().map(rotate_left);
+        }
+        rotate_right(node)
+    } else {
+        node
+    }
+}
+
+/// Join `left`, `middle` and `right` into one balanced tree, in that order,
+/// whatever their relative sizes.
+///
+/// Descends the spine of the heavier side until the other side fits beside
+/// it, so takes O(log(n / m)) for subtrees of n and m items.
+fn join<T>(left: Link<T>, mut middle: Box<Node<T>>, right: Link<T>) -> Box<Node<T>> {
+    let (left_weight, right_weight) = (weight(&left), weight(&right));
+    if too_heavy(left_weight, right_weight) {
+        let mut left = left.expect("heavy side is non-empty");
+        left.right = Some(join(left.right.take(), middle, right));
+        balance(left)
+    } else if too_heavy(right_weight, left_weight) {
+        let mut right = right.expect("heavy side is non-empty");
+        right.left = Some(join(left, middle, right.left.take()));
+        balance(right)
+    } else {
+        middle.left = left;
+        middle.right = right;
+        middle.update_size();
+        middle
+    }
+}
+
+/// Detach the first node of a subtree, returning it and the rest.
+fn remove_first<T>(mut node: Box<Node<T>>) -> (Box<Node<T>>, Link<T>) {
+    match node.left.take() {
+        None => {
+            let rest = node.right.take();
+            (node, rest)
+        }
+        Some(left) => {
+            let (first, left) = remove_first(left);
+            node.left = left;
+            (first, Some(balance(node)))
+        }
+    }
+}
+
+/// Join two subtrees, every item of `left` coming first.
+fn concat<T>(left: Link<T>, right: Link<T>) -> Link<T> {
+    match right {
+        None => left,
+        Some(right) => {
+            let (first, right) = remove_first(right);
+            Some(join(left, first, right))
+        }
+    }
+}
+
+/// Split a subtree into its first `index` items and the rest.
+fn split_at<T>(link: Link<T>, index: usize) -> (Link<T>, Link<T>) {
+    let Some(mut node) = link else {
+        return (None, None);
+    };
+    let (left, right) = (node.left.take(), node.right.take());
+    let left_size = size(&left);
+    if index <= left_size {
+        let (before, after) = split_at(left, index);
+        (before, Some(join(after, node, right)))
+    } else {
+        let (before, after) = split_at(right, index - left_size - 1);
+        (Some(join(left, node, before)), after)
+    }
+}
+
+/// Split a subtree into the items for which `goes_left` holds and the rest.
+///
+/// `goes_left` must hold for a prefix of the items in order.
+fn split_when<T, F>(link: Link<T>, goes_left: &F) -> (Link<T>, Link<T>)
+where
+    F: Fn(&T) -> bool,
+{
+    let Some(mut node) = link else {
+        return (None, None);
+    };
+    let (left, right) = (node.left.take(), node.right.take());
+    if goes_left(&node.item) {
+        let (before, after) = split_when(right, goes_left);
+        (Some(join(left, node, before)), after)
+    } else {
+        let (before, after) = split_when(left, goes_left);
+        (before, Some(join(after, node, right)))
+    }
+}
+
+/// Returns the node at in-order position `index`.
+fn nth<T>(mut link: &Link<T>, mut index: usize) -> Option<&Node<T>> {
+    while let Some(node) = link {
+        let left_size = size(&node.left);
+        link = match index.cmp(&left_size) {
+            Ordering::Less => &node.left,
+            Ordering::Greater => {
+                index -= left_size + 1;
+                &node.right
+            }
+            Ordering::Equal => return Some(node),
+        };
+    }
+    None
+}
+
+fn nth_mut<T>(mut link: &mut Link<T>, mut index: usize) -> Option<&mut Node<T>> {
+    while let Some(node) = link {
+        let left_size = size(&node.left);
+        link = match index.cmp(&left_size) {
+            Ordering::Less => &mut node.left,
+            Ordering::Greater => {
+                index -= left_size + 1;
+                &mut node.right
+            }
+            Ordering::Equal => return Some(node),
+        };
+    }
+    None
+}
+
+/// Build a balanced subtree from the next `len` items.
+fn build<T>(items: &mut impl Iterator<Item = T>, len: usize) -> Link<T> {
+    if len == 0 {
+        return None;
+    }
+    let left = build(items, len / 2);
+    let mut node = Node::leaf(items.next().expect("iterator yields len items"));
+    node.right = build(items, len - len / 2 - 1);
+    node.left = left;
+    node.update_size();
+    Some(node)
+}
+
+/// Checks sizes and weight balance below `link`, passing each node to
+/// `visit` in order, and returns the subtree size.
+fn validate<'a, T>(link: &'a Link<T>, visit: &mut impl FnMut(&'a T)) -> usize {
+    let Some(node) = link else {
+        return 0;
+    };
+    let left = validate(&node.left, visit);
+    visit(&node.item);
+    let right = validate(&node.right, visit);
+    assert_eq!(node.size, 1 + left + right, "stale subtree size");
+    assert!(
+        !too_heavy(left + 1, right + 1) && !too_heavy(right + 1, left + 1),
+        "subtrees out of weight balance"
+    );
+    node.size
+}
+
+/// In-order traversal of the nodes from a starting point up to an end node.
+struct Nodes<'a, T> {
+    /// Nodes still to visit whose left subtrees are already handled; the
+    /// next one is on top.
+    stack: Vec<&'a Node<T>>,
+    /// The first node past the end, if there is one.
+    end: Option<&'a Node<T>>,
+}
+
+impl<'a, T> Nodes<'a, T> {
+    /// The nodes from the first for which `before_start` fails to the last
+    /// for which `past_end` fails.
+    fn between(
+        root: &'a Link<T>,
+        before_start: impl Fn(&T) -> bool,
+        past_end: impl Fn(&T) -> bool,
+    ) -> Self {
+        let mut nodes = Nodes {
+            stack: Vec::new(),
+            end: None,
+        };
+        let mut link = root;
+        while let Some(node) = link {
+            if before_start(&node.item) {
+                link = &node.right;
+            } else {
+                nodes.stack.push(node);
+                link = &node.left;
+            }
+        }
+        let mut link = root;
+        while let Some(node) = link {
+            if past_end(&node.item) {
+                nodes.end = Some(node);
+                link = &node.left;
+            } else {
+                link = &node.right;
+            }
+        }
+        nodes
+    }
+}
+
+impl<'a, T> Iterator for Nodes<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let node = self.stack.pop()?;
+        if self.end.is_some_and(|end| std::ptr::eq(end, node)) {
+            self.stack.clear();
+            return None;
+        }
+        let mut link = &node.right;
+        while let Some(child) = link {
+            self.stack.push(child);
+            link = &child.left;
+        }
+        Some(&node.item)
+    }
+}
+
+/// An ordered map stored in a weight-balanced tree.
+///
+/// Every node records the size of its subtree, and the tree is kept in
+/// `BB[α]` balance: neither subtree of a node may weigh more than three times
+/// the other, counting a subtree of n entries as weighing n + 1, which is
+/// α = 1/4. Balancing by size rather than height means the sizes that keep
+/// the tree balanced also answer [`select`](Self::select) and
+/// [`rank`](Self::rank) in O(log n), and it makes joining two trees of any
+/// sizes around a middle entry cheap: descend the larger one until the
+/// smaller fits beside it, then rebalance on the way back up. Splitting is
+/// a series of such joins, so [`split_off`](Self::split_off) and
+/// [`merge`](Self::merge) are O(log n) in the worst case, where
+/// [`TreapMap`](crate::TreapMap) only manages it in expectation. The same
+/// tree ordered by position instead of key is a
+/// [`WeightBalancedSeq`].
+#[derive(Clone)]
+pub struct WeightBalancedMap<K, V> {
+    root: Link<(K, V)>,
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for WeightBalancedMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V> Default for WeightBalancedMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> WeightBalancedMap<K, V> {
+    /// Create a new empty WeightBalancedMap.
+    pub fn new() -> Self {
+        Self { root: None }
+    }
+
+    /// Returns the number of elements in the map.
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    /// Returns true if the map contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Clear all entries from the map.
+    pub fn clear(&mut self) {
+        self.root = None;
+    }
+
+    /// Returns the entry with the `k`-th smallest key, counting from 0, or
+    /// `None` if the map has no more than `k` entries.
+    pub fn select(&self, k: usize) -> Option<(&K, &V)> {
+        nth(&self.root, k).map(|node| (&node.item.0, &node.item.1))
+    }
+
+    /// Split the map in two by rank, returning every entry from the `k`-th
+    /// smallest key up.
+    ///
+    /// Takes O(log n) time.
+    pub fn split_off_nth(&mut self, k: usize) -> Self {
+        let (left, right) = split_at(self.root.take(), k);
+        self.root = left;
+        Self { root: right }
+    }
+}
+
+impl<K: Ord, V> WeightBalancedMap<K, V> {
+    /// Insert a key-value pair into the map.
+    ///
+    /// Returns the previous value if the key existed.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        fn insert<K: Ord, V>(
+            link: Link<(K, V)>,
+            key: K,
+            value: V,
+        ) -> (Box<Node<(K, V)>>, Option<V>) {
+            let Some(mut node) = link else {
+                return (Node::leaf((key, value)), None);
+            };
+            let old = match key.cmp(&node.item.0) {
+                Ordering::Less => {
+                    let (left, old) = insert(node.left.take(), key, value);
+                    node.left = Some(left);
+                    old
+                }
+                Ordering::Greater => {
+                    let (right, old) = insert(node.right.take(), key, value);
+                    node.right = Some(right);
+                    old
+                }
+                Ordering::Equal => {
+                    let old = std::mem::replace(&mut node.item.1, value);
+                    return (node, Some(old));
+                }
+            };
+            (balance(node), old)
+        }
+
+        let (root, old) = insert(self.root.take(), key, value);
+        self.root = Some(root);
+        old
+    }
+
+    /// Get a reference to the value associated with the key.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let mut link = &self.root;
+        while let Some(node) = link {
+            link = match key.cmp(&node.item.0) {
+                Ordering::Less => &node.left,
+                Ordering::Greater => &node.right,
+                Ordering::Equal => return Some(&node.item.1),
+            };
+        }
+        None
+    }
+
+    /// Get a mutable reference to the value associated with the key.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let mut link = &mut self.root;
+        while let Some(node) = link {
+            link = match key.cmp(&node.item.0) {
+                Ordering::Less => &mut node.left,
+                Ordering::Greater => &mut node.right,
+                Ordering::Equal => return Some(&mut node.item.1),
+            };
+        }
+        None
+    }
+
+    /// Check if the map contains the given key.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Remove a key-value pair from the map.
+    ///
+    /// Returns the removed value if the key existed.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        fn remove<K: Ord, V>(link: Link<(K, V)>, key: &K) -> (Link<(K, V)>, Option<V>) {
+            let Some(mut node) = link else {
+                return (None, None);
+            };
+            let removed = match key.cmp(&node.item.0) {
+                Ordering::Less => {
+                    let (left, removed) = remove(node.left.take(), key);
+                    node.left = left;
+                    removed
+                }
+                Ordering::Greater => {
+                    let (right, removed) = remove(node.right.take(), key);
+                    node.right = right;
+                    removed
+                }
+                Ordering::Equal => {
+                    let rest = concat(node.left.take(), node.right.take());
+                    return (rest, Some(node.item.1));
+                }
+            };
+            (Some(balance(node)), removed)
+        }
+
+        let (root, removed) = remove(self.root.take(), key);
+        self.root = root;
+        removed
+    }
+
+    /// Returns the number of keys less than `key`, which is its position
+    /// in key order if it is present.
+    pub fn rank(&self, key: &K) -> usize {
+        let mut rank = 0;
+        let mut link = &self.root;
+        while let Some(node) = link {
+            link = match key.cmp(&node.item.0) {
+                Ordering::Less => &node.left,
+                Ordering::Greater => {
+                    rank += size(&node.left) + 1;
+                    &node.right
+                }
+                Ordering::Equal => return rank + size(&node.left),
+            };
+        }
+        rank
+    }
+
+    /// Split the map in two at `key`, returning everything from `key` up.
+    ///
+    /// Takes O(log n) time.
+    pub fn split_off(&mut self, key: &K) -> Self {
+        let (left, right) = split_when(self.root.take(), &|(k, _): &(K, V)| k < key);
+        self.root = left;
+        Self { root: right }
+    }
+
+    /// Append `other`, every key of which must be greater than every key in
+    /// this map.
+    ///
+    /// Takes O(log n) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key ranges of the two maps overlap.
+    pub fn merge(&mut self, other: Self) {
+        let last = self.len().checked_sub(1).and_then(|k| self.select(k));
+        if let (Some((last, _)), Some((first, _))) = (last, other.select(0)) {
+            assert!(last < first, "merged keys must all be greater");
+        }
+        self.root = concat(self.root.take(), other.root);
+    }
+
+    /// Iterate over all key-value pairs in key order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.range(..)
+    }
+
+    /// Iterate over the key-value pairs whose keys fall in `range`, in key
+    /// order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range starts after it ends, or starts and ends at the
+    /// same excluded key.
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Iter<'_, K, V> {
+        check_range(&range);
+        Iter {
+            nodes: Nodes::between(
+                &self.root,
+                |(key, _)| before_start(key, range.start_bound()),
+                |(key, _)| past_end(key, range.end_bound()),
+            ),
+        }
+    }
+
+    /// Check the tree invariants, panicking on the first violation.
+    ///
+    /// Checks that keys are strictly increasing in order, that every stored
+    /// subtree size is correct, and that no subtree outweighs its sibling
+    /// more than threefold.
+    pub fn debug_validate(&self) {
+        let mut previous: Option<&K> = None;
+        validate(&self.root, &mut |(key, _)| {
+            assert!(
+                previous.map_or(true, |previous| previous < key),
+                "keys out of order"
+            );
+            previous = Some(key);
+        });
+    }
+}
+
+/// An iterator over a key range of a [`WeightBalancedMap`], in key order.
+pub struct Iter<'a, K, V> {
+    nodes: Nodes<'a, (K, V)>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.nodes.next().map(|(key, value)| (key, value))
+    }
+}
+
+/// A sequence stored in a weight-balanced tree, indexed by position.
+///
+/// The tree of a [`WeightBalancedMap`] ordered by position rather than by
+/// key: subtree sizes steer every lookup, so reading, inserting or removing
+/// at any index is O(log n), as are [`split_off`](Self::split_off) and
+/// [`append`](Self::append), where a `Vec` moves O(n) items for each. That
+/// makes it a rope-like list for editors and other workloads that cut and
+/// splice long sequences, at the cost of a pointer chase per level on
+/// every access.
+#[derive(Clone)]
+pub struct WeightBalancedSeq<T> {
+    root: Link<T>,
+}
+
+impl<T: fmt::Debug> fmt::Debug for WeightBalancedSeq<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T> Default for WeightBalancedSeq<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> WeightBalancedSeq<T> {
+    /// Create a new empty WeightBalancedSeq.
+    pub fn new() -> Self {
+        Self { root: None }
+    }
+
+    /// Returns the number of items in the sequence.
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    /// Returns true if the sequence contains no items.
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Clear all items from the sequence.
+    pub fn clear(&mut self) {
+        self.root = None;
+    }
+
+    /// Get a reference to the item at `index`.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        nth(&self.root, index).map(|node| &node.item)
+    }
+
+    /// Get a mutable reference to the item at `index`.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        nth_mut(&mut self.root, index).map(|node| &mut node.item)
+    }
+
+    /// Append an item to the end of the sequence.
+    pub fn push(&mut self, item: T) {
+        let len = self.len();
+        self.insert(len, item);
+    }
+
+    /// Insert an item at `index`, shifting the items after it along.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length.
+    pub fn insert(&mut self, index: usize, item: T) {
+        fn insert<T>(link: Link<T>, index: usize, item: T) -> Box<Node<T>> {
+            let Some(mut node) = link else {
+                return Node::leaf(item);
+            };
+            let left_size = size(&node.left);
+            if index <= left_size {
+                node.left = Some(insert(node.left.take(), index, item));
+            } else {
+                node.right = Some(insert(node.right.take(), index - left_size - 1, item));
+            }
+            balance(node)
+        }
+
+        assert!(index <= self.len(), "insertion index out of bounds");
+        self.root = Some(insert(self.root.take(), index, item));
+    }
+
+    /// Remove the item at `index`, shifting the items after it back.
+    ///
+    /// Returns the removed item, or `None` if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        fn remove<T>(mut node: Box<Node<T>>, index: usize) -> (Link<T>, T) {
+            let left_size = size(&node.left);
+            let removed = match index.cmp(&left_size) {
+                Ordering::Less => {
+                    let left = node.left.take().expect("index within the left subtree");
+                    let (left, removed) = remove(left, index);
+                    node.left = left;
+                    removed
+                }
+                Ordering::Greater => {
+                    let right = node.right.take().expect("index within the right subtree");
+                    let (right, removed) = remove(right, index - left_size - 1);
+                    node.right = right;
+                    removed
+                }
+                Ordering::Equal => {
+                    let rest = concat(node.left.take(), node.right.take());
+                    return (rest, node.item);
+                }
+            };
+            (Some(balance(node)), removed)
+        }
+
+        if index >= self.len() {
+            return None;
+        }
+        let root = self.root.take().expect("non-empty sequence has a root");
+        let (root, removed) = remove(root, index);
+        self.root = root;
+        Some(removed)
+    }
+
+    /// Split the sequence in two at `index`, returning the items from
+    /// `index` on.
+    ///
+    /// Takes O(log n) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length.
+    pub fn split_off(&mut self, index: usize) -> Self {
+        assert!(index <= self.len(), "split index out of bounds");
+        let (left, right) = split_at(self.root.take(), index);
+        self.root = left;
+        Self { root: right }
+    }
+
+    /// Move every item of `other` onto the end of the sequence.
+    ///
+    /// Takes O(log n) time.
+    pub fn append(&mut self, other: &mut Self) {
+        self.root = concat(self.root.take(), other.root.take());
+    }
+
+    /// Iterate over the items in order.
+    pub fn iter(&self) -> SeqIter<'_, T> {
+        SeqIter {
+            nodes: Nodes::between(&self.root, |_| false, |_| false),
+        }
+    }
+
+    /// Check the tree invariants, panicking on the first violation.
+    ///
+    /// Checks that every stored subtree size is correct and that no
+    /// subtree outweighs its sibling more than threefold.
+    pub fn debug_validate(&self) {
+        validate(&self.root, &mut |_| {});
+    }
+}
+
+impl<T> FromIterator<T> for WeightBalancedSeq<T> {
+    /// Builds a perfectly balanced tree in O(n).
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let items: Vec<T> = iter.into_iter().collect();
+        let len = items.len();
+        Self {
+            root: build(&mut items.into_iter(), len),
+        }
+    }
+}
+
+/// An iterator over the items of a [`WeightBalancedSeq`], in order.
+pub struct SeqIter<'a, T> {
+    nodes: Nodes<'a, T>,
+}
+
+impl<'a, T> Iterator for SeqIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.nodes.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_select_rank_and_split() {
+        let mut map = WeightBalancedMap::new();
+        for i in 0..1000 {
+            map.insert(i, i * 10);
+            map.debug_validate();
+        }
+        assert_eq!(map.select(250), Some((&250, &2500)));
+        assert_eq!(map.rank(&250), 250);
+        let mut upper = map.split_off(&600);
+        let top = upper.split_off_nth(300);
+        map.debug_validate();
+        upper.debug_validate();
+        assert_eq!((map.len(), upper.len(), top.len()), (600, 300, 100));
+        assert!(top.iter().map(|(k, _)| *k).eq(900..1000));
+        assert_eq!(upper.remove(&600), Some(6000));
+        map.merge(upper);
+        map.debug_validate();
+        assert_eq!(map.len(), 899);
+        assert_eq!(map.get(&600), None);
+        assert!(map
+            .range(598..603)
+            .map(|(k, _)| *k)
+            .eq([598, 599, 601, 602]));
+    }
+
+    #[test]
+    #[should_panic(expected = "merged keys must all be greater")]
+    fn test_merge_overlapping_panics() {
+        let mut map = WeightBalancedMap::new();
+        map.insert(5, ());
+        let mut other = WeightBalancedMap::new();
+        other.insert(3, ());
+        map.merge(other);
+    }
+
+    #[test]
+    fn test_seq_splice() {
+        let mut seq: WeightBalancedSeq<u32> = (0..1000).collect();
+        seq.debug_validate();
+        let mut tail = seq.split_off(10);
+        let mut small: WeightBalancedSeq<u32> = (5000..5003).collect();
+        // Joining a 3-item tree onto a 990-item one stays balanced.
+        small.append(&mut tail);
+        small.debug_validate();
+        seq.append(&mut small);
+        seq.debug_validate();
+        assert_eq!(seq.len(), 1003);
+        assert_eq!(seq.get(10), Some(&5000));
+        assert_eq!(seq.get(13), Some(&10));
+        seq.insert(0, 7);
+        assert_eq!(seq.remove(1), Some(0));
+        *seq.get_mut(1).unwrap() += 100;
+        seq.push(9);
+        seq.debug_validate();
+        assert!(seq.iter().take(3).eq(&[7, 101, 2]));
+        assert_eq!(seq.get(1003), Some(&9));
+        assert_eq!(seq.remove(1004), None);
+    }
+}
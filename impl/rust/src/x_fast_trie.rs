@@ -0,0 +1,304 @@
+//! X-fast trie: a u64 set with O(log w) successor and predecessor.
+
+use crate::HashMap;
+use std::fmt;
+
+/// Bits per key, the height of the trie.
+const WORD_BITS: usize = 64;
+
+/// The smallest and largest key under a trie node.
+#[derive(Debug, Clone, Copy)]
+struct Span {
+    min: u64,
+    max: u64,
+}
+
+/// A key's neighbors in sorted order.
+#[derive(Debug, Clone, Copy)]
+struct Link {
+    prev: Option<u64>,
+    next: Option<u64>,
+}
+
+/// The top `level` bits of `key`.
+fn prefix(key: u64, level: usize) -> u64 {
+    if level == 0 {
+        0
+    } else {
+        key >> (WORD_BITS - level)
+    }
+}
+
+/// A set of `u64` keys answering successor and predecessor queries in
+/// O(log w) expected time, for w = 64 key bits.
+///
+/// Conceptually a binary trie over the bits of the keys, with one
+/// [`HashMap`] per level holding the prefixes present at that depth and the
+/// smallest and largest key below each. Since a prefix being present means
+/// every shorter prefix is too, the longest prefix a query shares with the
+/// set is found by binary search over the levels, one hash lookup per step.
+/// Where that path ends, the missing branch says whether the node's smallest
+/// key is the successor or its largest key the predecessor, and a linked
+/// list through the keys in order supplies the other neighbor. Membership
+/// is one lookup. Insert and remove touch every level, so they take O(w)
+/// expected time, and the set takes O(n w) space; a
+/// [`YFastTrie`](crate::YFastTrie) brings both down.
+#[derive(Clone)]
+pub struct XFastTrie {
+    /// `levels[l]` maps each `l`-bit prefix present to its key span.
+    levels: Vec<HashMap<u64, Span>>,
+    /// Every key, linked to its neighbors.
+    leaves: HashMap<u64, Link>,
+}
+
+impl Default for XFastTrie {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl XFastTrie {
+    /// Create a new empty XFastTrie.
+    pub fn new() -> Self {
+        Self {
+            levels: (0..WORD_BITS).map(|_| HashMap::new()).collect(),
+            leaves: HashMap::new(),
+        }
+    }
+
+    /// Returns the number of keys in the set.
+    pub fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    /// Returns true if the set contains no keys.
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    /// Clear all keys from the set.
+    pub fn clear(&mut self) {
+        for level in &mut self.levels {
+            level.clear();
+        }
+        self.leaves.clear();
+    }
+
+    /// Check if `key` is in the set.
+    pub fn contains(&self, key: u64) -> bool {
+        self.leaves.contains_key(&key)
+    }
+
+    /// Returns the smallest key.
+    pub fn min(&self) -> Option<u64> {
+        self.levels[0].get(&0).map(|span| span.min)
+    }
+
+    /// Returns the largest key.
+    pub fn max(&self) -> Option<u64> {
+        self.levels[0].get(&0).map(|span| span.max)
+    }
+
+    /// The deepest level at which `key` shares a prefix with the set, and
+    /// that prefix's span, for a key not in the non-empty set.
+    fn longest_prefix(&self, key: u64) -> (usize, Span) {
+        // Level `lo` is always present and level `hi` never is.
+        let (mut lo, mut hi) = (0, WORD_BITS);
+        while hi - lo > 1 {
+            let mid = lo + (hi - lo) / 2;
+            if self.levels[mid].contains_key(&prefix(key, mid)) {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        (
+            lo,
+            *self.levels[lo]
+                .get(&prefix(key, lo))
+                .expect("level `lo` holds the prefix"),
+        )
+    }
+
+    fn link(&self, key: u64) -> Link {
+        *self.leaves.get(&key).expect("span ends are keys")
+    }
+
+    /// Whether `key` goes right below its longest shared prefix at `level`.
+    fn branches_right(key: u64, level: usize) -> bool {
+        key >> (WORD_BITS - 1 - level) & 1 == 1
+    }
+
+    /// Returns the smallest key greater than `key`.
+    pub fn successor(&self, key: u64) -> Option<u64> {
+        if let Some(link) = self.leaves.get(&key) {
+            return link.next;
+        }
+        if self.is_empty() {
+            return None;
+        }
+        let (level, span) = self.longest_prefix(key);
+        if Self::branches_right(key, level) {
+            // Everything below the node is smaller than `key`.
+            self.link(span.max).next
+        } else {
+            Some(span.min)
+        }
+    }
+
+    /// Returns the largest key less than `key`.
+    pub fn predecessor(&self, key: u64) -> Option<u64> {
+        if let Some(link) = self.leaves.get(&key) {
+            return link.prev;
+        }
+        if self.is_empty() {
+            return None;
+        }
+        let (level, span) = self.longest_prefix(key);
+        if Self::branches_right(key, level) {
+            Some(span.max)
+        } else {
+            // Everything below the node is greater than `key`.
+            self.link(span.min).prev
+        }
+    }
+
+    /// Insert a key.
+    ///
+    /// Returns true if the key was not already present.
+    pub fn insert(&mut self, key: u64) -> bool {
+        if self.contains(key) {
+            return false;
+        }
+        let link = Link {
+            prev: self.predecessor(key),
+            next: self.successor(key),
+        };
+        if let Some(prev) = link.prev {
+            self.leaves.get_mut(&prev).expect("linked key").next = Some(key);
+        }
+        if let Some(next) = link.next {
+            self.leaves.get_mut(&next).expect("linked key").prev = Some(key);
+        }
+        self.leaves.insert(key, link);
+        for (level, spans) in self.levels.iter_mut().enumerate() {
+            spans
+                .entry(prefix(key, level))
+                .and_modify(|span| {
+                    span.min = span.min.min(key);
+                    span.max = span.max.max(key);
+                })
+                .or_insert(Span { min: key, max: key });
+        }
+        true
+    }
+
+    /// Remove a key.
+    ///
+    /// Returns true if the key was present.
+    pub fn remove(&mut self, key: u64) -> bool {
+        let Some(link) = self.leaves.remove(&key) else {
+            return false;
+        };
+        if let Some(prev) = link.prev {
+            self.leaves.get_mut(&prev).expect("linked key").next = link.next;
+        }
+        if let Some(next) = link.next {
+            self.leaves.get_mut(&next).expect("linked key").prev = link.prev;
+        }
+        for (level, spans) in self.levels.iter_mut().enumerate() {
+            let prefix = prefix(key, level);
+            let span = spans.get_mut(&prefix).expect("prefix of a key");
+            if span.min == key && span.max == key {
+                spans.remove(&prefix);
+                continue;
+            }
+            // The keys under a node are contiguous in order, so the ones
+            // left next to `key` replace it at the edge of the span.
+            if span.min == key {
+                span.min = link.next.expect("span has a larger key");
+            }
+            if span.max == key {
+                span.max = link.prev.expect("span has a smaller key");
+            }
+        }
+        true
+    }
+
+    /// Iterate over the keys in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = u64> + '_ {
+        std::iter::successors(self.min(), |&key| self.link(key).next)
+    }
+}
+
+impl Extend<u64> for XFastTrie {
+    fn extend<I: IntoIterator<Item = u64>>(&mut self, iter: I) {
+        for key in iter {
+            self.insert(key);
+        }
+    }
+}
+
+impl FromIterator<u64> for XFastTrie {
+    fn from_iter<I: IntoIterator<Item = u64>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl fmt::Debug for XFastTrie {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_successor_and_predecessor() {
+        let set: XFastTrie = [3, 9, 10, 200, 1 << 40].into_iter().collect();
+        assert_eq!(set.len(), 5);
+        assert_eq!(set.successor(0), Some(3));
+        assert_eq!(set.successor(3), Some(9));
+        assert_eq!(set.successor(11), Some(200));
+        assert_eq!(set.successor(1 << 40), None);
+        assert_eq!(set.predecessor(10), Some(9));
+        assert_eq!(set.predecessor(199), Some(10));
+        assert_eq!(set.predecessor(u64::MAX), Some(1 << 40));
+        assert_eq!(set.predecessor(3), None);
+        assert_eq!((set.min(), set.max()), (Some(3), Some(1 << 40)));
+    }
+
+    #[test]
+    fn test_remove_updates_spans() {
+        let mut set: XFastTrie = (0..64).map(|i| i * 3).collect();
+        assert!(set.remove(0));
+        assert!(!set.remove(0));
+        assert!(set.remove(189));
+        assert_eq!((set.min(), set.max()), (Some(3), Some(186)));
+        for key in (6..186).step_by(3) {
+            set.remove(key);
+        }
+        assert_eq!(set.iter().collect::<Vec<_>>(), [3, 186]);
+        assert_eq!(set.successor(4), Some(186));
+        assert_eq!(set.predecessor(185), Some(3));
+        set.clear();
+        assert!(set.is_empty());
+        assert_eq!(set.successor(0), None);
+    }
+
+    #[test]
+    fn test_extreme_keys() {
+        let mut set = XFastTrie::new();
+        assert!(set.insert(u64::MAX));
+        assert!(set.insert(0));
+        assert!(!set.insert(0));
+        assert!(set.contains(u64::MAX));
+        assert_eq!(set.successor(1), Some(u64::MAX));
+        assert_eq!(set.predecessor(u64::MAX - 1), Some(0));
+        assert_eq!(set.iter().collect::<Vec<_>>(), [0, u64::MAX]);
+    }
+}
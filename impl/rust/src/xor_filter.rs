@@ -0,0 +1,178 @@
+//! Static XOR filter for approximate set membership.
+
+use crate::hashmap::DefaultHashBuilder;
+use crate::perfect::{displace, reduce};
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+
+/// A read-only approximate membership filter with 8-bit fingerprints.
+///
+/// Each key hashes to one cell in each third of a table about 1.23 times
+/// the key count, and the build assigns the cells so that the XOR of a key's
+/// three cells equals its fingerprint. A lookup reads three bytes and
+/// compares: there are no false negatives, and a key outside the set passes
+/// with probability 1/256. That costs about 9.8 bits per key, under a Bloom
+/// filter at the same error rate, but the set is fixed at build time; adding
+/// a key means rebuilding.
+#[derive(Debug, Clone)]
+pub struct XorFilter<K> {
+    fingerprints: Vec<u8>,
+    /// Length of each third of `fingerprints`.
+    block_length: usize,
+    seed: u32,
+    len: usize,
+    hash_builder: DefaultHashBuilder,
+    marker: PhantomData<fn(&K)>,
+}
+
+/// The fingerprint stored for a mixed key hash.
+fn fingerprint(hash: u64) -> u8 {
+    (hash ^ (hash >> 32)) as u8
+}
+
+/// The cell a mixed key hash occupies in each third of the table.
+fn cells(hash: u64, block_length: usize) -> [usize; 3] {
+    [
+        reduce(hash, block_length),
+        block_length + reduce(hash.rotate_left(21), block_length),
+        2 * block_length + reduce(hash.rotate_left(42), block_length),
+    ]
+}
+
+impl<K: Hash> XorFilter<K> {
+    /// Build a filter holding the given keys. Duplicates are ignored.
+    ///
+    /// Retries with a fresh seed whenever the keys' cells cannot be peeled
+    /// apart, which happens with small probability at this table size.
+    pub fn build<I: IntoIterator<Item = K>>(keys: I) -> Self {
+        let hash_builder = DefaultHashBuilder::default();
+        let mut hashes: Vec<u64> = keys
+            .into_iter()
+            .map(|key| hash_builder.hash_one(&key))
+            .collect();
+        // A repeated hash would make a cell that never peels.
+        hashes.sort_unstable();
+        hashes.dedup();
+
+        let block_length = (hashes.len() * 123 / 100 + 32) / 3;
+        let capacity = 3 * block_length;
+        let mut count = vec![0u32; capacity];
+        let mut mask = vec![0u64; capacity];
+        let mut queue = Vec::new();
+        let mut stack: Vec<(u64, usize)> = Vec::with_capacity(hashes.len());
+        for seed in 0.. {
+            count.fill(0);
+            mask.fill(0);
+            stack.clear();
+            for &hash in &hashes {
+                let hash = displace(hash, seed);
+                for cell in cells(hash, block_length) {
+                    count[cell] += 1;
+                    mask[cell] ^= hash;
+                }
+            }
+
+            // Peel: a cell hit by exactly one key fixes that key's order,
+            // and removing the key may leave other cells with one.
+            queue.extend((0..capacity).filter(|&cell| count[cell] == 1));
+            while let Some(cell) = queue.pop() {
+                if count[cell] != 1 {
+                    continue;
+                }
+                let hash = mask[cell];
+                stack.push((hash, cell));
+                for other in cells(hash, block_length) {
+                    count[other] -= 1;
+                    mask[other] ^= hash;
+                    if count[other] == 1 {
+                        queue.push(other);
+                    }
+                }
+            }
+            if stack.len() < hashes.len() {
+                continue;
+            }
+
+            // Assign in reverse peel order, so each key's cell is the last of
+            // its three to be written.
+            let mut fingerprints = vec![0u8; capacity];
+            for &(hash, cell) in stack.iter().rev() {
+                let [a, b, c] = cells(hash, block_length);
+                fingerprints[cell] =
+                    fingerprint(hash) ^ fingerprints[a] ^ fingerprints[b] ^ fingerprints[c];
+            }
+            return Self {
+                fingerprints,
+                block_length,
+                seed,
+                len: hashes.len(),
+                hash_builder,
+                marker: PhantomData,
+            };
+        }
+        unreachable!("ran out of seeds")
+    }
+
+    /// Returns true if the key may be in the set, and false if it is
+    /// definitely not.
+    pub fn contains(&self, key: &K) -> bool {
+        if self.len == 0 {
+            return false;
+        }
+        let hash = displace(self.hash_builder.hash_one(key), self.seed);
+        let [a, b, c] = cells(hash, self.block_length);
+        fingerprint(hash) == self.fingerprints[a] ^ self.fingerprints[b] ^ self.fingerprints[c]
+    }
+
+    /// Returns the number of distinct keys the filter was built from.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the filter was built from no keys.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the size of the fingerprint table in bytes.
+    pub fn size_in_bytes(&self) -> usize {
+        self.fingerprints.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_false_negatives() {
+        let filter = XorFilter::build((0..10_000u64).chain(0..100));
+        assert_eq!(filter.len(), 10_000);
+        assert!((0..10_000).all(|key| filter.contains(&key)));
+        // About 1.23 bytes per key.
+        assert!(filter.size_in_bytes() < 12_400);
+    }
+
+    #[test]
+    fn test_false_positive_rate() {
+        let filter = XorFilter::build(0..10_000u64);
+        let false_positives = (10_000..110_000u64)
+            .filter(|key| filter.contains(key))
+            .count();
+        // Expect 1/256 of 100,000, about 390.
+        assert!(
+            (250..550).contains(&false_positives),
+            "{} false positives",
+            false_positives
+        );
+    }
+
+    #[test]
+    fn test_empty_and_single() {
+        let empty: XorFilter<&str> = XorFilter::build([]);
+        assert!(empty.is_empty());
+        assert!(!empty.contains(&"a"));
+        let single = XorFilter::build(["a"]);
+        assert!(single.contains(&"a"));
+    }
+}
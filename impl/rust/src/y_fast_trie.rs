@@ -0,0 +1,312 @@
+//! Y-fast trie: an X-fast trie over bucket representatives.
+
+use crate::{HashMap, XFastTrie};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::ops::Bound;
+
+/// Buckets split above this many keys, twice the key width.
+const MAX_BUCKET: usize = 128;
+/// Buckets merge with a neighbor below this many keys, half the key width.
+const MIN_BUCKET: usize = 32;
+
+/// A set of `u64` keys answering successor and predecessor queries in
+/// O(log w) expected time, for w = 64 key bits, in O(n) space.
+///
+/// Keys are cut into buckets of between 32 and 128 consecutive keys, each a
+/// balanced search tree, and only one representative per bucket goes into
+/// an [`XFastTrie`]. A bucket holds the keys from its representative up to
+/// the next one, so a query finds its bucket by a predecessor search in the
+/// trie, then its answer inside that bucket or at the edge of a neighboring
+/// one. The trie then holds O(n / w) keys in O(n) space, and buckets of
+/// O(w) keys take O(log w) to search. A bucket that overflows is split at
+/// its median, which inserts one representative, and one that runs low
+/// merges with a neighbor, so the O(w) cost of updating the trie is paid
+/// once every Θ(w) updates, for O(log w) amortized expected insert and
+/// remove. Buckets are std `BTreeSet`s; representatives map to them through
+/// a [`HashMap`].
+#[derive(Clone, Default)]
+pub struct YFastTrie {
+    /// Each bucket's lower bound, which is not necessarily a key.
+    reps: XFastTrie,
+    buckets: HashMap<u64, BTreeSet<u64>>,
+    len: usize,
+}
+
+impl YFastTrie {
+    /// Create a new empty YFastTrie.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of keys in the set.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the set contains no keys.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of buckets.
+    pub fn bucket_count(&self) -> usize {
+        self.reps.len()
+    }
+
+    /// Clear all keys from the set.
+    pub fn clear(&mut self) {
+        self.reps.clear();
+        self.buckets.clear();
+        self.len = 0;
+    }
+
+    /// The representative of the bucket `key` belongs in, if any bucket
+    /// starts at or below it.
+    fn rep_of(&self, key: u64) -> Option<u64> {
+        if self.reps.contains(key) {
+            Some(key)
+        } else {
+            self.reps.predecessor(key)
+        }
+    }
+
+    fn bucket(&self, rep: u64) -> &BTreeSet<u64> {
+        self.buckets.get(&rep).expect("every rep has a bucket")
+    }
+
+    fn bucket_mut(&mut self, rep: u64) -> &mut BTreeSet<u64> {
+        self.buckets.get_mut(&rep).expect("every rep has a bucket")
+    }
+
+    /// Check if `key` is in the set.
+    pub fn contains(&self, key: u64) -> bool {
+        self.rep_of(key)
+            .is_some_and(|rep| self.bucket(rep).contains(&key))
+    }
+
+    /// Returns the smallest key.
+    pub fn min(&self) -> Option<u64> {
+        let rep = self.reps.min()?;
+        self.bucket(rep).first().copied()
+    }
+
+    /// Returns the largest key.
+    pub fn max(&self) -> Option<u64> {
+        let rep = self.reps.max()?;
+        self.bucket(rep).last().copied()
+    }
+
+    /// Returns the smallest key greater than `key`.
+    pub fn successor(&self, key: u64) -> Option<u64> {
+        let next_rep = match self.rep_of(key) {
+            Some(rep) => {
+                let after = (Bound::Excluded(key), Bound::Unbounded);
+                if let Some(&next) = self.bucket(rep).range(after).next() {
+                    return Some(next);
+                }
+                self.reps.successor(rep)?
+            }
+            // Every bucket starts above `key`.
+            None => self.reps.min()?,
+        };
+        self.bucket(next_rep).first().copied()
+    }
+
+    /// Returns the largest key less than `key`.
+    pub fn predecessor(&self, key: u64) -> Option<u64> {
+        // With no bucket at or below `key`, every key is greater.
+        let rep = self.rep_of(key)?;
+        if let Some(&prev) = self.bucket(rep).range(..key).next_back() {
+            return Some(prev);
+        }
+        let prev_rep = self.reps.predecessor(rep)?;
+        self.bucket(prev_rep).last().copied()
+    }
+
+    /// Insert a key.
+    ///
+    /// Returns true if the key was not already present.
+    pub fn insert(&mut self, key: u64) -> bool {
+        let rep = match self.rep_of(key) {
+            Some(rep) => rep,
+            None => {
+                // Below every bucket: lower the first bucket's bound, or
+                // start the first bucket.
+                let bucket = match self.reps.min() {
+                    Some(first) => {
+                        self.reps.remove(first);
+                        self.buckets.remove(&first).expect("every rep has a bucket")
+                    }
+                    None => BTreeSet::new(),
+                };
+                self.reps.insert(key);
+                self.buckets.insert(key, bucket);
+                key
+            }
+        };
+        if !self.bucket_mut(rep).insert(key) {
+            return false;
+        }
+        self.len += 1;
+        self.split_if_full(rep);
+        true
+    }
+
+    /// Split the bucket at `rep` at its median if it is over the maximum.
+    fn split_if_full(&mut self, rep: u64) {
+        let bucket = self.bucket_mut(rep);
+        if bucket.len() <= MAX_BUCKET {
+            return;
+        }
+        let median = *bucket.iter().nth(bucket.len() / 2).expect("full bucket");
+        let upper = bucket.split_off(&median);
+        self.reps.insert(median);
+        self.buckets.insert(median, upper);
+    }
+
+    /// Remove a key.
+    ///
+    /// Returns true if the key was present.
+    pub fn remove(&mut self, key: u64) -> bool {
+        let Some(rep) = self.rep_of(key) else {
+            return false;
+        };
+        if !self.bucket_mut(rep).remove(&key) {
+            return false;
+        }
+        self.len -= 1;
+        if self.bucket(rep).len() >= MIN_BUCKET {
+            return true;
+        }
+        // Merge into the previous bucket, or take in the next one.
+        let (into, from) = match (self.reps.predecessor(rep), self.reps.successor(rep)) {
+            (Some(prev), _) => (prev, rep),
+            (None, Some(next)) => (rep, next),
+            (None, None) => {
+                if self.bucket(rep).is_empty() {
+                    self.reps.remove(rep);
+                    self.buckets.remove(&rep);
+                }
+                return true;
+            }
+        };
+        let mut moved = self.buckets.remove(&from).expect("every rep has a bucket");
+        self.reps.remove(from);
+        self.bucket_mut(into).append(&mut moved);
+        self.split_if_full(into);
+        true
+    }
+
+    /// Iterate over the keys in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = u64> + '_ {
+        self.reps
+            .iter()
+            .flat_map(|rep| self.bucket(rep).iter().copied())
+    }
+
+    /// Checks that every bucket but a lone one holds between the minimum and
+    /// maximum number of keys, that each bucket's keys lie between its
+    /// representative and the next, and that the key count matches `len`.
+    pub fn debug_validate(&self) {
+        let reps: Vec<u64> = self.reps.iter().collect();
+        assert_eq!(reps.len(), self.buckets.len(), "reps and buckets differ");
+        let mut count = 0;
+        for (i, &rep) in reps.iter().enumerate() {
+            let bucket = self.bucket(rep);
+            assert!(bucket.len() <= MAX_BUCKET, "bucket has too many keys");
+            assert!(
+                reps.len() == 1 || bucket.len() >= MIN_BUCKET,
+                "bucket has too few keys"
+            );
+            assert!(!bucket.is_empty(), "empty bucket");
+            assert!(bucket.first() >= Some(&rep), "key below its bucket");
+            if let Some(&next) = reps.get(i + 1) {
+                assert!(bucket.last() < Some(&next), "key past its bucket");
+            }
+            count += bucket.len();
+        }
+        assert_eq!(count, self.len, "key count does not match len");
+    }
+}
+
+impl Extend<u64> for YFastTrie {
+    fn extend<I: IntoIterator<Item = u64>>(&mut self, iter: I) {
+        for key in iter {
+            self.insert(key);
+        }
+    }
+}
+
+impl FromIterator<u64> for YFastTrie {
+    fn from_iter<I: IntoIterator<Item = u64>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl fmt::Debug for YFastTrie {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate(set: &YFastTrie) {
+        if cfg!(debug_assertions) {
+            set.debug_validate();
+        }
+    }
+
+    #[test]
+    fn test_successor_and_predecessor() {
+        let set: YFastTrie = (1..=1000).map(|i| i * 10).collect();
+        validate(&set);
+        assert!(set.bucket_count() > 1);
+        assert_eq!(set.successor(0), Some(10));
+        assert_eq!(set.successor(10), Some(20));
+        assert_eq!(set.successor(5555), Some(5560));
+        assert_eq!(set.successor(10_000), None);
+        assert_eq!(set.predecessor(10), None);
+        assert_eq!(set.predecessor(5555), Some(5550));
+        assert_eq!(set.predecessor(u64::MAX), Some(10_000));
+        assert!(set.contains(640) && !set.contains(641));
+    }
+
+    #[test]
+    fn test_insert_below_first_bucket() {
+        let mut set: YFastTrie = (100..300).collect();
+        assert!(set.insert(5));
+        assert!(!set.insert(5));
+        validate(&set);
+        assert_eq!(set.min(), Some(5));
+        assert_eq!(set.successor(5), Some(100));
+        assert_eq!(set.predecessor(100), Some(5));
+    }
+
+    #[test]
+    fn test_remove_merges_buckets() {
+        let mut set: YFastTrie = (0..1000).collect();
+        let buckets = set.bucket_count();
+        for key in (0..1000).filter(|key| key % 8 != 0) {
+            assert!(set.remove(key));
+            validate(&set);
+        }
+        assert!(!set.remove(1));
+        assert!(set.bucket_count() < buckets);
+        assert_eq!(
+            set.iter().collect::<Vec<_>>(),
+            (0..1000).step_by(8).collect::<Vec<_>>()
+        );
+        for key in (0..1000).step_by(8) {
+            set.remove(key);
+        }
+        assert!(set.is_empty());
+        assert_eq!(set.bucket_count(), 0);
+        assert_eq!(set.max(), None);
+    }
+}
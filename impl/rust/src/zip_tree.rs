@@ -0,0 +1,396 @@
+//! Zip tree (rank-randomized binary search tree) ordered map.
+
+use crate::btree::{before_start, check_range, past_end};
+use crate::skip_list::xorshift64;
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::RangeBounds;
+
+const DEFAULT_SEED: u64 = 0x21B7;
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+#[derive(Debug, Clone)]
+struct Node<K, V> {
+    key: K,
+    value: V,
+    /// Random geometric rank; every node outranks its left child and at
+    /// least ties its right child.
+    rank: u32,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+impl<K: Ord, V> Node<K, V> {
+    /// Whether `self` belongs above a node with `rank` and `key`: a higher
+    /// rank wins, and between equal ranks the smaller key.
+    fn outranks(&self, rank: u32, key: &K) -> bool {
+        self.rank > rank || (self.rank == rank && self.key < *key)
+    }
+}
+
+/// Cut a subtree along the search path for `key`, which it must not
+/// contain, into the keys below it and the keys above it.
+fn unzip<K: Ord, V>(link: Link<K, V>, key: &K) -> (Link<K, V>, Link<K, V>) {
+    let Some(mut node) = link else {
+        return (None, None);
+    };
+    if node.key < *key {
+        let (middle, right) = unzip(node.right.take(), key);
+        node.right = middle;
+        (Some(node), right)
+    } else {
+        let (left, middle) = unzip(node.left.take(), key);
+        node.left = middle;
+        (left, Some(node))
+    }
+}
+
+/// Join two subtrees where every key in `left` is below every key in
+/// `right`, interleaving the right spine of one with the left spine of the
+/// other by rank. Ties go to `left`, whose keys are smaller.
+fn zip<K, V>(left: Link<K, V>, right: Link<K, V>) -> Link<K, V> {
+    match (left, right) {
+        (None, right) => right,
+        (left, None) => left,
+        (Some(mut left), Some(mut right)) => {
+            if left.rank >= right.rank {
+                left.right = zip(left.right.take(), Some(right));
+                Some(left)
+            } else {
+                right.left = zip(Some(left), right.left.take());
+                Some(right)
+            }
+        }
+    }
+}
+
+/// Insert `node`, whose key is not in the subtree, at the depth its rank
+/// gives it.
+fn insert<K: Ord, V>(link: Link<K, V>, mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    match link {
+        Some(mut top) if top.outranks(node.rank, &node.key) => {
+            if node.key < top.key {
+                top.left = Some(insert(top.left.take(), node));
+            } else {
+                top.right = Some(insert(top.right.take(), node));
+            }
+            top
+        }
+        rest => {
+            let (left, right) = unzip(rest, &node.key);
+            node.left = left;
+            node.right = right;
+            node
+        }
+    }
+}
+
+fn remove<K: Ord, V>(link: &mut Link<K, V>, key: &K) -> Option<V> {
+    let node = link.as_mut()?;
+    match key.cmp(&node.key) {
+        Ordering::Less => remove(&mut node.left, key),
+        Ordering::Greater => remove(&mut node.right, key),
+        Ordering::Equal => {
+            let mut node = link.take().expect("matched above");
+            *link = zip(node.left.take(), node.right.take());
+            Some(node.value)
+        }
+    }
+}
+
+/// An ordered map stored in a zip tree.
+///
+/// A zip tree is a binary search tree on keys that is also a heap on
+/// random ranks, like a treap, but each rank is a geometric random
+/// variable: the number of coin flips that come up heads before the first
+/// tails. Those are exactly the tower heights of a skip list, and the tree
+/// is the skip list's search structure laid out as a binary tree, with
+/// expected depth about 1.5 log2 n. Ranks tie often, so ties are broken by
+/// key, smaller above. Insertion finds the node's place on the search path
+/// by rank and *unzips* the subtree there into the parts below and above
+/// the new key; removal *zips* the two subtrees of the removed node back
+/// together. Neither rotates, and a rank needs only O(log log n) bits to
+/// store where a treap priority needs a full word. Ranks come from a seeded
+/// generator, so a given sequence of operations always builds the same
+/// tree.
+#[derive(Clone)]
+pub struct ZipTreeMap<K, V> {
+    root: Link<K, V>,
+    len: usize,
+    rng: u64,
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for ZipTreeMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V> Default for ZipTreeMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> ZipTreeMap<K, V> {
+    /// Create a new empty ZipTreeMap with a fixed seed.
+    pub fn new() -> Self {
+        Self::with_seed(DEFAULT_SEED)
+    }
+
+    /// Create a new empty ZipTreeMap drawing node ranks from `seed`.
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            root: None,
+            len: 0,
+            // xorshift gets stuck at zero.
+            rng: seed | 1,
+        }
+    }
+
+    /// Returns the number of elements in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the map contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Clear all entries from the map.
+    pub fn clear(&mut self) {
+        self.root = None;
+        self.len = 0;
+    }
+
+    /// Returns the number of levels in the tree.
+    ///
+    /// Walks every node, so takes O(n).
+    pub fn depth(&self) -> usize {
+        fn depth<K, V>(link: &Link<K, V>) -> usize {
+            link.as_ref()
+                .map_or(0, |node| 1 + depth(&node.left).max(depth(&node.right)))
+        }
+
+        depth(&self.root)
+    }
+
+    /// Draw a rank: the number of heads before the first tails.
+    fn random_rank(&mut self) -> u32 {
+        xorshift64(&mut self.rng).trailing_ones()
+    }
+}
+
+impl<K: Ord, V> ZipTreeMap<K, V> {
+    /// Insert a key-value pair into the map.
+    ///
+    /// Returns the previous value if the key existed.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(old) = self.get_mut(&key) {
+            return Some(std::mem::replace(old, value));
+        }
+        let node = Box::new(Node {
+            key,
+            value,
+            rank: self.random_rank(),
+            left: None,
+            right: None,
+        });
+        self.root = Some(insert(self.root.take(), node));
+        self.len += 1;
+        None
+    }
+
+    /// Get a reference to the value associated with the key.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let mut link = &self.root;
+        while let Some(node) = link {
+            link = match key.cmp(&node.key) {
+                Ordering::Less => &node.left,
+                Ordering::Greater => &node.right,
+                Ordering::Equal => return Some(&node.value),
+            };
+        }
+        None
+    }
+
+    /// Get a mutable reference to the value associated with the key.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let mut link = &mut self.root;
+        while let Some(node) = link {
+            link = match key.cmp(&node.key) {
+                Ordering::Less => &mut node.left,
+                Ordering::Greater => &mut node.right,
+                Ordering::Equal => return Some(&mut node.value),
+            };
+        }
+        None
+    }
+
+    /// Check if the map contains the given key.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Remove a key-value pair from the map.
+    ///
+    /// Returns the removed value if the key existed.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let removed = remove(&mut self.root, key);
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    /// Iterate over all key-value pairs in key order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.range(..)
+    }
+
+    /// Iterate over the key-value pairs whose keys fall in `range`, in key
+    /// order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range starts after it ends, or starts and ends at the
+    /// same excluded key.
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Iter<'_, K, V> {
+        check_range(&range);
+        let mut iter = Iter {
+            stack: Vec::new(),
+            end: None,
+        };
+        let mut link = &self.root;
+        while let Some(node) = link {
+            if before_start(&node.key, range.start_bound()) {
+                link = &node.right;
+            } else {
+                iter.stack.push(node);
+                link = &node.left;
+            }
+        }
+        let mut link = &self.root;
+        while let Some(node) = link {
+            if past_end(&node.key, range.end_bound()) {
+                iter.end = Some(node);
+                link = &node.left;
+            } else {
+                link = &node.right;
+            }
+        }
+        iter
+    }
+
+    /// Check the zip tree invariants, panicking on the first violation.
+    ///
+    /// Checks that keys are strictly increasing in order, that every node
+    /// outranks its left child and at least ties its right child, and that
+    /// the entry count matches `len`.
+    pub fn debug_validate(&self) {
+        fn walk<K: Ord, V>(link: &Link<K, V>, lower: Option<&K>, upper: Option<&K>) -> usize {
+            let Some(node) = link else {
+                return 0;
+            };
+            assert!(
+                lower.map_or(true, |lower| node.key > *lower),
+                "keys out of order"
+            );
+            assert!(
+                upper.map_or(true, |upper| node.key < *upper),
+                "keys out of order"
+            );
+            if let Some(left) = &node.left {
+                assert!(left.rank < node.rank, "left child ties or outranks parent");
+            }
+            if let Some(right) = &node.right {
+                assert!(right.rank <= node.rank, "right child outranks parent");
+            }
+            1 + walk(&node.left, lower, Some(&node.key)) + walk(&node.right, Some(&node.key), upper)
+        }
+
+        let count = walk(&self.root, None, None);
+        assert_eq!(count, self.len, "len does not match entry count");
+    }
+}
+
+/// An iterator over a key range of a [`ZipTreeMap`], in key order.
+pub struct Iter<'a, K, V> {
+    /// Nodes still to visit whose left subtrees are already handled; the
+    /// next entry is on top.
+    stack: Vec<&'a Node<K, V>>,
+    /// The first node past the range, if there is one.
+    end: Option<&'a Node<K, V>>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        if self.end.is_some_and(|end| std::ptr::eq(end, node)) {
+            self.stack.clear();
+            return None;
+        }
+        let mut link = &node.right;
+        while let Some(child) = link {
+            self.stack.push(child);
+            link = &child.left;
+        }
+        Some((&node.key, &node.value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sorted_inserts_stay_shallow() {
+        let mut map = ZipTreeMap::new();
+        for i in 0..4096 {
+            assert_eq!(map.insert(i, i), None);
+        }
+        map.debug_validate();
+        // Expected depth is about 1.5 log2 n = 18; a plain BST would be a
+        // 4096-level list.
+        assert!(map.depth() < 40, "depth {}", map.depth());
+        assert!(map.iter().map(|(k, _)| *k).eq(0..4096));
+    }
+
+    #[test]
+    fn test_remove_zips_subtrees() {
+        let mut map = ZipTreeMap::new();
+        for i in 0..500 {
+            map.insert(i, i * 10);
+        }
+        assert_eq!(map.insert(7, 0), Some(70));
+        for i in (0..500).filter(|i| i % 3 != 0) {
+            assert!(map.remove(&i).is_some());
+            map.debug_validate();
+        }
+        assert_eq!(map.remove(&1), None);
+        assert_eq!(map.len(), 167);
+        assert_eq!(map.get(&9), Some(&90));
+        assert!(map.range(10..=20).map(|(k, _)| *k).eq([12, 15, 18]));
+    }
+
+    #[test]
+    fn test_same_seed_builds_same_tree() {
+        let build = |seed| {
+            let mut map = ZipTreeMap::with_seed(seed);
+            for i in 0..1000 {
+                map.insert(i, ());
+            }
+            for i in (0..1000).step_by(7) {
+                map.remove(&i);
+            }
+            map.debug_validate();
+            map.root.as_ref().map(|node| (node.key, node.rank))
+        };
+        assert_eq!(build(1), build(1));
+        assert_ne!(build(1), build(2));
+    }
+}
@@ -0,0 +1,119 @@
+//! Oracle tests running the big integers against num-bigint's
+//!
+//! num-bigint is a dev-dependency only; nothing outside the tests uses it.
+
+use dsa_lab::{BigInt, BigUint};
+use num_bigint::Sign;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+type RefUint = num_bigint::BigUint;
+type RefInt = num_bigint::BigInt;
+
+/// A random number of up to `max_limbs` limbs. Limbs are often all zeros
+/// or all ones, where carries and borrows run furthest.
+fn random_uint(rng: &mut StdRng, max_limbs: usize) -> BigUint {
+    let len = rng.gen_range(0..=max_limbs);
+    let limbs = (0..len)
+        .map(|_| match rng.gen_range(0..4) {
+            0 => 0,
+            1 => u32::MAX,
+            _ => rng.gen(),
+        })
+        .collect();
+    BigUint::from_limbs(limbs)
+}
+
+fn random_int(rng: &mut StdRng, max_limbs: usize) -> BigInt {
+    BigInt::from_parts(rng.gen(), random_uint(rng, max_limbs))
+}
+
+fn to_ref(n: &BigUint) -> RefUint {
+    RefUint::from_bytes_le(&n.to_bytes_le())
+}
+
+fn to_ref_int(n: &BigInt) -> RefInt {
+    let sign = if n.is_negative() {
+        Sign::Minus
+    } else {
+        Sign::Plus
+    };
+    RefInt::from_biguint(sign, to_ref(n.magnitude()))
+}
+
+fn assert_same(actual: &BigUint, expected: &RefUint) {
+    assert_eq!(to_ref(actual), *expected);
+}
+
+fn assert_same_int(actual: &BigInt, expected: &RefInt) {
+    assert_eq!(to_ref_int(actual), *expected);
+}
+
+#[test]
+fn test_oracle_unsigned() {
+    let mut rng = StdRng::seed_from_u64(170);
+    for round in 0..2_000 {
+        let max_limbs = [2, 8, 40, 150][round % 4];
+        let a = random_uint(&mut rng, max_limbs);
+        let b = random_uint(&mut rng, max_limbs);
+        let (ra, rb) = (to_ref(&a), to_ref(&b));
+
+        assert_eq!(a.cmp(&b), ra.cmp(&rb));
+        assert_same(&(&a + &b), &(&ra + &rb));
+        match a.checked_sub(&b) {
+            Some(diff) => assert_same(&diff, &(&ra - &rb)),
+            None => assert!(ra < rb),
+        }
+        let product = &ra * &rb;
+        assert_same(&(&a * &b), &product);
+        assert_same(&a.mul_schoolbook(&b), &product);
+        assert_same(&a.mul_karatsuba(&b, rng.gen_range(0..10)), &product);
+
+        match a.div_rem(&b) {
+            Some((q, r)) => {
+                assert_same(&q, &(&ra / &rb));
+                assert_same(&r, &(&ra % &rb));
+            }
+            None => assert!(b.is_zero()),
+        }
+        // Exact division, where the remainder must come out zero.
+        if !b.is_zero() {
+            let (q, r) = (&a * &b).div_rem(&b).unwrap();
+            assert_eq!((q, r.is_zero()), (a.clone(), true));
+        }
+
+        let decimal = a.to_string();
+        assert_eq!(decimal, ra.to_string());
+        assert_eq!(decimal.parse::<BigUint>(), Ok(a.clone()));
+        assert_eq!(a.bits(), ra.bits());
+    }
+}
+
+#[test]
+fn test_oracle_signed() {
+    let mut rng = StdRng::seed_from_u64(171);
+    for round in 0..2_000 {
+        let max_limbs = [1, 3, 20, 80][round % 4];
+        let a = random_int(&mut rng, max_limbs);
+        let b = random_int(&mut rng, max_limbs);
+        let (ra, rb) = (to_ref_int(&a), to_ref_int(&b));
+
+        assert_eq!(a.cmp(&b), ra.cmp(&rb));
+        assert_same_int(&(&a + &b), &(&ra + &rb));
+        assert_same_int(&(&a - &b), &(&ra - &rb));
+        assert_same_int(&(&a * &b), &(&ra * &rb));
+        assert_same_int(&-&a, &-&ra);
+        // num-bigint's / and % truncate toward zero too.
+        match a.div_rem(&b) {
+            Some((q, r)) => {
+                assert_same_int(&q, &(&ra / &rb));
+                assert_same_int(&r, &(&ra % &rb));
+            }
+            None => assert!(b.is_zero()),
+        }
+
+        let decimal = a.to_string();
+        assert_eq!(decimal, ra.to_string());
+        assert_eq!(decimal.parse::<BigInt>(), Ok(a.clone()));
+    }
+}
@@ -0,0 +1,160 @@
+//! Round-trip tests for the Huffman, LZ77 and LZW compressors, and a check
+//! of Huffman code lengths against the optimal cost computed with std's
+//! heap
+
+use dsa_lab::huffman::{compress, decompress};
+use dsa_lab::lz77::{self, Token, MAX_MATCH, MIN_MATCH, WINDOW};
+use dsa_lab::{lzw, HuffmanCode};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// Random bytes of one of several shapes: uniform, from a small alphabet,
+/// geometrically skewed, or long runs of one byte.
+fn random_bytes(rng: &mut StdRng, shape: usize, n: usize) -> Vec<u8> {
+    let alphabet = rng.gen_range(1..=256);
+    let mut bytes = Vec::with_capacity(n);
+    while bytes.len() < n {
+        match shape {
+            0 => bytes.push(rng.gen()),
+            1 => bytes.push(rng.gen_range(0..alphabet) as u8),
+            2 => bytes.push(rng.gen_range(0..8u32).min(rng.gen_range(0..8)) as u8),
+            _ => {
+                let byte: u8 = rng.gen();
+                let run = rng.gen_range(1..50).min(n - bytes.len());
+                bytes.extend(std::iter::repeat(byte).take(run));
+            }
+        }
+    }
+    bytes
+}
+
+#[test]
+fn test_oracle_huffman_round_trip() {
+    let mut rng = StdRng::seed_from_u64(120);
+    for round in 0..400 {
+        let n = rng.gen_range(0..2_000);
+        let data = random_bytes(&mut rng, round % 4, n);
+        let compressed = compress(&data);
+        assert_eq!(decompress(&compressed).as_deref(), Some(&data[..]));
+        // A truncated stream fails rather than decoding garbage.
+        if n > 0 {
+            let cut = rng.gen_range(264..compressed.len());
+            assert_eq!(decompress(&compressed[..cut]), None);
+        }
+    }
+}
+
+/// The least total weighted code length of any prefix code over
+/// `weights`: the sum of the weights of every merge Huffman makes.
+fn optimal_cost(weights: &[u64]) -> u64 {
+    if weights.len() == 1 {
+        return weights[0];
+    }
+    let mut heap: BinaryHeap<Reverse<u64>> = weights.iter().map(|&w| Reverse(w)).collect();
+    let mut cost = 0;
+    while heap.len() > 1 {
+        let Reverse(a) = heap.pop().unwrap();
+        let Reverse(b) = heap.pop().unwrap();
+        cost += a + b;
+        heap.push(Reverse(a + b));
+    }
+    cost
+}
+
+#[test]
+fn test_oracle_huffman_optimal_and_canonical() {
+    let mut rng = StdRng::seed_from_u64(121);
+    for _ in 0..500 {
+        let n = rng.gen_range(1..300);
+        let frequencies: Vec<(u32, u64)> = (0..n)
+            .map(|symbol| {
+                let bits = rng.gen_range(1..20);
+                (symbol * 7, rng.gen_range(1..1u64 << bits))
+            })
+            .collect();
+        let code = HuffmanCode::from_frequencies(frequencies.iter().copied());
+        assert_eq!(code.len(), n as usize);
+
+        let weights: Vec<u64> = frequencies.iter().map(|&(_, f)| f).collect();
+        let cost: u64 = frequencies
+            .iter()
+            .map(|(symbol, f)| f * u64::from(code.codeword(symbol).unwrap().len))
+            .sum();
+        assert_eq!(cost, optimal_cost(&weights));
+
+        // Canonical: ordered by length, then symbol, and complete once
+        // there are two symbols.
+        let lengths: Vec<(u32, u8)> = code.lengths().map(|(&s, len)| (s, len)).collect();
+        assert!(lengths
+            .windows(2)
+            .all(|w| (w[0].1, w[0].0) < (w[1].1, w[1].0)));
+        let kraft: f64 = lengths
+            .iter()
+            .map(|&(_, len)| 0.5f64.powi(len.into()))
+            .sum();
+        assert!(n == 1 || kraft == 1.0);
+
+        let rebuilt = HuffmanCode::from_lengths(lengths).unwrap();
+        let symbols: Vec<u32> = (0..200).map(|_| rng.gen_range(0..n) * 7).collect();
+        let bits = code.encode(&symbols);
+        assert_eq!(rebuilt.encode(&symbols), bits);
+        assert_eq!(rebuilt.decode(&bits, symbols.len()), Some(symbols));
+    }
+}
+
+#[test]
+fn test_oracle_lz77_round_trip() {
+    let mut rng = StdRng::seed_from_u64(122);
+    for round in 0..400 {
+        let n = rng.gen_range(0..3_000);
+        let data = random_bytes(&mut rng, round % 4, n);
+        // Replaying the tokens by hand rebuilds the data, and every match
+        // is within the limits the format can hold.
+        let mut replayed: Vec<u8> = Vec::new();
+        for token in lz77::parse(&data) {
+            match token {
+                Token::Literal(byte) => replayed.push(byte),
+                Token::Match { offset, len } => {
+                    assert!((1..=WINDOW).contains(&offset));
+                    assert!((MIN_MATCH..=MAX_MATCH).contains(&len));
+                    let start = replayed.len() - offset;
+                    for k in start..start + len {
+                        replayed.push(replayed[k]);
+                    }
+                }
+            }
+        }
+        assert_eq!(replayed, data);
+        assert_eq!(lz77::decompress(&lz77::compress(&data)), Some(data));
+    }
+}
+
+#[test]
+fn test_oracle_lzw_round_trip() {
+    let mut rng = StdRng::seed_from_u64(123);
+    for round in 0..400 {
+        let n = rng.gen_range(0..3_000);
+        let data = random_bytes(&mut rng, round % 4, n);
+        assert_eq!(lzw::decode(&lzw::encode(&data)).as_ref(), Some(&data));
+        assert_eq!(lzw::decompress(&lzw::compress(&data)), Some(data));
+    }
+}
+
+#[test]
+fn test_oracle_large_inputs() {
+    // Long enough to slide past the LZ77 window and fill the LZW
+    // dictionary, with repeats far apart.
+    let mut rng = StdRng::seed_from_u64(124);
+    let mut data = random_bytes(&mut rng, 1, 200_000);
+    let copy = data[1_000..5_000].to_vec();
+    data.extend_from_slice(&copy);
+    data.extend(random_bytes(&mut rng, 0, 100_000));
+    assert_eq!(
+        lz77::decompress(&lz77::compress(&data)).as_ref(),
+        Some(&data)
+    );
+    assert_eq!(lzw::decompress(&lzw::compress(&data)).as_ref(), Some(&data));
+    assert_eq!(decompress(&compress(&data)), Some(data));
+}
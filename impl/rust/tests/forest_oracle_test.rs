@@ -0,0 +1,218 @@
+//! Oracle tests comparing the dynamic forest structures against
+//! recomputation on an explicit parent-pointer forest
+
+use dsa_lab::{EulerTourTree, LinkCutTree, Monoid, Sum};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Composition of affine maps `x -> a * x + b` modulo a prime, applied left
+/// to right. Not commutative, so it catches paths combined in the wrong
+/// direction.
+struct Affine;
+
+const P: u64 = 1_000_000_007;
+
+impl Monoid for Affine {
+    type Value = (u64, u64);
+
+    fn identity() -> (u64, u64) {
+        (1, 0)
+    }
+
+    fn combine(&(a1, b1): &(u64, u64), &(a2, b2): &(u64, u64)) -> (u64, u64) {
+        (a1 * a2 % P, (b1 * a2 + b2) % P)
+    }
+}
+
+/// A rooted forest stored as parent pointers, with every operation done by
+/// walking to the root.
+struct Forest<M: Monoid> {
+    parent: Vec<Option<usize>>,
+    values: Vec<M::Value>,
+}
+
+impl<M: Monoid> Forest<M> {
+    /// The vertices from `v` up to its root, `v` first.
+    fn ancestors(&self, mut v: usize) -> Vec<usize> {
+        let mut path = vec![v];
+        while let Some(parent) = self.parent[v] {
+            path.push(parent);
+            v = parent;
+        }
+        path
+    }
+
+    fn find_root(&self, v: usize) -> usize {
+        *self.ancestors(v).last().expect("contains v")
+    }
+
+    fn connected(&self, u: usize, v: usize) -> bool {
+        self.find_root(u) == self.find_root(v)
+    }
+
+    /// Reroot by reversing the parent pointers from `v` to the root.
+    fn evert(&mut self, v: usize) {
+        let path = self.ancestors(v);
+        self.parent[v] = None;
+        for pair in path.windows(2) {
+            self.parent[pair[1]] = Some(pair[0]);
+        }
+    }
+
+    fn link(&mut self, u: usize, v: usize) -> bool {
+        if self.connected(u, v) {
+            return false;
+        }
+        self.evert(u);
+        self.parent[u] = Some(v);
+        true
+    }
+
+    fn cut(&mut self, u: usize, v: usize) -> bool {
+        self.evert(u);
+        if self.parent[v] != Some(u) {
+            return false;
+        }
+        self.parent[v] = None;
+        true
+    }
+
+    fn path(&mut self, u: usize, v: usize) -> Option<M::Value> {
+        if !self.connected(u, v) {
+            return None;
+        }
+        self.evert(u);
+        // With u the root, the ancestors of v are the path, reversed.
+        let path = self.ancestors(v);
+        Some(
+            path.iter()
+                .rev()
+                .fold(M::identity(), |acc, &w| M::combine(&acc, &self.values[w])),
+        )
+    }
+
+    fn lca(&self, u: usize, v: usize) -> Option<usize> {
+        let above_u = self.ancestors(u);
+        self.ancestors(v).into_iter().find(|w| above_u.contains(w))
+    }
+}
+
+/// Interleave random links, cuts, value updates and queries on a link-cut
+/// tree and an explicit forest, linking more often than cutting early on so
+/// that trees grow long paths before they are broken up.
+fn link_cut_oracle<M, F>(seed: u64, mut random_value: F)
+where
+    M: Monoid,
+    M::Value: PartialEq + std::fmt::Debug,
+    F: FnMut(&mut StdRng) -> M::Value,
+{
+    let mut rng = StdRng::seed_from_u64(seed);
+    for n in [1, 2, 10, 60, 200] {
+        let values: Vec<M::Value> = (0..n).map(|_| random_value(&mut rng)).collect();
+        let mut tree: LinkCutTree<M> = LinkCutTree::from_vec(values.clone());
+        let mut oracle: Forest<M> = Forest {
+            parent: vec![None; n],
+            values,
+        };
+        for step in 0..4000 {
+            let u = rng.gen_range(0..n);
+            let v = rng.gen_range(0..n);
+            let link_bias = if step < 2000 { 0.4 } else { 0.15 };
+            match rng.gen_range(0.0..1.0) {
+                x if x < link_bias => assert_eq!(tree.link(u, v), oracle.link(u, v)),
+                x if x < 0.5 => {
+                    // Mostly cut real edges, which random pairs rarely are.
+                    let v = oracle.parent[u].filter(|_| rng.gen_bool(0.8)).unwrap_or(v);
+                    assert_eq!(tree.cut(u, v), oracle.cut(u, v), "cut {} {}", u, v);
+                }
+                x if x < 0.6 => {
+                    let value = random_value(&mut rng);
+                    oracle.values[u] = value.clone();
+                    tree.set(u, value);
+                }
+                x if x < 0.75 => assert_eq!(tree.path(u, v), oracle.path(u, v)),
+                x if x < 0.85 => assert_eq!(tree.find_root(u), oracle.find_root(u)),
+                x if x < 0.92 => assert_eq!(tree.connected(u, v), oracle.connected(u, v)),
+                _ => assert_eq!(tree.lca(u, v), oracle.lca(u, v), "lca {} {}", u, v),
+            }
+        }
+        for v in 0..n {
+            assert_eq!(tree.find_root(v), oracle.find_root(v));
+            assert_eq!(tree.get(v), &oracle.values[v]);
+        }
+    }
+}
+
+#[test]
+fn test_oracle_link_cut_sum() {
+    link_cut_oracle::<Sum<i64>, _>(42, |rng| rng.gen_range(-1000..1000));
+}
+
+#[test]
+fn test_oracle_link_cut_non_commutative() {
+    link_cut_oracle::<Affine, _>(43, |rng| (rng.gen_range(0..P), rng.gen_range(0..P)));
+}
+
+#[test]
+fn test_oracle_link_cut_long_path() {
+    // Everting the end of one long path and reading it back end to end
+    // exercises deep splay trees and lazy reversal.
+    let n = 2000;
+    let mut rng = StdRng::seed_from_u64(44);
+    let values: Vec<(u64, u64)> = (0..n)
+        .map(|_| (rng.gen_range(0..P), rng.gen_range(0..P)))
+        .collect();
+    let mut tree: LinkCutTree<Affine> = LinkCutTree::from_vec(values.clone());
+    for v in 1..n {
+        assert!(tree.link(v, v - 1));
+    }
+    let forward = values.iter().fold(Affine::identity(), |acc, value| {
+        Affine::combine(&acc, value)
+    });
+    let backward = values.iter().rev().fold(Affine::identity(), |acc, value| {
+        Affine::combine(&acc, value)
+    });
+    for _ in 0..50 {
+        assert_eq!(tree.path(0, n - 1), Some(forward));
+        assert_eq!(tree.path(n - 1, 0), Some(backward));
+        let v = rng.gen_range(0..n);
+        tree.evert(v);
+        assert_eq!(tree.find_root(0), v);
+    }
+}
+
+#[test]
+fn test_oracle_euler_tour() {
+    let mut rng = StdRng::seed_from_u64(45);
+    for n in [1, 2, 10, 60, 200] {
+        let mut tree = EulerTourTree::new(n);
+        let mut oracle: Forest<Sum<i64>> = Forest {
+            parent: vec![None; n],
+            values: vec![0; n],
+        };
+        for step in 0..4000 {
+            let u = rng.gen_range(0..n);
+            let v = rng.gen_range(0..n);
+            let link_bias = if step < 2000 { 0.4 } else { 0.15 };
+            match rng.gen_range(0.0..1.0) {
+                x if x < link_bias => assert_eq!(tree.link(u, v), oracle.link(u, v)),
+                x if x < 0.5 => {
+                    let v = oracle.parent[u].filter(|_| rng.gen_bool(0.8)).unwrap_or(v);
+                    assert_eq!(tree.cut(u, v), oracle.cut(u, v), "cut {} {}", u, v);
+                }
+                x if x < 0.8 => assert_eq!(tree.connected(u, v), oracle.connected(u, v)),
+                _ => {
+                    let root = oracle.find_root(u);
+                    let size = (0..n).filter(|&w| oracle.find_root(w) == root).count();
+                    assert_eq!(tree.tree_size(u), size);
+                }
+            }
+            if step % 500 == 0 {
+                tree.debug_validate();
+            }
+        }
+        let edges = (0..n).filter(|&v| oracle.parent[v].is_some()).count();
+        assert_eq!(tree.edge_count(), edges);
+        tree.debug_validate();
+    }
+}
@@ -0,0 +1,163 @@
+//! Oracle tests comparing the geometry algorithms against brute force
+
+use dsa_lab::geometry::{
+    closest_pair, convex_hull, cross, distance_squared, segments_intersect, Point,
+};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::BTreeSet;
+
+/// Random points with small integer coordinates, so every predicate is
+/// exact and ties, collinear runs and duplicates are common.
+fn random_points(rng: &mut StdRng, n: usize, range: i32) -> Vec<Point> {
+    (0..n)
+        .map(|_| {
+            [
+                rng.gen_range(-range..=range) as f64,
+                rng.gen_range(-range..=range) as f64,
+            ]
+        })
+        .collect()
+}
+
+/// Returns true if `c` lies on the closed segment `a b`.
+fn on_segment(a: Point, b: Point, c: Point) -> bool {
+    cross(a, b, c) == 0.0
+        && a[0].min(b[0]) <= c[0]
+        && c[0] <= a[0].max(b[0])
+        && a[1].min(b[1]) <= c[1]
+        && c[1] <= a[1].max(b[1])
+}
+
+/// The hull's edges as coordinate pairs, for comparing as sets.
+type Edge = ([i64; 2], [i64; 2]);
+
+fn edge(a: Point, b: Point) -> Edge {
+    ([a[0] as i64, a[1] as i64], [b[0] as i64, b[1] as i64])
+}
+
+#[test]
+fn test_oracle_convex_hull() {
+    let mut rng = StdRng::seed_from_u64(140);
+    for round in 0..500 {
+        let n = rng.gen_range(0..40);
+        let range = [2, 5, 100][round % 3];
+        let points = random_points(&mut rng, n, range);
+        let hull = convex_hull(&points);
+
+        // A directed edge a -> b is on the hull exactly when every point is
+        // strictly to its left or on the segment itself.
+        let mut expected = BTreeSet::new();
+        for &a in &points {
+            for &b in &points {
+                if a != b
+                    && points
+                        .iter()
+                        .all(|&c| cross(a, b, c) > 0.0 || on_segment(a, b, c))
+                {
+                    expected.insert(edge(a, b));
+                }
+            }
+        }
+        let actual: BTreeSet<Edge> = if hull.len() < 2 {
+            BTreeSet::new()
+        } else {
+            (0..hull.len())
+                .map(|i| edge(hull[i], hull[(i + 1) % hull.len()]))
+                .collect()
+        };
+        assert_eq!(actual, expected, "{:?}", points);
+        if hull.len() == 1 {
+            assert!(points.iter().all(|&p| p == hull[0]));
+        }
+    }
+}
+
+#[test]
+fn test_oracle_closest_pair() {
+    let mut rng = StdRng::seed_from_u64(141);
+    for round in 0..300 {
+        let n = rng.gen_range(0..300);
+        let range = [10, 1_000, 1_000_000][round % 3];
+        let points = random_points(&mut rng, n, range);
+        let mut best = None;
+        for i in 0..n {
+            for j in i + 1..n {
+                let d = distance_squared(points[i], points[j]);
+                if best.map_or(true, |b| d < b) {
+                    best = Some(d);
+                }
+            }
+        }
+        let found = closest_pair(&points);
+        assert_eq!(
+            found.map(|(i, j)| distance_squared(points[i], points[j])),
+            best
+        );
+        if let Some((i, j)) = found {
+            assert!(i < j);
+        }
+    }
+}
+
+/// Exact intersection test by solving for where the lines meet, in
+/// integers: a different route to the answer from the orientation tests.
+fn intersect_by_solving(p: [[i64; 2]; 2], q: [[i64; 2]; 2]) -> bool {
+    let sub = |a: [i64; 2], b: [i64; 2]| [a[0] - b[0], a[1] - b[1]];
+    let cross = |a: [i64; 2], b: [i64; 2]| a[0] * b[1] - a[1] * b[0];
+    let dot = |a: [i64; 2], b: [i64; 2]| a[0] * b[0] + a[1] * b[1];
+    let (r, s) = (sub(p[1], p[0]), sub(q[1], q[0]));
+    let w = sub(q[0], p[0]);
+    let denom = cross(r, s);
+    if denom != 0 {
+        // p[0] + t r = q[0] + u s, with t = t_num / denom, u = u_num / denom.
+        let (t_num, u_num) = (cross(w, s), cross(w, r));
+        let in_unit = |num: i64| {
+            if denom > 0 {
+                (0..=denom).contains(&num)
+            } else {
+                (denom..=0).contains(&num)
+            }
+        };
+        return in_unit(t_num) && in_unit(u_num);
+    }
+    if cross(r, w) != 0 || cross(s, w) != 0 {
+        // Parallel on different lines.
+        return false;
+    }
+    // All four points on one line, or some segment is a single point:
+    // project onto the line's direction and compare the intervals.
+    let direction = if r != [0, 0] { r } else { s };
+    if direction == [0, 0] {
+        return p[0] == q[0];
+    }
+    let project = |a: [i64; 2]| dot(a, direction);
+    let (p0, p1) = (project(p[0]), project(p[1]));
+    let (q0, q1) = (project(q[0]), project(q[1]));
+    p0.min(p1).max(q0.min(q1)) <= p0.max(p1).min(q0.max(q1))
+}
+
+#[test]
+fn test_oracle_segments_intersect() {
+    let mut rng = StdRng::seed_from_u64(142);
+    let mut hits = 0;
+    for round in 0..20_000 {
+        let range = [1, 3, 50][round % 3];
+        let mut point = || [rng.gen_range(-range..=range), rng.gen_range(-range..=range)];
+        let p = [point(), point()];
+        let q = [point(), point()];
+        let float = |s: [[i64; 2]; 2]| s.map(|a| a.map(|x| x as f64));
+        let expected = intersect_by_solving(p, q);
+        assert_eq!(
+            segments_intersect(float(p), float(q)),
+            expected,
+            "{:?} {:?}",
+            p,
+            q
+        );
+        assert_eq!(segments_intersect(float(q), float(p)), expected);
+        hits += expected as usize;
+    }
+    // Both answers come up often enough for the comparison to mean something.
+    assert!((5_000..15_000).contains(&hits), "{}", hits);
+}
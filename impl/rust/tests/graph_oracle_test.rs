@@ -0,0 +1,370 @@
+//! Oracle tests cross-checking the graph algorithms against each other and
+//! against certificates checked directly on random graphs
+
+use dsa_lab::{
+    bellman_ford, dijkstra, dinic, edmonds_karp, floyd_warshall, floyd_warshall_blocked,
+    hopcroft_karp, kosaraju_scc, tarjan_scc, AllPairsPaths, Graph, MaxFlow, NodeId, Relaxation,
+    ShortestPaths, StrongComponents,
+};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// A random graph on `n` nodes with about `n * degree` edges, weights drawn
+/// from `weights`.
+fn random_graph(
+    rng: &mut StdRng,
+    directed: bool,
+    n: usize,
+    degree: usize,
+    weights: std::ops::Range<i64>,
+) -> (Graph<(), i64>, Vec<NodeId>) {
+    let mut graph = if directed {
+        Graph::directed()
+    } else {
+        Graph::undirected()
+    };
+    let nodes: Vec<_> = (0..n).map(|_| graph.add_node(())).collect();
+    for _ in 0..n * degree {
+        let source = nodes[rng.gen_range(0..n)];
+        let target = nodes[rng.gen_range(0..n)];
+        graph.add_edge(source, target, rng.gen_range(weights.clone()));
+    }
+    (graph, nodes)
+}
+
+/// The lightest edge from `source` to `target`, if any.
+fn lightest_edge(graph: &Graph<(), i64>, source: NodeId, target: NodeId) -> Option<i64> {
+    graph
+        .outgoing(source)
+        .filter(|edge| edge.target == target)
+        .map(|edge| *edge.weight)
+        .min()
+}
+
+/// Check if `target` can be reached from `source`, by depth-first search.
+fn reachable(graph: &Graph<(), i64>, source: NodeId, target: NodeId) -> bool {
+    let mut seen = vec![false; graph.node_bound()];
+    let mut stack = vec![source];
+    while let Some(node) = stack.pop() {
+        if node == target {
+            return true;
+        }
+        if !std::mem::replace(&mut seen[node.index()], true) {
+            stack.extend(graph.neighbors(node));
+        }
+    }
+    false
+}
+
+/// Check that `paths` are shortest: every path is made of real edges and
+/// weighs its distance, and no edge leads anywhere more cheaply.
+fn check_shortest(graph: &Graph<(), i64>, paths: &ShortestPaths<i64>) {
+    for (node, _) in graph.nodes() {
+        let Some(distance) = paths.distance(node) else {
+            assert!(paths.path(node).is_none());
+            continue;
+        };
+        let path = paths.path(node).expect("reachable nodes have paths");
+        assert_eq!(path[0], paths.source());
+        let weight: i64 = path
+            .windows(2)
+            .map(|pair| lightest_edge(graph, pair[0], pair[1]).expect("path edges exist"))
+            .sum();
+        assert_eq!(weight, distance, "path weight differs from distance");
+        for edge in graph.outgoing(node) {
+            let target = paths
+                .distance(edge.target)
+                .expect("neighbors are reachable");
+            assert!(target <= distance + edge.weight, "edge still relaxes");
+        }
+    }
+}
+
+#[test]
+fn test_oracle_bellman_ford_matches_dijkstra() {
+    let mut rng = StdRng::seed_from_u64(42);
+    for round in 0..300 {
+        let directed = round % 2 == 0;
+        let n = rng.gen_range(1..60);
+        let degree = rng.gen_range(1..5);
+        let (graph, nodes) = random_graph(&mut rng, directed, n, degree, 0..100);
+        let source = nodes[rng.gen_range(0..n)];
+        let expected = dijkstra(&graph, source);
+        check_shortest(&graph, &expected);
+        for relaxation in [Relaxation::Rounds, Relaxation::Spfa] {
+            let paths = bellman_ford(&graph, source, relaxation).expect("no negative edges");
+            for &node in &nodes {
+                assert_eq!(paths.distance(node), expected.distance(node));
+            }
+            check_shortest(&graph, &paths);
+        }
+    }
+}
+
+#[test]
+fn test_oracle_bellman_ford_negative_weights() {
+    let mut rng = StdRng::seed_from_u64(43);
+    let mut cycles = 0;
+    for round in 0..500 {
+        let n = rng.gen_range(1..40);
+        // Mostly positive weights, so some graphs have negative cycles and
+        // some do not.
+        let (graph, nodes) = random_graph(&mut rng, true, n, 2, -20..100);
+        let source = nodes[rng.gen_range(0..n)];
+        let rounds = bellman_ford(&graph, source, Relaxation::Rounds);
+        let spfa = bellman_ford(&graph, source, Relaxation::Spfa);
+        match (rounds, spfa) {
+            (Ok(rounds), Ok(spfa)) => {
+                check_shortest(&graph, &rounds);
+                check_shortest(&graph, &spfa);
+                for &node in &nodes {
+                    assert_eq!(rounds.distance(node), spfa.distance(node));
+                }
+            }
+            (Err(a), Err(b)) => {
+                cycles += 1;
+                for cycle in [a, b] {
+                    let weight: i64 = (0..cycle.nodes.len())
+                        .map(|i| {
+                            let next = cycle.nodes[(i + 1) % cycle.nodes.len()];
+                            lightest_edge(&graph, cycle.nodes[i], next).expect("cycle edges exist")
+                        })
+                        .sum();
+                    assert!(weight < 0, "round {}: cycle weighs {}", round, weight);
+                    assert!(reachable(&graph, source, cycle.nodes[0]));
+                }
+            }
+            _ => panic!("round {}: variants disagree on negative cycles", round),
+        }
+    }
+    assert!(cycles > 50 && cycles < 450, "{} cycles", cycles);
+}
+
+/// Check all-pairs paths against single-source ones from every node, and
+/// that each path is made of real edges and weighs its distance.
+fn check_all_pairs(graph: &Graph<(), i64>, nodes: &[NodeId], all_pairs: &AllPairsPaths<i64>) {
+    for &source in nodes {
+        let single = bellman_ford(graph, source, Relaxation::Spfa).expect("no negative cycle");
+        for &target in nodes {
+            let distance = all_pairs.distance(source, target);
+            assert_eq!(distance, single.distance(target));
+            let Some(distance) = distance else {
+                assert!(all_pairs.path(source, target).is_none());
+                continue;
+            };
+            let path = all_pairs
+                .path(source, target)
+                .expect("reachable pairs have paths");
+            assert_eq!((path[0], path[path.len() - 1]), (source, target));
+            let weight: i64 = path
+                .windows(2)
+                .map(|pair| lightest_edge(graph, pair[0], pair[1]).expect("path edges exist"))
+                .sum();
+            assert_eq!(weight, distance, "path weight differs from distance");
+        }
+    }
+}
+
+#[test]
+fn test_oracle_floyd_warshall() {
+    let mut rng = StdRng::seed_from_u64(44);
+    let mut cycles = 0;
+    for round in 0..200 {
+        let directed = round % 4 != 0;
+        let n = rng.gen_range(1..30);
+        let low = if round % 2 == 0 { 0 } else { -10 };
+        let (mut graph, mut nodes) = random_graph(&mut rng, directed, n, 2, low..100);
+        // Leave holes in the node indices.
+        if n > 2 && rng.gen() {
+            graph.remove_node(nodes.swap_remove(0));
+        }
+        let block = rng.gen_range(1..8);
+        match (
+            floyd_warshall(&graph),
+            floyd_warshall_blocked(&graph, block),
+        ) {
+            (Ok(plain), Ok(blocked)) => {
+                check_all_pairs(&graph, &nodes, &plain);
+                check_all_pairs(&graph, &nodes, &blocked);
+            }
+            (Err(a), Err(b)) => {
+                cycles += 1;
+                assert!(nodes
+                    .iter()
+                    .any(|&node| bellman_ford(&graph, node, Relaxation::Rounds).is_err()));
+                assert!(!a.nodes.is_empty() && !b.nodes.is_empty());
+            }
+            _ => panic!("round {}: variants disagree on negative cycles", round),
+        }
+    }
+    assert!(cycles > 0, "no negative cycles generated");
+}
+
+/// Check `components` against reachability: two nodes share a component
+/// exactly when each reaches the other, and edges between components run
+/// from lower numbers to higher.
+fn check_components(graph: &Graph<(), i64>, nodes: &[NodeId], components: &StrongComponents) {
+    let total: usize = components.iter().map(|members| members.len()).sum();
+    assert_eq!(total, nodes.len());
+    for &u in nodes {
+        let cu = components.component(u).expect("every node has a component");
+        assert!(components.nodes(cu).contains(&u));
+        for &v in nodes {
+            let mutual = reachable(graph, u, v) && reachable(graph, v, u);
+            assert_eq!(components.component(v) == Some(cu), mutual);
+        }
+        for edge in graph.outgoing(u) {
+            assert!(cu <= components.component(edge.target).unwrap());
+        }
+    }
+}
+
+#[test]
+fn test_oracle_scc_tarjan_matches_kosaraju() {
+    let mut rng = StdRng::seed_from_u64(45);
+    for round in 0..300 {
+        let directed = round % 5 != 0;
+        let n = rng.gen_range(1..40);
+        // Around one edge per node sits near the threshold where large
+        // components appear, so the sizes vary.
+        let degree = rng.gen_range(1..3);
+        let (mut graph, mut nodes) = random_graph(&mut rng, directed, n, degree, 0..1);
+        if n > 2 && rng.gen() {
+            graph.remove_node(nodes.swap_remove(0));
+        }
+        let tarjan = tarjan_scc(&graph);
+        let kosaraju = kosaraju_scc(&graph);
+        check_components(&graph, &nodes, &tarjan);
+        check_components(&graph, &nodes, &kosaraju);
+        assert_eq!(tarjan.len(), kosaraju.len());
+        for (components, other) in [(&tarjan, &kosaraju), (&kosaraju, &tarjan)] {
+            let condensation = components.condensation(&graph);
+            assert_eq!(condensation.node_count(), components.len());
+            let ids: Vec<_> = condensation.nodes().map(|(id, _)| id).collect();
+            for (c, &id) in ids.iter().enumerate() {
+                assert_eq!(condensation.node(id).unwrap(), components.nodes(c));
+                // Each condensation edge stands for at least one real edge,
+                // and the condensation is acyclic, so its own components are
+                // single nodes.
+                for edge in condensation.outgoing(id) {
+                    let target = edge.target.index();
+                    assert!(c < target);
+                    assert!(components.nodes(c).iter().any(|&u| graph
+                        .neighbors(u)
+                        .any(|v| components.component(v) == Some(target))));
+                }
+            }
+            assert_eq!(tarjan_scc(&condensation).len(), components.len());
+            assert_eq!(other.len(), components.len());
+        }
+    }
+}
+
+/// Check that `flow` is a feasible flow of its value, and that its cut is
+/// a cut of the same capacity, which proves both optimal.
+fn check_flow(graph: &Graph<(), i64>, source: NodeId, sink: NodeId, flow: &MaxFlow<i64>) {
+    let mut net = vec![0; graph.node_bound()];
+    for edge in graph.edges() {
+        let carried = flow.flow(edge.id).expect("every edge has a flow");
+        assert!((0..=*edge.weight).contains(&carried), "flow over capacity");
+        net[edge.source.index()] -= carried;
+        net[edge.target.index()] += carried;
+    }
+    for (node, _) in graph.nodes() {
+        let expected = if node == source {
+            -flow.value()
+        } else if node == sink {
+            flow.value()
+        } else {
+            0
+        };
+        assert_eq!(net[node.index()], expected, "flow not conserved");
+    }
+    assert!(flow.is_source_side(source) && !flow.is_source_side(sink));
+    let cut: i64 = flow
+        .min_cut(graph)
+        .into_iter()
+        .map(|edge| *graph.edge(edge).unwrap())
+        .sum();
+    assert_eq!(cut, flow.value());
+}
+
+#[test]
+fn test_oracle_dinic_matches_edmonds_karp() {
+    let mut rng = StdRng::seed_from_u64(46);
+    for _ in 0..300 {
+        let n = rng.gen_range(2..40);
+        let degree = rng.gen_range(1..6);
+        let (mut graph, mut nodes) = random_graph(&mut rng, true, n, degree, 0..20);
+        if n > 3 && rng.gen() {
+            graph.remove_node(nodes.swap_remove(0));
+        }
+        let source = nodes[0];
+        let sink = nodes[rng.gen_range(1..nodes.len())];
+        let fast = dinic(&graph, source, sink);
+        let reference = edmonds_karp(&graph, source, sink);
+        assert_eq!(fast.value(), reference.value());
+        check_flow(&graph, source, sink, &fast);
+        check_flow(&graph, source, sink, &reference);
+    }
+}
+
+#[test]
+fn test_oracle_hopcroft_karp_matches_max_flow() {
+    let mut rng = StdRng::seed_from_u64(47);
+    for round in 0..300 {
+        let (left, right) = (rng.gen_range(1..30), rng.gen_range(1..30));
+        let mut graph = if round % 2 == 0 {
+            Graph::directed()
+        } else {
+            Graph::undirected()
+        };
+        let l: Vec<_> = (0..left).map(|_| graph.add_node(())).collect();
+        let r: Vec<_> = (0..right).map(|_| graph.add_node(())).collect();
+        let mut edges = Vec::new();
+        for _ in 0..rng.gen_range(0..4 * (left + right)) {
+            let (u, v) = (rng.gen_range(0..left), rng.gen_range(0..right));
+            // Direction does not matter to the matching.
+            if rng.gen() {
+                graph.add_edge(l[u], r[v], ());
+            } else {
+                graph.add_edge(r[v], l[u], ());
+            }
+            edges.push((l[u], r[v]));
+        }
+        let matching = hopcroft_karp(&graph, &l);
+
+        // The same problem as unit-capacity flow through the two sides.
+        let mut network = Graph::directed();
+        let ids: Vec<_> = (0..left + right).map(|_| network.add_node(())).collect();
+        let (source, sink) = (network.add_node(()), network.add_node(()));
+        for &u in &ids[..left] {
+            network.add_edge(source, u, 1i64);
+        }
+        for &v in &ids[left..] {
+            network.add_edge(v, sink, 1);
+        }
+        let position = |node: NodeId| l.iter().chain(&r).position(|&other| other == node).unwrap();
+        for &(u, v) in &edges {
+            network.add_edge(ids[position(u)], ids[position(v)], 1);
+        }
+        let flow = dinic(&network, source, sink);
+        assert_eq!(matching.len() as i64, flow.value(), "round {}", round);
+
+        let mut used = vec![false; graph.node_bound()];
+        for (u, v) in matching.pairs() {
+            assert!(edges.contains(&(u, v)), "matched pair is not an edge");
+            assert_eq!((matching.mate(u), matching.mate(v)), (Some(v), Some(u)));
+            assert!(!std::mem::replace(&mut used[u.index()], true));
+            assert!(!std::mem::replace(&mut used[v.index()], true));
+        }
+        let cover = matching.vertex_cover();
+        assert_eq!(cover.len(), matching.len());
+        for &(u, v) in &edges {
+            assert!(
+                cover.contains(&u) || cover.contains(&v),
+                "edge left uncovered"
+            );
+        }
+    }
+}
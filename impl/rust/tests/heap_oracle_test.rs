@@ -0,0 +1,192 @@
+//! Oracle tests comparing the heaps against std::collections::BinaryHeap and
+//! a plain Vec
+
+use dsa_lab::{
+    BinaryHeap, BinomialHeap, FibonacciHeap, HeapHandle, HeapOrder, IndexedPriorityQueue,
+    LeftistHeap, MergeableHeap, SkewHeap,
+};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap as StdBinaryHeap;
+
+#[test]
+fn test_oracle_max_heap_mixed_operations() {
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut our_heap = BinaryHeap::new(HeapOrder::Max);
+    let mut std_heap = StdBinaryHeap::new();
+    for _ in 0..20000 {
+        if rng.gen_bool(0.55) {
+            let value: u32 = rng.gen_range(0..1000);
+            our_heap.push(value);
+            std_heap.push(value);
+        } else {
+            assert_eq!(our_heap.pop(), std_heap.pop());
+        }
+        assert_eq!(our_heap.peek(), std_heap.peek());
+        assert_eq!(our_heap.len(), std_heap.len());
+    }
+    assert_eq!(our_heap.into_sorted_vec(), std_heap.into_sorted_vec());
+}
+
+#[test]
+fn test_oracle_min_heap_mixed_operations() {
+    let mut rng = StdRng::seed_from_u64(43);
+    let mut our_heap = BinaryHeap::new(HeapOrder::Min);
+    let mut std_heap = StdBinaryHeap::new();
+    for _ in 0..20000 {
+        if rng.gen_bool(0.55) {
+            let value: u32 = rng.gen_range(0..1000);
+            our_heap.push(value);
+            std_heap.push(Reverse(value));
+        } else {
+            assert_eq!(our_heap.pop(), std_heap.pop().map(|Reverse(value)| value));
+        }
+        assert_eq!(our_heap.peek(), std_heap.peek().map(|Reverse(value)| value));
+    }
+    let mut expected: Vec<u32> = std_heap.into_iter().map(|Reverse(value)| value).collect();
+    expected.sort_unstable();
+    assert_eq!(our_heap.into_sorted_vec(), expected);
+}
+
+#[test]
+fn test_oracle_heapify_then_drain() {
+    let mut rng = StdRng::seed_from_u64(44);
+    for len in [0, 1, 2, 3, 100, 1001] {
+        let values: Vec<i64> = (0..len).map(|_| rng.gen_range(-500..500)).collect();
+        let mut our_heap = BinaryHeap::from_vec(values.clone(), HeapOrder::Max);
+        let mut std_heap = StdBinaryHeap::from(values);
+        while let Some(value) = std_heap.pop() {
+            assert_eq!(our_heap.pop(), Some(value));
+        }
+        assert!(our_heap.is_empty());
+    }
+}
+
+#[test]
+fn test_oracle_fibonacci_heap_decrease_key() {
+    let mut rng = StdRng::seed_from_u64(45);
+    let mut heap = FibonacciHeap::new();
+    // Live elements as (value, id), with each id's handle alongside.
+    let mut oracle: Vec<(u32, usize)> = Vec::new();
+    let mut handles: Vec<HeapHandle> = Vec::new();
+    for step in 0..20000 {
+        match rng.gen_range(0..4) {
+            0 | 1 => {
+                let value = rng.gen_range(0..100_000);
+                let id = handles.len();
+                handles.push(heap.push((value, id)));
+                oracle.push((value, id));
+            }
+            2 => {
+                let expected = oracle.iter().copied().min();
+                oracle.retain(|&entry| Some(entry) != expected);
+                assert_eq!(heap.pop(), expected);
+            }
+            3 if !oracle.is_empty() => {
+                let slot = rng.gen_range(0..oracle.len());
+                let (value, id) = oracle[slot];
+                let lowered = rng.gen_range(0..=value);
+                oracle[slot] = (lowered, id);
+                heap.decrease_key(handles[id], (lowered, id));
+            }
+            _ => {}
+        }
+        assert_eq!(heap.peek(), oracle.iter().min());
+        if step % 1000 == 0 {
+            heap.debug_validate();
+        }
+    }
+    assert_eq!(heap.len(), oracle.len());
+}
+
+#[test]
+fn test_oracle_indexed_priority_queue() {
+    let mut rng = StdRng::seed_from_u64(46);
+    let mut queue = IndexedPriorityQueue::new();
+    // Priority per id, for the ids currently queued.
+    let mut oracle: Vec<Option<u32>> = vec![None; 500];
+    let oracle_min = |oracle: &[Option<u32>]| {
+        (0..oracle.len())
+            .filter_map(|id| oracle[id].map(|priority| (priority, id)))
+            .min()
+    };
+    for _ in 0..20000 {
+        let id = rng.gen_range(0..oracle.len());
+        let priority = rng.gen_range(0..10_000);
+        match (rng.gen_range(0..4), oracle[id]) {
+            (0, None) => {
+                queue.push(id, (priority, id));
+                oracle[id] = Some(priority);
+            }
+            (0, Some(current)) if priority < current => {
+                queue.decrease_key(id, (priority, id));
+                oracle[id] = Some(priority);
+            }
+            (0, Some(_)) => {
+                queue.increase_key(id, (priority, id));
+                oracle[id] = Some(priority);
+            }
+            (1, current) => {
+                assert_eq!(queue.remove(id).map(|(priority, _)| priority), current);
+                oracle[id] = None;
+            }
+            (2, _) => {
+                let expected = oracle_min(&oracle);
+                if let Some((_, id)) = expected {
+                    oracle[id] = None;
+                }
+                assert_eq!(queue.pop().map(|(_, priority)| priority), expected);
+            }
+            _ => {}
+        }
+        assert_eq!(
+            queue.peek().map(|(_, &priority)| priority),
+            oracle_min(&oracle)
+        );
+        assert_eq!(queue.len(), oracle.iter().flatten().count());
+    }
+}
+
+/// Replay random pushes, pops and melds of small heaps against std.
+fn check_mergeable_heap<H: MergeableHeap<u32>>(seed: u64, validate: fn(&H)) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut heap = H::default();
+    let mut std_heap = StdBinaryHeap::new();
+    for step in 0..5000 {
+        match rng.gen_range(0..5) {
+            0 | 1 => {
+                let value: u32 = rng.gen_range(0..10_000);
+                heap.push(value);
+                std_heap.push(Reverse(value));
+            }
+            2 | 3 => {
+                let expected = std_heap.pop().map(|Reverse(value)| value);
+                assert_eq!(heap.pop(), expected);
+            }
+            4 => {
+                let mut other = H::default();
+                for _ in 0..rng.gen_range(0..40) {
+                    let value: u32 = rng.gen_range(0..10_000);
+                    other.push(value);
+                    std_heap.push(Reverse(value));
+                }
+                heap.meld(other);
+            }
+            _ => unreachable!(),
+        }
+        assert_eq!(heap.peek(), std_heap.peek().map(|Reverse(value)| value));
+        assert_eq!(heap.len(), std_heap.len());
+        if step % 500 == 0 {
+            validate(&heap);
+        }
+    }
+}
+
+#[test]
+fn test_oracle_mergeable_heaps_meld() {
+    check_mergeable_heap::<BinomialHeap<u32>>(47, BinomialHeap::debug_validate);
+    check_mergeable_heap::<FibonacciHeap<u32>>(47, FibonacciHeap::debug_validate);
+    check_mergeable_heap::<LeftistHeap<u32>>(47, LeftistHeap::debug_validate);
+    check_mergeable_heap::<SkewHeap<u32>>(47, SkewHeap::debug_validate);
+}
@@ -0,0 +1,187 @@
+//! Oracle tests comparing the interval utilities against per-point arrays
+
+use dsa_lab::interval::{concurrency, max_concurrent, max_non_overlapping, merge_overlapping};
+use dsa_lab::{IntervalMap, RangeSet};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::ops::Range;
+
+/// Points on the line the random intervals live within.
+const SPAN: usize = 64;
+
+/// Random intervals inside `0..SPAN`, including empty and touching ones.
+fn random_intervals(rng: &mut StdRng, n: usize) -> Vec<Range<usize>> {
+    (0..n)
+        .map(|_| {
+            let start = rng.gen_range(0..SPAN);
+            let len = rng.gen_range(0..12);
+            start..(start + len).min(SPAN)
+        })
+        .collect()
+}
+
+/// How many intervals contain each point.
+fn coverage(intervals: &[Range<usize>]) -> Vec<usize> {
+    let mut counts = vec![0; SPAN];
+    for range in intervals {
+        for count in &mut counts[range.clone()] {
+            *count += 1;
+        }
+    }
+    counts
+}
+
+#[test]
+fn test_oracle_merge_and_concurrency() {
+    let mut rng = StdRng::seed_from_u64(150);
+    for _ in 0..1_000 {
+        let n = rng.gen_range(0..20);
+        let intervals = random_intervals(&mut rng, n);
+        let counts = coverage(&intervals);
+
+        // The merged ranges are disjoint, do not touch, and cover exactly
+        // the points some interval contains.
+        let merged = merge_overlapping(&intervals);
+        for pair in merged.windows(2) {
+            assert!(pair[0].end < pair[1].start, "{:?}", merged);
+        }
+        let mut covered = vec![false; SPAN];
+        for range in &merged {
+            assert!(range.start < range.end);
+            covered[range.clone()].fill(true);
+        }
+        let expected: Vec<bool> = counts.iter().map(|&count| count > 0).collect();
+        assert_eq!(covered, expected, "{:?}", intervals);
+
+        // Replaying the steps gives each point's count.
+        let steps = concurrency(&intervals);
+        // One past the end, as the last step can fall on SPAN itself.
+        let mut replayed = vec![0; SPAN + 1];
+        for (k, &(time, count)) in steps.iter().enumerate() {
+            let next = steps.get(k + 1).map_or(SPAN + 1, |&(t, _)| t);
+            assert!(time < next);
+            replayed[time..next].fill(count);
+        }
+        assert_eq!(replayed[..SPAN], counts, "{:?}", intervals);
+        assert_eq!(steps.last().map_or(0, |&(_, count)| count), 0);
+        assert_eq!(
+            max_concurrent(&intervals),
+            counts.iter().copied().max().unwrap_or(0)
+        );
+    }
+}
+
+#[test]
+fn test_oracle_max_non_overlapping() {
+    let mut rng = StdRng::seed_from_u64(151);
+    for _ in 0..1_000 {
+        let n = rng.gen_range(0..30);
+        let intervals = random_intervals(&mut rng, n);
+        let chosen = max_non_overlapping(&intervals);
+
+        // best[t] is the most disjoint nonempty intervals within 0..t.
+        let mut best = vec![0; SPAN + 1];
+        for t in 1..=SPAN {
+            best[t] = best[t - 1];
+            for range in intervals.iter().filter(|r| r.start < r.end && r.end == t) {
+                best[t] = best[t].max(best[range.start] + 1);
+            }
+        }
+        assert_eq!(chosen.len(), best[SPAN], "{:?}", intervals);
+        for pair in chosen.windows(2) {
+            assert!(intervals[pair[0]].end <= intervals[pair[1]].start);
+        }
+        assert!(chosen.iter().all(|&i| !intervals[i].is_empty()));
+    }
+}
+
+#[test]
+fn test_oracle_interval_map() {
+    let mut rng = StdRng::seed_from_u64(152);
+    for _ in 0..200 {
+        let mut map = IntervalMap::new();
+        let mut points: Vec<Option<u8>> = vec![None; SPAN];
+        for _ in 0..100 {
+            let range = random_intervals(&mut rng, 1).remove(0);
+            if rng.gen_bool(0.7) {
+                // Few values, so equal neighbours come up often.
+                let value = rng.gen_range(0..3);
+                map.insert(range.clone(), value);
+                points[range].fill(Some(value));
+            } else {
+                map.remove(range.clone());
+                points[range].fill(None);
+            }
+
+            for (key, &expected) in points.iter().enumerate() {
+                assert_eq!(map.get(&key).copied(), expected);
+            }
+            // Coalesced: no two stored ranges touch with the same value.
+            let ranges: Vec<(Range<usize>, u8)> =
+                map.iter().map(|(range, &value)| (range, value)).collect();
+            for pair in ranges.windows(2) {
+                assert!(pair[0].0.end <= pair[1].0.start);
+                assert!(pair[0].0.end < pair[1].0.start || pair[0].1 != pair[1].1);
+            }
+
+            let query = random_intervals(&mut rng, 1).remove(0);
+            let expected: Vec<(Range<usize>, u8)> = ranges
+                .iter()
+                .filter(|(range, _)| (query.start..query.end).any(|key| range.contains(&key)))
+                .cloned()
+                .collect();
+            let actual: Vec<(Range<usize>, u8)> = map
+                .overlapping(query.clone())
+                .map(|(range, &value)| (range, value))
+                .collect();
+            assert_eq!(actual, expected, "{:?}", query);
+        }
+    }
+}
+
+#[test]
+fn test_oracle_range_set() {
+    let mut rng = StdRng::seed_from_u64(153);
+    for _ in 0..200 {
+        let mut set = RangeSet::new();
+        let mut points = [false; SPAN];
+        for _ in 0..100 {
+            let range = random_intervals(&mut rng, 1).remove(0);
+            let insert = rng.gen_bool(0.6);
+            if insert {
+                set.insert(range.clone());
+            } else {
+                set.remove(range.clone());
+            }
+            points[range].fill(insert);
+
+            for (key, &expected) in points.iter().enumerate() {
+                assert_eq!(set.contains(&key), expected);
+            }
+            // The ranges are the maximal runs of present keys, and the
+            // gaps the runs of missing keys between them.
+            let runs = |present: bool| {
+                let mut runs: Vec<Range<usize>> = Vec::new();
+                for key in (0..SPAN).filter(|&key| points[key] == present) {
+                    match runs.last_mut() {
+                        Some(run) if run.end == key => run.end += 1,
+                        _ => runs.push(key..key + 1),
+                    }
+                }
+                runs
+            };
+            let ranges = runs(true);
+            assert_eq!(set.iter().collect::<Vec<_>>(), ranges);
+            let first = ranges.first().map_or(0, |r| r.start);
+            let last = ranges.last().map_or(0, |r| r.end);
+            let gaps: Vec<Range<usize>> = runs(false)
+                .into_iter()
+                .filter(|gap| first < gap.start && gap.end < last)
+                .collect();
+            assert_eq!(set.gaps().collect::<Vec<_>>(), gaps);
+
+            let query = random_intervals(&mut rng, 1).remove(0);
+            assert_eq!(set.covers(&query), points[query.clone()].iter().all(|&p| p));
+        }
+    }
+}
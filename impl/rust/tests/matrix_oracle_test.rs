@@ -0,0 +1,86 @@
+//! Oracle tests comparing the sparse matrix against nested vectors
+
+use dsa_lab::{erdos_renyi, CsrMatrix, Matrix};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Add up `entries` into nested vectors, the obvious way.
+fn nested(rows: usize, cols: usize, entries: &[(usize, usize, i64)]) -> Vec<Vec<i64>> {
+    let mut sums = vec![vec![0; cols]; rows];
+    for &(i, j, value) in entries {
+        sums[i][j] += value;
+    }
+    sums
+}
+
+#[test]
+fn test_oracle_sparse_matrix() {
+    let mut rng = StdRng::seed_from_u64(160);
+    for _ in 0..500 {
+        let rows = rng.gen_range(0..30);
+        let cols = rng.gen_range(0..30);
+        let count = if rows * cols == 0 {
+            0
+        } else {
+            rng.gen_range(0..rows * cols)
+        };
+        let entries: Vec<(usize, usize, i64)> = (0..count)
+            .map(|_| {
+                (
+                    rng.gen_range(0..rows),
+                    rng.gen_range(0..cols),
+                    rng.gen_range(-5..=5),
+                )
+            })
+            .collect();
+        let expected = nested(rows, cols, &entries);
+        let sparse = CsrMatrix::from_triplets(rows, cols, entries.iter().copied());
+        let dense = sparse.to_dense();
+        for (i, row) in expected.iter().enumerate() {
+            assert_eq!(dense.row(i), row);
+        }
+
+        let x: Vec<i64> = (0..cols).map(|_| rng.gen_range(-10..=10)).collect();
+        let product: Vec<i64> = expected
+            .iter()
+            .map(|row| row.iter().zip(&x).map(|(a, b)| a * b).sum())
+            .collect();
+        assert_eq!(sparse.mul_vec(&x), product);
+        assert_eq!(dense.mul_vec(&x), product);
+
+        let transposed = sparse.transpose();
+        assert_eq!(transposed.to_dense(), dense.transpose());
+        assert_eq!(transposed.transpose(), sparse);
+
+        // Back from dense, only the nonzero sums are stored.
+        let back = CsrMatrix::from_dense(&dense);
+        assert!(back.iter().all(|(_, _, &value)| value != 0));
+        assert_eq!(back.to_dense(), dense);
+        assert_eq!(
+            back.nnz(),
+            expected.iter().flatten().filter(|&&v| v != 0).count()
+        );
+    }
+}
+
+#[test]
+fn test_oracle_graph_adjacency() {
+    let mut rng = StdRng::seed_from_u64(161);
+    for round in 0..50 {
+        let n = rng.gen_range(1..60);
+        let graph = erdos_renyi(n, 0.1, round % 2 == 0, round, |w| (w % 7) as i64);
+        let matrix = CsrMatrix::from_graph(&graph);
+        let mut expected = Matrix::filled(n, n, 0);
+        for edge in graph.edges() {
+            let (u, v) = (edge.source.index(), edge.target.index());
+            expected[(u, v)] += *edge.weight;
+            if !graph.is_directed() && u != v {
+                expected[(v, u)] += *edge.weight;
+            }
+        }
+        assert_eq!(matrix.to_dense(), expected);
+        if !graph.is_directed() {
+            assert_eq!(matrix.transpose(), matrix);
+        }
+    }
+}
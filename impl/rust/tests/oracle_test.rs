@@ -1,6 +1,13 @@
 //! Oracle tests comparing our HashMap against std::collections::HashMap
 
-use dsa_lab::HashMap;
+#[cfg(feature = "ahash")]
+use dsa_lab::AHashMap;
+#[cfg(feature = "fxhash")]
+use dsa_lab::FxHashMap;
+use dsa_lab::{
+    ChainedHashMap, CuckooHashMap, DoubleHashMap, HashMap, IncrementalHashMap, QuadraticMap,
+    RobinHoodMap, SoaMap, SwissMap,
+};
 use std::collections::HashMap as StdHashMap;
 
 /// Test that our HashMap produces the same results as std HashMap
@@ -40,7 +47,7 @@ fn test_oracle_insert_get() {
     // Verify non-existent keys
     assert_eq!(
         our_map.get(&"nonexistent".to_string()),
-        std_map.get(&"nonexistent".to_string())
+        std_map.get("nonexistent")
     );
 }
 
@@ -146,8 +153,90 @@ fn test_oracle_mixed_operations() {
             }
             _ => unreachable!(),
         }
+
+        #[cfg(debug_assertions)]
+        our_map.debug_validate();
     }
 
     // Final verification
     assert_eq!(our_map.len(), std_map.len());
 }
+
+/// Replay a random insert/get/remove sequence against a map type and std.
+macro_rules! oracle_mixed_operations {
+    ($name:ident, $map:ty) => {
+        #[test]
+        fn $name() {
+            use rand::rngs::StdRng;
+            use rand::{Rng, SeedableRng};
+
+            let mut rng = StdRng::seed_from_u64(42);
+            let mut our_map: $map = <$map>::default();
+            let mut std_map: StdHashMap<String, String> = StdHashMap::new();
+
+            for _ in 0..10000 {
+                let op = rng.gen_range(0..3);
+                let key = format!("key_{}", rng.gen_range(0..100));
+                let value = format!("value_{}", rng.gen_range(0..1000));
+
+                match op {
+                    0 => assert_eq!(
+                        our_map.insert(key.clone(), value.clone()),
+                        std_map.insert(key, value)
+                    ),
+                    1 => assert_eq!(our_map.get(&key), std_map.get(&key)),
+                    2 => assert_eq!(our_map.remove(&key), std_map.remove(&key)),
+                    _ => unreachable!(),
+                }
+            }
+
+            assert_eq!(our_map.len(), std_map.len());
+            for (key, value) in &std_map {
+                assert_eq!(our_map.get(key), Some(value));
+            }
+        }
+    };
+}
+
+oracle_mixed_operations!(
+    test_oracle_robin_hood_mixed_operations,
+    RobinHoodMap<String, String>
+);
+oracle_mixed_operations!(
+    test_oracle_quadratic_mixed_operations,
+    QuadraticMap<String, String>
+);
+oracle_mixed_operations!(
+    test_oracle_double_hash_mixed_operations,
+    DoubleHashMap<String, String>
+);
+oracle_mixed_operations!(
+    test_oracle_chained_mixed_operations,
+    ChainedHashMap<String, String>
+);
+oracle_mixed_operations!(
+    test_oracle_cuckoo_mixed_operations,
+    CuckooHashMap<String, String>
+);
+oracle_mixed_operations!(
+    test_oracle_swiss_mixed_operations,
+    SwissMap<String, String>
+);
+oracle_mixed_operations!(
+    test_oracle_incremental_mixed_operations,
+    IncrementalHashMap<String, String>
+);
+oracle_mixed_operations!(
+    test_oracle_soa_mixed_operations,
+    SoaMap<String, String>
+);
+#[cfg(feature = "fxhash")]
+oracle_mixed_operations!(
+    test_oracle_fxhash_mixed_operations,
+    FxHashMap<String, String>
+);
+#[cfg(feature = "ahash")]
+oracle_mixed_operations!(
+    test_oracle_ahash_mixed_operations,
+    AHashMap<String, String>
+);
@@ -0,0 +1,280 @@
+//! Oracle tests comparing the ordered maps against std::collections::BTreeMap
+//! and the integer successor sets against std::collections::BTreeSet
+
+use dsa_lab::{
+    AaTreeMap, AvlMap, BPlusTreeMap, BTreeMap, OrderStatisticsMap, PersistentSortedMap,
+    SkipListMap, TreapMap, TwoThreeFourMap, WeightBalancedMap, XFastTrie, YFastTrie, ZipTreeMap,
+};
+use std::collections::BTreeMap as StdBTreeMap;
+use std::collections::BTreeSet;
+use std::ops::Bound;
+
+/// Replay a random insert/get/remove sequence against an ordered map and
+/// std, checking in-order iteration and random ranges along the way.
+macro_rules! ordered_oracle {
+    ($name:ident, $map:ty, $new:expr) => {
+        #[test]
+        fn $name() {
+            use rand::rngs::StdRng;
+            use rand::{Rng, SeedableRng};
+
+            let mut rng = StdRng::seed_from_u64(42);
+            let mut our_map: $map = $new;
+            let mut std_map: StdBTreeMap<u32, u32> = StdBTreeMap::new();
+
+            for step in 0..20000 {
+                let key = rng.gen_range(0..1000);
+                match rng.gen_range(0..3) {
+                    0 => assert_eq!(our_map.insert(key, step), std_map.insert(key, step)),
+                    1 => assert_eq!(our_map.get(&key), std_map.get(&key)),
+                    2 => assert_eq!(our_map.remove(&key), std_map.remove(&key)),
+                    _ => unreachable!(),
+                }
+
+                if step % 1000 == 0 {
+                    let start = rng.gen_range(0..1000);
+                    let end = rng.gen_range(start..=1000);
+                    assert!(our_map.range(start..end).eq(std_map.range(start..end)));
+                    assert!(our_map.range(start..=end).eq(std_map.range(start..=end)));
+                    assert!(our_map.range(..end).eq(std_map.range(..end)));
+                    assert!(our_map.range(start..).eq(std_map.range(start..)));
+                }
+            }
+
+            assert_eq!(our_map.len(), std_map.len());
+            assert!(our_map.iter().eq(std_map.iter()));
+        }
+    };
+}
+
+ordered_oracle!(
+    test_oracle_aa_tree_mixed_operations,
+    AaTreeMap<u32, u32>,
+    AaTreeMap::new()
+);
+ordered_oracle!(
+    test_oracle_avl_mixed_operations,
+    AvlMap<u32, u32>,
+    AvlMap::new()
+);
+ordered_oracle!(
+    test_oracle_btree_mixed_operations,
+    BTreeMap<u32, u32>,
+    BTreeMap::new()
+);
+ordered_oracle!(
+    test_oracle_btree_fanout_3_mixed_operations,
+    BTreeMap<u32, u32>,
+    BTreeMap::with_fanout(3)
+);
+ordered_oracle!(
+    test_oracle_bplus_tree_mixed_operations,
+    BPlusTreeMap<u32, u32>,
+    BPlusTreeMap::new()
+);
+ordered_oracle!(
+    test_oracle_bplus_tree_fanout_3_mixed_operations,
+    BPlusTreeMap<u32, u32>,
+    BPlusTreeMap::with_fanout(3)
+);
+ordered_oracle!(
+    test_oracle_order_statistics_mixed_operations,
+    OrderStatisticsMap<u32, u32>,
+    OrderStatisticsMap::new()
+);
+ordered_oracle!(
+    test_oracle_skip_list_mixed_operations,
+    SkipListMap<u32, u32>,
+    SkipListMap::new()
+);
+ordered_oracle!(
+    test_oracle_treap_mixed_operations,
+    TreapMap<u32, u32>,
+    TreapMap::new()
+);
+ordered_oracle!(
+    test_oracle_two_three_four_mixed_operations,
+    TwoThreeFourMap<u32, u32>,
+    TwoThreeFourMap::new()
+);
+ordered_oracle!(
+    test_oracle_zip_tree_mixed_operations,
+    ZipTreeMap<u32, u32>,
+    ZipTreeMap::new()
+);
+ordered_oracle!(
+    test_oracle_weight_balanced_mixed_operations,
+    WeightBalancedMap<u32, u32>,
+    WeightBalancedMap::new()
+);
+
+#[test]
+fn test_oracle_weight_balanced_split_and_merge() {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut our_map = WeightBalancedMap::new();
+    let mut std_map: StdBTreeMap<u32, u32> = StdBTreeMap::new();
+
+    for step in 0..5000 {
+        let key = rng.gen_range(0..1000);
+        match rng.gen_range(0..4) {
+            0 | 1 => assert_eq!(our_map.insert(key, step), std_map.insert(key, step)),
+            2 => {
+                // Split at a key or a rank, check both halves, rejoin.
+                let upper = if rng.gen() {
+                    our_map.split_off(&key)
+                } else {
+                    our_map.split_off_nth(std_map.range(..key).count())
+                };
+                our_map.debug_validate();
+                upper.debug_validate();
+                assert!(our_map.iter().eq(std_map.range(..key)));
+                assert!(upper.iter().eq(std_map.range(key..)));
+                our_map.merge(upper);
+            }
+            3 => {
+                let k = rng.gen_range(0..=std_map.len());
+                assert_eq!(our_map.select(k), std_map.iter().nth(k));
+                assert_eq!(our_map.rank(&key), std_map.range(..key).count());
+            }
+            _ => unreachable!(),
+        }
+    }
+    our_map.debug_validate();
+    assert!(our_map.iter().eq(std_map.iter()));
+}
+
+/// Replay a random insert/contains/remove sequence against a u64 successor
+/// set and std, with keys drawn from `0..$range` or, when the range is 0,
+/// from the whole of u64 mixed with both extremes.
+macro_rules! successor_oracle {
+    ($name:ident, $set:ty, $range:expr) => {
+        #[test]
+        fn $name() {
+            use rand::rngs::StdRng;
+            use rand::{Rng, SeedableRng};
+
+            let mut rng = StdRng::seed_from_u64(42);
+            let mut our_set = <$set>::new();
+            let mut std_set: BTreeSet<u64> = BTreeSet::new();
+            let mut inserted = Vec::new();
+            let draw = |rng: &mut StdRng, inserted: &Vec<u64>| -> u64 {
+                let range: u64 = $range;
+                if range > 0 {
+                    return rng.gen_range(0..range);
+                }
+                match rng.gen_range(0..8) {
+                    0 => 0,
+                    1 => u64::MAX,
+                    2 if !inserted.is_empty() => inserted[rng.gen_range(0..inserted.len())],
+                    _ => rng.gen(),
+                }
+            };
+
+            for _ in 0..20000 {
+                let key = draw(&mut rng, &inserted);
+                match rng.gen_range(0..4) {
+                    0 | 1 => {
+                        assert_eq!(our_set.insert(key), std_set.insert(key));
+                        inserted.push(key);
+                    }
+                    2 => assert_eq!(our_set.remove(key), std_set.remove(&key)),
+                    3 => assert_eq!(our_set.contains(key), std_set.contains(&key)),
+                    _ => unreachable!(),
+                }
+                let query = draw(&mut rng, &inserted);
+                let after = (Bound::Excluded(query), Bound::Unbounded);
+                assert_eq!(
+                    our_set.successor(query),
+                    std_set.range(after).next().copied()
+                );
+                assert_eq!(
+                    our_set.predecessor(query),
+                    std_set.range(..query).next_back().copied()
+                );
+            }
+
+            assert_eq!(our_set.len(), std_set.len());
+            assert_eq!(our_set.min(), std_set.first().copied());
+            assert_eq!(our_set.max(), std_set.last().copied());
+            assert!(our_set.iter().eq(std_set.iter().copied()));
+        }
+    };
+}
+
+successor_oracle!(test_oracle_x_fast_trie_small_keys, XFastTrie, 2000);
+successor_oracle!(test_oracle_x_fast_trie_full_range, XFastTrie, 0);
+successor_oracle!(test_oracle_y_fast_trie_small_keys, YFastTrie, 2000);
+successor_oracle!(test_oracle_y_fast_trie_full_range, YFastTrie, 0);
+
+#[test]
+fn test_oracle_order_statistics_select_and_rank() {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut our_map = OrderStatisticsMap::new();
+    let mut std_map: StdBTreeMap<u32, u32> = StdBTreeMap::new();
+
+    for step in 0..20000 {
+        let key = rng.gen_range(0..1000);
+        match rng.gen_range(0..3) {
+            0 => assert_eq!(our_map.insert(key, step), std_map.insert(key, step)),
+            1 => assert_eq!(our_map.remove(&key), std_map.remove(&key)),
+            2 => {
+                let k = rng.gen_range(0..=std_map.len());
+                let expected = std_map.iter().nth(k).map(|(&key, &value)| (key, value));
+                assert_eq!(our_map.remove_nth(k), expected);
+                if let Some((key, _)) = expected {
+                    std_map.remove(&key);
+                }
+            }
+            _ => unreachable!(),
+        }
+
+        let k = rng.gen_range(0..=std_map.len());
+        assert_eq!(our_map.select(k), std_map.iter().nth(k));
+        assert_eq!(our_map.rank(&key), std_map.range(..key).count());
+    }
+}
+
+#[test]
+fn test_oracle_persistent_sorted_versions() {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut versions = vec![(PersistentSortedMap::new(), StdBTreeMap::new())];
+
+    for step in 0..5000u32 {
+        // Branch off a random earlier version, not always the latest.
+        let (base, base_std) = if rng.gen_bool(0.8) {
+            versions.last().expect("starts with the empty version")
+        } else {
+            &versions[rng.gen_range(0..versions.len())]
+        };
+        let key = rng.gen_range(0..1000u32);
+        let mut std_map = base_std.clone();
+        let map = if rng.gen_range(0..3) < 2 {
+            std_map.insert(key, step);
+            base.insert(key, step)
+        } else {
+            std_map.remove(&key);
+            base.remove(&key)
+        };
+        assert_eq!(map.len(), std_map.len());
+        assert_eq!(map.get(&key), std_map.get(&key));
+        let k = rng.gen_range(0..=std_map.len());
+        assert_eq!(map.select(k), std_map.iter().nth(k));
+        assert_eq!(map.rank(&key), std_map.range(..key).count());
+        versions.push((map, std_map));
+    }
+
+    for (map, std_map) in versions.iter().step_by(97) {
+        map.debug_validate();
+        assert!(map.iter().eq(std_map.iter()));
+        assert!(map.range(250..750).eq(std_map.range(250..750)));
+    }
+}
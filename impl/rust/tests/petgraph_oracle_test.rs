@@ -0,0 +1,296 @@
+//! Oracle tests running the graph algorithms against petgraph's on the same
+//! random graphs, the way the maps are checked against std
+//!
+//! petgraph is a dev-dependency only; nothing outside the tests uses it.
+
+use dsa_lab::{
+    bellman_ford, dijkstra, floyd_warshall, kosaraju_scc, prim, random_dag, tarjan_scc, toposort,
+    Graph, NodeId, Relaxation, StrongComponents,
+};
+use petgraph::graph::NodeIndex;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::BTreeSet;
+
+type Reference = petgraph::Graph<(), i64>;
+
+/// Random edges on `n` nodes, with weights drawn from `weights`.
+fn random_edges(
+    rng: &mut StdRng,
+    n: usize,
+    edges: usize,
+    weights: std::ops::Range<i64>,
+) -> Vec<(usize, usize, i64)> {
+    (0..edges)
+        .map(|_| {
+            let (u, v) = (rng.gen_range(0..n), rng.gen_range(0..n));
+            (u, v, rng.gen_range(weights.clone()))
+        })
+        .collect()
+}
+
+/// The same graph for both libraries: node `i` is `nodes[i]` here and
+/// `NodeIndex::new(i)` in petgraph. An undirected edge becomes a pair of
+/// opposite edges in petgraph, which is the same to every algorithm
+/// checked here.
+fn build(
+    directed: bool,
+    n: usize,
+    edges: &[(usize, usize, i64)],
+) -> (Graph<(), i64>, Vec<NodeId>, Reference) {
+    let mut graph = if directed {
+        Graph::directed()
+    } else {
+        Graph::undirected()
+    };
+    let mut reference = Reference::new();
+    let nodes: Vec<_> = (0..n).map(|_| graph.add_node(())).collect();
+    for _ in 0..n {
+        reference.add_node(());
+    }
+    for &(u, v, weight) in edges {
+        graph.add_edge(nodes[u], nodes[v], weight);
+        reference.add_edge(NodeIndex::new(u), NodeIndex::new(v), weight);
+        if !directed {
+            reference.add_edge(NodeIndex::new(v), NodeIndex::new(u), weight);
+        }
+    }
+    (graph, nodes, reference)
+}
+
+/// A random graph for both libraries.
+fn random_pair(
+    rng: &mut StdRng,
+    directed: bool,
+    n: usize,
+    edges: usize,
+    weights: std::ops::Range<i64>,
+) -> (Graph<(), i64>, Vec<NodeId>, Reference) {
+    build(directed, n, &random_edges(rng, n, edges, weights))
+}
+
+/// The components as a set of sets of node positions, to compare
+/// partitions whatever order each library lists them in.
+fn partition(components: impl Iterator<Item = Vec<usize>>) -> BTreeSet<BTreeSet<usize>> {
+    components
+        .map(|component| component.into_iter().collect())
+        .collect()
+}
+
+fn our_partition(components: &StrongComponents, position: &[usize]) -> BTreeSet<BTreeSet<usize>> {
+    partition(
+        components
+            .iter()
+            .map(|nodes| nodes.iter().map(|node| position[node.index()]).collect()),
+    )
+}
+
+/// Each node's position in `nodes`, by index.
+fn positions(graph: &Graph<(), i64>, nodes: &[NodeId]) -> Vec<usize> {
+    let mut position = vec![usize::MAX; graph.node_bound()];
+    for (i, node) in nodes.iter().enumerate() {
+        position[node.index()] = i;
+    }
+    position
+}
+
+#[test]
+fn test_petgraph_dijkstra() {
+    let mut rng = StdRng::seed_from_u64(50);
+    for round in 0..1000 {
+        let n = rng.gen_range(1..80);
+        let edges = rng.gen_range(0..4 * n);
+        let (graph, nodes, reference) = random_pair(&mut rng, round % 3 != 0, n, edges, 0..100);
+        let source = rng.gen_range(0..n);
+        let ours = dijkstra(&graph, nodes[source]);
+        let theirs = petgraph::algo::dijkstra(&reference, NodeIndex::new(source), None, |edge| {
+            *edge.weight()
+        });
+        for (i, &node) in nodes.iter().enumerate() {
+            assert_eq!(
+                ours.distance(node),
+                theirs.get(&NodeIndex::new(i)).copied(),
+                "round {}",
+                round
+            );
+        }
+    }
+}
+
+#[test]
+fn test_petgraph_bellman_ford() {
+    let mut rng = StdRng::seed_from_u64(51);
+    let mut cycles = 0;
+    for round in 0..1000 {
+        let n = rng.gen_range(1..50);
+        let edges = rng.gen_range(0..3 * n);
+        let (graph, nodes, reference) = random_pair(&mut rng, true, n, edges, -20..100);
+        // petgraph's Bellman-Ford wants float weights; small integer sums
+        // are exact in f64.
+        let float = reference.map(|_, _| (), |_, &weight| weight as f64);
+        let source = rng.gen_range(0..n);
+        let theirs = petgraph::algo::bellman_ford(&float, NodeIndex::new(source));
+        for relaxation in [Relaxation::Rounds, Relaxation::Spfa] {
+            match (bellman_ford(&graph, nodes[source], relaxation), &theirs) {
+                (Ok(ours), Ok(theirs)) => {
+                    for (i, &node) in nodes.iter().enumerate() {
+                        let expected = Some(theirs.distances[i])
+                            .filter(|d| d.is_finite())
+                            .map(|d| d as i64);
+                        assert_eq!(ours.distance(node), expected, "round {}", round);
+                    }
+                }
+                (Err(_), Err(_)) => cycles += 1,
+                _ => panic!("round {}: disagree on negative cycles", round),
+            }
+        }
+    }
+    assert!(cycles > 100, "only {} negative cycles", cycles);
+}
+
+#[test]
+fn test_petgraph_floyd_warshall() {
+    let mut rng = StdRng::seed_from_u64(52);
+    for round in 0..500 {
+        let n = rng.gen_range(1..25);
+        let edges = rng.gen_range(0..3 * n);
+        let low = if round % 2 == 0 { 0 } else { -10 };
+        // petgraph's Floyd-Warshall keeps only the last of parallel edges
+        // and overwrites self-loops with zero, so give it neither.
+        let mut seen = BTreeSet::new();
+        let edges: Vec<_> = random_edges(&mut rng, n, edges, low..100)
+            .into_iter()
+            .filter(|&(u, v, _)| u != v && seen.insert((u, v)))
+            .collect();
+        let (graph, nodes, reference) = build(true, n, &edges);
+        let ours = floyd_warshall(&graph);
+        let theirs = petgraph::algo::floyd_warshall(&reference, |edge| *edge.weight());
+        match (ours, theirs) {
+            (Ok(ours), Ok(theirs)) => {
+                for (i, &u) in nodes.iter().enumerate() {
+                    for (j, &v) in nodes.iter().enumerate() {
+                        let expected = theirs[&(NodeIndex::new(i), NodeIndex::new(j))];
+                        // It marks unreachable pairs with i64::MAX, but adds
+                        // negative weights to that without overflowing, so
+                        // anything near it is unreachable too.
+                        let expected = Some(expected).filter(|&d| d < i64::MAX / 2);
+                        assert_eq!(ours.distance(u, v), expected, "round {}", round);
+                    }
+                }
+            }
+            (Err(_), Err(_)) => {}
+            _ => panic!("round {}: disagree on negative cycles", round),
+        }
+    }
+}
+
+#[test]
+fn test_petgraph_scc() {
+    let mut rng = StdRng::seed_from_u64(53);
+    for round in 0..2000 {
+        let n = rng.gen_range(1..60);
+        let edges = rng.gen_range(0..2 * n);
+        let (mut graph, mut nodes, mut reference) =
+            random_pair(&mut rng, round % 4 != 0, n, edges, 0..1);
+        // Leave a hole in our node indices; petgraph shifts its last node
+        // into the gap, so the positions follow suit.
+        if n > 2 && rng.gen() {
+            let gone = rng.gen_range(0..n);
+            graph.remove_node(nodes.swap_remove(gone));
+            reference.remove_node(NodeIndex::new(gone));
+        }
+        let position = positions(&graph, &nodes);
+        let expected = partition(
+            petgraph::algo::tarjan_scc(&reference)
+                .into_iter()
+                .map(|component| component.iter().map(|node| node.index()).collect()),
+        );
+        assert_eq!(our_partition(&tarjan_scc(&graph), &position), expected);
+        assert_eq!(our_partition(&kosaraju_scc(&graph), &position), expected);
+    }
+}
+
+#[test]
+fn test_petgraph_topological_order() {
+    let mut rng = StdRng::seed_from_u64(54);
+    for round in 0..1000 {
+        let n = rng.gen_range(1..60);
+        // Half the graphs are acyclic by construction, the rest random and
+        // sparse enough that many of them are too.
+        let edges = if round % 2 == 0 {
+            let dag = random_dag(n, rng.gen_range(0.0..0.3), round, |_| 0i64);
+            dag.edges()
+                .map(|edge| (edge.source.index(), edge.target.index(), 0))
+                .collect()
+        } else {
+            let edges = rng.gen_range(0..n);
+            random_edges(&mut rng, n, edges, 0..1)
+        };
+        let (graph, _, reference) = build(true, n, &edges);
+        let ours = toposort(&graph);
+        let theirs = petgraph::algo::toposort(&reference, None);
+        assert_eq!(ours.is_ok(), theirs.is_ok(), "round {}", round);
+        match ours {
+            // petgraph's order may differ, so check ours edge by edge.
+            Ok(order) => {
+                assert_eq!(order.len(), n, "round {}", round);
+                let mut rank = vec![usize::MAX; graph.node_bound()];
+                for (i, node) in order.iter().enumerate() {
+                    rank[node.index()] = i;
+                }
+                for edge in graph.edges() {
+                    assert!(
+                        rank[edge.source.index()] < rank[edge.target.index()],
+                        "round {}",
+                        round
+                    );
+                }
+            }
+            Err(cycle) => {
+                for (i, &node) in cycle.nodes.iter().enumerate() {
+                    let next = cycle.nodes[(i + 1) % cycle.nodes.len()];
+                    assert!(graph.find_edge(node, next).is_some(), "round {}", round);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_petgraph_minimum_spanning_tree() {
+    let mut rng = StdRng::seed_from_u64(55);
+    for round in 0..1000 {
+        let n = rng.gen_range(1..60);
+        // Sparse graphs leave several components, so this checks forests.
+        let edges = rng.gen_range(0..3 * n);
+        let (graph, nodes, reference) = random_pair(&mut rng, false, n, edges, -50..100);
+        let ours = prim(&graph);
+        // petgraph's Kruskal sees each of our edges twice, which changes
+        // neither the forest's weight nor its size.
+        let theirs: Vec<i64> = petgraph::algo::min_spanning_tree(&reference)
+            .filter_map(|element| match element {
+                petgraph::data::Element::Edge { weight, .. } => Some(weight),
+                petgraph::data::Element::Node { .. } => None,
+            })
+            .collect();
+        assert_eq!(ours.weight(), theirs.iter().sum::<i64>(), "round {}", round);
+        assert_eq!(ours.edges().len(), theirs.len(), "round {}", round);
+
+        // The forest's edges connect each component without a cycle.
+        let position = positions(&graph, &nodes);
+        let mut component: Vec<usize> = (0..n).collect();
+        for &edge in ours.edges() {
+            let (u, v) = graph.endpoints(edge).unwrap();
+            let (a, b) = (
+                component[position[u.index()]],
+                component[position[v.index()]],
+            );
+            assert_ne!(a, b, "round {}: cycle", round);
+            for c in &mut component {
+                if *c == b {
+                    *c = a;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,253 @@
+//! Oracle tests comparing the range-query, sliding-window and streaming
+//! quantile structures against brute-force recomputation over a plain Vec
+
+use dsa_lab::monotonic::{next_greater, next_smaller, previous_greater, previous_smaller};
+use dsa_lab::{
+    FenwickTree, Max, Min, Monoid, RunningMedian, SegmentTree, SlidingWindowAggregator,
+    SlidingWindowMinMax, SqrtDecomposition, Sum, WindowQuantiles,
+};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::VecDeque;
+
+/// Composition of affine maps `x -> a * x + b` modulo a prime, applied left
+/// to right. Not commutative, so it catches queries combining out of order.
+struct Affine;
+
+const P: u64 = 1_000_000_007;
+
+impl Monoid for Affine {
+    type Value = (u64, u64);
+
+    fn identity() -> (u64, u64) {
+        (1, 0)
+    }
+
+    fn combine(&(a1, b1): &(u64, u64), &(a2, b2): &(u64, u64)) -> (u64, u64) {
+        (a1 * a2 % P, (b1 * a2 + b2) % P)
+    }
+}
+
+fn brute_force<M: Monoid>(values: &[M::Value]) -> M::Value {
+    values
+        .iter()
+        .fold(M::identity(), |acc, value| M::combine(&acc, value))
+}
+
+/// Interleave random point updates and range queries on a segment tree and
+/// a Vec, for several lengths including non-powers of two.
+fn segment_tree_oracle<M, F>(seed: u64, mut random_value: F)
+where
+    M: Monoid,
+    M::Value: PartialEq + std::fmt::Debug,
+    F: FnMut(&mut StdRng) -> M::Value,
+{
+    let mut rng = StdRng::seed_from_u64(seed);
+    for len in [1, 2, 7, 64, 100, 257] {
+        let mut oracle: Vec<M::Value> = (0..len).map(|_| random_value(&mut rng)).collect();
+        let mut tree: SegmentTree<M> = SegmentTree::from_vec(oracle.clone());
+        for _ in 0..2000 {
+            if rng.gen_bool(0.3) {
+                let index = rng.gen_range(0..len);
+                let value = random_value(&mut rng);
+                oracle[index] = value.clone();
+                tree.set(index, value);
+            } else {
+                let start = rng.gen_range(0..=len);
+                let end = rng.gen_range(start..=len);
+                assert_eq!(
+                    tree.query(start..end),
+                    brute_force::<M>(&oracle[start..end]),
+                    "len {} range {}..{}",
+                    len,
+                    start,
+                    end
+                );
+            }
+        }
+        assert_eq!(tree.query(..), brute_force::<M>(&oracle));
+    }
+}
+
+#[test]
+fn test_oracle_segment_tree_sum() {
+    segment_tree_oracle::<Sum<i64>, _>(42, |rng| rng.gen_range(-1000..1000));
+}
+
+#[test]
+fn test_oracle_segment_tree_min_max() {
+    segment_tree_oracle::<Min<u32>, _>(43, |rng| rng.gen_range(0..1000));
+    segment_tree_oracle::<Max<i32>, _>(44, |rng| rng.gen_range(-1000..1000));
+}
+
+#[test]
+fn test_oracle_segment_tree_non_commutative() {
+    segment_tree_oracle::<Affine, _>(45, |rng| (rng.gen_range(0..P), rng.gen_range(0..P)));
+}
+
+#[test]
+fn test_oracle_fenwick_tree() {
+    let mut rng = StdRng::seed_from_u64(46);
+    for len in [1, 2, 7, 64, 100, 257] {
+        let mut oracle: Vec<u64> = (0..len).map(|_| rng.gen_range(0..10)).collect();
+        let mut tree = FenwickTree::from_vec(oracle.clone());
+        for _ in 0..2000 {
+            match rng.gen_range(0..3) {
+                0 => {
+                    let index = rng.gen_range(0..len);
+                    let delta = rng.gen_range(0..10);
+                    oracle[index] += delta;
+                    tree.add(index, delta);
+                }
+                1 => {
+                    let start = rng.gen_range(0..=len);
+                    let end = rng.gen_range(start..=len);
+                    assert_eq!(tree.range_sum(start..end), oracle[start..end].iter().sum());
+                }
+                2 => {
+                    let total: u64 = oracle.iter().sum();
+                    let target = rng.gen_range(1..=total + 1);
+                    let expected = oracle
+                        .iter()
+                        .scan(0, |sum, &value| {
+                            *sum += value;
+                            Some(*sum)
+                        })
+                        .position(|sum| sum >= target);
+                    assert_eq!(tree.kth(target), expected);
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_oracle_sqrt_decomposition() {
+    let mut rng = StdRng::seed_from_u64(47);
+    for len in [1, 2, 7, 64, 100, 257] {
+        for block_size in [1, 3, 16, 300] {
+            let mut oracle: Vec<(u64, u64)> = (0..len)
+                .map(|_| (rng.gen_range(0..P), rng.gen_range(0..P)))
+                .collect();
+            let mut blocks: SqrtDecomposition<Affine> =
+                SqrtDecomposition::with_block_size(oracle.clone(), block_size);
+            for _ in 0..500 {
+                if rng.gen_bool(0.3) {
+                    let index = rng.gen_range(0..len);
+                    let value = (rng.gen_range(0..P), rng.gen_range(0..P));
+                    oracle[index] = value;
+                    blocks.set(index, value);
+                } else {
+                    let start = rng.gen_range(0..=len);
+                    let end = rng.gen_range(start..=len);
+                    assert_eq!(
+                        blocks.query(start..end),
+                        brute_force::<Affine>(&oracle[start..end])
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_oracle_sliding_window_min_max() {
+    let mut rng = StdRng::seed_from_u64(100);
+    for _ in 0..200 {
+        let width = rng.gen_range(1..20);
+        let range = rng.gen_range(1..50);
+        let mut window = SlidingWindowMinMax::new(width);
+        let mut values: Vec<i32> = Vec::new();
+        for _ in 0..rng.gen_range(0..200) {
+            let value = rng.gen_range(0..range);
+            window.push(value);
+            values.push(value);
+            let last = &values[values.len().saturating_sub(width)..];
+            assert_eq!(window.min(), last.iter().min());
+            assert_eq!(window.max(), last.iter().max());
+            assert_eq!(window.len(), last.len());
+        }
+    }
+}
+
+#[test]
+fn test_oracle_streaming_quantiles() {
+    let mut rng = StdRng::seed_from_u64(105);
+    for _ in 0..100 {
+        let width = rng.gen_range(1..40);
+        let range = rng.gen_range(1..100);
+        let mut window = WindowQuantiles::new(width);
+        let mut median = RunningMedian::new();
+        let mut values: Vec<i32> = Vec::new();
+        for _ in 0..rng.gen_range(0..300) {
+            let value = rng.gen_range(0..range);
+            window.push(value);
+            median.push(value);
+            values.push(value);
+
+            let mut all = values.clone();
+            all.sort_unstable();
+            assert_eq!(median.median(), Some(&all[(all.len() - 1) / 2]));
+            assert_eq!(median.upper_median(), Some(&all[all.len() / 2]));
+
+            let mut last = values[values.len().saturating_sub(width)..].to_vec();
+            last.sort_unstable();
+            assert_eq!(window.median(), Some(&last[(last.len() - 1) / 2]));
+            let q: f64 = rng.gen();
+            let rank = ((last.len() - 1) as f64 * q).round() as usize;
+            assert_eq!(window.quantile(q), Some(&last[rank]));
+        }
+    }
+}
+
+/// Random pushes and pops on an aggregator and a VecDeque, checking the
+/// aggregate against a fold of the deque after each one.
+fn aggregator_oracle<M, F>(seed: u64, mut random_value: F)
+where
+    M: Monoid,
+    M::Value: PartialEq + std::fmt::Debug,
+    F: FnMut(&mut StdRng) -> M::Value,
+{
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut queue = SlidingWindowAggregator::<M>::new();
+    let mut expected: VecDeque<M::Value> = VecDeque::new();
+    for _ in 0..5000 {
+        // Lean towards pushing, with stretches of popping as the balance
+        // drifts, so the front stack is refilled at many sizes.
+        if rng.gen_bool(0.55) {
+            let value = random_value(&mut rng);
+            queue.push(value.clone());
+            expected.push_back(value);
+        } else {
+            assert_eq!(queue.pop(), expected.pop_front());
+        }
+        let values: Vec<M::Value> = expected.iter().cloned().collect();
+        assert_eq!(queue.query(), brute_force::<M>(&values));
+        assert_eq!(queue.len(), expected.len());
+    }
+}
+
+#[test]
+fn test_oracle_sliding_window_aggregator() {
+    aggregator_oracle::<Sum<i64>, _>(101, |rng| rng.gen_range(-100..100));
+    aggregator_oracle::<Min<i64>, _>(102, |rng| rng.gen_range(-100..100));
+    aggregator_oracle::<Affine, _>(103, |rng| (rng.gen_range(0..P), rng.gen_range(0..P)));
+}
+
+#[test]
+fn test_oracle_nearest_elements() {
+    let mut rng = StdRng::seed_from_u64(104);
+    for _ in 0..300 {
+        let n = rng.gen_range(0..100);
+        let range = rng.gen_range(1..20);
+        let v: Vec<u32> = (0..n).map(|_| rng.gen_range(0..range)).collect();
+        let before = |i: usize, pred: &dyn Fn(u32) -> bool| (0..i).rev().find(|&j| pred(v[j]));
+        let after = |i: usize, pred: &dyn Fn(u32) -> bool| (i + 1..n).find(|&j| pred(v[j]));
+        let scan = |f: &dyn Fn(usize) -> Option<usize>| (0..n).map(f).collect::<Vec<_>>();
+        assert_eq!(previous_smaller(&v), scan(&|i| before(i, &|x| x < v[i])));
+        assert_eq!(next_smaller(&v), scan(&|i| after(i, &|x| x < v[i])));
+        assert_eq!(previous_greater(&v), scan(&|i| before(i, &|x| x > v[i])));
+        assert_eq!(next_greater(&v), scan(&|i| after(i, &|x| x > v[i])));
+    }
+}
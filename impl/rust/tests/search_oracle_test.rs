@@ -0,0 +1,101 @@
+//! Oracle tests comparing the searches against linear scans
+
+use dsa_lab::search::{
+    binary_search, exponential_search, interpolation_search, lower_bound, partition_point,
+    partition_point_range, ternary_search, upper_bound,
+};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// The first index holding `x`, or where it would go, by scanning.
+fn scan<T: Ord>(v: &[T], x: &T) -> Result<usize, usize> {
+    match v.iter().position(|y| y >= x) {
+        Some(i) if v[i] == *x => Ok(i),
+        Some(i) => Err(i),
+        None => Err(v.len()),
+    }
+}
+
+/// Sorted keys of one of several shapes: dense with duplicates, uniform
+/// over a wide range, or skewed towards small values.
+fn sorted_keys(rng: &mut StdRng, shape: usize, n: usize) -> Vec<i64> {
+    let mut v: Vec<i64> = (0..n)
+        .map(|_| match shape {
+            0 => rng.gen_range(-20..20),
+            1 => rng.gen_range(i64::MIN / 2..i64::MAX / 2),
+            _ => 1 << rng.gen_range(0..62),
+        })
+        .collect();
+    v.sort_unstable();
+    v
+}
+
+#[test]
+fn test_oracle_slice_searches() {
+    let mut rng = StdRng::seed_from_u64(90);
+    for round in 0..600 {
+        let n = rng.gen_range(0..300);
+        let v = sorted_keys(&mut rng, round % 3, n);
+        for _ in 0..20 {
+            // Half the probes are present, half arbitrary.
+            let x = if !v.is_empty() && rng.gen() {
+                v[rng.gen_range(0..n)]
+            } else {
+                match round % 3 {
+                    0 => rng.gen_range(-25..25),
+                    _ => rng.gen(),
+                }
+            };
+            let expected = scan(&v, &x);
+            assert_eq!(binary_search(&v, &x), expected, "binary");
+            assert_eq!(exponential_search(&v, &x), expected, "exponential");
+            assert_eq!(interpolation_search(&v, &x), expected, "interpolation");
+            assert_eq!(lower_bound(&v, &x), v.iter().filter(|&&y| y < x).count());
+            assert_eq!(upper_bound(&v, &x), v.iter().filter(|&&y| y <= x).count());
+        }
+    }
+}
+
+#[test]
+fn test_oracle_partition_points() {
+    let mut rng = StdRng::seed_from_u64(91);
+    for _ in 0..500 {
+        let n = rng.gen_range(0..200);
+        let split = rng.gen_range(0..=n);
+        let v: Vec<bool> = (0..n).map(|i| i < split).collect();
+        assert_eq!(partition_point(&v, |&b| b), split);
+
+        let start = rng.gen_range(0..1_000u64);
+        let end = start + rng.gen_range(0..1_000);
+        let threshold = rng.gen_range(0..2_000);
+        let expected = (start..end).find(|&x| x >= threshold).unwrap_or(end);
+        assert_eq!(
+            partition_point_range(start..end, |x| x < threshold),
+            expected
+        );
+    }
+}
+
+#[test]
+fn test_oracle_ternary_search() {
+    let mut rng = StdRng::seed_from_u64(92);
+    for _ in 0..500 {
+        let start = rng.gen_range(0..1_000u64);
+        let end = start + rng.gen_range(1..2_000);
+        let peak = rng.gen_range(start..end);
+        // Strictly up to the peak and strictly down after, at different
+        // slopes on each side.
+        let (up, down) = (rng.gen_range(1..5i64), rng.gen_range(1..5i64));
+        let f = |x: u64| {
+            let d = x as i64 - peak as i64;
+            if d <= 0 {
+                d * up
+            } else {
+                -d * down
+            }
+        };
+        let expected = (start..end).max_by_key(|&x| f(x)).unwrap();
+        assert_eq!(ternary_search(start..end, f), expected);
+        assert_eq!(expected, peak);
+    }
+}
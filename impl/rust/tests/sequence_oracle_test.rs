@@ -0,0 +1,202 @@
+//! Oracle tests comparing the sequence containers against std::collections
+//! and plain vectors
+
+use dsa_lab::{Deque, DynArray, GrowthPolicy, OrderKey, OrderList, WeightBalancedSeq};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+
+#[test]
+fn test_oracle_deque_mixed_operations() {
+    let mut rng = StdRng::seed_from_u64(50);
+    let mut deque = Deque::new();
+    let mut oracle = VecDeque::new();
+    for step in 0..20_000 {
+        match rng.gen_range(0..10) {
+            0..=2 => {
+                let value: u32 = rng.gen();
+                deque.push_back(value);
+                oracle.push_back(value);
+            }
+            3..=4 => {
+                let value: u32 = rng.gen();
+                deque.push_front(value);
+                oracle.push_front(value);
+            }
+            5 => assert_eq!(deque.pop_back(), oracle.pop_back()),
+            6 => assert_eq!(deque.pop_front(), oracle.pop_front()),
+            7 if !oracle.is_empty() => {
+                let index = rng.gen_range(0..oracle.len());
+                let value: u32 = rng.gen();
+                deque[index] = value;
+                oracle[index] = value;
+            }
+            8 => {
+                let index = rng.gen_range(0..=oracle.len());
+                assert_eq!(deque.get(index), oracle.get(index));
+            }
+            9 if rng.gen_ratio(1, 200) => {
+                deque.clear();
+                oracle.clear();
+            }
+            _ => {}
+        }
+        assert_eq!(deque.len(), oracle.len());
+        assert_eq!(deque.front(), oracle.front());
+        assert_eq!(deque.back(), oracle.back());
+        if step % 1000 == 0 {
+            assert!(deque.iter().eq(oracle.iter()));
+            assert!(deque.iter().rev().eq(oracle.iter().rev()));
+        }
+    }
+    assert!(deque.iter().eq(oracle.iter()));
+}
+
+#[test]
+fn test_oracle_dyn_array_per_growth_policy() {
+    for policy in [
+        GrowthPolicy::Doubling,
+        GrowthPolicy::OneAndHalf,
+        GrowthPolicy::GoldenRatio,
+        GrowthPolicy::ExactFit,
+        GrowthPolicy::Primes,
+    ] {
+        let mut rng = StdRng::seed_from_u64(55);
+        let mut array = DynArray::with_growth_policy(policy);
+        let mut oracle = Vec::new();
+        for _ in 0..5000 {
+            match rng.gen_range(0..8) {
+                0..=4 => {
+                    let value: u32 = rng.gen();
+                    array.push(value);
+                    oracle.push(value);
+                }
+                5 => assert_eq!(array.pop(), oracle.pop()),
+                6 if !oracle.is_empty() => {
+                    let index = rng.gen_range(0..oracle.len());
+                    let value: u32 = rng.gen();
+                    array[index] = value;
+                    oracle[index] = value;
+                }
+                _ => {
+                    let index = rng.gen_range(0..=oracle.len());
+                    assert_eq!(array.get(index), oracle.get(index));
+                }
+            }
+            assert!(array.len() <= array.capacity());
+        }
+        assert_eq!(&array[..], &oracle[..], "{:?}", policy);
+    }
+}
+
+#[test]
+fn test_oracle_weight_balanced_seq_splicing() {
+    let mut rng = StdRng::seed_from_u64(60);
+    let mut seq = WeightBalancedSeq::new();
+    let mut oracle = Vec::new();
+    for step in 0..20_000 {
+        match rng.gen_range(0..10) {
+            0..=3 => {
+                let index = rng.gen_range(0..=oracle.len());
+                let value: u32 = rng.gen();
+                seq.insert(index, value);
+                oracle.insert(index, value);
+            }
+            4..=5 if !oracle.is_empty() => {
+                let index = rng.gen_range(0..oracle.len());
+                assert_eq!(seq.remove(index), Some(oracle.remove(index)));
+            }
+            6 => {
+                let index = rng.gen_range(0..=oracle.len());
+                assert_eq!(seq.get(index), oracle.get(index));
+            }
+            7 => {
+                // Cut out a random slice and splice it back in elsewhere.
+                let start = rng.gen_range(0..=oracle.len());
+                let end = rng.gen_range(start..=oracle.len());
+                let mut tail = seq.split_off(end);
+                let mut middle = seq.split_off(start);
+                seq.append(&mut tail);
+                let at = rng.gen_range(0..=seq.len());
+                let mut rest = seq.split_off(at);
+                seq.append(&mut middle);
+                seq.append(&mut rest);
+                let slice: Vec<u32> = oracle.drain(start..end).collect();
+                oracle.splice(at..at, slice);
+            }
+            8 => {
+                let items: Vec<u32> = (0..rng.gen_range(0..50)).map(|_| rng.gen()).collect();
+                let mut other: WeightBalancedSeq<u32> = items.iter().copied().collect();
+                seq.append(&mut other);
+                oracle.extend(items);
+            }
+            9 if rng.gen_ratio(1, 200) => {
+                seq.clear();
+                oracle.clear();
+            }
+            _ => {}
+        }
+        assert_eq!(seq.len(), oracle.len());
+        if step % 500 == 0 {
+            seq.debug_validate();
+            assert!(seq.iter().eq(oracle.iter()));
+        }
+    }
+    seq.debug_validate();
+    assert!(seq.iter().eq(oracle.iter()));
+}
+
+#[test]
+fn test_oracle_order_list() {
+    let mut rng = StdRng::seed_from_u64(190);
+    let mut list = OrderList::new();
+    // The keys in list order, with the value stored under each.
+    let mut oracle: Vec<(OrderKey, u32)> = Vec::new();
+    for step in 0..20_000 {
+        let value: u32 = rng.gen();
+        match rng.gen_range(0..10) {
+            // Mostly insertions next to a few hot spots, which exhausts
+            // the label gaps there and forces relabeling.
+            0..=5 if !oracle.is_empty() => {
+                let at = if rng.gen_bool(0.5) {
+                    rng.gen_range(0..oracle.len().min(3))
+                } else {
+                    rng.gen_range(0..oracle.len())
+                };
+                if rng.gen_bool(0.5) {
+                    let key = list.insert_after(oracle[at].0, value).unwrap();
+                    oracle.insert(at + 1, (key, value));
+                } else {
+                    let key = list.insert_before(oracle[at].0, value).unwrap();
+                    oracle.insert(at, (key, value));
+                }
+            }
+            6 => oracle.push((list.push_back(value), value)),
+            7 => oracle.insert(0, (list.push_front(value), value)),
+            _ if !oracle.is_empty() => {
+                let (key, value) = oracle.remove(rng.gen_range(0..oracle.len()));
+                assert_eq!(list.remove(key), Some(value));
+                assert_eq!(list.get(key), None);
+            }
+            _ => {}
+        }
+
+        assert_eq!(list.len(), oracle.len());
+        if !oracle.is_empty() {
+            let (i, j) = (
+                rng.gen_range(0..oracle.len()),
+                rng.gen_range(0..oracle.len()),
+            );
+            assert_eq!(list.order(oracle[i].0, oracle[j].0), Some(i.cmp(&j)));
+        }
+        if step % 1_000 == 0 {
+            let actual: Vec<(OrderKey, u32)> = list.iter().map(|(k, &v)| (k, v)).collect();
+            assert_eq!(actual, oracle);
+            for pair in oracle.windows(2) {
+                assert_eq!(list.order(pair[0].0, pair[1].0), Some(Ordering::Less));
+            }
+        }
+    }
+    assert!(list.relabel_count() > 0);
+}
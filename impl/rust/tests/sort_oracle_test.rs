@@ -0,0 +1,223 @@
+//! Oracle tests comparing the comparison and radix sorts against std's
+//! slice::sort on random and adversarial inputs
+
+use dsa_lab::radix_sort::{counting_sort, lsd_radix_sort, msd_radix_sort, RadixKey};
+use dsa_lab::sort::{self, Algorithm};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Inputs known to trip up one sort or another, of length `n`.
+fn adversarial(n: usize) -> Vec<(&'static str, Vec<u32>)> {
+    let n32 = n as u32;
+    vec![
+        ("sorted", (0..n32).collect()),
+        ("reversed", (0..n32).rev().collect()),
+        ("all equal", vec![7; n]),
+        ("organ pipe", (0..n32).map(|i| i.min(n32 - 1 - i)).collect()),
+        ("sawtooth", (0..n32).map(|i| i % 17).collect()),
+        ("few unique", (0..n32).map(|i| (i * 7919) % 3).collect()),
+        ("sorted then one", {
+            let mut v: Vec<u32> = (1..n32).collect();
+            v.push(0);
+            v
+        }),
+        ("median of three killer", median_of_three_killer(n)),
+    ]
+}
+
+/// Musser's input built to make the textbook median-of-three quicksort
+/// pick the second smallest element as pivot at every step.
+fn median_of_three_killer(n: usize) -> Vec<u32> {
+    let k = n / 2;
+    let mut v = vec![0; n];
+    for i in 1..=k {
+        if i % 2 == 1 {
+            v[i - 1] = i as u32;
+            v[i] = (k + i) as u32;
+        }
+        v[k + i - 1] = (2 * i) as u32;
+    }
+    v
+}
+
+#[test]
+fn test_oracle_sorts_adversarial() {
+    for n in [0, 1, 2, 3, 16, 17, 100, 1024, 4000] {
+        for (name, input) in adversarial(n) {
+            let mut expected = input.clone();
+            expected.sort();
+            for algorithm in Algorithm::ALL {
+                if algorithm == Algorithm::Insertion && n > 1024 {
+                    continue;
+                }
+                let mut v = input.clone();
+                algorithm.sort(&mut v);
+                assert_eq!(v, expected, "{:?} on {} of {}", algorithm, name, n);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_oracle_sorts_random() {
+    let mut rng = StdRng::seed_from_u64(70);
+    for round in 0..500 {
+        let n = rng.gen_range(0..600);
+        // Narrow ranges give many duplicates, wide ones almost none.
+        let range = [2, 50, u32::MAX][round % 3];
+        let input: Vec<u32> = (0..n).map(|_| rng.gen_range(0..range)).collect();
+        let mut expected = input.clone();
+        expected.sort_unstable();
+        for algorithm in Algorithm::ALL {
+            let mut v = input.clone();
+            algorithm.sort(&mut v);
+            assert_eq!(v, expected, "{:?} in round {}", algorithm, round);
+        }
+        let mut v = input.clone();
+        sort::sort(&mut v);
+        assert_eq!(v, expected);
+    }
+}
+
+/// Ordered by `key` alone, so equal keys with different tags show whether a
+/// sort kept them in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Tagged {
+    key: u8,
+    tag: u32,
+}
+
+impl PartialOrd for Tagged {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Tagged {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+#[test]
+fn test_oracle_stable_sorts_match_std() {
+    let mut rng = StdRng::seed_from_u64(71);
+    for _ in 0..300 {
+        let n = rng.gen_range(0..500);
+        let input: Vec<Tagged> = (0..n)
+            .map(|tag| Tagged {
+                key: rng.gen_range(0..8),
+                tag,
+            })
+            .collect();
+        // std's sort is stable, so tags must come out in the same order.
+        let mut expected = input.clone();
+        expected.sort();
+        let expected: Vec<_> = expected.iter().map(|t| (t.key, t.tag)).collect();
+        for algorithm in [Algorithm::Insertion, Algorithm::Merge] {
+            let mut v = input.clone();
+            algorithm.sort(&mut v);
+            let v: Vec<_> = v.iter().map(|t| (t.key, t.tag)).collect();
+            assert_eq!(v, expected, "{:?}", algorithm);
+        }
+    }
+}
+
+#[test]
+fn test_oracle_sorts_strings() {
+    let mut rng = StdRng::seed_from_u64(72);
+    for _ in 0..100 {
+        let n = rng.gen_range(0..200);
+        let input: Vec<String> = (0..n)
+            .map(|_| {
+                let len = rng.gen_range(0..6);
+                (0..len)
+                    .map(|_| rng.gen_range(b'a'..b'd') as char)
+                    .collect()
+            })
+            .collect();
+        let mut expected = input.clone();
+        expected.sort();
+        for algorithm in Algorithm::ALL {
+            let mut v = input.clone();
+            algorithm.sort(&mut v);
+            assert_eq!(v, expected, "{:?}", algorithm);
+        }
+    }
+}
+
+/// Check both radix sorts against `sort_unstable` on `input`.
+fn check_radix<T: RadixKey + Ord + Clone + std::fmt::Debug>(input: &[T]) {
+    let mut expected = input.to_vec();
+    expected.sort_unstable();
+    let mut v = input.to_vec();
+    lsd_radix_sort(&mut v);
+    assert_eq!(v, expected, "lsd");
+    let mut v = input.to_vec();
+    msd_radix_sort(&mut v);
+    assert_eq!(v, expected, "msd");
+}
+
+#[test]
+fn test_oracle_radix_sorts() {
+    let mut rng = StdRng::seed_from_u64(73);
+    for round in 0..300 {
+        let n = rng.gen_range(0..2000);
+        // Narrow ranges leave the high bytes shared, which the sorts skip.
+        let bits = [4, 12, 32, 64][round % 4];
+        let mask = if bits == 64 {
+            u64::MAX
+        } else {
+            (1 << bits) - 1
+        };
+        let wide: Vec<u64> = (0..n).map(|_| rng.gen::<u64>() & mask).collect();
+        check_radix(&wide);
+        check_radix(&wide.iter().map(|&x| x as i64).collect::<Vec<_>>());
+        check_radix(&wide.iter().map(|&x| x as u32).collect::<Vec<_>>());
+        check_radix(&wide.iter().map(|&x| x as i16).collect::<Vec<_>>());
+        check_radix(&wide.iter().map(|&x| x as i8).collect::<Vec<_>>());
+        check_radix(
+            &wide
+                .iter()
+                .map(|&x| ((x as u128) << 64 | x.rotate_left(7) as u128) as i128)
+                .collect::<Vec<_>>(),
+        );
+    }
+    for n in [0, 1, 33, 1000] {
+        for (_, input) in adversarial(n) {
+            check_radix(&input);
+        }
+    }
+}
+
+impl RadixKey for Tagged {
+    type Key = u8;
+    fn key(&self) -> u8 {
+        self.key
+    }
+}
+
+#[test]
+fn test_oracle_linear_stable_sorts_match_std() {
+    let mut rng = StdRng::seed_from_u64(74);
+    for _ in 0..300 {
+        let n = rng.gen_range(0..500);
+        let input: Vec<Tagged> = (0..n)
+            .map(|tag| Tagged {
+                key: rng.gen_range(0..8),
+                tag,
+            })
+            .collect();
+        let mut expected = input.clone();
+        expected.sort();
+        let expected: Vec<_> = expected.iter().map(|t| (t.key, t.tag)).collect();
+        let mut v = input.clone();
+        lsd_radix_sort(&mut v);
+        let v: Vec<_> = v.iter().map(|t| (t.key, t.tag)).collect();
+        assert_eq!(v, expected, "lsd");
+        let mut v = input.clone();
+        counting_sort(&mut v, 8, |t| t.key as usize);
+        let v: Vec<_> = v.iter().map(|t| (t.key, t.tag)).collect();
+        assert_eq!(v, expected, "counting");
+    }
+}
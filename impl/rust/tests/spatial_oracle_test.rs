@@ -0,0 +1,179 @@
+//! Oracle tests comparing the spatial indexes against brute-force scans
+
+use dsa_lab::{KdTree, RTree, Rect};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+fn random_points<const K: usize>(rng: &mut StdRng, n: usize, grid: bool) -> Vec<[f64; K]> {
+    (0..n)
+        .map(|_| {
+            let mut point = [0.0; K];
+            for x in &mut point {
+                // Grid points give many ties and duplicates.
+                *x = if grid {
+                    rng.gen_range(0..8) as f64
+                } else {
+                    rng.gen_range(-100.0..100.0)
+                };
+            }
+            point
+        })
+        .collect()
+}
+
+fn distance<const K: usize>(a: &[f64; K], b: &[f64; K]) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| (x - y) * (x - y))
+        .sum::<f64>()
+        .sqrt()
+}
+
+fn check_kd_tree<const K: usize>(seed: u64) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    for round in 0..40 {
+        let n = rng.gen_range(0..400);
+        let points = random_points::<K>(&mut rng, n, round % 2 == 0);
+        let tree: KdTree<usize, K> = points.iter().copied().zip(0..).collect();
+        assert_eq!(tree.len(), n);
+        for query in random_points::<K>(&mut rng, 20, round % 2 == 0) {
+            let k = rng.gen_range(0..12);
+            let mut expected: Vec<f64> = points.iter().map(|p| distance(p, &query)).collect();
+            expected.sort_by(f64::total_cmp);
+            expected.truncate(k);
+            let found = tree.nearest(&query, k);
+            let distances: Vec<f64> = found.iter().map(|&(_, _, d)| d).collect();
+            assert_eq!(distances.len(), expected.len());
+            for (&(point, &index, d), e) in found.iter().zip(&expected) {
+                assert!((d - e).abs() < 1e-9, "{} vs {}", d, e);
+                assert_eq!(point, &points[index]);
+            }
+
+            let mut min = query;
+            let mut max = query;
+            for axis in 0..K {
+                let radius = rng.gen_range(0.0..60.0);
+                min[axis] -= radius;
+                max[axis] += radius;
+            }
+            let mut inside: Vec<usize> = (0..n)
+                .filter(|&i| (0..K).all(|a| min[a] <= points[i][a] && points[i][a] <= max[a]))
+                .collect();
+            let mut found: Vec<usize> = tree.within(&min, &max).iter().map(|&(_, &i)| i).collect();
+            found.sort_unstable();
+            inside.sort_unstable();
+            assert_eq!(found, inside);
+        }
+    }
+}
+
+#[test]
+fn test_oracle_kd_tree_against_brute_force() {
+    check_kd_tree::<1>(72);
+    check_kd_tree::<2>(73);
+    check_kd_tree::<3>(74);
+    check_kd_tree::<5>(75);
+}
+
+fn random_rect<const K: usize>(rng: &mut StdRng) -> Rect<K> {
+    let mut min = [0.0; K];
+    let mut max = [0.0; K];
+    for axis in 0..K {
+        // A third of the boxes are points.
+        let side = if rng.gen_bool(0.3) {
+            0.0
+        } else {
+            rng.gen_range(0.0..15.0)
+        };
+        min[axis] = rng.gen_range(-100.0..100.0);
+        max[axis] = min[axis] + side;
+    }
+    Rect::new(min, max)
+}
+
+fn validate<T, const K: usize>(tree: &RTree<T, K>) {
+    if cfg!(debug_assertions) {
+        tree.debug_validate();
+    }
+}
+
+fn check_r_tree_queries<const K: usize>(
+    rng: &mut StdRng,
+    tree: &RTree<usize, K>,
+    entries: &[(Rect<K>, usize)],
+) {
+    for _ in 0..10 {
+        let window = random_rect::<K>(rng);
+        let mut expected: Vec<usize> = entries
+            .iter()
+            .filter(|(rect, _)| rect.intersects(&window))
+            .map(|&(_, id)| id)
+            .collect();
+        let mut found: Vec<usize> = tree
+            .intersecting(&window)
+            .iter()
+            .map(|&(_, &id)| id)
+            .collect();
+        expected.sort_unstable();
+        found.sort_unstable();
+        assert_eq!(found, expected);
+
+        let point = *random_rect::<K>(rng).min();
+        let k = rng.gen_range(0..10);
+        let mut distances: Vec<f64> = entries
+            .iter()
+            .map(|(rect, _)| rect.distance_to(&point))
+            .collect();
+        distances.sort_by(f64::total_cmp);
+        distances.truncate(k);
+        let nearest = tree.nearest(&point, k);
+        assert_eq!(nearest.len(), distances.len());
+        for (&(rect, _, d), e) in nearest.iter().zip(&distances) {
+            assert!((d - e).abs() < 1e-9, "{} vs {}", d, e);
+            assert_eq!(d, rect.distance_to(&point));
+        }
+    }
+}
+
+fn check_r_tree<const K: usize>(seed: u64) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    for fanout in [4, 5, 9, 16] {
+        let mut tree = RTree::with_fanout(fanout);
+        let mut entries: Vec<(Rect<K>, usize)> = Vec::new();
+        for id in 0..600 {
+            if entries.is_empty() || rng.gen_bool(0.65) {
+                let rect = random_rect::<K>(&mut rng);
+                tree.insert(rect, id);
+                entries.push((rect, id));
+            } else {
+                let (rect, id) = entries.swap_remove(rng.gen_range(0..entries.len()));
+                assert_eq!(tree.remove(&rect, &id), Some(id));
+                assert_eq!(tree.remove(&rect, &id), None);
+            }
+            if id % 50 == 0 {
+                validate(&tree);
+                check_r_tree_queries(&mut rng, &tree, &entries);
+            }
+        }
+        assert_eq!(tree.len(), entries.len());
+        validate(&tree);
+
+        let n = rng.gen_range(0..800);
+        let entries: Vec<(Rect<K>, usize)> = (0..n).map(|id| (random_rect(&mut rng), id)).collect();
+        let mut tree = RTree::bulk_load_with_fanout(fanout, entries.clone());
+        validate(&tree);
+        check_r_tree_queries(&mut rng, &tree, &entries);
+        for (rect, id) in entries.iter().take(n / 2) {
+            assert_eq!(tree.remove(rect, id), Some(*id));
+        }
+        validate(&tree);
+        check_r_tree_queries(&mut rng, &tree, &entries[n / 2..]);
+    }
+}
+
+#[test]
+fn test_oracle_r_tree_against_brute_force() {
+    check_r_tree::<1>(874);
+    check_r_tree::<2>(875);
+    check_r_tree::<3>(876);
+}
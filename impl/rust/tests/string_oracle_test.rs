@@ -0,0 +1,151 @@
+//! Oracle tests comparing the string algorithms against std's str methods
+//! and naive scans
+
+use dsa_lab::fm_index::{bwt, inverse_bwt};
+use dsa_lab::palindrome;
+use dsa_lab::strsearch::{self, Algorithm};
+use dsa_lab::suffix_array::suffix_array;
+use dsa_lab::FmIndex;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+fn random_text(rng: &mut StdRng, alphabet: &[u8], len: usize) -> String {
+    (0..len)
+        .map(|_| alphabet[rng.gen_range(0..alphabet.len())] as char)
+        .collect()
+}
+
+/// Keep matches that start after the previous kept one ends, the way
+/// `str::match_indices` reports them.
+fn non_overlapping(matches: &[usize], pattern_len: usize) -> Vec<usize> {
+    let mut kept: Vec<usize> = Vec::new();
+    for &start in matches {
+        if kept
+            .last()
+            .map_or(true, |&last| start >= last + pattern_len)
+        {
+            kept.push(start);
+        }
+    }
+    kept
+}
+
+#[test]
+fn test_oracle_find_all_against_match_indices() {
+    let mut rng = StdRng::seed_from_u64(63);
+    for alphabet in [&b"ab"[..], b"acgt", b"abcdefghijklmnopqrstuvwxyz"] {
+        for _ in 0..200 {
+            let len = rng.gen_range(0..300);
+            let text = random_text(&mut rng, alphabet, len);
+            // Half the patterns are cut from the text so they occur.
+            let pattern = if rng.gen_bool(0.5) && !text.is_empty() {
+                let start = rng.gen_range(0..text.len());
+                let end = rng.gen_range(start..=text.len().min(start + 12));
+                text[start..end].to_string()
+            } else {
+                let len = rng.gen_range(1..6);
+                random_text(&mut rng, alphabet, len)
+            };
+            if pattern.is_empty() {
+                continue;
+            }
+            let expected: Vec<usize> = text.match_indices(&pattern).map(|(i, _)| i).collect();
+            let overlapping: Vec<usize> = (0..=text.len().saturating_sub(pattern.len()))
+                .filter(|&i| text[i..].starts_with(&pattern))
+                .collect();
+            for algorithm in Algorithm::ALL {
+                let found = algorithm.find_all(pattern.as_bytes(), text.as_bytes());
+                assert_eq!(
+                    found, overlapping,
+                    "{:?} {:?} in {:?}",
+                    algorithm, pattern, text
+                );
+                assert_eq!(non_overlapping(&found, pattern.len()), expected);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_oracle_find_all_multibyte_text() {
+    let text = "naïve café, naïve résumé — naïveté";
+    for pattern in ["naïve", "é", "ï", " — ", "e"] {
+        let expected: Vec<usize> = text.match_indices(pattern).map(|(i, _)| i).collect();
+        assert_eq!(
+            strsearch::find_all(pattern.as_bytes(), text.as_bytes()),
+            expected
+        );
+    }
+}
+
+fn is_palindrome(s: &[u8]) -> bool {
+    s.iter().eq(s.iter().rev())
+}
+
+#[test]
+fn test_oracle_palindromes_against_quadratic_scan() {
+    let mut rng = StdRng::seed_from_u64(65);
+    for alphabet in [&b"a"[..], b"ab", b"abc"] {
+        for _ in 0..200 {
+            let len = rng.gen_range(0..60);
+            let text = random_text(&mut rng, alphabet, len);
+            let s = text.as_bytes();
+            // Expand around every center, O(n^2).
+            let mut lengths = Vec::new();
+            for c in 0..=2 * s.len() {
+                let mut len = c % 2;
+                while len < c && c + len < 2 * s.len() && s[(c - len) / 2 - 1] == s[(c + len) / 2] {
+                    len += 2;
+                }
+                lengths.push(len);
+            }
+            assert_eq!(palindrome::manacher(s), lengths, "{:?}", text);
+
+            let mut count = 0;
+            let mut longest = 0..0;
+            for start in 0..s.len() {
+                for end in start + 1..=s.len() {
+                    if is_palindrome(&s[start..end]) {
+                        count += 1;
+                        if end - start > longest.len() {
+                            longest = start..end;
+                        }
+                    }
+                }
+            }
+            assert_eq!(palindrome::count_palindromes(s), count);
+            assert_eq!(palindrome::longest_palindrome(s), longest, "{:?}", text);
+        }
+    }
+}
+
+#[test]
+fn test_oracle_fm_index_against_kmp() {
+    let mut rng = StdRng::seed_from_u64(66);
+    for alphabet in [&b"ab"[..], b"acgt", b"\0\x01\xff"] {
+        for _ in 0..50 {
+            let len = rng.gen_range(0..700);
+            let text: Vec<u8> = (0..len)
+                .map(|_| alphabet[rng.gen_range(0..alphabet.len())])
+                .collect();
+            let mut sorted: Vec<usize> = (0..text.len()).collect();
+            sorted.sort_by_key(|&i| &text[i..]);
+            assert_eq!(suffix_array(&text), sorted);
+
+            let (last, sentinel) = bwt(&text);
+            assert_eq!(inverse_bwt(&last, sentinel), text);
+
+            let index = FmIndex::new(&text);
+            assert_eq!(index.bwt(), (last, sentinel));
+            for _ in 0..20 {
+                let len = rng.gen_range(0..8);
+                let pattern: Vec<u8> = (0..len)
+                    .map(|_| alphabet[rng.gen_range(0..alphabet.len())])
+                    .collect();
+                let expected = Algorithm::Kmp.find_all(&pattern, &text);
+                assert_eq!(index.count(&pattern), expected.len());
+                assert_eq!(index.locate(&pattern), expected, "{:?}", pattern);
+            }
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Oracle tests comparing the bit-level structures against std collections
+//! and plain bit scans
+
+use dsa_lab::{BitSet, BitVector};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::BTreeSet;
+
+#[test]
+fn test_oracle_bit_vector_rank_select() {
+    let mut rng = StdRng::seed_from_u64(68);
+    // Densities from sparse to full, lengths up to a few superblocks.
+    for density in [0.0, 0.01, 0.5, 0.99, 1.0] {
+        for _ in 0..8 {
+            let len = rng.gen_range(0..200_000);
+            let bits: Vec<bool> = (0..len).map(|_| rng.gen_bool(density)).collect();
+            let vector: BitVector = bits.iter().copied().collect();
+            assert_eq!(vector.len(), len);
+
+            let mut ones = Vec::new();
+            let mut zeros = Vec::new();
+            let mut rank = 0;
+            for (i, &bit) in bits.iter().enumerate() {
+                if i % 97 == 0 || i % 512 == 0 {
+                    assert_eq!(vector.rank1(i), rank, "rank1({}) of {}", i, len);
+                    assert_eq!(vector.rank0(i), i - rank);
+                }
+                if bit {
+                    ones.push(i);
+                    rank += 1;
+                } else {
+                    zeros.push(i);
+                }
+            }
+            assert_eq!(vector.rank1(len), ones.len());
+            assert_eq!(vector.count_ones(), ones.len());
+
+            for _ in 0..500 {
+                let k = rng.gen_range(0..=len);
+                assert_eq!(vector.select1(k), ones.get(k).copied(), "select1({})", k);
+                assert_eq!(vector.select0(k), zeros.get(k).copied(), "select0({})", k);
+            }
+            for (k, &i) in ones.iter().enumerate().step_by(61) {
+                assert_eq!(vector.select1(k), Some(i));
+                assert!(vector.get(i));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_oracle_bit_set_against_btree_set() {
+    let mut rng = StdRng::seed_from_u64(69);
+    for _ in 0..50 {
+        let mut sets = [BitSet::new(), BitSet::new()];
+        let mut oracles = [BTreeSet::new(), BTreeSet::new()];
+        for _ in 0..500 {
+            let which = rng.gen_range(0..2);
+            let value = rng.gen_range(0..300);
+            if rng.gen_bool(0.7) {
+                assert_eq!(sets[which].insert(value), oracles[which].insert(value));
+            } else {
+                assert_eq!(sets[which].remove(value), oracles[which].remove(&value));
+            }
+        }
+        let [a, b] = &sets;
+        let [x, y] = &oracles;
+        let values = |set: &BitSet| set.iter().collect::<Vec<_>>();
+        assert_eq!(values(a), x.iter().copied().collect::<Vec<_>>());
+        assert_eq!(a.count(), x.len());
+        assert_eq!(values(&(a | b)), x.union(y).copied().collect::<Vec<_>>());
+        assert_eq!(
+            values(&(a & b)),
+            x.intersection(y).copied().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            values(&(a ^ b)),
+            x.symmetric_difference(y).copied().collect::<Vec<_>>()
+        );
+        let mut difference = a.clone();
+        difference.difference_with(b);
+        assert_eq!(
+            values(&difference),
+            x.difference(y).copied().collect::<Vec<_>>()
+        );
+        assert_eq!(a.is_subset(b), x.is_subset(y));
+        assert_eq!(a.is_disjoint(b), x.is_disjoint(y));
+        let complement: Vec<usize> = (0..a.capacity()).filter(|v| !x.contains(v)).collect();
+        assert_eq!(values(&!a), complement);
+
+        let vector = BitVector::from(a);
+        assert_eq!(vector.count_ones(), x.len());
+        for (k, &value) in x.iter().enumerate() {
+            assert_eq!(vector.select1(k), Some(value));
+        }
+    }
+}
@@ -2,8 +2,8 @@
 """
 Workload Generator for dsa-lab
 
-Generates deterministic workload files for benchmarking hash map implementations.
-All workloads use fixed seeds for reproducibility.
+Generates deterministic workload files for benchmarking hash map implementations,
+graph algorithms and sorts. All workloads use fixed seeds for reproducibility.
 """
 
 import json
@@ -31,6 +31,60 @@ SIZES = {
     "large": 100_000,
 }
 
+# Graph workload seeds, by generator model
+GRAPH_SEEDS = {
+    "erdos_renyi": 60,
+    "barabasi_albert": 61,
+    "grid": 62,
+    "random_dag": 63,
+    "random_tree": 64,
+}
+
+# Graph workload sizes, in nodes
+GRAPH_SIZES = {
+    "small": 1_000,
+    "medium": 10_000,
+}
+
+# Grid dimensions giving each graph size exactly
+GRID_SHAPES = {
+    1_000: (25, 40),
+    10_000: (100, 100),
+}
+
+# Average degree of the random graph models
+GRAPH_DEGREE = 8
+
+# Edge weights are drawn uniformly from 1..=MAX_WEIGHT
+MAX_WEIGHT = 1000
+
+# Sort workload seeds, by input distribution
+SORT_SEEDS = {
+    "random": 70,
+    "nearly_sorted": 71,
+    "reversed": 72,
+    "few_unique": 73,
+    "sawtooth": 74,
+}
+
+# Sort workload sizes, in keys
+SORT_SIZES = {
+    "small": 1_000,
+    "medium": 100_000,
+}
+
+# Sort keys are u32s
+SORT_KEY_BOUND = 2**32
+
+# Out-of-place pairs in a nearly sorted input, per 100 keys
+NEARLY_SORTED_SWAPS = 1
+
+# Distinct keys in a few-unique input
+FEW_UNIQUE = 16
+
+# Ascending runs in a sawtooth input
+SAWTOOTH_TEETH = 16
+
 # Operation types
 OP_INSERT = "insert"
 OP_GET = "get"
@@ -179,6 +233,267 @@ def generate_workload(
     }
 
 
+def bernoulli_slots(total: int, p: float, rng: random.Random) -> List[int]:
+    """
+    Pick each slot below total independently with probability p.
+
+    Jumps between picked slots with geometric gaps, so the cost is
+    proportional to the number picked rather than to total.
+    """
+    if p <= 0.0:
+        return []
+    if p >= 1.0:
+        return list(range(total))
+    log_miss = math.log1p(-p)
+    slots = []
+    slot = 0
+    while True:
+        slot += int(math.log(1.0 - rng.random()) / log_miss)
+        if slot >= total:
+            return slots
+        slots.append(slot)
+        slot += 1
+
+
+def unordered_pair(k: int) -> Tuple[int, int]:
+    """The pair (v, w) with w < v at position k, listed by v, then w."""
+    v = (1 + math.isqrt(1 + 8 * k)) // 2
+    return v, k - v * (v - 1) // 2
+
+
+def erdos_renyi_edges(n: int, p: float, rng: random.Random) -> List[Tuple[int, int]]:
+    """Directed G(n, p) without self-loops."""
+    edges = []
+    for k in bernoulli_slots(n * (n - 1), p, rng):
+        source, target = divmod(k, n - 1)
+        edges.append((source, target + 1 if target >= source else target))
+    return edges
+
+
+def barabasi_albert_edges(n: int, m: int, rng: random.Random) -> List[Tuple[int, int]]:
+    """Preferential attachment from a clique on m + 1 nodes."""
+    core = min(m + 1, n)
+    edges = [(v, w) for v in range(core) for w in range(v)]
+    # One entry per edge end, so uniform picks are proportional to degree
+    ends = [end for edge in edges for end in edge]
+    for v in range(core, n):
+        targets: List[int] = []
+        while len(targets) < m:
+            target = rng.choice(ends)
+            if target not in targets:
+                targets.append(target)
+        for target in targets:
+            edges.append((v, target))
+            ends.extend((v, target))
+    return edges
+
+
+def grid_edges(rows: int, columns: int) -> List[Tuple[int, int]]:
+    """Four-neighbour lattice; node r * columns + c is at row r, column c."""
+    edges = []
+    for r in range(rows):
+        for c in range(columns):
+            here = r * columns + c
+            if c + 1 < columns:
+                edges.append((here, here + 1))
+            if r + 1 < rows:
+                edges.append((here, here + columns))
+    return edges
+
+
+def random_dag_edges(n: int, p: float, rng: random.Random) -> List[Tuple[int, int]]:
+    """Pairs joined from earlier to later in a shuffled node order."""
+    order = list(range(n))
+    rng.shuffle(order)
+    edges = []
+    for k in bernoulli_slots(n * (n - 1) // 2, p, rng):
+        later, earlier = unordered_pair(k)
+        edges.append((order[earlier], order[later]))
+    return edges
+
+
+def random_tree_edges(n: int, rng: random.Random) -> List[Tuple[int, int]]:
+    """Uniform labelled tree, decoded from a random Prufer sequence."""
+    if n < 2:
+        return []
+    sequence = [rng.randrange(n) for _ in range(n - 2)]
+    degree = [1] * n
+    for v in sequence:
+        degree[v] += 1
+    scan = degree.index(1)
+    leaf = scan
+    edges = []
+    for v in sequence:
+        edges.append((leaf, v))
+        degree[v] -= 1
+        if degree[v] == 1 and v < scan:
+            leaf = v
+        else:
+            scan += 1
+            while degree[scan] != 1:
+                scan += 1
+            leaf = scan
+    edges.append((leaf, n - 1))
+    return edges
+
+
+def generate_graph_workload(model: str, size_name: str, n: int, seed: int) -> Dict[str, Any]:
+    """
+    Generate a graph workload: a weighted edge list from one generator model.
+
+    Args:
+        model: Generator model, a key of GRAPH_SEEDS
+        size_name: Size name, a key of GRAPH_SIZES
+        n: Number of nodes
+        seed: Random seed
+
+    Returns:
+        Graph workload dict
+    """
+    rng = random.Random(seed)
+    p = GRAPH_DEGREE / n
+    if model == "erdos_renyi":
+        directed, parameters = True, {"p": p}
+        edges = erdos_renyi_edges(n, p, rng)
+    elif model == "barabasi_albert":
+        m = GRAPH_DEGREE // 2
+        directed, parameters = False, {"m": m}
+        edges = barabasi_albert_edges(n, m, rng)
+    elif model == "grid":
+        rows, columns = GRID_SHAPES[n]
+        directed, parameters = False, {"rows": rows, "columns": columns}
+        edges = grid_edges(rows, columns)
+    elif model == "random_dag":
+        directed, parameters = True, {"p": p}
+        edges = random_dag_edges(n, p, rng)
+    elif model == "random_tree":
+        directed, parameters = False, {}
+        edges = random_tree_edges(n, rng)
+    else:
+        raise ValueError(f"Unknown graph model: {model}")
+
+    return {
+        "name": f"{model}_{size_name}",
+        "description": f"{model} graph on {n} nodes with weights in 1..={MAX_WEIGHT}",
+        "model": model,
+        "directed": directed,
+        "nodes": n,
+        "parameters": parameters,
+        "seed": seed,
+        "edges": [[s, t, rng.randint(1, MAX_WEIGHT)] for s, t in edges],
+    }
+
+
+def generate_graph_workloads(root: Path) -> None:
+    """Generate the graph workloads and their manifest."""
+    graph_dir = root / "workloads" / "graph"
+    graph_dir.mkdir(parents=True, exist_ok=True)
+
+    generated = []
+    for size_name, n in GRAPH_SIZES.items():
+        for model, seed in GRAPH_SEEDS.items():
+            workload = generate_graph_workload(model, size_name, n, seed + n)
+            print(f"Generating {workload['name']}...")
+            filename = f"{workload['name']}.json"
+            # Edge lists are long; keep them one line rather than four
+            # lines per edge.
+            with open(graph_dir / filename, "w") as f:
+                json.dump(workload, f, separators=(",", ":"))
+            generated.append(filename)
+
+    manifest = {
+        "workloads": generated,
+        "sizes": GRAPH_SIZES,
+        "models": list(GRAPH_SEEDS),
+        "seeds": GRAPH_SEEDS,
+    }
+    with open(graph_dir / "manifest.json", "w") as f:
+        json.dump(manifest, f, indent=2)
+
+    print(f"\nGenerated {len(generated)} graph workloads in {graph_dir}")
+
+
+def generate_sort_workload(distribution: str, size_name: str, n: int, seed: int) -> Dict[str, Any]:
+    """
+    Generate a sort workload: n u32 keys in one input distribution.
+
+    Args:
+        distribution: Input distribution, a key of SORT_SEEDS
+        size_name: Size name, a key of SORT_SIZES
+        n: Number of keys
+        seed: Random seed
+
+    Returns:
+        Sort workload dict
+    """
+    rng = random.Random(seed)
+
+    def uniform(count: int) -> List[int]:
+        return [rng.randrange(SORT_KEY_BOUND) for _ in range(count)]
+
+    if distribution == "random":
+        parameters = {}
+        keys = uniform(n)
+    elif distribution == "nearly_sorted":
+        swaps = n * NEARLY_SORTED_SWAPS // 100
+        parameters = {"swaps": swaps}
+        keys = sorted(uniform(n))
+        for _ in range(swaps):
+            i, j = rng.randrange(n), rng.randrange(n)
+            keys[i], keys[j] = keys[j], keys[i]
+    elif distribution == "reversed":
+        parameters = {}
+        keys = sorted(uniform(n), reverse=True)
+    elif distribution == "few_unique":
+        parameters = {"unique": FEW_UNIQUE}
+        values = uniform(FEW_UNIQUE)
+        keys = [rng.choice(values) for _ in range(n)]
+    elif distribution == "sawtooth":
+        period = -(-n // SAWTOOTH_TEETH)
+        parameters = {"teeth": SAWTOOTH_TEETH, "period": period}
+        tooth = sorted(uniform(period))
+        keys = [tooth[i % period] for i in range(n)]
+    else:
+        raise ValueError(f"Unknown sort distribution: {distribution}")
+
+    return {
+        "name": f"{distribution}_{size_name}",
+        "description": f"{n} u32 keys, {distribution.replace('_', ' ')}",
+        "distribution": distribution,
+        "size": n,
+        "parameters": parameters,
+        "seed": seed,
+        "keys": keys,
+    }
+
+
+def generate_sort_workloads(root: Path) -> None:
+    """Generate the sort workloads and their manifest."""
+    sort_dir = root / "workloads" / "sort"
+    sort_dir.mkdir(parents=True, exist_ok=True)
+
+    generated = []
+    for size_name, n in SORT_SIZES.items():
+        for distribution, seed in SORT_SEEDS.items():
+            workload = generate_sort_workload(distribution, size_name, n, seed + n)
+            print(f"Generating {workload['name']}...")
+            filename = f"{workload['name']}.json"
+            with open(sort_dir / filename, "w") as f:
+                json.dump(workload, f, separators=(",", ":"))
+            generated.append(filename)
+
+    manifest = {
+        "workloads": generated,
+        "sizes": SORT_SIZES,
+        "distributions": list(SORT_SEEDS),
+        "seeds": SORT_SEEDS,
+    }
+    with open(sort_dir / "manifest.json", "w") as f:
+        json.dump(manifest, f, indent=2)
+
+    print(f"\nGenerated {len(generated)} sort workloads in {sort_dir}")
+
+
 def main():
     """Generate all workloads."""
     root = Path(__file__).parent.parent
@@ -240,6 +555,9 @@ def main():
     print(f"\nGenerated {len(generated)} workloads in {workloads_dir}")
     print("Manifest written to manifest.json")
 
+    generate_graph_workloads(root)
+    generate_sort_workloads(root)
+
 
 if __name__ == "__main__":
     main()